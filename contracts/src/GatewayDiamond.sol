@@ -33,8 +33,8 @@ contract GatewayDiamond {
     }
 
     constructor(IDiamond.FacetCut[] memory _diamondCut, ConstructorParams memory params) {
-        // The bottomUpCheckPeriod should be non-zero for now.
-        if (params.bottomUpCheckPeriod == 0) {
+        // The bottomUpCheckPeriod should be non-zero, except on the rootnet where 0 disables checkpointing.
+        if (params.bottomUpCheckPeriod == 0 && params.networkName.route.length != 0) {
             revert InvalidSubmissionPeriod();
         }
 
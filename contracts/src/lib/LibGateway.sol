@@ -8,7 +8,7 @@ import {SubnetID, Subnet, SupplyKind, SupplySource} from "../structs/Subnet.sol"
 import {SubnetActorGetterFacet} from "../subnet/SubnetActorGetterFacet.sol";
 import {CallMsg, IpcMsgKind, IpcEnvelope, OutcomeType, BottomUpMsgBatch, BottomUpMsgBatch, BottomUpCheckpoint, ParentFinality} from "../structs/CrossNet.sol";
 import {Membership} from "../structs/Subnet.sol";
-import {CannotSendCrossMsgToItself, MethodNotAllowed, MaxMsgsPerBatchExceeded, InvalidXnetMessage ,OldConfigurationNumber, NotRegisteredSubnet, InvalidActorAddress, ParentFinalityAlreadyCommitted, InvalidXnetMessageReason} from "../errors/IPCErrors.sol";
+import {CannotSendCrossMsgToItself, CheckpointingDisabled, MethodNotAllowed, MaxMsgsPerBatchExceeded, InvalidXnetMessage ,OldConfigurationNumber, NotRegisteredSubnet, InvalidActorAddress, ParentFinalityAlreadyCommitted, InvalidXnetMessageReason} from "../errors/IPCErrors.sol";
 import {CrossMsgHelper} from "../lib/CrossMsgHelper.sol";
 import {FilAddress} from "fevmate/utils/FilAddress.sol";
 import {SubnetIDHelper} from "../lib/SubnetIDHelper.sol";
@@ -38,6 +38,9 @@ library LibGateway {
         returns (bool exists, uint256 epoch, BottomUpCheckpoint memory checkpoint)
     {
         GatewayActorStorage storage s = LibGatewayActorStorage.appStorage();
+        if (s.bottomUpCheckPeriod == 0) {
+            revert CheckpointingDisabled();
+        }
         epoch = LibGateway.getNextEpoch(block.number, s.bottomUpCheckPeriod);
         checkpoint = s.bottomUpCheckpoints[epoch];
         exists = !checkpoint.subnetID.isEmpty();
@@ -251,6 +254,10 @@ library LibGateway {
     /// @param crossMessage - the cross message to be committed
     function commitBottomUpMsg(IpcEnvelope memory crossMessage) internal {
         GatewayActorStorage storage s = LibGatewayActorStorage.appStorage();
+        // A rootnet without bottom-up checkpoints has no parent to send the message to.
+        if (s.bottomUpCheckPeriod == 0) {
+            revert CheckpointingDisabled();
+        }
         uint256 epoch = getNextEpoch(block.number, s.bottomUpCheckPeriod);
 
         // assign nonce to the message.
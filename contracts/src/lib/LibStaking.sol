@@ -148,6 +148,18 @@ library LibValidatorSet {
         return addresses;
     }
 
+    function listWaitingValidators(ValidatorSet storage validators) internal view returns (address[] memory addresses) {
+        uint16 size = validators.waitingValidators.getSize();
+        addresses = new address[](size);
+        for (uint16 i = 1; i <= size; ) {
+            addresses[i - 1] = validators.waitingValidators.getAddress(i);
+            unchecked {
+                ++i;
+            }
+        }
+        return addresses;
+    }
+
     /// @notice Get the total collateral of *active* validators.
     function getTotalActivePower(ValidatorSet storage validators) internal view returns (uint256 collateral) {
         uint16 size = validators.activeValidators.getSize();
@@ -425,6 +437,18 @@ library LibStaking {
         return s.validatorSet.totalActiveValidators();
     }
 
+    /// @notice Lists the addresses of the active validators
+    function listActiveValidators() internal view returns (address[] memory) {
+        SubnetActorStorage storage s = LibSubnetActorStorage.appStorage();
+        return s.validatorSet.listActiveValidators();
+    }
+
+    /// @notice Lists the addresses of the waiting validators
+    function listWaitingValidators() internal view returns (address[] memory) {
+        SubnetActorStorage storage s = LibSubnetActorStorage.appStorage();
+        return s.validatorSet.listWaitingValidators();
+    }
+
     /// @notice Gets the total number of validators, including active and waiting
     function totalValidators() internal view returns (uint16) {
         SubnetActorStorage storage s = LibSubnetActorStorage.appStorage();
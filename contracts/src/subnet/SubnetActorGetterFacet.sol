@@ -128,6 +128,16 @@ contract SubnetActorGetterFacet {
         return LibStaking.totalActiveValidators();
     }
 
+    /// @notice Returns the addresses of the active validators.
+    function getActiveValidators() external view returns (address[] memory) {
+        return LibStaking.listActiveValidators();
+    }
+
+    /// @notice Returns the addresses of the waiting validators.
+    function getWaitingValidators() external view returns (address[] memory) {
+        return LibStaking.listWaitingValidators();
+    }
+
     /// @notice Returns the total amount of confirmed collateral across all validators.
     function getTotalConfirmedCollateral() external view returns (uint256) {
         return LibStaking.getTotalConfirmedCollateral();
@@ -3,7 +3,7 @@ pragma solidity ^0.8.23;
 
 import {VALIDATOR_SECP256K1_PUBLIC_KEY_LENGTH} from "../constants/Constants.sol";
 import {ERR_VALIDATOR_JOINED, ERR_VALIDATOR_NOT_JOINED} from "../errors/IPCErrors.sol";
-import {InvalidFederationPayload, SubnetAlreadyBootstrapped, NotEnoughFunds, CollateralIsZero, CannotReleaseZero, NotOwnerOfPublicKey, EmptyAddress, NotEnoughBalance, NotEnoughCollateral, NotValidator, NotAllValidatorsHaveLeft, InvalidPublicKeyLength, MethodNotAllowed, SubnetNotBootstrapped} from "../errors/IPCErrors.sol";
+import {InvalidFederationPayload, SubnetAlreadyBootstrapped, NotEnoughFunds, CollateralIsZero, CannotReleaseZero, NotOwnerOfPublicKey, EmptyAddress, NotEnoughBalance, NotEnoughCollateral, NotValidator, NotAllValidatorsHaveLeft, InvalidPublicKeyLength, MethodNotAllowed, SubnetNotBootstrapped, BootstrapNodeNotFound} from "../errors/IPCErrors.sol";
 import {IGateway} from "../interfaces/IGateway.sol";
 import {Validator, ValidatorSet} from "../structs/Subnet.sol";
 import {LibDiamond} from "../lib/LibDiamond.sol";
@@ -268,4 +268,18 @@ contract SubnetActorManagerFacet is SubnetActorModifiers, ReentrancyGuard, Pausa
         // slither-disable-next-line unused-return
         s.bootstrapOwners.add(msg.sender);
     }
+
+    /// @notice Remove the bootstrap node advertised by the caller.
+    /// @param netAddress The network address of the bootstrap node to remove.
+    function removeBootstrapNode(string memory netAddress) external whenNotPaused {
+        if (
+            !s.bootstrapOwners.contains(msg.sender) ||
+            keccak256(bytes(s.bootstrapNodes[msg.sender])) != keccak256(bytes(netAddress))
+        ) {
+            revert BootstrapNodeNotFound();
+        }
+        // slither-disable-next-line unused-return
+        s.bootstrapOwners.remove(msg.sender);
+        delete s.bootstrapNodes[msg.sender];
+    }
 }
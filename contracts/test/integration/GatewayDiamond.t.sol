@@ -260,6 +260,61 @@ contract GatewayActorDiamondTest is Test, IntegrationTestBase {
         require(depGetter.majorityPercentage() == 100, "unexpected majorityPercentage");
     }
 
+    function testGatewayDiamond_Deployment_Works_RootWithoutCheckpointing() public {
+        GatewayDiamond.ConstructorParams memory constructorParams = GatewayDiamond.ConstructorParams({
+            networkName: SubnetID({root: ROOTNET_CHAINID, route: new address[](0)}),
+            bottomUpCheckPeriod: 0,
+            majorityPercentage: DEFAULT_MAJORITY_PERCENTAGE,
+            genesisValidators: new Validator[](0),
+            activeValidatorsLimit: 100,
+            commitSha: DEFAULT_COMMIT_SHA
+        });
+
+        GatewayDiamond dep = createGatewayDiamond(constructorParams);
+
+        require(dep.getter().bottomUpCheckPeriod() == 0, "unexpected bottomUpCheckPeriod");
+
+        vm.expectRevert(CheckpointingDisabled.selector);
+        dep.getter().getCurrentBottomUpCheckpoint();
+    }
+
+    function testGatewayDiamond_Release_Fails_RootWithoutCheckpointing() public {
+        GatewayDiamond.ConstructorParams memory constructorParams = GatewayDiamond.ConstructorParams({
+            networkName: SubnetID({root: ROOTNET_CHAINID, route: new address[](0)}),
+            bottomUpCheckPeriod: 0,
+            majorityPercentage: DEFAULT_MAJORITY_PERCENTAGE,
+            genesisValidators: new Validator[](0),
+            activeValidatorsLimit: 100,
+            commitSha: DEFAULT_COMMIT_SHA
+        });
+        gatewayDiamond = createGatewayDiamond(constructorParams);
+
+        address callerAddress = address(100);
+
+        vm.startPrank(callerAddress);
+        vm.deal(callerAddress, 1 ether);
+        vm.expectRevert(CheckpointingDisabled.selector);
+
+        gatewayDiamond.manager().release{value: 1 ether}(FvmAddressHelper.from(callerAddress));
+    }
+
+    function testGatewayDiamond_Deployment_Fails_NotRootWithoutCheckpointing() public {
+        address[] memory path = new address[](1);
+        path[0] = address(1);
+
+        GatewayDiamond.ConstructorParams memory constructorParams = GatewayDiamond.ConstructorParams({
+            networkName: SubnetID({root: ROOTNET_CHAINID, route: path}),
+            bottomUpCheckPeriod: 0,
+            majorityPercentage: DEFAULT_MAJORITY_PERCENTAGE,
+            genesisValidators: new Validator[](0),
+            activeValidatorsLimit: 100,
+            commitSha: DEFAULT_COMMIT_SHA
+        });
+
+        vm.expectRevert(InvalidSubmissionPeriod.selector);
+        new GatewayDiamond(new IDiamond.FacetCut[](0), constructorParams);
+    }
+
     function testGatewayDiamond_Register_Works_SingleSubnet(uint256 subnetCollateral) public {
         vm.assume(subnetCollateral < type(uint64).max);
         address subnetAddress = vm.addr(100);
@@ -413,6 +413,40 @@ contract SubnetActorDiamondTest is Test, IntegrationTestBase {
         require(nodes.length == 0, "no nodes");
     }
 
+    function testSubnetActorDiamond_Remove_Bootstrap_Node() public {
+        (address validator, , bytes memory publicKey) = TestUtils.newValidator(100);
+
+        vm.deal(validator, DEFAULT_MIN_VALIDATOR_STAKE);
+        vm.prank(validator);
+        saDiamond.manager().join{value: DEFAULT_MIN_VALIDATOR_STAKE}(publicKey);
+
+        vm.prank(validator);
+        saDiamond.manager().addBootstrapNode("1.2.3.4:26656");
+
+        // the node of another validator can't be removed
+        vm.prank(vm.addr(200));
+        vm.expectRevert(BootstrapNodeNotFound.selector);
+        saDiamond.manager().removeBootstrapNode("1.2.3.4:26656");
+
+        // a stale endpoint can't be removed
+        vm.prank(validator);
+        vm.expectRevert(BootstrapNodeNotFound.selector);
+        saDiamond.manager().removeBootstrapNode("5.6.7.8:26656");
+
+        vm.prank(validator);
+        saDiamond.manager().removeBootstrapNode("1.2.3.4:26656");
+
+        string[] memory nodes = saDiamond.getter().getBootstrapNodes();
+        require(nodes.length == 0, "no nodes");
+
+        // the node can be advertised again
+        vm.prank(validator);
+        saDiamond.manager().addBootstrapNode("5.6.7.8:26656");
+
+        nodes = saDiamond.getter().getBootstrapNodes();
+        require(nodes.length == 1, "it returns one node");
+    }
+
     function testSubnetActorDiamond_Leave_NotValidator() public {
         (address validator, , ) = TestUtils.newValidator(100);
 
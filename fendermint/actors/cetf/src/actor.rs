@@ -2,32 +2,47 @@
 // Copyright 2021-2023 BadBoi Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use core::hash;
-
 use crate::state::State;
-use crate::AddSignedBlockHeightTagParams;
 use crate::AddSignedTagParams;
+use crate::AddSignedTagsParams;
 use crate::AddValidatorParams;
-use crate::{EnqueueTagParams, GetTagParams};
+use crate::BlsPublicKey;
+use crate::ConstructorParams;
+use crate::RemoveValidatorParams;
+use crate::UpdateValidatorKeyParams;
+use crate::{BlockHeight, Tag};
+use crate::{EnqueueTagAtHeightParams, EnqueueTagParams, GetTagParams};
 use crate::{Method, CETF_ACTOR_NAME};
 use fil_actors_runtime::actor_dispatch;
 use fil_actors_runtime::actor_error;
 use fil_actors_runtime::builtin::singletons::SYSTEM_ACTOR_ADDR;
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 use fil_actors_runtime::ActorError;
+use fvm_ipld_encoding::IPLD_RAW;
+use fvm_shared::address::Address;
+use fvm_shared::event::{ActorEvent, Entry, Flags};
 use sha3::{Digest, Keccak256};
 
 // Note for myself: trampoline initializes a logger if debug mode is enabled.
+#[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
 
+pub const EVENT_VALIDATOR_KEY_UPDATED: &str = "validator-key-updated";
+pub const EVENT_VALIDATOR_REMOVED: &str = "validator-removed";
+
 pub struct Actor;
 impl Actor {
     /// Initialize the HAMT store for tags in the actor state
     /// Callable only by the system actor at genesis
-    pub fn constructor(rt: &impl Runtime) -> Result<(), ActorError> {
+    pub fn constructor(rt: &impl Runtime, params: ConstructorParams) -> Result<(), ActorError> {
         rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
         log::info!("cetf actor constructor called");
-        let st = State::new(rt.store())?;
+        let st = State::new(
+            rt.store(),
+            params.max_pending_tags,
+            params.max_tags_per_height,
+            params.min_tag_distance,
+        )?;
         rt.create(&st)?;
         Ok(())
     }
@@ -42,11 +57,50 @@ impl Actor {
         Ok(())
     }
 
-    /// Add a new tag to the state to be signed by the validators
+    /// Add a new tag to the state to be signed by the validators as soon as possible,
+    /// that is `min_tag_distance` epochs after the current one, or the first height
+    /// after that with fewer than `max_tags_per_height` tags.
     /// Callable by anyone and designed to be called from Solidity contracts
-    pub fn enqueue_tag(rt: &impl Runtime, tag: EnqueueTagParams) -> Result<u64, ActorError> {
+    /// Enqueuing a tag that was already enqueued returns the epoch it was first scheduled for
+    pub fn enqueue_tag(rt: &impl Runtime, params: EnqueueTagParams) -> Result<u64, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let st: State = rt.state()?;
+        let height = rt.curr_epoch() as u64 + st.min_tag_distance;
+
+        Self::enqueue(rt, &params.tag, height, false)
+    }
+
+    /// Add a new tag to the state to be signed by the validators at the given height,
+    /// which has to be at least `min_tag_distance` epochs after the current one.
+    /// Enqueuing a tag again for the same height is a no-op, but it cannot be moved to another height.
+    /// Fails if there are already `max_tags_per_height` tags scheduled at the height.
+    pub fn enqueue_tag_at_height(
+        rt: &impl Runtime,
+        params: EnqueueTagAtHeightParams,
+    ) -> Result<u64, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
 
+        let height = Self::enqueue(rt, &params.tag, params.height, true)?;
+
+        if height != params.height {
+            return Err(actor_error!(
+                illegal_argument,
+                "tag is already scheduled for height {}",
+                height
+            ));
+        }
+        Ok(height)
+    }
+
+    /// Schedule the tag of the calling contract to be signed at the given height,
+    /// or a later one if that is full and `exact_height` is not set.
+    fn enqueue(
+        rt: &impl Runtime,
+        tag: &Tag,
+        height: BlockHeight,
+        exact_height: bool,
+    ) -> Result<u64, ActorError> {
         let calling_contract = rt
             .lookup_delegated_address(rt.message().caller().id().unwrap())
             .ok_or(ActorError::assertion_failed(
@@ -58,38 +112,50 @@ impl Actor {
         // hash together the calling address and the tag to create a unique identifier for the tag
         let mut hashdata = Vec::new();
         hashdata.extend_from_slice(&calling_eth_address);
-        hashdata.extend_from_slice(&tag.tag.0);
+        hashdata.extend_from_slice(&tag.0);
         let mut signing_tag = [0x0_u8; 32];
         signing_tag.copy_from_slice(&Keccak256::digest(hashdata));
 
         log::info!(
-            "cetf actor enqueue_tag called by {} with tag {:?}. Resulting signing tag is {:?}",
+            "cetf actor enqueue_tag called by {} with tag {:?} for height {}. Resulting signing tag is {:?}",
             hex::encode(calling_eth_address),
             tag,
+            height,
             &signing_tag,
         );
 
-        let scheduled_epoch = rt.transaction(|st: &mut State, rt| {
-            // +2 because the Validators sign the tag in the next epoch
-            // then it gets included into the block one more epoch after that
-            // Then in 1 MORE epoch, it should be available to be queried in VM
-            let scheduled_epoch = rt.curr_epoch() + 2;
+        rt.transaction(|st: &mut State, rt| {
+            // The validators sign the tag in the vote extensions of the previous block,
+            // against the state committed before that, so the tags to sign at a height
+            // must not change in the last `min_tag_distance` epochs before it.
+            // NOTE: use of epoch is intentional here. In fendermint the epoch is the block height
+            let min_height = rt.curr_epoch() as u64 + st.min_tag_distance;
+            if height < min_height {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "cannot schedule tag for height {}; the earliest height is {}",
+                    height,
+                    min_height
+                ));
+            }
             if st.enabled {
-                // NOTE: use of epoch is intentional here. In fendermint the epoch is the block height
+                let height = if exact_height {
+                    st.enqueue_tag_at_height(rt.store(), height, &signing_tag.into())?
+                } else {
+                    st.enqueue_tag(rt.store(), height, &signing_tag.into())?
+                };
                 log::info!(
-                    "Cetf actor enqueue_tag called by {} with tag {:?} for height: {}",
-                    rt.message().caller(),
+                    "Scheduled Cetf Tag for height {}. Current FVM epoch: {}. Tag: {:?}",
+                    height,
+                    rt.curr_epoch(),
                     &signing_tag,
-                    scheduled_epoch
                 );
-                st.add_tag_at_height(rt, &(scheduled_epoch as u64), &signing_tag.into())?;
+                Ok(height)
             } else {
                 log::info!("CETF actor is disabled. Not all validators have added their keys. No tag was enqueued.");
+                Ok(height)
             }
-            Ok(scheduled_epoch)
-        })?;
-
-        Ok(scheduled_epoch as u64)
+        })
     }
 
     pub fn get_tag(rt: &impl Runtime, params: GetTagParams) -> Result<(), ActorError> {
@@ -97,7 +163,7 @@ impl Actor {
         rt.validate_immediate_caller_accept_any()?;
 
         let state: State = rt.state()?;
-        state.get_tag_at_height(rt.store(), &params.height)?;
+        state.get_tags_at_height(rt.store(), params.height)?;
         Ok(())
     }
 
@@ -138,10 +204,75 @@ impl Actor {
         Ok(())
     }
 
+    /// Replace the BLS key of a registered validator.
+    /// Callable only by the validator itself. Vote extensions are verified against
+    /// the committed state, so the new key is used from the next height onwards.
+    pub fn update_validator_key(
+        rt: &impl Runtime,
+        params: UpdateValidatorKeyParams,
+    ) -> Result<(), ActorError> {
+        log::info!(
+            "update_validator_key called with caller: {}",
+            rt.message().caller()
+        );
+        rt.validate_immediate_caller_accept_any()?;
+        validate_caller_is_validator(rt, &params.address, false)?;
+
+        rt.transaction(|st: &mut State, rt| {
+            st.update_validator_key(rt.store(), &params.address, &params.public_key)?;
+            Ok(())
+        })?;
+
+        rt.emit_event(&validator_event(
+            EVENT_VALIDATOR_KEY_UPDATED,
+            &params.address,
+            Some(&params.public_key),
+        ))
+    }
+
+    /// Remove a validator and its BLS key from the aggregate.
+    /// Callable by the validator itself, or by the system actor when the validator leaves the power table.
+    pub fn remove_validator(
+        rt: &impl Runtime,
+        params: RemoveValidatorParams,
+    ) -> Result<(), ActorError> {
+        log::info!(
+            "remove_validator called with caller: {}",
+            rt.message().caller()
+        );
+        rt.validate_immediate_caller_accept_any()?;
+        validate_caller_is_validator(rt, &params.address, true)?;
+
+        rt.transaction(|st: &mut State, rt| {
+            st.remove_validator(rt.store(), &params.address)?;
+            Ok(())
+        })?;
+
+        rt.emit_event(&validator_event(
+            EVENT_VALIDATOR_REMOVED,
+            &params.address,
+            None,
+        ))
+    }
+
+    /// Add the signature of the only tag scheduled at a height, as sent for the first version of the actor.
     pub fn add_signed_tag(rt: &impl Runtime, params: AddSignedTagParams) -> Result<(), ActorError> {
         rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
         rt.transaction(|st: &mut State, rt| {
-            st.add_signed_tag_at_height(rt, &params.height, &params.signature)?;
+            st.add_signed_tags_at_height(rt, params.height, &[params.signature])?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// Add the signatures of all the tags scheduled at a height.
+    pub fn add_signed_tags(
+        rt: &impl Runtime,
+        params: AddSignedTagsParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+        rt.transaction(|st: &mut State, rt| {
+            st.add_signed_tags_at_height(rt, params.height, &params.signatures)?;
             Ok(())
         })?;
         Ok(())
@@ -160,6 +291,46 @@ impl Actor {
     // }
 }
 
+/// Check that the caller is the account the validator registered with,
+/// or optionally the system actor.
+fn validate_caller_is_validator(
+    rt: &impl Runtime,
+    address: &Address,
+    allow_system: bool,
+) -> Result<(), ActorError> {
+    let caller = rt.message().caller();
+    if allow_system && caller == SYSTEM_ACTOR_ADDR {
+        return Ok(());
+    }
+    match rt.resolve_address(address) {
+        Some(id) if Address::new_id(id) == caller => Ok(()),
+        _ => Err(actor_error!(
+            forbidden,
+            "caller {} is not validator {}",
+            caller,
+            address
+        )),
+    }
+}
+
+/// Event announcing a change to the registered key of a validator.
+fn validator_event(kind: &str, address: &Address, public_key: Option<&BlsPublicKey>) -> ActorEvent {
+    let entry = |key: &str, value: Vec<u8>| Entry {
+        flags: Flags::FLAG_INDEXED_ALL,
+        key: key.to_owned(),
+        codec: IPLD_RAW,
+        value,
+    };
+    let mut entries = vec![
+        entry("type", kind.as_bytes().to_vec()),
+        entry("validator", address.to_bytes()),
+    ];
+    if let Some(public_key) = public_key {
+        entries.push(entry("public_key", public_key.0.to_vec()));
+    }
+    ActorEvent::from(entries)
+}
+
 impl ActorCode for Actor {
     type Methods = Method;
 
@@ -171,11 +342,346 @@ impl ActorCode for Actor {
         Constructor => constructor,
         Echo => echo,
         EnqueueTag => enqueue_tag,
+        EnqueueTagAtHeight => enqueue_tag_at_height,
         GetTag => get_tag,
         Enable => enable,
         AddValidator => add_validator,
         Disable => disable,
         AddSignedTag => add_signed_tag,
+        AddSignedTags => add_signed_tags,
+        RemoveValidator => remove_validator,
+        UpdateValidatorKey => update_validator_key,
         // AddSignedBlockHeightTag => add_signed_blockheight_tag,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use fil_actors_runtime::test_utils::{
+        MockRuntime, ACCOUNT_ACTOR_CODE_ID, EVM_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID,
+    };
+    use fil_actors_runtime::SYSTEM_ACTOR_ADDR;
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_shared::address::Address;
+    use fvm_shared::error::ExitCode;
+    use sha3::{Digest, Keccak256};
+
+    use super::{validator_event, Actor, EVENT_VALIDATOR_KEY_UPDATED, EVENT_VALIDATOR_REMOVED};
+    use crate::state::State;
+    use crate::{
+        AddSignedTagParams, AddSignedTagsParams, AddValidatorParams, BlockHeight, BlsPublicKey,
+        BlsSignature, ConstructorParams, EnqueueTagAtHeightParams, EnqueueTagParams, Method,
+        RemoveValidatorParams, Tag, UpdateValidatorKeyParams, DEFAULT_MAX_PENDING_TAGS,
+        DEFAULT_MAX_TAGS_PER_HEIGHT, DEFAULT_MIN_TAG_DISTANCE,
+    };
+
+    const VALIDATOR: Address = Address::new_id(100);
+    const OTHER: Address = Address::new_id(101);
+    const CONTRACT: Address = Address::new_id(200);
+    const CONTRACT_ETH_ADDR: [u8; 20] = [0xab; 20];
+
+    fn construct_and_verify() -> MockRuntime {
+        let rt = MockRuntime {
+            receiver: Address::new_id(10),
+            ..Default::default()
+        };
+
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        rt.call::<Actor>(
+            Method::Constructor as u64,
+            IpldBlock::serialize_cbor(&ConstructorParams {
+                max_pending_tags: DEFAULT_MAX_PENDING_TAGS,
+                max_tags_per_height: DEFAULT_MAX_TAGS_PER_HEIGHT,
+                min_tag_distance: DEFAULT_MIN_TAG_DISTANCE,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, VALIDATOR);
+        rt.expect_validate_caller_any();
+        rt.call::<Actor>(
+            Method::AddValidator as u64,
+            IpldBlock::serialize_cbor(&AddValidatorParams {
+                address: VALIDATOR,
+                public_key: BlsPublicKey([1; 48]),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        rt.reset();
+
+        rt
+    }
+
+    fn validator_key(rt: &MockRuntime) -> Option<BlsPublicKey> {
+        let state: State = rt.get_state();
+        state
+            .get_validators_keymap(rt.store())
+            .unwrap()
+            .get(&VALIDATOR)
+            .unwrap()
+            .copied()
+    }
+
+    fn call(rt: &MockRuntime, caller: Address, method: Method, params: IpldBlock) -> ExitCode {
+        let code = if caller == SYSTEM_ACTOR_ADDR {
+            *SYSTEM_ACTOR_CODE_ID
+        } else {
+            *ACCOUNT_ACTOR_CODE_ID
+        };
+        rt.set_caller(code, caller);
+        rt.expect_validate_caller_any();
+        let res = rt.call::<Actor>(method as u64, Some(params));
+        rt.verify();
+        rt.reset();
+        res.map(|_| ExitCode::OK).unwrap_or_else(|e| e.exit_code())
+    }
+
+    fn update_params(public_key: BlsPublicKey) -> IpldBlock {
+        IpldBlock::serialize_cbor(&UpdateValidatorKeyParams {
+            address: VALIDATOR,
+            public_key,
+        })
+        .unwrap()
+        .unwrap()
+    }
+
+    fn remove_params() -> IpldBlock {
+        IpldBlock::serialize_cbor(&RemoveValidatorParams { address: VALIDATOR })
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_update_validator_key() {
+        let rt = construct_and_verify();
+        let new_key = BlsPublicKey([2; 48]);
+
+        rt.expect_emitted_event(validator_event(
+            EVENT_VALIDATOR_KEY_UPDATED,
+            &VALIDATOR,
+            Some(&new_key),
+        ));
+        let code = call(
+            &rt,
+            VALIDATOR,
+            Method::UpdateValidatorKey,
+            update_params(new_key),
+        );
+        assert_eq!(code, ExitCode::OK);
+        assert_eq!(validator_key(&rt), Some(new_key));
+    }
+
+    #[test]
+    fn test_update_validator_key_unauthorized() {
+        let rt = construct_and_verify();
+
+        for caller in [OTHER, SYSTEM_ACTOR_ADDR] {
+            let code = call(
+                &rt,
+                caller,
+                Method::UpdateValidatorKey,
+                update_params(BlsPublicKey([2; 48])),
+            );
+            assert_eq!(code, ExitCode::USR_FORBIDDEN);
+        }
+        assert_eq!(validator_key(&rt), Some(BlsPublicKey([1; 48])));
+    }
+
+    #[test]
+    fn test_remove_validator() {
+        for caller in [VALIDATOR, SYSTEM_ACTOR_ADDR] {
+            let rt = construct_and_verify();
+
+            rt.expect_emitted_event(validator_event(EVENT_VALIDATOR_REMOVED, &VALIDATOR, None));
+            let code = call(&rt, caller, Method::RemoveValidator, remove_params());
+            assert_eq!(code, ExitCode::OK);
+            assert_eq!(validator_key(&rt), None);
+        }
+    }
+
+    #[test]
+    fn test_remove_validator_unauthorized() {
+        let rt = construct_and_verify();
+
+        let code = call(&rt, OTHER, Method::RemoveValidator, remove_params());
+        assert_eq!(code, ExitCode::USR_FORBIDDEN);
+        assert_eq!(validator_key(&rt), Some(BlsPublicKey([1; 48])));
+    }
+
+    /// Enable the actor and set up a contract which can enqueue tags at epoch 10.
+    fn enable_for_contract(rt: &MockRuntime) {
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        rt.call::<Actor>(Method::Enable as u64, None).unwrap();
+        rt.verify();
+        rt.reset();
+
+        rt.set_delegated_address(
+            CONTRACT.id().unwrap(),
+            Address::new_delegated(10, &CONTRACT_ETH_ADDR).unwrap(),
+        );
+        rt.set_epoch(10);
+    }
+
+    /// The tag the actor schedules for signing when the contract enqueues `tag`.
+    fn signing_tag(tag: &Tag) -> Tag {
+        let mut hashdata = CONTRACT_ETH_ADDR.to_vec();
+        hashdata.extend_from_slice(&tag.0);
+        let mut signing_tag = [0u8; 32];
+        signing_tag.copy_from_slice(&Keccak256::digest(hashdata));
+        Tag(signing_tag)
+    }
+
+    fn enqueue_at(rt: &MockRuntime, tag: Tag, height: BlockHeight) -> Result<u64, ExitCode> {
+        rt.set_caller(*EVM_ACTOR_CODE_ID, CONTRACT);
+        rt.expect_validate_caller_any();
+        let res = rt.call::<Actor>(
+            Method::EnqueueTagAtHeight as u64,
+            IpldBlock::serialize_cbor(&EnqueueTagAtHeightParams { tag, height }).unwrap(),
+        );
+        rt.verify();
+        rt.reset();
+        res.map(|ret| ret.unwrap().deserialize().unwrap())
+            .map_err(|e| e.exit_code())
+    }
+
+    fn tags_at(rt: &MockRuntime, height: BlockHeight) -> Vec<Tag> {
+        let state: State = rt.get_state();
+        state.get_tags_at_height(rt.store(), height).unwrap()
+    }
+
+    #[test]
+    fn test_enqueue_tag_at_past_height() {
+        let rt = construct_and_verify();
+        enable_for_contract(&rt);
+
+        for height in [5, 10, 11] {
+            assert_eq!(
+                enqueue_at(&rt, Tag([1; 32]), height),
+                Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+            );
+            assert!(tags_at(&rt, height).is_empty());
+        }
+        assert_eq!(enqueue_at(&rt, Tag([1; 32]), 12), Ok(12));
+        assert_eq!(tags_at(&rt, 12), vec![signing_tag(&Tag([1; 32]))]);
+    }
+
+    #[test]
+    fn test_enqueue_duplicate_tag_at_height() {
+        let rt = construct_and_verify();
+        enable_for_contract(&rt);
+
+        let tag1 = Tag([1; 32]);
+        let tag2 = Tag([2; 32]);
+
+        assert_eq!(enqueue_at(&rt, tag1, 20), Ok(20));
+        // Enqueuing again for the same height doesn't sign it twice.
+        assert_eq!(enqueue_at(&rt, tag1, 20), Ok(20));
+        // It cannot be moved to another height.
+        assert_eq!(
+            enqueue_at(&rt, tag1, 21),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        // Other tags can be signed at the same height.
+        assert_eq!(enqueue_at(&rt, tag2, 20), Ok(20));
+
+        assert_eq!(
+            tags_at(&rt, 20),
+            vec![signing_tag(&tag1), signing_tag(&tag2)]
+        );
+        assert!(tags_at(&rt, 21).is_empty());
+
+        let state: State = rt.get_state();
+        assert_eq!(state.pending_tags, 2);
+    }
+
+    #[test]
+    fn test_sign_tags_at_boundary() {
+        let rt = construct_and_verify();
+        enable_for_contract(&rt);
+
+        let tag1 = Tag([1; 32]);
+        let tag2 = Tag([2; 32]);
+        let tag3 = Tag([3; 32]);
+
+        // The tag enqueued for the earliest possible height ends up with the explicitly scheduled ones.
+        assert_eq!(enqueue_at(&rt, tag1, 12), Ok(12));
+        rt.set_caller(*EVM_ACTOR_CODE_ID, CONTRACT);
+        rt.expect_validate_caller_any();
+        let ret = rt
+            .call::<Actor>(
+                Method::EnqueueTag as u64,
+                IpldBlock::serialize_cbor(&EnqueueTagParams { tag: tag2 }).unwrap(),
+            )
+            .unwrap();
+        rt.verify();
+        rt.reset();
+        assert_eq!(ret.unwrap().deserialize::<u64>().unwrap(), 12);
+        assert_eq!(enqueue_at(&rt, tag3, 13), Ok(13));
+
+        let add_signed_tags = |height: BlockHeight, count: usize| {
+            rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+            rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+            let res = rt.call::<Actor>(
+                Method::AddSignedTags as u64,
+                IpldBlock::serialize_cbor(&AddSignedTagsParams {
+                    height,
+                    signatures: vec![BlsSignature([height as u8; 96]); count],
+                })
+                .unwrap(),
+            );
+            rt.verify();
+            rt.reset();
+            res.map(|_| ()).map_err(|e| e.exit_code())
+        };
+
+        // All the tags at the height have to be signed together.
+        assert_eq!(add_signed_tags(12, 1), Err(ExitCode::USR_ILLEGAL_ARGUMENT));
+        assert_eq!(add_signed_tags(12, 2), Ok(()));
+
+        let state: State = rt.get_state();
+        assert!(state
+            .is_tag_signed(rt.store(), &signing_tag(&tag1))
+            .unwrap());
+        assert!(state
+            .is_tag_signed(rt.store(), &signing_tag(&tag2))
+            .unwrap());
+        assert!(!state
+            .is_tag_signed(rt.store(), &signing_tag(&tag3))
+            .unwrap());
+        assert_eq!(state.pending_tags, 1);
+    }
+
+    #[test]
+    fn test_sign_single_tag() {
+        let rt = construct_and_verify();
+        enable_for_contract(&rt);
+
+        let tag = Tag([1; 32]);
+        assert_eq!(enqueue_at(&rt, tag, 12), Ok(12));
+
+        // Blocks proposed for the first version of the actor carry a single signature.
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        rt.call::<Actor>(
+            Method::AddSignedTag as u64,
+            IpldBlock::serialize_cbor(&AddSignedTagParams {
+                height: 12,
+                signature: BlsSignature([12; 96]),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        rt.reset();
+
+        let state: State = rt.get_state();
+        assert!(state.is_tag_signed(rt.store(), &signing_tag(&tag)).unwrap());
+        assert_eq!(state.pending_tags, 0);
+    }
+}
@@ -1,10 +1,11 @@
 // Copyright 2022-2024 Protocol Labs
 // Copyright 2021-2023 BadBoi Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
-#[cfg(feature = "fil-actor")]
+#[cfg(any(feature = "fil-actor", test))]
 mod actor;
 mod shared;
 pub mod state;
+pub mod state_v1;
 
 pub use shared::*;
 pub use state::State;
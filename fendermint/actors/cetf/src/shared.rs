@@ -15,6 +15,20 @@ pub type BlockHeight = u64;
 
 pub const CETF_ACTOR_NAME: &str = "cetf";
 
+/// The default maximum number of enqueued tags waiting to be signed by the validators.
+pub const DEFAULT_MAX_PENDING_TAGS: u64 = 1024;
+
+/// The largest allowed maximum number of pending tags; every pending tag has to be signed
+/// in a vote extension, so the queue cannot be allowed to grow without bounds.
+pub const MAX_PENDING_TAGS_LIMIT: u64 = 65536;
+
+/// The default maximum number of tags signed at the same height; the validators sign each of
+/// them in their vote extensions, so this bounds the size of the extensions.
+pub const DEFAULT_MAX_TAGS_PER_HEIGHT: u64 = 32;
+
+/// The default, and smallest, number of epochs between enqueuing a tag and the height it is signed at.
+pub const DEFAULT_MIN_TAG_DISTANCE: u64 = 2;
+
 /// Tag which will be signed by Validators
 #[derive(Deserialize, Serialize, Clone, Copy, Eq, PartialEq, Debug)]
 #[serde(transparent)]
@@ -100,11 +114,25 @@ impl From<&[u8; 96]> for BlsSignature {
     }
 }
 
+#[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ConstructorParams {
+    pub max_pending_tags: u64,
+    pub max_tags_per_height: u64,
+    pub min_tag_distance: u64,
+}
+
 #[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct EnqueueTagParams {
     pub tag: Tag,
 }
 
+#[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct EnqueueTagAtHeightParams {
+    pub tag: Tag,
+    /// Height to sign the tag at; has to be at least `min_tag_distance` epochs in the future.
+    pub height: BlockHeight,
+}
+
 #[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct GetTagParams {
     pub height: BlockHeight,
@@ -116,12 +144,31 @@ pub struct AddValidatorParams {
     pub public_key: BlsPublicKey,
 }
 
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct UpdateValidatorKeyParams {
+    pub address: Address,
+    pub public_key: BlsPublicKey,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct RemoveValidatorParams {
+    pub address: Address,
+}
+
+/// Parameters of the first version of the actor, which scheduled a single tag per height.
 #[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct AddSignedTagParams {
     pub height: BlockHeight,
     pub signature: BlsSignature,
 }
 
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct AddSignedTagsParams {
+    pub height: BlockHeight,
+    /// Aggregated signatures of the tags scheduled at the height, in the order they were enqueued.
+    pub signatures: Vec<BlsSignature>,
+}
+
 #[derive(Deserialize, Serialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
 #[serde(transparent)]
 pub struct Hash32(#[serde(with = "strict_bytes")] pub [u8; 32]);
@@ -155,10 +202,14 @@ pub enum Method {
     Constructor = frc42_dispatch::method_hash!("Constructor"),
     Echo = frc42_dispatch::method_hash!("Echo"),
     EnqueueTag = frc42_dispatch::method_hash!("EnqueueTag"),
+    EnqueueTagAtHeight = frc42_dispatch::method_hash!("EnqueueTagAtHeight"),
     GetTag = frc42_dispatch::method_hash!("GetTag"),
     AddValidator = frc42_dispatch::method_hash!("AddValidator"),
     Enable = frc42_dispatch::method_hash!("Enable"),
     Disable = frc42_dispatch::method_hash!("Disable"),
     AddSignedTag = frc42_dispatch::method_hash!("AddSignedTag"),
+    AddSignedTags = frc42_dispatch::method_hash!("AddSignedTags"),
+    RemoveValidator = frc42_dispatch::method_hash!("RemoveValidator"),
+    UpdateValidatorKey = frc42_dispatch::method_hash!("UpdateValidatorKey"),
     // AddSignedBlockHeightTag = frc42_dispatch::method_hash!("AddSignedBlockHeightTag"),
 }
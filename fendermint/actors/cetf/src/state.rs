@@ -1,18 +1,21 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use crate::{BlockHeight, Tag};
+use crate::{state_v1, BlockHeight, Tag, DEFAULT_MIN_TAG_DISTANCE, MAX_PENDING_TAGS_LIMIT};
 use crate::{BlsPublicKey, BlsSignature};
 use cid::Cid;
 use fil_actors_runtime::actor_error;
-use fil_actors_runtime::{runtime::Runtime, ActorError, Map2};
+use fil_actors_runtime::{runtime::Runtime, ActorError, AsActorError, Map2};
+use fvm_ipld_amt::Amt;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::tuple::*;
 use fvm_sdk::crypto::hash_into;
 use fvm_shared::address::Address;
 use fvm_shared::crypto::hash::SupportedHashes;
+use fvm_shared::error::ExitCode;
 
-pub type TagMap<BS> = Map2<BS, BlockHeight, Tag>;
+pub type TagQueue<BS> = Amt<Vec<Tag>, BS>;
+pub type ScheduledTagMap<BS> = Map2<BS, Tag, BlockHeight>;
 pub type ValidatorBlsPublicKeyMap<BS> = Map2<BS, Address, BlsPublicKey>;
 
 pub type SignedHashedTagMap<BS> = Map2<BS, Tag, BlsSignature>;
@@ -20,16 +23,56 @@ pub type SignedHashedTagMap<BS> = Map2<BS, Tag, BlsSignature>;
 pub use fil_actors_runtime::DEFAULT_HAMT_CONFIG;
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
 pub struct State {
-    pub tag_map: Cid,    // HAMT[BlockHeight] => Tag
+    pub tag_queue: Cid,  // AMT[BlockHeight] => Vec<Tag>
     pub validators: Cid, // HAMT[Address] => BlsPublicKey (Assumes static validator set)
     pub enabled: bool,
 
     pub signed_hashed_tags: Cid, // HAMT[HashedTag] => BlsSignature(bytes 96)
+
+    pub scheduled_tags: Cid, // HAMT[Tag] => BlockHeight (reverse of tag_map, to reject duplicates)
+    pub pending_tags: u64,   // Number of enqueued tags not signed yet
+    pub max_pending_tags: u64,
+    pub max_tags_per_height: u64, // Maximum number of tags signed at the same height
+    pub min_tag_distance: u64,    // Minimum number of epochs between enqueuing a tag and signing it
 }
 
 impl State {
-    pub fn new<BS: Blockstore>(store: &BS) -> Result<State, ActorError> {
-        let tag_map = TagMap::empty(store, DEFAULT_HAMT_CONFIG, "empty tag_map").flush()?;
+    /// Create an empty state, validating the parameters, which can come from the constructor
+    /// as well as directly from the genesis.
+    pub fn new<BS: Blockstore>(
+        store: &BS,
+        max_pending_tags: u64,
+        max_tags_per_height: u64,
+        min_tag_distance: u64,
+    ) -> Result<State, ActorError> {
+        if max_pending_tags == 0 || max_pending_tags > MAX_PENDING_TAGS_LIMIT {
+            return Err(actor_error!(
+                illegal_argument,
+                "maximum number of pending tags must be between 1 and {}",
+                MAX_PENDING_TAGS_LIMIT
+            ));
+        }
+        if max_tags_per_height == 0 || max_tags_per_height > max_pending_tags {
+            return Err(actor_error!(
+                illegal_argument,
+                "maximum number of tags per height must be between 1 and {}",
+                max_pending_tags
+            ));
+        }
+        // Tags are signed in the vote extensions of the block before the target height,
+        // against the state committed before that, so they have to be known 2 epochs ahead.
+        if min_tag_distance < DEFAULT_MIN_TAG_DISTANCE {
+            return Err(actor_error!(
+                illegal_argument,
+                "minimum tag distance must be at least {}",
+                DEFAULT_MIN_TAG_DISTANCE
+            ));
+        }
+        let tag_queue = TagQueue::new(store)
+            .flush()
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "empty tag_queue")?;
+        let scheduled_tags =
+            ScheduledTagMap::empty(store, DEFAULT_HAMT_CONFIG, "empty scheduled_tags").flush()?;
         let validators =
             ValidatorBlsPublicKeyMap::empty(store, DEFAULT_HAMT_CONFIG, "empty validators")
                 .flush()?;
@@ -38,13 +81,52 @@ impl State {
             SignedHashedTagMap::empty(store, DEFAULT_HAMT_CONFIG, "empty signed_hashed_tags")
                 .flush()?;
         Ok(State {
-            tag_map,
+            tag_queue,
             validators,
             enabled: false,
             signed_hashed_tags,
+            scheduled_tags,
+            pending_tags: 0,
+            max_pending_tags,
+            max_tags_per_height,
+            min_tag_distance,
         })
     }
 
+    /// Convert the state of the first version of the actor, keeping the validators and the
+    /// signatures, and scheduling the tags of the old map at their heights.
+    ///
+    /// Tags which have been signed already are scheduled as well, so the signatures of their
+    /// heights can still be added, but they don't count towards the pending tags.
+    pub fn migrate_v1<BS: Blockstore>(
+        store: &BS,
+        old: &state_v1::State,
+        max_pending_tags: u64,
+        max_tags_per_height: u64,
+        min_tag_distance: u64,
+    ) -> Result<State, ActorError> {
+        let mut state = State::new(
+            store,
+            max_pending_tags,
+            max_tags_per_height,
+            min_tag_distance,
+        )?;
+        state.validators = old.validators;
+        state.enabled = old.enabled;
+        state.signed_hashed_tags = old.signed_hashed_tags;
+
+        for (height, tag) in old.scheduled_tags(store)? {
+            if state.is_tag_scheduled(store, &tag)? {
+                continue;
+            }
+            state.schedule_tag(store, height, &tag)?;
+            if !state.is_tag_signed(store, &tag)? {
+                state.pending_tags += 1;
+            }
+        }
+        Ok(state)
+    }
+
     pub fn add_validator<BS: Blockstore>(
         &mut self,
         store: &BS,
@@ -63,36 +145,179 @@ impl State {
         Ok(())
     }
 
-    pub fn add_tag_at_height(
+    /// Replace the public key of an already registered validator.
+    pub fn update_validator_key<BS: Blockstore>(
         &mut self,
-        rt: &impl Runtime,
-        height: &BlockHeight,
+        store: &BS,
+        address: &Address,
+        public_key: &BlsPublicKey,
+    ) -> Result<(), ActorError> {
+        let mut validators = ValidatorBlsPublicKeyMap::load(
+            store,
+            &self.validators,
+            DEFAULT_HAMT_CONFIG,
+            "writing validators",
+        )?;
+        if !validators.contains_key(address)? {
+            return Err(actor_error!(not_found, "validator {} not found", address));
+        }
+        validators.set(address, *public_key)?;
+        self.validators = validators.flush()?;
+
+        Ok(())
+    }
+
+    pub fn remove_validator<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        address: &Address,
+    ) -> Result<(), ActorError> {
+        let mut validators = ValidatorBlsPublicKeyMap::load(
+            store,
+            &self.validators,
+            DEFAULT_HAMT_CONFIG,
+            "writing validators",
+        )?;
+        validators
+            .delete(address)?
+            .ok_or_else(|| actor_error!(not_found, "validator {} not found", address))?;
+        self.validators = validators.flush()?;
+
+        Ok(())
+    }
+
+    /// Schedules the tag to be signed at the given height, along with any other tags
+    /// scheduled there, and returns that height. If there are already `max_tags_per_height`
+    /// tags at that height, the tag is moved to the first height after it which has room.
+    /// If the tag is already scheduled, returns the height it was first scheduled for instead.
+    /// Fails if there are already `max_pending_tags` tags waiting to be signed.
+    pub fn enqueue_tag<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        height: BlockHeight,
+        tag: &Tag,
+    ) -> Result<BlockHeight, ActorError> {
+        self.enqueue(store, height, tag, false)
+    }
+
+    /// Schedules the tag to be signed exactly at the given height, like [State::enqueue_tag],
+    /// except that it fails if there are already `max_tags_per_height` tags at that height.
+    pub fn enqueue_tag_at_height<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        height: BlockHeight,
+        tag: &Tag,
+    ) -> Result<BlockHeight, ActorError> {
+        self.enqueue(store, height, tag, true)
+    }
+
+    fn enqueue<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        mut height: BlockHeight,
+        tag: &Tag,
+        exact_height: bool,
+    ) -> Result<BlockHeight, ActorError> {
+        if let Some(scheduled) = self.scheduled_height(store, tag)? {
+            return Ok(scheduled);
+        }
+
+        if self.pending_tags >= self.max_pending_tags {
+            return Err(actor_error!(
+                forbidden,
+                "cannot enqueue tag: {} tags are already pending",
+                self.pending_tags
+            ));
+        }
+
+        // Pending tags are bounded, so this finds a height with room after a few iterations.
+        loop {
+            let scheduled = self.get_tags_at_height(store, height)?.len() as u64;
+            if scheduled < self.max_tags_per_height {
+                break;
+            }
+            if exact_height {
+                return Err(actor_error!(
+                    forbidden,
+                    "cannot enqueue tag: {} tags are already scheduled at height {}",
+                    scheduled,
+                    height
+                ));
+            }
+            height += 1;
+        }
+
+        self.schedule_tag(store, height, tag)?;
+        self.pending_tags += 1;
+
+        Ok(height)
+    }
+
+    /// The height a tag is scheduled to be signed at, if it has been enqueued.
+    fn scheduled_height<BS: Blockstore>(
+        &self,
+        store: &BS,
+        tag: &Tag,
+    ) -> Result<Option<BlockHeight>, ActorError> {
+        let scheduled_tags = ScheduledTagMap::load(
+            store,
+            &self.scheduled_tags,
+            DEFAULT_HAMT_CONFIG,
+            "reading scheduled_tags",
+        )?;
+        Ok(scheduled_tags.get(tag)?.copied())
+    }
+
+    fn is_tag_scheduled<BS: Blockstore>(&self, store: &BS, tag: &Tag) -> Result<bool, ActorError> {
+        Ok(self.scheduled_height(store, tag)?.is_some())
+    }
+
+    /// Append the tag to the ones scheduled at the height, without checking the limits.
+    fn schedule_tag<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        height: BlockHeight,
         tag: &Tag,
     ) -> Result<(), ActorError> {
-        let mut tag_map = TagMap::load(
-            rt.store(),
-            &self.tag_map,
+        let mut tag_queue = TagQueue::load(&self.tag_queue, store)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to load tag_queue")?;
+        let mut tags = tag_queue
+            .get(height)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to read tag_queue")?
+            .cloned()
+            .unwrap_or_default();
+        tags.push(*tag);
+        tag_queue
+            .set(height, tags)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to write tag_queue")?;
+        self.tag_queue = tag_queue
+            .flush()
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to flush tag_queue")?;
+
+        let mut scheduled_tags = ScheduledTagMap::load(
+            store,
+            &self.scheduled_tags,
             DEFAULT_HAMT_CONFIG,
-            "writing tag_map",
+            "writing scheduled_tags",
         )?;
-        tag_map.set(&height, tag.clone())?;
-        self.tag_map = tag_map.flush()?;
-        log::info!(
-            "Scheduled Cetf Tag for height {}. Current FVM epoch: {}. Tag: {:?}",
-            height,
-            rt.curr_epoch(),
-            tag.0,
-        );
+        scheduled_tags.set(tag, height)?;
+        self.scheduled_tags = scheduled_tags.flush()?;
+
         Ok(())
     }
 
-    pub fn get_tag_at_height<BS: Blockstore>(
+    /// The tags to be signed at the given height, in the order they were enqueued.
+    pub fn get_tags_at_height<BS: Blockstore>(
         &self,
         store: &BS,
-        height: &BlockHeight,
-    ) -> Result<Option<Tag>, ActorError> {
-        let tag_map = TagMap::load(store, &self.tag_map, DEFAULT_HAMT_CONFIG, "reading tag_map")?;
-        Ok(tag_map.get(&height)?.copied())
+        height: BlockHeight,
+    ) -> Result<Vec<Tag>, ActorError> {
+        let tag_queue = TagQueue::load(&self.tag_queue, store)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to load tag_queue")?;
+        let tags = tag_queue
+            .get(height)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to read tag_queue")?;
+        Ok(tags.cloned().unwrap_or_default())
     }
 
     pub fn get_validators_keymap<BS: Blockstore>(
@@ -107,28 +332,43 @@ impl State {
         )
     }
 
-    pub fn add_signed_tag_at_height(
+    /// Add the aggregated signatures of all the tags scheduled at a height, in the order they were enqueued.
+    pub fn add_signed_tags_at_height(
         &mut self,
         rt: &impl Runtime,
-        height: &BlockHeight,
-        signature: &BlsSignature,
+        height: BlockHeight,
+        signatures: &[BlsSignature],
     ) -> Result<(), ActorError> {
-        let tag = self
-            .get_tag_at_height(rt.store(), height)?
-            .ok_or_else(|| actor_error!(illegal_state, "Tag not found at height {}", height))?;
+        let tags = self.get_tags_at_height(rt.store(), height)?;
 
-        self.add_signed_and_hashed_tag(rt, tag, signature)?;
+        if tags.len() != signatures.len() {
+            return Err(actor_error!(
+                illegal_argument,
+                "expected {} signatures for the tags at height {}, got {}",
+                tags.len(),
+                height,
+                signatures.len()
+            ));
+        }
+
+        for (tag, signature) in tags.into_iter().zip(signatures) {
+            if !self.is_tag_signed(rt.store(), &tag)? {
+                self.pending_tags = self.pending_tags.saturating_sub(1);
+            }
+            self.add_signed_and_hashed_tag(rt, tag, signature)?;
+            log::trace!(
+                r#"Tag: {:?}
+                Signature: {:?}"#,
+                tag.0,
+                signature,
+            );
+        }
         log::info!(
-            "Added Signed Cetf Tag into map at height {}. FVM epoch: {}.",
+            "Added {} Signed Cetf Tags into map at height {}. FVM epoch: {}.",
+            signatures.len(),
             height,
             rt.curr_epoch(),
         );
-        log::trace!(
-            r#"Tag: {:?}
-            Signature: {:?}"#,
-            tag.0,
-            signature,
-        );
         Ok(())
     }
 
@@ -158,6 +398,16 @@ impl State {
         Ok(())
     }
 
+    pub fn is_tag_signed<BS: Blockstore>(&self, store: &BS, tag: &Tag) -> Result<bool, ActorError> {
+        let signed_hashed_tags = SignedHashedTagMap::load(
+            store,
+            &self.signed_hashed_tags,
+            DEFAULT_HAMT_CONFIG,
+            "reading signed_hashed_tags",
+        )?;
+        Ok(signed_hashed_tags.contains_key(tag)?)
+    }
+
     pub fn add_signed_and_hashed_tag(
         &mut self,
         rt: &impl Runtime,
@@ -175,3 +425,298 @@ impl State {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bls_signatures::{PrivateKey, PublicKey, Serialize};
+    use fvm_ipld_blockstore::MemoryBlockstore;
+    use fvm_shared::address::Address;
+
+    use super::{SignedHashedTagMap, State, ValidatorBlsPublicKeyMap, DEFAULT_HAMT_CONFIG};
+    use crate::state_v1;
+    use crate::{
+        BlsPublicKey, BlsSignature, Tag, DEFAULT_MAX_PENDING_TAGS, DEFAULT_MAX_TAGS_PER_HEIGHT,
+        DEFAULT_MIN_TAG_DISTANCE, MAX_PENDING_TAGS_LIMIT,
+    };
+
+    fn bls_key(seed: &[u8]) -> (PublicKey, BlsPublicKey) {
+        let pk = PrivateKey::new(seed).public_key();
+        let mut bytes = [0u8; 48];
+        bytes.copy_from_slice(&pk.as_bytes());
+        (pk, BlsPublicKey(bytes))
+    }
+
+    fn aggregate_key(state: &State, store: &MemoryBlockstore) -> Vec<u8> {
+        let mut keys = vec![];
+        state
+            .get_validators_keymap(store)
+            .unwrap()
+            .for_each(|_, v| {
+                keys.push(PublicKey::from_bytes(&v.0).unwrap());
+                Ok(())
+            })
+            .unwrap();
+        bls_signatures::aggregate_keys(&keys).unwrap().as_bytes()
+    }
+
+    #[test]
+    fn test_remove_validator() {
+        let store = MemoryBlockstore::new();
+        let mut state = State::new(
+            &store,
+            DEFAULT_MAX_PENDING_TAGS,
+            DEFAULT_MAX_TAGS_PER_HEIGHT,
+            DEFAULT_MIN_TAG_DISTANCE,
+        )
+        .unwrap();
+
+        let (pk1, key1) = bls_key(b"validator-1");
+        let (pk2, key2) = bls_key(b"validator-2");
+        let addr1 = Address::new_id(100);
+        let addr2 = Address::new_id(101);
+
+        state.add_validator(&store, &addr1, &key1).unwrap();
+        state.add_validator(&store, &addr2, &key2).unwrap();
+
+        let both = bls_signatures::aggregate_keys(&[pk1, pk2]).unwrap();
+        assert_eq!(aggregate_key(&state, &store), both.as_bytes());
+
+        state.remove_validator(&store, &addr2).unwrap();
+
+        let validators = state.get_validators_keymap(&store).unwrap();
+        assert!(validators.get(&addr2).unwrap().is_none());
+        assert_eq!(aggregate_key(&state, &store), pk1.as_bytes());
+
+        // Removing an unknown validator is an error.
+        assert!(state.remove_validator(&store, &addr2).is_err());
+    }
+
+    #[test]
+    fn test_update_validator_key() {
+        let store = MemoryBlockstore::new();
+        let mut state = State::new(
+            &store,
+            DEFAULT_MAX_PENDING_TAGS,
+            DEFAULT_MAX_TAGS_PER_HEIGHT,
+            DEFAULT_MIN_TAG_DISTANCE,
+        )
+        .unwrap();
+
+        let (pk1, key1) = bls_key(b"validator-1");
+        let (pk2, key2) = bls_key(b"validator-2");
+        let (pk3, key3) = bls_key(b"validator-2-rotated");
+        let addr1 = Address::new_id(100);
+        let addr2 = Address::new_id(101);
+
+        state.add_validator(&store, &addr1, &key1).unwrap();
+        state.add_validator(&store, &addr2, &key2).unwrap();
+
+        let before = bls_signatures::aggregate_keys(&[pk1, pk2]).unwrap();
+        assert_eq!(aggregate_key(&state, &store), before.as_bytes());
+
+        state.update_validator_key(&store, &addr2, &key3).unwrap();
+
+        let validators = state.get_validators_keymap(&store).unwrap();
+        assert_eq!(validators.get(&addr2).unwrap(), Some(&key3));
+
+        let after = bls_signatures::aggregate_keys(&[pk1, pk3]).unwrap();
+        assert_eq!(aggregate_key(&state, &store), after.as_bytes());
+        assert_ne!(after.as_bytes(), before.as_bytes());
+
+        // Only registered validators can rotate their keys.
+        let addr3 = Address::new_id(102);
+        assert!(state.update_validator_key(&store, &addr3, &key1).is_err());
+    }
+
+    #[test]
+    fn test_enqueue_duplicate_tag() {
+        let store = MemoryBlockstore::new();
+        let mut state = State::new(
+            &store,
+            DEFAULT_MAX_PENDING_TAGS,
+            DEFAULT_MAX_TAGS_PER_HEIGHT,
+            DEFAULT_MIN_TAG_DISTANCE,
+        )
+        .unwrap();
+        let tag = Tag([1; 32]);
+
+        assert_eq!(state.enqueue_tag(&store, 10, &tag).unwrap(), 10);
+        // Enqueuing the same tag again returns the epoch it was first scheduled for.
+        assert_eq!(state.enqueue_tag(&store, 12, &tag).unwrap(), 10);
+
+        assert_eq!(state.pending_tags, 1);
+        assert_eq!(state.get_tags_at_height(&store, 10).unwrap(), vec![tag]);
+        assert!(state.get_tags_at_height(&store, 12).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_enqueue_same_height() {
+        let store = MemoryBlockstore::new();
+        let mut state = State::new(
+            &store,
+            DEFAULT_MAX_PENDING_TAGS,
+            DEFAULT_MAX_TAGS_PER_HEIGHT,
+            DEFAULT_MIN_TAG_DISTANCE,
+        )
+        .unwrap();
+        let tag1 = Tag([1; 32]);
+        let tag2 = Tag([2; 32]);
+        let tag3 = Tag([3; 32]);
+
+        // Tags enqueued for the same height are all signed there, in order.
+        assert_eq!(state.enqueue_tag(&store, 10, &tag1).unwrap(), 10);
+        assert_eq!(state.enqueue_tag(&store, 10, &tag2).unwrap(), 10);
+        assert_eq!(state.enqueue_tag(&store, 10, &tag3).unwrap(), 10);
+
+        assert_eq!(state.pending_tags, 3);
+        assert_eq!(
+            state.get_tags_at_height(&store, 10).unwrap(),
+            vec![tag1, tag2, tag3]
+        );
+        assert!(state.get_tags_at_height(&store, 11).unwrap().is_empty());
+
+        // A duplicate is found where it was first scheduled.
+        assert_eq!(state.enqueue_tag(&store, 11, &tag2).unwrap(), 10);
+        assert_eq!(state.pending_tags, 3);
+    }
+
+    #[test]
+    fn test_enqueue_over_cap() {
+        let store = MemoryBlockstore::new();
+        let mut state = State::new(&store, 2, 2, DEFAULT_MIN_TAG_DISTANCE).unwrap();
+
+        state.enqueue_tag(&store, 10, &Tag([1; 32])).unwrap();
+        state.enqueue_tag(&store, 11, &Tag([2; 32])).unwrap();
+
+        let err = state.enqueue_tag(&store, 12, &Tag([3; 32])).unwrap_err();
+        assert_eq!(err.exit_code(), fvm_shared::error::ExitCode::USR_FORBIDDEN);
+        assert!(state.get_tags_at_height(&store, 12).unwrap().is_empty());
+
+        // Duplicates are still accepted since they don't add to the queue.
+        assert_eq!(state.enqueue_tag(&store, 12, &Tag([2; 32])).unwrap(), 11);
+    }
+
+    #[test]
+    fn test_enqueue_full_height() {
+        let store = MemoryBlockstore::new();
+        let mut state = State::new(&store, 10, 2, DEFAULT_MIN_TAG_DISTANCE).unwrap();
+
+        assert_eq!(state.enqueue_tag(&store, 10, &Tag([1; 32])).unwrap(), 10);
+        assert_eq!(state.enqueue_tag(&store, 10, &Tag([2; 32])).unwrap(), 10);
+
+        // Asking for an exact height which is full is rejected.
+        let err = state
+            .enqueue_tag_at_height(&store, 10, &Tag([3; 32]))
+            .unwrap_err();
+        assert_eq!(err.exit_code(), fvm_shared::error::ExitCode::USR_FORBIDDEN);
+        assert_eq!(state.pending_tags, 2);
+
+        // Otherwise the tag is moved to the next height with room.
+        assert_eq!(state.enqueue_tag(&store, 10, &Tag([3; 32])).unwrap(), 11);
+        assert_eq!(state.enqueue_tag(&store, 10, &Tag([4; 32])).unwrap(), 11);
+        assert_eq!(state.enqueue_tag(&store, 10, &Tag([5; 32])).unwrap(), 12);
+
+        assert_eq!(state.get_tags_at_height(&store, 10).unwrap().len(), 2);
+        assert_eq!(
+            state.get_tags_at_height(&store, 11).unwrap(),
+            vec![Tag([3; 32]), Tag([4; 32])]
+        );
+        assert_eq!(state.pending_tags, 5);
+    }
+
+    #[test]
+    fn test_max_tags_per_height_is_validated() {
+        let store = MemoryBlockstore::new();
+        for max_tags_per_height in [0, 11] {
+            let err =
+                State::new(&store, 10, max_tags_per_height, DEFAULT_MIN_TAG_DISTANCE).unwrap_err();
+            assert_eq!(
+                err.exit_code(),
+                fvm_shared::error::ExitCode::USR_ILLEGAL_ARGUMENT
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_tag_distance() {
+        let store = MemoryBlockstore::new();
+        let err = State::new(
+            &store,
+            DEFAULT_MAX_PENDING_TAGS,
+            DEFAULT_MAX_TAGS_PER_HEIGHT,
+            DEFAULT_MIN_TAG_DISTANCE - 1,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.exit_code(),
+            fvm_shared::error::ExitCode::USR_ILLEGAL_ARGUMENT
+        );
+    }
+
+    #[test]
+    fn test_max_pending_tags_is_validated() {
+        let store = MemoryBlockstore::new();
+        for max_pending_tags in [0, MAX_PENDING_TAGS_LIMIT + 1] {
+            let err =
+                State::new(&store, max_pending_tags, 1, DEFAULT_MIN_TAG_DISTANCE).unwrap_err();
+            assert_eq!(
+                err.exit_code(),
+                fvm_shared::error::ExitCode::USR_ILLEGAL_ARGUMENT
+            );
+        }
+        State::new(&store, MAX_PENDING_TAGS_LIMIT, 1, DEFAULT_MIN_TAG_DISTANCE).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_v1() {
+        let store = MemoryBlockstore::new();
+        let signed = Tag([1; 32]);
+        let unsigned = Tag([2; 32]);
+        let (_, key) = bls_key(b"validator-1");
+        let addr = Address::new_id(100);
+
+        let mut tag_map = state_v1::TagMap::empty(&store, DEFAULT_HAMT_CONFIG, "empty tag_map");
+        tag_map.set(&10, signed).unwrap();
+        tag_map.set(&12, unsigned).unwrap();
+
+        let mut validators =
+            ValidatorBlsPublicKeyMap::empty(&store, DEFAULT_HAMT_CONFIG, "empty validators");
+        validators.set(&addr, key).unwrap();
+
+        let mut signed_hashed_tags =
+            SignedHashedTagMap::empty(&store, DEFAULT_HAMT_CONFIG, "empty signed_hashed_tags");
+        signed_hashed_tags
+            .set(&signed, BlsSignature([3; 96]))
+            .unwrap();
+
+        let old = state_v1::State {
+            tag_map: tag_map.flush().unwrap(),
+            validators: validators.flush().unwrap(),
+            enabled: true,
+            signed_hashed_tags: signed_hashed_tags.flush().unwrap(),
+        };
+
+        let mut state = State::migrate_v1(
+            &store,
+            &old,
+            DEFAULT_MAX_PENDING_TAGS,
+            DEFAULT_MAX_TAGS_PER_HEIGHT,
+            DEFAULT_MIN_TAG_DISTANCE,
+        )
+        .unwrap();
+
+        assert!(state.enabled);
+        assert_eq!(state.validators, old.validators);
+        assert_eq!(state.signed_hashed_tags, old.signed_hashed_tags);
+        assert_eq!(state.get_tags_at_height(&store, 10).unwrap(), vec![signed]);
+        assert_eq!(
+            state.get_tags_at_height(&store, 12).unwrap(),
+            vec![unsigned]
+        );
+        assert_eq!(state.pending_tags, 1);
+
+        // The migrated tags are known as duplicates.
+        assert_eq!(state.enqueue_tag(&store, 20, &unsigned).unwrap(), 12);
+        assert_eq!(state.pending_tags, 1);
+    }
+}
@@ -0,0 +1,49 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! The state of the first version of the actor, which scheduled a single tag per height,
+//! kept so that existing state can be migrated to the current layout.
+
+use crate::{BlockHeight, Tag};
+use cid::Cid;
+use fil_actors_runtime::{ActorError, Map2, DEFAULT_HAMT_CONFIG};
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::*;
+
+pub type TagMap<BS> = Map2<BS, BlockHeight, Tag>;
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct State {
+    pub tag_map: Cid,    // HAMT[BlockHeight] => Tag
+    pub validators: Cid, // HAMT[Address] => BlsPublicKey (Assumes static validator set)
+    pub enabled: bool,
+
+    pub signed_hashed_tags: Cid, // HAMT[HashedTag] => BlsSignature(bytes 96)
+}
+
+impl State {
+    /// The tag scheduled at the given height, if any.
+    pub fn get_tag_at_height<BS: Blockstore>(
+        &self,
+        store: &BS,
+        height: BlockHeight,
+    ) -> Result<Option<Tag>, ActorError> {
+        let tag_map = TagMap::load(store, &self.tag_map, DEFAULT_HAMT_CONFIG, "reading tag_map")?;
+        Ok(tag_map.get(&height)?.copied())
+    }
+
+    /// All the scheduled tags, ordered by their height.
+    pub fn scheduled_tags<BS: Blockstore>(
+        &self,
+        store: &BS,
+    ) -> Result<Vec<(BlockHeight, Tag)>, ActorError> {
+        let tag_map = TagMap::load(store, &self.tag_map, DEFAULT_HAMT_CONFIG, "reading tag_map")?;
+        let mut tags = Vec::new();
+        tag_map.for_each(|height, tag| {
+            tags.push((height, *tag));
+            Ok(())
+        })?;
+        tags.sort_by_key(|(height, _)| *height);
+        Ok(tags)
+    }
+}
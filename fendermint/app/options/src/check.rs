@@ -0,0 +1,7 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct CheckArgs;
@@ -1,7 +1,16 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use clap::Args;
+use clap::{Args, Subcommand};
 
 #[derive(Args, Debug)]
-pub struct ConfigArgs;
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: Option<ConfigCommands>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommands {
+    /// Check the settings for invalid combinations without starting the node.
+    Validate,
+}
@@ -7,6 +7,7 @@ use crate::parse::parse_eth_address;
 use clap::{Args, Subcommand};
 use fvm_shared::address::Address;
 use ipc_api::subnet_id::SubnetID;
+use tendermint_rpc::Url;
 
 #[derive(Args, Debug)]
 pub struct DebugArgs {
@@ -21,6 +22,98 @@ pub enum DebugCommands {
         #[command(subcommand)]
         command: DebugIpcCommands,
     },
+    /// Database commands.
+    Db {
+        #[command(subcommand)]
+        command: DebugDbCommands,
+    },
+    /// Top-down finality commands.
+    Topdown {
+        #[command(subcommand)]
+        command: DebugTopDownCommands,
+    },
+    /// Ledger state commands.
+    State {
+        #[command(subcommand)]
+        command: DebugStateCommands,
+    },
+    /// Network upgrade commands.
+    Upgrades {
+        #[command(subcommand)]
+        command: DebugUpgradesCommands,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DebugUpgradesCommands {
+    /// Print the upgrades the node would schedule from its upgrades file, to confirm before
+    /// a network upgrade that the migration is registered at the expected height.
+    Schedule {
+        /// Path to the upgrades file, which is `config/upgrades.toml` in the home directory by default.
+        #[arg(long, default_value = "~/.fendermint/config/upgrades.toml")]
+        upgrades_file: PathBuf,
+
+        /// Fail unless an upgrade is scheduled at this block height.
+        #[arg(long)]
+        height: Option<u64>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DebugStateCommands {
+    /// Print the sum of the balances of all actors in the last committed state, next to the
+    /// circulating supply tracked in the state parameters, to help spot the latter drifting.
+    ///
+    /// The sum is not a circulating supply: the burnt funds actor is shown separately, and it
+    /// holds the burnt gas as well as the released tokens. The node has to be stopped, and it
+    /// iterates over all the actors in the state, which can take a while.
+    Balances {
+        /// Data directory of the node, which contains the `rocksdb` directory.
+        #[arg(long, default_value = "~/.fendermint/data")]
+        data_dir: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DebugTopDownCommands {
+    /// Print the view of the parent chain cached by the top-down finality provider of a running node.
+    View {
+        /// The URL of the Tendermint node's RPC endpoint.
+        #[arg(
+            long,
+            short,
+            default_value = "http://127.0.0.1:26657",
+            env = "TENDERMINT_RPC_URL"
+        )]
+        url: Url,
+
+        /// An optional HTTP/S proxy through which to submit requests to the
+        /// Tendermint node's RPC endpoint.
+        #[arg(long)]
+        proxy_url: Option<Url>,
+
+        /// Parent block height to show the cached data of.
+        #[arg(long)]
+        height: Option<u64>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DebugDbCommands {
+    /// Print the schema version of the database, and the latest one this binary supports.
+    Version {
+        /// Data directory of the node, which contains the `rocksdb` directory.
+        #[arg(long, default_value = "~/.fendermint/data")]
+        data_dir: PathBuf,
+    },
+    /// Print the size of the files of each namespace in the database, in bytes.
+    ///
+    /// Only data that has been flushed to disk is counted.
+    Stats {
+        /// Data directory of the node, which contains the `rocksdb` directory.
+        #[arg(long, default_value = "~/.fendermint/data")]
+        data_dir: PathBuf,
+    },
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -32,8 +32,16 @@ pub enum EthCommands {
         )]
         ws_url: WebSocketClientUrl,
 
-        /// Seconds to wait between trying to connect to the websocket.
+        /// Seconds to wait before retrying to connect to the websocket, doubled after every failed attempt.
         #[arg(long, short = 'd', default_value = "5")]
         connect_retry_delay: u64,
+
+        /// Maximum seconds to wait between attempts to connect to the websocket.
+        #[arg(long, default_value = "60")]
+        connect_max_retry_delay: u64,
+
+        /// Number of times to retry connecting to the websocket before giving up; retry forever if not set.
+        #[arg(long)]
+        connect_max_retries: Option<u32>,
     },
 }
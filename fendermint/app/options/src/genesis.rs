@@ -11,7 +11,7 @@ use super::parse::{
     parse_token_amount,
 };
 use fendermint_vm_genesis::SignerAddr;
-use fvm_shared::{address::Address, econ::TokenAmount, version::NetworkVersion};
+use fvm_shared::{address::Address, clock::ChainEpoch, econ::TokenAmount, version::NetworkVersion};
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum AccountKind {
@@ -87,6 +87,15 @@ pub struct GenesisNewArgs {
     /// Number of decimals to use during converting FIL to Power.
     #[arg(long, short)]
     pub power_scale: i8,
+    /// Maximum number of CETF tags waiting to be signed; uses the actor default if not set.
+    #[arg(long)]
+    pub cetf_max_pending_tags: Option<u64>,
+    /// Maximum number of CETF tags signed at the same height; uses the actor default if not set.
+    #[arg(long)]
+    pub cetf_max_tags_per_height: Option<u64>,
+    /// Minimum number of epochs between enqueuing a CETF tag and signing it; uses the actor default if not set.
+    #[arg(long)]
+    pub cetf_min_tag_distance: Option<u64>,
 }
 
 #[derive(Args, Debug)]
@@ -204,4 +213,17 @@ pub struct GenesisFromParentArgs {
     /// Number of decimals to use during converting FIL to Power.
     #[arg(long, default_value = "3")]
     pub power_scale: i8,
+
+    /// Parent height to query the genesis parameters at, to get the same genesis every time;
+    /// defaults to the latest block.
+    #[arg(long)]
+    pub parent_height: Option<ChainEpoch>,
+
+    /// Permission mode (unrestricted/allowlist) that controls who can deploy contracts in the subnet.
+    #[arg(long, default_value = "unrestricted")]
+    pub eam_permission_mode: String,
+
+    /// List of addresses that can deploy contracts. Field is ignored if mode is unrestricted.
+    #[arg(long, value_delimiter = ',', value_parser = parse_signer_addr)]
+    pub eam_addresses: Vec<SignerAddr>,
 }
@@ -11,6 +11,8 @@ pub enum KeyCommands {
     Gen(KeyGenArgs),
     /// Convert a secret key file from base64 into the format expected by Tendermint.
     IntoTendermint(KeyIntoTendermintArgs),
+    /// Convert a CometBFT `priv_validator_key.json` file with a Secp256k1 key into a Base64 encoded Fendermint keypair.
+    FromTendermint(KeyFromTendermintArgs),
     /// Convert a public key file from base64 into an f1 Address format an print it to STDOUT.
     Address(KeyAddressArgs),
     /// Get the peer ID corresponding to a node ID and its network address and print it to a local file.
@@ -18,7 +20,8 @@ pub enum KeyCommands {
     /// Converts a hex encoded Ethereum private key into a Base64 encoded Fendermint keypair.
     #[clap(alias = "eth-to-fendermint")]
     FromEth(KeyFromEthArgs),
-    /// Converts a Base64 encoded Fendermint private key into a hex encoded Ethereum secret key, public key and address (20 bytes).
+    /// Converts a Base64 encoded Fendermint private key into a hex encoded Ethereum secret key, public key and address (20 bytes),
+    /// which can be imported with `ipc-cli wallet import --wallet-type evm`.
     IntoEth(KeyIntoEthArgs),
     /// Show the libp2p peer ID derived from a Secp256k1 public key.
     ShowPeerId(KeyShowPeerIdArgs),
@@ -61,11 +64,17 @@ pub struct KeyFromEthArgs {
     #[arg(long, short)]
     pub secret_key: PathBuf,
     /// Name used to distinguish the files from other exported keys.
-    #[arg(long, short)]
-    pub name: String,
+    #[arg(long, short, required_unless_present_any = ["out_file", "show_secret"])]
+    pub name: Option<String>,
     /// Directory to export the key files to; it must exist.
     #[arg(long, short, default_value = ".")]
     pub out_dir: PathBuf,
+    /// Write only the converted secret key to this file.
+    #[arg(long)]
+    pub out_file: Option<PathBuf>,
+    /// Print the converted secret key to STDOUT.
+    #[arg(long)]
+    pub show_secret: bool,
 }
 
 #[derive(Args, Debug)]
@@ -74,11 +83,17 @@ pub struct KeyIntoEthArgs {
     #[arg(long, short)]
     pub secret_key: PathBuf,
     /// Name used to distinguish the files from other exported keys.
-    #[arg(long, short)]
-    pub name: String,
+    #[arg(long, short, required_unless_present_any = ["out_file", "show_secret"])]
+    pub name: Option<String>,
     /// Directory to export the key files to; it must exist.
     #[arg(long, short, default_value = ".")]
     pub out_dir: PathBuf,
+    /// Write only the converted secret key to this file.
+    #[arg(long)]
+    pub out_file: Option<PathBuf>,
+    /// Print the converted secret key to STDOUT.
+    #[arg(long)]
+    pub show_secret: bool,
 }
 
 #[derive(Args, Debug)]
@@ -87,8 +102,35 @@ pub struct KeyIntoTendermintArgs {
     #[arg(long, short)]
     pub secret_key: PathBuf,
     /// Output file name for the Tendermint private validator key JSON file.
+    #[arg(
+        long,
+        short,
+        alias = "out-file",
+        required_unless_present = "show_secret"
+    )]
+    pub out: Option<PathBuf>,
+    /// Print the Tendermint private validator key JSON to STDOUT.
+    #[arg(long)]
+    pub show_secret: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct KeyFromTendermintArgs {
+    /// Path to the CometBFT private validator key JSON file.
     #[arg(long, short)]
-    pub out: PathBuf,
+    pub priv_validator_key: PathBuf,
+    /// Name used to distinguish the files from other exported keys.
+    #[arg(long, short, required_unless_present_any = ["out_file", "show_secret"])]
+    pub name: Option<String>,
+    /// Directory to export the key files to; it must exist.
+    #[arg(long, short, default_value = ".")]
+    pub out_dir: PathBuf,
+    /// Write only the converted secret key to this file.
+    #[arg(long)]
+    pub out_file: Option<PathBuf>,
+    /// Print the converted secret key to STDOUT.
+    #[arg(long)]
+    pub show_secret: bool,
 }
 
 #[derive(Args, Debug)]
@@ -3,7 +3,8 @@
 
 use std::path::PathBuf;
 
-use clap::{Args, Parser, Subcommand};
+use check::CheckArgs;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use config::ConfigArgs;
 use debug::DebugArgs;
 use fvm_shared::address::Network;
@@ -15,6 +16,7 @@ use self::{
     run::RunArgs,
 };
 
+pub mod check;
 pub mod config;
 pub mod debug;
 pub mod eth;
@@ -27,7 +29,7 @@ pub mod run;
 mod log;
 mod parse;
 
-use log::{parse_log_level, LogLevel};
+use log::{parse_log_filter_file, parse_log_level, LogLevel};
 use parse::parse_network;
 
 lazy_static! {
@@ -135,6 +137,19 @@ pub struct Options {
     )]
     log_file_level: Option<LogLevel>,
 
+    /// Read additional log filters from a file, one directive per line, e.g. `libp2p::gossipsub=info`.
+    /// They are applied after `--log-level` and `--log-file-level`, so they win for the same target.
+    #[arg(
+        long,
+        env = "FM_LOG_FILTER_FILE",
+        value_parser = parse_log_filter_file,
+    )]
+    log_filter_file: Option<LogLevel>,
+
+    /// Format of what the commands print, for people or for scripts.
+    #[arg(long, value_enum, default_value = "text", env = "FM_OUTPUT")]
+    pub output: OutputFormat,
+
     /// Global options repeated here for discoverability, so they show up in `--help` among the others.
     #[command(flatten)]
     pub global: GlobalArgs,
@@ -150,18 +165,28 @@ impl Options {
     /// because the `tracing_subscriber` setup methods like `with_filter` and `with_level`
     /// produce different static types and it's not obvious how to use them as alternatives.
     pub fn log_console_filter(&self) -> anyhow::Result<EnvFilter> {
-        self.log_level.to_filter()
+        self.log_filter(&self.log_level)
     }
 
     /// Tracing filter for the log file.
     pub fn log_file_filter(&self) -> anyhow::Result<EnvFilter> {
         if let Some(ref level) = self.log_file_level {
-            level.to_filter()
+            self.log_filter(level)
         } else {
             self.log_console_filter()
         }
     }
 
+    /// Merge the directives from the filter file, if any, into the level.
+    fn log_filter(&self, level: &LogLevel) -> anyhow::Result<EnvFilter> {
+        match self.log_filter_file {
+            Some(ref directives) => {
+                LogLevel::Filter(format!("{},{}", level.as_str(), directives.as_str())).to_filter()
+            }
+            None => level.to_filter(),
+        }
+    }
+
     /// Path to the configuration directories.
     ///
     /// If not specified then returns the default under the home directory.
@@ -178,11 +203,22 @@ impl Options {
     }
 }
 
+/// Format of the output printed by the commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable text.
+    Text,
+    /// A single line of JSON.
+    Json,
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Parse the configuration file and print it to the console.
     Config(ConfigArgs),
+    /// Check the configuration and the files it points at, without starting the node.
+    Check(CheckArgs),
     /// Arbitrary commands that aid in debugging.
     Debug(DebugArgs),
     /// Run the `App`, listening to ABCI requests from Tendermint.
@@ -296,6 +332,46 @@ mod tests {
         assert_level("fendermint --log-level info run", LevelFilter::INFO);
     }
 
+    #[test]
+    fn parse_log_filter_file() {
+        let path =
+            std::env::temp_dir().join(format!("fendermint-log-filter-{}", std::process::id()));
+        std::fs::write(
+            &path,
+            "# Quieter networking.\nlibp2p=warn\n\nfendermint_app=debug\n",
+        )
+        .unwrap();
+
+        let cmd = format!(
+            "fendermint --log-level info --log-filter-file {} run",
+            path.to_string_lossy()
+        );
+        let opts: Options = Options::parse_from(cmd.split_ascii_whitespace());
+        let filter = opts.log_console_filter().expect("filter should parse");
+        std::fs::remove_file(&path).unwrap();
+
+        // The most verbose directive comes from the file.
+        assert_eq!(filter.max_level_hint(), Some(LevelFilter::DEBUG));
+    }
+
+    #[test]
+    fn parse_output_format() {
+        let opts: Options =
+            Options::parse_from(["fendermint", "key", "address", "--public-key", "pk"]);
+        assert_eq!(opts.output, OutputFormat::Text);
+
+        let opts: Options = Options::parse_from([
+            "fendermint",
+            "--output",
+            "json",
+            "key",
+            "address",
+            "--public-key",
+            "pk",
+        ]);
+        assert_eq!(opts.output, OutputFormat::Json);
+    }
+
     #[test]
     fn parse_invalid_log_level() {
         // NOTE: `nonsense` in itself is interpreted as a target. Maybe we should mandate at least `=` in it?
@@ -68,6 +68,24 @@ impl ValueEnum for LogLevel {
     }
 }
 
+/// Read filter directives from a file, one per line, ignoring empty lines and `#` comments.
+pub fn parse_log_filter_file(path: &str) -> Result<LogLevel, String> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| format!("failed to read {path}: {e}"))?;
+
+    let directives = contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Vec<_>>();
+
+    if directives.is_empty() {
+        return Err(format!("no filter directives in {path}"));
+    }
+
+    parse_log_level(&directives.join(","))
+}
+
 pub fn parse_log_level(s: &str) -> Result<LogLevel, String> {
     if let Ok(lvl) = ValueEnum::from_str(s, true) {
         return Ok(lvl);
@@ -23,6 +23,18 @@ pub struct MaterializerArgs {
     #[arg(long, short, env = "FM_MATERIALIZER__SEED", default_value = "0")]
     pub seed: u64,
 
+    /// Docker image to run `fendermint` and `ipc-cli` with, instead of the default `fendermint:latest`.
+    ///
+    /// Nodes can still override it in the manifest.
+    #[arg(long, env = "FM_MATERIALIZER__FENDERMINT_IMAGE")]
+    pub fendermint_image: Option<String>,
+
+    /// Docker image to run `cometbft` with, instead of the default.
+    ///
+    /// Nodes can still override it in the manifest.
+    #[arg(long, env = "FM_MATERIALIZER__COMETBFT_IMAGE")]
+    pub cometbft_image: Option<String>,
+
     #[command(subcommand)]
     pub command: MaterializerCommands,
 }
@@ -36,6 +48,8 @@ pub enum MaterializerCommands {
     /// Tear down a testnet.
     #[clap(aliases  = &["teardown", "rm"])]
     Remove(MaterializerRemoveArgs),
+    /// Remove the resources of crashed or abandoned testnets, keeping the ones which are still running.
+    Prune(MaterializerPruneArgs),
     /// Import an existing secret key into a testnet; for example to use an already funded account on Calibration net.
     ImportKey(MaterializerImportKeyArgs),
 }
@@ -71,6 +85,13 @@ pub struct MaterializerRemoveArgs {
     pub testnet_id: TestnetId,
 }
 
+#[derive(Args, Debug)]
+pub struct MaterializerPruneArgs {
+    /// Only print what would be removed, without removing anything.
+    #[arg(long, default_value = "false")]
+    pub dry_run: bool,
+}
+
 #[derive(Args, Debug)]
 pub struct MaterializerImportKeyArgs {
     /// Path to the manifest file.
@@ -67,6 +67,12 @@ pub enum RpcCommands {
         #[command(flatten)]
         args: TransArgs,
     },
+    /// Get the hash of a block as recorded by the chain metadata actor; print it as JSON.
+    BlockHash {
+        /// Height of the block to look up.
+        #[arg(long)]
+        height: u64,
+    },
     /// Subcommands related to FEVM.
     Fevm {
         #[command(subcommand)]
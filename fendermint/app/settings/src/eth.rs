@@ -18,6 +18,10 @@ pub struct EthSettings {
     pub cache_capacity: usize,
     pub gas: GasOpt,
     pub max_nonce_gap: u64,
+    /// Maximum number of blocks an `eth_getLogs` query can span.
+    pub max_log_block_range: u64,
+    /// Maximum size of an HTTP request body in bytes.
+    pub max_request_bytes: usize,
 }
 
 #[serde_as]
@@ -11,10 +11,11 @@ use crate::IsHumanReadable;
 #[derive(Debug, Deserialize, Clone)]
 pub struct FvmSettings {
     /// Overestimation rate applied to gas estimations to ensure that the
-    /// message goes through
+    /// message goes through; it has to be at least 1.0.
     pub gas_overestimation_rate: f64,
     /// Gas search step increase used to find the optimal gas limit.
-    /// It determines how fine-grained we want the gas estimation to be.
+    /// It determines how fine-grained we want the gas estimation to be;
+    /// it has to be more than 1.0.
     pub gas_search_step: f64,
     /// Indicate whether transactions should be fully executed during the checks performed
     /// when they are added to the mempool, or just the most basic ones are performed.
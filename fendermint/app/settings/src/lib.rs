@@ -8,6 +8,7 @@ use fvm_shared::econ::TokenAmount;
 use ipc_api::subnet_id::SubnetID;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DurationSeconds};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::net::{SocketAddr, ToSocketAddrs};
 use std::path::{Path, PathBuf};
@@ -29,6 +30,9 @@ pub mod fvm;
 pub mod resolver;
 pub mod testing;
 pub mod utils;
+mod validation;
+
+pub use validation::SettingsError;
 
 /// Marker to be used with the `#[serde_as(as = "IsHumanReadable")]` annotations.
 ///
@@ -102,6 +106,10 @@ pub struct AbciSettings {
     pub bound: usize,
     /// Maximum number of messages allowed in a block.
     pub block_max_msgs: usize,
+    /// Maximum cumulative size of the messages in a block, in bytes.
+    ///
+    /// Should be below the maximum block size of CometBFT, which also includes the header and the evidence.
+    pub block_max_bytes: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -134,14 +142,38 @@ impl Display for DbCompaction {
     }
 }
 
+#[serde_as]
 #[derive(Debug, Deserialize, Clone)]
 pub struct DbSettings {
     /// Length of the app state history to keep in the database before pruning; 0 means unlimited.
     ///
     /// This affects how long we can go back in state queries.
     pub state_hist_size: u64,
+    /// Interval of pruning the app state history in the background.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    pub state_hist_prune_interval: Duration,
     /// How to compact the datastore.
     pub compaction_style: DbCompaction,
+    /// Interval of manually compacting all namespaces, which is how the datastore
+    /// gets compacted when the compaction style is `none`.
+    #[serde_as(as = "Option<DurationSeconds<u64>>")]
+    #[serde(default)]
+    pub compaction_interval: Option<Duration>,
+    /// Time-to-live of the entries in seconds for namespaces holding ephemeral data,
+    /// overriding the defaults declared by the namespaces themselves.
+    ///
+    /// Only the namespaces which declared a default TTL can be listed.
+    #[serde(default)]
+    pub namespace_ttl: BTreeMap<String, u64>,
+    /// Size of the RocksDB block cache in MiB.
+    #[serde(default)]
+    pub block_cache_size_mb: Option<usize>,
+    /// Size of the RocksDB memtable in MiB.
+    #[serde(default)]
+    pub write_buffer_size_mb: Option<usize>,
+    /// Maximum number of files RocksDB can keep open; -1 means unlimited.
+    #[serde(default)]
+    pub max_open_files: Option<i32>,
 }
 
 /// Settings affecting how we deal with failures in trying to send transactions to the local CometBFT node.
@@ -152,11 +184,35 @@ pub struct DbSettings {
 pub struct BroadcastSettings {
     /// Number of times to retry broadcasting a transaction.
     pub max_retries: u8,
+    /// Number of times to retry a transaction which lost a nonce race against another
+    /// transaction from the same sender. These don't count against `max_retries`.
+    pub nonce_race_retries: u8,
     /// Time to wait between retries. This should roughly correspond to the block interval.
     #[serde_as(as = "DurationSeconds<u64>")]
     pub retry_delay: Duration,
     /// Any over-estimation to apply on top of the estimate returned by the API.
     pub gas_overestimation_rate: f64,
+    /// Overrides of the gas parameters per kind of transaction, which otherwise use the global FVM settings.
+    #[serde(default)]
+    pub gas: BroadcastGasSettings,
+}
+
+/// Overrides of the gas parameters for a kind of transaction broadcasted by the validator.
+#[serde_as]
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct GasOverrideSettings {
+    /// Over-estimation to apply on top of the gas estimate.
+    pub gas_overestimation_rate: Option<f64>,
+    /// Gas premium to offer.
+    #[serde_as(as = "Option<IsHumanReadable>")]
+    pub gas_premium: Option<TokenAmount>,
+}
+
+/// Gas parameter overrides per kind of transaction broadcasted by the validator.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct BroadcastGasSettings {
+    /// Submitting signatures over bottom-up checkpoints.
+    pub checkpoint: Option<GasOverrideSettings>,
 }
 
 #[serde_as]
@@ -175,6 +231,10 @@ pub struct TopDownSettings {
     pub max_proposal_range: BlockHeight,
     /// The max number of blocks to hold in memory for parent syncer
     pub max_cache_blocks: Option<BlockHeight>,
+    /// Reject parent finality proposals which don't commit to the top-down messages and
+    /// validator changes they execute. Only turn it on once all validators propose them.
+    #[serde(default)]
+    pub require_effects_commitment: bool,
     /// Parent syncing cron period, in seconds
     #[serde_as(as = "DurationSeconds<u64>")]
     pub polling_interval: Duration,
@@ -209,6 +269,10 @@ pub struct IpcSettings {
     /// Timeout after which the last vote is re-published.
     #[serde_as(as = "DurationSeconds<u64>")]
     pub vote_timeout: Duration,
+    /// Parent block height from which votes are gossiped in the versioned V1 format.
+    ///
+    /// Until then they are sent in the unversioned format understood by older releases.
+    pub vote_v1_height: Option<BlockHeight>,
     /// The config for top down checkpoint. It's None if subnet id is root or not activating
     /// any top down checkpoint related operations
     pub topdown: Option<TopDownSettings>,
@@ -233,6 +297,10 @@ pub struct SnapshotSettings {
     pub hist_size: usize,
     /// Target chunk size, in bytes.
     pub chunk_size_bytes: usize,
+    /// Compress the chunks with zstd at this level; leave empty to not compress them.
+    ///
+    /// Snapshots are imported according to the compression recorded in their manifest.
+    pub compression_level: Option<i32>,
     /// How long to keep a snapshot from being purged after it has been requested by a peer.
     #[serde_as(as = "DurationSeconds<u64>")]
     pub last_access_hold: Duration,
@@ -257,6 +325,7 @@ pub struct MetricsSettings {
     pub listen: SocketAddress,
 }
 
+#[serde_as]
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     /// Home directory configured on the CLI, to which all paths in settings can be set relative.
@@ -271,12 +340,18 @@ pub struct Settings {
     builtin_actors_bundle: PathBuf,
     /// Custom actors CAR file.
     custom_actors_bundle: PathBuf,
+    /// Upgrades to schedule by name and height; ignored if the file doesn't exist.
+    upgrades_file: PathBuf,
 
     /// Where to reach CometBFT for queries or broadcasting transactions.
     tendermint_rpc_url: Url,
 
     /// Block height where we should gracefully stop the node
     pub halt_height: i64,
+    /// Time to wait for the background tasks to stop and the database to be flushed
+    /// after receiving a SIGTERM or SIGINT, before exiting anyway.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    pub shutdown_timeout: Duration,
 
     /// Secp256k1 private key used for signing transactions sent in the validator's name. Leave empty if not validating.
     pub validator_key: Option<SigningKey>,
@@ -301,7 +376,8 @@ impl Settings {
         snapshots_dir,
         contracts_dir,
         builtin_actors_bundle,
-        custom_actors_bundle
+        custom_actors_bundle,
+        upgrades_file
     );
 
     /// Load the default configuration from a directory,
@@ -381,12 +457,17 @@ impl Settings {
 #[serial_test::serial]
 #[cfg(test)]
 mod tests {
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::subnet_id::SubnetID;
     use multiaddr::multiaddr;
     use std::path::PathBuf;
+    use std::str::FromStr;
+    use std::time::Duration;
 
     use crate::utils::tests::with_env_vars;
 
-    use crate::DbCompaction;
+    use crate::{DbCompaction, SettingsError, TopDownSettings};
 
     use super::Settings;
 
@@ -419,6 +500,162 @@ mod tests {
         assert!(settings.resolver_enabled());
     }
 
+    #[test]
+    fn parse_broadcast_gas_overrides() {
+        let settings = parse_config("");
+        assert!(settings.broadcast.gas.checkpoint.is_none());
+
+        let settings = parse_config("test");
+        let checkpoint = settings
+            .broadcast
+            .gas
+            .checkpoint
+            .expect("checkpoint overrides");
+        assert_eq!(checkpoint.gas_overestimation_rate, Some(3.0));
+        assert_eq!(checkpoint.gas_premium, Some(TokenAmount::from_atto(1000)));
+    }
+
+    #[test]
+    fn parse_resolver_subnet_limits() {
+        let settings = parse_config("");
+        assert_eq!(
+            settings.resolver.content.default_subnet_limit.bytes_per_sec,
+            0
+        );
+        assert!(settings.resolver.content.subnet_limits.is_empty());
+
+        let settings = parse_config("test");
+        let subnet_id = SubnetID::from_str("/r31415926/f2xwzbdu7z5sam6hc57xxwkctciuaz7oe5omipwbq")
+            .expect("valid subnet ID");
+        let limit = settings
+            .resolver
+            .content
+            .subnet_limits
+            .get(&subnet_id)
+            .expect("subnet limit");
+        assert_eq!(limit.bytes_per_sec, 1048576);
+        assert_eq!(limit.requests_per_sec, 100);
+    }
+
+    #[test]
+    fn parse_db_tuning() {
+        let settings = parse_config("");
+        assert_eq!(settings.db.block_cache_size_mb, None);
+        assert_eq!(settings.db.write_buffer_size_mb, None);
+        assert_eq!(settings.db.max_open_files, None);
+
+        let settings = parse_config("test");
+        assert_eq!(settings.db.block_cache_size_mb, Some(64));
+        assert_eq!(settings.db.write_buffer_size_mb, Some(128));
+        assert_eq!(settings.db.max_open_files, Some(1024));
+    }
+
+    fn topdown_settings(chain_head_delay: u64, proposal_delay: u64) -> TopDownSettings {
+        TopDownSettings {
+            chain_head_delay,
+            proposal_delay,
+            max_proposal_range: 100,
+            max_cache_blocks: None,
+            require_effects_commitment: false,
+            polling_interval: Duration::from_secs(10),
+            exponential_back_off: Duration::from_secs(5),
+            exponential_retry_limit: 5,
+            parent_http_endpoint: "http://127.0.0.1:8545".parse().unwrap(),
+            parent_http_timeout: None,
+            parent_http_auth_token: None,
+            parent_registry: Address::new_id(100),
+            parent_gateway: Address::new_id(101),
+        }
+    }
+
+    #[test]
+    fn validate_default_config() {
+        parse_config("")
+            .validate()
+            .expect("default config should be valid");
+        parse_config("test")
+            .validate()
+            .expect("test config should be valid");
+    }
+
+    #[test]
+    fn validate_chain_head_delay() {
+        let mut settings = parse_config("");
+        settings.ipc.subnet_id = "/r31415926/f2xwzbdu7z5sam6hc57xxwkctciuaz7oe5omipwbq"
+            .parse()
+            .unwrap();
+        settings.ipc.topdown = Some(topdown_settings(10, 10));
+        settings.validate().expect("equal delays should be valid");
+
+        settings.ipc.topdown = Some(topdown_settings(5, 10));
+        let errors = settings.validate().unwrap_err();
+        assert_eq!(
+            errors,
+            vec![SettingsError::ChainHeadDelayTooSmall {
+                chain_head_delay: 5,
+                proposal_delay: 10
+            }]
+        );
+        assert_eq!(errors[0].key(), "ipc.topdown.chain_head_delay");
+    }
+
+    #[test]
+    fn validate_topdown_on_root() {
+        let mut settings = parse_config("");
+        settings.ipc.topdown = Some(topdown_settings(10, 1));
+        let errors = settings.validate().unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [SettingsError::TopDownOnRootSubnet { .. }]
+        ));
+    }
+
+    #[test]
+    fn validate_snapshot_interval() {
+        let mut settings = parse_config("");
+        settings.snapshots.block_interval = 0;
+        settings.snapshots.enabled = false;
+        settings
+            .validate()
+            .expect("disabled snapshots can have any interval");
+
+        settings.snapshots.enabled = true;
+        let errors = settings.validate().unwrap_err();
+        assert_eq!(errors, vec![SettingsError::ZeroSnapshotInterval]);
+    }
+
+    #[test]
+    fn validate_listen_addresses() {
+        let mut settings = parse_config("");
+        settings.eth.listen = settings.metrics.listen.clone();
+
+        settings.metrics.enabled = false;
+        settings.validate().expect("disabled metrics don't listen");
+
+        settings.metrics.enabled = true;
+        let errors = settings.validate().unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [SettingsError::ListenAddressConflict {
+                first: "eth",
+                second: "metrics",
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn validate_collects_all_errors() {
+        let mut settings = parse_config("");
+        settings.ipc.topdown = Some(topdown_settings(1, 10));
+        settings.snapshots.enabled = true;
+        settings.snapshots.block_interval = 0;
+        settings.eth.listen = settings.abci.listen.clone();
+
+        let errors = settings.validate().unwrap_err();
+        assert_eq!(errors.len(), 4);
+    }
+
     #[test]
     fn compaction_to_string() {
         assert_eq!(DbCompaction::Level.to_string(), "level");
@@ -1,7 +1,7 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::{path::PathBuf, time::Duration};
+use std::{collections::HashMap, path::PathBuf, time::Duration};
 
 use serde::Deserialize;
 use serde_with::{serde_as, DurationSeconds};
@@ -89,6 +89,10 @@ pub struct ConnectionSettings {
     pub expected_peer_count: u32,
     /// Maximum number of peers to send Bitswap requests to in a single attempt.
     pub max_peers_per_query: u32,
+    /// Maximum number of CID resolutions in flight; the rest are queued until one finishes.
+    ///
+    /// 0 means no limit.
+    pub max_concurrent_requests: u32,
     /// Maximum number of events in the push-based broadcast channel before a slow
     /// consumer gets an error because it's falling behind.
     pub event_buffer_capacity: u32,
@@ -107,4 +111,22 @@ pub struct ContentSettings {
     /// 0 means no limit.
     #[serde_as(as = "DurationSeconds<u64>")]
     pub rate_limit_period: Duration,
+    /// Limit on the content served on behalf of subnets which don't appear in `subnet_limits`.
+    #[serde(default)]
+    pub default_subnet_limit: SubnetLimitSettings,
+    /// Limits on the content served on behalf of specific subnets, keyed by subnet ID.
+    #[serde_as(as = "HashMap<IsHumanReadable, _>")]
+    #[serde(default)]
+    pub subnet_limits: HashMap<SubnetID, SubnetLimitSettings>,
+}
+
+/// Limits on the content served on behalf of a subnet.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct SubnetLimitSettings {
+    /// Number of bytes that can be served per second. 0 means no limit.
+    ///
+    /// Blocks larger than this are never served.
+    pub bytes_per_sec: u32,
+    /// Number of block requests that can be served per second. 0 means no limit.
+    pub requests_per_sec: u32,
 }
@@ -0,0 +1,92 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Checks of the relationships between settings which deserialization can't catch.
+
+use fendermint_vm_topdown::BlockHeight;
+
+use crate::{Settings, SocketAddress};
+
+/// A violated rule, with the key paths of the offending settings in the message.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SettingsError {
+    #[error("ipc.topdown.chain_head_delay ({chain_head_delay}) is smaller than ipc.topdown.proposal_delay ({proposal_delay})")]
+    ChainHeadDelayTooSmall {
+        chain_head_delay: BlockHeight,
+        proposal_delay: BlockHeight,
+    },
+    #[error(
+        "ipc.topdown is set, but ipc.subnet_id ({subnet_id}) is a root subnet without a parent"
+    )]
+    TopDownOnRootSubnet { subnet_id: String },
+    #[error("snapshots.block_interval is 0, but snapshots.enabled is true")]
+    ZeroSnapshotInterval,
+    #[error("{first}.listen and {second}.listen are both {address}")]
+    ListenAddressConflict {
+        first: &'static str,
+        second: &'static str,
+        address: String,
+    },
+}
+
+impl SettingsError {
+    /// Key path of the first setting involved in the violation.
+    pub fn key(&self) -> &'static str {
+        match self {
+            SettingsError::ChainHeadDelayTooSmall { .. } => "ipc.topdown.chain_head_delay",
+            SettingsError::TopDownOnRootSubnet { .. } => "ipc.topdown",
+            SettingsError::ZeroSnapshotInterval => "snapshots.block_interval",
+            SettingsError::ListenAddressConflict { first, .. } => first,
+        }
+    }
+}
+
+impl Settings {
+    /// Check the settings for combinations which would only show up as odd behaviour at runtime.
+    ///
+    /// Returns all the violations, not just the first one.
+    pub fn validate(&self) -> Result<(), Vec<SettingsError>> {
+        let mut errors = Vec::new();
+
+        if let Some(ref topdown) = self.ipc.topdown {
+            if topdown.chain_head_delay < topdown.proposal_delay {
+                errors.push(SettingsError::ChainHeadDelayTooSmall {
+                    chain_head_delay: topdown.chain_head_delay,
+                    proposal_delay: topdown.proposal_delay,
+                });
+            }
+            if self.ipc.subnet_id.is_root() {
+                errors.push(SettingsError::TopDownOnRootSubnet {
+                    subnet_id: self.ipc.subnet_id.to_string(),
+                });
+            }
+        }
+
+        if self.snapshots.enabled && self.snapshots.block_interval == 0 {
+            errors.push(SettingsError::ZeroSnapshotInterval);
+        }
+
+        let mut listeners: Vec<(&'static str, &SocketAddress)> =
+            vec![("abci", &self.abci.listen), ("eth", &self.eth.listen)];
+        if self.metrics.enabled {
+            listeners.push(("metrics", &self.metrics.listen));
+        }
+        for (i, (first, a)) in listeners.iter().enumerate() {
+            for (second, b) in listeners.iter().skip(i + 1) {
+                if a.host == b.host && a.port == b.port {
+                    errors.push(SettingsError::ListenAddressConflict {
+                        first,
+                        second,
+                        address: a.to_string(),
+                    });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
@@ -4,7 +4,9 @@ use std::future::Future;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use crate::events::{ExtendVote, NewBlock, ProposalProcessed};
+use crate::events::{ExtendVote, NewBlock, ProposalProcessed, StateHistoryPruned};
+use crate::migrations::{app_migrations, SchemaVersion};
+use crate::prune;
 use crate::AppExitCode;
 use crate::BlockHeight;
 use crate::{tmconv::*, VERSION};
@@ -28,19 +30,23 @@ use fendermint_vm_interpreter::chain::{
     ChainMessageApplyRet, IllegalMessage,
 };
 use fendermint_vm_interpreter::fvm::extend::{SignatureKind, SignedTags, TagKind, Tags};
-use fendermint_vm_interpreter::fvm::state::cetf::get_tag_at_height;
+use fendermint_vm_interpreter::fvm::state::cetf::get_tags_at_height;
+use fendermint_vm_interpreter::fvm::state::ipc::GatewayCaller;
 use fendermint_vm_interpreter::fvm::state::{
     empty_state_tree, CheckStateRef, FvmExecState, FvmGenesisState, FvmQueryState, FvmStateParams,
     FvmUpdatableParams,
 };
 use fendermint_vm_interpreter::fvm::store::ReadOnlyBlockstore;
+use fendermint_vm_interpreter::fvm::upgrades::{ExecutedUpgrade, UpgradeFile, UpgradeHistory};
 use fendermint_vm_interpreter::fvm::{FvmApplyRet, FvmGenesisOutput, PowerUpdates};
 use fendermint_vm_interpreter::signed::InvalidSignature;
 use fendermint_vm_interpreter::{
     CheckInterpreter, ExecInterpreter, ExtendVoteInterpreter, GenesisInterpreter,
     ProposalInterpreter, QueryInterpreter,
 };
-use fendermint_vm_message::query::FvmQueryHeight;
+use fendermint_vm_message::query::{
+    FvmQueryHeight, ParentBlockView, TopDownView, TOPDOWN_VIEW_PATH,
+};
 use fendermint_vm_snapshot::{SnapshotClient, SnapshotError};
 use fvm::engine::MultiEngine;
 use fvm_ipld_blockstore::Blockstore;
@@ -58,6 +64,8 @@ use tracing::instrument;
 #[repr(u8)]
 pub enum AppStoreKey {
     State,
+    SchemaVersion,
+    Upgrades,
 }
 
 // TODO: What range should we use for our own error codes? Should we shift FVM errors?
@@ -78,14 +86,25 @@ pub enum AppError {
 #[derive(Serialize, Deserialize)]
 pub struct AppState {
     /// Last committed block height.
-    block_height: BlockHeight,
+    pub(crate) block_height: BlockHeight,
     /// Oldest state hash height.
-    oldest_state_height: BlockHeight,
+    ///
+    /// No longer maintained since the state history is pruned in the background;
+    /// kept for the sake of the serialization format.
+    pub(crate) oldest_state_height: BlockHeight,
     /// Last committed version of the evolving state of the FVM.
-    state_params: FvmStateParams,
+    pub(crate) state_params: FvmStateParams,
 }
 
 impl AppState {
+    pub fn block_height(&self) -> BlockHeight {
+        self.block_height
+    }
+
+    pub fn state_params(&self) -> &FvmStateParams {
+        &self.state_params
+    }
+
     pub fn state_root(&self) -> Cid {
         self.state_params.state_root
     }
@@ -106,6 +125,8 @@ impl AppState {
 }
 
 pub struct AppConfig<S: KVStore> {
+    /// Namespace to store the schema version of the data.
+    pub schema_namespace: S::Namespace,
     /// Namespace to store the current app state.
     pub app_namespace: S::Namespace,
     /// Namespace to store the app state history.
@@ -120,6 +141,8 @@ pub struct AppConfig<S: KVStore> {
     pub custom_actors_bundle: PathBuf,
     /// Block height where we should gracefully stop the node
     pub halt_height: i64,
+    /// Upgrades scheduled from a file, which are checked against the ones already executed.
+    pub upgrade_file: Option<UpgradeFile>,
 }
 
 /// Handle ABCI requests.
@@ -170,10 +193,15 @@ where
     exec_state: Arc<tokio::sync::Mutex<Option<FvmExecState<SS>>>>,
     /// Projected (partial) state accumulating during transaction checks.
     check_state: CheckStateRef<SS>,
+    /// Held while a block is being executed; set to `true` when the node is shutting down,
+    /// after which no more blocks are executed.
+    block_gate: Arc<tokio::sync::Mutex<bool>>,
     /// How much history to keep.
     ///
     /// Zero means unlimited.
     state_hist_size: u64,
+    /// Upgrades scheduled from a file, checked against the chain ID and the executed upgrades.
+    upgrade_file: Option<UpgradeFile>,
 }
 
 impl<DB, SS, S, I> App<DB, SS, S, I>
@@ -182,7 +210,10 @@ where
         + Codec<AppState>
         + Encode<AppStoreKey>
         + Encode<BlockHeight>
-        + Codec<FvmStateParams>,
+        + Codec<FvmStateParams>
+        + Codec<SchemaVersion>
+        + Codec<UpgradeHistory>
+        + 'static,
     DB: KVWritable<S> + KVReadable<S> + Clone + 'static,
     SS: Blockstore + Clone + 'static,
 {
@@ -194,6 +225,10 @@ where
         chain_env: ChainEnv,
         snapshots: Option<SnapshotClient>,
     ) -> Result<Self> {
+        app_migrations(config.schema_namespace, config.app_namespace.clone())
+            .run(&db)
+            .context("failed to migrate the database")?;
+
         let app = Self {
             db: Arc::new(db),
             state_store: Arc::new(state_store),
@@ -209,10 +244,33 @@ where
             snapshots,
             exec_state: Arc::new(tokio::sync::Mutex::new(None)),
             check_state: Arc::new(tokio::sync::Mutex::new(None)),
+            block_gate: Arc::new(tokio::sync::Mutex::new(false)),
+            upgrade_file: config.upgrade_file,
         };
         app.init_committed_state()?;
+
+        if let Some(ref file) = app.upgrade_file {
+            let state = app.committed_state()?;
+            // Before genesis the chain ID is unknown; it's checked in `init_chain` instead.
+            if state.state_params.chain_id != 0 {
+                file.check_chain_id(state.chain_id())
+                    .context("invalid upgrade schedule")?;
+            }
+            let history = app.upgrade_history()?;
+            file.check_pending(state.block_height, &history)
+                .context("invalid upgrade schedule")?;
+        }
+
         Ok(app)
     }
+
+    /// Wait for the block being executed, if any, to be committed to the database,
+    /// then refuse to execute any further blocks.
+    ///
+    /// Used during shutdown; CometBFT replays the blocks we didn't execute after a restart.
+    pub async fn stop_executing_blocks(&self) {
+        *self.block_gate.lock().await = true;
+    }
 }
 
 impl<DB, SS, S, I> App<DB, SS, S, I>
@@ -221,7 +279,8 @@ where
         + Codec<AppState>
         + Encode<AppStoreKey>
         + Encode<BlockHeight>
-        + Codec<FvmStateParams>,
+        + Codec<FvmStateParams>
+        + Codec<UpgradeHistory>,
     DB: KVWritable<S> + KVReadable<S> + 'static + Clone,
     SS: Blockstore + 'static + Clone,
 {
@@ -263,6 +322,28 @@ where
             .context("get failed")
     }
 
+    /// Get the record of the upgrades executed and committed so far, if exists.
+    fn get_upgrade_history(&self) -> Result<Option<UpgradeHistory>> {
+        let tx = self.db.read();
+        tx.get(&self.namespace, &AppStoreKey::Upgrades)
+            .context("get failed")
+    }
+
+    /// Get the record of the upgrades executed and committed so far.
+    fn upgrade_history(&self) -> Result<UpgradeHistory> {
+        Ok(self.get_upgrade_history()?.unwrap_or_default())
+    }
+
+    /// Replace the record of the executed upgrades.
+    fn set_upgrade_history(&self, history: UpgradeHistory) -> Result<()> {
+        self.db
+            .with_write(|tx| {
+                tx.put(&self.namespace, &AppStoreKey::Upgrades, &history)?;
+                Ok(())
+            })
+            .context("put failed")
+    }
+
     /// Get the last committed state; return error if it doesn't exist.
     fn committed_state(&self) -> Result<AppState> {
         match self.get_committed_state()? {
@@ -272,7 +353,16 @@ where
     }
 
     /// Set the last committed state.
-    fn set_committed_state(&self, mut state: AppState) -> Result<()> {
+    fn set_committed_state(&self, state: AppState) -> Result<()> {
+        self.set_committed_state_with_upgrade(state, None)
+    }
+
+    /// Set the last committed state, along with the upgrade the scheduler executed in the block.
+    fn set_committed_state_with_upgrade(
+        &self,
+        state: AppState,
+        executed_upgrade: Option<&str>,
+    ) -> Result<()> {
         self.db
             .with_write(|tx| {
                 // Insert latest state history point at the `block_height + 1`,
@@ -282,18 +372,31 @@ where
                 self.state_hist
                     .put(tx, &state_height, &state.state_params)?;
 
-                // Prune state history.
-                if self.state_hist_size > 0 && state_height >= self.state_hist_size {
-                    let prune_height = state_height.saturating_sub(self.state_hist_size);
-                    while state.oldest_state_height <= prune_height {
-                        self.state_hist.delete(tx, &state.oldest_state_height)?;
-                        state.oldest_state_height += 1;
-                    }
-                }
+                // The state history is pruned by `prune_state_history`, in the background.
 
                 // Update the application state.
                 tx.put(&self.namespace, &AppStoreKey::State, &state)?;
 
+                // Record the upgrade executed at this height, which is now part of the state.
+                // Replaying the same height doesn't add it again.
+                if let Some(name) = executed_upgrade {
+                    let mut history: UpgradeHistory = tx
+                        .get(&self.namespace, &AppStoreKey::Upgrades)?
+                        .unwrap_or_default();
+
+                    if !history
+                        .executed
+                        .iter()
+                        .any(|e| e.block_height == state.block_height)
+                    {
+                        history.executed.push(ExecutedUpgrade {
+                            name: name.to_string(),
+                            block_height: state.block_height,
+                        });
+                        tx.put(&self.namespace, &AppStoreKey::Upgrades, &history)?;
+                    }
+                }
+
                 Ok(())
             })
             .context("commit failed")
@@ -393,6 +496,146 @@ where
         // It's really the empty state tree that would be the best indicator.
         !(height == 0 && params.timestamp.0 == 0 && params.network_version == NetworkVersion::V0)
     }
+
+    /// Answer the query about the view of the parent chain cached by the top-down finality provider.
+    async fn query_topdown_view(&self, data: &[u8]) -> Result<response::Query> {
+        let height: Option<u64> = match from_slice(data) {
+            Ok(height) => height,
+            Err(e) => {
+                return Ok(invalid_query(
+                    AppError::InvalidEncoding,
+                    format!("failed to decode parent height: {e}"),
+                ))
+            }
+        };
+
+        let provider = &self.chain_env.parent_finality_provider;
+
+        let (committed_finality, committed_block, cached_heights, block) = atomically(|| {
+            let committed_finality = provider.last_committed_finality()?;
+            // The block of the committed finality stays in the cache until the next one.
+            let committed_block = match committed_finality {
+                Some(ref f) => provider.cached_block(f.height)?,
+                None => None,
+            };
+            let block = match height {
+                Some(h) => provider.cached_block(h)?,
+                None => None,
+            };
+            Ok((
+                committed_finality,
+                committed_block,
+                provider.cached_heights()?,
+                block,
+            ))
+        })
+        .await;
+
+        let view = TopDownView {
+            enabled: provider.is_enabled(),
+            committed_finality: committed_finality.map(|f| ParentBlockView {
+                height: f.height,
+                block_hash: Some(f.block_hash),
+                top_down_msgs: committed_block.as_ref().map(|b| b.top_down_msgs),
+                validator_changes: committed_block.as_ref().map(|b| b.validator_changes),
+            }),
+            cached_heights,
+            block: block.map(|b| ParentBlockView {
+                height: b.height,
+                block_hash: b.block_hash,
+                top_down_msgs: Some(b.top_down_msgs),
+                validator_changes: Some(b.validator_changes),
+            }),
+        };
+
+        let value = to_vec(&view).context("failed to encode top-down view")?;
+
+        Ok(response::Query {
+            value: value.into(),
+            ..Default::default()
+        })
+    }
+}
+
+impl<DB, SS, S, I> App<DB, SS, S, I>
+where
+    S: KVStore
+        + Codec<AppState>
+        + Encode<AppStoreKey>
+        + Codec<BlockHeight>
+        + Codec<FvmStateParams>
+        + Codec<UpgradeHistory>,
+    S::Repr: Ord + 'static,
+    DB: KVWritable<S> + KVReadable<S> + 'static + Clone,
+    SS: Blockstore + 'static + Clone,
+{
+    /// Delete the state history older than `state_hist_size` blocks, but never
+    /// the states of the newest snapshot or the latest bottom-up checkpoint.
+    ///
+    /// Returns the lowest retained height and the number of deleted entries,
+    /// or `None` if the history is unlimited.
+    pub async fn prune_state_history(&self) -> Result<Option<(BlockHeight, usize)>> {
+        if self.state_hist_size == 0 {
+            return Ok(None);
+        }
+
+        let state = self.committed_state()?;
+        let mut protected = Vec::new();
+
+        if let Some(ref snapshots) = self.snapshots {
+            let snapshots = atomically(|| snapshots.list_snapshots()).await;
+            protected.extend(snapshots.iter().map(|s| s.manifest.block_height).max());
+        }
+
+        protected.extend(
+            self.checkpoint_height(state.block_height)
+                .context("failed to get the bottom-up checkpoint height")?,
+        );
+
+        let Some(retain_height) =
+            prune::retain_height(state.state_height(), self.state_hist_size, protected)
+        else {
+            return Ok(None);
+        };
+
+        let num_pruned =
+            prune::prune_state_history(self.db.as_ref(), &self.state_hist, retain_height)
+                .context("failed to prune state history")?;
+
+        emit!(StateHistoryPruned {
+            retain_height,
+            num_pruned
+        });
+
+        Ok(Some((retain_height, num_pruned)))
+    }
+
+    /// The lowest of the latest bottom-up checkpoint height and the heights
+    /// of the checkpoints still waiting for signatures, if IPC is enabled.
+    fn checkpoint_height(&self, block_height: BlockHeight) -> Result<Option<BlockHeight>> {
+        let Some(mut exec_state) = self.new_read_only_exec_state()? else {
+            return Ok(None);
+        };
+
+        let gateway = GatewayCaller::default();
+        if !gateway.enabled(&mut exec_state)? {
+            return Ok(None);
+        }
+
+        let period = gateway.bottom_up_check_period(&mut exec_state)?;
+        if period == 0 {
+            return Ok(None);
+        }
+        let latest = block_height - block_height % period;
+
+        let lowest_incomplete = gateway
+            .incomplete_checkpoints(&mut exec_state)?
+            .iter()
+            .map(|cp| cp.block_height.as_u64())
+            .min();
+
+        Ok(Some(lowest_incomplete.map_or(latest, |h| h.min(latest))))
+    }
 }
 // NOTE: The `Application` interface doesn't allow failures at the moment. The protobuf
 // of `Response` actually has an `Exception` type, so in theory we could use that, and
@@ -406,7 +649,8 @@ where
         + Codec<AppState>
         + Encode<AppStoreKey>
         + Encode<BlockHeight>
-        + Codec<FvmStateParams>,
+        + Codec<FvmStateParams>
+        + Codec<UpgradeHistory>,
     S::Namespace: Sync + Send,
     DB: KVWritable<S> + KVReadable<S> + Clone + Send + Sync + 'static,
     SS: Blockstore + Clone + Send + Sync + 'static,
@@ -474,19 +718,16 @@ where
         let db = self.state_store_clone();
 
         let tags = Tags({
-            // Check for cetf tag
+            // Check for cetf tags
             let mut tags = vec![];
 
             // We haven't started execution yet so TM height is one ahead of FVM height.
             let fvm_tags_height = block_height + 2;
 
-            let cetf_tag = get_tag_at_height(db, &state_root, block_height + 2)
-                .context(format!("failed to get tag at height {}", block_height + 2))?;
-
-            if let Some(tag) = cetf_tag {
-                tags.push(TagKind::Cetf(tag));
-            }
+            let cetf_tags = get_tags_at_height(db, &state_root, fvm_tags_height)
+                .context(format!("failed to get tags at height {}", fvm_tags_height))?;
 
+            tags.extend(cetf_tags.into_iter().map(TagKind::Cetf));
             tags.push(TagKind::BlockHeight(fvm_tags_height));
             tags
         });
@@ -555,22 +796,36 @@ where
             from_slice(&request.vote_extension).context("failed to deserialize signatures")?;
         let fvm_tags_height = block_height + 2;
 
-        let tags = Tags(
-            sigs.0
-                .iter()
-                .map(|sig_kind| match sig_kind {
-                    SignatureKind::Cetf(_) => {
-                        let db = db.clone();
-                        let tag = get_tag_at_height(db, &state_root, block_height + 2)
-                            .context("failed to get tag at height")?
-                            .ok_or_else(|| anyhow!("failed to get tag at height: None"))?;
-
-                        Ok(TagKind::Cetf(tag))
+        // The CETF signatures are in the same order as the tags scheduled at the height.
+        let mut cetf_tags = get_tags_at_height(db, &state_root, fvm_tags_height)
+            .context("failed to get tags at height")?
+            .into_iter();
+
+        let mut tags = Vec::new();
+        for sig_kind in sigs.0.iter() {
+            match sig_kind {
+                SignatureKind::Cetf(_) => match cetf_tags.next() {
+                    Some(tag) => tags.push(TagKind::Cetf(tag)),
+                    None => {
+                        tracing::warn!(
+                            height = fvm_tags_height,
+                            "vote extension has more signatures than tags"
+                        );
+                        return Ok(response::VerifyVoteExtension::Reject);
                     }
-                    SignatureKind::BlockHeight(_) => Ok(TagKind::BlockHeight(fvm_tags_height)),
-                })
-                .collect::<anyhow::Result<Vec<TagKind>>>()?,
-        );
+                },
+                SignatureKind::BlockHeight(_) => tags.push(TagKind::BlockHeight(fvm_tags_height)),
+            }
+        }
+        // All the tags scheduled at the height have to be signed.
+        if cetf_tags.next().is_some() {
+            tracing::warn!(
+                height = fvm_tags_height,
+                "vote extension is missing tag signatures"
+            );
+            return Ok(response::VerifyVoteExtension::Reject);
+        }
+        let tags = Tags(tags);
 
         let id = request.validator_address;
 
@@ -640,6 +895,11 @@ where
             .await
             .context("failed to init from genesis")?;
 
+        if let Some(ref file) = self.upgrade_file {
+            file.check_chain_id(out.chain_id)
+                .context("invalid upgrade schedule")?;
+        }
+
         let state_root = state.commit().context("failed to commit genesis state")?;
         let validators =
             to_validator_updates(out.validators).context("failed to convert validators")?;
@@ -694,6 +954,11 @@ where
     /// Query the application for data at the current or past height.
     #[instrument(skip(self))]
     async fn query(&self, request: request::Query) -> AbciResult<response::Query> {
+        // Node local queries which don't need the state.
+        if request.path == TOPDOWN_VIEW_PATH {
+            return Ok(self.query_topdown_view(&request.data).await?);
+        }
+
         let db = self.state_store_clone();
         let height = FvmQueryHeight::from(request.height.value());
         let (state_params, block_height) = self.state_params_at_height(height)?;
@@ -812,16 +1077,28 @@ where
                             from_slice::<SignedTags>(&vote.vote_extension).unwrap().0
                         }
                     })
-                    .flatten()
                     .collect::<Vec<_>>();
 
-                let cetf_sigs = votes
-                    .iter()
-                    .filter(|t| matches!(t, SignatureKind::Cetf(_)))
-                    .map(SignatureKind::as_slice)
-                    .map(bls_signatures::Signature::from_bytes)
-                    .collect::<Result<Vec<_>, bls_signatures::Error>>()
-                    .unwrap();
+                // Group the signatures of each validator by the position of the tag they signed.
+                let mut cetf_sigs: Vec<Vec<bls_signatures::Signature>> = Vec::new();
+                for vote in votes.iter() {
+                    let sigs = vote
+                        .iter()
+                        .filter(|t| matches!(t, SignatureKind::Cetf(_)))
+                        .map(SignatureKind::as_slice)
+                        .map(bls_signatures::Signature::from_bytes)
+                        .collect::<Result<Vec<_>, bls_signatures::Error>>()
+                        .unwrap();
+
+                    for (i, sig) in sigs.into_iter().enumerate() {
+                        if cetf_sigs.len() <= i {
+                            cetf_sigs.push(Vec::new());
+                        }
+                        cetf_sigs[i].push(sig);
+                    }
+                }
+                let votes = votes.into_iter().flatten().collect::<Vec<_>>();
+
                 let height_sigs = votes
                     .iter()
                     .filter(|t| matches!(t, SignatureKind::BlockHeight(_)))
@@ -830,11 +1107,10 @@ where
                     .collect::<Result<Vec<_>, bls_signatures::Error>>()
                     .unwrap();
 
-                let agg_cetf_sig = if !cetf_sigs.is_empty() {
-                    Some(bls_signatures::aggregate(&cetf_sigs).unwrap())
-                } else {
-                    None
-                };
+                let agg_cetf_sigs = cetf_sigs
+                    .iter()
+                    .map(|sigs| bls_signatures::aggregate(sigs).unwrap())
+                    .collect::<Vec<_>>();
                 let agg_height_sig = if !height_sigs.is_empty() {
                     Some(bls_signatures::aggregate(&height_sigs).unwrap())
                 } else {
@@ -843,17 +1119,20 @@ where
 
                 tracing::debug!(
                     r#"prepare proposal signature aggregation result (TM Height: {})): 
-                    agg_cetf_sig: {:?}
+                    agg_cetf_sigs: {:?}
                     agg_height_sig: {:?}
                     "#,
                     request.height.value(),
-                    agg_cetf_sig.as_ref().map(|s| s.as_bytes()),
+                    agg_cetf_sigs
+                        .iter()
+                        .map(|s| s.as_bytes())
+                        .collect::<Vec<_>>(),
                     agg_height_sig.as_ref().map(|s| s.as_bytes()),
                 );
-                if let Some(agg_cetf) = agg_cetf_sig {
+                if !agg_cetf_sigs.is_empty() {
                     cetf_tx.push(cetf_tag_msg_to_chainmessage(&(
                         request.height.value(),
-                        agg_cetf,
+                        agg_cetf_sigs,
                     ))?);
                 };
                 // if let Some(agg_height) = agg_height_sig {
@@ -931,6 +1210,17 @@ where
             "finalize block"
         );
 
+        // Hold the gate until the block is committed, so a shutdown doesn't interrupt it halfway.
+        let block_gate = self.block_gate.lock().await;
+        if *block_gate {
+            drop(block_gate);
+            tracing::info!(
+                height = request.height.value(),
+                "not executing block while shutting down"
+            );
+            return std::future::pending().await;
+        }
+
         // BeginBlock
         let block_height = request.height.into();
         let block_hash = match request.hash {
@@ -1013,6 +1303,7 @@ where
             .context("end failed")?;
 
         let exec_state = self.take_exec_state().await;
+        let executed_upgrade = exec_state.executed_upgrade().map(|name| name.to_string());
 
         // TODO: This is technically "right" but I think we actually wanna do all this stuff in `commit`.
         // The "issue" is that we need to know the app_hash before `commit`. But we can't actually get that
@@ -1051,7 +1342,7 @@ where
         );
 
         // Commit app state to the datastore.
-        self.set_committed_state(state)?;
+        self.set_committed_state_with_upgrade(state, executed_upgrade.as_deref())?;
 
         Ok(to_finalize_block(ret, tx_results, power_table, app_hash)
             .context("finalize block failed")?)
@@ -1253,6 +1544,12 @@ where
                         state.state_params = snapshot.manifest.state_params;
                         self.set_committed_state(state)?;
 
+                        // The upgrades before the snapshot were executed by other nodes.
+                        self.set_upgrade_history(UpgradeHistory {
+                            since: snapshot.manifest.block_height,
+                            executed: Vec::new(),
+                        })?;
+
                         // TODO: We can remove the `current_download` from the STM
                         // state here which would cause it to get dropped from /tmp,
                         // but for now let's keep it just in case we need to investigate
@@ -0,0 +1,71 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use fendermint_app_options::check::CheckArgs;
+use fendermint_eth_hardhat::Hardhat;
+use fendermint_vm_actor_interface::{diamond, ipc::IPC_CONTRACTS};
+use fvm_ipld_blockstore::MemoryBlockstore;
+use fvm_ipld_car::load_car_unchecked;
+
+use crate::{
+    cmd::{self, ConfigError},
+    settings::Settings,
+};
+
+cmd! {
+  CheckArgs(self, settings) {
+    check_files(&settings).await.context(ConfigError)?;
+    println!("settings are valid");
+    Ok(())
+  }
+}
+
+/// Check that the files the settings point at exist and can be parsed.
+///
+/// Returns the first problem found.
+async fn check_files(settings: &Settings) -> anyhow::Result<()> {
+    check_bundle(&settings.builtin_actors_bundle())
+        .await
+        .context("invalid builtin_actors_bundle")?;
+
+    check_bundle(&settings.custom_actors_bundle())
+        .await
+        .context("invalid custom_actors_bundle")?;
+
+    check_contracts(&settings.contracts_dir()).context("invalid contracts_dir")?;
+
+    Ok(())
+}
+
+/// Load an actor bundle into memory to see that it's a CAR file with at least one root.
+async fn check_bundle(path: &Path) -> anyhow::Result<()> {
+    let bundle = tokio::fs::read(path)
+        .await
+        .with_context(|| format!("failed to read {path:?}"))?;
+
+    let roots = load_car_unchecked(&MemoryBlockstore::new(), bundle.as_slice())
+        .await
+        .with_context(|| format!("failed to load CAR file {path:?}"))?;
+
+    if roots.is_empty() {
+        bail!("CAR file {path:?} has no roots");
+    }
+
+    Ok(())
+}
+
+/// Parse the Hardhat artifacts of the IPC contracts deployed at genesis, and all their libraries.
+fn check_contracts(path: &Path) -> anyhow::Result<()> {
+    if !path.is_dir() {
+        bail!("{path:?} is not a directory");
+    }
+
+    let hardhat = Hardhat::new(path.to_path_buf());
+
+    diamond::eth_libraries(&hardhat, &IPC_CONTRACTS)?;
+
+    Ok(())
+}
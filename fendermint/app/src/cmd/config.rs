@@ -1,13 +1,16 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use fendermint_app_options::config::ConfigArgs;
+use fendermint_app_options::config::{ConfigArgs, ConfigCommands};
 
 use crate::{cmd, settings::Settings};
 
 cmd! {
   ConfigArgs(self, settings) {
-    print_settings(settings)
+    match &self.command {
+      None => print_settings(settings),
+      Some(ConfigCommands::Validate) => validate_settings(settings),
+    }
   }
 }
 
@@ -17,3 +20,9 @@ fn print_settings(settings: Settings) -> anyhow::Result<()> {
     println!("{settings:?}");
     Ok(())
 }
+
+fn validate_settings(settings: Settings) -> anyhow::Result<()> {
+    cmd::validate(&settings)?;
+    println!("settings are valid");
+    Ok(())
+}
@@ -1,10 +1,22 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use anyhow::{anyhow, Context};
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context};
+use fendermint_app::{migrations::app_migrations, AppState, AppStore, AppStoreKey};
 use fendermint_app_options::debug::{
-    DebugArgs, DebugCommands, DebugExportTopDownEventsArgs, DebugIpcCommands,
+    DebugArgs, DebugCommands, DebugDbCommands, DebugExportTopDownEventsArgs, DebugIpcCommands,
+    DebugStateCommands, DebugTopDownCommands, DebugUpgradesCommands,
 };
+use fendermint_app_settings::utils::expand_tilde;
+use fendermint_rocksdb::{blockstore::NamespaceBlockstore, RocksDb, RocksDbConfig};
+use fendermint_rpc::{client::FendermintClient, query::QueryClient};
+use fendermint_storage::{KVRead, KVReadable};
+use fendermint_vm_interpreter::fvm::state::balances::sum_balances;
+use fendermint_vm_interpreter::fvm::store::memory::MemoryBlockstore;
+use fendermint_vm_interpreter::fvm::upgrades::{MigrationRegistry, UpgradeFile, UpgradeScheduler};
+use fendermint_vm_message::query::ParentBlockView;
 use fendermint_vm_topdown::proxy::IPCProviderProxy;
 use ipc_provider::{
     config::subnet::{EVMSubnet, SubnetConfig},
@@ -13,10 +25,16 @@ use ipc_provider::{
 
 use crate::cmd;
 
+use super::run::Namespaces;
+
 cmd! {
   DebugArgs(self) {
     match &self.command {
         DebugCommands::Ipc { command } => command.exec(()).await,
+        DebugCommands::Db { command } => command.exec(()).await,
+        DebugCommands::Topdown { command } => command.exec(()).await,
+        DebugCommands::State { command } => command.exec(()).await,
+        DebugCommands::Upgrades { command } => command.exec(()).await,
     }
   }
 }
@@ -30,6 +48,185 @@ cmd! {
   }
 }
 
+cmd! {
+  DebugDbCommands(self) {
+    match self {
+        DebugDbCommands::Version { data_dir } => print_db_version(data_dir),
+        DebugDbCommands::Stats { data_dir } => print_db_stats(data_dir),
+    }
+  }
+}
+
+cmd! {
+  DebugTopDownCommands(self) {
+    match self {
+        DebugTopDownCommands::View { url, proxy_url, height } =>
+            print_topdown_view(url.clone(), proxy_url.clone(), *height).await,
+    }
+  }
+}
+
+cmd! {
+  DebugStateCommands(self) {
+    match self {
+        DebugStateCommands::Balances { data_dir } => print_state_balances(data_dir),
+    }
+  }
+}
+
+cmd! {
+  DebugUpgradesCommands(self) {
+    match self {
+        DebugUpgradesCommands::Schedule { upgrades_file, height } =>
+            print_upgrade_schedule(upgrades_file, *height),
+    }
+  }
+}
+
+/// Open an existing database in the data directory.
+fn open_existing_db(data_dir: &Path) -> anyhow::Result<RocksDb> {
+    let path = expand_tilde(data_dir).join("rocksdb");
+    let config = RocksDbConfig {
+        create_if_missing: false,
+        ..Default::default()
+    };
+    RocksDb::open(&path, &config).with_context(|| format!("failed to open database at {path:?}"))
+}
+
+fn print_db_version(data_dir: &Path) -> anyhow::Result<()> {
+    let db = open_existing_db(data_dir)?;
+
+    let ns = Namespaces::default();
+
+    // Databases created before the schema was versioned don't have its namespace,
+    // which is only created when the node starts with a newer binary.
+    let has_schema = db.has_cf_handle(&ns.schema);
+
+    let migrations = app_migrations::<AppStore, _>(ns.schema, ns.app);
+    let latest = migrations.latest_version();
+
+    if has_schema {
+        let current = migrations.current_version(&db)?;
+        println!("current: {current}");
+    } else {
+        println!("current: 0 (pre-schema)");
+    }
+    println!("latest: {latest}");
+
+    Ok(())
+}
+
+fn print_db_stats(data_dir: &Path) -> anyhow::Result<()> {
+    let db = open_existing_db(data_dir)?;
+    let stats = db
+        .namespace_stats()
+        .context("failed to get namespace stats")?;
+
+    for (ns, size) in stats.iter() {
+        println!("{ns}: {size}");
+    }
+    println!("total: {}", stats.values().sum::<u64>());
+
+    Ok(())
+}
+
+async fn print_topdown_view(
+    url: tendermint_rpc::Url,
+    proxy_url: Option<tendermint_rpc::Url>,
+    height: Option<u64>,
+) -> anyhow::Result<()> {
+    let client = FendermintClient::new_http(url, proxy_url)?;
+    let view = client.topdown_view(height).await?;
+
+    let block_json = |b: &ParentBlockView| {
+        serde_json::json!({
+            "height": b.height,
+            "block_hash": b.block_hash.as_ref().map(hex::encode),
+            "top_down_msgs": b.top_down_msgs,
+            "validator_changes": b.validator_changes,
+        })
+    };
+
+    let json = serde_json::json!({
+        "enabled": view.enabled,
+        "committed_finality": view.committed_finality.as_ref().map(block_json),
+        "cached_heights": view.cached_heights.map(|(lo, hi)| serde_json::json!({
+            "lowest": lo,
+            "highest": hi,
+        })),
+        "block": view.block.as_ref().map(block_json),
+    });
+
+    println!("{}", serde_json::to_string_pretty(&json)?);
+
+    Ok(())
+}
+
+fn print_state_balances(data_dir: &Path) -> anyhow::Result<()> {
+    let db = open_existing_db(data_dir)?;
+    let ns = Namespaces::default();
+
+    let state: AppState = KVReadable::<AppStore>::read(&db)
+        .get(&ns.app, &AppStoreKey::State)
+        .context("failed to read the app state")?
+        .ok_or_else(|| anyhow!("app state not found"))?;
+
+    let store = NamespaceBlockstore::new(db, ns.state_store).context("error creating state DB")?;
+    let totals = sum_balances(store, &state.state_root())?;
+
+    let json = serde_json::json!({
+        "height": state.block_height(),
+        "circ_supply": state.state_params().circ_supply.to_string(),
+        "balances": totals.balances.to_string(),
+        "burnt": totals.burnt.to_string(),
+    });
+
+    println!("{}", serde_json::to_string_pretty(&json)?);
+
+    Ok(())
+}
+
+fn print_upgrade_schedule(upgrades_file: &Path, height: Option<u64>) -> anyhow::Result<()> {
+    let path = expand_tilde(upgrades_file);
+    let file =
+        UpgradeFile::load(&path)?.ok_or_else(|| anyhow!("upgrades file not found at {path:?}"))?;
+
+    // Schedule the upgrades the same way `run` does, so unknown migrations are caught here.
+    let mut scheduler = UpgradeScheduler::<MemoryBlockstore>::new();
+    scheduler
+        .add_file(&file, &MigrationRegistry::new())
+        .context("failed to schedule upgrades")?;
+
+    let upgrades = scheduler
+        .scheduled_upgrades()
+        .into_iter()
+        .filter_map(|(chain_id, height)| {
+            let upgrade = scheduler.upgrade_at(chain_id, height)?;
+            Some(serde_json::json!({
+                "chain_id": u64::from(chain_id),
+                "height": height,
+                "name": upgrade.name(),
+                "app_version": upgrade.new_app_version(),
+            }))
+        })
+        .collect::<Vec<_>>();
+
+    let json = serde_json::json!({
+        "chain_name": file.chain_name,
+        "upgrades": upgrades,
+    });
+
+    println!("{}", serde_json::to_string_pretty(&json)?);
+
+    if let Some(height) = height {
+        if scheduler.upgrade_at(file.chain_id()?, height).is_none() {
+            bail!("no upgrade is scheduled at height {height}");
+        }
+    }
+
+    Ok(())
+}
+
 async fn export_topdown_events(args: &DebugExportTopDownEventsArgs) -> anyhow::Result<()> {
     // Configuration for the child subnet on the parent network,
     // based on how it's done in `run.rs` and the `genesis ipc from-parent` command.
@@ -42,10 +239,12 @@ async fn export_topdown_events(args: &DebugExportTopDownEventsArgs) -> anyhow::R
                 .ok_or_else(|| anyhow!("subnet is not a child"))?,
             config: SubnetConfig::Fevm(EVMSubnet {
                 provider_http: args.parent_endpoint.clone(),
+                provider_ws: None,
                 provider_timeout: None,
                 auth_token: args.parent_auth_token.clone(),
                 registry_addr: args.parent_registry,
                 gateway_addr: args.parent_gateway,
+                checkpoint_submission: None,
             }),
         },
     )?;
@@ -4,24 +4,33 @@
 use std::time::Duration;
 
 use anyhow::Context;
-use fendermint_eth_api::HybridClient;
+use fendermint_app::shutdown::{shutdown_signal, ShutdownTimeout};
+use fendermint_eth_api::{HybridClient, ReconnectOpt};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
 
 use crate::{
     cmd,
     options::eth::{EthArgs, EthCommands},
-    settings::eth::EthSettings,
+    settings::{eth::EthSettings, Settings},
 };
 
 cmd! {
-  EthArgs(self, settings: EthSettings) {
+  EthArgs(self, settings) {
     match self.command.clone() {
-      EthCommands::Run { ws_url, http_url, connect_retry_delay } => {
+      EthCommands::Run { ws_url, http_url, connect_retry_delay, connect_max_retry_delay, connect_max_retries } => {
 
-        let (client, driver) = HybridClient::new(http_url, ws_url, Duration::from_secs(connect_retry_delay)).context("failed to create HybridClient")?;
+        let reconnect_opt = ReconnectOpt {
+          initial_delay: Duration::from_secs(connect_retry_delay),
+          max_delay: Duration::from_secs(connect_max_retry_delay),
+          max_retries: connect_max_retries,
+        };
+
+        let (client, driver) = HybridClient::new(http_url, ws_url, reconnect_opt).context("failed to create HybridClient")?;
 
         let driver_handle = tokio::spawn(async move { driver.run().await });
 
-        let result = run(settings, client).await;
+        let result = run_until_signal(settings, client).await;
 
         // Await the driver's termination to ensure proper connection closure.
         let _ = driver_handle.await;
@@ -31,8 +40,39 @@ cmd! {
   }
 }
 
+/// Run the Ethereum API facade until it fails or we are asked to stop.
+///
+/// On SIGTERM or SIGINT the in-flight requests are given `shutdown_timeout` to finish.
+async fn run_until_signal(settings: Settings, client: HybridClient) -> anyhow::Result<()> {
+    let shutdown = CancellationToken::new();
+    let mut server = tokio::spawn(run(settings.eth, client, shutdown.clone()));
+
+    tokio::select! {
+        res = &mut server => res?,
+        signal = shutdown_signal() => {
+            let signal = signal?;
+            info!(signal, "shutting down");
+            shutdown.cancel();
+
+            let timeout = settings.shutdown_timeout;
+            match tokio::time::timeout(timeout, server).await {
+                Ok(res) => res?,
+                Err(_) => Err(ShutdownTimeout {
+                    timeout,
+                    pending: vec!["Ethereum API"],
+                }
+                .into()),
+            }
+        }
+    }
+}
+
 /// Run the Ethereum API facade.
-async fn run(settings: EthSettings, client: HybridClient) -> anyhow::Result<()> {
+async fn run(
+    settings: EthSettings,
+    client: HybridClient,
+    shutdown: CancellationToken,
+) -> anyhow::Result<()> {
     let gas = fendermint_eth_api::GasOpt {
         min_gas_premium: settings.gas.min_gas_premium,
         num_blocks_max_prio_fee: settings.gas.num_blocks_max_prio_fee,
@@ -45,6 +85,9 @@ async fn run(settings: EthSettings, client: HybridClient) -> anyhow::Result<()>
         settings.cache_capacity,
         settings.max_nonce_gap,
         gas,
+        settings.max_log_block_range,
+        settings.max_request_bytes,
+        shutdown,
     )
     .await
 }
@@ -2,7 +2,6 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use anyhow::{anyhow, Context};
-use fendermint_crypto::PublicKey;
 use fvm_shared::address::Address;
 use ipc_provider::config::subnet::{EVMSubnet, SubnetConfig};
 use ipc_provider::IpcProvider;
@@ -11,8 +10,9 @@ use std::path::PathBuf;
 use fendermint_vm_actor_interface::eam::EthAddress;
 use fendermint_vm_core::{chainid, Timestamp};
 use fendermint_vm_genesis::{
-    ipc, Account, Actor, ActorMeta, Collateral, Genesis, Multisig, PermissionMode, SignerAddr,
-    Validator, ValidatorKey,
+    from_parent::{genesis_from_parent, FromParentParams, ParentGenesisInfo},
+    ipc, Account, Actor, ActorMeta, CetfParams, Collateral, Genesis, Multisig, PermissionMode,
+    SignerAddr, Validator, ValidatorKey,
 };
 
 use crate::cmd;
@@ -47,6 +47,20 @@ cmd! {
       accounts: Vec::new(),
       eam_permission_mode: PermissionMode::Unrestricted,
       ipc: None,
+      cetf: if self.cetf_max_pending_tags.is_some()
+        || self.cetf_max_tags_per_height.is_some()
+        || self.cetf_min_tag_distance.is_some()
+      {
+        Some(CetfParams {
+          max_pending_tags: self
+            .cetf_max_pending_tags
+            .unwrap_or(fendermint_actor_cetf::DEFAULT_MAX_PENDING_TAGS),
+          max_tags_per_height: self.cetf_max_tags_per_height,
+          min_tag_distance: self.cetf_min_tag_distance,
+        })
+      } else {
+        None
+      },
     };
 
     let json = serde_json::to_string_pretty(&genesis)?;
@@ -198,18 +212,21 @@ fn set_eam_permissions(
     args: &GenesisSetEAMPermissionsArgs,
 ) -> anyhow::Result<()> {
     update_genesis(genesis_file, |mut genesis| {
-        genesis.eam_permission_mode = match args.mode.to_lowercase().as_str() {
-            "unrestricted" => PermissionMode::Unrestricted,
-            "allowlist" => {
-                let addresses = args.addresses.clone();
-                PermissionMode::AllowList { addresses }
-            }
-            _ => return Err(anyhow!("unknown eam permisison mode")),
-        };
+        genesis.eam_permission_mode = eam_permission_mode(&args.mode, &args.addresses)?;
         Ok(genesis)
     })
 }
 
+fn eam_permission_mode(mode: &str, addresses: &[SignerAddr]) -> anyhow::Result<PermissionMode> {
+    match mode.to_lowercase().as_str() {
+        "unrestricted" => Ok(PermissionMode::Unrestricted),
+        "allowlist" => Ok(PermissionMode::AllowList {
+            addresses: addresses.to_vec(),
+        }),
+        _ => Err(anyhow!("unknown eam permisison mode")),
+    }
+}
+
 fn into_tendermint(genesis_file: &PathBuf, args: &GenesisIntoTendermintArgs) -> anyhow::Result<()> {
     let genesis = read_genesis(genesis_file)?;
     let genesis_json = serde_json::to_value(&genesis)?;
@@ -265,6 +282,7 @@ fn set_ipc_gateway(genesis_file: &PathBuf, args: &GenesisIpcGatewayArgs) -> anyh
             majority_percentage: args.majority_percentage,
             active_validators_limit: args.active_validators_limit,
         };
+        gateway_params.validate()?;
 
         let ipc_params = match genesis.ipc {
             Some(mut ipc) => {
@@ -296,59 +314,37 @@ async fn new_genesis_from_parent(
                 .ok_or_else(|| anyhow!("subnet is not a child"))?,
             config: SubnetConfig::Fevm(EVMSubnet {
                 provider_http: args.parent_endpoint.clone(),
+                provider_ws: None,
                 provider_timeout: None,
                 auth_token: args.parent_auth_token.clone(),
                 registry_addr: args.parent_registry,
                 gateway_addr: args.parent_gateway,
+                checkpoint_submission: None,
             }),
         },
     )?;
 
-    let genesis_info = parent_provider.get_genesis_info(&args.subnet_id).await?;
-
-    // get gateway genesis
-    let ipc_params = ipc::IpcParams {
-        gateway: ipc::GatewayParams {
-            subnet_id: args.subnet_id.clone(),
-            bottom_up_check_period: genesis_info.bottom_up_checkpoint_period,
-            majority_percentage: genesis_info.majority_percentage,
-            active_validators_limit: genesis_info.active_validators_limit,
-        },
+    let genesis_info = parent_provider
+        .get_genesis_info(&args.subnet_id, args.parent_height)
+        .await?;
+
+    let info = ParentGenesisInfo {
+        genesis_epoch: genesis_info.genesis_epoch,
+        bottom_up_checkpoint_period: genesis_info.bottom_up_checkpoint_period,
+        majority_percentage: genesis_info.majority_percentage,
+        active_validators_limit: genesis_info.active_validators_limit,
+        validators: genesis_info.validators,
+        genesis_balances: genesis_info.genesis_balances,
     };
-    let mut genesis = Genesis {
-        // We set the genesis epoch as the genesis timestamp so it can be
-        // generated deterministically by all participants
-        // genesis_epoch should be a positive number, we can afford panicking
-        // here if this is not the case.
-        timestamp: Timestamp(genesis_info.genesis_epoch.try_into().unwrap()),
-        chain_name: args.subnet_id.to_string(),
+
+    let params = FromParentParams {
         network_version: args.network_version,
         base_fee: args.base_fee.clone(),
         power_scale: args.power_scale,
-        validators: Vec::new(),
-        accounts: Vec::new(),
-        eam_permission_mode: PermissionMode::Unrestricted,
-        ipc: Some(ipc_params),
+        eam_permission_mode: eam_permission_mode(&args.eam_permission_mode, &args.eam_addresses)?,
     };
 
-    for v in genesis_info.validators {
-        let pk = PublicKey::parse_slice(&v.metadata, None)?;
-        genesis.validators.push(Validator {
-            public_key: ValidatorKey(pk),
-            power: Collateral(v.weight),
-        })
-    }
-
-    for (a, b) in genesis_info.genesis_balances {
-        let meta = ActorMeta::Account(Account {
-            owner: SignerAddr(a),
-        });
-        let actor = Actor {
-            meta,
-            balance: b.clone(),
-        };
-        genesis.accounts.push(actor);
-    }
+    let genesis = genesis_from_parent(&args.subnet_id, info, params)?;
 
     let json = serde_json::to_string_pretty(&genesis)?;
     std::fs::write(genesis_file, json)?;
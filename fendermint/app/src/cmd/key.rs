@@ -1,10 +1,10 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use anyhow::{anyhow, Context};
+use anyhow::{anyhow, bail, Context};
 use bls_signatures::Serialize;
-use fendermint_app_options::key::KeyShowPeerIdArgs;
-use fendermint_crypto::{from_b64, to_b64, PublicKey, SecretKey};
+use fendermint_app_options::key::{KeyFromTendermintArgs, KeyShowPeerIdArgs};
+use fendermint_crypto::{from_b64, normalize_public_key, to_b64, PublicKey, SecretKey};
 use fendermint_vm_actor_interface::eam::EthAddress;
 use fvm_shared::address::Address;
 use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
@@ -14,22 +14,26 @@ use tendermint_config::NodeKey;
 
 use crate::{
     cmd,
-    options::key::{
-        AddPeer, KeyAddressArgs, KeyArgs, KeyCommands, KeyFromEthArgs, KeyGenArgs, KeyIntoEthArgs,
-        KeyIntoTendermintArgs,
+    options::{
+        key::{
+            AddPeer, KeyAddressArgs, KeyArgs, KeyCommands, KeyFromEthArgs, KeyGenArgs,
+            KeyIntoEthArgs, KeyIntoTendermintArgs,
+        },
+        OutputFormat,
     },
 };
 
 cmd! {
-    KeyArgs(self) {
+    KeyArgs(self, output: OutputFormat) {
         match &self.command {
             KeyCommands::Gen(args) => args.exec(()).await,
             KeyCommands::IntoTendermint(args) => args.exec(()).await,
+            KeyCommands::FromTendermint(args) => args.exec(()).await,
             KeyCommands::AddPeer(args) => args.exec(()).await,
-            KeyCommands::Address(args) => args.exec(()).await,
+            KeyCommands::Address(args) => args.exec(output).await,
             KeyCommands::FromEth(args) => args.exec(()).await,
             KeyCommands::IntoEth(args) => args.exec(()).await,
-            KeyCommands::ShowPeerId(args) => args.exec(()).await,
+            KeyCommands::ShowPeerId(args) => args.exec(output).await,
         }
     }
 }
@@ -37,10 +41,14 @@ cmd! {
 cmd! {
     KeyFromEthArgs(self) {
         let sk = read_secret_key_hex(&self.secret_key)?;
-        let pk = sk.public_key();
+        let pk = normalize_public_key(sk.public_key());
+        let sk_b64 = secret_to_b64(&sk);
 
-        export(&self.out_dir, &self.name, "sk", &secret_to_b64(&sk))?;
-        export(&self.out_dir, &self.name, "pk", &public_to_b64(&pk))?;
+        if let Some(ref name) = self.name {
+            export(&self.out_dir, name, "sk", &sk_b64)?;
+            export(&self.out_dir, name, "pk", &public_to_b64(&pk))?;
+        }
+        output_secret(self.out_file.as_deref(), self.show_secret, &sk_b64)?;
 
         Ok(())
     }
@@ -48,12 +56,18 @@ cmd! {
 
 cmd! {
     KeyIntoEthArgs(self) {
-        let sk = read_secret_key(&self.secret_key)?;
-        let pk = sk.public_key();
-
-        export(&self.out_dir, &self.name, "sk", &hex::encode(sk.serialize()))?;
-        export(&self.out_dir, &self.name, "pk", &hex::encode(pk.serialize()))?;
-        export(&self.out_dir, &self.name, "addr", &hex::encode(EthAddress::from(pk).0))?;
+        let sk = read_convertible_secret_key(&self.secret_key)?;
+        let pk = normalize_public_key(sk.public_key());
+        let sk_hex = secret_to_hex(&sk);
+        let addr = EthAddress::from(pk);
+
+        if let Some(ref name) = self.name {
+            export(&self.out_dir, name, "sk", &sk_hex)?;
+            export(&self.out_dir, name, "pk", &hex::encode(pk.serialize()))?;
+            export(&self.out_dir, name, "addr", &hex::encode(addr.0))?;
+        }
+        println!("0x{}", hex::encode(addr.0));
+        output_secret(self.out_file.as_deref(), self.show_secret, &sk_hex)?;
 
         Ok(())
     }
@@ -80,28 +94,27 @@ cmd! {
 
 cmd! {
   KeyIntoTendermintArgs(self) {
-    let sk = read_secret_key(&self.secret_key)?;
-    let pk = sk.public_key();
-    let vk = tendermint::crypto::default::ecdsa_secp256k1::VerifyingKey::from_sec1_bytes(&pk.serialize())
-      .map_err(|e| anyhow!("failed to convert public key: {e}"))?;
-    let pub_key = tendermint::PublicKey::Secp256k1(vk);
-    let address = tendermint::account::Id::from(pub_key);
+    let sk = read_convertible_secret_key(&self.secret_key)?;
+    let json = secret_to_tendermint(&sk)?;
 
-    // tendermint-rs doesn't seem to handle Secp256k1 private keys;
-    // if it did, we could use tendermint_config::PrivateValidatorKey
-    // to encode the data structure. Tendermint should be okay with it
-    // though, as long as we match the expected keys in the JSON.
-    let priv_validator_key = json! ({
-        "address": address,
-        "pub_key": pub_key,
-        "priv_key": {
-            "type": "tendermint/PrivKeySecp256k1",
-            "value": secret_to_b64(&sk)
-        }
-    });
-    let json = serde_json::to_string_pretty(&priv_validator_key)?;
+    output_secret(self.out.as_deref(), self.show_secret, &json)?;
 
-    std::fs::write(&self.out, json)?;
+    Ok(())
+  }
+}
+
+cmd! {
+  KeyFromTendermintArgs(self) {
+    let json = std::fs::read_to_string(&self.priv_validator_key).context("failed to read private validator key")?;
+    let sk = tendermint_to_secret(&json)?;
+    let pk = normalize_public_key(sk.public_key());
+    let sk_b64 = secret_to_b64(&sk);
+
+    if let Some(ref name) = self.name {
+        export(&self.out_dir, name, "sk", &sk_b64)?;
+        export(&self.out_dir, name, "pk", &public_to_b64(&pk))?;
+    }
+    output_secret(self.out_file.as_deref(), self.show_secret, &sk_b64)?;
 
     Ok(())
   }
@@ -126,30 +139,112 @@ cmd! {
 }
 
 cmd! {
-    KeyAddressArgs(self) {
+    KeyAddressArgs(self, output: OutputFormat) {
         let pk = read_public_key(&self.public_key)?;
         let addr = Address::new_secp256k1(&pk.serialize())?;
-        println!("{}", addr);
+        match output {
+            OutputFormat::Text => println!("{}", addr),
+            OutputFormat::Json => println!("{}", json!({ "address": addr.to_string() })),
+        }
         Ok(())
     }
 }
 
 cmd! {
-    KeyShowPeerIdArgs(self) {
+    KeyShowPeerIdArgs(self, output: OutputFormat) {
         let pk = read_public_key(&self.public_key)?;
         // Just using this type because it does the conversion we need.
         let vk = ipc_ipld_resolver::ValidatorKey::from(pk);
         let pk: libp2p::identity::PublicKey = vk.into();
         let id = pk.to_peer_id();
-        println!("{}", id);
+        match output {
+            OutputFormat::Text => println!("{}", id),
+            OutputFormat::Json => println!("{}", json!({ "peer_id": id.to_string() })),
+        }
         Ok(())
     }
 }
 
+/// Key type of Secp256k1 private keys in the CometBFT private validator key file.
+const TENDERMINT_SECP256K1: &str = "tendermint/PrivKeySecp256k1";
+
+/// Length of a Secp256k1 secret key in bytes.
+const SECRET_KEY_SIZE: usize = 32;
+
 fn secret_to_b64(sk: &SecretKey) -> String {
     to_b64(sk.serialize().as_ref())
 }
 
+fn secret_to_hex(sk: &SecretKey) -> String {
+    hex::encode(sk.serialize())
+}
+
+/// Encode a secret key as a CometBFT private validator key JSON.
+fn secret_to_tendermint(sk: &SecretKey) -> anyhow::Result<String> {
+    let pk = normalize_public_key(sk.public_key());
+    let vk = tendermint::crypto::default::ecdsa_secp256k1::VerifyingKey::from_sec1_bytes(
+        &pk.serialize(),
+    )
+    .map_err(|e| anyhow!("failed to convert public key: {e}"))?;
+    let pub_key = tendermint::PublicKey::Secp256k1(vk);
+    let address = tendermint::account::Id::from(pub_key);
+
+    // tendermint-rs doesn't seem to handle Secp256k1 private keys;
+    // if it did, we could use tendermint_config::PrivateValidatorKey
+    // to encode the data structure. Tendermint should be okay with it
+    // though, as long as we match the expected keys in the JSON.
+    let priv_validator_key = json! ({
+        "address": address,
+        "pub_key": pub_key,
+        "priv_key": {
+            "type": TENDERMINT_SECP256K1,
+            "value": secret_to_b64(sk)
+        }
+    });
+
+    Ok(serde_json::to_string_pretty(&priv_validator_key)?)
+}
+
+/// Parse a CometBFT private validator key JSON, which must hold a Secp256k1 key.
+fn tendermint_to_secret(json: &str) -> anyhow::Result<SecretKey> {
+    let value: serde_json::Value =
+        serde_json::from_str(json).context("failed to parse private validator key")?;
+
+    let key_type = value["priv_key"]["type"]
+        .as_str()
+        .ok_or_else(|| anyhow!("missing priv_key.type"))?;
+    if key_type != TENDERMINT_SECP256K1 {
+        bail!("unsupported key type {key_type}; only Secp256k1 keys can be converted");
+    }
+
+    let b64 = value["priv_key"]["value"]
+        .as_str()
+        .ok_or_else(|| anyhow!("missing priv_key.value"))?;
+    let sk = b64_to_secret(b64).context("failed to parse secret key")?;
+
+    // Don't silently carry on with a different validator identity than the file claims.
+    if let Some(pub_b64) = value["pub_key"]["value"].as_str() {
+        let pk = normalize_public_key(sk.public_key());
+        if pub_b64 != public_to_b64(&pk) {
+            bail!("the public key in the file doesn't belong to the private key");
+        }
+    }
+
+    Ok(sk)
+}
+
+/// Write the converted secret to `out_file`, if given, and print it only if `show_secret` is set.
+fn output_secret(out_file: Option<&Path>, show_secret: bool, secret: &str) -> anyhow::Result<()> {
+    if let Some(path) = out_file {
+        std::fs::write(path, secret)
+            .with_context(|| format!("failed to write {}", path.to_string_lossy()))?;
+    }
+    if show_secret {
+        println!("{secret}");
+    }
+    Ok(())
+}
+
 fn public_to_b64(pk: &PublicKey) -> String {
     to_b64(&pk.serialize_compressed())
 }
@@ -162,6 +257,24 @@ fn b64_to_public(b64: &str) -> anyhow::Result<PublicKey> {
 
 fn b64_to_secret(b64: &str) -> anyhow::Result<SecretKey> {
     let bz = from_b64(b64)?;
+    bytes_to_secret(bz)
+}
+
+fn hex_to_secret(hex_str: &str) -> anyhow::Result<SecretKey> {
+    let hex_str = hex_str.trim();
+    let hex_str = hex_str.strip_prefix("0x").unwrap_or(hex_str);
+    let bz = hex::decode(hex_str).context("cannot decode hex private key")?;
+    bytes_to_secret(bz)
+}
+
+fn bytes_to_secret(bz: Vec<u8>) -> anyhow::Result<SecretKey> {
+    if bz.len() != SECRET_KEY_SIZE {
+        bail!(
+            "expected a {} byte Secp256k1 secret key, got {} bytes; BLS and Ed25519 keys are not supported",
+            SECRET_KEY_SIZE,
+            bz.len()
+        );
+    }
     let sk = SecretKey::try_from(bz)?;
     Ok(sk)
 }
@@ -180,12 +293,7 @@ pub fn read_public_key(public_key: &Path) -> anyhow::Result<PublicKey> {
 
 pub fn read_secret_key_hex(private_key: &Path) -> anyhow::Result<SecretKey> {
     let hex_str = std::fs::read_to_string(private_key).context("failed to read private key")?;
-    let mut hex_str = hex_str.trim();
-    if hex_str.starts_with("0x") {
-        hex_str = &hex_str[2..];
-    }
-    let raw_secret = hex::decode(hex_str).context("cannot decode hex private key")?;
-    let sk = SecretKey::try_from(raw_secret).context("failed to parse secret key")?;
+    let sk = hex_to_secret(&hex_str).context("failed to parse secret key")?;
     Ok(sk)
 }
 
@@ -195,6 +303,19 @@ pub fn read_secret_key(secret_key: &Path) -> anyhow::Result<SecretKey> {
     Ok(sk)
 }
 
+/// Read a Base64 encoded Secp256k1 secret key to convert into another format.
+///
+/// The BLS secret keys exported by `key gen` have the same length, so they are told apart by name.
+fn read_convertible_secret_key(secret_key: &Path) -> anyhow::Result<SecretKey> {
+    if secret_key.to_string_lossy().ends_with(".bls.sk") {
+        bail!(
+            "{} is a BLS key; only Secp256k1 keys can be converted",
+            secret_key.to_string_lossy()
+        );
+    }
+    read_secret_key(secret_key)
+}
+
 pub fn read_bls_secret_key(secret_key: &Path) -> anyhow::Result<bls_signatures::PrivateKey> {
     let b64 = std::fs::read_to_string(secret_key).context("failed to read secret key")?;
     let sk = b64_to_bls_secret(&b64).context("failed to parse secret key")?;
@@ -209,12 +330,23 @@ fn export(output_dir: &Path, name: &str, ext: &str, b64: &str) -> anyhow::Result
 
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
+
+    use fendermint_crypto::{to_b64, SecretKey};
     use fendermint_vm_genesis::ValidatorKey;
     use quickcheck_macros::quickcheck;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
 
     use crate::cmd::key::b64_to_public;
 
-    use super::public_to_b64;
+    use super::{
+        b64_to_secret, hex_to_secret, public_to_b64, read_convertible_secret_key, secret_to_b64,
+        secret_to_hex, secret_to_tendermint, tendermint_to_secret,
+    };
+
+    fn secret_key(seed: u64) -> SecretKey {
+        SecretKey::random(&mut ChaCha8Rng::seed_from_u64(seed))
+    }
 
     #[quickcheck]
     fn prop_public_key_deserialize_to_genesis(vk: ValidatorKey) {
@@ -222,4 +354,58 @@ mod tests {
         let pk = b64_to_public(&b64).unwrap();
         assert_eq!(pk, vk.0)
     }
+
+    #[quickcheck]
+    fn prop_b64_roundtrip(seed: u64) {
+        let sk = secret_key(seed);
+        assert_eq!(b64_to_secret(&secret_to_b64(&sk)).unwrap(), sk);
+    }
+
+    #[quickcheck]
+    fn prop_eth_roundtrip(seed: u64) {
+        let sk = secret_key(seed);
+        let hex = secret_to_hex(&sk);
+        assert_eq!(hex_to_secret(&hex).unwrap(), sk);
+        // As printed by other tools.
+        assert_eq!(hex_to_secret(&format!("0x{hex}\n")).unwrap(), sk);
+    }
+
+    #[quickcheck]
+    fn prop_tendermint_roundtrip(seed: u64) {
+        let sk = secret_key(seed);
+        let json = secret_to_tendermint(&sk).unwrap();
+        assert_eq!(tendermint_to_secret(&json).unwrap(), sk);
+    }
+
+    #[test]
+    fn tendermint_rejects_ed25519() {
+        let json = serde_json::json!({
+            "priv_key": {
+                "type": "tendermint/PrivKeyEd25519",
+                "value": to_b64(&[1u8; 64]),
+            }
+        });
+        let err = tendermint_to_secret(&json.to_string()).unwrap_err();
+        assert!(err.to_string().contains("tendermint/PrivKeyEd25519"));
+    }
+
+    #[test]
+    fn tendermint_rejects_mismatching_public_key() {
+        let json = secret_to_tendermint(&secret_key(1)).unwrap();
+        let mut json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        json["priv_key"]["value"] = secret_to_b64(&secret_key(2)).into();
+        assert!(tendermint_to_secret(&json.to_string()).is_err());
+    }
+
+    #[test]
+    fn eth_rejects_ed25519() {
+        let err = hex_to_secret(&hex::encode([1u8; 64])).unwrap_err();
+        assert!(err.to_string().contains("got 64 bytes"));
+    }
+
+    #[test]
+    fn conversion_rejects_bls() {
+        let err = read_convertible_secret_key(Path::new("keys/alice.bls.sk")).unwrap_err();
+        assert!(err.to_string().contains("BLS"));
+    }
 }
@@ -7,7 +7,7 @@ use anyhow::{anyhow, bail};
 use fendermint_app_options::materializer::*;
 use fendermint_app_settings::utils::expand_tilde;
 use fendermint_materializer::{
-    docker::{DockerMaterializer, DropPolicy},
+    docker::{DockerImages, DockerMaterializer, DropPolicy},
     logging::LoggingMaterializer,
     manifest::Manifest,
     materials::DefaultAccount,
@@ -22,12 +22,20 @@ use super::key::{read_secret_key, read_secret_key_hex};
 cmd! {
   MaterializerArgs(self) {
     let data_dir = expand_tilde(&self.data_dir);
-    let dm = || DockerMaterializer::new(&data_dir, self.seed).map(|m| m.with_policy(DropPolicy::PERSISTENT));
+    let mut images = DockerImages::default();
+    if let Some(ref image) = self.fendermint_image {
+        images.fendermint = image.clone();
+    }
+    if let Some(ref image) = self.cometbft_image {
+        images.cometbft = image.clone();
+    }
+    let dm = || DockerMaterializer::new(&data_dir, self.seed).map(|m| m.with_policy(DropPolicy::PERSISTENT).with_images(images.clone()));
     let lm = || dm().map(|m| LoggingMaterializer::new(m, "cli".to_string()));
     match &self.command {
         MaterializerCommands::Validate(args) => args.exec(()).await,
         MaterializerCommands::Setup(args) => args.exec(lm()?).await,
         MaterializerCommands::Remove(args) => args.exec(dm()?).await,
+        MaterializerCommands::Prune(args) => args.exec(dm()?).await,
         MaterializerCommands::ImportKey(args) => args.exec(data_dir).await,
     }
   }
@@ -51,6 +59,12 @@ cmd! {
   }
 }
 
+cmd! {
+  MaterializerPruneArgs(self, m: DockerMaterializer) {
+    prune(m, self.dry_run).await
+  }
+}
+
 cmd! {
   MaterializerImportKeyArgs(self, data_dir: PathBuf) {
     import_key(&data_dir, &self.secret_key, &self.manifest_file, &self.account_id)
@@ -85,6 +99,26 @@ async fn remove(mut m: DockerMaterializer, id: TestnetId) -> anyhow::Result<()>
     m.remove(&TestnetName::new(id)).await
 }
 
+/// Remove orphaned testnet resources.
+async fn prune(mut m: DockerMaterializer, dry_run: bool) -> anyhow::Result<()> {
+    let plan = m.prune(dry_run).await?;
+
+    for item in plan.keep.iter() {
+        println!("kept {item}");
+    }
+    let removed = if dry_run { "would remove" } else { "removed" };
+    for item in plan.remove.iter() {
+        println!("{removed} {item}");
+    }
+    println!(
+        "kept {} and {removed} {} testnet resources",
+        plan.keep.len(),
+        plan.remove.len()
+    );
+
+    Ok(())
+}
+
 /// Read a manifest file; use its file name as the testnet name.
 fn read_manifest(manifest_file: &Path) -> anyhow::Result<(TestnetName, Manifest)> {
     let testnet_id = manifest_file
@@ -10,6 +10,7 @@ use crate::{
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
 
+pub mod check;
 pub mod config;
 pub mod debug;
 pub mod eth;
@@ -19,6 +20,16 @@ pub mod materializer;
 pub mod rpc;
 pub mod run;
 
+/// Marks errors in loading the settings, as opposed to failures while executing a command.
+#[derive(Debug)]
+pub struct ConfigError;
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to load the configuration")
+    }
+}
+
 #[async_trait]
 pub trait Cmd {
     type Settings;
@@ -62,19 +73,27 @@ macro_rules! cmd {
 /// Execute the command specified in the options.
 pub async fn exec(opts: &Options) -> anyhow::Result<()> {
     match &opts.command {
+        // Not validated, so that `config` can be used to look at what's wrong.
         Commands::Config(args) => args.exec(settings(opts)?).await,
+        Commands::Check(args) => args.exec(validated(settings(opts)?)?).await,
         Commands::Debug(args) => args.exec(()).await,
-        Commands::Run(args) => args.exec(settings(opts)?).await,
-        Commands::Key(args) => args.exec(()).await,
+        Commands::Run(args) => args.exec(validated(settings(opts)?)?).await,
+        Commands::Key(args) => args.exec(opts.output).await,
         Commands::Genesis(args) => args.exec(()).await,
-        Commands::Rpc(args) => args.exec(()).await,
-        Commands::Eth(args) => args.exec(settings(opts)?.eth).await,
+        Commands::Rpc(args) => args.exec(opts.output).await,
+        Commands::Eth(args) => args.exec(validated(settings(opts)?)?).await,
         Commands::Materializer(args) => args.exec(()).await,
     }
 }
 
 /// Try to parse the settings in the configuration directory.
+///
+/// Errors are marked with [ConfigError].
 fn settings(opts: &Options) -> anyhow::Result<Settings> {
+    parse_settings(opts).context(ConfigError)
+}
+
+fn parse_settings(opts: &Options) -> anyhow::Result<Settings> {
     let config_dir = match expand_tilde(opts.config_dir()) {
         d if !d.exists() => return Err(anyhow!("'{d:?}' does not exist")),
         d if !d.is_dir() => return Err(anyhow!("'{d:?}' is a not a directory")),
@@ -90,3 +109,26 @@ fn settings(opts: &Options) -> anyhow::Result<Settings> {
 
     Ok(settings)
 }
+
+/// Pass on the settings only if they are valid.
+fn validated(settings: Settings) -> anyhow::Result<Settings> {
+    validate(&settings)?;
+    Ok(settings)
+}
+
+/// Check the settings for invalid combinations, listing all of them in the error.
+///
+/// Errors are marked with [ConfigError].
+pub fn validate(settings: &Settings) -> anyhow::Result<()> {
+    settings
+        .validate()
+        .map_err(|errors| {
+            let errors = errors
+                .iter()
+                .map(|e| format!("  - {e}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            anyhow!("invalid settings:\n{errors}")
+        })
+        .context(ConfigError)
+}
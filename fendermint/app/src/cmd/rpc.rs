@@ -17,9 +17,10 @@ use fendermint_rpc::tx::{
 };
 use fendermint_vm_core::chainid;
 use fendermint_vm_message::chain::ChainMessage;
-use fendermint_vm_message::query::FvmQueryHeight;
+use fendermint_vm_message::query::{BlockHashLookup, FvmQueryHeight};
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::MethodNum;
 use serde::Serialize;
@@ -35,16 +36,17 @@ use fendermint_vm_actor_interface::eam::{self, CreateReturn, EthAddress};
 use crate::cmd;
 use crate::options::rpc::{BroadcastMode, FevmArgs, RpcFevmCommands, TransArgs};
 use crate::options::rpc::{RpcArgs, RpcCommands, RpcQueryCommands};
+use crate::options::OutputFormat;
 
 use super::key::read_secret_key;
 
 cmd! {
-  RpcArgs(self) {
+  RpcArgs(self, output: OutputFormat) {
     let client = FendermintClient::new_http(self.url.clone(), self.proxy_url.clone())?;
     match self.command.clone() {
       RpcCommands::Query { height, command } => {
         let height = Height::try_from(height)?;
-        query(client, height, command).await
+        query(client, height, command, output).await
       },
       RpcCommands::Transfer { args, to } => {
         transfer(client, args, to).await
@@ -52,6 +54,9 @@ cmd! {
       RpcCommands::Transaction { args, to, method_number, params } => {
         transaction(client, args, to, method_number, params.clone()).await
       },
+      RpcCommands::BlockHash { height } => {
+        block_hash(client, height, output).await
+      },
       RpcCommands::Fevm { args, command } => match command {
         RpcFevmCommands::Create { contract, constructor_args } => {
             fevm_create(client, args, contract, constructor_args).await
@@ -73,16 +78,25 @@ cmd! {
 }
 
 /// Run an ABCI query and print the results on STDOUT.
+///
+/// In JSON mode, a missing result is printed as `null`.
 async fn query(
     client: FendermintClient,
     height: Height,
     command: RpcQueryCommands,
+    output: OutputFormat,
 ) -> anyhow::Result<()> {
     let height = FvmQueryHeight::from(height.value());
     match command {
         RpcQueryCommands::Ipld { cid } => match client.ipld(&cid, height).await? {
-            Some(data) => println!("{}", to_b64(&data)),
-            None => eprintln!("CID not found"),
+            Some(data) => match output {
+                OutputFormat::Text => println!("{}", to_b64(&data)),
+                OutputFormat::Json => print_output(&json!({ "data": to_b64(&data) }), output)?,
+            },
+            None => {
+                eprintln!("CID not found");
+                print_not_found(output);
+            }
         },
         RpcQueryCommands::ActorState { address } => {
             match client.actor_state(&address, height).await?.value {
@@ -91,22 +105,51 @@ async fn query(
                       "id": id,
                       "state": state,
                     });
-                    print_json(&out)?;
+                    print_output(&out, output)?;
                 }
                 None => {
-                    eprintln!("actor not found")
+                    eprintln!("actor not found");
+                    print_not_found(output);
                 }
             }
         }
         RpcQueryCommands::StateParams => {
             let res = client.state_params(height).await?;
             let json = json!({ "response": res });
-            print_json(&json)?;
+            print_output(&json, output)?;
         }
     };
     Ok(())
 }
 
+/// Look up the hash of a block in the latest committed state and print it on STDOUT.
+async fn block_hash(
+    client: FendermintClient,
+    height: u64,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let block_height = ChainEpoch::try_from(height).context("height too big")?;
+    let res = client
+        .block_hash(block_height, FvmQueryHeight::Committed)
+        .await?;
+    match res.value {
+        BlockHashLookup::Found(hash) => {
+            let json = json!({ "height": height, "hash": hex::encode(hash) });
+            print_output(&json, output)?;
+        }
+        BlockHashLookup::Pruned { lookback_len } => {
+            eprintln!(
+                "block hash pruned; only the last {lookback_len} are kept as of height {}",
+                res.height
+            )
+        }
+        BlockHashLookup::NotFound => {
+            eprintln!("block hash not found as of height {}", res.height)
+        }
+    }
+    Ok(())
+}
+
 /// Create a client, make a call to Tendermint with a closure, then maybe extract some JSON
 /// depending on the return value, finally print the result in JSON.
 async fn broadcast_and_print<F, T, G>(
@@ -293,6 +336,24 @@ fn print_json<T: Serialize>(value: &T) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Print the results of queries in the chosen format: pretty-printed for people, a single line for scripts.
+fn print_output<T: Serialize>(value: &T, output: OutputFormat) -> anyhow::Result<()> {
+    match output {
+        OutputFormat::Text => print_json(value),
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string(value)?);
+            Ok(())
+        }
+    }
+}
+
+/// Let scripts tell apart a missing result from a failed command.
+fn print_not_found(output: OutputFormat) {
+    if output == OutputFormat::Json {
+        println!("null");
+    }
+}
+
 /// Print all the various addresses we can use to refer to an EVM contract.
 fn create_return_to_json(ret: CreateReturn) -> serde_json::Value {
     // The only reference I can point to about how to use them are the integration tests:
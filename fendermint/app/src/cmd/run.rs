@@ -6,34 +6,41 @@ use async_stm::atomically_or_err;
 use fendermint_abci::ApplicationService;
 use fendermint_app::events::{ParentFinalityVoteAdded, ParentFinalityVoteIgnored};
 use fendermint_app::ipc::{AppParentFinalityQuery, AppVote};
+use fendermint_app::shutdown::{shutdown_signal, ShutdownController, ShutdownStage};
 use fendermint_app::{App, AppConfig, AppStore, BitswapBlockstore};
-use fendermint_app_settings::AccountKind;
+use fendermint_app_settings::resolver::SubnetLimitSettings;
+use fendermint_app_settings::{AccountKind, DbCompaction};
 use fendermint_crypto::SecretKey;
 use fendermint_rocksdb::{blockstore::NamespaceBlockstore, namespaces, RocksDb, RocksDbConfig};
 use fendermint_tracing::emit;
 use fendermint_vm_actor_interface::eam::EthAddress;
 use fendermint_vm_interpreter::chain::ChainEnv;
-use fendermint_vm_interpreter::fvm::upgrades::UpgradeScheduler;
+use fendermint_vm_interpreter::fvm::upgrades::{MigrationRegistry, UpgradeFile, UpgradeScheduler};
 use fendermint_vm_interpreter::{
     bytes::{BytesMessageInterpreter, ProposalPrepareMode},
     chain::{ChainMessageInterpreter, CheckpointPool},
-    fvm::{Broadcaster, FvmMessageInterpreter, ValidatorContext},
+    fvm::{Broadcaster, FvmMessageInterpreter, GasOverride, MessageKind, ValidatorContext},
     signed::SignedMessageInterpreter,
 };
 use fendermint_vm_resolver::ipld::IpldResolver;
-use fendermint_vm_snapshot::{SnapshotManager, SnapshotParams};
+use fendermint_vm_snapshot::{PartCompression, SnapshotManager, SnapshotParams, SnapshotProgress};
 use fendermint_vm_topdown::proxy::IPCProviderProxy;
 use fendermint_vm_topdown::sync::launch_polling_syncer;
 use fendermint_vm_topdown::voting::{publish_vote_loop, Error as VoteError, VoteTally};
 use fendermint_vm_topdown::{CachedFinalityProvider, IPCParentFinality, Toggle};
 use fvm_shared::address::{current_network, Address, Network};
-use ipc_ipld_resolver::{Event as ResolverEvent, VoteRecord};
+use ipc_ipld_resolver::{
+    Event as ResolverEvent, RateLimitedStore, SubnetLimit, SubnetLimits, VoteRecord,
+};
 use ipc_provider::config::subnet::{EVMSubnet, SubnetConfig};
 use ipc_provider::IpcProvider;
 use libp2p::identity::secp256k1;
 use libp2p::identity::Keypair;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::watch;
+use tokio::time::MissedTickBehavior;
 use tower::ServiceBuilder;
 use tracing::info;
 
@@ -48,7 +55,8 @@ cmd! {
 
 // Database collection names.
 namespaces! {
-    Namespaces {
+    pub Namespaces {
+        schema,
         app,
         state_hist,
         state_store,
@@ -131,7 +139,7 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
     let validator_ctx = validator.map(|(sk, addr)| {
         // For now we are using the validator key for submitting transactions.
         // This allows us to identify transactions coming from empowered validators, to give priority to protocol related transactions.
-        let broadcaster = Broadcaster::new(
+        let mut broadcaster = Broadcaster::new(
             tendermint_client.clone(),
             addr,
             sk.clone(),
@@ -140,8 +148,19 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
             settings.fvm.gas_overestimation_rate,
         )
         .with_max_retries(settings.broadcast.max_retries)
+        .with_nonce_race_retries(settings.broadcast.nonce_race_retries)
         .with_retry_delay(settings.broadcast.retry_delay);
 
+        if let Some(o) = &settings.broadcast.gas.checkpoint {
+            broadcaster = broadcaster.with_gas_override(
+                MessageKind::Checkpoint,
+                GasOverride {
+                    overestimation_rate: o.gas_overestimation_rate,
+                    gas_premium: o.gas_premium.clone(),
+                },
+            );
+        }
+
         ValidatorContext::new(sk, bls_private_key.unwrap(), broadcaster)
     });
 
@@ -152,6 +171,21 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
         other => other,
     };
 
+    let upgrade_file = UpgradeFile::load(&settings.upgrades_file())?;
+
+    let mut upgrade_scheduler = UpgradeScheduler::new();
+    if let Some(ref file) = upgrade_file {
+        upgrade_scheduler
+            .add_file(file, &MigrationRegistry::new())
+            .context("failed to schedule upgrades")?;
+
+        tracing::info!(
+            chain_name = file.chain_name,
+            upgrades = file.upgrades.len(),
+            "scheduled upgrades from file"
+        );
+    }
+
     let interpreter = FvmMessageInterpreter::<NamespaceBlockstore, _>::new(
         tendermint_client.clone(),
         validator_ctx,
@@ -159,8 +193,9 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
         settings.fvm.gas_overestimation_rate,
         settings.fvm.gas_search_step,
         settings.fvm.exec_in_check,
-        UpgradeScheduler::new(),
+        upgrade_scheduler,
     )
+    .context("invalid fvm settings")?
     .with_push_chain_meta(testing_settings.map_or(true, |t| t.push_chain_meta));
 
     let interpreter = SignedMessageInterpreter::new(interpreter);
@@ -170,11 +205,23 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
         ProposalPrepareMode::PrependOnly,
         false,
         settings.abci.block_max_msgs,
+        settings.abci.block_max_bytes,
     );
 
     let ns = Namespaces::default();
     let db = open_db(&settings, &ns).context("error opening DB")?;
 
+    match settings.db.compaction_interval {
+        Some(interval) => {
+            tracing::info!(?interval, "starting the periodic database compaction...");
+            tokio::spawn(compact_db_loop(db.clone(), interval));
+        }
+        None if matches!(settings.db.compaction_style, DbCompaction::None) => {
+            tracing::warn!("auto-compaction is disabled and there is no compaction interval; the database will not be compacted");
+        }
+        None => {}
+    }
+
     // Blockstore for actors.
     let state_store =
         NamespaceBlockstore::new(db.clone(), ns.state_store).context("error creating state DB")?;
@@ -184,6 +231,30 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
 
     let topdown_enabled = settings.topdown_enabled();
 
+    // Components to stop before exiting, so they don't get interrupted halfway through writing something.
+    let mut controller = ShutdownController::new();
+
+    // Flush the database once everything else has stopped writing to it.
+    {
+        let db = db.clone();
+        controller.spawn(
+            "database",
+            ShutdownStage::Storage,
+            move |token| async move {
+                token.cancelled().await;
+                match tokio::task::spawn_blocking(move || db.flush_all()).await {
+                    Ok(Ok(())) => info!("flushed the database"),
+                    Ok(Err(e)) => {
+                        tracing::error!(error = e.to_string(), "failed to flush the database")
+                    }
+                    Err(e) => {
+                        tracing::error!(error = e.to_string(), "flushing the database panicked")
+                    }
+                }
+            },
+        );
+    }
+
     // If enabled, start a resolver that communicates with the application through the resolve pool.
     if settings.resolver_enabled() {
         let mut service =
@@ -221,11 +292,16 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
                         parent_finality_votes,
                         settings.ipc.vote_interval,
                         settings.ipc.vote_timeout,
+                        settings.ipc.vote_v1_height,
                         key,
                         own_subnet_id,
                         client,
                         |height, block_hash| {
-                            AppVote::ParentFinality(IPCParentFinality { height, block_hash })
+                            AppVote::ParentFinality(IPCParentFinality {
+                                height,
+                                block_hash,
+                                effects_commitment: None,
+                            })
                         },
                     )
                     .await
@@ -243,11 +319,20 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
         });
 
         tracing::info!("starting the IPLD Resolver Service...");
-        tokio::spawn(async move {
-            if let Err(e) = service.run().await {
-                tracing::error!("IPLD Resolver Service failed: {e:#}")
-            }
-        });
+        controller.spawn(
+            "IPLD Resolver Service",
+            ShutdownStage::Workers,
+            move |token| async move {
+                tokio::select! {
+                    res = service.run() => {
+                        if let Err(e) = res {
+                            tracing::error!("IPLD Resolver Service failed: {e:#}")
+                        }
+                    }
+                    _ = token.cancelled() => {}
+                }
+            },
+        );
 
         tracing::info!("starting the IPLD Resolver...");
         tokio::spawn(async move { resolver.run().await });
@@ -265,7 +350,8 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
             topdown_config.exponential_retry_limit,
         )
         .with_proposal_delay(topdown_config.proposal_delay)
-        .with_max_proposal_range(topdown_config.max_proposal_range);
+        .with_max_proposal_range(topdown_config.max_proposal_range)
+        .with_require_effects_commitment(topdown_config.require_effects_commitment);
 
         if let Some(v) = topdown_config.max_cache_blocks {
             info!(value = v, "setting max cache blocks");
@@ -291,6 +377,11 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
                 download_dir: settings.snapshots.download_dir(),
                 block_interval: settings.snapshots.block_interval,
                 chunk_size: settings.snapshots.chunk_size_bytes,
+                compression: settings
+                    .snapshots
+                    .compression_level
+                    .map(|level| PartCompression::Zstd { level })
+                    .unwrap_or_default(),
                 hist_size: settings.snapshots.hist_size,
                 last_access_hold: settings.snapshots.last_access_hold,
                 sync_poll_interval: settings.snapshots.sync_poll_interval,
@@ -298,9 +389,18 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
         )
         .context("failed to create snapshot manager")?;
 
+        let (export_tx, export_rx) = watch::channel(SnapshotProgress::default());
+        let (import_tx, import_rx) = watch::channel(SnapshotProgress::default());
+        let manager = manager.with_progress(export_tx);
+        let client = client.with_progress(import_tx);
+        tokio::spawn(log_snapshot_progress("export", export_rx));
+        tokio::spawn(log_snapshot_progress("import", import_rx));
+
         tracing::info!("starting the SnapshotManager...");
         let tendermint_client = tendermint_client.clone();
-        tokio::spawn(async move { manager.run(tendermint_client).await });
+        controller.spawn("SnapshotManager", ShutdownStage::Workers, move |token| {
+            manager.run(tendermint_client, token)
+        });
 
         Some(client)
     } else {
@@ -310,14 +410,16 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
 
     let app: App<_, _, AppStore, _> = App::new(
         AppConfig {
+            schema_namespace: ns.schema,
             app_namespace: ns.app,
             state_hist_namespace: ns.state_hist,
             state_hist_size: settings.db.state_hist_size,
             builtin_actors_bundle: settings.builtin_actors_bundle(),
             custom_actors_bundle: settings.custom_actors_bundle(),
             halt_height: settings.halt_height,
+            upgrade_file,
         },
-        db,
+        db.clone(),
         state_store,
         interpreter,
         ChainEnv {
@@ -328,23 +430,52 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
         snapshots,
     )?;
 
+    if settings.db.state_hist_size > 0 {
+        let app = app.clone();
+        let interval = settings.db.state_hist_prune_interval;
+        tracing::info!(?interval, "starting the state history pruning...");
+        controller.spawn(
+            "state history pruning",
+            ShutdownStage::Workers,
+            move |token| async move {
+                let mut ticker = tokio::time::interval(interval);
+                ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                loop {
+                    tokio::select! {
+                        biased;
+                        _ = token.cancelled() => return,
+                        _ = ticker.tick() => {}
+                    }
+                    if let Err(e) = app.prune_state_history().await {
+                        tracing::error!(error = e.to_string(), "failed to prune state history");
+                    }
+                }
+            },
+        );
+    }
+
     if let Some((agent_proxy, config)) = ipc_tuple {
         let app_parent_finality_query = AppParentFinalityQuery::new(app.clone());
-        tokio::spawn(async move {
-            match launch_polling_syncer(
-                app_parent_finality_query,
-                config,
-                parent_finality_provider,
-                parent_finality_votes,
-                agent_proxy,
-                tendermint_client,
-            )
-            .await
-            {
-                Ok(_) => {}
-                Err(e) => tracing::error!("cannot launch polling syncer: {e}"),
-            }
-        });
+        controller.spawn(
+            "parent syncer",
+            ShutdownStage::Workers,
+            move |token| async move {
+                match launch_polling_syncer(
+                    app_parent_finality_query,
+                    config,
+                    parent_finality_provider,
+                    parent_finality_votes,
+                    agent_proxy,
+                    tendermint_client,
+                    token,
+                )
+                .await
+                {
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("cannot launch polling syncer: {e}"),
+                }
+            },
+        );
     }
 
     // Start the metrics on a background thread.
@@ -360,6 +491,15 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
         info!("metrics disabled");
     }
 
+    // Once the ABCI server stops accepting connections, let the block in progress finish.
+    {
+        let app = app.clone();
+        controller.spawn("ABCI", ShutdownStage::Ingress, move |token| async move {
+            token.cancelled().await;
+            app.stop_executing_blocks().await;
+        });
+    }
+
     let service = ApplicationService(app);
 
     // Split it into components.
@@ -389,11 +529,17 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
         .finish()
         .context("error creating ABCI server")?;
 
-    // Run the ABCI server.
-    server
-        .listen_tcp(settings.abci.listen.to_string())
-        .await
-        .map_err(|e| anyhow!("error listening: {e}"))?;
+    // Run the ABCI server until we are asked to stop; dropping it stops accepting new connections.
+    tokio::select! {
+        res = server.listen_tcp(settings.abci.listen.to_string()) => {
+            res.map_err(|e| anyhow!("error listening: {e}"))?;
+        }
+        signal = shutdown_signal() => {
+            let signal = signal?;
+            info!(signal, "shutting down");
+            controller.shutdown(settings.shutdown_timeout).await?;
+        }
+    }
 
     Ok(())
 }
@@ -405,14 +551,47 @@ fn open_db(settings: &Settings, ns: &Namespaces) -> anyhow::Result<RocksDb> {
         path = path.to_string_lossy().into_owned(),
         "opening database"
     );
+    let namespace_ttl = ns
+        .ttls_with(&settings.db.namespace_ttl)
+        .context("invalid namespace TTL settings")?;
+
+    let defaults = RocksDbConfig::default();
     let config = RocksDbConfig {
         compaction_style: settings.db.compaction_style.to_string(),
-        ..Default::default()
+        namespace_ttl,
+        block_cache_size_mb: settings.db.block_cache_size_mb,
+        write_buffer_size: settings
+            .db
+            .write_buffer_size_mb
+            .map_or(defaults.write_buffer_size, |mb| mb * 1024 * 1024),
+        max_open_files: settings
+            .db
+            .max_open_files
+            .unwrap_or(defaults.max_open_files),
+        ..defaults
     };
     let db = RocksDb::open_cf(path, &config, ns.values().iter())?;
     Ok(db)
 }
 
+/// Compact all namespaces of the database at regular intervals.
+async fn compact_db_loop(db: RocksDb, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes immediately, but there is no need to compact during startup.
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        let db = db.clone();
+        let start = Instant::now();
+        match tokio::task::spawn_blocking(move || db.compact_all()).await {
+            Ok(Ok(())) => info!(elapsed = ?start.elapsed(), "compacted the database"),
+            Ok(Err(e)) => tracing::error!(error = e.to_string(), "failed to compact the database"),
+            Err(e) => tracing::error!(error = e.to_string(), "database compaction panicked"),
+        }
+    }
+}
+
 fn make_resolver_service(
     settings: &Settings,
     db: RocksDb,
@@ -425,6 +604,15 @@ fn make_resolver_service(
     // Blockstore for Bitswap with a fallback on the actor store for reads.
     let bitswap_store = BitswapBlockstore::new(state_store, bit_store);
 
+    // We can't tell which subnet a block was originally resolved from,
+    // so everything we serve is charged to our own subnet.
+    let subnet_id = settings.ipc.subnet_id.clone();
+    let bitswap_store = RateLimitedStore::new(
+        bitswap_store,
+        to_subnet_limits(settings),
+        move |_: &libipld::Cid| Some(subnet_id.clone()),
+    );
+
     let config = to_resolver_config(settings).context("error creating resolver config")?;
 
     let service = ipc_ipld_resolver::Service::new(config, bitswap_store)
@@ -447,10 +635,12 @@ fn make_ipc_provider_proxy(settings: &Settings) -> anyhow::Result<IPCProviderPro
                 .to_string()
                 .parse()
                 .unwrap(),
+            provider_ws: None,
             provider_timeout: topdown_config.parent_http_timeout,
             auth_token: topdown_config.parent_http_auth_token.as_ref().cloned(),
             registry_addr: topdown_config.parent_registry,
             gateway_addr: topdown_config.parent_gateway,
+            checkpoint_submission: None,
         }),
     };
     info!("init ipc provider with subnet: {}", subnet.id);
@@ -486,6 +676,7 @@ fn to_resolver_config(settings: &Settings) -> anyhow::Result<ipc_ipld_resolver::
             expected_peer_count: r.connection.expected_peer_count,
             max_incoming: r.connection.max_incoming,
             max_peers_per_query: r.connection.max_peers_per_query,
+            max_concurrent_requests: r.connection.max_concurrent_requests,
             event_buffer_capacity: r.connection.event_buffer_capacity,
         },
         network: NetworkConfig {
@@ -513,6 +704,23 @@ fn to_resolver_config(settings: &Settings) -> anyhow::Result<ipc_ipld_resolver::
     Ok(config)
 }
 
+fn to_subnet_limits(settings: &Settings) -> SubnetLimits {
+    let to_limit = |l: &SubnetLimitSettings| SubnetLimit {
+        bytes_per_sec: l.bytes_per_sec,
+        requests_per_sec: l.requests_per_sec,
+    };
+    let c = &settings.resolver.content;
+
+    SubnetLimits {
+        default: to_limit(&c.default_subnet_limit),
+        subnets: c
+            .subnet_limits
+            .iter()
+            .map(|(id, l)| (id.clone(), to_limit(l)))
+            .collect(),
+    }
+}
+
 fn to_address(sk: &SecretKey, kind: &AccountKind) -> anyhow::Result<Address> {
     let pk = sk.public_key().serialize();
     match kind {
@@ -521,6 +729,21 @@ fn to_address(sk: &SecretKey, kind: &AccountKind) -> anyhow::Result<Address> {
     }
 }
 
+/// Log the progress of snapshot exports or imports as it's reported.
+async fn log_snapshot_progress(operation: &'static str, mut rx: watch::Receiver<SnapshotProgress>) {
+    while rx.changed().await.is_ok() {
+        let progress = *rx.borrow_and_update();
+        tracing::debug!(
+            operation,
+            block_height = progress.block_height,
+            chunks_done = progress.chunks_done,
+            chunks_total = progress.chunks_total,
+            bytes_done = progress.bytes_done,
+            "snapshot progress"
+        );
+    }
+}
+
 async fn dispatch_resolver_events(
     mut rx: tokio::sync::broadcast::Receiver<ResolverEvent<AppVote>>,
     parent_finality_votes: VoteTally,
@@ -46,5 +46,11 @@ pub struct ExtendVote {
     pub bytes: u64,
 }
 
+#[derive(Debug, Default)]
+pub struct StateHistoryPruned {
+    pub retain_height: BlockHeight,
+    pub num_pruned: usize,
+}
+
 // TODO: Add new events for:
 // * snapshots
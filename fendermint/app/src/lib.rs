@@ -4,10 +4,13 @@ mod app;
 pub mod events;
 pub mod ipc;
 pub mod metrics;
+pub mod migrations;
+mod prune;
+pub mod shutdown;
 mod store;
 mod tmconv;
 
-pub use app::{App, AppConfig};
+pub use app::{App, AppConfig, AppState, AppStoreKey};
 pub use store::{AppStore, BitswapBlockstore};
 
 // Different type from `ChainEpoch` just because we might use epoch in a more traditional sense for checkpointing.
@@ -15,12 +18,21 @@ pub type BlockHeight = u64;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Exit codes of the process.
+///
+/// Supervisors can rely on these to decide on a restart policy: restarting after
+/// an `UnknownError` might help, but a `ConfigError` needs the configuration to be fixed,
+/// and a `Halt` is expected until the `halt_height` is raised or the binary upgraded.
 #[derive(Debug)]
 pub enum AppExitCode {
     /// Fendermint exited normally
     Ok = 0,
-    /// Fendermint exited with an unknown error
+    /// Fendermint exited with an unknown error, e.g. a runtime fault
     UnknownError = 1,
     /// Fendermint exited since it reached a block height equal to halt_height
     Halt = 2,
+    /// Fendermint exited because the settings could not be loaded or were invalid
+    ConfigError = 3,
+    /// Fendermint was asked to stop, but the components didn't stop in time
+    ShutdownTimeout = 4,
 }
@@ -1,6 +1,8 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use fendermint_app::shutdown::ShutdownTimeout;
+use fendermint_app::AppExitCode;
 pub use fendermint_app_options as options;
 pub use fendermint_app_settings as settings;
 use tracing_appender::{
@@ -105,7 +107,16 @@ async fn main() {
 
     if let Err(e) = cmd::exec(&opts).await {
         tracing::error!("failed to execute {:?}: {e:?}", opts);
-        std::process::exit(fendermint_app::AppExitCode::UnknownError as i32);
+
+        let exit_code = if e.downcast_ref::<cmd::ConfigError>().is_some() {
+            AppExitCode::ConfigError
+        } else if e.downcast_ref::<ShutdownTimeout>().is_some() {
+            AppExitCode::ShutdownTimeout
+        } else {
+            AppExitCode::UnknownError
+        };
+
+        std::process::exit(exit_code as i32);
     }
 }
 
@@ -43,6 +43,9 @@ pub mod app {
         // This metrics is available in CometBFT as well, but it's something that should increase even without subnets,
         // which can be a useful way to check if metrics work at all.
         ABCI_COMMITTED_BLOCK_HEIGHT: IntGauge = "Highest committed block";
+
+        STATE_HIST_RETAIN_HEIGHT: IntGauge = "Lowest height retained in the app state history";
+        STATE_HIST_NUM_PRUNED: IntCounter = "Number of app state history entries pruned since start";
     }
 }
 
@@ -149,6 +149,10 @@ impl<S: Subscriber> Layer<S> for MetricsLayer<S> {
             },
             NewBlock {
                 block_height              => set_gauge   ! &am::ABCI_COMMITTED_BLOCK_HEIGHT
+            },
+            StateHistoryPruned {
+                retain_height             => set_gauge   ! &am::STATE_HIST_RETAIN_HEIGHT,
+                num_pruned                => inc_counter ! &am::STATE_HIST_NUM_PRUNED,
             }
         });
     }
@@ -0,0 +1,378 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Versioning of the on-disk layout of the data the app keeps in its namespaces.
+//!
+//! Every change to the encoding of keys or values has to come with a [Migration]
+//! which brings the data written by the previous version up to date. They are
+//! applied in order when the [App](crate::App) starts.
+
+use anyhow::{bail, Context};
+use fendermint_storage::{
+    Codec, Encode, KVRead, KVReadable, KVResult, KVStore, KVTransaction, KVWritable, KVWrite,
+};
+
+use fendermint_vm_interpreter::fvm::upgrades::UpgradeHistory;
+
+use crate::app::{AppState, AppStoreKey};
+
+/// Version of the database schema.
+///
+/// A database without a version is considered to be at version 0.
+pub type SchemaVersion = u32;
+
+/// A change to the layout of the data.
+pub trait Migration<S, DB>
+where
+    S: KVStore,
+    DB: KVWritable<S>,
+{
+    /// The schema version the migration brings the database to.
+    fn version(&self) -> SchemaVersion;
+
+    /// Transform the data written under the previous version.
+    fn migrate(&self, tx: &mut DB::Tx<'_>) -> KVResult<()>;
+}
+
+/// The migrations to apply to a database, in the order of their versions.
+pub struct Migrations<S: KVStore, DB> {
+    /// Namespace where the schema version is stored.
+    namespace: S::Namespace,
+    migrations: Vec<Box<dyn Migration<S, DB>>>,
+}
+
+impl<S, DB> Migrations<S, DB>
+where
+    S: KVStore + Encode<AppStoreKey> + Codec<SchemaVersion>,
+    DB: KVWritable<S> + KVReadable<S>,
+{
+    pub fn new(namespace: S::Namespace) -> Self {
+        Self {
+            namespace,
+            migrations: Vec::new(),
+        }
+    }
+
+    /// Add the next migration, which has to have a higher version than the previous ones.
+    pub fn add<M>(mut self, migration: M) -> Self
+    where
+        M: Migration<S, DB> + 'static,
+    {
+        assert!(
+            migration.version() > self.latest_version(),
+            "migrations have to be added in the order of their versions"
+        );
+        self.migrations.push(Box::new(migration));
+        self
+    }
+
+    /// The schema version this binary writes.
+    pub fn latest_version(&self) -> SchemaVersion {
+        self.migrations.last().map(|m| m.version()).unwrap_or(0)
+    }
+
+    /// The schema version of the data on disk.
+    pub fn current_version(&self, db: &DB) -> anyhow::Result<SchemaVersion> {
+        let version = db
+            .read()
+            .get(&self.namespace, &AppStoreKey::SchemaVersion)
+            .context("failed to read schema version")?;
+
+        Ok(version.unwrap_or_default())
+    }
+
+    /// Apply the pending migrations, each in its own transaction.
+    ///
+    /// Fails if the data on disk was written by a newer version of the software.
+    pub fn run(&self, db: &DB) -> anyhow::Result<SchemaVersion> {
+        let current = self.current_version(db)?;
+        let latest = self.latest_version();
+
+        if current > latest {
+            bail!("the database schema version {current} is newer than the latest version {latest} supported by this binary");
+        }
+
+        for migration in self.migrations.iter().filter(|m| m.version() > current) {
+            let version = migration.version();
+            let mut tx = db.write();
+
+            let res = migration
+                .migrate(&mut tx)
+                .and_then(|()| tx.put(&self.namespace, &AppStoreKey::SchemaVersion, &version));
+
+            let res = match res {
+                Ok(()) => tx.commit(),
+                Err(e) => {
+                    tx.rollback()?;
+                    Err(e)
+                }
+            };
+
+            res.with_context(|| format!("failed to migrate the database to version {version}"))?;
+
+            tracing::info!(version, "migrated the database schema");
+        }
+
+        Ok(latest)
+    }
+}
+
+/// All the migrations of the app store.
+pub fn app_migrations<S, DB>(
+    schema_namespace: S::Namespace,
+    app_namespace: S::Namespace,
+) -> Migrations<S, DB>
+where
+    S: KVStore
+        + Codec<AppState>
+        + Codec<UpgradeHistory>
+        + Encode<AppStoreKey>
+        + Codec<SchemaVersion>
+        + 'static,
+    DB: KVWritable<S> + KVReadable<S> + 'static,
+{
+    Migrations::new(schema_namespace)
+        .add(Baseline {
+            app_namespace: app_namespace.clone(),
+        })
+        .add(UpgradeHistoryStart { app_namespace })
+}
+
+/// The layout at the time the schema version was introduced.
+///
+/// Nothing needs to change, but it checks that the existing app state can be read.
+struct Baseline<S: KVStore> {
+    app_namespace: S::Namespace,
+}
+
+impl<S, DB> Migration<S, DB> for Baseline<S>
+where
+    S: KVStore + Codec<AppState> + Encode<AppStoreKey>,
+    DB: KVWritable<S>,
+{
+    fn version(&self) -> SchemaVersion {
+        1
+    }
+
+    fn migrate(&self, tx: &mut DB::Tx<'_>) -> KVResult<()> {
+        let _: Option<AppState> = tx.get(&self.app_namespace, &AppStoreKey::State)?;
+        Ok(())
+    }
+}
+
+/// Start the record of the executed upgrades of databases created before it was kept.
+///
+/// The upgrades executed so far are unknown, so the history starts at the last committed height.
+/// A database without an app state yet is left alone, its history starts at genesis.
+struct UpgradeHistoryStart<S: KVStore> {
+    app_namespace: S::Namespace,
+}
+
+impl<S, DB> Migration<S, DB> for UpgradeHistoryStart<S>
+where
+    S: KVStore + Codec<AppState> + Codec<UpgradeHistory> + Encode<AppStoreKey>,
+    DB: KVWritable<S>,
+{
+    fn version(&self) -> SchemaVersion {
+        2
+    }
+
+    fn migrate(&self, tx: &mut DB::Tx<'_>) -> KVResult<()> {
+        let ns = &self.app_namespace;
+
+        let history: Option<UpgradeHistory> = tx.get(ns, &AppStoreKey::Upgrades)?;
+        if history.is_some() {
+            return Ok(());
+        }
+
+        let state: Option<AppState> = tx.get(ns, &AppStoreKey::State)?;
+        if let Some(state) = state {
+            let history = UpgradeHistory {
+                since: state.block_height,
+                executed: Vec::new(),
+            };
+            tx.put(ns, &AppStoreKey::Upgrades, &history)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use fendermint_storage::{
+        im::InMemoryBackend, KVError, KVRead, KVReadable, KVResult, KVTransaction, KVWritable,
+        KVWrite,
+    };
+
+    use cid::Cid;
+    use fendermint_vm_core::Timestamp;
+    use fendermint_vm_interpreter::fvm::state::FvmStateParams;
+    use fendermint_vm_interpreter::fvm::upgrades::UpgradeHistory;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::version::NetworkVersion;
+
+    use super::{app_migrations, Migration, Migrations, SchemaVersion};
+    use crate::app::{AppState, AppStoreKey};
+    use crate::AppStore;
+
+    type DB = InMemoryBackend<AppStore>;
+
+    const DATA_NS: &str = "data";
+
+    /// Appends its version to a list stored in the database, or fails.
+    struct Append {
+        version: SchemaVersion,
+        fail: bool,
+        applied: Arc<Mutex<Vec<SchemaVersion>>>,
+    }
+
+    impl Migration<AppStore, DB> for Append {
+        fn version(&self) -> SchemaVersion {
+            self.version
+        }
+
+        fn migrate(&self, tx: &mut <DB as KVWritable<AppStore>>::Tx<'_>) -> KVResult<()> {
+            let ns = DATA_NS.to_string();
+            let mut versions: Vec<SchemaVersion> = tx.get(&ns, &0u8)?.unwrap_or_default();
+            versions.push(self.version);
+            tx.put(&ns, &0u8, &versions)?;
+            if self.fail {
+                return Err(KVError::Unexpected("migration failed".into()));
+            }
+            self.applied.lock().unwrap().push(self.version);
+            Ok(())
+        }
+    }
+
+    fn migrations(
+        versions: &[SchemaVersion],
+        fail: Option<SchemaVersion>,
+    ) -> (Migrations<AppStore, DB>, Arc<Mutex<Vec<SchemaVersion>>>) {
+        let applied = Arc::new(Mutex::new(Vec::new()));
+        let ms = versions
+            .iter()
+            .fold(Migrations::new("schema".into()), |ms, version| {
+                ms.add(Append {
+                    version: *version,
+                    fail: fail == Some(*version),
+                    applied: applied.clone(),
+                })
+            });
+        (ms, applied)
+    }
+
+    fn stored_versions(db: &DB) -> Vec<SchemaVersion> {
+        db.read()
+            .get(&DATA_NS.to_string(), &0u8)
+            .unwrap()
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn run_pending_migrations_in_order() {
+        let db = DB::default();
+
+        let (ms, applied) = migrations(&[1, 2], None);
+        assert_eq!(ms.current_version(&db).unwrap(), 0);
+        assert_eq!(ms.run(&db).unwrap(), 2);
+        assert_eq!(ms.current_version(&db).unwrap(), 2);
+        assert_eq!(*applied.lock().unwrap(), vec![1, 2]);
+
+        // An upgraded binary only applies the new ones.
+        let (ms, applied) = migrations(&[1, 2, 5], None);
+        assert_eq!(ms.run(&db).unwrap(), 5);
+        assert_eq!(*applied.lock().unwrap(), vec![5]);
+        assert_eq!(stored_versions(&db), vec![1, 2, 5]);
+
+        // Running again is a no-op.
+        let (ms, applied) = migrations(&[1, 2, 5], None);
+        assert_eq!(ms.run(&db).unwrap(), 5);
+        assert!(applied.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn failed_migration_is_rolled_back() {
+        let db = DB::default();
+
+        let (ms, _) = migrations(&[1, 2, 3], Some(2));
+        assert!(ms.run(&db).is_err());
+        assert_eq!(ms.current_version(&db).unwrap(), 1);
+        assert_eq!(stored_versions(&db), vec![1]);
+    }
+
+    #[test]
+    fn refuse_newer_schema() {
+        let db = DB::default();
+
+        let (ms, _) = migrations(&[1, 2], None);
+        ms.run(&db).unwrap();
+
+        let (ms, applied) = migrations(&[1], None);
+        let err = ms.run(&db).unwrap_err();
+        assert!(err.to_string().contains("newer"));
+        assert!(applied.lock().unwrap().is_empty());
+        assert_eq!(ms.current_version(&db).unwrap(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn migrations_must_be_ordered() {
+        let _ = migrations(&[2, 1], None);
+    }
+
+    fn app_state(block_height: u64) -> AppState {
+        AppState {
+            block_height,
+            oldest_state_height: 0,
+            state_params: FvmStateParams {
+                state_root: Cid::default(),
+                timestamp: Timestamp(0),
+                network_version: NetworkVersion::V21,
+                base_fee: TokenAmount::from_atto(100),
+                circ_supply: TokenAmount::from_whole(1000),
+                chain_id: 1,
+                power_scale: 0,
+                app_version: 0,
+            },
+        }
+    }
+
+    fn upgrade_history(db: &DB) -> Option<UpgradeHistory> {
+        db.read()
+            .get(&"app".to_string(), &AppStoreKey::Upgrades)
+            .unwrap()
+    }
+
+    #[test]
+    fn start_upgrade_history_at_committed_height() {
+        let db = DB::default();
+        let ms = app_migrations::<AppStore, DB>("schema".into(), "app".into());
+
+        // Pretend the database was created by a version which only had the baseline schema.
+        let mut tx = db.write();
+        tx.put(&"app".to_string(), &AppStoreKey::State, &app_state(100))
+            .unwrap();
+        tx.put(&"schema".to_string(), &AppStoreKey::SchemaVersion, &1u32)
+            .unwrap();
+        tx.commit().unwrap();
+
+        assert!(upgrade_history(&db).is_none());
+        assert_eq!(ms.run(&db).unwrap(), 2);
+
+        let history = upgrade_history(&db).expect("history should be written");
+        assert_eq!(history.since, 100);
+        assert!(history.executed.is_empty());
+    }
+
+    #[test]
+    fn no_upgrade_history_before_genesis() {
+        let db = DB::default();
+        let ms = app_migrations::<AppStore, DB>("schema".into(), "app".into());
+
+        ms.run(&db).unwrap();
+        assert!(upgrade_history(&db).is_none());
+    }
+}
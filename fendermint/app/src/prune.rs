@@ -0,0 +1,161 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Pruning of the application state history.
+
+use fendermint_storage::{
+    Codec, IterRange, KVCollection, KVReadable, KVResult, KVStore, KVWritable,
+};
+use fendermint_vm_interpreter::fvm::state::FvmStateParams;
+
+use crate::BlockHeight;
+
+/// The lowest height to keep in the state history, or `None` if the history is unlimited.
+///
+/// Keeps the last `state_hist_size` states up to and including `state_height`,
+/// but never goes above any of the `protected` heights, which are still needed,
+/// e.g. by the newest snapshot or the latest bottom-up checkpoint.
+pub fn retain_height(
+    state_height: BlockHeight,
+    state_hist_size: u64,
+    protected: impl IntoIterator<Item = BlockHeight>,
+) -> Option<BlockHeight> {
+    if state_hist_size == 0 {
+        return None;
+    }
+    let retain_height = state_height
+        .saturating_add(1)
+        .saturating_sub(state_hist_size);
+    Some(protected.into_iter().fold(retain_height, std::cmp::min))
+}
+
+/// Delete the entries of the state history below `retain_height`.
+///
+/// Returns the number of deleted entries.
+pub fn prune_state_history<DB, S>(
+    db: &DB,
+    state_hist: &KVCollection<S, BlockHeight, FvmStateParams>,
+    retain_height: BlockHeight,
+) -> KVResult<usize>
+where
+    DB: KVReadable<S> + KVWritable<S>,
+    S: KVStore + Codec<BlockHeight> + Codec<FvmStateParams>,
+    S::Repr: Ord + 'static,
+{
+    // The CBOR encoding of unsigned integers preserves their order,
+    // so we can iterate the heights by their binary representation.
+    let heights = {
+        let tx = db.read();
+        state_hist
+            .iterate_range(&tx, IterRange::all().with_end(&retain_height))?
+            .map(|kv| kv.map(|(height, _)| height))
+            .collect::<KVResult<Vec<_>>>()?
+    };
+
+    if !heights.is_empty() {
+        db.with_write(|tx| state_hist.delete_many(tx, heights.iter()))?;
+    }
+
+    Ok(heights.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::Cid;
+    use fendermint_storage::{im::InMemoryBackend, KVCollection, KVReadable, KVWritable};
+    use fendermint_vm_core::Timestamp;
+    use fendermint_vm_interpreter::fvm::state::FvmStateParams;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::version::NetworkVersion;
+    use num_traits::Zero;
+
+    use super::{prune_state_history, retain_height};
+    use crate::{AppStore, BlockHeight};
+
+    type DB = InMemoryBackend<AppStore>;
+    type StateHist = KVCollection<AppStore, BlockHeight, FvmStateParams>;
+
+    fn state_params(height: BlockHeight) -> FvmStateParams {
+        FvmStateParams {
+            timestamp: Timestamp(height),
+            state_root: Cid::default(),
+            network_version: NetworkVersion::V21,
+            base_fee: TokenAmount::zero(),
+            circ_supply: TokenAmount::zero(),
+            chain_id: 0,
+            power_scale: 0,
+            app_version: 0,
+        }
+    }
+
+    /// Fill the history with states from height 1 up to and including `max_height`.
+    fn setup(max_height: BlockHeight) -> (DB, StateHist) {
+        let db = DB::default();
+        let state_hist = StateHist::new("state_hist".to_string());
+        db.with_write(|tx| {
+            for h in 1..=max_height {
+                state_hist.put(tx, &h, &state_params(h))?;
+            }
+            Ok(())
+        })
+        .unwrap();
+        (db, state_hist)
+    }
+
+    fn heights(db: &DB, state_hist: &StateHist) -> Vec<BlockHeight> {
+        let tx = db.read();
+        state_hist.iterate(&tx).map(|kv| kv.unwrap().0).collect()
+    }
+
+    #[test]
+    fn retain_unlimited() {
+        assert_eq!(retain_height(1000, 0, []), None);
+    }
+
+    #[test]
+    fn retain_last_states() {
+        assert_eq!(retain_height(1000, 10, []), Some(991));
+        assert_eq!(retain_height(1000, 1, []), Some(1000));
+        // Not enough history to prune anything yet.
+        assert_eq!(retain_height(5, 10, []), Some(0));
+    }
+
+    #[test]
+    fn retain_protected_heights() {
+        // The snapshot and the checkpoint are older than the history we want to keep.
+        assert_eq!(retain_height(1000, 10, [900, 950]), Some(900));
+        // Protected heights which are already retained don't matter.
+        assert_eq!(retain_height(1000, 10, [995]), Some(991));
+    }
+
+    #[test]
+    fn prune_below_retain_height() {
+        // Heights with different CBOR encoding lengths around 24 and 256.
+        let (db, state_hist) = setup(300);
+
+        let pruned = prune_state_history(&db, &state_hist, 250).unwrap();
+        assert_eq!(pruned, 249);
+        assert_eq!(heights(&db, &state_hist), (250..=300).collect::<Vec<_>>());
+
+        // Pruning again at the same height is a no-op.
+        let pruned = prune_state_history(&db, &state_hist, 250).unwrap();
+        assert_eq!(pruned, 0);
+
+        let pruned = prune_state_history(&db, &state_hist, 260).unwrap();
+        assert_eq!(pruned, 10);
+        assert_eq!(heights(&db, &state_hist).first(), Some(&260));
+    }
+
+    #[test]
+    fn prune_keeps_protected_height() {
+        let (db, state_hist) = setup(100);
+
+        // A snapshot at height 20 keeps the history from there, even though we want only 10 entries.
+        let retain = retain_height(100, 10, [20]).unwrap();
+        prune_state_history(&db, &state_hist, retain).unwrap();
+
+        assert_eq!(heights(&db, &state_hist), (20..=100).collect::<Vec<_>>());
+        let tx = db.read();
+        assert!(state_hist.get(&tx, &20).unwrap().is_some());
+        assert!(state_hist.get(&tx, &19).unwrap().is_none());
+    }
+}
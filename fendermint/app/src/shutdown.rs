@@ -0,0 +1,186 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Coordinated shutdown of the long running components of the node.
+//!
+//! Components are spawned through the [`ShutdownController`], which gives them a
+//! [`CancellationToken`] to watch. When the token is cancelled, they are expected to
+//! stop accepting new work, finish or flush what they are in the middle of, and return.
+//! The controller stops the components stage by stage, so that e.g. the database is only
+//! flushed once nothing is writing to it any more.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Context;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// The order in which components are stopped; earlier stages are stopped first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShutdownStage {
+    /// Components taking requests from the outside, e.g. the ABCI server.
+    Ingress,
+    /// Background components working on their own, e.g. the parent syncer or the snapshot manager.
+    Workers,
+    /// Persistence of whatever the other components left behind, e.g. flushing the database.
+    Storage,
+}
+
+/// Returned when the components didn't stop within the timeout.
+#[derive(Debug)]
+pub struct ShutdownTimeout {
+    pub timeout: Duration,
+    /// Names of the components which were still running.
+    pub pending: Vec<&'static str>,
+}
+
+impl std::fmt::Display for ShutdownTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "timed out after {:?} waiting for {} to stop",
+            self.timeout,
+            self.pending.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ShutdownTimeout {}
+
+#[derive(Default)]
+struct Stage {
+    token: CancellationToken,
+    tasks: Vec<(&'static str, JoinHandle<()>)>,
+}
+
+/// Keeps track of the running components and stops them in order.
+#[derive(Default)]
+pub struct ShutdownController {
+    stages: BTreeMap<ShutdownStage, Stage>,
+}
+
+impl ShutdownController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawn a component, passing it the token which is cancelled when its stage is stopped.
+    ///
+    /// The component is considered stopped when the future returns.
+    pub fn spawn<F, Fut>(&mut self, name: &'static str, stage: ShutdownStage, f: F)
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let stage = self.stages.entry(stage).or_default();
+        let handle = tokio::spawn(f(stage.token.clone()));
+        stage.tasks.push((name, handle));
+    }
+
+    /// Stop the components stage by stage, waiting for each stage to finish before the next.
+    ///
+    /// Gives up after the timeout, leaving the rest of the components running.
+    pub async fn shutdown(self, timeout: Duration) -> Result<(), ShutdownTimeout> {
+        let pending = Arc::new(Mutex::new(Vec::new()));
+
+        let stop = {
+            let pending = pending.clone();
+            async move {
+                for (stage, Stage { token, tasks }) in self.stages {
+                    tracing::info!(?stage, "stopping components");
+                    *pending.lock().unwrap() = tasks.iter().map(|(name, _)| *name).collect();
+                    token.cancel();
+
+                    for (name, task) in tasks {
+                        match task.await {
+                            Err(e) if e.is_panic() => {
+                                tracing::error!(component = name, "component panicked")
+                            }
+                            _ => tracing::info!(component = name, "component stopped"),
+                        }
+                        pending.lock().unwrap().retain(|n| *n != name);
+                    }
+                }
+            }
+        };
+
+        if tokio::time::timeout(timeout, stop).await.is_err() {
+            let pending = pending.lock().unwrap().clone();
+            return Err(ShutdownTimeout { timeout, pending });
+        }
+
+        Ok(())
+    }
+}
+
+/// Wait for a SIGTERM or SIGINT, returning the name of the signal.
+pub async fn shutdown_signal() -> anyhow::Result<&'static str> {
+    let mut sigterm =
+        signal(SignalKind::terminate()).context("failed to install SIGTERM handler")?;
+
+    tokio::select! {
+        _ = sigterm.recv() => Ok("SIGTERM"),
+        res = tokio::signal::ctrl_c() => {
+            res.context("failed to install SIGINT handler")?;
+            Ok("SIGINT")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::{ShutdownController, ShutdownStage};
+
+    type Log = Arc<Mutex<Vec<&'static str>>>;
+
+    /// A component which takes some time to wrap up after being asked to stop,
+    /// then records that it has stopped.
+    fn register(c: &mut ShutdownController, log: &Log, name: &'static str, stage: ShutdownStage) {
+        let log = log.clone();
+        c.spawn(name, stage, move |token| async move {
+            token.cancelled().await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            log.lock().unwrap().push(name);
+        });
+    }
+
+    #[tokio::test]
+    async fn shutdown_in_stage_order() {
+        let log = Log::default();
+        let mut c = ShutdownController::new();
+
+        // Registered in the wrong order on purpose.
+        register(&mut c, &log, "db", ShutdownStage::Storage);
+        register(&mut c, &log, "syncer", ShutdownStage::Workers);
+        register(&mut c, &log, "abci", ShutdownStage::Ingress);
+
+        c.shutdown(Duration::from_secs(5)).await.unwrap();
+
+        assert_eq!(*log.lock().unwrap(), vec!["abci", "syncer", "db"]);
+    }
+
+    #[tokio::test]
+    async fn shutdown_timeout() {
+        let log = Log::default();
+        let mut c = ShutdownController::new();
+
+        register(&mut c, &log, "abci", ShutdownStage::Ingress);
+        // Ignores the token.
+        c.spawn("stuck", ShutdownStage::Workers, |_| {
+            std::future::pending::<()>()
+        });
+        register(&mut c, &log, "db", ShutdownStage::Storage);
+
+        let err = c.shutdown(Duration::from_millis(200)).await.unwrap_err();
+
+        assert_eq!(err.pending, vec!["stuck"]);
+        // The storage is not flushed while something might still be writing to it.
+        assert_eq!(*log.lock().unwrap(), vec!["abci"]);
+    }
+}
@@ -9,7 +9,7 @@ use fendermint_vm_interpreter::fvm::{
     FvmApplyRet, FvmCheckRet, FvmQueryRet, PowerUpdates,
 };
 use fendermint_vm_message::signed::DomainHash;
-use fendermint_vm_snapshot::{SnapshotItem, SnapshotManifest};
+use fendermint_vm_snapshot::{PartCompression, SnapshotItem, SnapshotManifest};
 use fvm_shared::{address::Address, error::ExitCode, event::StampedEvent, ActorID};
 use prost::Message;
 use serde::{Deserialize, Serialize};
@@ -25,6 +25,12 @@ use crate::{app::AppError, BlockHeight};
 struct SnapshotMetadata {
     size: u64,
     state_params: FvmStateParams,
+    /// Missing from snapshots offered by nodes which don't record chunk checksums.
+    #[serde(default)]
+    chunk_checksums: Vec<tendermint::Hash>,
+    /// Missing from snapshots offered by nodes which don't compress chunks.
+    #[serde(default)]
+    compression: PartCompression,
 }
 
 /// IPLD encoding of data types we know we must be able to encode.
@@ -249,6 +255,8 @@ pub fn to_query(ret: FvmQueryRet, block_height: BlockHeight) -> anyhow::Result<r
         FvmQueryRet::Call(_) | FvmQueryRet::EstimateGas(_) => ExitCode::OK,
         FvmQueryRet::StateParams(_) => ExitCode::OK,
         FvmQueryRet::BuiltinActors(_) => ExitCode::OK,
+        // The caller needs to look into the `value` to see if the hash was found, or why not.
+        FvmQueryRet::BlockHash(_) => ExitCode::OK,
     };
 
     // The return value has a `key` field which is supposed to be set to the data matched.
@@ -289,6 +297,10 @@ pub fn to_query(ret: FvmQueryRet, block_height: BlockHeight) -> anyhow::Result<r
             let v = ipld_encode!(ba);
             (Vec::new(), v)
         }
+        FvmQueryRet::BlockHash(bh) => {
+            let v = ipld_encode!(bh);
+            (Vec::new(), v)
+        }
     };
 
     // The height here is the height of the block that was committed, not in which the app hash appeared.
@@ -380,6 +392,8 @@ pub fn to_snapshot(snapshot: SnapshotItem) -> anyhow::Result<tendermint::abci::t
     let metadata = SnapshotMetadata {
         size: snapshot.manifest.size,
         state_params: snapshot.manifest.state_params,
+        chunk_checksums: snapshot.manifest.chunk_checksums,
+        compression: snapshot.manifest.compression,
     };
 
     Ok(tendermint::abci::types::Snapshot {
@@ -420,6 +434,8 @@ pub fn from_snapshot(
         size: metadata.size,
         chunks: offer.snapshot.chunks,
         checksum,
+        chunk_checksums: metadata.chunk_checksums,
+        compression: metadata.compression,
         state_params: metadata.state_params,
         version: offer.snapshot.format,
     };
@@ -29,7 +29,6 @@ use fvm_shared::{chainid::ChainID, error::ExitCode};
 use jsonrpc_v2::Params;
 use rand::Rng;
 use tendermint::block::Height;
-use tendermint_rpc::endpoint::{self, status};
 use tendermint_rpc::SubscriptionClient;
 use tendermint_rpc::{
     endpoint::{block, block_results, broadcast::tx_sync, consensus_params, header},
@@ -39,15 +38,14 @@ use tendermint_rpc::{
 use fil_actors_evm_shared::uints;
 
 use crate::conv::from_eth::{self, to_fvm_message};
-use crate::conv::from_tm::{self, msg_hash, to_chain_message, to_cumulative, to_eth_block_zero};
+use crate::conv::from_tm::{
+    self, msg_hash, to_chain_message, to_eth_block_zero, BlockReceiptContext,
+};
 use crate::error::{error_with_revert, OutOfSequence};
-use crate::filters::{matches_topics, FilterId, FilterKind, FilterRecords};
+use crate::filters::{check_block_range, matches_topics, FilterId, FilterKind, FilterRecords};
+use crate::mpool::{pending_nonce, pending_spend, pending_txs};
 use crate::{
-    conv::{
-        from_eth::to_fvm_address,
-        from_fvm::to_eth_tokens,
-        from_tm::{to_eth_receipt, to_eth_transaction},
-    },
+    conv::{from_eth::to_fvm_address, from_fvm::to_eth_tokens, from_tm::to_eth_transaction},
     error, JsonRpcData, JsonRpcResult,
 };
 
@@ -115,7 +113,7 @@ where
     // we may be able to de-duplicate a lot of this code from fee_history
     let latest_h: u64 = latest_h.into();
     let mut blk = latest_h;
-    while blk > latest_h - data.gas_opt.num_blocks_max_prio_fee {
+    while blk > latest_h.saturating_sub(data.gas_opt.num_blocks_max_prio_fee) {
         let block = data
             .block_by_height(blk.into())
             .await
@@ -153,11 +151,11 @@ where
     }
 
     // compute median gas price
-    let mut median = crate::gas::median_gas_premium(&mut premiums, block_gas_limit);
-    let min_premium = data.gas_opt.min_gas_premium.clone();
-    if median < min_premium {
-        median = min_premium;
-    }
+    let mut median = crate::gas::suggested_gas_premium(
+        &mut premiums,
+        block_gas_limit,
+        &data.gas_opt.min_gas_premium,
+    );
 
     // add some noise to normalize behaviour of message selection
     // mean 1, stddev 0.005 => 95% within +-1%
@@ -168,7 +166,7 @@ where
     let coeff: u64 = ((noise * (1 << precision) as f64) as u64) + 1;
 
     median *= BigInt::from(coeff);
-    median.div_ceil(BigInt::from(1 << PRECISION));
+    median = median.div_ceil(BigInt::from(1u64 << PRECISION));
 
     Ok(to_eth_tokens(&median)?)
 }
@@ -185,10 +183,14 @@ pub async fn fee_history<C>(
 where
     C: Client + Sync + Send,
 {
-    if block_count > et::U256::from(data.gas_opt.max_fee_hist_size) {
+    if reward_percentiles.windows(2).any(|w| w[0] > w[1])
+        || reward_percentiles
+            .iter()
+            .any(|p| !(0.0..=100.0).contains(p))
+    {
         return error(
             ExitCode::USR_ILLEGAL_ARGUMENT,
-            "block_count must be <= 1024",
+            "reward percentiles must be increasing and between 0 and 100",
         );
     }
 
@@ -199,7 +201,8 @@ where
         reward: Vec::new(),
     };
     let mut block_number = last_block;
-    let mut block_count = block_count.as_usize();
+    let mut block_count =
+        crate::gas::fee_history_block_count(block_count, data.gas_opt.max_fee_hist_size);
 
     let get_base_fee = |height: Height| {
         data.client
@@ -258,25 +261,11 @@ where
             }
             premiums.sort();
 
-            let premium_gas_used: i64 = premiums.iter().map(|(_, gas)| *gas).sum();
-
-            let rewards: Result<Vec<et::U256>, _> = reward_percentiles
-                .iter()
-                .map(|p| {
-                    if premiums.is_empty() {
-                        Ok(et::U256::zero())
-                    } else {
-                        let threshold_gas_used = (premium_gas_used as f64 * p / 100f64) as i64;
-                        let mut sum_gas_used = 0;
-                        let mut idx = 0;
-                        while sum_gas_used < threshold_gas_used && idx < premiums.len() - 1 {
-                            sum_gas_used += premiums[idx].1;
-                            idx += 1;
-                        }
-                        to_eth_tokens(&premiums[idx].0)
-                    }
-                })
-                .collect();
+            let rewards: Result<Vec<et::U256>, _> =
+                crate::gas::reward_percentiles(&premiums, &reward_percentiles)
+                    .iter()
+                    .map(to_eth_tokens)
+                    .collect();
 
             hist.oldest_block = et::U256::from(height.value());
             hist.base_fee_per_gas.push(to_eth_tokens(&base_fee)?);
@@ -326,7 +315,16 @@ where
     let res = data.client.actor_state(&addr, height).await?;
 
     match res.value {
-        Some((_, state)) => Ok(to_eth_tokens(&state.balance)?),
+        Some((_, state)) => {
+            let balance = to_eth_tokens(&state.balance)?;
+            if is_pending(&block_id) {
+                // Best effort to account for what the transactions we know about could spend.
+                let pending = pending_txs(&data.tx_cache, &data.tx_buffer, &addr);
+                Ok(balance.saturating_sub(pending_spend(state.sequence, &pending)))
+            } else {
+                Ok(balance)
+            }
+        }
         None => Ok(et::U256::zero()),
     }
 }
@@ -467,15 +465,25 @@ where
     let height = data.query_height(block_id).await?;
     let res = data.client.actor_state(&addr, height).await?;
 
-    match res.value {
-        Some((_, state)) => {
-            let nonce = state.sequence;
-            Ok(et::U64::from(nonce))
-        }
-        None => Ok(et::U64::zero()),
+    let nonce = match res.value {
+        Some((_, state)) => state.sequence,
+        None => 0,
+    };
+
+    if is_pending(&block_id) {
+        // Count the transactions which have been submitted or buffered, but not included yet,
+        // so that wallets sending several transactions in a row don't reuse the same nonce.
+        let pending = pending_txs(&data.tx_cache, &data.tx_buffer, &addr);
+        Ok(et::U64::from(pending_nonce(nonce, &pending)))
+    } else {
+        Ok(et::U64::from(nonce))
     }
 }
 
+fn is_pending(block_id: &et::BlockId) -> bool {
+    matches!(block_id, et::BlockId::Number(et::BlockNumber::Pending))
+}
+
 /// Returns the receipt of a transaction by transaction hash.
 pub async fn get_transaction_receipt<C>(
     data: JsonRpcData<C>,
@@ -487,22 +495,20 @@ where
     if let Some(res) = data.tx_by_hash(tx_hash).await? {
         let header: header::Response = data.tm().header(res.height).await?;
         let block_results: block_results::Response = data.tm().block_results(res.height).await?;
-        let cumulative = to_cumulative(&block_results);
         let state_params = data
             .client
             .state_params(FvmQueryHeight::Height(header.header.height.value()))
             .await?;
         let msg = to_chain_message(&res.tx)?;
         if let ChainMessage::Signed(msg) = msg {
-            let receipt = to_eth_receipt(
-                &msg,
-                &res,
-                &cumulative,
+            let ctx = BlockReceiptContext::new(
                 &header.header,
+                &block_results,
                 &state_params.value.base_fee,
-            )
-            .await
-            .context("failed to convert to receipt")?;
+            );
+            let receipt = ctx
+                .to_eth_receipt(&msg, res.index, &res.tx, &res.tx_result)
+                .context("failed to convert to receipt")?;
 
             Ok(Some(receipt))
         } else {
@@ -513,56 +519,55 @@ where
     }
 }
 
-/// Returns receipts for all the transactions in a block.
+/// Returns receipts for all the transactions in a block, or null if the block is unknown.
+///
+/// The receipts are produced from a single `block_results` query.
 pub async fn get_block_receipts<C>(
     data: JsonRpcData<C>,
-    Params((block_number,)): Params<(et::BlockNumber,)>,
-) -> JsonRpcResult<Vec<et::TransactionReceipt>>
+    Params((block_id,)): Params<(et::BlockId,)>,
+) -> JsonRpcResult<Option<Vec<et::TransactionReceipt>>>
 where
     C: Client + Sync + Send,
 {
-    let block = data.block_by_height(block_number).await?;
+    let block = match block_id {
+        et::BlockId::Hash(h) => data.block_by_hash_opt(h).await?,
+        et::BlockId::Number(block_number) => {
+            let is_future = match block_number {
+                et::BlockNumber::Number(height) => {
+                    height.as_u64() > data.latest_height().await?.value()
+                }
+                _ => false,
+            };
+            if is_future {
+                None
+            } else {
+                Some(data.block_by_height(block_number).await?)
+            }
+        }
+    };
+
+    let Some(block) = block else {
+        return Ok(None);
+    };
+
     if from_tm::is_block_zero(&block) {
-        return Ok(Vec::new());
+        return Ok(Some(Vec::new()));
     }
+
     let height = block.header.height;
     let state_params = data
         .client
         .state_params(FvmQueryHeight::Height(height.value()))
         .await?;
     let block_results: block_results::Response = data.tm().block_results(height).await?;
-    let cumulative = to_cumulative(&block_results);
-    let mut receipts = Vec::new();
 
-    for (index, (tx, tx_result)) in block
-        .data
-        .into_iter()
-        .zip(block_results.txs_results.unwrap_or_default())
-        .enumerate()
-    {
-        let msg = to_chain_message(&tx)?;
-        if let ChainMessage::Signed(msg) = msg {
-            let result = endpoint::tx::Response {
-                hash: Default::default(), // Shouldn't use this anyway.
-                height,
-                index: index as u32,
-                tx_result,
-                tx,
-                proof: None,
-            };
+    let ctx = BlockReceiptContext::new(&block.header, &block_results, &state_params.value.base_fee);
+    let receipts = ctx.to_eth_receipts(
+        block.data(),
+        block_results.txs_results.as_deref().unwrap_or_default(),
+    )?;
 
-            let receipt = to_eth_receipt(
-                &msg,
-                &result,
-                &cumulative,
-                &block.header,
-                &state_params.value.base_fee,
-            )
-            .await?;
-            receipts.push(receipt)
-        }
-    }
-    Ok(receipts)
+    Ok(Some(receipts))
 }
 
 /// Returns the number of uncles in a block from a block matching the given block hash.
@@ -630,10 +635,7 @@ where
     let sender = msg.from;
     let nonce = msg.sequence;
 
-    let msg = SignedMessage {
-        message: msg,
-        signature: Signature::new_secp256k1(sig.to_vec()),
-    };
+    let msg = SignedMessage::new_unchecked(msg, Signature::new_secp256k1(sig.to_vec()));
     let msg = ChainMessage::Signed(msg);
     let bz: Vec<u8> = SignedMessageFactory::serialize(&msg)?;
 
@@ -669,6 +671,9 @@ where
             }
         }
 
+        // The transaction is not going to be included, so it should not count as pending.
+        data.tx_cache.remove(&msghash);
+
         error_with_revert(exit_code, res.log, bz)
     }
 }
@@ -847,41 +852,14 @@ where
 }
 
 /// Returns an object with data about the sync status or false.
+///
+/// The status of CometBFT is cached briefly, so this is cheap to call by load balancers.
 pub async fn syncing<C>(data: JsonRpcData<C>) -> JsonRpcResult<et::SyncingStatus>
 where
     C: Client + Sync + Send,
 {
-    let status: status::Response = data.tm().status().await.context("failed to fetch status")?;
-    let info = status.sync_info;
-    let status = if !info.catching_up {
-        et::SyncingStatus::IsFalse
-    } else {
-        let progress = et::SyncProgress {
-            // This would be the block we executed.
-            current_block: et::U64::from(info.latest_block_height.value()),
-            // This would be the block we know about but haven't got to yet.
-            highest_block: et::U64::from(info.latest_block_height.value()),
-            // This would be the block we started syncing from.
-            starting_block: Default::default(),
-            pulled_states: None,
-            known_states: None,
-            healed_bytecode_bytes: None,
-            healed_bytecodes: None,
-            healed_trienode_bytes: None,
-            healed_trienodes: None,
-            healing_bytecode: None,
-            healing_trienodes: None,
-            synced_account_bytes: None,
-            synced_accounts: None,
-            synced_bytecode_bytes: None,
-            synced_bytecodes: None,
-            synced_storage: None,
-            synced_storage_bytes: None,
-        };
-        et::SyncingStatus::IsSyncing(Box::new(progress))
-    };
-
-    Ok(status)
+    let info = data.sync_info.sync_info().await?;
+    Ok(from_tm::to_eth_syncing_status(&info))
 }
 
 /// Returns an array of all logs matching a given filter object.
@@ -941,6 +919,12 @@ where
         }
     };
 
+    check_block_range(
+        from_height.value(),
+        to_height.value(),
+        data.max_log_block_range,
+    )?;
+
     let addrs = match &filter.address {
         Some(et::ValueOrArray::Value(addr)) => vec![*addr],
         Some(et::ValueOrArray::Array(addrs)) => addrs.clone(),
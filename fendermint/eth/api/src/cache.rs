@@ -9,7 +9,7 @@ use std::{
 use anyhow::Context;
 use cid::Cid;
 use lru_time_cache::LruCache;
-use tendermint_rpc::Client;
+use tendermint_rpc::{endpoint::status, Client};
 
 use fvm_shared::{
     address::{Address, Payload},
@@ -191,15 +191,136 @@ where
     }
 }
 
+/// Briefly remember the sync info of CometBFT, so we don't ask for its status on every request.
+#[derive(Clone)]
+pub struct SyncInfoCache<C> {
+    client: FendermintClient<C>,
+    cache: Cache<(), status::SyncInfo>,
+}
+
+impl<C> SyncInfoCache<C>
+where
+    C: Client + Sync + Send,
+{
+    pub fn new(client: FendermintClient<C>, ttl: Duration) -> Self {
+        Self {
+            client,
+            cache: Cache::new_with_ttl(1, ttl),
+        }
+    }
+
+    pub async fn sync_info(&self) -> anyhow::Result<status::SyncInfo> {
+        if let Some(info) = self.cache.get(&()) {
+            return Ok(info);
+        }
+
+        let status: status::Response = self
+            .client
+            .underlying()
+            .status()
+            .await
+            .context("failed to fetch status")?;
+
+        self.cache.insert((), status.sync_info.clone());
+
+        Ok(status.sync_info)
+    }
+
+    /// Check whether CometBFT is still catching up with its peers.
+    pub async fn is_catching_up(&self) -> anyhow::Result<bool> {
+        self.sync_info().await.map(|info| info.catching_up)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::cache::AddressCache;
+    use crate::cache::{AddressCache, SyncInfoCache};
+    use crate::conv::from_tm::to_eth_syncing_status;
     use crate::state::ActorType;
     use cid::Cid;
+    use ethers_core::types as et;
     use fendermint_rpc::FendermintClient;
     use fvm_shared::address::Address;
     use std::str::FromStr;
-    use tendermint_rpc::MockClient;
+    use std::time::Duration;
+    use tendermint_rpc::{Method, MockClient, MockRequestMethodMatcher};
+
+    /// A `status` response of CometBFT with the given sync info.
+    fn status_response(catching_up: bool, earliest_height: u64, latest_height: u64) -> String {
+        let hash = "0".repeat(64);
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "",
+            "result": {
+                "node_info": {
+                    "protocol_version": { "p2p": "8", "block": "11", "app": "0" },
+                    "id": "0".repeat(40),
+                    "listen_addr": "tcp://0.0.0.0:26656",
+                    "network": "test",
+                    "version": "0.37.1",
+                    "channels": "40202122233038606100",
+                    "moniker": "test",
+                    "other": { "tx_index": "on", "rpc_address": "tcp://0.0.0.0:26657" }
+                },
+                "sync_info": {
+                    "latest_block_hash": hash,
+                    "latest_app_hash": hash,
+                    "latest_block_height": latest_height.to_string(),
+                    "latest_block_time": "2024-01-01T00:00:00Z",
+                    "earliest_block_hash": hash,
+                    "earliest_app_hash": hash,
+                    "earliest_block_height": earliest_height.to_string(),
+                    "earliest_block_time": "2024-01-01T00:00:00Z",
+                    "catching_up": catching_up
+                },
+                "validator_info": {
+                    "address": "0".repeat(40),
+                    "pub_key": {
+                        "type": "tendermint/PubKeyEd25519",
+                        "value": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA="
+                    },
+                    "voting_power": "0"
+                }
+            }
+        })
+        .to_string()
+    }
+
+    fn sync_info_cache(response: String) -> SyncInfoCache<MockClient<MockRequestMethodMatcher>> {
+        let matcher = MockRequestMethodMatcher::default().map(Method::Status, Ok(response));
+        let client = FendermintClient::new(MockClient::new(matcher).0);
+        SyncInfoCache::new(client, Duration::from_secs(1))
+    }
+
+    #[tokio::test]
+    async fn test_syncing_when_caught_up() {
+        let cache = sync_info_cache(status_response(false, 1, 100));
+
+        assert!(!cache.is_catching_up().await.unwrap());
+
+        let info = cache.sync_info().await.unwrap();
+        assert!(matches!(
+            to_eth_syncing_status(&info),
+            et::SyncingStatus::IsFalse
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_syncing_when_catching_up() {
+        let cache = sync_info_cache(status_response(true, 50, 100));
+
+        assert!(cache.is_catching_up().await.unwrap());
+
+        let info = cache.sync_info().await.unwrap();
+        match to_eth_syncing_status(&info) {
+            et::SyncingStatus::IsSyncing(progress) => {
+                assert_eq!(progress.starting_block, et::U64::from(50));
+                assert_eq!(progress.current_block, et::U64::from(100));
+                assert_eq!(progress.highest_block, et::U64::from(100));
+            }
+            other => panic!("expected to be syncing: {other:?}"),
+        }
+    }
 
     #[test]
     fn test_read_and_write_addr_to_actor_type() {
@@ -1,36 +1,64 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::{pin::Pin, time::Duration};
+use std::time::Duration;
 
 use anyhow::Context;
 use async_trait::async_trait;
 use fendermint_rpc::client::{http_client, ws_client};
-use futures::Future;
+use futures::StreamExt;
 use tendermint_rpc::{
-    error::ErrorDetail, query::Query, Client, Error, HttpClient, SimpleRequest, Subscription,
-    SubscriptionClient, Url, WebSocketClient, WebSocketClientDriver, WebSocketClientUrl,
+    client::sync::{unbounded, ChannelTx},
+    error::ErrorDetail,
+    event::Event,
+    query::Query,
+    Client, Error, HttpClient, SimpleRequest, Subscription, SubscriptionClient, Url,
+    WebSocketClient, WebSocketClientUrl,
 };
+use tokio::task::JoinHandle;
 
 /// A mixed HTTP and WebSocket client. Uses HTTP to perform all
 /// the JSON-RPC requests except the ones which require subscription,
 /// which go through a WebSocket client.
 ///
 /// The WebSocket client is expected to lose connection with CometBFT,
-/// in which case it will be re-established in the background.
+/// in which case it will be re-established in the background, with
+/// exponential backoff between the attempts.
 ///
-/// Existing subscriptions should receive an error and they can try
-/// re-subscribing through the Ethereum API facade, which should create
-/// new subscriptions through a fresh CometBFT client.
+/// Existing subscriptions are re-established on the new connection,
+/// without the subscribers noticing, apart from the events missed while
+/// the connection was down. If the driver gives up reconnecting they
+/// receive an error and they can try re-subscribing through the Ethereum
+/// API facade later.
 #[derive(Clone)]
 pub struct HybridClient {
     http_client: HttpClient,
     cmd_tx: tokio::sync::mpsc::UnboundedSender<DriverCommand>,
 }
 
+/// Parameters of the exponential backoff when (re)connecting to the WebSocket.
+#[derive(Debug, Clone)]
+pub struct ReconnectOpt {
+    /// Delay after the first failed attempt, doubled after every further one.
+    pub initial_delay: Duration,
+    /// Upper limit of the delay between attempts.
+    pub max_delay: Duration,
+    /// Number of retries after which the driver gives up, or `None` to retry forever.
+    pub max_retries: Option<u32>,
+}
+
+impl ReconnectOpt {
+    /// The delay before the given retry, starting from 0.
+    fn delay(&self, retry: u32) -> Duration {
+        self.initial_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay)
+    }
+}
+
 pub struct HybridClientDriver {
     ws_url: WebSocketClientUrl,
-    retry_delay: Duration,
+    reconnect_opt: ReconnectOpt,
     cmd_rx: tokio::sync::mpsc::UnboundedReceiver<DriverCommand>,
 }
 
@@ -43,11 +71,28 @@ enum DriverCommand {
     Close,
 }
 
+type SubscriptionTx = ChannelTx<Result<Event, Error>>;
+
+/// An open WebSocket connection.
+struct Connection {
+    client: WebSocketClient,
+    driver: JoinHandle<Result<(), Error>>,
+}
+
+/// A subscription handed out to a subscriber, which outlives the WebSocket connections.
+struct ActiveSubscription {
+    query: Query,
+    tx: SubscriptionTx,
+    /// Forwards the events of the subscription on the current connection to the subscriber.
+    /// Returns `false` if the subscriber has gone away.
+    forwarder: JoinHandle<bool>,
+}
+
 impl HybridClient {
     pub fn new(
         http_url: Url,
         ws_url: WebSocketClientUrl,
-        retry_delay: Duration,
+        reconnect_opt: ReconnectOpt,
     ) -> anyhow::Result<(Self, HybridClientDriver)> {
         let http_client =
             http_client(http_url, None).context("failed to create Tendermint client")?;
@@ -61,7 +106,7 @@ impl HybridClient {
 
         let driver = HybridClientDriver {
             ws_url,
-            retry_delay,
+            reconnect_opt,
             cmd_rx,
         };
 
@@ -110,84 +155,396 @@ impl SubscriptionClient for HybridClient {
     }
 }
 
+/// Something the driver has to react to.
+enum DriverEvent {
+    Command(Option<DriverCommand>),
+    Disconnected(String),
+}
+
 impl HybridClientDriver {
     pub async fn run(mut self) {
-        let mut client = self.ws_client().await;
-
-        while let Some(cmd) = self.cmd_rx.recv().await {
-            match cmd {
-                DriverCommand::Subscribe(query, tx) => {
-                    client = self
-                        .send_loop(client, tx, |client| {
-                            let query = query.clone();
-                            Box::pin(async move { client.subscribe(query.clone()).await })
-                        })
-                        .await;
+        let mut conn = self.connect().await;
+        let mut subs = Vec::new();
+
+        loop {
+            let event = tokio::select! {
+                cmd = self.cmd_rx.recv() => DriverEvent::Command(cmd),
+                reason = disconnected(&mut conn) => DriverEvent::Disconnected(reason),
+            };
+
+            match event {
+                DriverEvent::Command(Some(DriverCommand::Subscribe(query, tx))) => {
+                    let res = self.subscribe(&mut conn, &mut subs, query).await;
+                    let _ = tx.send(res);
                 }
-                DriverCommand::Unsubscribe(query, tx) => {
-                    client = self
-                        .send_loop(client, tx, |client| {
-                            let query = query.clone();
-                            Box::pin(async move { client.unsubscribe(query.clone()).await })
-                        })
-                        .await;
+                DriverEvent::Command(Some(DriverCommand::Unsubscribe(query, tx))) => {
+                    let query_str = query.to_string();
+                    subs.retain(|sub: &ActiveSubscription| {
+                        let keep = sub.query.to_string() != query_str;
+                        if !keep {
+                            sub.forwarder.abort();
+                        }
+                        keep
+                    });
+                    let res = match &conn {
+                        Some(conn) => conn.client.unsubscribe(query).await,
+                        None => Ok(()),
+                    };
+                    let _ = tx.send(res);
                 }
-                DriverCommand::Close => {
+                DriverEvent::Command(Some(DriverCommand::Close) | None) => {
                     break;
                 }
+                DriverEvent::Disconnected(reason) => {
+                    tracing::warn!(
+                        reason,
+                        url = self.ws_url.to_string(),
+                        subscriptions = subs.len(),
+                        "lost connection to Tendermint WebSocket; reconnecting..."
+                    );
+                    conn = self.reconnect(&mut subs).await;
+                }
             }
         }
-        let _ = client.close();
+
+        if let Some(conn) = conn {
+            let _ = conn.client.close();
+        }
     }
 
-    /// Try to send something to the socket. If it fails, reconnect and send again.
-    async fn send_loop<F, T>(
+    /// Subscribe on the current connection, reconnecting first if necessary.
+    async fn subscribe(
         &self,
-        mut client: WebSocketClient,
-        tx: tokio::sync::oneshot::Sender<Result<T, Error>>,
-        f: F,
-    ) -> WebSocketClient
-    where
-        F: Fn(WebSocketClient) -> Pin<Box<dyn Future<Output = Result<T, Error>> + Send>>,
-    {
+        conn: &mut Option<Connection>,
+        subs: &mut Vec<ActiveSubscription>,
+        query: Query,
+    ) -> Result<Subscription, Error> {
+        let mut retried = false;
         loop {
-            match f(client.clone()).await {
-                Err(e) if matches!(e.detail(), ErrorDetail::ChannelSend(_)) => {
-                    client = self.ws_client().await;
+            if conn.is_none() {
+                *conn = self.reconnect(subs).await;
+            }
+            let Some(c) = conn.as_ref() else {
+                return Err(Error::client_internal(
+                    "not connected to the Tendermint WebSocket".to_string(),
+                ));
+            };
+            match c.client.subscribe(query.clone()).await {
+                // The connection is gone, but the driver hasn't noticed yet.
+                Err(e) if !retried && matches!(e.detail(), ErrorDetail::ChannelSend(_)) => {
+                    *conn = None;
+                    retried = true;
                 }
-                res => {
-                    let _ = tx.send(res);
-                    return client;
+                Err(e) => return Err(e),
+                Ok(sub) => {
+                    let (tx, rx) = unbounded();
+                    let id = sub.id().to_string();
+                    subs.push(ActiveSubscription {
+                        query: query.clone(),
+                        tx: tx.clone(),
+                        forwarder: tokio::spawn(forward(sub, tx)),
+                    });
+                    return Ok(Subscription::new(id, query, rx));
                 }
             }
         }
     }
 
-    /// Connect to the WebSocket and start the driver, returning the client.
-    async fn ws_client(&self) -> WebSocketClient {
-        let (client, driver) = self.ws_connect().await;
-        tokio::spawn(async move { driver.run().await });
-        client
+    /// Connect again and re-establish the subscriptions which still have subscribers.
+    async fn reconnect(&self, subs: &mut Vec<ActiveSubscription>) -> Option<Connection> {
+        // The forwarders finish when the subscriptions of the old connection are closed.
+        let mut alive = Vec::new();
+        for sub in subs.drain(..) {
+            if sub.forwarder.await.unwrap_or(false) {
+                alive.push((sub.query, sub.tx));
+            }
+        }
+
+        let conn = match self.connect().await {
+            Some(conn) => conn,
+            None => {
+                for (_, tx) in alive {
+                    let _ = tx.send(Err(Error::client_internal(
+                        "lost connection to the Tendermint WebSocket".to_string(),
+                    )));
+                }
+                return None;
+            }
+        };
+
+        for (query, tx) in alive {
+            match conn.client.subscribe(query.clone()).await {
+                Ok(sub) => {
+                    tracing::debug!(query = query.to_string(), "re-established subscription");
+                    subs.push(ActiveSubscription {
+                        query,
+                        tx: tx.clone(),
+                        forwarder: tokio::spawn(forward(sub, tx)),
+                    });
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        error = e.to_string(),
+                        query = query.to_string(),
+                        "failed to re-establish subscription"
+                    );
+                    let _ = tx.send(Err(e));
+                }
+            }
+        }
+
+        Some(conn)
     }
 
-    /// Try connecting repeatedly until it succeeds.
-    async fn ws_connect(&self) -> (WebSocketClient, WebSocketClientDriver) {
+    /// Try connecting with exponential backoff until it succeeds or the retries run out.
+    async fn connect(&self) -> Option<Connection> {
         let url: Url = self.ws_url.clone().into();
+        let mut retry = 0;
         loop {
             match ws_client(url.clone()).await {
-                Ok(cd) => {
-                    return cd;
+                Ok((client, driver)) => {
+                    if retry > 0 {
+                        tracing::info!(
+                            url = url.to_string(),
+                            retry,
+                            "connected to Tendermint WebSocket"
+                        );
+                    }
+                    let driver = tokio::spawn(async move { driver.run().await });
+                    return Some(Connection { client, driver });
                 }
                 Err(e) => {
+                    if self
+                        .reconnect_opt
+                        .max_retries
+                        .is_some_and(|max| retry >= max)
+                    {
+                        tracing::error!(
+                            error = e.to_string(),
+                            url = url.to_string(),
+                            "failed to connect to Tendermint WebSocket; giving up after {retry} retries"
+                        );
+                        return None;
+                    }
+                    let delay = self.reconnect_opt.delay(retry);
+                    retry += 1;
                     tracing::warn!(
                         error = e.to_string(),
                         url = url.to_string(),
-                        "failed to connect to Tendermint WebSocket; retrying in {}s...",
-                        self.retry_delay.as_secs()
+                        retry,
+                        "failed to connect to Tendermint WebSocket; retrying in {}ms...",
+                        delay.as_millis()
                     );
-                    tokio::time::sleep(self.retry_delay).await;
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+}
+
+/// Resolves when the connection is lost, or never if there is no connection.
+async fn disconnected(conn: &mut Option<Connection>) -> String {
+    match conn {
+        Some(conn) => match (&mut conn.driver).await {
+            Ok(Ok(())) => "connection closed".to_string(),
+            Ok(Err(e)) => e.to_string(),
+            Err(e) => e.to_string(),
+        },
+        None => futures::future::pending().await,
+    }
+}
+
+/// Forward events to the subscriber until the subscription is closed, which happens
+/// when the connection is lost. Returns `false` if the subscriber is no longer listening,
+/// in which case dropping the subscription unsubscribes from CometBFT.
+async fn forward(mut sub: Subscription, tx: SubscriptionTx) -> bool {
+    while let Some(result) = sub.next().await {
+        if tx.send(result).is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, time::Duration};
+
+    use futures::{SinkExt, StreamExt};
+    use serde_json::json;
+    use tendermint_rpc::{query::EventType, query::Query, SubscriptionClient};
+    use tokio::{
+        net::TcpListener,
+        sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+        task::JoinHandle,
+    };
+    use tokio_tungstenite::tungstenite::Message;
+
+    use super::{HybridClient, ReconnectOpt};
+
+    /// A CometBFT WebSocket endpoint serving a single connection, which records
+    /// the (un)subscribe requests and publishes events to the subscriptions on demand.
+    struct MockServer {
+        addr: SocketAddr,
+        requests: UnboundedReceiver<(String, String)>,
+        events: UnboundedSender<String>,
+        handle: JoinHandle<()>,
+    }
+
+    impl MockServer {
+        async fn start(addr: SocketAddr) -> Self {
+            let listener = TcpListener::bind(addr).await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let (requests_tx, requests) = unbounded_channel();
+            let (events, mut events_rx) = unbounded_channel::<String>();
+
+            let handle = tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let mut ids = Vec::new();
+                loop {
+                    tokio::select! {
+                        Some(query) = events_rx.recv() => {
+                            let Some((id, _)) = ids.iter().find(|(_, q)| *q == query) else {
+                                continue;
+                            };
+                            let event = json!({
+                                "jsonrpc": "2.0",
+                                "id": id,
+                                "result": {
+                                    "query": query,
+                                    "data": { "type": "GenericJsonEvent", "value": {} },
+                                    "events": null,
+                                }
+                            });
+                            ws.send(Message::Text(event.to_string())).await.unwrap();
+                        }
+                        msg = ws.next() => {
+                            let text = match msg {
+                                Some(Ok(Message::Text(text))) => text,
+                                Some(Ok(_)) => continue,
+                                _ => break,
+                            };
+                            let req: serde_json::Value = serde_json::from_str(&text).unwrap();
+                            let method = req["method"].as_str().unwrap().to_string();
+                            let query = req["params"]["query"].as_str().unwrap().to_string();
+                            if method == "subscribe" {
+                                ids.push((req["id"].clone(), query.clone()));
+                            }
+                            let _ = requests_tx.send((method, query));
+                            let res = json!({ "jsonrpc": "2.0", "id": req["id"], "result": {} });
+                            ws.send(Message::Text(res.to_string())).await.unwrap();
+                        }
+                    }
                 }
+            });
+
+            Self {
+                addr,
+                requests,
+                events,
+                handle,
             }
         }
+
+        /// Drop the connection and the listener.
+        async fn stop(self) {
+            self.handle.abort();
+            let _ = self.handle.await;
+        }
+
+        async fn restart(self) -> Self {
+            let addr = self.addr;
+            self.stop().await;
+            Self::start(addr).await
+        }
+
+        fn publish(&self, query: &Query) {
+            self.events.send(query.to_string()).unwrap();
+        }
+
+        async fn next_request(&mut self) -> Option<(String, String)> {
+            tokio::time::timeout(Duration::from_millis(500), self.requests.recv())
+                .await
+                .ok()
+                .flatten()
+        }
+    }
+
+    #[test]
+    fn reconnect_delay_backoff() {
+        let opt = ReconnectOpt {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(5),
+            max_retries: None,
+        };
+        let delays = (0..5).map(|r| opt.delay(r).as_secs()).collect::<Vec<_>>();
+        assert_eq!(delays, vec![1, 2, 4, 5, 5]);
+        assert_eq!(opt.delay(u32::MAX), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn reconnect_restores_subscriptions() {
+        let mut server = MockServer::start("127.0.0.1:0".parse().unwrap()).await;
+        let addr = server.addr;
+
+        let (client, driver) = HybridClient::new(
+            format!("http://{addr}").parse().unwrap(),
+            format!("ws://{addr}/websocket").parse().unwrap(),
+            ReconnectOpt {
+                initial_delay: Duration::from_millis(50),
+                max_delay: Duration::from_millis(200),
+                max_retries: Some(3),
+            },
+        )
+        .unwrap();
+
+        let driver = tokio::spawn(driver.run());
+
+        let blocks = Query::from(EventType::NewBlock);
+        let txs = Query::from(EventType::Tx);
+        let subscribe = |q: &Query| Some(("subscribe".to_string(), q.to_string()));
+        let unsubscribe = |q: &Query| Some(("unsubscribe".to_string(), q.to_string()));
+
+        let mut blocks_sub = client.subscribe(blocks.clone()).await.unwrap();
+        let txs_sub = client.subscribe(txs.clone()).await.unwrap();
+        assert_eq!(server.next_request().await, subscribe(&blocks));
+        assert_eq!(server.next_request().await, subscribe(&txs));
+
+        // The forwarder only notices the subscriber is gone when it has an event to deliver,
+        // after which the next event makes the WebSocket client unsubscribe.
+        drop(txs_sub);
+        loop {
+            server.publish(&txs);
+            match server.next_request().await {
+                None => continue,
+                req => {
+                    assert_eq!(req, unsubscribe(&txs));
+                    break;
+                }
+            }
+        }
+
+        server.publish(&blocks);
+        assert!(blocks_sub.next().await.unwrap().is_ok());
+
+        // Only the subscription which still has a subscriber is re-established.
+        let mut server = server.restart().await;
+        assert_eq!(server.next_request().await, subscribe(&blocks));
+
+        server.publish(&blocks);
+        assert!(blocks_sub.next().await.unwrap().is_ok());
+        assert!(server.requests.try_recv().is_err());
+
+        // Once the retries run out the subscriber gets an error and the subscription ends.
+        server.stop().await;
+        let res = tokio::time::timeout(Duration::from_secs(5), blocks_sub.next())
+            .await
+            .expect("should give up reconnecting");
+        assert!(res.unwrap().is_err());
+        assert!(blocks_sub.next().await.is_none());
+
+        client.close().unwrap();
+        driver.await.unwrap();
     }
 }
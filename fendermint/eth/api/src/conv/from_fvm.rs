@@ -4,3 +4,8 @@
 //! Helper methods to convert between FVM and Ethereum data formats.
 
 pub use fendermint_vm_message::conv::from_fvm::*;
+
+/// The EIP-712 typed-data of an FVM message, which wallets can sign with `eth_signTypedData_v4`.
+///
+/// The signature can be turned into a message envelope with [super::from_eth::to_eip712_signed_message].
+pub use fendermint_vm_message::eip712::typed_data as to_eip712_typed_data;
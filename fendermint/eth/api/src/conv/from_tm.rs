@@ -240,79 +240,121 @@ pub fn to_cumulative(block_results: &endpoint::block_results::Response) -> Vec<(
     records
 }
 
-// https://github.com/filecoin-project/lotus/blob/6cc506f5cf751215be6badc94a960251c6453202/node/impl/full/eth.go#L2174
-// https://github.com/evmos/ethermint/blob/07cf2bd2b1ce9bdb2e44ec42a39e7239292a14af/rpc/backend/tx_info.go#L147
-pub async fn to_eth_receipt(
-    msg: &SignedMessage,
-    result: &endpoint::tx::Response,
-    cumulative: &[(et::U256, usize)],
-    header: &tendermint::block::Header,
-    base_fee: &TokenAmount,
-) -> anyhow::Result<et::TransactionReceipt> {
-    let block_hash = et::H256::from_slice(header.hash().as_bytes());
-    let block_number = et::U64::from(result.height.value());
-    let transaction_index = et::U64::from(result.index);
-    let transaction_hash = msg_hash(&result.tx_result.events, &result.tx);
-
-    let msg = &msg.message;
-    // Lotus effective gas price is based on total spend divided by gas used,
-    // for which it recalculates the gas outputs. However, we don't have access
-    // to the VM interpreter here to restore those results, and they are discarded
-    // from the [`ApplyRet`] during the conversion to [`DeliverTx`].
-    // We could put it into the [`DeliverTx::info`] field, or we can calculate
-    // something based on the gas fields of the transaction, like Ethermint.
-    let effective_gas_price =
-        crate::gas::effective_gas_price(msg, base_fee, result.tx_result.gas_used);
-
-    // Sum up gas up to this transaction.
-    let (cumulative_gas_used, cumulative_event_count) = cumulative
-        .get(result.index as usize)
-        .cloned()
-        .unwrap_or_default();
-
-    let log_index_start = cumulative_event_count.saturating_sub(result.tx_result.events.len());
-
-    let logs = to_logs(
-        &result.tx_result.events,
-        block_hash,
-        block_number,
-        transaction_hash,
-        transaction_index,
-        log_index_start,
-    )
-    .context("failed to collect logs")?;
+/// Block level data to produce the receipts of the transactions in a block.
+///
+/// The cumulative gas used and the log indexes are computed across the whole block,
+/// so a receipt is the same whether it is looked up on its own or with the rest of the block.
+pub struct BlockReceiptContext<'a> {
+    header: &'a tendermint::block::Header,
+    base_fee: &'a TokenAmount,
+    cumulative: Vec<(et::U256, usize)>,
+}
 
-    // See if the return value is an Ethereum contract creation.
-    // https://github.com/filecoin-project/lotus/blob/6cc506f5cf751215be6badc94a960251c6453202/node/impl/full/eth.go#LL2240C9-L2240C15
-    let contract_address = if result.tx_result.code.is_err() {
-        None
-    } else {
-        maybe_contract_address(&result.tx_result).map(|ca| et::H160::from_slice(&ca.0))
-    };
+impl<'a> BlockReceiptContext<'a> {
+    pub fn new(
+        header: &'a tendermint::block::Header,
+        block_results: &endpoint::block_results::Response,
+        base_fee: &'a TokenAmount,
+    ) -> Self {
+        Self {
+            header,
+            base_fee,
+            cumulative: to_cumulative(block_results),
+        }
+    }
 
-    let receipt = et::TransactionReceipt {
-        transaction_hash,
-        transaction_index,
-        block_hash: Some(block_hash),
-        block_number: Some(block_number),
-        from: to_eth_address(&msg.from).ok().flatten().unwrap_or_default(),
-        to: to_eth_address(&msg.to).ok().flatten(),
-        cumulative_gas_used,
-        gas_used: Some(et::U256::from(result.tx_result.gas_used)),
-        contract_address,
-        logs,
-        status: Some(et::U64::from(if result.tx_result.code.is_ok() {
-            1
+    /// Produce the receipts of all the signed transactions in the block, in order.
+    ///
+    /// The `tx_results` are expected to be the results of the `txs`, in the same order.
+    pub fn to_eth_receipts(
+        &self,
+        txs: &[Vec<u8>],
+        tx_results: &[ExecTxResult],
+    ) -> anyhow::Result<Vec<et::TransactionReceipt>> {
+        let mut receipts = Vec::new();
+        for (index, (tx, tx_result)) in txs.iter().zip(tx_results).enumerate() {
+            if let ChainMessage::Signed(msg) = to_chain_message(tx)? {
+                let receipt = self
+                    .to_eth_receipt(&msg, index as u32, tx, tx_result)
+                    .with_context(|| format!("failed to convert transaction {index} to receipt"))?;
+                receipts.push(receipt);
+            }
+        }
+        Ok(receipts)
+    }
+
+    /// Produce the receipt of the transaction at `index` in the block.
+    // https://github.com/filecoin-project/lotus/blob/6cc506f5cf751215be6badc94a960251c6453202/node/impl/full/eth.go#L2174
+    // https://github.com/evmos/ethermint/blob/07cf2bd2b1ce9bdb2e44ec42a39e7239292a14af/rpc/backend/tx_info.go#L147
+    pub fn to_eth_receipt(
+        &self,
+        msg: &SignedMessage,
+        index: u32,
+        tx: &[u8],
+        tx_result: &ExecTxResult,
+    ) -> anyhow::Result<et::TransactionReceipt> {
+        let block_hash = et::H256::from_slice(self.header.hash().as_bytes());
+        let block_number = et::U64::from(self.header.height.value());
+        let transaction_index = et::U64::from(index);
+        let transaction_hash = msg_hash(&tx_result.events, tx);
+
+        let msg = &msg.message;
+        // Lotus effective gas price is based on total spend divided by gas used,
+        // for which it recalculates the gas outputs. However, we don't have access
+        // to the VM interpreter here to restore those results, and they are discarded
+        // from the [`ApplyRet`] during the conversion to [`DeliverTx`].
+        // We could put it into the [`DeliverTx::info`] field, or we can calculate
+        // something based on the gas fields of the transaction, like Ethermint.
+        let effective_gas_price =
+            crate::gas::effective_gas_price(msg, self.base_fee, tx_result.gas_used);
+
+        // Sum up gas up to this transaction.
+        let (cumulative_gas_used, cumulative_event_count) = self
+            .cumulative
+            .get(index as usize)
+            .cloned()
+            .unwrap_or_default();
+
+        let log_index_start = cumulative_event_count.saturating_sub(tx_result.events.len());
+
+        let logs = to_logs(
+            &tx_result.events,
+            block_hash,
+            block_number,
+            transaction_hash,
+            transaction_index,
+            log_index_start,
+        )
+        .context("failed to collect logs")?;
+
+        // See if the return value is an Ethereum contract creation.
+        // https://github.com/filecoin-project/lotus/blob/6cc506f5cf751215be6badc94a960251c6453202/node/impl/full/eth.go#LL2240C9-L2240C15
+        let contract_address = if tx_result.code.is_err() {
+            None
         } else {
-            0
-        })),
-        root: Some(app_hash_to_root(&header.app_hash)?),
-        logs_bloom: et::Bloom::from_slice(&*EMPTY_ETH_BLOOM),
-        transaction_type: Some(et::U64::from(2)), // Value used by Lotus.
-        effective_gas_price: Some(to_eth_tokens(&effective_gas_price)?),
-        other: Default::default(),
-    };
-    Ok(receipt)
+            maybe_contract_address(tx_result).map(|ca| et::H160::from_slice(&ca.0))
+        };
+
+        let receipt = et::TransactionReceipt {
+            transaction_hash,
+            transaction_index,
+            block_hash: Some(block_hash),
+            block_number: Some(block_number),
+            from: to_eth_address(&msg.from).ok().flatten().unwrap_or_default(),
+            to: to_eth_address(&msg.to).ok().flatten(),
+            cumulative_gas_used,
+            gas_used: Some(et::U256::from(tx_result.gas_used)),
+            contract_address,
+            logs,
+            status: Some(et::U64::from(if tx_result.code.is_ok() { 1 } else { 0 })),
+            root: Some(app_hash_to_root(&self.header.app_hash)?),
+            logs_bloom: et::Bloom::from_slice(&*EMPTY_ETH_BLOOM),
+            transaction_type: Some(et::U64::from(2)), // Value used by Lotus.
+            effective_gas_price: Some(to_eth_tokens(&effective_gas_price)?),
+            other: Default::default(),
+        };
+        Ok(receipt)
+    }
 }
 
 /// Change the type of transactions in a block by mapping a function over them.
@@ -434,6 +476,40 @@ pub fn to_eth_block_zero(block: tendermint::Block) -> anyhow::Result<et::Block<s
     Ok(block)
 }
 
+/// Map the sync info of CometBFT to what `eth_syncing` returns.
+///
+/// CometBFT doesn't tell us how high its peers are while it's catching up,
+/// so the highest block we can report is the latest one it has.
+pub fn to_eth_syncing_status(info: &endpoint::status::SyncInfo) -> et::SyncingStatus {
+    if !info.catching_up {
+        return et::SyncingStatus::IsFalse;
+    }
+    let latest_height = et::U64::from(info.latest_block_height.value());
+    let progress = et::SyncProgress {
+        // The first block we have, which is where we started syncing from, e.g. after a state sync.
+        starting_block: et::U64::from(info.earliest_block_height.value()),
+        // The block we executed.
+        current_block: latest_height,
+        // The block we know about but haven't got to yet.
+        highest_block: latest_height,
+        pulled_states: None,
+        known_states: None,
+        healed_bytecode_bytes: None,
+        healed_bytecodes: None,
+        healed_trienode_bytes: None,
+        healed_trienodes: None,
+        healing_bytecode: None,
+        healing_trienodes: None,
+        synced_account_bytes: None,
+        synced_accounts: None,
+        synced_bytecode_bytes: None,
+        synced_bytecodes: None,
+        synced_storage: None,
+        synced_storage_bytes: None,
+    };
+    et::SyncingStatus::IsSyncing(Box::new(progress))
+}
+
 /// Turn Events into Ethereum logs.
 ///
 /// We need to turn Actor IDs into Ethereum addresses because that's what the tooling expects.
@@ -603,9 +679,17 @@ pub fn collect_emitters(events: &[abci::Event]) -> HashSet<Address> {
 
 #[cfg(test)]
 mod tests {
+    use ethers_core::types as et;
+    use fendermint_vm_message::{chain::ChainMessage, signed::SignedMessage};
+    use fvm_shared::{
+        address::Address, crypto::signature::Signature, econ::TokenAmount, message::Message,
+    };
+    use tendermint::abci::{types::ExecTxResult, Event, EventAttribute};
+    use tendermint_rpc::endpoint::block_results;
+
     use crate::conv::from_tm::is_block_zero;
 
-    use super::{to_eth_block_zero, BLOCK_ZERO};
+    use super::{to_chain_message, to_eth_block_zero, BlockReceiptContext, BLOCK_ZERO};
 
     #[test]
     fn block_zero_can_be_created() {
@@ -616,4 +700,94 @@ mod tests {
     fn block_zero_can_be_turned_into_eth() {
         let _ = to_eth_block_zero(BLOCK_ZERO.clone()).unwrap();
     }
+
+    fn signed_tx(sequence: u64) -> Vec<u8> {
+        let message = Message {
+            version: 0,
+            from: Address::new_id(100),
+            to: Address::new_id(101),
+            sequence,
+            value: TokenAmount::from_atto(1),
+            method_num: 0,
+            params: Default::default(),
+            gas_limit: 10_000_000,
+            gas_fee_cap: TokenAmount::from_atto(100),
+            gas_premium: TokenAmount::from_atto(10),
+        };
+        let signed = SignedMessage::new_unchecked(message, Signature::new_secp256k1(vec![0; 65]));
+        fvm_ipld_encoding::to_vec(&ChainMessage::Signed(signed)).unwrap()
+    }
+
+    fn tx_result(gas_used: i64, num_logs: usize) -> ExecTxResult {
+        let events = (0..num_logs)
+            .map(|_| {
+                Event::new(
+                    "event",
+                    vec![EventAttribute::from((
+                        "emitter.id".to_string(),
+                        "101".to_string(),
+                        true,
+                    ))],
+                )
+            })
+            .collect();
+
+        ExecTxResult {
+            gas_used,
+            events,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn block_receipts_are_cumulative() {
+        let mut header = BLOCK_ZERO.header.clone();
+        header.height = tendermint::block::Height::from(10u32);
+
+        let txs = vec![signed_tx(0), signed_tx(1), signed_tx(2)];
+        let tx_results = vec![tx_result(100, 2), tx_result(200, 0), tx_result(300, 3)];
+
+        let block_results = block_results::Response {
+            height: header.height,
+            txs_results: Some(tx_results.clone()),
+            begin_block_events: None,
+            end_block_events: None,
+            validator_updates: Vec::new(),
+            consensus_param_updates: None,
+            finalize_block_events: Vec::new(),
+            app_hash: Default::default(),
+        };
+
+        let base_fee = TokenAmount::from_atto(50);
+        let ctx = BlockReceiptContext::new(&header, &block_results, &base_fee);
+
+        let receipts = ctx.to_eth_receipts(&txs, &tx_results).unwrap();
+        assert_eq!(receipts.len(), 3);
+
+        let cumulative_gas = receipts
+            .iter()
+            .map(|r| r.cumulative_gas_used.as_u64())
+            .collect::<Vec<_>>();
+        assert_eq!(cumulative_gas, vec![100, 300, 600]);
+
+        let log_indexes = receipts
+            .iter()
+            .flat_map(|r| r.logs.iter().map(|l| l.log_index.unwrap().as_u64()))
+            .collect::<Vec<_>>();
+        assert_eq!(log_indexes, vec![0, 1, 2, 3, 4]);
+
+        for (i, r) in receipts.iter().enumerate() {
+            assert_eq!(r.transaction_index, et::U64::from(i));
+            assert_eq!(r.block_number, Some(et::U64::from(10)));
+        }
+
+        // Looking up a single receipt gives the same result as the whole block.
+        let ChainMessage::Signed(msg) = to_chain_message(&txs[2]).unwrap() else {
+            panic!("expected a signed message");
+        };
+        let single = ctx
+            .to_eth_receipt(&msg, 2, &txs[2], &tx_results[2])
+            .unwrap();
+        assert_eq!(single, receipts[2]);
+    }
 }
@@ -28,8 +28,9 @@ use tokio::sync::{
 };
 
 use crate::{
+    cache::SyncInfoCache,
     conv::from_tm::{self, find_hash_event, map_rpc_block_txs, msg_hash, tx_hash},
-    error::JsonRpcError,
+    error::{error, JsonRpcError},
     handlers::ws::{MethodNotification, Notification},
     state::{enrich_block, WebSocketSender},
     JsonRpcResult,
@@ -417,8 +418,12 @@ impl FilterDriver {
     /// Consume commands until some end condition is met.
     ///
     /// In the end the filter removes itself from the registry.
-    pub async fn run<C>(mut self, filters: FilterMap, client: FendermintClient<C>)
-    where
+    pub async fn run<C>(
+        mut self,
+        filters: FilterMap,
+        client: FendermintClient<C>,
+        sync_info: SyncInfoCache<C>,
+    ) where
         C: Client + Send + Sync + Clone + 'static,
     {
         let id = self.id;
@@ -519,6 +524,20 @@ impl FilterDriver {
                 }
                 FilterState::Subscription(ref state) => match cmd {
                     FilterCommand::Update(event) => {
+                        // Don't flood the subscribers with historical blocks while CometBFT is catching up.
+                        if let FilterKind::NewBlocks = self.kind {
+                            match sync_info.is_catching_up().await {
+                                Ok(true) => {
+                                    tracing::debug!(?id, "skipping new block while catching up");
+                                    continue;
+                                }
+                                Ok(false) => {}
+                                Err(e) => {
+                                    tracing::warn!(?id, "failed to check the sync status: {e}")
+                                }
+                            }
+                        }
+
                         let mut records = FilterRecords::<et::Block<et::TxHash>>::new(&self.kind);
 
                         let res = match &chain_id {
@@ -755,11 +774,23 @@ pub async fn run_subscription(id: FilterId, mut sub: Subscription, tx: Sender<Fi
     // See https://docs.rs/tendermint-rpc/0.31.1/tendermint_rpc/client/struct.WebSocketClient.html
 }
 
+/// Check that a log query doesn't span more than `max_range` blocks, so that a single request
+/// cannot make the node scan the whole chain and buffer all of its logs in memory.
+pub fn check_block_range(from_height: u64, to_height: u64, max_range: u64) -> JsonRpcResult<()> {
+    if to_height.saturating_sub(from_height) > max_range {
+        return error(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            format!("query returned more than {max_range} blocks"),
+        );
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use ethers_core::types as et;
 
-    use super::FilterKind;
+    use super::{check_block_range, FilterKind};
 
     #[test]
     fn default_filter_to_query() {
@@ -823,4 +854,15 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn block_range_limit() {
+        assert!(check_block_range(100, 100, 10).is_ok());
+        assert!(check_block_range(100, 110, 10).is_ok());
+        // An inverted range returns nothing rather than an error.
+        assert!(check_block_range(110, 100, 10).is_ok());
+
+        let err = check_block_range(100, 111, 10).unwrap_err();
+        assert_eq!(err.message, "query returned more than 10 blocks");
+    }
 }
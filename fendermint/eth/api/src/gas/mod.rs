@@ -1,5 +1,6 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
+use ethers_core::types as et;
 use fvm_shared::{
     bigint::{BigInt, Zero},
     econ::TokenAmount,
@@ -68,8 +69,129 @@ pub fn median_gas_premium(prices: &mut [(TokenAmount, i64)], block_gas_target: i
 
     if prev2 != TokenAmount::zero() {
         premium += &prev2;
-        premium.div_ceil(BigInt::from(2));
+        premium = premium.div_ceil(BigInt::from(2));
     }
 
     premium
 }
+
+/// Suggests a priority fee for `eth_maxPriorityFeePerGas` from the effective premiums paid
+/// in recent blocks, which is never below `min_premium`, even if the blocks are empty.
+pub fn suggested_gas_premium(
+    premiums: &mut [(TokenAmount, i64)],
+    block_gas_limit: i64,
+    min_premium: &TokenAmount,
+) -> TokenAmount {
+    let median = median_gas_premium(premiums, block_gas_limit);
+    if median < *min_premium {
+        min_premium.clone()
+    } else {
+        median
+    }
+}
+
+/// The number of blocks to return in `eth_feeHistory`. Like Geth, requests for more than
+/// `max_fee_hist_size` blocks are clamped rather than rejected.
+pub fn fee_history_block_count(block_count: et::U256, max_fee_hist_size: u64) -> usize {
+    block_count
+        .min(et::U256::from(max_fee_hist_size))
+        .as_usize()
+}
+
+/// Returns the premium at each of the `percentiles` of the gas used in a block.
+///
+/// The `premiums` are the effective premiums with the gas used by each transaction,
+/// sorted in ascending order of premium; the `percentiles` are expected to be increasing.
+// https://github.com/filecoin-project/lotus/blob/v1.25.2/node/impl/full/eth.go#L2434
+pub fn reward_percentiles(
+    premiums: &[(TokenAmount, i64)],
+    percentiles: &[f64],
+) -> Vec<TokenAmount> {
+    if premiums.is_empty() {
+        return percentiles.iter().map(|_| TokenAmount::zero()).collect();
+    }
+
+    let gas_used: i64 = premiums.iter().map(|(_, gas)| *gas).sum();
+
+    let mut rewards = Vec::with_capacity(percentiles.len());
+    let mut sum_gas_used = 0;
+    let mut idx = 0;
+    for p in percentiles {
+        let threshold_gas_used = (gas_used as f64 * p / 100f64) as i64;
+        while sum_gas_used < threshold_gas_used && idx < premiums.len() - 1 {
+            sum_gas_used += premiums[idx].1;
+            idx += 1;
+        }
+        rewards.push(premiums[idx].0.clone());
+    }
+    rewards
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::types as et;
+    use fvm_shared::econ::TokenAmount;
+
+    use super::{fee_history_block_count, reward_percentiles, suggested_gas_premium};
+
+    const BLOCK_GAS_LIMIT: i64 = 10_000_000_000;
+
+    #[test]
+    fn test_fee_history_block_count_clamp() {
+        assert_eq!(fee_history_block_count(et::U256::from(10), 1024), 10);
+        assert_eq!(fee_history_block_count(et::U256::from(2000), 1024), 1024);
+        assert_eq!(fee_history_block_count(et::U256::MAX, 1024), 1024);
+        assert_eq!(fee_history_block_count(et::U256::zero(), 1024), 0);
+    }
+
+    #[test]
+    fn test_reward_percentiles() {
+        let premiums = [
+            (TokenAmount::from_atto(1), 100),
+            (TokenAmount::from_atto(2), 100),
+            (TokenAmount::from_atto(5), 200),
+        ];
+
+        let rewards = reward_percentiles(&premiums, &[0.0, 25.0, 50.0, 75.0, 100.0]);
+        let rewards = rewards.iter().map(|r| r.atto().clone()).collect::<Vec<_>>();
+        assert_eq!(
+            rewards,
+            [1, 2, 5, 5, 5]
+                .into_iter()
+                .map(Into::into)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_reward_percentiles_empty_block() {
+        let rewards = reward_percentiles(&[], &[10.0, 90.0]);
+        assert_eq!(rewards, vec![TokenAmount::from_atto(0); 2]);
+    }
+
+    #[test]
+    fn test_suggested_gas_premium_floor() {
+        let min_premium = TokenAmount::from_atto(100_000);
+
+        // No transactions in the recent blocks.
+        let premium = suggested_gas_premium(&mut [], BLOCK_GAS_LIMIT, &min_premium);
+        assert_eq!(premium, min_premium);
+
+        // Only transactions without a tip.
+        let mut premiums = vec![(TokenAmount::from_atto(0), BLOCK_GAS_LIMIT); 3];
+        let premium = suggested_gas_premium(&mut premiums, BLOCK_GAS_LIMIT, &min_premium);
+        assert_eq!(premium, min_premium);
+    }
+
+    #[test]
+    fn test_suggested_gas_premium_above_floor() {
+        let min_premium = TokenAmount::from_atto(100_000);
+        let mut premiums = vec![
+            (TokenAmount::from_atto(300_000), BLOCK_GAS_LIMIT),
+            (TokenAmount::from_atto(200_000), BLOCK_GAS_LIMIT),
+        ];
+
+        let premium = suggested_gas_premium(&mut premiums, BLOCK_GAS_LIMIT, &min_premium);
+        assert_eq!(premium, TokenAmount::from_atto(250_000));
+    }
+}
@@ -4,6 +4,7 @@
 
 // Based on https://github.com/ChainSafe/forest/blob/v0.8.2/node/rpc/src/rpc_http_handler.rs
 
+use axum::extract::rejection::JsonRejection;
 use axum::http::{HeaderMap, StatusCode};
 use axum::response::IntoResponse;
 use jsonrpc_v2::{RequestObject, ResponseObjects};
@@ -15,6 +16,11 @@ type ResponseHeaders = [(&'static str, &'static str); 1];
 
 const RESPONSE_HEADERS: ResponseHeaders = [("content-type", "application/json-rpc;charset=utf-8")];
 
+/// JSON-RPC error code for invalid JSON.
+const PARSE_ERROR: i64 = -32700;
+/// JSON-RPC error code for a request which is not a valid request object.
+const INVALID_REQUEST: i64 = -32600;
+
 /// The Ethereum API implementations accept `{}` or `[{}, {}, ...]` as requests,
 /// with the expectation of as many responses.
 ///
@@ -30,8 +36,12 @@ pub enum RequestKind {
 pub async fn handle(
     _headers: HeaderMap,
     axum::extract::State(state): axum::extract::State<AppState>,
-    axum::Json(request): axum::Json<RequestKind>,
+    request: Result<axum::Json<RequestKind>, JsonRejection>,
 ) -> impl IntoResponse {
+    let request = match request {
+        Ok(axum::Json(request)) => request,
+        Err(rejection) => return rejection_response(rejection),
+    };
     // NOTE: Any authorization can come here.
     let response = match request {
         RequestKind::One(request) => {
@@ -84,6 +94,27 @@ fn json_response(response: &ResponseObjects) -> (StatusCode, ResponseHeaders, st
     }
 }
 
+/// Turn a request that could not be read into a JSON-RPC error, so clients get the same kind of
+/// response as for any other failure, e.g. when the body is over the size limit.
+fn rejection_response(
+    rejection: JsonRejection,
+) -> (StatusCode, ResponseHeaders, std::string::String) {
+    let status = rejection.status();
+    let (code, message) = if status == StatusCode::PAYLOAD_TOO_LARGE {
+        (INVALID_REQUEST, "request body too large".to_string())
+    } else {
+        (PARSE_ERROR, rejection.body_text())
+    };
+    tracing::debug!(?status, %message, "RPC request rejected");
+
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": { "code": code, "message": message }
+    });
+    (status, RESPONSE_HEADERS, body.to_string())
+}
+
 fn check_request(
     request: &RequestObject,
 ) -> Result<(), (StatusCode, ResponseHeaders, std::string::String)> {
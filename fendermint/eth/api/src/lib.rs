@@ -2,10 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use anyhow::anyhow;
+use axum::extract::DefaultBodyLimit;
 use axum::routing::{get, post};
 use fvm_shared::econ::TokenAmount;
 use jsonrpc_v2::Data;
 use std::{net::ToSocketAddrs, sync::Arc, time::Duration};
+use tokio_util::sync::CancellationToken;
 use tower_http::cors::CorsLayer;
 
 mod apis;
@@ -19,7 +21,7 @@ mod handlers;
 mod mpool;
 mod state;
 
-pub use client::{HybridClient, HybridClientDriver};
+pub use client::{HybridClient, HybridClientDriver, ReconnectOpt};
 
 use error::{error, JsonRpcError};
 use state::{JsonRpcState, Nonce};
@@ -43,7 +45,15 @@ pub struct GasOpt {
     pub max_fee_hist_size: u64,
 }
 
-/// Start listening to JSON-RPC requests.
+/// Start listening to JSON-RPC requests, until the shutdown token is cancelled.
+///
+/// After the cancellation no new connections are accepted, but the requests
+/// in flight are answered before returning.
+///
+/// The WebSocket reconnection backoff is not a parameter here: it's a [ReconnectOpt]
+/// given to [HybridClient::new], because the [HybridClientDriver] which reconnects
+/// is spawned by the caller and runs independently of this server.
+#[allow(clippy::too_many_arguments)]
 pub async fn listen<A: ToSocketAddrs>(
     listen_addr: A,
     client: HybridClient,
@@ -51,6 +61,9 @@ pub async fn listen<A: ToSocketAddrs>(
     cache_capacity: usize,
     max_nonce_gap: Nonce,
     gas_opt: GasOpt,
+    max_log_block_range: u64,
+    max_request_bytes: usize,
+    shutdown: CancellationToken,
 ) -> anyhow::Result<()> {
     if let Some(listen_addr) = listen_addr.to_socket_addrs()?.next() {
         let rpc_state = Arc::new(JsonRpcState::new(
@@ -59,6 +72,7 @@ pub async fn listen<A: ToSocketAddrs>(
             cache_capacity,
             max_nonce_gap,
             gas_opt,
+            max_log_block_range,
         ));
 
         // Start the transaction cache pruning subscription.
@@ -73,10 +87,13 @@ pub async fn listen<A: ToSocketAddrs>(
             rpc_server,
             rpc_state,
         };
-        let router = make_router(app_state);
-        let server = axum::Server::try_bind(&listen_addr)?.serve(router.into_make_service());
+        let router = make_router(app_state, max_request_bytes);
+        let server = axum::Server::try_bind(&listen_addr)?
+            .serve(router.into_make_service())
+            .with_graceful_shutdown(async move { shutdown.cancelled().await });
         tracing::info!(?listen_addr, "bound Ethereum API");
         server.await?;
+        tracing::info!("stopped Ethereum API");
         Ok(())
     } else {
         Err(anyhow!("failed to convert to any socket address"))
@@ -91,10 +108,93 @@ fn make_server(state: Arc<JsonRpcState<HybridClient>>) -> JsonRpcServer {
 }
 
 /// Register routes in the `axum` HTTP router to handle JSON-RPC and WebSocket calls.
-fn make_router(state: AppState) -> axum::Router {
+///
+/// Request bodies larger than `max_request_bytes` are rejected with `413 Payload Too Large`.
+fn make_router(state: AppState, max_request_bytes: usize) -> axum::Router {
     axum::Router::new()
         .route("/", post(handlers::http::handle))
         .route("/", get(handlers::ws::handle))
+        .layer(DefaultBodyLimit::max(max_request_bytes))
         .layer(CorsLayer::permissive())
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum::body::{Body, HttpBody};
+    use axum::http::{Request, StatusCode};
+    use fvm_shared::econ::TokenAmount;
+    use tower::ServiceExt;
+
+    use crate::state::JsonRpcState;
+    use crate::{make_router, make_server, AppState, GasOpt, HybridClient, ReconnectOpt};
+
+    const MAX_REQUEST_BYTES: usize = 1024;
+
+    /// A router whose client points at an address nothing listens on; the requests in these
+    /// tests are rejected before any method handler would need to reach CometBFT.
+    fn test_router() -> axum::Router {
+        let (client, _driver) = HybridClient::new(
+            "http://127.0.0.1:1".parse().unwrap(),
+            "ws://127.0.0.1:1/websocket".parse().unwrap(),
+            ReconnectOpt {
+                initial_delay: Duration::from_secs(1),
+                max_delay: Duration::from_secs(1),
+                max_retries: Some(0),
+            },
+        )
+        .unwrap();
+
+        let rpc_state = Arc::new(JsonRpcState::new(
+            client,
+            Duration::from_secs(60),
+            100,
+            10,
+            GasOpt {
+                min_gas_premium: TokenAmount::from_atto(0),
+                num_blocks_max_prio_fee: 10,
+                max_fee_hist_size: 1024,
+            },
+            1000,
+        ));
+        let app_state = AppState {
+            rpc_server: make_server(rpc_state.clone()),
+            rpc_state,
+        };
+        make_router(app_state, MAX_REQUEST_BYTES)
+    }
+
+    fn post(body: String) -> Request<Body> {
+        Request::post("/")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn oversized_request_is_rejected() {
+        let params = "0".repeat(MAX_REQUEST_BYTES);
+        let body =
+            format!(r#"{{"jsonrpc":"2.0","id":1,"method":"eth_chainId","params":["{params}"]}}"#);
+
+        let response = test_router().oneshot(post(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let mut body = response.into_body();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = body.data().await {
+            bytes.extend_from_slice(&chunk.unwrap());
+        }
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], -32600);
+    }
+
+    #[tokio::test]
+    async fn request_within_limit_is_not_rejected_for_size() {
+        // Malformed, but small: it should fail to parse rather than hit the limit.
+        let response = test_router().oneshot(post("{".to_string())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
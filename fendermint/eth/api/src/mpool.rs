@@ -17,7 +17,12 @@ use tendermint_rpc::{
     Client, SubscriptionClient,
 };
 
-use crate::{cache::Cache, state::Nonce, HybridClient};
+use crate::{
+    cache::Cache,
+    conv::{from_eth::to_fvm_address, from_fvm::to_eth_tokens},
+    state::Nonce,
+    HybridClient,
+};
 
 const RETRY_SLEEP_SECS: u64 = 5;
 
@@ -78,6 +83,58 @@ impl TransactionBuffer {
     }
 }
 
+/// Collect the transactions of a sender which have been submitted or buffered,
+/// but not yet included in a block, along with the maximum amount they can spend.
+pub fn pending_txs(
+    tx_cache: &TransactionCache,
+    tx_buffer: &TransactionBuffer,
+    sender: &Address,
+) -> BTreeMap<Nonce, et::U256> {
+    let mut pending = BTreeMap::new();
+
+    tx_cache.with(|c| {
+        for (_, tx) in c.peek_iter() {
+            if to_fvm_address(tx.from) == *sender {
+                let fee_cap = tx.max_fee_per_gas.or(tx.gas_price).unwrap_or_default();
+                let max_spend = tx.value.saturating_add(tx.gas.saturating_mul(fee_cap));
+                pending.insert(tx.nonce.as_u64(), max_spend);
+            }
+        }
+    });
+
+    tx_buffer.0.with(|c| {
+        if let Some(buffer) = c.peek(sender) {
+            for (nonce, msg) in buffer {
+                if let ChainMessage::Signed(msg) = msg {
+                    let msg = &msg.message;
+                    let max_spend = msg.value.clone() + msg.gas_fee_cap.clone() * msg.gas_limit;
+                    pending.insert(*nonce, to_eth_tokens(&max_spend).unwrap_or(et::U256::MAX));
+                }
+            }
+        }
+    });
+
+    pending
+}
+
+/// The next nonce of a sender, after the pending transactions which follow `nonce` without a gap.
+pub fn pending_nonce(nonce: Nonce, pending: &BTreeMap<Nonce, et::U256>) -> Nonce {
+    let mut nonce = nonce;
+    while pending.contains_key(&nonce) {
+        nonce += 1;
+    }
+    nonce
+}
+
+/// The maximum amount the pending transactions which follow `nonce` without a gap can spend.
+pub fn pending_spend(nonce: Nonce, pending: &BTreeMap<Nonce, et::U256>) -> et::U256 {
+    pending
+        .range(nonce..pending_nonce(nonce, pending))
+        .fold(et::U256::zero(), |acc, (_, spend)| {
+            acc.saturating_add(*spend)
+        })
+}
+
 /// Subscribe to `NewBlock`  notifications and clear transactions from the caches.`
 pub fn start_tx_cache_clearing(
     client: FendermintClient<HybridClient>,
@@ -201,3 +258,107 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ethers_core::types as et;
+    use fendermint_vm_message::{chain::ChainMessage, signed::SignedMessage};
+    use fvm_shared::{
+        address::Address, crypto::signature::Signature, econ::TokenAmount, message::Message,
+    };
+
+    use super::{pending_nonce, pending_spend, pending_txs, TransactionBuffer, TransactionCache};
+    use crate::{cache::Cache, conv::from_eth::to_fvm_address};
+
+    const SENDER: et::Address = et::H160([1u8; 20]);
+
+    fn caches() -> (TransactionCache, TransactionBuffer) {
+        let ttl = Duration::from_secs(60);
+        (
+            Cache::new_with_ttl(100, ttl),
+            TransactionBuffer(Cache::new_with_ttl(100, ttl)),
+        )
+    }
+
+    /// Cache a submitted transaction, like `eth_sendRawTransaction` does.
+    fn submit(tx_cache: &TransactionCache, from: et::Address, nonce: u64) {
+        let tx = et::Transaction {
+            hash: et::H256::from_low_u64_be(nonce + 1),
+            from,
+            nonce: et::U256::from(nonce),
+            value: et::U256::from(1000),
+            gas: et::U256::from(100),
+            max_fee_per_gas: Some(et::U256::from(10)),
+            ..Default::default()
+        };
+        tx_cache.insert(tx.hash, tx);
+    }
+
+    /// Buffer an out-of-order transaction, like `eth_sendRawTransaction` does.
+    fn buffer(tx_buffer: &TransactionBuffer, from: et::Address, nonce: u64) {
+        let sender = to_fvm_address(from);
+        let msg = Message {
+            version: 0,
+            from: sender,
+            to: Address::new_id(100),
+            sequence: nonce,
+            value: TokenAmount::from_atto(1000),
+            method_num: 0,
+            params: Default::default(),
+            gas_limit: 100,
+            gas_fee_cap: TokenAmount::from_atto(10),
+            gas_premium: TokenAmount::from_atto(1),
+        };
+        let msg = SignedMessage::new_unchecked(msg, Signature::new_secp256k1(vec![0; 65]));
+        tx_buffer.insert(sender, nonce, ChainMessage::Signed(msg));
+    }
+
+    #[test]
+    fn pending_nonce_counts_submitted_txs() {
+        let (tx_cache, tx_buffer) = caches();
+        let sender = to_fvm_address(SENDER);
+        let committed = 5;
+
+        let pending = pending_txs(&tx_cache, &tx_buffer, &sender);
+        assert_eq!(pending_nonce(committed, &pending), 5);
+        assert_eq!(pending_spend(committed, &pending), et::U256::zero());
+
+        submit(&tx_cache, SENDER, 5);
+        let pending = pending_txs(&tx_cache, &tx_buffer, &sender);
+        assert_eq!(pending_nonce(committed, &pending), 6);
+
+        submit(&tx_cache, SENDER, 6);
+        let pending = pending_txs(&tx_cache, &tx_buffer, &sender);
+        assert_eq!(pending_nonce(committed, &pending), 7);
+        assert_eq!(pending_spend(committed, &pending), et::U256::from(2 * 2000));
+
+        // Transactions from other senders don't count.
+        submit(&tx_cache, et::H160([2u8; 20]), 7);
+        let pending = pending_txs(&tx_cache, &tx_buffer, &sender);
+        assert_eq!(pending_nonce(committed, &pending), 7);
+    }
+
+    #[test]
+    fn pending_nonce_stops_at_gap() {
+        let (tx_cache, tx_buffer) = caches();
+        let sender = to_fvm_address(SENDER);
+        let committed = 5;
+
+        submit(&tx_cache, SENDER, 5);
+        buffer(&tx_buffer, SENDER, 7);
+        let pending = pending_txs(&tx_cache, &tx_buffer, &sender);
+        assert_eq!(pending_nonce(committed, &pending), 6);
+        assert_eq!(pending_spend(committed, &pending), et::U256::from(2000));
+
+        // Filling the gap makes the buffered transaction count as well.
+        submit(&tx_cache, SENDER, 6);
+        let pending = pending_txs(&tx_cache, &tx_buffer, &sender);
+        assert_eq!(pending_nonce(committed, &pending), 8);
+        assert_eq!(pending_spend(committed, &pending), et::U256::from(3 * 2000));
+
+        // Once included in a block, the committed nonce catches up.
+        assert_eq!(pending_nonce(8, &pending), 8);
+    }
+}
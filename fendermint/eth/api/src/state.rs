@@ -30,7 +30,7 @@ use tendermint_rpc::{Order, Subscription, SubscriptionClient};
 use tokio::sync::mpsc::{Sender, UnboundedSender};
 use tokio::sync::RwLock;
 
-use crate::cache::{AddressCache, Cache};
+use crate::cache::{AddressCache, Cache, SyncInfoCache};
 use crate::conv::from_tm;
 use crate::filters::{
     run_subscription, BlockHash, FilterCommand, FilterDriver, FilterId, FilterKind, FilterMap,
@@ -46,6 +46,8 @@ use crate::{
 
 /// How long to keep transactions in the caches.
 const TX_CACHE_TTL_SECS: u64 = 5 * 60;
+/// How long to keep the sync status of CometBFT before asking again.
+const SYNC_INFO_TTL_SECS: u64 = 1;
 
 pub type WebSocketId = usize;
 pub type WebSocketSender = UnboundedSender<MethodNotification>;
@@ -58,6 +60,8 @@ pub type Nonce = u64;
 pub struct JsonRpcState<C> {
     pub client: FendermintClient<C>,
     pub addr_cache: AddressCache<C>,
+    /// Cache the sync status of CometBFT for `eth_syncing` and subscriptions.
+    pub sync_info: SyncInfoCache<C>,
     /// Cache submitted transactions until they are added to a block.
     pub tx_cache: TransactionCache,
     /// Buffer out-of-order transactions until they can be submitted.
@@ -68,6 +72,8 @@ pub struct JsonRpcState<C> {
     web_sockets: RwLock<HashMap<WebSocketId, WebSocketSender>>,
     pub max_nonce_gap: Nonce,
     pub gas_opt: GasOpt,
+    /// Maximum number of blocks an `eth_getLogs` query can span.
+    pub max_log_block_range: u64,
 }
 
 impl<C> JsonRpcState<C>
@@ -80,9 +86,11 @@ where
         cache_capacity: usize,
         max_nonce_gap: Nonce,
         gas_opt: GasOpt,
+        max_log_block_range: u64,
     ) -> Self {
         let client = FendermintClient::new(client);
         let addr_cache = AddressCache::new(client.clone(), cache_capacity);
+        let sync_info = SyncInfoCache::new(client.clone(), Duration::from_secs(SYNC_INFO_TTL_SECS));
         let tx_cache = Cache::new_with_ttl(cache_capacity, Duration::from_secs(TX_CACHE_TTL_SECS));
         let tx_buffer = TransactionBuffer(Cache::new_with_ttl(
             cache_capacity,
@@ -91,6 +99,7 @@ where
         Self {
             client,
             addr_cache,
+            sync_info,
             tx_cache,
             tx_buffer,
             filter_timeout,
@@ -99,6 +108,7 @@ where
             web_sockets: Default::default(),
             gas_opt,
             max_nonce_gap,
+            max_log_block_range,
         }
     }
 }
@@ -563,8 +573,9 @@ where
         let id = state.id();
         let filters = self.filters.clone();
         let client = self.client.clone();
+        let sync_info = self.sync_info.clone();
 
-        tokio::spawn(async move { state.run(filters, client).await });
+        tokio::spawn(async move { state.run(filters, client, sync_info).await });
 
         for sub in subs {
             let tx = tx.clone();
@@ -7,34 +7,45 @@ use fendermint_storage::KVResult;
 use fendermint_storage::KVTransaction;
 use fendermint_storage::KVWritable;
 use fendermint_storage::KVWrite;
-use fendermint_storage::{KVError, KVRead, KVReadable, KVStore};
+use fendermint_storage::{IterRange, KVError, KVRead, KVReadable, KVStore};
 use rocksdb::BoundColumnFamily;
+use rocksdb::Direction;
 use rocksdb::ErrorKind;
+use rocksdb::IteratorMode;
 use rocksdb::OptimisticTransactionDB;
 use rocksdb::SnapshotWithThreadMode;
 use rocksdb::Transaction;
+use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::mem::ManuallyDrop;
 use std::sync::Arc;
 use std::thread;
 
+use crate::rocks::ttl;
 use crate::RocksDb;
 
 /// Cache column families to avoid further cloning on each access.
 struct ColumnFamilyCache<'a> {
     db: &'a OptimisticTransactionDB,
+    ttls: &'a BTreeMap<String, u64>,
     cfs: RefCell<BTreeMap<String, Arc<BoundColumnFamily<'a>>>>,
 }
 
 impl<'a> ColumnFamilyCache<'a> {
-    fn new(db: &'a OptimisticTransactionDB) -> Self {
+    fn new(db: &'a RocksDb) -> Self {
         Self {
-            db,
+            db: &db.db,
+            ttls: &db.ttls,
             cfs: Default::default(),
         }
     }
 
+    /// The TTL of a column family, if it has one.
+    fn ttl(&self, name: &str) -> Option<u64> {
+        self.ttls.get(name).copied()
+    }
+
     /// Look up a column family and pass it to a closure.
     /// Return an error if it doesn't exist.
     fn with_cf_handle<F, T>(&self, name: &str, f: F) -> KVResult<T>
@@ -92,7 +103,7 @@ where
     fn read(&self) -> Self::Tx<'_> {
         let snapshot = self.db.snapshot();
         RocksDbReadTx {
-            cache: ColumnFamilyCache::new(&self.db),
+            cache: ColumnFamilyCache::new(self),
             snapshot,
         }
     }
@@ -109,7 +120,7 @@ where
 
     fn write(&self) -> Self::Tx<'_> {
         RocksDbWriteTx {
-            cache: ColumnFamilyCache::new(&self.db),
+            cache: ColumnFamilyCache::new(self),
             tx: ManuallyDrop::new(self.db.transaction()),
         }
     }
@@ -132,34 +143,40 @@ where
                 .get_cf(cf, key.as_ref())
                 .map_err(to_kv_error)?;
 
-            match res {
+            match res.and_then(|bz| live_value(bz, self.cache.ttl(ns.as_ref()))) {
                 Some(bz) => Ok(Some(S::from_repr(&bz)?)),
                 None => Ok(None),
             }
         })
     }
 
-    fn iterate<K, V>(&self, ns: &S::Namespace) -> impl Iterator<Item = KVResult<(K, V)>>
+    fn iterate_range<K, V>(
+        &self,
+        ns: &S::Namespace,
+        range: IterRange<S::Repr>,
+    ) -> impl Iterator<Item = KVResult<(K, V)>>
     where
         S: Decode<K> + Decode<V>,
         <S as KVStore>::Repr: Ord + 'static,
     {
         self.cache
             .with_cf_handle(ns.as_ref(), |cf| {
-                let it = self.snapshot.iterator_cf(cf, rocksdb::IteratorMode::Start);
-
-                let it = it.map(|res| res.map_err(to_kv_error)).map(|res| {
-                    res.and_then(|(k, v)| {
-                        let k: K = S::from_repr(&k.to_vec())?;
-                        let v: V = S::from_repr(&v.to_vec())?;
-                        Ok((k, v))
-                    })
-                });
-
-                Ok(it)
+                let it = self.snapshot.iterator_cf(cf, iterator_mode(&range));
+                let ttl = self.cache.ttl(ns.as_ref());
+                Ok(decode_range::<S, K, V, _>(it, range, ttl))
             })
             .expect("just wrapped into ok")
     }
+
+    fn count_range(&self, ns: &S::Namespace, range: IterRange<S::Repr>) -> KVResult<usize>
+    where
+        <S as KVStore>::Repr: Ord + 'static,
+    {
+        self.cache.with_cf_handle(ns.as_ref(), |cf| {
+            let it = self.snapshot.iterator_cf(cf, iterator_mode(&range));
+            count_range(it, range, self.cache.ttl(ns.as_ref()))
+        })
+    }
 }
 
 impl<'a, S> KVRead<S> for RocksDbWriteTx<'a>
@@ -176,34 +193,40 @@ where
 
             let res = self.tx.get_cf(cf, key.as_ref()).map_err(to_kv_error)?;
 
-            match res {
+            match res.and_then(|bz| live_value(bz, self.cache.ttl(ns.as_ref()))) {
                 Some(bz) => Ok(Some(S::from_repr(&bz)?)),
                 None => Ok(None),
             }
         })
     }
 
-    fn iterate<K, V>(&self, ns: &S::Namespace) -> impl Iterator<Item = KVResult<(K, V)>>
+    fn iterate_range<K, V>(
+        &self,
+        ns: &S::Namespace,
+        range: IterRange<S::Repr>,
+    ) -> impl Iterator<Item = KVResult<(K, V)>>
     where
         S: Decode<K> + Decode<V>,
         <S as KVStore>::Repr: Ord + 'static,
     {
         self.cache
             .with_cf_handle(ns.as_ref(), |cf| {
-                let it = self.tx.iterator_cf(cf, rocksdb::IteratorMode::Start);
-
-                let it = it.map(|res| res.map_err(to_kv_error)).map(|res| {
-                    res.and_then(|(k, v)| {
-                        let k: K = S::from_repr(&k.to_vec())?;
-                        let v: V = S::from_repr(&v.to_vec())?;
-                        Ok((k, v))
-                    })
-                });
-
-                Ok(it)
+                let it = self.tx.iterator_cf(cf, iterator_mode(&range));
+                let ttl = self.cache.ttl(ns.as_ref());
+                Ok(decode_range::<S, K, V, _>(it, range, ttl))
             })
             .expect("just wrapped into ok")
     }
+
+    fn count_range(&self, ns: &S::Namespace, range: IterRange<S::Repr>) -> KVResult<usize>
+    where
+        <S as KVStore>::Repr: Ord + 'static,
+    {
+        self.cache.with_cf_handle(ns.as_ref(), |cf| {
+            let it = self.tx.iterator_cf(cf, iterator_mode(&range));
+            count_range(it, range, self.cache.ttl(ns.as_ref()))
+        })
+    }
 }
 
 impl<'a, S> KVWrite<S> for RocksDbWriteTx<'a>
@@ -218,6 +241,7 @@ where
         self.cache.with_cf_handle(ns.as_ref(), |cf| {
             let k = S::to_repr(k)?;
             let v = S::to_repr(v)?;
+            let v = stamp_value(&v, self.cache.ttl(ns.as_ref()));
 
             self.tx
                 .put_cf(cf, k.as_ref(), v.as_ref())
@@ -239,6 +263,51 @@ where
             Ok(())
         })
     }
+
+    /// Encode all items before writing any of them, then look up the column family once.
+    fn put_many<'i, K, V, I>(&mut self, ns: &S::Namespace, items: I) -> KVResult<()>
+    where
+        S: Encode<K> + Encode<V>,
+        I: IntoIterator<Item = (&'i K, &'i V)>,
+        K: 'i,
+        V: 'i,
+    {
+        let items = items
+            .into_iter()
+            .map(|(k, v)| Ok((S::to_repr(k)?, S::to_repr(v)?)))
+            .collect::<KVResult<Vec<_>>>()?;
+
+        let ttl = self.cache.ttl(ns.as_ref());
+
+        self.cache.with_cf_handle(ns.as_ref(), |cf| {
+            for (k, v) in items {
+                let v = stamp_value(&v, ttl);
+                self.tx
+                    .put_cf(cf, k.as_ref(), v.as_ref())
+                    .map_err(to_kv_error)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn delete_many<'i, K, I>(&mut self, ns: &S::Namespace, keys: I) -> KVResult<()>
+    where
+        S: Encode<K>,
+        I: IntoIterator<Item = &'i K>,
+        K: 'i,
+    {
+        let keys = keys
+            .into_iter()
+            .map(S::to_repr)
+            .collect::<KVResult<Vec<_>>>()?;
+
+        self.cache.with_cf_handle(ns.as_ref(), |cf| {
+            for k in keys {
+                self.tx.delete_cf(cf, k.as_ref()).map_err(to_kv_error)?;
+            }
+            Ok(())
+        })
+    }
 }
 
 impl<'a> KVTransaction for RocksDbWriteTx<'a> {
@@ -261,6 +330,25 @@ impl<'a> Drop for RocksDbWriteTx<'a> {
     }
 }
 
+/// Append the write timestamp to values in namespaces with a TTL.
+fn stamp_value(v: &[u8], ttl: Option<u64>) -> Cow<[u8]> {
+    match ttl {
+        Some(_) => Cow::Owned(ttl::stamp(v, ttl::now())),
+        None => Cow::Borrowed(v),
+    }
+}
+
+/// Strip the write timestamp from values in namespaces with a TTL, hiding the expired ones.
+fn live_value<T>(bz: T, ttl: Option<u64>) -> Option<Vec<u8>>
+where
+    T: AsRef<[u8]> + Into<Vec<u8>>,
+{
+    match ttl {
+        Some(ttl) => ttl::unstamp(bz.as_ref(), ttl, ttl::now()).map(|v| v.to_vec()),
+        None => Some(bz.into()),
+    }
+}
+
 fn to_kv_error(e: rocksdb::Error) -> KVError {
     if e.kind() == ErrorKind::Busy {
         KVError::Conflict
@@ -269,14 +357,83 @@ fn to_kv_error(e: rocksdb::Error) -> KVError {
     }
 }
 
+/// Position the iterator at the first item of the range, in the direction of the iteration.
+fn iterator_mode(range: &IterRange<Vec<u8>>) -> IteratorMode<'_> {
+    match (range.reverse, &range.start, &range.end) {
+        (false, Some(start), _) => IteratorMode::From(start, Direction::Forward),
+        (false, None, _) => IteratorMode::Start,
+        (true, _, Some(end)) => IteratorMode::From(end, Direction::Reverse),
+        (true, _, None) => IteratorMode::End,
+    }
+}
+
+/// Take the items from an iterator positioned by [`iterator_mode`] until it leaves the range,
+/// skipping the expired ones if the namespace has a TTL.
+fn take_range<I>(
+    it: I,
+    range: IterRange<Vec<u8>>,
+    ttl: Option<u64>,
+) -> impl Iterator<Item = KVResult<(Box<[u8]>, Vec<u8>)>>
+where
+    I: Iterator<Item = Result<(Box<[u8]>, Box<[u8]>), rocksdb::Error>>,
+{
+    let reverse = range.reverse;
+    let end = range.clone();
+
+    it.map(|res| res.map_err(to_kv_error))
+        // Seeking backwards lands on the exclusive upper bound itself, if it exists.
+        .skip_while(move |res| reverse && matches!(res, Ok((k, _)) if end.is_past_end::<[u8]>(k)))
+        .take_while(move |res| match res {
+            Ok((k, _)) => range.contains::<[u8]>(k),
+            Err(_) => true,
+        })
+        .filter_map(move |res| match res {
+            Ok((k, v)) => live_value(v, ttl).map(|v| Ok((k, v))),
+            Err(e) => Some(Err(e)),
+        })
+}
+
+/// Decode the items of the range.
+fn decode_range<S, K, V, I>(
+    it: I,
+    range: IterRange<Vec<u8>>,
+    ttl: Option<u64>,
+) -> impl Iterator<Item = KVResult<(K, V)>>
+where
+    S: KVStore<Repr = Vec<u8>> + Decode<K> + Decode<V>,
+    I: Iterator<Item = Result<(Box<[u8]>, Box<[u8]>), rocksdb::Error>>,
+{
+    take_range(it, range, ttl).map(|res| {
+        res.and_then(|(k, v)| {
+            let k: K = S::from_repr(&k.to_vec())?;
+            let v: V = S::from_repr(&v)?;
+            Ok((k, v))
+        })
+    })
+}
+
+/// Count the items of the range, stopping at the first error.
+fn count_range<I>(it: I, range: IterRange<Vec<u8>>, ttl: Option<u64>) -> KVResult<usize>
+where
+    I: Iterator<Item = Result<(Box<[u8]>, Box<[u8]>), rocksdb::Error>>,
+{
+    take_range(it, range, ttl).try_fold(0, |n, res| res.map(|_| n + 1))
+}
+
 #[cfg(all(feature = "kvstore", test))]
 mod tests {
     use std::borrow::Cow;
+    use std::time::Duration;
 
     use quickcheck::{QuickCheck, Testable};
+    use rocksdb::IteratorMode;
     use serde::{de::DeserializeOwned, Serialize};
+    use tempfile::TempDir;
 
-    use fendermint_storage::{testing::*, Codec, Decode, Encode, KVError, KVResult, KVStore};
+    use fendermint_storage::{
+        testing::*, Codec, Decode, Encode, KVError, KVRead, KVReadable, KVResult, KVStore,
+        KVTransaction, KVWritable, KVWrite,
+    };
 
     use crate::{RocksDb, RocksDbConfig};
 
@@ -306,18 +463,41 @@ mod tests {
     impl<T> Codec<T> for TestKVStore where TestKVStore: Encode<T> + Decode<T> {}
 
     fn new_backend() -> RocksDb {
+        new_backend_with_config(&RocksDbConfig::default())
+    }
+
+    /// Backend where all test namespaces have a TTL.
+    fn new_backend_with_ttl(ttl: u64) -> RocksDb {
+        let config = RocksDbConfig {
+            namespace_ttl: test_namespaces()
+                .iter()
+                .map(|ns| (ns.to_string(), ttl))
+                .collect(),
+            ..Default::default()
+        };
+        new_backend_with_config(&config)
+    }
+
+    fn new_backend_with_config(config: &RocksDbConfig) -> RocksDb {
+        let (_dir, db) = new_backend_with_dir(config);
+        db
+    }
+
+    /// Backend along with its temporary directory, which has to be kept around
+    /// by tests that flush or compact, which creates new files.
+    fn new_backend_with_dir(config: &RocksDbConfig) -> (TempDir, RocksDb) {
         let dir = tempfile::Builder::new()
             .tempdir()
             .expect("error creating temporary path for db");
         let path = dir.path().join("rocksdb");
-        let db = RocksDb::open(path, &RocksDbConfig::default()).expect("error creating RocksDB");
+        let db = RocksDb::open(path, config).expect("error creating RocksDB");
 
         // Create the column families the test will use.
         for name in test_namespaces() {
             let _ = db.new_cf_handle(name).unwrap();
         }
 
-        db
+        (dir, db)
     }
 
     // Not using the `#[quickcheck]` macro so I can run fewer tests becasue they are slow.
@@ -374,4 +554,157 @@ mod tests {
             }) as fn(TestData) -> bool,
         )
     }
+
+    #[test]
+    fn iterate_range() {
+        let backend = new_backend();
+        assert!(check_iterate_range::<TestKVStore>(&backend))
+    }
+
+    #[test]
+    fn put_many() {
+        let backend = new_backend();
+        assert!(check_put_many::<TestKVStore>(&backend))
+    }
+
+    #[test]
+    fn put_many_conflict() {
+        let backend = new_backend();
+        assert!(check_put_many_conflict::<TestKVStore, _>(&backend))
+    }
+
+    #[test]
+    fn writable_with_ttl() {
+        run_quickcheck(
+            (|data| {
+                let backend = new_backend_with_ttl(3600);
+                check_writable::<TestKVStore>(&backend, data)
+            }) as fn(TestData) -> bool,
+        )
+    }
+
+    #[test]
+    fn iterate_range_with_ttl() {
+        let backend = new_backend_with_ttl(3600);
+        assert!(check_iterate_range::<TestKVStore>(&backend))
+    }
+
+    #[test]
+    fn expire_with_ttl() {
+        let config = RocksDbConfig {
+            namespace_ttl: [("fizz".to_string(), 1)].into(),
+            ..Default::default()
+        };
+        let (_dir, backend) = new_backend_with_dir(&config);
+        let key = "foo".to_string();
+
+        let mut tx = <RocksDb as KVWritable<TestKVStore>>::write(&backend);
+        tx.put(&"fizz", &key, &1u8).unwrap();
+        tx.put(&"buzz", &key, &2u8).unwrap();
+        tx.commit().unwrap();
+
+        let get = |ns: &'static str| {
+            let tx = <RocksDb as KVReadable<TestKVStore>>::read(&backend);
+            tx.get::<String, u8>(&ns, &key).unwrap()
+        };
+        let count_raw = |ns: &str| {
+            let cf = backend.db.cf_handle(ns).unwrap();
+            backend.db.iterator_cf(&cf, IteratorMode::Start).count()
+        };
+
+        assert_eq!(get("fizz"), Some(1));
+        assert_eq!(get("buzz"), Some(2));
+
+        std::thread::sleep(Duration::from_secs(2));
+
+        // Expired but not compacted yet.
+        assert_eq!(get("fizz"), None);
+        assert_eq!(get("buzz"), Some(2));
+        assert_eq!(count_raw("fizz"), 1);
+
+        backend.compact_range(Some("fizz")).unwrap();
+
+        assert_eq!(count_raw("fizz"), 0);
+        assert_eq!(count_raw("buzz"), 1);
+    }
+
+    #[test]
+    fn ttl_changes_on_reopen() {
+        let (dir, backend) = new_backend_with_dir(&RocksDbConfig::default());
+        let path = dir.path().join("rocksdb");
+        let with_ttl = |ns: &str| RocksDbConfig {
+            namespace_ttl: [(ns.to_string(), 3600)].into(),
+            ..Default::default()
+        };
+
+        let mut tx = <RocksDb as KVWritable<TestKVStore>>::write(&backend);
+        tx.put(&"fizz", &"foo".to_string(), &1u8).unwrap();
+        tx.commit().unwrap();
+        drop(backend);
+
+        // Values written without a timestamp would count as expired.
+        assert!(RocksDb::open(&path, &with_ttl("fizz")).is_err());
+
+        // An empty namespace can be given a TTL, but then it cannot lose it.
+        let backend = RocksDb::open(&path, &with_ttl("buzz")).unwrap();
+        drop(backend);
+        assert!(RocksDb::open(&path, &RocksDbConfig::default()).is_err());
+        RocksDb::open(&path, &with_ttl("buzz")).unwrap();
+    }
+
+    #[test]
+    fn compact_all_without_auto_compaction() {
+        let config = RocksDbConfig {
+            compaction_style: "none".into(),
+            namespace_ttl: [("fizz".to_string(), 1), ("buzz".to_string(), 1)].into(),
+            ..Default::default()
+        };
+        let (_dir, backend) = new_backend_with_dir(&config);
+        let key = "foo".to_string();
+
+        let mut tx = <RocksDb as KVWritable<TestKVStore>>::write(&backend);
+        tx.put(&"fizz", &key, &1u8).unwrap();
+        tx.put(&"buzz", &key, &2u8).unwrap();
+        tx.commit().unwrap();
+
+        let count_raw = |ns: &str| {
+            let cf = backend.db.cf_handle(ns).unwrap();
+            backend.db.iterator_cf(&cf, IteratorMode::Start).count()
+        };
+
+        std::thread::sleep(Duration::from_secs(2));
+
+        assert_eq!(count_raw("fizz"), 1);
+        assert_eq!(count_raw("buzz"), 1);
+
+        backend.compact_all().unwrap();
+
+        assert_eq!(count_raw("fizz"), 0);
+        assert_eq!(count_raw("buzz"), 0);
+
+        assert!(backend.compact_range(Some("no-such-namespace")).is_err());
+    }
+
+    #[test]
+    fn namespace_stats() {
+        let (_dir, backend) = new_backend_with_dir(&RocksDbConfig::default());
+        let value = vec![1u8; 64 * 1024];
+
+        let mut tx = <RocksDb as KVWritable<TestKVStore>>::write(&backend);
+        for i in 0..16u8 {
+            tx.put(&"fizz", &i, &value).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let cf = backend.db.cf_handle("fizz").unwrap();
+        backend.db.flush_cf(&cf).unwrap();
+
+        let stats = backend.namespace_stats().unwrap();
+        for ns in test_namespaces() {
+            assert!(stats.contains_key(*ns), "missing {ns}");
+        }
+        assert!(stats.contains_key("default"));
+        assert!(stats["fizz"] > 0);
+        assert_eq!(stats["buzz"], 0);
+    }
 }
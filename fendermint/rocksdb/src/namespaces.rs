@@ -3,22 +3,36 @@
 
 /// List all column families to help keep them unique.
 ///
+/// The struct can be given a visibility, e.g. `namespaces!(pub(crate) MySpace { foo })`.
+///
+/// Namespaces meant for ephemeral data can declare a default TTL in seconds,
+/// which can be passed to [RocksDbConfig](crate::RocksDbConfig::namespace_ttl).
+/// Only these namespaces store the write timestamp with their values, so only
+/// they can be given a different TTL.
+///
 /// # Example
 ///
 /// ```
 /// use fendermint_rocksdb::namespaces;
 ///
-/// namespaces!(MySpace { foo, bar });
+/// namespaces!(MySpace { foo, bar = 3600 });
 ///
 /// let ms = MySpace::default();
 /// let nss = ms.values();
 /// let ns_foo = &ms.foo;
+/// let ttls = ms.ttls();
+/// assert_eq!(ttls.get("bar"), Some(&3600));
+///
+/// let ttls = ms.ttls_with(&[("bar".to_string(), 60)].into()).unwrap();
+/// assert_eq!(ttls.get("bar"), Some(&60));
+/// assert!(ms.ttls_with(&[("foo".to_string(), 60)].into()).is_err());
+/// assert!(ms.ttls_with(&[("baz".to_string(), 60)].into()).is_err());
 /// ```
 #[macro_export]
 macro_rules! namespaces {
-    ($name:ident { $($col:ident),* }) => {
-        struct $name {
-            pub $($col: String),+
+    ($vis:vis $name:ident { $($col:ident $(= $ttl:literal)?),* }) => {
+        $vis struct $name {
+            $(pub $col: String),+
         }
 
         impl Default for $name {
@@ -34,6 +48,38 @@ macro_rules! namespaces {
             pub fn values(&self) -> Vec<&str> {
                 vec![$(self.$col.as_ref()),+]
             }
+
+            /// The default TTL of the column families which declared one, in seconds.
+            #[allow(unused_mut)]
+            pub fn ttls(&self) -> std::collections::BTreeMap<String, u64> {
+                let mut ttls = std::collections::BTreeMap::new();
+                $($(ttls.insert(self.$col.clone(), $ttl);)?)*
+                ttls
+            }
+
+            /// The default TTL of the column families with some of them overridden, in seconds.
+            ///
+            /// Fails if an override names an unknown column family, or one which didn't declare a TTL.
+            pub fn ttls_with(
+                &self,
+                overrides: &std::collections::BTreeMap<String, u64>,
+            ) -> Result<std::collections::BTreeMap<String, u64>, $crate::RocksDbError> {
+                let mut ttls = self.ttls();
+                for (name, ttl) in overrides {
+                    if !self.values().contains(&name.as_str()) {
+                        return Err($crate::RocksDbError::Other(format!(
+                            "unknown namespace '{name}'"
+                        )));
+                    }
+                    if !ttls.contains_key(name) {
+                        return Err($crate::RocksDbError::Other(format!(
+                            "namespace '{name}' cannot have a TTL, it doesn't store timestamped values"
+                        )));
+                    }
+                    ttls.insert(name.clone(), *ttl);
+                }
+                Ok(ttls)
+            }
         }
     };
 }
@@ -8,6 +8,7 @@ use rocksdb::{
     Options,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Only subset of possible options is implemented, add missing ones when needed.
 /// For description of different options please refer to the `rocksdb` crate documentation.
@@ -26,7 +27,16 @@ pub struct RocksDbConfig {
     pub stats_dump_period_sec: u32,
     pub log_level: String,
     pub optimize_filters_for_hits: bool,
+    /// Size of the block cache in MiB when optimizing for point lookups; negative disables the optimization.
     pub optimize_for_point_lookup: i32,
+    /// Size of the LRU block cache in MiB, overriding the one set by `optimize_for_point_lookup`.
+    #[serde(default)]
+    pub block_cache_size_mb: Option<usize>,
+    /// Time-to-live of the entries in seconds, for the namespaces meant for ephemeral data.
+    ///
+    /// Expired entries are not returned by reads and get removed during compaction.
+    #[serde(default)]
+    pub namespace_ttl: BTreeMap<String, u64>,
 }
 
 impl Default for RocksDbConfig {
@@ -44,6 +54,8 @@ impl Default for RocksDbConfig {
             log_level: "warn".into(),
             optimize_filters_for_hits: true,
             optimize_for_point_lookup: 8,
+            block_cache_size_mb: None,
+            namespace_ttl: BTreeMap::new(),
         }
     }
 }
@@ -76,7 +88,9 @@ impl From<&RocksDbConfig> for Options {
         // Comes from https://github.com/facebook/rocksdb/blob/main/options/options.cc#L606
         // Only modified to upgrade format to v5
         if !config.optimize_for_point_lookup.is_negative() {
-            let cache_size = config.optimize_for_point_lookup as usize;
+            let cache_size = config
+                .block_cache_size_mb
+                .unwrap_or(config.optimize_for_point_lookup as usize);
             let mut opts = BlockBasedOptions::default();
             opts.set_format_version(5);
             opts.set_data_block_index_type(DataBlockIndexType::BinaryAndHash);
@@ -87,6 +101,11 @@ impl From<&RocksDbConfig> for Options {
             db_opts.set_block_based_table_factory(&opts);
             db_opts.set_memtable_prefix_bloom_ratio(0.02);
             db_opts.set_memtable_whole_key_filtering(true);
+        } else if let Some(cache_size) = config.block_cache_size_mb {
+            let mut opts = BlockBasedOptions::default();
+            let cache = Cache::new_lru_cache(cache_size * 1024 * 1024);
+            opts.set_block_cache(&cache);
+            db_opts.set_block_based_table_factory(&opts);
         }
         db_opts
     }
@@ -209,4 +228,21 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn open_with_tuning_options() {
+        for optimize_for_point_lookup in [8, -1] {
+            let config = RocksDbConfig {
+                write_buffer_size: 4 * 1024 * 1024,
+                max_open_files: 256,
+                optimize_for_point_lookup,
+                block_cache_size_mb: Some(16),
+                ..Default::default()
+            };
+            let dir = tempfile::tempdir().unwrap();
+            let db = crate::RocksDb::open(dir.path().join("rocksdb"), &config).unwrap();
+            db.write(b"foo", b"bar").unwrap();
+            assert_eq!(db.read(b"foo").unwrap(), Some(b"bar".to_vec()));
+        }
+    }
 }
@@ -4,11 +4,13 @@
 
 use rocksdb::{
     ColumnFamilyDescriptor, ErrorKind, OptimisticTransactionDB, Options, WriteBatchWithTransaction,
+    DEFAULT_COLUMN_FAMILY_NAME,
 };
-use std::{path::Path, sync::Arc};
+use std::{collections::BTreeMap, path::Path, sync::Arc};
 
 mod config;
 mod error;
+pub(crate) mod ttl;
 
 pub use config::RocksDbConfig;
 pub use error::Error;
@@ -17,6 +19,8 @@ pub use error::Error;
 pub struct RocksDb {
     pub db: Arc<OptimisticTransactionDB>,
     options: Options,
+    /// Time-to-live of the entries in the namespaces which have one, in seconds.
+    pub(crate) ttls: Arc<BTreeMap<String, u64>>,
 }
 
 /// `RocksDb` is used as the KV store. Unlike the implementation in Forest
@@ -40,6 +44,10 @@ impl RocksDb {
     }
 
     /// Open existing column families and potentially create new ones, using the same config.
+    ///
+    /// The namespaces in [RocksDbConfig::namespace_ttl] get a compaction filter removing
+    /// their expired entries. Fails if an existing namespace with data is given a TTL
+    /// it didn't have before, or loses the TTL it had, since its values would be misread.
     pub fn open_cf<P, I, N>(path: P, config: &RocksDbConfig, cfs: I) -> Result<Self, Error>
     where
         P: AsRef<Path>,
@@ -48,17 +56,23 @@ impl RocksDb {
     {
         let db_opts: rocksdb::Options = config.into();
         let ex_cfs = Self::list_cf(&path, config)?;
-        let ex_cfs = ex_cfs
-            .into_iter()
-            .map(|cf| ColumnFamilyDescriptor::new(cf, db_opts.clone()));
+        let ex_cf_descs = ex_cfs.iter().map(|cf| {
+            let cf_opts = cf_options(&db_opts, &config.namespace_ttl, cf);
+            ColumnFamilyDescriptor::new(cf, cf_opts)
+        });
 
-        let db = OptimisticTransactionDB::open_cf_descriptors(&db_opts, path, ex_cfs)?;
+        let db = OptimisticTransactionDB::open_cf_descriptors(&db_opts, path, ex_cf_descs)?;
 
         let db = Self {
             db: Arc::new(db),
             options: db_opts,
+            ttls: Arc::new(config.namespace_ttl.clone()),
         };
 
+        for cf in ex_cfs.iter().filter(|cf| *cf != DEFAULT_COLUMN_FAMILY_NAME) {
+            db.check_ttl(cf)?;
+        }
+
         for cf in cfs {
             if !db.has_cf_handle(cf.as_ref()) {
                 db.new_cf_handle(cf.as_ref())?;
@@ -136,12 +150,82 @@ impl RocksDb {
         self.db.flush().map_err(|e| Error::Other(e.to_string()))
     }
 
+    /// Flush the memtables of all column families to disk, including writes without WAL.
+    pub fn flush_all(&self) -> Result<(), Error> {
+        self.flush()?;
+        for name in self.cf_names()? {
+            if name != DEFAULT_COLUMN_FAMILY_NAME {
+                if let Some(cf) = self.db.cf_handle(&name) {
+                    self.db.flush_cf(&cf)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Compact the whole key range of a namespace, or of the default column family if `None`.
+    ///
+    /// With the `none` compaction style auto-compaction is disabled, so this has to be
+    /// called to reclaim the space of overwritten, deleted and expired entries.
+    pub fn compact_range(&self, ns: Option<&str>) -> Result<(), Error> {
+        match ns {
+            None => self.db.compact_range(None::<&[u8]>, None::<&[u8]>),
+            Some(name) => {
+                let cf = self
+                    .db
+                    .cf_handle(name)
+                    .ok_or_else(|| Error::Other(format!("column family '{name}' doesn't exist")))?;
+                self.db.compact_range_cf(&cf, None::<&[u8]>, None::<&[u8]>)
+            }
+        }
+        Ok(())
+    }
+
+    /// Compact all column families in the database, one after the other.
+    pub fn compact_all(&self) -> Result<(), Error> {
+        self.compact_range(None)?;
+        for cf in self.cf_names()? {
+            if cf != DEFAULT_COLUMN_FAMILY_NAME {
+                self.compact_range(Some(&cf))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Total size of the SST files of each column family, including the default one, in bytes.
+    ///
+    /// Data which is still only in the memtables is not counted.
+    pub fn namespace_stats(&self) -> Result<BTreeMap<String, u64>, Error> {
+        let mut stats = BTreeMap::new();
+        for name in self.cf_names()? {
+            let cf = self
+                .db
+                .cf_handle(&name)
+                .ok_or_else(|| Error::Other(format!("column family '{name}' is not open")))?;
+            let size = self
+                .db
+                .property_int_value_cf(&cf, "rocksdb.total-sst-files-size")?
+                .unwrap_or_default();
+            stats.insert(name, size);
+        }
+        Ok(stats)
+    }
+
+    /// Names of all column families in the database.
+    fn cf_names(&self) -> Result<Vec<String>, Error> {
+        let names = OptimisticTransactionDB::<rocksdb::MultiThreaded>::list_cf(
+            &self.options,
+            self.db.path(),
+        )?;
+        Ok(names)
+    }
+
     /// Check if a column family exists
     pub fn has_cf_handle(&self, name: &str) -> bool {
         self.db.cf_handle(name).is_some()
     }
 
-    /// Create a new column family, using the default options.
+    /// Create a new column family, using the default options, plus the TTL if it has one.
     ///
     /// Returns error if it already exists.
     pub fn new_cf_handle<'a>(&self, name: &'a str) -> Result<&'a str, Error> {
@@ -150,7 +234,55 @@ impl RocksDb {
                 "column family '{name}' already exists"
             )));
         }
-        self.db.create_cf(name, &self.options)?;
+        self.db
+            .create_cf(name, &cf_options(&self.options, &self.ttls, name))?;
+        if self.ttls.contains_key(name) {
+            self.db.put(ttl::marker_key(name), [])?;
+        }
         Ok(name)
     }
+
+    /// Check that an existing column family is opened with a TTL if and only if its values
+    /// are timestamped, which is recorded by a marker when the column family gets its TTL.
+    ///
+    /// A column family without data can be given a TTL, but not one which has values
+    /// written without the timestamp, which would be treated as expired.
+    fn check_ttl(&self, name: &str) -> Result<(), Error> {
+        let has_marker = self.exists(ttl::marker_key(name))?;
+        match (self.ttls.contains_key(name), has_marker) {
+            (true, false) => {
+                let cf = self
+                    .db
+                    .cf_handle(name)
+                    .ok_or_else(|| Error::Other(format!("column family '{name}' is not open")))?;
+                if self
+                    .db
+                    .iterator_cf(&cf, rocksdb::IteratorMode::Start)
+                    .next()
+                    .is_some()
+                {
+                    return Err(Error::Other(format!(
+                        "column family '{name}' already has data written without a TTL"
+                    )));
+                }
+                self.db.put(ttl::marker_key(name), [])?;
+            }
+            (false, true) => {
+                return Err(Error::Other(format!(
+                    "column family '{name}' has timestamped values and needs a TTL"
+                )));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Options of a column family, which remove the expired entries during compaction if it has a TTL.
+fn cf_options(db_opts: &Options, ttls: &BTreeMap<String, u64>, name: &str) -> Options {
+    let mut opts = db_opts.clone();
+    if let Some(ttl) = ttls.get(name) {
+        opts.set_compaction_filter("ttl", ttl::compaction_filter(*ttl));
+    }
+    opts
 }
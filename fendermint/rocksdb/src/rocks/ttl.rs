@@ -0,0 +1,79 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Expiry of entries in column families with a TTL.
+//!
+//! `OptimisticTransactionDB` cannot be opened with RocksDB's own TTL support,
+//! so values written to namespaces with a TTL get the write timestamp appended
+//! to them, which a compaction filter uses to remove the expired entries.
+//!
+//! Until they are compacted away expired entries are still on disk, but reads
+//! check the embedded timestamp and treat them as absent. Values without a
+//! timestamp, ie. ones written before the TTL was enabled, count as expired.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rocksdb::compaction_filter::Decision;
+
+/// Number of bytes appended to the values.
+const TIMESTAMP_LEN: usize = 8;
+
+/// Key in the default column family marking a column family as having timestamped values.
+///
+/// The prefix cannot be the start of a CID, which is what the default column family stores.
+pub fn marker_key(name: &str) -> Vec<u8> {
+    format!("__ttl__/{name}").into_bytes()
+}
+
+/// Seconds since the epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Append the write timestamp to a value.
+pub fn stamp(value: &[u8], now: u64) -> Vec<u8> {
+    let mut bz = Vec::with_capacity(value.len() + TIMESTAMP_LEN);
+    bz.extend_from_slice(value);
+    bz.extend_from_slice(&now.to_be_bytes());
+    bz
+}
+
+/// Strip the write timestamp from a value, unless it has expired.
+pub fn unstamp(bz: &[u8], ttl: u64, now: u64) -> Option<&[u8]> {
+    if bz.len() < TIMESTAMP_LEN {
+        return None;
+    }
+    let (value, ts) = bz.split_at(bz.len() - TIMESTAMP_LEN);
+    let ts = u64::from_be_bytes(ts.try_into().expect("timestamp length checked"));
+
+    if ts.saturating_add(ttl) < now {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Compaction filter removing the expired entries of a column family.
+pub fn compaction_filter(ttl: u64) -> impl FnMut(u32, &[u8], &[u8]) -> Decision + Send + 'static {
+    move |_level, _key, value| match unstamp(value, ttl, now()) {
+        Some(_) => Decision::Keep,
+        None => Decision::Remove,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{stamp, unstamp};
+
+    #[test]
+    fn stamp_unstamp() {
+        let bz = stamp(b"foo", 100);
+        assert_eq!(unstamp(&bz, 10, 100), Some(&b"foo"[..]));
+        assert_eq!(unstamp(&bz, 10, 110), Some(&b"foo"[..]));
+        assert_eq!(unstamp(&bz, 10, 111), None);
+        assert_eq!(unstamp(b"foo", 10, 100), None, "missing timestamp");
+    }
+}
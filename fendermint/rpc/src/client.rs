@@ -3,12 +3,14 @@
 
 use std::fmt::Display;
 use std::marker::PhantomData;
+use std::time::Duration;
 
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
 use fendermint_vm_message::chain::ChainMessage;
 use tendermint::abci::types::ExecTxResult;
 use tendermint::block::Height;
+use tendermint::Hash;
 use tendermint_rpc::{endpoint::abci_query::AbciQuery, Client, HttpClient, Scheme, Url};
 use tendermint_rpc::{WebSocketClient, WebSocketClientDriver, WebSocketClientUrl};
 
@@ -17,7 +19,8 @@ use fendermint_vm_message::query::{FvmQuery, FvmQueryHeight};
 use crate::message::SignedMessageFactory;
 use crate::query::QueryClient;
 use crate::tx::{
-    AsyncResponse, BoundClient, CommitResponse, SyncResponse, TxAsync, TxClient, TxCommit, TxSync,
+    wait_for_commit, AsyncResponse, BoundClient, CommitResponse, SyncResponse, TxAsync, TxClient,
+    TxCommit, TxSync, WaitResponse,
 };
 
 // Retrieve the proxy URL with precedence:
@@ -126,6 +129,40 @@ impl<C> TendermintClient<C> for FendermintClient<C> {
     }
 }
 
+impl<C> FendermintClient<C>
+where
+    C: Client + Sync + Send,
+{
+    /// Poll the chain until a transaction broadcasted earlier is included in a block and is
+    /// `confirmations` blocks deep, then decode its return data with `f`.
+    ///
+    /// See [`wait_for_commit`] for the re-broadcasting of the optional message.
+    pub async fn wait_for_commit<F, T>(
+        &self,
+        hash: Hash,
+        confirmations: u64,
+        timeout: Duration,
+        poll_interval: Duration,
+        rebroadcast: Option<&ChainMessage>,
+        f: F,
+    ) -> anyhow::Result<WaitResponse<T>>
+    where
+        F: FnOnce(&ExecTxResult) -> anyhow::Result<T> + Send,
+        T: Send,
+    {
+        wait_for_commit(
+            self,
+            hash,
+            confirmations,
+            timeout,
+            poll_interval,
+            rebroadcast,
+            f,
+        )
+        .await
+    }
+}
+
 #[async_trait]
 impl<C> QueryClient for FendermintClient<C>
 where
@@ -134,6 +171,10 @@ where
     async fn perform(&self, query: FvmQuery, height: FvmQueryHeight) -> anyhow::Result<AbciQuery> {
         perform_query(&self.inner, query, height).await
     }
+
+    async fn perform_path(&self, path: &str, data: Vec<u8>) -> anyhow::Result<AbciQuery> {
+        perform_path_query(&self.inner, path, data).await
+    }
 }
 
 /// Fendermint client capable of signing transactions.
@@ -174,6 +215,10 @@ where
     async fn perform(&self, query: FvmQuery, height: FvmQueryHeight) -> anyhow::Result<AbciQuery> {
         perform_query(&self.inner, query, height).await
     }
+
+    async fn perform_path(&self, path: &str, data: Vec<u8>) -> anyhow::Result<AbciQuery> {
+        perform_path_query(&self.inner, path, data).await
+    }
 }
 
 #[async_trait]
@@ -282,7 +327,27 @@ where
     //     .await
     //     .context("abci query failed")?;
 
-    let req = tendermint_rpc::endpoint::abci_query::Request::new(None, data, Some(height), false);
+    abci_query(client, None, data, Some(height)).await
+}
+
+async fn perform_path_query<C>(client: &C, path: &str, data: Vec<u8>) -> anyhow::Result<AbciQuery>
+where
+    C: Client + Sync + Send,
+{
+    tracing::debug!(path, "perform ABCI path query");
+    abci_query(client, Some(path.to_string()), data, None).await
+}
+
+async fn abci_query<C>(
+    client: &C,
+    path: Option<String>,
+    data: Vec<u8>,
+    height: Option<Height>,
+) -> anyhow::Result<AbciQuery>
+where
+    C: Client + Sync + Send,
+{
+    let req = tendermint_rpc::endpoint::abci_query::Request::new(path, data, height, false);
 
     let res = client
         .perform(debug::DebugRequest(req))
@@ -189,6 +189,24 @@ impl SignedMessageFactory {
         Ok(chain)
     }
 
+    /// Send a message to an actor, signed over its EIP-712 typed-data representation,
+    /// the same way a wallet such as MetaMask would sign it.
+    pub fn transaction_eip712(
+        &mut self,
+        to: Address,
+        method_num: MethodNum,
+        params: RawBytes,
+        value: TokenAmount,
+        gas_params: GasParams,
+    ) -> anyhow::Result<ChainMessage> {
+        let message = self
+            .inner
+            .transaction(to, method_num, params, value, gas_params);
+        let signed = SignedMessage::new_eip712(message, &self.sk, &self.chain_id)?;
+        let chain = ChainMessage::Signed(signed);
+        Ok(chain)
+    }
+
     /// Deploy a FEVM contract.
     pub fn fevm_create(
         &mut self,
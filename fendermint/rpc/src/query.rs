@@ -3,6 +3,7 @@
 
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
+use futures::{StreamExt, TryStreamExt};
 use fvm_ipld_encoding::serde::Serialize;
 use fvm_shared::message::Message;
 use prost::Message as ProstMessage;
@@ -11,15 +12,19 @@ use tendermint::block::Height;
 use tendermint_rpc::endpoint::abci_query::AbciQuery;
 
 use cid::Cid;
-use fvm_shared::ActorID;
 use fvm_shared::{address::Address, error::ExitCode};
+use fvm_shared::{clock::ChainEpoch, ActorID};
 
 use fendermint_vm_message::query::{
-    ActorState, BuiltinActors, FvmQuery, FvmQueryHeight, GasEstimate, StateParams,
+    ActorState, BlockHashLookup, BuiltinActors, FvmQuery, FvmQueryHeight, GasEstimate, StateParams,
+    TopDownView, TOPDOWN_VIEW_PATH,
 };
 
 use crate::response::encode_data;
 
+/// Maximum number of queries in flight when querying in batches.
+const BATCH_QUERY_CONCURRENCY: usize = 16;
+
 #[derive(Serialize, Debug, Clone)]
 /// The parsed value from a query, along with the height at which the query was performed.
 pub struct QueryResponse<T> {
@@ -54,6 +59,28 @@ pub trait QueryClient: Sync {
         Ok(QueryResponse { height, value })
     }
 
+    /// Query the state of many actors concurrently.
+    ///
+    /// The results are in the same order as the addresses. The queries are independent,
+    /// so unless the `height` is fixed they might be answered from different blocks.
+    async fn actor_states(
+        &self,
+        addresses: &[Address],
+        height: FvmQueryHeight,
+    ) -> anyhow::Result<Vec<(Address, Option<(ActorID, ActorState)>)>> {
+        futures::stream::iter(addresses)
+            .map(|address| async move {
+                let res = self
+                    .actor_state(address, height)
+                    .await
+                    .with_context(|| format!("failed to query actor state of {address}"))?;
+                anyhow::Ok((*address, res.value))
+            })
+            .buffered(BATCH_QUERY_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
     /// Run a message in a read-only fashion.
     async fn call(
         &self,
@@ -127,8 +154,43 @@ pub trait QueryClient: Sync {
         Ok(QueryResponse { height, value })
     }
 
+    /// Look up the hash of the block at `block_height`, as recorded by the chain metadata actor.
+    async fn block_hash(
+        &self,
+        block_height: ChainEpoch,
+        height: FvmQueryHeight,
+    ) -> anyhow::Result<QueryResponse<BlockHashLookup>> {
+        let res = self
+            .perform(FvmQuery::BlockHash(block_height), height)
+            .await
+            .context("block hash query failed")?;
+        let height = res.height;
+        let value = extract(res, |res| {
+            fvm_ipld_encoding::from_slice(&res.value)
+                .context("failed to decode BlockHashLookup from query")
+        })?;
+        Ok(QueryResponse { height, value })
+    }
+
+    /// Inspect the view of the parent chain cached by the top-down finality provider of the node,
+    /// optionally including the cached data of the parent block at `parent_height`.
+    async fn topdown_view(&self, parent_height: Option<u64>) -> anyhow::Result<TopDownView> {
+        let data = fvm_ipld_encoding::to_vec(&parent_height).context("failed to encode height")?;
+        let res = self
+            .perform_path(TOPDOWN_VIEW_PATH, data)
+            .await
+            .context("top-down view query failed")?;
+        extract(res, |res| {
+            fvm_ipld_encoding::from_slice(&res.value)
+                .context("failed to decode TopDownView from query")
+        })
+    }
+
     /// Run an ABCI query.
     async fn perform(&self, query: FvmQuery, height: FvmQueryHeight) -> anyhow::Result<AbciQuery>;
+
+    /// Run an ABCI query on a node specific path, against the latest state of the node.
+    async fn perform_path(&self, path: &str, data: Vec<u8>) -> anyhow::Result<AbciQuery>;
 }
 
 /// Extract some value from the query result, unless it's not found or other error.
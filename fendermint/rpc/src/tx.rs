@@ -2,13 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use std::marker::PhantomData;
+use std::time::{Duration, Instant};
 
 use anyhow::Context;
 use async_trait::async_trait;
 use bytes::Bytes;
 use fendermint_vm_message::query::{FvmQueryHeight, GasEstimate};
 use tendermint::abci::types::ExecTxResult;
+use tendermint::abci::Code;
+use tendermint::block::Height;
+use tendermint::Hash;
 use tendermint_rpc::endpoint::broadcast::{tx_async, tx_commit, tx_sync};
+use tendermint_rpc::endpoint::tx;
+use tendermint_rpc::query::Query;
+use tendermint_rpc::{Client, Order};
 
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
@@ -18,6 +25,7 @@ use fvm_shared::MethodNum;
 use fendermint_vm_actor_interface::eam::CreateReturn;
 use fendermint_vm_message::chain::ChainMessage;
 
+use crate::client::TendermintClient;
 use crate::message::{GasParams, SignedMessageFactory};
 use crate::query::{QueryClient, QueryResponse};
 use crate::response::{decode_bytes, decode_fevm_create, decode_fevm_invoke};
@@ -99,12 +107,248 @@ pub trait TxClient<M: BroadcastMode = TxCommit>: BoundClient + Send + Sync {
         Ok(res)
     }
 
+    /// Broadcast a message without waiting for the check results, then poll the chain until
+    /// the transaction is included and is `confirmations` blocks deep, counting the block
+    /// it is included in.
+    ///
+    /// See [`wait_for_commit`] for how the waiting ends.
+    async fn submit_and_wait<C>(
+        &self,
+        msg: ChainMessage,
+        confirmations: u64,
+        timeout: Duration,
+    ) -> anyhow::Result<WaitResponse<RawBytes>>
+    where
+        Self: QueryClient + TendermintClient<C>,
+        C: Client + Sync + Send,
+    {
+        let data = SignedMessageFactory::serialize(&msg)?;
+
+        let hash = self
+            .underlying()
+            .broadcast_tx_async(data)
+            .await
+            .context("broadcast_tx_async failed")?
+            .hash;
+
+        wait_for_commit(
+            self,
+            hash,
+            confirmations,
+            timeout,
+            TX_WAIT_POLL_INTERVAL,
+            Some(&msg),
+            decode_bytes,
+        )
+        .await
+    }
+
+    /// Send a message to an actor and poll the chain until it's included in a block,
+    /// rather than relying on the server side timeout of `broadcast_tx_commit`.
+    ///
+    /// Fails with [`TxWaitError::Rejected`] if `CheckTx` rejects the message, otherwise
+    /// see [`wait_for_commit`] for how the waiting ends.
+    #[allow(clippy::too_many_arguments)]
+    async fn transaction_and_wait<C>(
+        &mut self,
+        to: Address,
+        method_num: MethodNum,
+        params: RawBytes,
+        value: TokenAmount,
+        gas_params: GasParams,
+        timeout: Duration,
+    ) -> anyhow::Result<WaitResponse<RawBytes>>
+    where
+        Self: QueryClient + TendermintClient<C>,
+        C: Client + Sync + Send,
+    {
+        let mf = self.message_factory_mut();
+        let msg = mf.transaction(to, method_num, params, value, gas_params)?;
+        let data = SignedMessageFactory::serialize(&msg)?;
+
+        let res = self
+            .underlying()
+            .broadcast_tx_sync(data)
+            .await
+            .context("broadcast_tx_sync failed")?;
+
+        if res.code.is_err() {
+            return Err(TxWaitError::Rejected {
+                hash: res.hash,
+                code: res.code,
+                log: res.log,
+            }
+            .into());
+        }
+
+        wait_for_commit(
+            &*self,
+            res.hash,
+            1,
+            timeout,
+            TX_WAIT_POLL_INTERVAL,
+            Some(&msg),
+            decode_bytes,
+        )
+        .await
+    }
+
     async fn perform<F, T>(&self, msg: ChainMessage, f: F) -> anyhow::Result<M::Response<T>>
     where
         F: FnOnce(&ExecTxResult) -> anyhow::Result<T> + Sync + Send,
         T: Sync + Send;
 }
 
+/// Poll the chain until a transaction is included in a block and is `confirmations` blocks
+/// deep, counting the block it is included in, then decode its return data with `f`.
+///
+/// If `rebroadcast` is given, it is broadcast again every time the transaction is not found
+/// in a block while the nonce of the sender is still unused, in case it was dropped from the
+/// mempool. Nodes ignore the duplicates if it's still there.
+///
+/// Fails with [`TxWaitError::Failed`] if the transaction was executed with an error,
+/// with [`TxWaitError::NotIncluded`] if it isn't in a block before the `timeout`, and
+/// with [`TxWaitError::Timeout`] if it is, but isn't deep enough.
+pub async fn wait_for_commit<Q, C, F, T>(
+    client: &Q,
+    hash: Hash,
+    confirmations: u64,
+    timeout: Duration,
+    poll_interval: Duration,
+    rebroadcast: Option<&ChainMessage>,
+    f: F,
+) -> anyhow::Result<WaitResponse<T>>
+where
+    Q: QueryClient + TendermintClient<C> + Sync,
+    C: Client + Sync + Send,
+    F: FnOnce(&ExecTxResult) -> anyhow::Result<T> + Send,
+    T: Send,
+{
+    let query = Query::eq("tx.hash", hash.to_string());
+    let start = Instant::now();
+
+    loop {
+        let res = client
+            .underlying()
+            .tx_search(query.clone(), false, 1, 1, Order::Ascending)
+            .await
+            .context("tx_search failed")?;
+
+        let included = match res.txs.into_iter().next() {
+            Some(response) => {
+                if response.tx_result.code.is_err() {
+                    return Err(TxWaitError::Failed {
+                        hash,
+                        height: response.height,
+                        code: response.tx_result.code,
+                        info: response.tx_result.info,
+                    }
+                    .into());
+                }
+
+                // Being included is one confirmation, no need to ask for the head.
+                let depth = if confirmations <= 1 {
+                    1
+                } else {
+                    let head = client
+                        .underlying()
+                        .latest_block()
+                        .await
+                        .context("failed to get latest block")?
+                        .block
+                        .header
+                        .height;
+
+                    tx_depth(response.height, head)
+                };
+
+                if depth >= confirmations {
+                    let return_data = f(&response.tx_result)
+                        .context("error decoding data from deliver_tx in wait")?;
+
+                    return Ok(WaitResponse {
+                        response,
+                        confirmations: depth,
+                        return_data,
+                    });
+                }
+                true
+            }
+            None => false,
+        };
+
+        if start.elapsed() > timeout {
+            return Err(if included {
+                TxWaitError::Timeout {
+                    hash,
+                    confirmations,
+                    timeout,
+                }
+            } else {
+                TxWaitError::NotIncluded { hash, timeout }
+            }
+            .into());
+        }
+
+        if let (false, Some(msg)) = (included, rebroadcast) {
+            rebroadcast_if_unused(client, hash, msg).await?;
+        }
+
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Broadcast a signed message again, unless the sender has already used its nonce.
+async fn rebroadcast_if_unused<Q, C>(
+    client: &Q,
+    hash: Hash,
+    msg: &ChainMessage,
+) -> anyhow::Result<()>
+where
+    Q: QueryClient + TendermintClient<C> + Sync,
+    C: Client + Sync + Send,
+{
+    let ChainMessage::Signed(signed) = msg else {
+        return Ok(());
+    };
+
+    let sequence = client
+        .actor_state(&signed.message.from, FvmQueryHeight::Committed)
+        .await
+        .context("failed to get the sequence of the sender")?
+        .value
+        .map(|(_, state)| state.sequence)
+        .unwrap_or_default();
+
+    if sequence > signed.message.sequence {
+        // Either it has just been included, or it has been replaced by another transaction.
+        return Ok(());
+    }
+
+    let data = SignedMessageFactory::serialize(msg)?;
+
+    match client.underlying().broadcast_tx_sync(data).await {
+        Ok(res) if res.code.is_ok() => {
+            tracing::info!(%hash, "re-broadcasted transaction missing from the mempool")
+        }
+        Ok(res) => tracing::debug!(%hash, log = res.log, "re-broadcasted transaction rejected"),
+        // The node returns an error if the transaction is still in its mempool.
+        Err(e) => {
+            tracing::debug!(%hash, error = e.to_string(), "failed to re-broadcast transaction")
+        }
+    }
+
+    Ok(())
+}
+
+/// How often to check whether a transaction we are waiting for has been included.
+const TX_WAIT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of blocks on top of a transaction included at `height`, counting its own block.
+fn tx_depth(height: Height, head: Height) -> u64 {
+    head.value().saturating_sub(height.value()) + 1
+}
+
 /// Convenience trait to call FEVM methods in read-only mode, without doing a transaction.
 #[async_trait]
 pub trait CallClient: QueryClient + BoundClient {
@@ -185,6 +429,37 @@ pub struct CommitResponse<T> {
     pub return_data: Option<T>,
 }
 
+pub struct WaitResponse<T> {
+    /// The transaction as included in the block, with its successful delivery results.
+    pub response: tx::Response,
+    /// Number of blocks the transaction was deep when we returned, counting its own block.
+    pub confirmations: u64,
+    /// Parsed return data.
+    pub return_data: T,
+}
+
+/// Reasons why [`wait_for_commit`] gave up on a transaction.
+#[derive(Debug, thiserror::Error)]
+pub enum TxWaitError {
+    #[error("transaction {hash} is not {confirmations} blocks deep after {timeout:?}")]
+    Timeout {
+        hash: Hash,
+        confirmations: u64,
+        timeout: Duration,
+    },
+    #[error("transaction {hash} was not included in a block after {timeout:?}")]
+    NotIncluded { hash: Hash, timeout: Duration },
+    #[error("transaction {hash} was rejected with code {code:?}: {log}")]
+    Rejected { hash: Hash, code: Code, log: String },
+    #[error("transaction {hash} failed at height {height} with code {code:?}: {info}")]
+    Failed {
+        hash: Hash,
+        height: Height,
+        code: Code,
+        info: String,
+    },
+}
+
 pub struct CallResponse<T> {
     /// Response from Tendermint.
     pub response: QueryResponse<tendermint::abci::types::ExecTxResult>,
@@ -203,3 +478,19 @@ impl BroadcastMode for TxSync {
 impl BroadcastMode for TxCommit {
     type Response<T> = CommitResponse<T>;
 }
+
+#[cfg(test)]
+mod tests {
+    use tendermint::block::Height;
+
+    use super::tx_depth;
+
+    #[test]
+    fn test_tx_depth() {
+        let h = |n: u32| Height::from(n);
+        assert_eq!(tx_depth(h(10), h(10)), 1);
+        assert_eq!(tx_depth(h(10), h(12)), 3);
+        // The node we asked for the head might lag behind the one which indexed the transaction.
+        assert_eq!(tx_depth(h(10), h(9)), 1);
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Tests of the query client against a mock Tendermint client which answers
+//! actor state queries based on the address in the request.
+
+use cid::Cid;
+use fendermint_rpc::{FendermintClient, QueryClient};
+use fendermint_vm_message::query::{ActorState, FvmQuery, FvmQueryHeight};
+use fvm_shared::{address::Address, econ::TokenAmount, error::ExitCode, ActorID};
+use tendermint::abci::Code;
+use tendermint_rpc::{
+    endpoint::abci_query::AbciQuery, request::RequestMessage, MockClient, MockRequestMatcher,
+    Request, Response,
+};
+
+/// Actors with an ID below this exist; the ones above are not found,
+/// except for [`FAILING_ID`] which fails with an error.
+const MAX_EXISTING_ID: ActorID = 1000;
+const FAILING_ID: ActorID = 1500;
+
+struct ActorStateMatcher;
+
+impl MockRequestMatcher for ActorStateMatcher {
+    fn response_for<R>(&self, request: R) -> Option<Result<R::Response, tendermint_rpc::Error>>
+    where
+        R: Request,
+    {
+        let request: serde_json::Value = serde_json::from_str(&request.into_json()).ok()?;
+        let data = request["params"]["data"].as_str()?;
+        let data = hex::decode(data).ok()?;
+
+        let FvmQuery::ActorState(address) = fvm_ipld_encoding::from_slice(&data).ok()? else {
+            return None;
+        };
+        let id = address.id().ok()?;
+
+        let query = if id == FAILING_ID {
+            AbciQuery {
+                code: Code::from(ExitCode::USR_ILLEGAL_STATE.value()),
+                ..Default::default()
+            }
+        } else if id > MAX_EXISTING_ID {
+            AbciQuery {
+                code: Code::from(ExitCode::USR_NOT_FOUND.value()),
+                ..Default::default()
+            }
+        } else {
+            AbciQuery {
+                key: fvm_ipld_encoding::to_vec(&id).unwrap(),
+                value: fvm_ipld_encoding::to_vec(&actor_state(id)).unwrap(),
+                ..Default::default()
+            }
+        };
+
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "",
+            "result": { "response": query },
+        });
+
+        Some(R::Response::from_string(response.to_string()))
+    }
+}
+
+fn actor_state(id: ActorID) -> ActorState {
+    ActorState {
+        code: Cid::default(),
+        state: Cid::default(),
+        sequence: id,
+        balance: TokenAmount::from_atto(id),
+        delegated_address: None,
+    }
+}
+
+fn client() -> FendermintClient<MockClient<ActorStateMatcher>> {
+    FendermintClient::new(MockClient::new(ActorStateMatcher).0)
+}
+
+#[tokio::test]
+async fn test_actor_states_preserve_order() {
+    let addresses = (0..40)
+        .rev()
+        .map(|i| Address::new_id(i * 45))
+        .collect::<Vec<_>>();
+
+    let states = client()
+        .actor_states(&addresses, FvmQueryHeight::Committed)
+        .await
+        .expect("failed to query actor states");
+
+    assert_eq!(states.len(), addresses.len());
+
+    for ((address, state), expected) in states.into_iter().zip(addresses) {
+        assert_eq!(address, expected);
+        let id = expected.id().unwrap();
+        if id > MAX_EXISTING_ID {
+            assert!(state.is_none(), "{id} should not be found");
+        } else {
+            assert_eq!(state, Some((id, actor_state(id))));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_actor_states_error_has_address() {
+    let addresses = vec![Address::new_id(1), Address::new_id(FAILING_ID)];
+
+    let err = client()
+        .actor_states(&addresses, FvmQueryHeight::Committed)
+        .await
+        .expect_err("the failing actor should fail the batch");
+
+    assert!(err
+        .to_string()
+        .contains(&Address::new_id(FAILING_ID).to_string()));
+}
@@ -0,0 +1,158 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Tests of waiting for transactions against a mock Tendermint client which
+//! only finds the transaction in a block after it has been searched for a number of times.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use fendermint_rpc::response::{decode_bytes, encode_data};
+use fendermint_rpc::tx::TxWaitError;
+use fendermint_rpc::FendermintClient;
+use fvm_ipld_encoding::RawBytes;
+use tendermint::Hash;
+use tendermint_rpc::{MockClient, MockRequestMatcher, Request, Response};
+
+const RETURN_DATA: [u8; 3] = [1, 2, 3];
+
+struct TxSearchMatcher {
+    polls: Arc<AtomicUsize>,
+    found_after: usize,
+    code: u32,
+}
+
+impl MockRequestMatcher for TxSearchMatcher {
+    fn response_for<R>(&self, request: R) -> Option<Result<R::Response, tendermint_rpc::Error>>
+    where
+        R: Request,
+    {
+        let request: serde_json::Value = serde_json::from_str(&request.into_json()).ok()?;
+        if request["method"] != "tx_search" {
+            return None;
+        }
+
+        let polls = self.polls.fetch_add(1, Ordering::SeqCst) + 1;
+
+        let txs = if polls < self.found_after {
+            vec![]
+        } else {
+            // Tendermint wraps the data in base64 once more, which `decode_bytes` undoes.
+            let data = base64::engine::general_purpose::STANDARD.encode(encode_data(&RETURN_DATA));
+            vec![serde_json::json!({
+                "hash": tx_hash().to_string(),
+                "height": "10",
+                "index": 0,
+                "tx_result": {
+                    "code": self.code,
+                    "data": data,
+                    "log": "",
+                    "info": "",
+                    "gas_wanted": "100",
+                    "gas_used": "50",
+                    "events": [],
+                    "codespace": "",
+                },
+                "tx": "",
+            })]
+        };
+
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "",
+            "result": { "txs": txs, "total_count": txs.len().to_string() },
+        });
+
+        Some(R::Response::from_string(response.to_string()))
+    }
+}
+
+fn tx_hash() -> Hash {
+    Hash::Sha256([7u8; 32])
+}
+
+fn client(
+    found_after: usize,
+    code: u32,
+) -> (
+    FendermintClient<MockClient<TxSearchMatcher>>,
+    Arc<AtomicUsize>,
+) {
+    let polls = Arc::new(AtomicUsize::new(0));
+    let matcher = TxSearchMatcher {
+        polls: polls.clone(),
+        found_after,
+        code,
+    };
+    (FendermintClient::new(MockClient::new(matcher).0), polls)
+}
+
+#[tokio::test]
+async fn test_wait_for_commit_found_after_polls() {
+    let (client, polls) = client(3, 0);
+
+    let res = client
+        .wait_for_commit(
+            tx_hash(),
+            1,
+            Duration::from_secs(10),
+            Duration::from_millis(1),
+            None,
+            decode_bytes,
+        )
+        .await
+        .expect("transaction should be found");
+
+    assert_eq!(polls.load(Ordering::SeqCst), 3);
+    assert_eq!(res.response.hash, tx_hash());
+    assert_eq!(res.response.height.value(), 10);
+    assert_eq!(res.confirmations, 1);
+    assert_eq!(res.return_data, RawBytes::new(RETURN_DATA.to_vec()));
+}
+
+#[tokio::test]
+async fn test_wait_for_commit_timeout() {
+    let (client, polls) = client(usize::MAX, 0);
+
+    let err = client
+        .wait_for_commit(
+            tx_hash(),
+            1,
+            Duration::from_millis(20),
+            Duration::from_millis(5),
+            None,
+            decode_bytes,
+        )
+        .await
+        .expect_err("transaction should not be found");
+
+    assert!(matches!(
+        err.downcast_ref::<TxWaitError>(),
+        Some(TxWaitError::NotIncluded { .. })
+    ));
+    assert!(polls.load(Ordering::SeqCst) > 1);
+}
+
+#[tokio::test]
+async fn test_wait_for_commit_failed() {
+    let (client, polls) = client(2, 1);
+
+    let err = client
+        .wait_for_commit(
+            tx_hash(),
+            1,
+            Duration::from_secs(10),
+            Duration::from_millis(1),
+            None,
+            decode_bytes,
+        )
+        .await
+        .expect_err("transaction should fail");
+
+    assert!(matches!(
+        err.downcast_ref::<TxWaitError>(),
+        Some(TxWaitError::Failed { .. })
+    ));
+    assert_eq!(polls.load(Ordering::SeqCst), 2);
+}
@@ -9,8 +9,8 @@ use std::{
 };
 
 use crate::{
-    Decode, Encode, KVError, KVRead, KVReadable, KVResult, KVStore, KVTransaction, KVWritable,
-    KVWrite,
+    Decode, Encode, IterRange, KVError, KVRead, KVReadable, KVResult, KVStore, KVTransaction,
+    KVWritable, KVWrite,
 };
 
 /// Read-only mode.
@@ -156,7 +156,11 @@ where
         Ok(None)
     }
 
-    fn iterate<K, V>(&self, ns: &S::Namespace) -> impl Iterator<Item = KVResult<(K, V)>>
+    fn iterate_range<K, V>(
+        &self,
+        ns: &S::Namespace,
+        range: IterRange<S::Repr>,
+    ) -> impl Iterator<Item = KVResult<(K, V)>>
     where
         S: Decode<K> + Decode<V>,
         <S as KVStore>::Repr: Ord + 'static,
@@ -164,14 +168,34 @@ where
         V: 'static,
     {
         if let Some(m) = self.data.get(ns) {
-            let mut items = m.iter().map(|(k, v)| (k, v.as_ref())).collect::<Vec<_>>();
+            let mut items = m
+                .iter()
+                .filter(|(k, _)| range.contains(*k))
+                .map(|(k, v)| (k, v.as_ref()))
+                .collect::<Vec<_>>();
             items.sort_by(|a, b| a.0.cmp(b.0));
+            if range.reverse {
+                items.reverse();
+            }
 
             KVIter::<S, K, V>::new(items)
         } else {
             KVIter::empty()
         }
     }
+
+    fn count_range(&self, ns: &S::Namespace, range: IterRange<S::Repr>) -> KVResult<usize>
+    where
+        <S as KVStore>::Repr: Ord + 'static,
+    {
+        let count = self
+            .data
+            .get(ns)
+            .map(|m| m.keys().filter(|k| range.contains(*k)).count())
+            .unwrap_or_default();
+
+        Ok(count)
+    }
 }
 
 impl<'a, S: KVStore> KVWrite<S> for Transaction<'a, S, Write>
@@ -201,6 +225,45 @@ where
         }
         Ok(())
     }
+
+    /// Encode all items before touching the namespace, then update it once.
+    fn put_many<'i, K, V, I>(&mut self, ns: &S::Namespace, items: I) -> KVResult<()>
+    where
+        S: Encode<K> + Encode<V>,
+        I: IntoIterator<Item = (&'i K, &'i V)>,
+        K: 'i,
+        V: 'i,
+    {
+        let items = items
+            .into_iter()
+            .map(|(k, v)| {
+                let kr = S::to_repr(k)?.into_owned();
+                let vr = S::to_repr(v)?.into_owned();
+                Ok((kr, Arc::new(vr)))
+            })
+            .collect::<KVResult<Vec<_>>>()?;
+
+        let mut m = self.data.get(ns).cloned().unwrap_or_default();
+        m.extend(items);
+        self.data.insert(ns.clone(), m);
+        Ok(())
+    }
+
+    fn delete_many<'i, K, I>(&mut self, ns: &S::Namespace, keys: I) -> KVResult<()>
+    where
+        S: Encode<K>,
+        I: IntoIterator<Item = &'i K>,
+        K: 'i,
+    {
+        if let Some(mut m) = self.data.get(ns).cloned() {
+            for k in keys {
+                let kr = S::to_repr(k)?;
+                m.remove(&kr);
+            }
+            self.data.insert(ns.clone(), m);
+        }
+        Ok(())
+    }
 }
 
 struct KVIter<'a, S: KVStore, K, V> {
@@ -309,4 +372,16 @@ mod tests {
         let backend = InMemoryBackend::<TestKVStore>::default();
         check_read_isolation(&backend, data)
     }
+
+    #[test]
+    fn iterate_range() {
+        let backend = InMemoryBackend::<TestKVStore>::default();
+        assert!(check_iterate_range(&backend))
+    }
+
+    #[test]
+    fn put_many() {
+        let backend = InMemoryBackend::<TestKVStore>::default();
+        assert!(check_put_many(&backend))
+    }
 }
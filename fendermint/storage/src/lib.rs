@@ -1,6 +1,6 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
-use std::borrow::Cow;
+use std::borrow::{Borrow, Cow};
 use std::error::Error;
 use std::hash::Hash;
 use std::marker::PhantomData;
@@ -61,21 +61,133 @@ where
 /// Ideally this would be just a trait alias, but that's an unstable feature.
 pub trait Codec<T>: Encode<T> + Decode<T> {}
 
+/// Bounds and direction of an iteration over a namespace.
+///
+/// The bounds are compared to the representation of the keys and form a half-open
+/// range: `start` is inclusive, `end` is exclusive, regardless of the direction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IterRange<R> {
+    pub start: Option<R>,
+    pub end: Option<R>,
+    pub reverse: bool,
+}
+
+impl<R> Default for IterRange<R> {
+    fn default() -> Self {
+        Self {
+            start: None,
+            end: None,
+            reverse: false,
+        }
+    }
+}
+
+impl<R> IterRange<R> {
+    /// Iterate all items in ascending order.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Start iterating at an inclusive lower bound.
+    pub fn with_start(mut self, start: R) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Stop iterating at an exclusive upper bound.
+    pub fn with_end(mut self, end: R) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// Iterate in descending order.
+    pub fn reversed(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    /// Convert the bounds, e.g. from keys to their representation.
+    pub fn try_map<T, E, F>(self, f: F) -> Result<IterRange<T>, E>
+    where
+        F: Fn(R) -> Result<T, E>,
+    {
+        Ok(IterRange {
+            start: self.start.map(&f).transpose()?,
+            end: self.end.map(&f).transpose()?,
+            reverse: self.reverse,
+        })
+    }
+}
+
+impl<R> IterRange<R> {
+    /// Check whether a key is within the bounds.
+    pub fn contains<Q>(&self, r: &Q) -> bool
+    where
+        R: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let after_start = match self.start {
+            Some(ref s) => r >= Borrow::<Q>::borrow(s),
+            None => true,
+        };
+        after_start && !self.is_past_end(r)
+    }
+
+    /// Check whether a key is at or above the exclusive upper bound.
+    pub fn is_past_end<Q>(&self, r: &Q) -> bool
+    where
+        R: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.end {
+            Some(ref e) => r >= Borrow::<Q>::borrow(e),
+            None => false,
+        }
+    }
+}
+
 /// Operations available on a read transaction.
 pub trait KVRead<S: KVStore> {
     fn get<K, V>(&self, ns: &S::Namespace, k: &K) -> KVResult<Option<V>>
     where
         S: Encode<K> + Decode<V>;
 
-    /// Iterate items in the namespace ordered by their representation.
-    ///
-    /// TODO: Add parameters for iteration direction and bounds.
+    /// Iterate items in the namespace within the bounds of the range,
+    /// ordered by their representation in the direction of the range.
+    fn iterate_range<K, V>(
+        &self,
+        ns: &S::Namespace,
+        range: IterRange<S::Repr>,
+    ) -> impl Iterator<Item = KVResult<(K, V)>>
+    where
+        K: 'static,
+        V: 'static,
+        S: Decode<K> + Decode<V>,
+        <S as KVStore>::Repr: Ord + 'static;
+
+    /// Iterate all items in the namespace ordered by their representation.
     fn iterate<K, V>(&self, ns: &S::Namespace) -> impl Iterator<Item = KVResult<(K, V)>>
     where
         K: 'static,
         V: 'static,
         S: Decode<K> + Decode<V>,
+        <S as KVStore>::Repr: Ord + 'static,
+    {
+        self.iterate_range(ns, IterRange::all())
+    }
+
+    /// Count the items in the namespace within the bounds of the range, without decoding them.
+    fn count_range(&self, ns: &S::Namespace, range: IterRange<S::Repr>) -> KVResult<usize>
+    where
         <S as KVStore>::Repr: Ord + 'static;
+
+    /// Count all items in the namespace.
+    fn count(&self, ns: &S::Namespace) -> KVResult<usize>
+    where
+        <S as KVStore>::Repr: Ord + 'static,
+    {
+        self.count_range(ns, IterRange::all())
+    }
 }
 
 /// Operations available on a write transaction.
@@ -87,6 +199,36 @@ pub trait KVWrite<S: KVStore>: KVRead<S> {
     fn delete<K>(&mut self, ns: &S::Namespace, k: &K) -> KVResult<()>
     where
         S: Encode<K>;
+
+    /// Put multiple items into the namespace.
+    ///
+    /// Backends should encode all items first and write them in a single pass;
+    /// the default implementation simply puts them one by one.
+    fn put_many<'i, K, V, I>(&mut self, ns: &S::Namespace, items: I) -> KVResult<()>
+    where
+        S: Encode<K> + Encode<V>,
+        I: IntoIterator<Item = (&'i K, &'i V)>,
+        K: 'i,
+        V: 'i,
+    {
+        for (k, v) in items {
+            self.put(ns, k, v)?;
+        }
+        Ok(())
+    }
+
+    /// Delete multiple items from the namespace.
+    fn delete_many<'i, K, I>(&mut self, ns: &S::Namespace, keys: I) -> KVResult<()>
+    where
+        S: Encode<K>,
+        I: IntoIterator<Item = &'i K>,
+        K: 'i,
+    {
+        for k in keys {
+            self.delete(ns, k)?;
+        }
+        Ok(())
+    }
 }
 
 /// Transaction running on a KV store, ending with a commit or a rollback.
@@ -167,6 +309,37 @@ where
         kv.delete(&self.ns, k)
     }
 
+    pub fn put_many<'i>(
+        &self,
+        kv: &mut impl KVWrite<S>,
+        items: impl IntoIterator<Item = (&'i K, &'i V)>,
+    ) -> KVResult<()>
+    where
+        K: 'i,
+        V: 'i,
+    {
+        kv.put_many(&self.ns, items)
+    }
+
+    pub fn delete_many<'i>(
+        &self,
+        kv: &mut impl KVWrite<S>,
+        keys: impl IntoIterator<Item = &'i K>,
+    ) -> KVResult<()>
+    where
+        K: 'i,
+    {
+        kv.delete_many(&self.ns, keys)
+    }
+
+    /// Count the items in the collection without decoding them.
+    pub fn count(&self, kv: &impl KVRead<S>) -> KVResult<usize>
+    where
+        S::Repr: Ord + 'static,
+    {
+        kv.count(&self.ns)
+    }
+
     pub fn iterate<'a, 'b>(
         &'a self,
         kv: &'b impl KVRead<S>,
@@ -180,4 +353,24 @@ where
     {
         kv.iterate::<K, V>(&self.ns)
     }
+
+    /// Iterate the items with keys between the bounds of the range.
+    ///
+    /// The bounds apply to the representation of the keys, which for example
+    /// with a binary encoding might not have the same order as the keys.
+    pub fn iterate_range<'a, 'b>(
+        &'a self,
+        kv: &'b impl KVRead<S>,
+        range: IterRange<&K>,
+    ) -> KVResult<impl Iterator<Item = KVResult<(K, V)>> + 'b>
+    where
+        S::Repr: Ord + 'static,
+        S: Encode<K> + Decode<K>,
+        K: 'static,
+        V: 'static,
+        'a: 'b,
+    {
+        let range = range.try_map(|k| S::to_repr(k).map(Cow::into_owned))?;
+        Ok(kv.iterate_range::<K, V>(&self.ns, range))
+    }
 }
@@ -1,7 +1,8 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 use crate::{
-    Codec, KVCollection, KVError, KVRead, KVReadable, KVStore, KVTransaction, KVWritable, KVWrite,
+    Codec, Encode, IterRange, KVCollection, KVError, KVRead, KVReadable, KVStore, KVTransaction,
+    KVWritable, KVWrite,
 };
 use quickcheck::{Arbitrary, Gen};
 use std::collections::HashMap;
@@ -26,6 +27,8 @@ pub enum TestOpKV<K, V> {
     Put(K, V),
     Del(K),
     Iter,
+    /// Iterate between optional bounds, optionally in reverse.
+    IterRange(Option<K>, Option<K>, bool),
 }
 
 #[derive(Clone, Debug)]
@@ -54,7 +57,13 @@ impl Arbitrary for TestOpNs {
                 let k = *g.choose(&["foo", "bar", "baz"]).unwrap();
                 match u8::arbitrary(g) % 10 {
                     i if i < 3 => S2I(ns, Get(k.to_owned())),
-                    i if i < 4 => S2I(ns, Iter),
+                    i if i < 4 => {
+                        let bounds = [None, Some("bar"), Some("baz"), Some("foo")];
+                        let start = g.choose(&bounds).unwrap().map(String::from);
+                        let end = g.choose(&bounds).unwrap().map(String::from);
+                        S2I(ns, IterRange(start, end, bool::arbitrary(g)))
+                    }
+                    i if i < 5 => S2I(ns, Iter),
                     i if i < 9 => S2I(ns, Put(k.to_owned(), Arbitrary::arbitrary(g))),
                     _ => S2I(ns, Del(k.to_owned())),
                 }
@@ -64,7 +73,12 @@ impl Arbitrary for TestOpNs {
                 let k = u8::arbitrary(g) % 3;
                 match u8::arbitrary(g) % 10 {
                     i if i < 3 => I2S(ns, Get(k)),
-                    i if i < 4 => I2S(ns, Iter),
+                    i if i < 4 => {
+                        let start = Option::<u8>::arbitrary(g).map(|b| b % 4);
+                        let end = Option::<u8>::arbitrary(g).map(|b| b % 4);
+                        I2S(ns, IterRange(start, end, bool::arbitrary(g)))
+                    }
+                    i if i < 5 => I2S(ns, Iter),
                     i if i < 9 => {
                         let sz = u8::arbitrary(g) % 5;
                         let s = (0..sz).map(|_| char::arbitrary(g)).collect();
@@ -418,23 +432,24 @@ where
         }
         TestOpKV::Iter => {
             let found = coll.iterate(tx).collect::<Result<Vec<_>, _>>().unwrap();
+            let expected = expected_range::<S, K, V>(model.get(ns), IterRange::all());
 
-            let expected = if let Some(m) = model.get(ns) {
-                let mut expected = m
-                    .iter()
-                    .map(|(k, v)| (k.clone(), v.clone()))
-                    .collect::<Vec<_>>();
-
-                expected.sort_by(|a, b| {
-                    let ka = S::to_repr(&a.0).unwrap();
-                    let kb = S::to_repr(&b.0).unwrap();
-                    ka.cmp(&kb)
-                });
-
-                expected
-            } else {
-                Vec::new()
+            if found != expected {
+                return false;
+            }
+        }
+        TestOpKV::IterRange(start, end, reverse) => {
+            let range = IterRange {
+                start: start.as_ref(),
+                end: end.as_ref(),
+                reverse,
             };
+            let found = coll
+                .iterate_range(tx, range.clone())
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let expected = expected_range::<S, K, V>(model.get(ns), range);
 
             if found != expected {
                 return false;
@@ -443,3 +458,156 @@ where
     }
     true
 }
+
+/// Items of the model within the range, in the order the store is expected to iterate them.
+fn expected_range<S, K, V>(model: Option<&HashMap<K, V>>, range: IterRange<&K>) -> Vec<(K, V)>
+where
+    S: KVStore + Codec<K>,
+    K: Clone,
+    V: Clone,
+    S::Repr: Ord,
+{
+    let Some(m) = model else {
+        return Vec::new();
+    };
+
+    let range = range
+        .try_map(|k| S::to_repr(k).map(|r| r.into_owned()))
+        .unwrap();
+
+    let mut expected = m
+        .iter()
+        .map(|(k, v)| (S::to_repr(k).unwrap().into_owned(), (k.clone(), v.clone())))
+        .filter(|(r, _)| range.contains(r))
+        .collect::<Vec<_>>();
+
+    expected.sort_by(|a, b| a.0.cmp(&b.0));
+
+    if range.reverse {
+        expected.reverse();
+    }
+
+    expected.into_iter().map(|(_, kv)| kv).collect()
+}
+
+/// Check iterating a namespace forwards and backwards with half-open bounds.
+pub fn check_iterate_range<S>(sut: &impl KVWritable<S>) -> bool
+where
+    S: KVStore<Namespace = TestNamespace> + Clone + Codec<String> + Codec<u8>,
+    S::Repr: Ord + 'static,
+{
+    let coll = KVCollection::<S, u8, String>::new("fizz");
+    let mut tx = sut.write();
+
+    for i in 0..10u8 {
+        coll.put(&mut tx, &i, &i.to_string()).unwrap();
+    }
+
+    let keys = |start: Option<u8>, end: Option<u8>, reverse: bool| {
+        let range = IterRange {
+            start: start.as_ref(),
+            end: end.as_ref(),
+            reverse,
+        };
+        coll.iterate_range(&tx, range)
+            .unwrap()
+            .map(|r| r.unwrap().0)
+            .collect::<Vec<_>>()
+    };
+
+    let cases = [
+        ((None, None, false), (0..10).collect::<Vec<_>>()),
+        ((None, None, true), (0..10).rev().collect()),
+        ((Some(3), None, false), (3..10).collect()),
+        ((Some(3), None, true), (3..10).rev().collect()),
+        ((None, Some(7), false), (0..7).collect()),
+        ((None, Some(7), true), (0..7).rev().collect()),
+        ((Some(3), Some(7), false), (3..7).collect()),
+        ((Some(3), Some(7), true), (3..7).rev().collect()),
+        ((Some(5), Some(5), false), vec![]),
+        ((Some(7), Some(3), true), vec![]),
+        ((Some(3), Some(20), true), (3..10).rev().collect()),
+    ];
+
+    let ok = cases
+        .into_iter()
+        .all(|((start, end, reverse), expected)| keys(start, end, reverse) == expected);
+
+    tx.rollback().unwrap();
+    ok
+}
+
+/// Check writing and deleting items in batches, and counting what is left.
+pub fn check_put_many<S>(sut: &impl KVWritable<S>) -> bool
+where
+    S: KVStore<Namespace = TestNamespace> + Clone + Codec<String> + Codec<u8>,
+    S::Repr: Ord + 'static,
+{
+    let coll = KVCollection::<S, u8, String>::new("buzz");
+    let other = KVCollection::<S, u8, String>::new("fizz");
+    let items = (0..10u8).map(|i| (i, i.to_string())).collect::<Vec<_>>();
+    let mut tx = sut.write();
+    let mut ok = true;
+
+    coll.put_many(&mut tx, items.iter().map(|(k, v)| (k, v)))
+        .unwrap();
+    other.put(&mut tx, &0, &"other".to_string()).unwrap();
+
+    ok &= coll.count(&tx).unwrap() == 10;
+    ok &= coll.iterate(&tx).collect::<Result<Vec<_>, _>>().unwrap() == items;
+
+    // Overwrite some, delete others, including ones that don't exist.
+    let updates = [(2u8, "two".to_string()), (20u8, "twenty".to_string())];
+    coll.put_many(&mut tx, updates.iter().map(|(k, v)| (k, v)))
+        .unwrap();
+    coll.delete_many(&mut tx, &[0u8, 1, 30]).unwrap();
+
+    ok &= coll.count(&tx).unwrap() == 9;
+    ok &= coll.get(&tx, &2).unwrap() == Some("two".to_string());
+    ok &= coll.get(&tx, &0).unwrap().is_none();
+    ok &= other.count(&tx).unwrap() == 1;
+
+    let range = IterRange::all().with_start(<S as Encode<u8>>::to_repr(&5u8).unwrap().into_owned());
+    ok &= tx.count_range(&"buzz", range).unwrap() == 6;
+
+    coll.delete_many(&mut tx, items.iter().map(|(k, _)| k))
+        .unwrap();
+    ok &= coll.count(&tx).unwrap() == 1;
+
+    tx.rollback().unwrap();
+    ok
+}
+
+/// Check that two transactions writing overlapping batches conflict.
+///
+/// This test assumes that write transactions can be executed concurrently and that
+/// conflicts are detected at commit time. If that's not the case don't call this test.
+pub fn check_put_many_conflict<S, B>(sut: &B) -> bool
+where
+    S: KVStore<Namespace = TestNamespace> + Clone + Codec<String> + Codec<u8>,
+    S::Repr: Ord + 'static,
+    B: KVWritable<S> + KVReadable<S>,
+{
+    let coll = KVCollection::<S, u8, String>::new("buzz");
+    let items1 = (0..5u8).map(|i| (i, "one".to_string())).collect::<Vec<_>>();
+    let items2 = (3..8u8).map(|i| (i, "two".to_string())).collect::<Vec<_>>();
+
+    let mut tx1 = sut.write();
+    let mut tx2 = sut.write();
+
+    coll.put_many(&mut tx1, items1.iter().map(|(k, v)| (k, v)))
+        .unwrap();
+    coll.put_many(&mut tx2, items2.iter().map(|(k, v)| (k, v)))
+        .unwrap();
+
+    let committed = tx1.commit().is_ok();
+    let conflicted = matches!(tx2.commit(), Err(KVError::Conflict));
+
+    // Only the first batch should be visible.
+    let tx = sut.read();
+    let found = coll.iterate(&tx).collect::<Result<Vec<_>, _>>().unwrap();
+    let count = coll.count(&tx).unwrap();
+    drop(tx);
+
+    committed && conflicted && found == items1 && count == items1.len()
+}
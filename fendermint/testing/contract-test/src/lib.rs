@@ -6,21 +6,26 @@ use byteorder::{BigEndian, WriteBytesExt};
 use cid::Cid;
 use fendermint_vm_core::Timestamp;
 use fendermint_vm_interpreter::fvm::PowerUpdates;
-use fvm_shared::{bigint::Zero, clock::ChainEpoch, econ::TokenAmount, version::NetworkVersion};
+use fvm_shared::{
+    address::Address, bigint::Zero, clock::ChainEpoch, econ::TokenAmount, version::NetworkVersion,
+};
 use std::{future::Future, sync::Arc};
 
 use fendermint_vm_genesis::Genesis;
 use fendermint_vm_interpreter::{
     fvm::{
         bundle::{bundle_path, contracts_path, custom_actors_bundle_path},
-        state::{FvmExecState, FvmGenesisState, FvmStateParams, FvmUpdatableParams},
-        store::memory::MemoryBlockstore,
+        state::{FvmExecState, FvmGenesisState, FvmQueryState, FvmStateParams, FvmUpdatableParams},
+        store::{memory::MemoryBlockstore, ReadOnlyBlockstore},
         upgrades::UpgradeScheduler,
-        FvmApplyRet, FvmGenesisOutput, FvmMessage, FvmMessageInterpreter,
+        FvmApplyRet, FvmGenesisOutput, FvmMessage, FvmMessageInterpreter, FvmQuery, FvmQueryRet,
     },
-    ExecInterpreter, GenesisInterpreter,
+    ExecInterpreter, GenesisInterpreter, QueryInterpreter,
+};
+use fvm::{
+    engine::MultiEngine,
+    state_tree::{ActorState, StateTree},
 };
-use fvm::engine::MultiEngine;
 
 pub mod ipc;
 
@@ -57,7 +62,7 @@ pub async fn init_exec_state(
         1.05,
         false,
         UpgradeScheduler::new(),
-    );
+    )?;
 
     let (state, out) = interpreter
         .init(state, genesis)
@@ -77,6 +82,8 @@ pub struct Tester<I> {
     multi_engine: Arc<MultiEngine>,
     exec_state: Arc<tokio::sync::Mutex<Option<FvmExecState<MemoryBlockstore>>>>,
     state_params: FvmStateParams,
+    /// Parameters set by the caller, which take precedence over the genesis.
+    custom_state_params: Option<FvmStateParams>,
 }
 
 impl<I> Tester<I>
@@ -94,26 +101,52 @@ where
         EndOutput = PowerUpdates,
     >,
 {
-    fn state_store_clone(&self) -> MemoryBlockstore {
+    /// A handle to the state store, e.g. to inspect the committed state tree.
+    pub fn state_store_clone(&self) -> MemoryBlockstore {
         self.state_store.as_ref().clone()
     }
 
     pub fn new(interpreter: I, state_store: MemoryBlockstore) -> Self {
+        let state_params = FvmStateParams {
+            timestamp: Timestamp(0),
+            state_root: Cid::default(),
+            network_version: NetworkVersion::V21,
+            base_fee: TokenAmount::zero(),
+            circ_supply: TokenAmount::zero(),
+            chain_id: 0,
+            power_scale: 0,
+            app_version: 0,
+        };
+        Self::build(interpreter, state_store, state_params, None)
+    }
+
+    /// Create a tester which executes blocks with the given parameters, e.g. on top of
+    /// a state root already in the store, with a specific network version or base fee.
+    ///
+    /// The parameters are kept when calling [`Tester::init`], except for the state root,
+    /// which is the one created from the genesis.
+    pub fn with_state_params(
+        interpreter: I,
+        state_store: MemoryBlockstore,
+        state_params: FvmStateParams,
+    ) -> Self {
+        let custom_state_params = Some(state_params.clone());
+        Self::build(interpreter, state_store, state_params, custom_state_params)
+    }
+
+    fn build(
+        interpreter: I,
+        state_store: MemoryBlockstore,
+        state_params: FvmStateParams,
+        custom_state_params: Option<FvmStateParams>,
+    ) -> Self {
         Self {
             interpreter: Arc::new(interpreter),
             state_store: Arc::new(state_store),
             multi_engine: Arc::new(MultiEngine::new(1)),
             exec_state: Arc::new(tokio::sync::Mutex::new(None)),
-            state_params: FvmStateParams {
-                timestamp: Timestamp(0),
-                state_root: Cid::default(),
-                network_version: NetworkVersion::V21,
-                base_fee: TokenAmount::zero(),
-                circ_supply: TokenAmount::zero(),
-                chain_id: 0,
-                power_scale: 0,
-                app_version: 0,
-            },
+            state_params,
+            custom_state_params,
         }
     }
 
@@ -148,15 +181,21 @@ where
 
         let state_root = state.commit().context("failed to commit genesis state")?;
 
-        self.state_params = FvmStateParams {
-            state_root,
-            timestamp: out.timestamp,
-            network_version: out.network_version,
-            base_fee: out.base_fee,
-            circ_supply: out.circ_supply,
-            chain_id: out.chain_id.into(),
-            power_scale: out.power_scale,
-            app_version: 0,
+        self.state_params = match self.custom_state_params.clone() {
+            Some(params) => FvmStateParams {
+                state_root,
+                ..params
+            },
+            None => FvmStateParams {
+                state_root,
+                timestamp: out.timestamp,
+                network_version: out.network_version,
+                base_fee: out.base_fee,
+                circ_supply: out.circ_supply,
+                chain_id: out.chain_id.into(),
+                power_scale: out.power_scale,
+                app_version: 0,
+            },
         };
 
         Ok(())
@@ -250,4 +289,42 @@ where
     pub fn state_params(&self) -> FvmStateParams {
         self.state_params.clone()
     }
+
+    /// Read the state of an actor as of the last commit.
+    ///
+    /// Changes made by messages in a block which hasn't been committed yet are not visible.
+    pub fn query_actor_state(&self, addr: &Address) -> anyhow::Result<Option<ActorState>> {
+        let store = ReadOnlyBlockstore::new(self.state_store_clone());
+        let state_tree = StateTree::new_from_root(store, &self.state_params.state_root)
+            .context("failed to load state tree")?;
+
+        match state_tree.lookup_id(addr)? {
+            Some(id) => Ok(state_tree.get_actor(id)?),
+            None => Ok(None),
+        }
+    }
+
+    /// Run a query on the state as of the last commit, which was made at `block_height`.
+    pub async fn query(&self, block_height: ChainEpoch, query: FvmQuery) -> Result<FvmQueryRet>
+    where
+        I: QueryInterpreter<
+            State = FvmQueryState<MemoryBlockstore>,
+            Query = FvmQuery,
+            Output = FvmQueryRet,
+        >,
+    {
+        let state = FvmQueryState::new(
+            self.state_store_clone(),
+            self.multi_engine.clone(),
+            block_height,
+            self.state_params.clone(),
+            Arc::new(tokio::sync::Mutex::new(None)),
+            false,
+        )
+        .context("error creating query state")?;
+
+        let (_, ret) = self.interpreter.query(state, query).await?;
+
+        Ok(ret)
+    }
 }
@@ -0,0 +1,69 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fendermint_contract_test::Tester;
+use fendermint_vm_core::Timestamp;
+use fendermint_vm_genesis::{Account, Actor, ActorMeta, Genesis, PermissionMode, SignerAddr};
+use fendermint_vm_interpreter::fvm::state::balances::sum_balances;
+use fendermint_vm_interpreter::fvm::store::memory::MemoryBlockstore;
+use fendermint_vm_interpreter::fvm::upgrades::UpgradeScheduler;
+use fendermint_vm_interpreter::fvm::{bundle::contracts_path, FvmMessageInterpreter};
+use fvm_shared::address::Address;
+use fvm_shared::bigint::Zero;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::version::NetworkVersion;
+use tendermint_rpc::{MockClient, MockRequestMethodMatcher};
+
+fn account(seed: u8, balance: u64) -> Actor {
+    Actor {
+        meta: ActorMeta::Account(Account {
+            owner: SignerAddr(Address::new_secp256k1(&[seed; 65]).unwrap()),
+        }),
+        balance: TokenAmount::from_whole(balance),
+    }
+}
+
+// the sum of the balances matches the circulating supply tracked since genesis
+#[tokio::test]
+async fn test_sum_balances() {
+    let (client, _) = MockClient::new(MockRequestMethodMatcher::default());
+
+    let interpreter: FvmMessageInterpreter<MemoryBlockstore, _> = FvmMessageInterpreter::new(
+        client,
+        None,
+        contracts_path(),
+        1.05,
+        1.05,
+        false,
+        UpgradeScheduler::new(),
+    )
+    .unwrap();
+
+    let mut tester = Tester::new(interpreter, MemoryBlockstore::new());
+
+    let genesis = Genesis {
+        chain_name: "mychain".to_string(),
+        timestamp: Timestamp(0),
+        network_version: NetworkVersion::V21,
+        base_fee: TokenAmount::zero(),
+        power_scale: 0,
+        validators: Vec::new(),
+        accounts: vec![account(1, 100), account(2, 250)],
+        eam_permission_mode: PermissionMode::Unrestricted,
+        ipc: None,
+        cetf: None,
+    };
+
+    tester.init(genesis).await.unwrap();
+
+    tester.begin_block(1).await.unwrap();
+    tester.end_block(1).await.unwrap();
+    tester.commit().await.unwrap();
+
+    let state_params = tester.state_params();
+    let totals = sum_balances(tester.state_store_clone(), &state_params.state_root).unwrap();
+
+    assert_eq!(state_params.circ_supply, TokenAmount::from_whole(350));
+    assert_eq!(totals.balances, state_params.circ_supply);
+    assert!(totals.burnt.is_zero());
+}
@@ -0,0 +1,108 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fendermint_actor_chainmetadata::DEFAULT_LOOKBACK_LEN;
+use fendermint_contract_test::Tester;
+use fendermint_vm_core::Timestamp;
+use fendermint_vm_genesis::{Genesis, PermissionMode};
+use fendermint_vm_interpreter::fvm::store::memory::MemoryBlockstore;
+use fendermint_vm_interpreter::fvm::upgrades::UpgradeScheduler;
+use fendermint_vm_interpreter::fvm::{
+    bundle::contracts_path, FvmMessageInterpreter, FvmQuery, FvmQueryRet,
+};
+use fendermint_vm_message::query::BlockHashLookup;
+use fvm_shared::bigint::Zero;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::version::NetworkVersion;
+use tendermint_rpc::{MockClient, MockRequestMethodMatcher};
+
+type TestTester =
+    Tester<FvmMessageInterpreter<MemoryBlockstore, MockClient<MockRequestMethodMatcher>>>;
+
+// the block hash the tester uses for each height
+fn block_hash(block_height: ChainEpoch) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    hash[..8].copy_from_slice(&block_height.to_be_bytes());
+    hash
+}
+
+async fn query_block_hash(
+    tester: &TestTester,
+    query_height: ChainEpoch,
+    block_height: ChainEpoch,
+) -> BlockHashLookup {
+    match tester
+        .query(query_height, FvmQuery::BlockHash(block_height))
+        .await
+        .expect("block hash query failed")
+    {
+        FvmQueryRet::BlockHash(lookup) => lookup,
+        _ => panic!("unexpected query result"),
+    }
+}
+
+// this test executes more blocks than the chain metadata actor keeps and checks which hashes can be queried
+#[tokio::test]
+async fn test_query_block_hash() {
+    let (client, _) = MockClient::new(MockRequestMethodMatcher::default());
+
+    let interpreter: FvmMessageInterpreter<MemoryBlockstore, _> = FvmMessageInterpreter::new(
+        client,
+        None,
+        contracts_path(),
+        1.05,
+        1.05,
+        false,
+        UpgradeScheduler::new(),
+    )
+    .unwrap();
+
+    let mut tester = Tester::new(interpreter, MemoryBlockstore::new());
+
+    let genesis = Genesis {
+        chain_name: "mychain".to_string(),
+        timestamp: Timestamp(0),
+        network_version: NetworkVersion::V21,
+        base_fee: TokenAmount::zero(),
+        power_scale: 0,
+        validators: Vec::new(),
+        accounts: Vec::new(),
+        eam_permission_mode: PermissionMode::Unrestricted,
+        ipc: None,
+        cetf: None,
+    };
+
+    tester.init(genesis).await.unwrap();
+
+    let lookback_len = DEFAULT_LOOKBACK_LEN as ChainEpoch;
+    let last_height = lookback_len + 2;
+
+    for block_height in 1..=last_height {
+        tester.begin_block(block_height).await.unwrap();
+        tester.end_block(block_height).await.unwrap();
+        tester.commit().await.unwrap();
+    }
+
+    // the latest and the oldest block in the lookback window are available
+    for block_height in [last_height, last_height - lookback_len + 1] {
+        assert_eq!(
+            query_block_hash(&tester, last_height, block_height).await,
+            BlockHashLookup::Found(block_hash(block_height))
+        );
+    }
+
+    // the block just outside the lookback window has been pruned
+    assert_eq!(
+        query_block_hash(&tester, last_height, last_height - lookback_len).await,
+        BlockHashLookup::Pruned {
+            lookback_len: DEFAULT_LOOKBACK_LEN
+        }
+    );
+
+    // future blocks are not found
+    assert_eq!(
+        query_block_hash(&tester, last_height, last_height + 1).await,
+        BlockHashLookup::NotFound
+    );
+}
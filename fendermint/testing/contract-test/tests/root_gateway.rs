@@ -0,0 +1,66 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::sync::Arc;
+
+use fendermint_vm_core::Timestamp;
+use fendermint_vm_genesis::ipc::{GatewayParams, IpcParams};
+use fendermint_vm_genesis::{Genesis, PermissionMode};
+use fendermint_vm_interpreter::fvm::state::ipc::GatewayCaller;
+use fvm::engine::MultiEngine;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::Zero;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::version::NetworkVersion;
+use ipc_api::subnet_id::SubnetID;
+
+fn genesis(subnet_id: SubnetID, bottom_up_check_period: u64) -> Genesis {
+    Genesis {
+        chain_name: "mychain".to_string(),
+        timestamp: Timestamp(0),
+        network_version: NetworkVersion::V21,
+        base_fee: TokenAmount::zero(),
+        power_scale: 0,
+        validators: Vec::new(),
+        accounts: Vec::new(),
+        eam_permission_mode: PermissionMode::Unrestricted,
+        ipc: Some(IpcParams {
+            gateway: GatewayParams {
+                subnet_id,
+                bottom_up_check_period,
+                majority_percentage: 67,
+                active_validators_limit: 100,
+            },
+        }),
+        cetf: None,
+    }
+}
+
+// the rootnet gateway initializes with checkpointing disabled
+#[tokio::test]
+async fn test_root_gateway_without_checkpointing() {
+    let genesis = genesis(SubnetID::new_root(123), 0);
+
+    let (mut exec_state, _) =
+        fendermint_contract_test::init_exec_state(Arc::new(MultiEngine::new(1)), genesis)
+            .await
+            .expect("failed to init the rootnet");
+
+    let period = GatewayCaller::default()
+        .bottom_up_check_period(&mut exec_state)
+        .expect("error calling the gateway");
+
+    assert_eq!(period, 0);
+}
+
+// a child subnet cannot disable checkpointing
+#[tokio::test]
+async fn test_child_gateway_requires_checkpointing() {
+    let root = SubnetID::new_root(123);
+    let genesis = genesis(SubnetID::new_from_parent(&root, Address::new_id(1001)), 0);
+
+    let res =
+        fendermint_contract_test::init_exec_state(Arc::new(MultiEngine::new(1)), genesis).await;
+
+    assert!(res.is_err());
+}
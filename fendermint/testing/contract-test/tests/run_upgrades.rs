@@ -11,10 +11,13 @@ use fendermint_rpc::response::decode_fevm_return_data;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicI64, Ordering};
 
+use cid::Cid;
 use ethers::contract::abigen;
 use fvm_shared::address::Address;
 use fvm_shared::bigint::Zero;
+use fvm_shared::chainid::ChainID;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::version::NetworkVersion;
 use tendermint_rpc::Client;
@@ -24,8 +27,11 @@ use fendermint_vm_actor_interface::eam;
 use fendermint_vm_actor_interface::eam::EthAddress;
 use fendermint_vm_core::Timestamp;
 use fendermint_vm_genesis::{Account, Actor, ActorMeta, Genesis, PermissionMode, SignerAddr};
+use fendermint_vm_interpreter::fvm::state::FvmStateParams;
 use fendermint_vm_interpreter::fvm::store::memory::MemoryBlockstore;
-use fendermint_vm_interpreter::fvm::upgrades::{Upgrade, UpgradeScheduler};
+use fendermint_vm_interpreter::fvm::upgrades::{
+    MigrationRegistry, Upgrade, UpgradeFile, UpgradeScheduler,
+};
 use fendermint_vm_interpreter::fvm::{bundle::contracts_path, FvmMessageInterpreter};
 
 // returns a seeded secret key which is guaranteed to be the same every time
@@ -202,7 +208,8 @@ async fn test_applying_upgrades() {
         1.05,
         false,
         upgrade_scheduler,
-    );
+    )
+    .unwrap();
 
     let mut tester = Tester::new(interpreter, MemoryBlockstore::new());
 
@@ -221,6 +228,7 @@ async fn test_applying_upgrades() {
         }],
         eam_permission_mode: PermissionMode::Unrestricted,
         ipc: None,
+        cetf: None,
     };
 
     tester.init(genesis).await.unwrap();
@@ -228,6 +236,11 @@ async fn test_applying_upgrades() {
     // check that the app version is 0
     assert_eq!(tester.state_params().app_version, 0);
 
+    // check that the genesis account exists but the contract doesn't yet
+    let contract_addr = Address::from_str(CONTRACT_ADDRESS).unwrap();
+    assert!(tester.query_actor_state(&ADDR).unwrap().is_some());
+    assert!(tester.query_actor_state(&contract_addr).unwrap().is_none());
+
     // iterate over all the upgrades
     for block_height in 1..=3 {
         tester.begin_block(block_height).await.unwrap();
@@ -236,9 +249,158 @@ async fn test_applying_upgrades() {
 
         // check that the app_version was upgraded to 1
         assert_eq!(tester.state_params().app_version, 1);
+
+        // check that the contract deployed by the first upgrade is visible after the commit
+        assert!(tester.query_actor_state(&contract_addr).unwrap().is_some());
     }
 }
 
+// this test checks that the state params given to the tester are used to execute blocks, instead of the ones in the genesis
+#[tokio::test]
+async fn test_custom_base_fee_reaches_execution() {
+    const BASE_FEE_ATTO: u64 = 1000;
+
+    let mut upgrade_scheduler = UpgradeScheduler::new();
+    upgrade_scheduler
+        .add(
+            Upgrade::new_by_id(ChainID::from(0), 1, None, |state| {
+                // check the base fee the block is executed with
+                anyhow::ensure!(
+                    state.params().base_fee == TokenAmount::from_atto(BASE_FEE_ATTO),
+                    "unexpected base fee: {}",
+                    state.params().base_fee
+                );
+                Ok(())
+            })
+            .unwrap(),
+        )
+        .unwrap();
+
+    let interpreter: FvmMessageInterpreter<MemoryBlockstore, _> = FvmMessageInterpreter::new(
+        NeverCallClient,
+        None,
+        contracts_path(),
+        1.05,
+        1.05,
+        false,
+        upgrade_scheduler,
+    )
+    .unwrap();
+
+    let state_params = FvmStateParams {
+        timestamp: Timestamp(0),
+        state_root: Cid::default(),
+        network_version: NetworkVersion::V21,
+        base_fee: TokenAmount::from_atto(BASE_FEE_ATTO),
+        circ_supply: TokenAmount::zero(),
+        chain_id: 0,
+        power_scale: 0,
+        app_version: 0,
+    };
+
+    let mut tester = Tester::with_state_params(interpreter, MemoryBlockstore::new(), state_params);
+
+    // the genesis has a zero base fee, which should not replace the one given to the tester
+    let genesis = Genesis {
+        chain_name: "mychain".to_string(),
+        timestamp: Timestamp(0),
+        network_version: NetworkVersion::V21,
+        base_fee: TokenAmount::zero(),
+        power_scale: 0,
+        validators: Vec::new(),
+        accounts: Vec::new(),
+        eam_permission_mode: PermissionMode::Unrestricted,
+        ipc: None,
+        cetf: None,
+    };
+
+    tester.init(genesis).await.unwrap();
+    assert_eq!(
+        tester.state_params().base_fee,
+        TokenAmount::from_atto(BASE_FEE_ATTO)
+    );
+
+    // the upgrade at height 1 fails the block if the base fee didn't reach the execution state
+    tester.begin_block(1).await.unwrap();
+    tester.end_block(1).await.unwrap();
+    tester.commit().await.unwrap();
+
+    assert_eq!(
+        tester.state_params().base_fee,
+        TokenAmount::from_atto(BASE_FEE_ATTO)
+    );
+}
+
+// this test schedules an upgrade from a file, with a migration resolved from the registry by its name
+#[tokio::test]
+async fn test_upgrades_from_file() {
+    const UPGRADES: &str = r#"
+        chain_name = "mychain"
+
+        [[upgrades]]
+        name = "fake-upgrade"
+        height = 2
+        app_version = 3
+        migration = "fake"
+    "#;
+
+    // the height the fake migration was executed at
+    static EXECUTED_AT: AtomicI64 = AtomicI64::new(0);
+
+    let mut registry = MigrationRegistry::new();
+    registry
+        .register("fake", |state| {
+            EXECUTED_AT.store(state.block_height(), Ordering::SeqCst);
+            Ok(())
+        })
+        .unwrap();
+
+    let file = UpgradeFile::parse(UPGRADES).unwrap();
+
+    let mut upgrade_scheduler = UpgradeScheduler::new();
+    upgrade_scheduler.add_file(&file, &registry).unwrap();
+
+    let interpreter: FvmMessageInterpreter<MemoryBlockstore, _> = FvmMessageInterpreter::new(
+        NeverCallClient,
+        None,
+        contracts_path(),
+        1.05,
+        1.05,
+        false,
+        upgrade_scheduler,
+    )
+    .unwrap();
+
+    let mut tester = Tester::new(interpreter, MemoryBlockstore::new());
+
+    let genesis = Genesis {
+        chain_name: file.chain_name.clone(),
+        timestamp: Timestamp(0),
+        network_version: NetworkVersion::V21,
+        base_fee: TokenAmount::zero(),
+        power_scale: 0,
+        validators: Vec::new(),
+        accounts: Vec::new(),
+        eam_permission_mode: PermissionMode::Unrestricted,
+        ipc: None,
+        cetf: None,
+    };
+
+    tester.init(genesis).await.unwrap();
+
+    for block_height in 1..=3 {
+        tester.begin_block(block_height).await.unwrap();
+        tester.end_block(block_height).await.unwrap();
+        tester.commit().await.unwrap();
+
+        // the app version only changes once the upgrade height has been reached
+        let expected_app_version = if block_height < 2 { 0 } else { 3 };
+        assert_eq!(tester.state_params().app_version, expected_app_version);
+    }
+
+    assert_eq!(EXECUTED_AT.load(Ordering::SeqCst), 2);
+}
+
 #[derive(Clone)]
 struct NeverCallClient;
 
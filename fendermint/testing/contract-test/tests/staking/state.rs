@@ -586,6 +586,7 @@ impl arbitrary::Arbitrary<'_> for StakingState {
             accounts: parent_actors,
             eam_permission_mode: PermissionMode::Unrestricted,
             ipc: Some(parent_ipc),
+            cetf: None,
         };
 
         let child_ipc = IpcParams {
@@ -607,6 +608,7 @@ impl arbitrary::Arbitrary<'_> for StakingState {
             accounts: Vec::new(),
             eam_permission_mode: PermissionMode::Unrestricted,
             ipc: Some(child_ipc),
+            cetf: None,
         };
 
         Ok(StakingState::new(accounts, parent_genesis, child_genesis))
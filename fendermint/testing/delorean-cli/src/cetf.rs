@@ -0,0 +1,221 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Queries of the CETF actor state, and waiting for tags to be signed.
+
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use fendermint_actor_cetf::state::{ScheduledTagMap, SignedHashedTagMap, DEFAULT_HAMT_CONFIG};
+use fendermint_actor_cetf::{BlsSignature, State as CetfActorState};
+use fendermint_rpc::query::{QueryClient, QueryResponse};
+use fendermint_vm_message::query::FvmQueryHeight;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::CborStore;
+use tokio::time::Instant;
+
+use crate::error::{ErrorKind, ErrorKindExt};
+
+/// Query the state of the CETF actor, along with the height it was taken at.
+pub async fn cetf_state<BS: Blockstore>(
+    client: &impl QueryClient,
+    store: &BS,
+) -> anyhow::Result<(u64, CetfActorState)> {
+    let QueryResponse { height, value } = client
+        .actor_state(
+            &fendermint_vm_actor_interface::cetf::CETFSYSCALL_ACTOR_ADDR,
+            FvmQueryHeight::default(),
+        )
+        .await
+        .context("failed to get cetf actor state")
+        .kind(ErrorKind::Rpc)?;
+
+    let (id, act_state) = value
+        .ok_or_else(|| anyhow!("cetf actor state not found"))
+        .kind(ErrorKind::Rpc)?;
+    tracing::debug!("Get Cetf State (id: {}) at height {}", id, height);
+    let state: CetfActorState = store
+        .get_cbor(&act_state.state)
+        .context("failed to get cetf actor")
+        .kind(ErrorKind::Rpc)?
+        .ok_or_else(|| anyhow!("no actor state found"))
+        .kind(ErrorKind::Rpc)?;
+
+    Ok((height.into(), state))
+}
+
+/// Look up the aggregated signature of the validators over a tag, if it has been signed.
+pub fn find_signature<BS: Blockstore + Clone>(
+    store: &BS,
+    state: &CetfActorState,
+    signing_tag: [u8; 32],
+) -> anyhow::Result<Option<BlsSignature>> {
+    let signed_hashed_tag = SignedHashedTagMap::load(
+        store.clone(),
+        &state.signed_hashed_tags,
+        DEFAULT_HAMT_CONFIG,
+        "load signed hashed tags",
+    )
+    .context("failed to load signed hashed tags")
+    .kind(ErrorKind::Rpc)?;
+
+    let sig = signed_hashed_tag
+        .get(&signing_tag.into())
+        .context("failed to get signature from signed hashed tag")
+        .kind(ErrorKind::Rpc)?;
+
+    Ok(sig.copied())
+}
+
+/// Look up the epoch a tag is scheduled to be signed at, if it has been enqueued.
+pub fn scheduled_epoch<BS: Blockstore + Clone>(
+    store: &BS,
+    state: &CetfActorState,
+    signing_tag: [u8; 32],
+) -> anyhow::Result<Option<u64>> {
+    let scheduled_tags = ScheduledTagMap::load(
+        store.clone(),
+        &state.scheduled_tags,
+        DEFAULT_HAMT_CONFIG,
+        "load scheduled tags",
+    )
+    .context("failed to load scheduled tags")
+    .kind(ErrorKind::Rpc)?;
+
+    let epoch = scheduled_tags
+        .get(&signing_tag.into())
+        .context("failed to get scheduled epoch of tag")
+        .kind(ErrorKind::Rpc)?;
+
+    Ok(epoch.copied())
+}
+
+/// Poll the CETF actor until the validators have signed a tag, or the timeout elapses.
+pub async fn wait_for_signature<BS: Blockstore + Clone>(
+    client: &impl QueryClient,
+    store: &BS,
+    signing_tag: [u8; 32],
+    timeout: Duration,
+    poll_interval: Duration,
+) -> anyhow::Result<BlsSignature> {
+    poll_until(timeout, poll_interval, || async move {
+        let (height, state) = cetf_state(client, store).await?;
+
+        if let Some(sig) = find_signature(store, &state, signing_tag)? {
+            return Ok(Some(sig));
+        }
+
+        match scheduled_epoch(store, &state, signing_tag)? {
+            Some(epoch) => tracing::info!(
+                height,
+                scheduled_epoch = epoch,
+                "waiting for the tag to be signed"
+            ),
+            None => tracing::info!(height, "waiting for the tag to be enqueued"),
+        }
+
+        Ok(None)
+    })
+    .await
+    .with_context(|| format!("no signature for tag 0x{}", hex::encode(signing_tag)))
+}
+
+/// Call `poll` until it returns a value, waiting `poll_interval` between the calls.
+///
+/// Fails with [ErrorKind::Timeout] if there is no value after `timeout`.
+pub async fn poll_until<T, F, Fut>(
+    timeout: Duration,
+    poll_interval: Duration,
+    mut poll: F,
+) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<Option<T>>>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(value) = poll().await? {
+            return Ok(value);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(anyhow!("timed out after {timeout:?}")).kind(ErrorKind::Timeout);
+        }
+        tokio::time::sleep(poll_interval.min(deadline - now)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    use anyhow::anyhow;
+    use fendermint_actor_cetf::BlsSignature;
+
+    use super::poll_until;
+    use crate::error::{exit_code, ErrorKind};
+
+    /// Mock of the actor state which only has the signature after a number of polls.
+    struct MockStore {
+        polls: Cell<usize>,
+        signed_after: usize,
+    }
+
+    impl MockStore {
+        fn new(signed_after: usize) -> Self {
+            Self {
+                polls: Cell::new(0),
+                signed_after,
+            }
+        }
+
+        async fn get_signature(&self) -> anyhow::Result<Option<BlsSignature>> {
+            let polls = self.polls.get() + 1;
+            self.polls.set(polls);
+            Ok((polls >= self.signed_after).then_some(BlsSignature([1u8; 96])))
+        }
+    }
+
+    #[tokio::test]
+    async fn poll_until_signed() {
+        let store = MockStore::new(3);
+
+        let sig = poll_until(Duration::from_secs(10), Duration::from_millis(1), || {
+            store.get_signature()
+        })
+        .await
+        .expect("should be signed");
+
+        assert_eq!(sig, BlsSignature([1u8; 96]));
+        assert_eq!(store.polls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn poll_until_timeout() {
+        let store = MockStore::new(usize::MAX);
+
+        let err = poll_until(Duration::from_millis(20), Duration::from_millis(5), || {
+            store.get_signature()
+        })
+        .await
+        .expect_err("should time out");
+
+        assert_eq!(exit_code(&err), ErrorKind::Timeout.exit_code());
+        assert!(store.polls.get() > 1);
+    }
+
+    #[tokio::test]
+    async fn poll_until_error() {
+        let err = poll_until(
+            Duration::from_secs(10),
+            Duration::from_millis(1),
+            || async { Err::<Option<()>, _>(anyhow!("boom")) },
+        )
+        .await
+        .expect_err("should fail");
+
+        assert_eq!(err.to_string(), "boom");
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Classification of failures into exit codes, so scripts driving the CLI
+//! can tell apart the kind of problem without parsing the error message.
+
+use std::fmt::Display;
+
+use anyhow::{anyhow, Context};
+use tendermint::abci::types::ExecTxResult;
+use tendermint_rpc::endpoint::broadcast::tx_commit;
+
+/// Exit code for failures which haven't been classified.
+pub const EXIT_FAILURE: u8 = 1;
+
+/// The kinds of failure reported with distinct exit codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Invalid arguments or unreadable input files.
+    Config,
+    /// The node could not be reached or returned something unexpected.
+    Rpc,
+    /// The transaction was rejected by the check or reverted during execution.
+    Reverted,
+    /// A key or signature could not be parsed, aggregated or verified.
+    Verification,
+    /// Gave up waiting for something to happen on chain.
+    Timeout,
+}
+
+impl ErrorKind {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ErrorKind::Config => 2,
+            ErrorKind::Rpc => 3,
+            ErrorKind::Reverted => 4,
+            ErrorKind::Verification => 5,
+            ErrorKind::Timeout => 6,
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::Config => write!(f, "configuration error"),
+            ErrorKind::Rpc => write!(f, "RPC error"),
+            ErrorKind::Reverted => write!(f, "transaction failed"),
+            ErrorKind::Verification => write!(f, "verification failed"),
+            ErrorKind::Timeout => write!(f, "timed out"),
+        }
+    }
+}
+
+/// Attach an [ErrorKind] to the error of a result.
+pub trait ErrorKindExt<T> {
+    fn kind(self, kind: ErrorKind) -> anyhow::Result<T>;
+}
+
+impl<T, E> ErrorKindExt<T> for Result<T, E>
+where
+    Result<T, E>: Context<T, E>,
+{
+    fn kind(self, kind: ErrorKind) -> anyhow::Result<T> {
+        self.context(kind)
+    }
+}
+
+/// The exit code of the first [ErrorKind] attached to the error, if any.
+pub fn exit_code(err: &anyhow::Error) -> u8 {
+    err.downcast_ref::<ErrorKind>()
+        .map(ErrorKind::exit_code)
+        .unwrap_or(EXIT_FAILURE)
+}
+
+/// Turn a failed check or delivery of a transaction into an error with the logs of the node.
+pub fn check_tx_commit(response: &tx_commit::Response) -> anyhow::Result<()> {
+    let check = &response.check_tx;
+    if check.code.is_err() {
+        return Err(anyhow!(
+            "transaction {} failed the check with code {}: {}",
+            response.hash,
+            check.code.value(),
+            check.log
+        )
+        .context(ErrorKind::Reverted));
+    }
+    check_exec_result(&response.tx_result)
+        .with_context(|| format!("transaction {} failed", response.hash))
+}
+
+/// Turn a failed execution into an error with the exit code and logs of the node.
+pub fn check_exec_result(result: &ExecTxResult) -> anyhow::Result<()> {
+    if result.code.is_err() {
+        return Err(anyhow!(
+            "execution failed with exit code {}: {} {}",
+            result.code.value(),
+            result.info,
+            result.log
+        )
+        .context(ErrorKind::Reverted));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{anyhow, Context};
+    use tendermint::abci::types::ExecTxResult;
+    use tendermint::abci::{response::CheckTx, Code};
+    use tendermint_rpc::endpoint::broadcast::tx_commit;
+
+    use super::{check_tx_commit, exit_code, ErrorKind, ErrorKindExt, EXIT_FAILURE};
+
+    fn tx_commit_response(check_code: u32, deliver_code: u32) -> tx_commit::Response {
+        tx_commit::Response {
+            check_tx: CheckTx {
+                code: Code::from(check_code),
+                log: "check log".to_string(),
+                ..Default::default()
+            },
+            tx_result: ExecTxResult {
+                code: Code::from(deliver_code),
+                info: "deliver info".to_string(),
+                log: "deliver log".to_string(),
+                ..Default::default()
+            },
+            hash: Default::default(),
+            height: Default::default(),
+        }
+    }
+
+    #[test]
+    fn exit_code_of_kind() {
+        for kind in [
+            ErrorKind::Config,
+            ErrorKind::Rpc,
+            ErrorKind::Reverted,
+            ErrorKind::Verification,
+            ErrorKind::Timeout,
+        ] {
+            let err = Err::<(), _>(anyhow!("boom")).kind(kind).unwrap_err();
+            assert_eq!(exit_code(&err), kind.exit_code());
+        }
+    }
+
+    #[test]
+    fn exit_code_survives_context() {
+        let err = Err::<(), _>(std::io::Error::other("boom"))
+            .kind(ErrorKind::Config)
+            .context("failed to read key")
+            .context("failed to register key")
+            .unwrap_err();
+
+        assert_eq!(exit_code(&err), ErrorKind::Config.exit_code());
+        assert!(format!("{err:#}").contains("configuration error: boom"));
+    }
+
+    #[test]
+    fn exit_code_unclassified() {
+        let err = anyhow!("boom").context("something failed");
+        assert_eq!(exit_code(&err), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn check_tx_commit_success() {
+        check_tx_commit(&tx_commit_response(0, 0)).expect("transaction should succeed");
+    }
+
+    #[test]
+    fn check_tx_commit_failures() {
+        let err = check_tx_commit(&tx_commit_response(1, 0)).unwrap_err();
+        assert_eq!(exit_code(&err), ErrorKind::Reverted.exit_code());
+        assert!(format!("{err:#}").contains("check log"));
+
+        let err = check_tx_commit(&tx_commit_response(0, 33)).unwrap_err();
+        assert_eq!(exit_code(&err), ErrorKind::Reverted.exit_code());
+        let msg = format!("{err:#}");
+        assert!(msg.contains("exit code 33"));
+        assert!(msg.contains("deliver info"));
+        assert!(msg.contains("deliver log"));
+    }
+}
@@ -34,6 +34,9 @@
 //!
 //! Make sure you installed cargo-make by running `cargo install cargo-make` first.
 
+pub mod cetf;
+pub mod error;
+
 use cid::Cid;
 use fendermint_rpc::QueryClient;
 use fendermint_vm_message::query::FvmQueryHeight;
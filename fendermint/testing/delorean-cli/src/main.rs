@@ -12,28 +12,32 @@
 //! ```
 
 use std::any;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
 
 use anyhow::{anyhow, Context};
 use bls_signatures::Serialize;
 use bytes::Bytes;
 use cetf_actor::State as CetfActorState;
 use clap::{Parser, Subcommand};
+use delorean_cli::cetf::{cetf_state, find_signature, wait_for_signature};
+use delorean_cli::error::{check_exec_result, check_tx_commit, exit_code, ErrorKind, ErrorKindExt};
+use delorean_cli::RemoteBlockstore;
 use ethers::abi::Tokenizable;
 use ethers::prelude::*;
 use fendermint_actor_cetf::state::DEFAULT_HAMT_CONFIG;
 use fendermint_actor_cetf::{self as cetf_actor, BlsSignature};
-use delorean_cli::RemoteBlockstore;
-use fendermint_rpc::query::{QueryClient, QueryResponse};
+use fendermint_rpc::query::QueryClient;
 use fendermint_vm_actor_interface::eam;
 use fendermint_vm_message::query::FvmQueryHeight;
-use fvm_ipld_encoding::{CborStore, RawBytes};
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
 use fvm_shared::chainid::ChainID;
 use fvm_shared::econ::TokenAmount;
 use k256::sha2::{Digest, Sha256};
-use lazy_static::lazy_static;
 use tendermint_rpc::Url;
 use tracing::Level;
 
@@ -47,14 +51,11 @@ type MockContractCall<T> = ethers::prelude::ContractCall<MockProvider, T>;
 const DEMO_CONTRACT_SPEC_JSON: &str =
     include_str!("../../../../contracts/out/Demo.sol/DeloreanDemo.json");
 
-lazy_static! {
-    /// Default gas params based on the testkit.
-    static ref GAS_PARAMS: GasParams = GasParams {
-        gas_limit: 10_000_000_000,
-        gas_fee_cap: TokenAmount::default(),
-        gas_premium: TokenAmount::default(),
-    };
-}
+/// Default gas limit based on the testkit.
+const DEFAULT_GAS_LIMIT: u64 = 10_000_000_000;
+
+/// Number of decimal places in a whole FIL.
+const FIL_DECIMALS: usize = 18;
 
 abigen!(
     DeloreanContract,
@@ -110,6 +111,18 @@ pub struct Options {
     /// and that it has a corresponding f410 account in genesis.
     #[arg(long, short, env = "DELORIAN_SECRET_KEY")]
     pub secret_key: PathBuf,
+
+    /// Gas limit for the transactions sent.
+    #[arg(long, default_value_t = DEFAULT_GAS_LIMIT)]
+    pub gas_limit: u64,
+
+    /// Maximum gas fee per unit of gas, in FIL (e.g. `0.000000001`).
+    #[arg(long, default_value = "0", value_parser = parse_fil)]
+    pub gas_fee_cap: TokenAmount,
+
+    /// Gas premium per unit of gas, in FIL (e.g. `0.000000001`).
+    #[arg(long, default_value = "0", value_parser = parse_fil)]
+    pub gas_premium: TokenAmount,
 }
 
 #[derive(Debug, Subcommand)]
@@ -118,23 +131,58 @@ enum Commands {
         #[arg(long, short)]
         bls_secret_key: PathBuf,
     },
-    QueueTag,
+    /// Replace the registered BLS key of the sender with a new one.
+    ///
+    /// The new key is used to verify the vote extensions from the next height.
+    RotateBls {
+        #[arg(long, short)]
+        bls_secret_key: PathBuf,
+    },
+    /// Enqueue a demo tag to be signed by the validators.
+    QueueTag {
+        /// Height at which the tag should be signed; defaults to the earliest possible one.
+        #[arg(long)]
+        height: Option<u64>,
+    },
     DeployDemoContract,
     CallReleaseKeys {
         address: String,
     },
     RegisteredKeys,
+    /// List the enqueued tags which have not been signed yet.
+    PendingTags,
     Encrypt {
         contract_address: String,
+        /// File to encrypt; reads from stdin if not given.
+        #[arg(long, short)]
+        input: Option<PathBuf>,
         #[arg(long, short)]
         output: PathBuf,
     },
     Decrypt {
         contract_address: String,
+        /// File to decrypt; reads from stdin if not given.
+        #[arg(long, short)]
+        input: Option<PathBuf>,
         #[arg(long, short)]
         output: Option<PathBuf>,
     },
     TestIfHeightsAreSignedProperly,
+    /// Wait until the validators have signed a tag, then print the signature.
+    WaitForSignature {
+        /// Hex encoded signing tag to wait for.
+        #[arg(long, required_unless_present = "contract_address")]
+        tag: Option<String>,
+        /// Address of a deployed demo contract to read the signing tag from.
+        #[arg(long, conflicts_with = "tag")]
+        contract_address: Option<String>,
+        /// Number of seconds to wait before giving up.
+        #[arg(long, default_value_t = 300)]
+        timeout: u64,
+        /// Number of seconds between checking for the signature.
+        #[arg(long, default_value_t = 5)]
+        poll_interval: u64,
+    },
 }
 
 impl Options {
@@ -145,22 +193,72 @@ impl Options {
             Level::INFO
         }
     }
+
+    /// Gas parameters to use for every transaction, based on the CLI flags.
+    pub fn gas_params(&self) -> GasParams {
+        GasParams {
+            gas_limit: self.gas_limit,
+            gas_fee_cap: self.gas_fee_cap.clone(),
+            gas_premium: self.gas_premium.clone(),
+        }
+    }
+}
+
+/// Parse a decimal amount of FIL, e.g. `1.5`, into a token amount without loss of precision.
+fn parse_fil(s: &str) -> Result<TokenAmount, String> {
+    let (whole, frac) = s.trim().split_once('.').unwrap_or((s.trim(), ""));
+
+    if whole.is_empty() && frac.is_empty() {
+        return Err("empty token amount".to_owned());
+    }
+    if frac.len() > FIL_DECIMALS {
+        return Err(format!(
+            "token amount has more than {FIL_DECIMALS} decimal places"
+        ));
+    }
+    if !whole
+        .chars()
+        .chain(frac.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+        return Err(format!("not a decimal token amount: {s}"));
+    }
+
+    let atto = format!("{whole}{frac:0<FIL_DECIMALS$}");
+    let atto = atto
+        .parse::<u128>()
+        .map_err(|e| format!("not a token amount: {e}"))?;
+
+    Ok(TokenAmount::from_atto(atto))
 }
 
 /// See the module docs for how to run.
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> ExitCode {
     let opts: Options = Options::parse();
 
     tracing_subscriber::fmt()
         .with_max_level(opts.log_level())
         .init();
 
-    let client = FendermintClient::new_http(opts.url, None).expect("error creating client");
+    match run(opts).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            ExitCode::from(exit_code(&e))
+        }
+    }
+}
+
+async fn run(opts: Options) -> anyhow::Result<()> {
+    let client = FendermintClient::new_http(opts.url.clone(), None)
+        .context("error creating client")
+        .kind(ErrorKind::Config)?;
     let store = RemoteBlockstore::new(client.clone());
 
-    let sk =
-        SignedMessageFactory::read_secret_key(&opts.secret_key).expect("error reading secret key");
+    let sk = SignedMessageFactory::read_secret_key(&opts.secret_key)
+        .context("error reading secret key")
+        .kind(ErrorKind::Config)?;
 
     let pk = sk.public_key();
 
@@ -169,13 +267,14 @@ async fn main() -> anyhow::Result<()> {
     // Query the account nonce from the state, so it doesn't need to be passed as an arg.
     let sn = sequence(&client, &f1_addr)
         .await
-        .expect("error getting sequence");
+        .context("error getting sequence")?;
 
     // Query the chain ID, so it doesn't need to be passed as an arg.
     let chain_id = client
         .state_params(FvmQueryHeight::default())
         .await
-        .expect("error getting state params")
+        .context("error getting state params")
+        .kind(ErrorKind::Rpc)?
         .value
         .chain_id;
 
@@ -183,109 +282,123 @@ async fn main() -> anyhow::Result<()> {
 
     let mut client = client.bind(mf);
 
+    let gas_params = opts.gas_params();
+
     match opts.command {
         Commands::RegisterBls { bls_secret_key } => {
-            let bls_sk = {
-                let b64 = std::fs::read_to_string(&bls_secret_key)
-                    .expect("failed to read bls secret key");
-                bls_signatures::PrivateKey::from_bytes(
-                    &fendermint_crypto::from_b64(&b64)
-                        .expect("failed to decode b64 bls secret key"),
-                )
-                .expect("failed to parse bls secret key")
-            };
-
-            let bls_pk = bls_sk.public_key();
+            let params = RawBytes::serialize(cetf_actor::AddValidatorParams {
+                address: f1_addr,
+                public_key: read_bls_public_key(&bls_secret_key)?,
+            })?;
 
-            let res = TxClient::<TxCommit>::transaction(
+            cetf_transaction(
                 &mut client,
-                fendermint_vm_actor_interface::cetf::CETFSYSCALL_ACTOR_ADDR,
-                cetf_actor::Method::AddValidator as u64,
-                RawBytes::serialize(cetf_actor::AddValidatorParams {
-                    address: f1_addr,
-                    public_key: fendermint_actor_cetf::BlsPublicKey(
-                        bls_pk
-                            .as_bytes()
-                            .try_into()
-                            .expect("Failed to convert BLS public key to bytes"),
-                    ),
-                })
-                .expect("failed to serialize params"),
-                TokenAmount::from_whole(0),
-                GAS_PARAMS.clone(),
+                cetf_actor::Method::AddValidator,
+                params,
+                &gas_params,
             )
             .await
-            .expect("transfer failed");
+            .context("failed to register BLS key")?;
+        }
+        Commands::RotateBls { bls_secret_key } => {
+            let params = RawBytes::serialize(cetf_actor::UpdateValidatorKeyParams {
+                address: f1_addr,
+                public_key: read_bls_public_key(&bls_secret_key)?,
+            })?;
 
-            assert!(res.response.check_tx.code.is_ok(), "check is ok");
-            assert!(res.response.tx_result.code.is_ok(), "deliver is ok");
-            assert!(res.return_data.is_some());
+            cetf_transaction(
+                &mut client,
+                cetf_actor::Method::UpdateValidatorKey,
+                params,
+                &gas_params,
+            )
+            .await
+            .context("failed to rotate BLS key")?;
         }
         Commands::RegisteredKeys => {
-            let QueryResponse { height, value } = client
-                .actor_state(
-                    &fendermint_vm_actor_interface::cetf::CETFSYSCALL_ACTOR_ADDR,
-                    FvmQueryHeight::default(),
-                )
-                .await
-                .expect("failed to get cetf actor state");
-
-            let (id, act_state) = value.expect("cetf actor state not found");
-            tracing::info!("Get Cetf State (id: {}) at height {}", id, height);
-            let state: CetfActorState = store
-                .get_cbor(&act_state.state)
-                .expect("failed to get cetf actor")
-                .expect("no actor state found");
-
-            let validator_map = cetf_actor::state::ValidatorBlsPublicKeyMap::load(
-                store,
-                &state.validators,
+            for (addr, key) in registered_keys(&client, &store).await? {
+                tracing::info!("Validator: {}, Bls: {:?}", addr, key);
+            }
+        }
+        Commands::PendingTags => {
+            let (_, state) = cetf_state(&client, &store).await?;
+
+            let tag_queue = cetf_actor::state::TagQueue::load(&state.tag_queue, store.clone())
+                .context("failed to load tag queue")
+                .kind(ErrorKind::Rpc)?;
+            let signed_hashed_tag = cetf_actor::state::SignedHashedTagMap::load(
+                store.clone(),
+                &state.signed_hashed_tags,
                 DEFAULT_HAMT_CONFIG,
-                "load validator hamt",
+                "load signed hashed tags",
             )
-            .expect("failed to load validator hamt");
-            validator_map
-                .for_each(|k, v| {
-                    tracing::info!("Validator: {}, Bls: {:?}", k, v);
+            .context("failed to load signed hashed tags")
+            .kind(ErrorKind::Rpc)?;
+
+            let mut queued = vec![];
+            tag_queue
+                .for_each(|epoch, tags| {
+                    queued.extend(tags.iter().map(|tag| (epoch, *tag)));
                     Ok(())
                 })
-                .expect("failed to iterate validator hamt");
+                .context("failed to iterate tag queue")
+                .kind(ErrorKind::Rpc)?;
+
+            let mut pending = vec![];
+            for (epoch, tag) in queued {
+                if signed_hashed_tag
+                    .get(&tag)
+                    .context("failed to read signed hashed tags")
+                    .kind(ErrorKind::Rpc)?
+                    .is_none()
+                {
+                    pending.push((epoch, tag));
+                }
+            }
+
+            for (epoch, tag) in pending.iter() {
+                tracing::info!(
+                    "Pending tag 0x{} scheduled at epoch {}",
+                    hex::encode(tag.0),
+                    epoch
+                );
+            }
+            tracing::info!("{} enqueued tags awaiting signatures", pending.len());
         }
-        Commands::QueueTag => {
+        Commands::QueueTag { height } => {
             let to_queue: [u8; 32] = std::array::from_fn(|i| i as u8);
-            let params = RawBytes::serialize(cetf_actor::EnqueueTagParams {
-                tag: to_queue.into(),
-            })
-            .expect("failed to serialize params");
+            let (method, params) = match height {
+                Some(height) => (
+                    cetf_actor::Method::EnqueueTagAtHeight,
+                    RawBytes::serialize(cetf_actor::EnqueueTagAtHeightParams {
+                        tag: to_queue.into(),
+                        height,
+                    }),
+                ),
+                None => (
+                    cetf_actor::Method::EnqueueTag,
+                    RawBytes::serialize(cetf_actor::EnqueueTagParams {
+                        tag: to_queue.into(),
+                    }),
+                ),
+            };
+            let params = params?;
             tracing::info!("CBOR encoded input should look like: {:?}", params);
 
-            let res = TxClient::<TxCommit>::transaction(
-                &mut client,
-                fendermint_vm_actor_interface::cetf::CETFSYSCALL_ACTOR_ADDR,
-                cetf_actor::Method::EnqueueTag as u64,
-                params,
-                TokenAmount::from_whole(0),
-                GAS_PARAMS.clone(),
-            )
-            .await
-            .expect("transfer failed");
-
-            assert!(res.response.check_tx.code.is_ok(), "check is ok");
-            assert!(res.response.tx_result.code.is_ok(), "deliver is ok");
-            assert!(res.return_data.is_some());
-            let scheduled_epoch: u64 = res
-                .return_data
-                .expect("no return data")
+            let scheduled_epoch: u64 = cetf_transaction(&mut client, method, params, &gas_params)
+                .await
+                .context("failed to queue tag")?
                 .deserialize()
-                .expect("failed to deserialize return data");
+                .context("failed to deserialize return data")
+                .kind(ErrorKind::Rpc)?;
             tracing::info!("Scheduled epoch: {}", scheduled_epoch);
         }
         Commands::DeployDemoContract => {
             let spec: serde_json::Value = serde_json::from_str(DEMO_CONTRACT_SPEC_JSON)?;
             let example_contract = hex::decode(
-                &spec["bytecode"]["object"]
+                spec["bytecode"]["object"]
                     .as_str()
-                    .expect("missing bytecode")
+                    .ok_or_else(|| anyhow!("missing bytecode"))?
                     .trim_start_matches("0x"),
             )?;
 
@@ -296,111 +409,113 @@ async fn main() -> anyhow::Result<()> {
                 Bytes::from(example_contract),
                 Bytes::default(),
                 TokenAmount::default(),
-                GAS_PARAMS.clone(),
+                gas_params.clone(),
             )
             .await
-            .expect("error deploying contract");
+            .context("error deploying contract")
+            .kind(ErrorKind::Rpc)?;
 
             tracing::info!(tx_hash = ?res.response.hash, "deployment transaction");
 
+            check_tx_commit(&res.response).context("error deploying contract")?;
+
             let ret = res
                 .return_data
-                .ok_or(anyhow!(
-                    "no CreateReturn data; response was {:?}",
-                    res.response
-                ))
-                .expect("failed to get CreateReturn data");
+                .ok_or_else(|| anyhow!("no CreateReturn data; response was {:?}", res.response))
+                .kind(ErrorKind::Rpc)?;
             let address = ret.eth_address;
             tracing::info!(address = ?address, "contract deployed");
         }
         Commands::CallReleaseKeys { address } => {
-            let contract = delorean_contract(&address);
+            let contract = delorean_contract(&address)?;
             let call = contract.release_key();
 
             tracing::info!("Calling releaeKeys on contract at address {}", address);
-            let result: bool = invoke_or_call_contract(&mut client, &address, call, true).await?;
+            let result: bool =
+                invoke_or_call_contract(&mut client, &address, call, true, &gas_params).await?;
 
             tracing::info!(result = ?result, "contract call result");
         }
-        Commands::Encrypt { contract_address, output } => {
-            let signing_tag = retrieve_signing_tag(&mut client, &contract_address).await?;
-            tracing::info!("Retrieved signing tag 0x{} from contract", hex::encode(&signing_tag));
+        Commands::Encrypt {
+            contract_address,
+            input,
+            output,
+        } => {
+            let input = open_input(input.as_ref())?;
+            let signing_tag =
+                retrieve_signing_tag(&mut client, &contract_address, &gas_params).await?;
+            tracing::info!(
+                "Retrieved signing tag 0x{} from contract",
+                hex::encode(&signing_tag)
+            );
 
             let agg_pubkey = get_agg_pubkey(&client, &store).await?;
-            tracing::info!("Computed aggregate BLS pubkey 0x{}", hex::encode(&agg_pubkey.as_bytes()));
+            tracing::info!(
+                "Computed aggregate BLS pubkey 0x{}",
+                hex::encode(&agg_pubkey.as_bytes())
+            );
 
             tracing::info!("Encrypting...");
 
-            // encrypt whatever is on std-in into our armor writer
-            let mut armored = tlock_age::armor::ArmoredWriter::wrap_output(vec![]).unwrap();
+            // encrypt the input into our armor writer
+            let mut armored = tlock_age::armor::ArmoredWriter::wrap_output(vec![])
+                .context("failed to create armored writer")?;
             tlock_age::encrypt(
                 &mut armored,
-                std::io::stdin().lock(),
+                input,
                 &[0x0; 32], // I think this can be anything..
                 &agg_pubkey.as_bytes(),
                 signing_tag,
-            )?;
-            let encrypted = armored.finish().unwrap();
+            )
+            .context("failed to encrypt")
+            .kind(ErrorKind::Verification)?;
+            let encrypted = armored
+                .finish()
+                .context("failed to finish armored output")?;
 
             // write the encrypted data to the output file
-            std::fs::write(&output, &encrypted).expect("failed to write output file");
+            std::fs::write(&output, &encrypted)
+                .with_context(|| format!("failed to write output file {}", output.display()))
+                .kind(ErrorKind::Config)?;
             tracing::info!("Done!");
-
         }
-        Commands::Decrypt { contract_address, output } => {
-            let signing_tag = retrieve_signing_tag(&mut client, &contract_address).await?;
-            tracing::info!("Retrieved signing tag 0x{} from contract", hex::encode(&signing_tag));
+        Commands::Decrypt {
+            contract_address,
+            input,
+            output,
+        } => {
+            let input = open_input(input.as_ref())?;
+            let signing_tag =
+                retrieve_signing_tag(&mut client, &contract_address, &gas_params).await?;
+            tracing::info!(
+                "Retrieved signing tag 0x{} from contract",
+                hex::encode(&signing_tag)
+            );
             tracing::info!("Attempting to retrieve signature for tag");
 
             let sig_bytes = get_signature_for_tag(&client, &store, signing_tag).await?;
             tracing::info!("Got key/signature 0x{}", hex::encode(sig_bytes.0));
 
             let mut decrypted = vec![];
-            tlock_age::decrypt(
-                &mut decrypted,
-                std::io::stdin().lock(),
-                &[0x0; 32],
-                &sig_bytes.0,
-            )?;
+            tlock_age::decrypt(&mut decrypted, input, &[0x0; 32], &sig_bytes.0)
+                .context("failed to decrypt")
+                .kind(ErrorKind::Verification)?;
             if let Some(output) = output {
-                std::fs::write(&output, &decrypted).expect("failed to write output file");
+                std::fs::write(&output, &decrypted)
+                    .with_context(|| format!("failed to write output file {}", output.display()))
+                    .kind(ErrorKind::Config)?;
             } else {
-                std::io::stdout().write_all(&decrypted).expect("failed to write to stdout");
+                std::io::stdout()
+                    .write_all(&decrypted)
+                    .context("failed to write to stdout")?;
             }
         }
         Commands::TestIfHeightsAreSignedProperly => {
-            let QueryResponse { height, value } = client
-                .actor_state(
-                    &fendermint_vm_actor_interface::cetf::CETFSYSCALL_ACTOR_ADDR,
-                    FvmQueryHeight::default(),
-                )
-                .await
-                .expect("failed to get cetf actor state");
-
-            let (id, act_state) = value.expect("cetf actor state not found");
-            tracing::info!("Get Cetf State (id: {}) at height {}", id, height);
-            let state: CetfActorState = store
-                .get_cbor(&act_state.state)
-                .expect("failed to get cetf actor")
-                .expect("no actor state found");
+            let (height, state) = cetf_state(&client, &store).await?;
 
-            let height: u64 = height.into();
             let height = height - 1u64;
             // Get all the validators BLS keys
-            let mut bls_keys_bytes = vec![];
-            let validator_map = cetf_actor::state::ValidatorBlsPublicKeyMap::load(
-                store.clone(),
-                &state.validators,
-                DEFAULT_HAMT_CONFIG,
-                "load validator hamt",
-            )
-            .expect("failed to load validator hamt");
-            validator_map
-                .for_each(|_k, v| {
-                    bls_keys_bytes.push(*v);
-                    Ok(())
-                })
-                .expect("failed to iterate validator hamt");
+            let pub_keys = validator_public_keys(&store, &state)?;
 
             // Find the Signature of the Hashed Tag
             let tag_bytes = height.to_be_bytes().to_vec();
@@ -409,54 +524,93 @@ async fn main() -> anyhow::Result<()> {
 
             let digest: [u8; 32] = hasher.finalize().into();
 
-            let signed_hashed_tag = cetf_actor::state::SignedHashedTagMap::load(
-                store.clone(),
-                &state.signed_hashed_tags,
-                DEFAULT_HAMT_CONFIG,
-                "load signed hashed tags",
-            )
-            .expect("failed to load signed hashed tags");
-
-            let sig_bytes: BlsSignature = *signed_hashed_tag
-                .get(&digest.into())
-                .expect("failed to get signature from signed hashed tag")
-                .expect("signature not found");
+            let sig_bytes = signature_for_tag(&store, &state, digest)?;
 
             let sig = bls_signatures::Signature::from_bytes(&sig_bytes.0)
-                .expect("failed to parse signature from bytes");
-            let pub_keys = bls_keys_bytes
-                .iter()
-                .map(|b| {
-                    bls_signatures::PublicKey::from_bytes(&b.0)
-                        .expect("failed to parse public key from bytes")
-                })
-                .collect::<Vec<_>>();
+                .context("failed to parse signature from bytes")
+                .kind(ErrorKind::Verification)?;
             // tracing::info!("Public Keys: {:?}", pub_keys);
             tracing::info!("Tag: {:?}", tag_bytes);
             tracing::info!("Hashed Tag: {:?}", digest);
             tracing::info!("Signature: {:?}", sig_bytes.0);
 
-            assert!(
-                bls_signatures::verify_messages(&sig, &[tag_bytes.as_slice()], &pub_keys),
-                "Signature is invalid"
+            if !bls_signatures::verify_messages(&sig, &[tag_bytes.as_slice()], &pub_keys) {
+                return Err(anyhow!("signature of height {height} is invalid"))
+                    .kind(ErrorKind::Verification);
+            }
+        }
+        Commands::WaitForSignature {
+            tag,
+            contract_address,
+            timeout,
+            poll_interval,
+        } => {
+            let signing_tag = match (tag, contract_address) {
+                (Some(tag), _) => parse_tag(&tag)?,
+                (None, Some(contract_address)) => {
+                    retrieve_signing_tag(&mut client, &contract_address, &gas_params).await?
+                }
+                (None, None) => unreachable!("clap requires either the tag or the contract"),
+            };
+            tracing::info!(
+                "Waiting for signature of tag 0x{}",
+                hex::encode(signing_tag)
             );
+
+            let sig = wait_for_signature(
+                &client,
+                &store,
+                signing_tag,
+                Duration::from_secs(timeout),
+                Duration::from_secs(poll_interval),
+            )
+            .await?;
+
+            println!("0x{}", hex::encode(sig.0));
         }
     }
     Ok(())
 }
 
+/// Send a transaction to the CETF actor and return its result once it has been executed.
+async fn cetf_transaction(
+    client: &mut impl TxClient<TxCommit>,
+    method: cetf_actor::Method,
+    params: RawBytes,
+    gas_params: &GasParams,
+) -> anyhow::Result<RawBytes> {
+    let res = TxClient::<TxCommit>::transaction(
+        client,
+        fendermint_vm_actor_interface::cetf::CETFSYSCALL_ACTOR_ADDR,
+        method as u64,
+        params,
+        TokenAmount::from_whole(0),
+        gas_params.clone(),
+    )
+    .await
+    .context("failed to send transaction")
+    .kind(ErrorKind::Rpc)?;
+
+    check_tx_commit(&res.response)?;
+
+    res.return_data
+        .ok_or_else(|| anyhow!("no return data"))
+        .kind(ErrorKind::Rpc)
+}
+
 /// Invoke FEVM through Tendermint with the calldata encoded by ethers, decoding the result into the expected type.
 async fn invoke_or_call_contract<T: Tokenizable>(
     client: &mut (impl TxClient<TxCommit> + CallClient),
     contract_eth_addr: &str,
     call: MockContractCall<T>,
     in_transaction: bool,
+    gas_params: &GasParams,
 ) -> anyhow::Result<T> {
     let calldata: ethers::types::Bytes = call
         .calldata()
         .expect("calldata should contain function and parameters");
 
-    let contract_addr = eth_addr_to_eam(contract_eth_addr);
+    let contract_addr = eth_addr_to_eam(contract_eth_addr)?;
 
     // We can perform the read as a distributed transaction (if we don't trust any particular node to give the right answer),
     // or we can send a query with the same message and get a result without involving a transaction.
@@ -466,13 +620,16 @@ async fn invoke_or_call_contract<T: Tokenizable>(
                 contract_addr,
                 calldata.0,
                 TokenAmount::default(),
-                GAS_PARAMS.clone(),
+                gas_params.clone(),
             )
             .await
-            .context("failed to invoke FEVM")?;
+            .context("failed to invoke FEVM")
+            .kind(ErrorKind::Rpc)?;
 
         // tracing::info!(tx_hash = ?res.response.hash, "invoked transaction");
 
+        check_tx_commit(&res.response).context("Contract returned error. Key release denied.")?;
+
         res.return_data
     } else {
         let res = client
@@ -480,144 +637,267 @@ async fn invoke_or_call_contract<T: Tokenizable>(
                 contract_addr,
                 calldata.0,
                 TokenAmount::default(),
-                GAS_PARAMS.clone(),
+                gas_params.clone(),
                 FvmQueryHeight::default(),
             )
             .await
-            .context("failed to call FEVM")?;
+            .context("failed to call FEVM")
+            .kind(ErrorKind::Rpc)?;
+
+        check_exec_result(&res.response.value)
+            .context("Contract returned error. Key release denied.")?;
 
         res.return_data
     };
 
-    let bytes = return_data.ok_or(anyhow!("Contract returned error. Key release denied."))?;
+    let bytes = return_data
+        .ok_or_else(|| anyhow!("no return data"))
+        .kind(ErrorKind::Rpc)?;
 
     let res = decode_function_data(&call.function, bytes, false)
-        .context("error deserializing return data")?;
+        .context("error deserializing return data")
+        .kind(ErrorKind::Rpc)?;
 
     Ok(res)
 }
 
+/// Open the input file, or stdin if no path was given.
+///
+/// Opening the file up front means a missing or unreadable file fails fast,
+/// before anything is queried from the chain.
+fn open_input(path: Option<&PathBuf>) -> anyhow::Result<Box<dyn Read>> {
+    match path {
+        Some(path) => {
+            let file = std::fs::File::open(path)
+                .with_context(|| format!("failed to open input file {}", path.display()))
+                .kind(ErrorKind::Config)?;
+            Ok(Box::new(file))
+        }
+        None => Ok(Box::new(std::io::stdin().lock())),
+    }
+}
+
+/// Read a base64 encoded BLS secret key and derive the public key to register.
+fn read_bls_public_key(path: &PathBuf) -> anyhow::Result<fendermint_actor_cetf::BlsPublicKey> {
+    let b64 = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read bls secret key {}", path.display()))
+        .kind(ErrorKind::Config)?;
+    let bz = fendermint_crypto::from_b64(&b64)
+        .context("failed to decode b64 bls secret key")
+        .kind(ErrorKind::Config)?;
+    let bls_sk = bls_signatures::PrivateKey::from_bytes(&bz)
+        .context("failed to parse bls secret key")
+        .kind(ErrorKind::Config)?;
+
+    let public_key = bls_sk
+        .public_key()
+        .as_bytes()
+        .try_into()
+        .map_err(|_| anyhow!("unexpected BLS public key length"))
+        .kind(ErrorKind::Verification)?;
+
+    Ok(fendermint_actor_cetf::BlsPublicKey(public_key))
+}
+
 /// Get the next sequence number (nonce) of an account.
 async fn sequence(client: &impl QueryClient, addr: &Address) -> anyhow::Result<u64> {
     let state = client
         .actor_state(&addr, FvmQueryHeight::default())
         .await
-        .context("failed to get actor state")?;
+        .context("failed to get actor state")
+        .kind(ErrorKind::Rpc)?;
 
     match state.value {
         Some((_id, state)) => Ok(state.sequence),
-        None => Err(anyhow!("cannot find actor {addr}")),
+        None => Err(anyhow!("cannot find actor {addr}")).kind(ErrorKind::Config),
     }
 }
 
 /// Create an instance of the statically typed contract client.
-fn delorean_contract(contract_eth_addr: &str) -> DeloreanContract<MockProvider> {
+fn delorean_contract(contract_eth_addr: &str) -> anyhow::Result<DeloreanContract<MockProvider>> {
     // A dummy client that we don't intend to use to call the contract or send transactions.
     let (client, _mock) = ethers::providers::Provider::mocked();
-    let contract_h160_addr = ethers::core::types::Address::from_slice(
-        hex::decode(contract_eth_addr.trim_start_matches("0x"))
-            .unwrap()
-            .as_slice(),
-    );
+    let contract_h160_addr =
+        ethers::core::types::Address::from_slice(parse_eth_addr(contract_eth_addr)?.as_slice());
     let contract = DeloreanContract::new(contract_h160_addr, std::sync::Arc::new(client));
-    contract
+    Ok(contract)
 }
 
 /// Retrive the signing tag from a deployed Demo contract given its address
 async fn retrieve_signing_tag(
     client: &mut (impl TxClient<TxCommit> + CallClient),
     contract_eth_addr: &str,
+    gas_params: &GasParams,
 ) -> anyhow::Result<[u8; 32]> {
-    let contract = delorean_contract(contract_eth_addr);
+    let contract = delorean_contract(contract_eth_addr)?;
     let call = contract.signing_tag();
-    let signing_tag: [u8; 32] = invoke_or_call_contract(client, contract_eth_addr, call, true)
-        .await
-        .context("failed to call contract")?;
+    let signing_tag: [u8; 32] =
+        invoke_or_call_contract(client, contract_eth_addr, call, true, gas_params)
+            .await
+            .context("failed to call contract")?;
     Ok(signing_tag)
 }
 
-async fn get_agg_pubkey(
+/// List the validators and their registered BLS keys.
+async fn registered_keys<BS: Blockstore + Clone>(
     client: &impl QueryClient,
-    store: &RemoteBlockstore<FendermintClient>,
-) -> anyhow::Result<bls_signatures::PublicKey> {
-    let QueryResponse { height, value } = client
-        .actor_state(
-            &fendermint_vm_actor_interface::cetf::CETFSYSCALL_ACTOR_ADDR,
-            FvmQueryHeight::default(),
-        )
-        .await
-        .expect("failed to get cetf actor state");
+    store: &BS,
+) -> anyhow::Result<Vec<(Address, cetf_actor::BlsPublicKey)>> {
+    let (_, state) = cetf_state(client, store).await?;
 
-    let (id, act_state) = value.expect("cetf actor state not found");
-    // tracing::info!("Get Cetf State (id: {}) at height {}", id, height);
-    let state: CetfActorState = store
-        .get_cbor(&act_state.state)
-        .expect("failed to get cetf actor")
-        .expect("no actor state found");
+    let validator_map = cetf_actor::state::ValidatorBlsPublicKeyMap::load(
+        store.clone(),
+        &state.validators,
+        DEFAULT_HAMT_CONFIG,
+        "load validator hamt",
+    )
+    .context("failed to load validator hamt")
+    .kind(ErrorKind::Rpc)?;
 
-    // Get all the validators BLS keys
-    let mut bls_keys_bytes = vec![];
+    let mut keys = vec![];
+    validator_map
+        .for_each(|k, v| {
+            keys.push((k, *v));
+            Ok(())
+        })
+        .context("failed to iterate validator hamt")
+        .kind(ErrorKind::Rpc)?;
+
+    Ok(keys)
+}
+
+/// Parse the registered BLS keys of all validators.
+fn validator_public_keys<BS: Blockstore + Clone>(
+    store: &BS,
+    state: &CetfActorState,
+) -> anyhow::Result<Vec<bls_signatures::PublicKey>> {
     let validator_map = cetf_actor::state::ValidatorBlsPublicKeyMap::load(
         store.clone(),
         &state.validators,
         DEFAULT_HAMT_CONFIG,
         "load validator hamt",
     )
-    .expect("failed to load validator hamt");
+    .context("failed to load validator hamt")
+    .kind(ErrorKind::Rpc)?;
+
+    let mut bls_keys_bytes = vec![];
     validator_map
         .for_each(|_k, v| {
             bls_keys_bytes.push(*v);
             Ok(())
         })
-        .expect("failed to iterate validator hamt");
+        .context("failed to iterate validator hamt")
+        .kind(ErrorKind::Rpc)?;
 
-    let pub_keys = bls_keys_bytes
+    bls_keys_bytes
         .iter()
-        .map(|b| {
-            bls_signatures::PublicKey::from_bytes(&b.0)
-                .expect("failed to parse public key from bytes")
-        })
-        .collect::<Vec<_>>();
+        .map(|b| bls_signatures::PublicKey::from_bytes(&b.0))
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to parse public key from bytes")
+        .kind(ErrorKind::Verification)
+}
 
-    Ok(bls_signatures::aggregate_keys(&pub_keys).expect("failed to aggregate public keys"))
+async fn get_agg_pubkey<BS: Blockstore + Clone>(
+    client: &impl QueryClient,
+    store: &BS,
+) -> anyhow::Result<bls_signatures::PublicKey> {
+    let (_, state) = cetf_state(client, store).await?;
+    let pub_keys = validator_public_keys(store, &state)?;
+
+    bls_signatures::aggregate_keys(&pub_keys)
+        .context("failed to aggregate public keys")
+        .kind(ErrorKind::Verification)
 }
 
-async fn get_signature_for_tag(
+/// Look up the aggregated signature of the validators over a tag.
+fn signature_for_tag<BS: Blockstore + Clone>(
+    store: &BS,
+    state: &CetfActorState,
+    signing_tag: [u8; 32],
+) -> anyhow::Result<BlsSignature> {
+    find_signature(store, state, signing_tag)?
+        .ok_or_else(|| anyhow!("signature not found for tag 0x{}", hex::encode(signing_tag)))
+        .kind(ErrorKind::Verification)
+}
+
+async fn get_signature_for_tag<BS: Blockstore + Clone>(
     client: &impl QueryClient,
-    store: &RemoteBlockstore<FendermintClient>,
+    store: &BS,
     signing_tag: [u8; 32],
-) -> anyhow::Result<cetf_actor::BlsSignature> {
-    let QueryResponse { height, value } = client
-        .actor_state(
-            &fendermint_vm_actor_interface::cetf::CETFSYSCALL_ACTOR_ADDR,
-            FvmQueryHeight::default(),
-        )
-        .await
-        .expect("failed to get cetf actor state");
+) -> anyhow::Result<BlsSignature> {
+    let (_, state) = cetf_state(client, store).await?;
+    signature_for_tag(store, &state, signing_tag)
+}
 
-    let (id, act_state) = value.expect("cetf actor state not found");
-    tracing::info!("Get Cetf State (id: {}) at height {}", id, height);
-    let state: CetfActorState = store
-        .get_cbor(&act_state.state)
-        .expect("failed to get cetf actor")
-        .expect("no actor state found");
+/// Decode a hex encoded 32 byte signing tag, with or without the `0x` prefix.
+fn parse_tag(tag: &str) -> anyhow::Result<[u8; 32]> {
+    let bz = hex::decode(tag.trim_start_matches("0x"))
+        .with_context(|| format!("invalid tag: {tag}"))
+        .kind(ErrorKind::Config)?;
 
-    let signed_hashed_tag = cetf_actor::state::SignedHashedTagMap::load(
-        store.clone(),
-        &state.signed_hashed_tags,
-        DEFAULT_HAMT_CONFIG,
-        "load signed hashed tags",
-    )
-    .expect("failed to load signed hashed tags");
+    bz.try_into()
+        .map_err(|_| anyhow!("tag must be 32 bytes: {tag}"))
+        .kind(ErrorKind::Config)
+}
+
+/// Decode a hex encoded Ethereum address, with or without the `0x` prefix.
+fn parse_eth_addr(eth_addr: &str) -> anyhow::Result<Vec<u8>> {
+    let bz = hex::decode(eth_addr.trim_start_matches("0x"))
+        .with_context(|| format!("invalid contract address: {eth_addr}"))
+        .kind(ErrorKind::Config)?;
+    if bz.len() != 20 {
+        return Err(anyhow!("invalid contract address length: {eth_addr}")).kind(ErrorKind::Config);
+    }
+    Ok(bz)
+}
 
-    Ok(*signed_hashed_tag
-        .get(&signing_tag.into())
-        .expect("failed to get signature from signed hashed tag")
-        .expect("signature not found"))
+fn eth_addr_to_eam(eth_addr: &str) -> anyhow::Result<Address> {
+    let eth_addr = parse_eth_addr(eth_addr)?;
+    let addr = Address::new_delegated(eam::EAM_ACTOR_ID, &eth_addr)
+        .expect("ETH address to delegated should work");
+    Ok(addr)
 }
 
-fn eth_addr_to_eam(eth_addr: &str) -> Address {
-    let eth_addr = hex::decode(eth_addr.trim_start_matches("0x")).expect("valid hex");
-    Address::new_delegated(eam::EAM_ACTOR_ID, &eth_addr)
-        .expect("ETH address to delegated should work")
+#[cfg(test)]
+mod tests {
+    use delorean_cli::error::{exit_code, ErrorKind};
+    use fendermint_rpc::FendermintClient;
+    use fvm_ipld_blockstore::MemoryBlockstore;
+    use tendermint_rpc::{MockClient, MockRequestMethodMatcher};
+
+    use super::{parse_eth_addr, parse_tag, registered_keys};
+
+    #[tokio::test]
+    async fn registered_keys_unreachable_node() {
+        // The mock has no responses, so every request fails like a broken connection would.
+        let client = FendermintClient::new(MockClient::new(MockRequestMethodMatcher::default()).0);
+        let store = MemoryBlockstore::new();
+
+        let err = registered_keys(&client, &store)
+            .await
+            .expect_err("query should fail");
+
+        assert_eq!(exit_code(&err), ErrorKind::Rpc.exit_code());
+        assert!(format!("{err:#}").contains("failed to get cetf actor state"));
+    }
+
+    #[test]
+    fn invalid_contract_address() {
+        let err = parse_eth_addr("0xnot-an-address").unwrap_err();
+        assert_eq!(exit_code(&err), ErrorKind::Config.exit_code());
+
+        let err = parse_eth_addr("0x1234").unwrap_err();
+        assert_eq!(exit_code(&err), ErrorKind::Config.exit_code());
+
+        parse_eth_addr("0x8ed26a19f0e0d6708546495611e9a298d9befb59").expect("valid address");
+    }
+
+    #[test]
+    fn invalid_tag() {
+        let err = parse_tag("0x1234").unwrap_err();
+        assert_eq!(exit_code(&err), ErrorKind::Config.exit_code());
+
+        let tag = parse_tag(&format!("0x{}", "ab".repeat(32))).expect("valid tag");
+        assert_eq!(tag, [0xab; 32]);
+    }
 }
@@ -149,6 +149,11 @@ fn gen_manifest(
                 }
             },
             urls: gen_urls(g),
+            faucet: if bool::arbitrary(g) {
+                gen_urls(g).pop()
+            } else {
+                None
+            },
         }
     } else {
         let initial_balances = balances.clone();
@@ -289,6 +294,12 @@ fn gen_subnets(
                 } else {
                     Some(choose_one(g, parent_nodes))
                 },
+                fendermint_image: if bool::arbitrary(g) {
+                    Some("fendermint:latest".to_string())
+                } else {
+                    None
+                },
+                cometbft_image: None,
             };
             let id = NodeId::arbitrary(g);
             node_ids.push(id.clone());
@@ -0,0 +1,235 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context};
+use ethers::{
+    abi::{Abi, Tokenize},
+    core::k256::ecdsa::SigningKey,
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, Provider},
+    signers::{Signer, Wallet},
+    types::{self as et, Eip1559TransactionRequest},
+};
+use fendermint_eth_hardhat::{Hardhat, FQN};
+use fendermint_vm_actor_interface::{
+    diamond::{self, EthContract, EthContractMap},
+    ipc::{self, IPC_CONTRACTS},
+};
+use fendermint_vm_genesis::ipc::GatewayParams;
+use ipc_actors_abis::i_diamond::FacetCut;
+use ipc_api::subnet_id::SubnetID;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::materials::DefaultAccount;
+
+type DeployerMiddleware = SignerMiddleware<Provider<Http>, Wallet<SigningKey>>;
+
+/// Addresses of the IPC contracts deployed on a rootnet, persisted so that
+/// re-running the materializer reuses the same deployment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeployedContracts {
+    pub gateway: et::Address,
+    pub registry: et::Address,
+}
+
+/// Deploy the IPC stack through the Ethereum API of a rootnet: the libraries and
+/// facets in topological order, then the Gateway and SubnetRegistry diamonds.
+pub async fn deploy_ipc_contracts(
+    contracts_dir: &Path,
+    url: &Url,
+    deployer: &DefaultAccount,
+) -> anyhow::Result<DeployedContracts> {
+    let provider =
+        Provider::<Http>::try_from(url.to_string()).context("failed to create provider")?;
+
+    let chain_id = provider
+        .get_chainid()
+        .await
+        .context("failed to get chain ID")?;
+
+    let wallet: Wallet<SigningKey> =
+        Wallet::from_bytes(deployer.secret_key().serialize().as_ref())?
+            .with_chain_id(chain_id.as_u64());
+
+    let hardhat = Hardhat::new(contracts_dir.to_path_buf());
+    let mut deployer = ContractDeployer::new(
+        &hardhat,
+        &IPC_CONTRACTS,
+        SignerMiddleware::new(provider, wallet),
+    );
+
+    // Deploy the libraries and facets the top level contracts depend on.
+    for (lib_src, lib_name) in diamond::eth_libraries(&hardhat, &IPC_CONTRACTS)? {
+        deployer.deploy_library(lib_src, &lib_name).await?;
+    }
+
+    let gateway = {
+        use ipc::gateway::ConstructorParameters;
+
+        // The same parameters we use for the root genesis of a new rootnet.
+        let params = ConstructorParameters::new(
+            GatewayParams {
+                subnet_id: SubnetID::new_root(chain_id.as_u64()),
+                bottom_up_check_period: 0,
+                majority_percentage: 67,
+                active_validators_limit: 100,
+            },
+            Vec::new(),
+        )
+        .context("failed to create gateway constructor")?;
+
+        let facets = deployer
+            .facets(ipc::gateway::CONTRACT_NAME)
+            .context("failed to collect gateway facets")?;
+
+        deployer
+            .deploy_contract(ipc::gateway::CONTRACT_NAME, (facets, params))
+            .await?
+    };
+
+    let registry = {
+        use ipc::registry::ConstructorParameters;
+
+        let facets = deployer
+            .facets(ipc::registry::CONTRACT_NAME)
+            .context("failed to collect registry facets")?;
+
+        let (facets, params) = ConstructorParameters::new(gateway, facets)
+            .context("failed to create registry constructor")?;
+
+        deployer
+            .deploy_contract(ipc::registry::CONTRACT_NAME, (facets, params))
+            .await?
+    };
+
+    Ok(DeployedContracts { gateway, registry })
+}
+
+/// Deploys contracts with transactions, the way the genesis does it with actors.
+struct ContractDeployer<'a> {
+    hardhat: &'a Hardhat,
+    top_contracts: &'a EthContractMap,
+    middleware: DeployerMiddleware,
+    lib_addrs: HashMap<FQN, et::Address>,
+}
+
+impl<'a> ContractDeployer<'a> {
+    fn new(
+        hardhat: &'a Hardhat,
+        top_contracts: &'a EthContractMap,
+        middleware: DeployerMiddleware,
+    ) -> Self {
+        Self {
+            hardhat,
+            top_contracts,
+            middleware,
+            lib_addrs: Default::default(),
+        }
+    }
+
+    /// Deploy a library contract with no constructor.
+    async fn deploy_library(
+        &mut self,
+        lib_src: impl AsRef<Path>,
+        lib_name: &str,
+    ) -> anyhow::Result<()> {
+        let fqn = self.hardhat.fqn(lib_src.as_ref(), lib_name);
+
+        let bytecode = self
+            .hardhat
+            .bytecode(&lib_src, lib_name, &self.lib_addrs)
+            .with_context(|| format!("failed to load library bytecode {fqn}"))?;
+
+        let eth_addr = self
+            .deploy(bytecode)
+            .await
+            .with_context(|| format!("failed to deploy library {fqn}"))?;
+
+        tracing::info!(?eth_addr, fqn, "deployed Ethereum library");
+
+        self.lib_addrs.insert(fqn, eth_addr);
+
+        Ok(())
+    }
+
+    /// Construct the bytecode of a top-level contract and deploy it with some constructor parameters.
+    async fn deploy_contract<T>(
+        &self,
+        contract_name: &str,
+        constructor_params: T,
+    ) -> anyhow::Result<et::Address>
+    where
+        T: Tokenize,
+    {
+        let contract = self.top_contract(contract_name)?;
+
+        let bytecode = self
+            .hardhat
+            .bytecode(
+                diamond::contract_src(contract_name),
+                contract_name,
+                &self.lib_addrs,
+            )
+            .with_context(|| format!("failed to load {contract_name} bytecode"))?;
+
+        let initcode = initcode(&contract.abi, bytecode, constructor_params)
+            .with_context(|| format!("failed to encode {contract_name} constructor"))?;
+
+        let eth_addr = self
+            .deploy(initcode)
+            .await
+            .with_context(|| format!("failed to deploy {contract_name}"))?;
+
+        tracing::info!(?eth_addr, contract_name, "deployed Ethereum contract");
+
+        Ok(eth_addr)
+    }
+
+    /// Collect Facet Cuts for the diamond pattern, where the facet address comes from already deployed library facets.
+    fn facets(&self, contract_name: &str) -> anyhow::Result<Vec<FacetCut>> {
+        let contract = self.top_contract(contract_name)?;
+        diamond::facet_cuts(self.hardhat, contract, &self.lib_addrs)
+    }
+
+    fn top_contract(&self, contract_name: &str) -> anyhow::Result<&EthContract> {
+        self.top_contracts
+            .get(contract_name)
+            .ok_or_else(|| anyhow!("unknown top contract name: {contract_name}"))
+    }
+
+    /// Send a contract creation transaction and wait for the address in the receipt.
+    async fn deploy(&self, initcode: Vec<u8>) -> anyhow::Result<et::Address> {
+        let tx = Eip1559TransactionRequest::new().data(initcode);
+
+        let receipt = self
+            .middleware
+            .send_transaction(tx, None)
+            .await
+            .context("failed to send deployment")?
+            .await
+            .context("failed to get deployment receipt")?
+            .ok_or_else(|| anyhow!("deployment dropped from the mempool"))?;
+
+        if receipt.status != Some(1.into()) {
+            bail!("deployment failed: {:?}", receipt.transaction_hash);
+        }
+
+        receipt
+            .contract_address
+            .ok_or_else(|| anyhow!("no contract address in the deployment receipt"))
+    }
+}
+
+fn initcode<T: Tokenize>(abi: &Abi, bytecode: Vec<u8>, params: T) -> anyhow::Result<Vec<u8>> {
+    let constructor = abi
+        .constructor()
+        .ok_or_else(|| anyhow!("contract doesn't have a constructor"))?;
+
+    constructor
+        .encode_input(bytecode, &params.into_tokens())
+        .context("failed to encode constructor input")
+}
@@ -0,0 +1,192 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context};
+use ethers::{
+    core::k256::ecdsa::SigningKey,
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, Provider},
+    signers::{Signer, Wallet},
+    types::{Eip1559TransactionRequest, H160},
+};
+use fendermint_crypto::SecretKey;
+use fendermint_vm_message::conv::from_fvm::to_eth_tokens;
+use fvm_shared::econ::TokenAmount;
+use url::Url;
+
+use crate::materials::DefaultAccount;
+
+/// How long to wait for the transfer to be reflected in the balance of the account.
+const BALANCE_TIMEOUT: Duration = Duration::from_secs(60);
+const BALANCE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How long to wait for a faucet web service to respond.
+const FAUCET_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An account on the rootnet with enough funds to hand out some to every
+/// account of a testnet, reached through the Ethereum API of the rootnet.
+#[derive(Clone)]
+pub struct DockerFaucet {
+    /// Ethereum API endpoint of the rootnet, reachable from the host.
+    pub url: Url,
+    /// The key of the rich account, e.g. one with a large balance in the root genesis.
+    pub secret_key: SecretKey,
+    /// The amount sent to each account.
+    pub amount: TokenAmount,
+}
+
+impl DockerFaucet {
+    /// Send the funds to the account and wait until they show up in its balance.
+    pub async fn fund(&self, account: &DefaultAccount) -> anyhow::Result<()> {
+        let provider = Provider::<Http>::try_from(self.url.to_string())
+            .context("failed to create faucet provider")?;
+
+        let chain_id = provider
+            .get_chainid()
+            .await
+            .context("failed to get chain ID")?;
+
+        let wallet: Wallet<SigningKey> = Wallet::from_bytes(self.secret_key.serialize().as_ref())?
+            .with_chain_id(chain_id.as_u64());
+
+        let middleware = SignerMiddleware::new(provider, wallet);
+
+        let to: H160 = account.eth_addr().into();
+        let value = to_eth_tokens(&self.amount)?;
+
+        let balance = middleware
+            .get_balance(to, None)
+            .await
+            .context("failed to get balance")?;
+
+        let transfer = Eip1559TransactionRequest::new().to(to).value(value);
+
+        let receipt = middleware
+            .send_transaction(transfer, None)
+            .await
+            .context("failed to send faucet transfer")?
+            .await
+            .context("failed to get faucet transfer receipt")?
+            .ok_or_else(|| anyhow!("faucet transfer dropped from the mempool"))?;
+
+        if receipt.status != Some(1.into()) {
+            bail!("faucet transfer failed: {:?}", receipt.transaction_hash);
+        }
+
+        // The API we sent the transaction to might lag behind the block the receipt is from.
+        let expected = balance.saturating_add(value);
+        let start = Instant::now();
+        loop {
+            let balance = middleware
+                .get_balance(to, None)
+                .await
+                .context("failed to get balance")?;
+
+            if balance >= expected {
+                return Ok(());
+            }
+            if start.elapsed() > BALANCE_TIMEOUT {
+                bail!("faucet funds not visible in the balance of {account}: {balance}");
+            }
+            tokio::time::sleep(BALANCE_POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Ask a faucet running as a web service to send funds to the account.
+///
+/// The address of the account is sent as JSON in the form of `{"address": "0x..."}`.
+/// Any response other than a success status is treated as a failure.
+pub async fn fund_from_http_faucet(url: &Url, account: &DefaultAccount) -> anyhow::Result<()> {
+    let address: H160 = account.eth_addr().into();
+
+    let res = reqwest::Client::new()
+        .post(url.clone())
+        .json(&serde_json::json!({ "address": format!("{address:?}") }))
+        .timeout(FAUCET_REQUEST_TIMEOUT)
+        .send()
+        .await
+        .with_context(|| format!("faucet at {url} is unreachable"))?;
+
+    let status = res.status();
+    if !status.is_success() {
+        let body = res.text().await.unwrap_or_default();
+        bail!("faucet at {url} responded with {status}: {body}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    use ethers::core::rand::{rngs::StdRng, SeedableRng};
+    use url::Url;
+
+    use super::fund_from_http_faucet;
+    use crate::{materials::DefaultAccount, TestnetName};
+
+    /// Serve a single request with a canned response, returning the URL to send it to.
+    fn serve_once(response: &'static str) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).unwrap();
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        Url::parse(&format!("http://{addr}/fund")).unwrap()
+    }
+
+    fn account(dir: &std::path::Path) -> DefaultAccount {
+        let mut rng = StdRng::seed_from_u64(0);
+        let name = TestnetName::new("faucet").account("alice");
+        DefaultAccount::get_or_create(&mut rng, dir, &name).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_http_faucet_success() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = serve_once("HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n");
+
+        fund_from_http_faucet(&url, &account(dir.path()))
+            .await
+            .expect("faucet should succeed");
+    }
+
+    #[tokio::test]
+    async fn test_http_faucet_error_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let url =
+            serve_once("HTTP/1.1 429 Too Many Requests\r\ncontent-length: 9\r\n\r\nslow down");
+
+        let err = fund_from_http_faucet(&url, &account(dir.path()))
+            .await
+            .unwrap_err();
+
+        let err = err.to_string();
+        assert!(err.contains("429"), "{err}");
+        assert!(err.contains("slow down"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_http_faucet_unreachable() {
+        let url = {
+            // Bind to get a free port, then close it again.
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+            Url::parse(&format!("http://{addr}/fund")).unwrap()
+        };
+        let dir = tempfile::tempdir().unwrap();
+
+        let err = fund_from_http_faucet(&url, &account(dir.path()))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("unreachable"));
+    }
+}
@@ -30,7 +30,7 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     os::unix::fs::MetadataExt,
     path::{Path, PathBuf},
     str::FromStr,
@@ -52,30 +52,42 @@ use crate::{
 };
 
 mod container;
+mod deployment;
 mod dropper;
+mod faucet;
 mod network;
 mod node;
+mod prune;
 mod relayer;
 mod runner;
 
 pub use dropper::DropPolicy;
+pub use faucet::{fund_from_http_faucet, DockerFaucet};
 pub use network::DockerNetwork;
 pub use node::DockerNode;
+pub use prune::{prune_plan, PruneItem, PrunePlan};
 pub use relayer::DockerRelayer;
 
-use self::{dropper::DropHandle, network::NetworkName, runner::DockerRunner};
+use self::{
+    deployment::DeployedContracts, dropper::DropHandle, network::NetworkName, runner::DockerRunner,
+};
 
-// TODO: Add these to the materializer.
 const COMETBFT_IMAGE: &str = "cometbft/cometbft:v0.38.x";
 const FENDERMINT_IMAGE: &str = "fendermint:latest";
 
 const STATE_JSON_FILE_NAME: &str = "materializer-state.json";
+const DEPLOYMENT_JSON_FILE_NAME: &str = "deployment.json";
+
+/// Directory with the Hardhat artifacts of the IPC contracts, unless overridden by `FM_CONTRACTS_DIR`.
+const DEFAULT_CONTRACTS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../contracts/out");
 
 const DOCKER_ENTRY_SCRIPT: &str = include_str!("../../scripts/docker-entry.sh");
 const DOCKER_ENTRY_FILE_NAME: &str = "docker-entry.sh";
 
 const PORT_RANGE_START: u32 = 30000;
 const PORT_RANGE_SIZE: u32 = 100;
+/// Default upper bound of the allocated port ranges, leaving room for 100 nodes.
+const DEFAULT_PORT_RANGE_END: u32 = PORT_RANGE_START + 100 * PORT_RANGE_SIZE;
 
 lazy_static! {
     static ref STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
@@ -116,7 +128,7 @@ pub struct DockerConstruct {
 }
 
 /// Allocated (inclusive) range we can use to expose containers' ports on the host.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct DockerPortRange {
     pub from: u32,
     pub to: u32,
@@ -151,15 +163,175 @@ impl DockerPortRange {
     pub fn fendermint_metrics_host_port(&self) -> u32 {
         self.from + 84
     }
+
+    /// All the ports a node exposes on the host.
+    pub fn host_ports(&self) -> [u32; 5] {
+        [
+            self.resolver_p2p_host_port(),
+            self.cometbft_p2p_host_port(),
+            self.cometbft_rpc_host_port(),
+            self.ethapi_rpc_host_port(),
+            self.fendermint_metrics_host_port(),
+        ]
+    }
+}
+
+/// Check that nothing else on the host is listening on the ports of a range,
+/// e.g. a testnet the materializer doesn't know about, or some unrelated service.
+fn host_ports_free(range: &DockerPortRange) -> bool {
+    range.host_ports().into_iter().all(|port| {
+        u16::try_from(port)
+            .map(|port| std::net::TcpListener::bind(("0.0.0.0", port)).is_ok())
+            .unwrap_or_default()
+    })
+}
+
+/// A port range in use by a node.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+struct PortAllocation {
+    range: DockerPortRange,
+    node_name: NodeName,
 }
 
 /// State of the materializer that it persists, so that it can resume operations.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct DockerMaterializerState {
-    /// Port ranges ever allocated by this materializer.
+    /// Port ranges currently allocated by this materializer, ordered by the start of the range.
+    #[serde(default)]
+    port_allocations: BTreeSet<PortAllocation>,
+    /// Port ranges as they were recorded by earlier versions, which never released them.
+    ///
+    /// They are moved into `port_allocations` when the state is loaded.
+    #[serde(default, skip_serializing)]
     port_ranges: BTreeMap<NodeName, DockerPortRange>,
 }
 
+impl DockerMaterializerState {
+    /// Move allocations from the legacy format into the current one.
+    fn migrate(mut self) -> Self {
+        let legacy = std::mem::take(&mut self.port_ranges);
+        for (node_name, range) in legacy {
+            if self.port_range(&node_name).is_none() {
+                self.port_allocations
+                    .insert(PortAllocation { range, node_name });
+            }
+        }
+        self
+    }
+
+    /// Look up the range already allocated to a node.
+    fn port_range(&self, node_name: &NodeName) -> Option<DockerPortRange> {
+        self.port_allocations
+            .iter()
+            .find(|a| a.node_name == *node_name)
+            .map(|a| a.range.clone())
+    }
+
+    /// Allocate the first free range, unless the node already has one.
+    ///
+    /// Ranges which are not allocated, but where `is_free` finds the host ports
+    /// in use are skipped. Fails if there is no free range which would end at
+    /// or below `range_end`.
+    fn allocate_port_range(
+        &mut self,
+        node_name: &NodeName,
+        range_end: u32,
+        is_free: impl Fn(&DockerPortRange) -> bool,
+    ) -> anyhow::Result<DockerPortRange> {
+        if let Some(range) = self.port_range(node_name) {
+            return Ok(range);
+        }
+
+        let mut from = PORT_RANGE_START;
+        let mut busy = 0;
+
+        let range = loop {
+            let to = from + PORT_RANGE_SIZE;
+            if to > range_end {
+                if busy > 0 {
+                    bail!(
+                        "no free port range below {range_end} for {node_name:?}; {busy} unallocated ranges have ports in use on the host"
+                    );
+                }
+                bail!(
+                    "no free port range below {range_end} for {node_name:?}; remove or prune unused testnets to release their ports"
+                );
+            }
+
+            // The allocations are ordered, so we can jump over the first one we collide with.
+            if let Some(a) = self
+                .port_allocations
+                .iter()
+                .find(|a| a.range.from < to && from < a.range.from + PORT_RANGE_SIZE)
+            {
+                from = a.range.from + PORT_RANGE_SIZE;
+                continue;
+            }
+
+            let range = DockerPortRange { from, to };
+
+            if is_free(&range) {
+                break range;
+            }
+
+            tracing::warn!(
+                from,
+                to,
+                "skipping port range because some of its ports are in use on the host"
+            );
+            busy += 1;
+            from = to;
+        };
+
+        self.port_allocations.insert(PortAllocation {
+            range: range.clone(),
+            node_name: node_name.clone(),
+        });
+
+        Ok(range)
+    }
+
+    /// Free up the range allocated to a node, returning it if there was one.
+    fn release_port_range(&mut self, node_name: &NodeName) -> Option<DockerPortRange> {
+        let allocation = self
+            .port_allocations
+            .iter()
+            .find(|a| a.node_name == *node_name)
+            .cloned()?;
+
+        self.port_allocations.remove(&allocation);
+
+        Some(allocation.range)
+    }
+
+    /// Names of the nodes with port ranges allocated in a testnet.
+    fn port_range_nodes(&self, testnet_name: &TestnetName) -> Vec<NodeName> {
+        self.port_allocations
+            .iter()
+            .filter(|a| testnet_name.contains(&a.node_name))
+            .map(|a| a.node_name.clone())
+            .collect()
+    }
+}
+
+/// The docker images the materializer runs, unless a node overrides them in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DockerImages {
+    /// Image with `fendermint`, `ipc-cli` and the contracts.
+    pub fendermint: String,
+    /// Image with `cometbft`.
+    pub cometbft: String,
+}
+
+impl Default for DockerImages {
+    fn default() -> Self {
+        Self {
+            fendermint: FENDERMINT_IMAGE.to_string(),
+            cometbft: COMETBFT_IMAGE.to_string(),
+        }
+    }
+}
+
 pub struct DockerMaterializer {
     dir: PathBuf,
     rng: StdRng,
@@ -168,6 +340,9 @@ pub struct DockerMaterializer {
     drop_chute: dropper::DropChute,
     drop_policy: dropper::DropPolicy,
     state: DockerMaterializerState,
+    faucet: Option<DockerFaucet>,
+    port_range_end: u32,
+    images: DockerImages,
 }
 
 impl DockerMaterializer {
@@ -183,6 +358,7 @@ impl DockerMaterializer {
         // Read in the state if it exists, otherwise create a default one.
         let state = import_json(dir.join(STATE_JSON_FILE_NAME))
             .context("failed to read state")?
+            .map(DockerMaterializerState::migrate)
             .unwrap_or_default();
 
         let m = Self {
@@ -193,6 +369,9 @@ impl DockerMaterializer {
             drop_chute,
             state,
             drop_policy: DropPolicy::default(),
+            faucet: None,
+            port_range_end: DEFAULT_PORT_RANGE_END,
+            images: DockerImages::default(),
         };
 
         m.save_state().context("failed to save state")?;
@@ -206,6 +385,26 @@ impl DockerMaterializer {
         self
     }
 
+    /// Set the highest host port the materializer can allocate to nodes.
+    pub fn with_port_range_end(mut self, port_range_end: u32) -> Self {
+        self.port_range_end = port_range_end;
+        self
+    }
+
+    /// Set the images to run, e.g. to pin exact tags or use locally built images.
+    pub fn with_images(mut self, images: DockerImages) -> Self {
+        self.images = images;
+        self
+    }
+
+    /// Set the account which funds the accounts on external rootnets.
+    ///
+    /// It can be changed after construction, e.g. to use an account of a testnet
+    /// that was materialized earlier as the faucet of another one.
+    pub fn set_faucet(&mut self, faucet: Option<DockerFaucet>) {
+        self.faucet = faucet;
+    }
+
     /// Remove all traces of a testnet.
     pub async fn remove(&mut self, testnet_name: &TestnetName) -> anyhow::Result<()> {
         let testnet = testnet_name.path_string();
@@ -227,17 +426,7 @@ impl DockerMaterializer {
 
         for id in ids {
             eprintln!("removing docker container {id}");
-            self.docker
-                .remove_container(
-                    &id,
-                    Some(RemoveContainerOptions {
-                        force: true,
-                        v: true,
-                        ..Default::default()
-                    }),
-                )
-                .await
-                .with_context(|| format!("failed to remove container {id}"))?;
+            self.remove_container(&id).await?;
         }
 
         let mut filters = HashMap::new();
@@ -253,12 +442,125 @@ impl DockerMaterializer {
 
         for id in ids {
             eprintln!("removing docker network {id}");
-            self.docker
-                .remove_network(&id)
-                .await
-                .context("failed to remove network")?;
+            self.remove_network(&id).await?;
+        }
+
+        for node_name in self.state.port_range_nodes(testnet_name) {
+            self.release_ports(&node_name)?;
+        }
+
+        self.remove_testnet_dir(testnet_name)
+    }
+
+    /// Free up the port range allocated to a node, so that it can be reused by others.
+    pub fn release_ports(&mut self, node_name: &NodeName) -> anyhow::Result<()> {
+        self.update_state(|s| {
+            s.release_port_range(node_name);
+        })
+    }
+
+    /// Remove the resources of testnets which are no longer in use, e.g. left behind by crashed runs,
+    /// without touching the healthy ones. See [prune_plan] for what counts as orphaned.
+    ///
+    /// With `dry_run` nothing is removed, only the plan is returned.
+    pub async fn prune(&mut self, dry_run: bool) -> anyhow::Result<PrunePlan> {
+        let mut filters = HashMap::new();
+        filters.insert("label".to_string(), vec!["testnet".to_string()]);
+
+        let containers: Vec<ContainerSummary> = self
+            .docker
+            .list_containers(Some(ListContainersOptions {
+                all: true,
+                filters,
+                ..Default::default()
+            }))
+            .await
+            .context("failed to list docker containers")?;
+
+        // All testnet names start with this prefix, see `TestnetName::new`.
+        let testnets = "testnets";
+
+        let mut filters = HashMap::new();
+        filters.insert("name".to_string(), vec![testnets.to_string()]);
+
+        // The name filter matches substrings, so only keep the ones that look like testnets.
+        let networks: Vec<Network> = self
+            .docker
+            .list_networks(Some(ListNetworksOptions { filters }))
+            .await
+            .context("failed to list networks")?
+            .into_iter()
+            .filter(|n| {
+                n.name
+                    .as_ref()
+                    .map(|n| n.starts_with(&format!("{testnets}/")))
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        let mut testnet_dirs = Vec::new();
+        let testnets_dir = self.dir.join(testnets);
+        if testnets_dir.exists() {
+            for entry in std::fs::read_dir(&testnets_dir).with_context(|| {
+                format!(
+                    "failed to list testnet directories in {}",
+                    testnets_dir.to_string_lossy()
+                )
+            })? {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    let id = entry.file_name().to_string_lossy().to_string();
+                    testnet_dirs.push(TestnetName::new(id));
+                }
+            }
+        }
+
+        let plan = prune_plan(&containers, &networks, &testnet_dirs);
+
+        if dry_run {
+            return Ok(plan);
+        }
+
+        // Containers come first in the plan, so the networks are detached by the time we get to them.
+        for item in plan.remove.iter() {
+            eprintln!("removing {item}");
+            match item {
+                PruneItem::Container { id, .. } => self.remove_container(id).await?,
+                PruneItem::Network { id, .. } => self.remove_network(id).await?,
+                PruneItem::Directory(testnet_name) => {
+                    for node_name in self.state.port_range_nodes(testnet_name) {
+                        self.release_ports(&node_name)?;
+                    }
+                    self.remove_testnet_dir(testnet_name)?;
+                }
+            }
         }
 
+        Ok(plan)
+    }
+
+    async fn remove_container(&self, id: &str) -> anyhow::Result<()> {
+        self.docker
+            .remove_container(
+                id,
+                Some(RemoveContainerOptions {
+                    force: true,
+                    v: true,
+                    ..Default::default()
+                }),
+            )
+            .await
+            .with_context(|| format!("failed to remove container {id}"))
+    }
+
+    async fn remove_network(&self, id: &str) -> anyhow::Result<()> {
+        self.docker
+            .remove_network(id)
+            .await
+            .context("failed to remove network")
+    }
+
+    fn remove_testnet_dir(&self, testnet_name: &TestnetName) -> anyhow::Result<()> {
         let dir = self.dir.join(testnet_name.path());
         if let Err(e) = std::fs::remove_dir_all(&dir) {
             if !e.to_string().contains("No such file") {
@@ -268,7 +570,6 @@ impl DockerMaterializer {
                 );
             }
         };
-
         Ok(())
     }
 
@@ -282,6 +583,19 @@ impl DockerMaterializer {
         drop_handle
     }
 
+    /// Drop the materializer and wait until the docker constructs that went out of scope have been removed.
+    ///
+    /// The dropper only finishes when every handle to it has been dropped, and the materials
+    /// (e.g. the nodes of a [Testnet](crate::testnet::Testnet)) hold on to one, so they must be
+    /// dropped before this is called, otherwise it would wait forever. Whatever is still in scope
+    /// when the process exits is left behind.
+    pub async fn shutdown(mut self) -> anyhow::Result<()> {
+        let drop_handle = self.take_dropper();
+        // Dropping the materializer drops the fresh chute that replaced the current one.
+        drop(self);
+        drop_handle.await.context("the dropper task failed")
+    }
+
     /// Path to a directory based on a resource name.
     fn path<T: AsRef<ResourceName>>(&self, name: T) -> PathBuf {
         let name: &ResourceName = name.as_ref();
@@ -378,18 +692,11 @@ impl DockerMaterializer {
     /// Pick a range for a container. Remember the choice so that we can recreate
     /// this materializer in a test and allocate more if needed without clashes.
     fn port_range(&mut self, node_name: &NodeName) -> anyhow::Result<DockerPortRange> {
-        if let Some(range) = self.state.port_ranges.get(node_name) {
-            return Ok(range.clone());
+        if let Some(range) = self.state.port_range(node_name) {
+            return Ok(range);
         }
-        // Currently the range allocations are not dropped from the materializer,
-        // so the length can be used to derive the next available port. Otherwise
-        // we could loop through to find an unused slot.
-        let node_count = self.state.port_ranges.len() as u32;
-        let from = PORT_RANGE_START + PORT_RANGE_SIZE * node_count;
-        let to = from + PORT_RANGE_SIZE;
-        let range = DockerPortRange { from, to };
-        self.update_state(|s| s.port_ranges.insert(node_name.clone(), range.clone()))?;
-        Ok(range)
+        let range_end = self.port_range_end;
+        self.update_state(|s| s.allocate_port_range(node_name, range_end, host_ports_free))?
     }
 
     fn ipc_dir(&self, testnet_name: &TestnetName) -> PathBuf {
@@ -420,7 +727,7 @@ impl DockerMaterializer {
             self.drop_policy.clone(),
             cli_name,
             user,
-            FENDERMINT_IMAGE,
+            &self.images.fendermint,
             volumes,
             network_name.cloned(),
         );
@@ -455,7 +762,7 @@ impl DockerMaterializer {
             self.drop_policy.clone(),
             cli_name,
             user,
-            FENDERMINT_IMAGE,
+            &self.images.fendermint,
             volumes,
             network_name.cloned(),
         );
@@ -514,10 +821,12 @@ impl DockerMaterializer {
                 id: subnet_id,
                 config: IpcCliSubnetConfig::Fevm(EVMSubnet {
                     provider_http: url,
+                    provider_ws: None,
                     provider_timeout: Some(Duration::from_secs(30)),
                     auth_token: None,
                     registry_addr: submit_config.deployment.registry.into(),
                     gateway_addr: submit_config.deployment.gateway.into(),
+                    checkpoint_submission: None,
                 }),
             })
         })
@@ -612,12 +921,28 @@ impl Materializer<DockerMaterials> for DockerMaterializer {
     async fn fund_from_faucet<'s, 'a>(
         &'s mut self,
         account: &'a DefaultAccount,
+        faucet: Option<&'a Url>,
         reference: Option<ResourceHash>,
     ) -> anyhow::Result<()>
     where
         's: 'a,
     {
-        todo!("use curl or something to trigger the faucet")
+        let root_name = account.account_name().testnet().root();
+
+        if self.has_reference(&root_name, &reference) {
+            return Ok(());
+        }
+
+        match faucet {
+            Some(url) => fund_from_http_faucet(url, account).await,
+            None => match self.faucet {
+                Some(ref faucet) => faucet.fund(account).await,
+                None => Err(anyhow!("no faucet configured")),
+            },
+        }
+        .with_context(|| format!("failed to fund {account} from the faucet"))?;
+
+        self.add_reference(&root_name, &reference)
     }
 
     async fn new_deployment<'s, 'a>(
@@ -629,7 +954,37 @@ impl Materializer<DockerMaterials> for DockerMaterializer {
     where
         's: 'a,
     {
-        todo!("use the deploy scripts to create a new IPC stack on L1")
+        let deployment_file = self.path(subnet_name).join(DEPLOYMENT_JSON_FILE_NAME);
+
+        // Check if we have already deployed the contracts.
+        let deployed = match import_json::<DeployedContracts>(&deployment_file)
+            .context("failed to read deployment")?
+        {
+            Some(deployed) => deployed,
+            None => {
+                let url = urls
+                    .first()
+                    .ok_or_else(|| anyhow!("no URL to deploy the IPC contracts to"))?;
+
+                let contracts_dir = std::env::var("FM_CONTRACTS_DIR")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| PathBuf::from(DEFAULT_CONTRACTS_DIR));
+
+                let deployed = deployment::deploy_ipc_contracts(&contracts_dir, url, deployer)
+                    .await
+                    .context("failed to deploy IPC contracts")?;
+
+                export_json(&deployment_file, &deployed).context("failed to export deployment")?;
+
+                deployed
+            }
+        };
+
+        Ok(DefaultDeployment {
+            name: subnet_name.clone(),
+            gateway: EthAddress::from(deployed.gateway),
+            registry: EthAddress::from(deployed.registry),
+        })
     }
 
     fn existing_deployment(
@@ -690,12 +1045,13 @@ impl Materializer<DockerMaterials> for DockerMaterializer {
                 ipc: Some(IpcParams {
                     gateway: GatewayParams {
                         subnet_id: SubnetID::new_root(chain_id.into()),
-                        // TODO: The gateway constructor doesn't allow 0 bottom-up-checkpoint-period even on the rootnet!
-                        bottom_up_check_period: 1,
+                        // Checkpointing is disabled on the rootnet.
+                        bottom_up_check_period: 0,
                         majority_percentage: 67,
                         active_validators_limit: 100,
                     },
                 }),
+                cetf: None,
             };
             Ok(genesis)
         })
@@ -751,6 +1107,7 @@ impl Materializer<DockerMaterials> for DockerMaterializer {
             node_name,
             node_config,
             port_range,
+            &self.images,
         )
         .await
         .context("failed to create node")
@@ -1012,6 +1369,7 @@ impl Materializer<DockerMaterials> for DockerMaterializer {
             relayer_config.submitter,
             network_name,
             relayer_config.env,
+            &self.images.fendermint,
         )
         .await?;
 
@@ -1061,7 +1419,16 @@ mod tests {
     use std::str::FromStr;
     use std::time::Duration;
 
-    use super::find_subnet_id;
+    use crate::TestnetName;
+
+    use super::{
+        find_subnet_id, host_ports_free, DockerMaterializerState, DockerPortRange, PORT_RANGE_SIZE,
+        PORT_RANGE_START,
+    };
+
+    fn all_free(_: &DockerPortRange) -> bool {
+        true
+    }
 
     #[test]
     fn test_ipc_cli_config_toml_roundtrip() {
@@ -1074,10 +1441,12 @@ mod tests {
             id: SubnetID::new_root(12345),
             config: IpcCliSubnetConfig::Fevm(EVMSubnet {
                 provider_http: url::Url::parse("http://example.net").unwrap(),
+                provider_ws: None,
                 provider_timeout: Some(Duration::from_secs(30)),
                 auth_token: None,
                 registry_addr: ipc::SUBNETREGISTRY_ACTOR_ADDR,
                 gateway_addr: ipc::GATEWAY_ACTOR_ADDR,
+                checkpoint_submission: None,
             }),
         });
 
@@ -1089,6 +1458,140 @@ mod tests {
         assert_eq!(config0, config1);
     }
 
+    #[test]
+    fn test_port_range_allocate_release_reallocate() {
+        let testnet = TestnetName::new("ports");
+        let node = |id: &str| testnet.root().node(id);
+        let range = |i: u32| DockerPortRange {
+            from: PORT_RANGE_START + i * PORT_RANGE_SIZE,
+            to: PORT_RANGE_START + (i + 1) * PORT_RANGE_SIZE,
+        };
+        let end = PORT_RANGE_START + 3 * PORT_RANGE_SIZE;
+
+        let mut state = DockerMaterializerState::default();
+
+        for (i, id) in ["a", "b", "c"].into_iter().enumerate() {
+            let r = state.allocate_port_range(&node(id), end, all_free).unwrap();
+            assert_eq!(r, range(i as u32));
+        }
+
+        // Allocating again returns the existing range.
+        assert_eq!(
+            state
+                .allocate_port_range(&node("b"), end, all_free)
+                .unwrap(),
+            range(1)
+        );
+
+        // All slots are taken.
+        let err = state
+            .allocate_port_range(&node("d"), end, all_free)
+            .unwrap_err();
+        assert!(err.to_string().contains("prune"));
+
+        // Releasing a node frees up its slot for the next one.
+        assert_eq!(state.release_port_range(&node("b")), Some(range(1)));
+        assert_eq!(state.release_port_range(&node("b")), None);
+        assert_eq!(
+            state
+                .allocate_port_range(&node("d"), end, all_free)
+                .unwrap(),
+            range(1)
+        );
+
+        assert_eq!(
+            state.port_range_nodes(&testnet),
+            vec![node("a"), node("d"), node("c")]
+        );
+    }
+
+    #[test]
+    fn test_port_range_skips_ports_in_use() {
+        let testnet = TestnetName::new("ports");
+        let node = |id: &str| testnet.root().node(id);
+        let range = |i: u32| DockerPortRange {
+            from: PORT_RANGE_START + i * PORT_RANGE_SIZE,
+            to: PORT_RANGE_START + (i + 1) * PORT_RANGE_SIZE,
+        };
+        let end = PORT_RANGE_START + 3 * PORT_RANGE_SIZE;
+        // Something else on the host is using the ports of the second slot.
+        let is_free = |r: &DockerPortRange| *r != range(1);
+
+        let mut state = DockerMaterializerState::default();
+
+        assert_eq!(
+            state.allocate_port_range(&node("a"), end, is_free).unwrap(),
+            range(0)
+        );
+        assert_eq!(
+            state.allocate_port_range(&node("b"), end, is_free).unwrap(),
+            range(2)
+        );
+
+        let err = state
+            .allocate_port_range(&node("c"), end, is_free)
+            .unwrap_err();
+        assert!(err.to_string().contains("in use on the host"));
+    }
+
+    #[test]
+    fn test_host_ports_free() {
+        let listener = std::net::TcpListener::bind(("0.0.0.0", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port() as u32;
+        // Put the taken port where the CometBFT RPC would be mapped to.
+        let range = DockerPortRange {
+            from: port - 57,
+            to: port - 57 + PORT_RANGE_SIZE,
+        };
+        assert!(!host_ports_free(&range));
+    }
+
+    #[test]
+    fn test_state_json_roundtrip() {
+        let testnet = TestnetName::new("ports");
+        let mut state0 = DockerMaterializerState::default();
+        for id in ["a", "b"] {
+            state0
+                .allocate_port_range(&testnet.root().node(id), u32::MAX, all_free)
+                .unwrap();
+        }
+
+        let json = serde_json::to_string(&state0).unwrap();
+        let state1: DockerMaterializerState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(state0, state1);
+    }
+
+    #[test]
+    fn test_state_migrate_legacy_port_ranges() {
+        let node = TestnetName::new("ports").root().node("a");
+        let json = format!(
+            r#"{{"port_ranges": {{"{}": {{"from": 30100, "to": 30200}}}}}}"#,
+            node.path_string()
+        );
+        let state: DockerMaterializerState = serde_json::from_str(&json).unwrap();
+        let mut state = state.migrate();
+
+        assert!(state.port_ranges.is_empty());
+        assert_eq!(
+            state.port_range(&node),
+            Some(DockerPortRange {
+                from: 30100,
+                to: 30200
+            })
+        );
+
+        // The gap before the legacy range is filled first.
+        let other = TestnetName::new("ports").root().node("b");
+        assert_eq!(
+            state
+                .allocate_port_range(&other, u32::MAX, all_free)
+                .unwrap()
+                .from,
+            PORT_RANGE_START
+        );
+    }
+
     #[test]
     fn test_parse_subnet_id_from_log() {
         let example = "[2024-03-05T15:10:01Z INFO  ipc_cli::commands::subnet::create] created subnet actor with id: /r314159/f410fu6ua642sypnlukccd3gaizwhonk5kwlpml6r3pa";
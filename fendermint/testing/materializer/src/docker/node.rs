@@ -22,7 +22,7 @@ use super::{
     dropper::{DropChute, DropPolicy},
     network::NetworkName,
     runner::DockerRunner,
-    user_id, DockerMaterials, DockerPortRange, Volumes, COMETBFT_IMAGE, FENDERMINT_IMAGE,
+    user_id, DockerImages, DockerMaterials, DockerPortRange, Volumes,
 };
 use crate::{
     docker::DOCKER_ENTRY_FILE_NAME,
@@ -81,6 +81,7 @@ impl Display for DockerNode {
 }
 
 impl DockerNode {
+    #[allow(clippy::too_many_arguments)]
     pub async fn get_or_create<'a>(
         root: impl AsRef<Path>,
         docker: Docker,
@@ -89,6 +90,7 @@ impl DockerNode {
         node_name: &NodeName,
         node_config: &NodeConfig<'a, DockerMaterials>,
         port_range: DockerPortRange,
+        images: &DockerImages,
     ) -> anyhow::Result<Self> {
         let fendermint_name = container_name(node_name, "fendermint");
         let cometbft_name = container_name(node_name, "cometbft");
@@ -125,6 +127,14 @@ impl DockerNode {
         // Get the current user ID to use with docker containers.
         let user = user_id(&node_dir)?;
 
+        // Different nodes can run different versions, e.g. to rehearse a rolling upgrade.
+        let fendermint_image = node_config
+            .fendermint_image
+            .unwrap_or(images.fendermint.as_str());
+        let cometbft_image = node_config
+            .cometbft_image
+            .unwrap_or(images.cometbft.as_str());
+
         let make_runner = |image, volumes| {
             DockerRunner::new(
                 docker.clone(),
@@ -173,10 +183,10 @@ impl DockerNode {
         // However, at least this way they are tested.
 
         let cometbft_runner =
-            make_runner(COMETBFT_IMAGE, vec![(cometbft_dir.clone(), "/cometbft")]);
+            make_runner(cometbft_image, vec![(cometbft_dir.clone(), "/cometbft")]);
 
         let fendermint_runner = make_runner(
-            FENDERMINT_IMAGE,
+            fendermint_image,
             vec![
                 (keys_dir.clone(), "/fendermint/keys"),
                 (cometbft_dir.clone(), "/cometbft"),
@@ -384,7 +394,7 @@ impl DockerNode {
             Some(c) => c,
             None => {
                 let creator = make_runner(
-                    FENDERMINT_IMAGE,
+                    fendermint_image,
                     volumes(vec![
                         (keys_dir.clone(), "/fendermint/keys"),
                         (fendermint_dir.join("data"), "/fendermint/data"),
@@ -412,7 +422,7 @@ impl DockerNode {
             Some(c) => c,
             None => {
                 let creator = make_runner(
-                    COMETBFT_IMAGE,
+                    cometbft_image,
                     volumes(vec![(cometbft_dir.clone(), "/cometbft")]),
                 );
 
@@ -434,7 +444,7 @@ impl DockerNode {
         let ethapi = match ethapi {
             None if node_config.ethapi => {
                 let creator = make_runner(
-                    FENDERMINT_IMAGE,
+                    fendermint_image,
                     volumes(vec![(ethapi_dir.join("logs"), "/fendermint/logs")]),
                 );
 
@@ -647,17 +657,18 @@ fn parse_fendermint_peer_id(value: impl AsRef<str>) -> anyhow::Result<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::{DockerRunner, COMETBFT_IMAGE};
+    use super::DockerRunner;
     use crate::{
         docker::{
             dropper::{self, DropPolicy},
             node::parse_cometbft_node_id,
+            DockerImages,
         },
         NodeName, TestnetName,
     };
     use bollard::Docker;
 
-    fn make_runner() -> DockerRunner<NodeName> {
+    fn make_runner(image: &str) -> DockerRunner<NodeName> {
         let nn = TestnetName::new("test-network").root().node("test-node");
         let docker = Docker::connect_with_local_defaults().expect("failed to connect to docker");
         let (_drop_handle, drop_chute) = dropper::start(docker.clone());
@@ -669,7 +680,7 @@ mod tests {
             drop_policy,
             nn,
             0,
-            COMETBFT_IMAGE,
+            image,
             Vec::new(),
             None,
         )
@@ -677,7 +688,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_docker_run_output() {
-        let runner = make_runner();
+        let runner = make_runner(&DockerImages::default().cometbft);
         // Based on my manual testing, this will initialise the config and then show the ID:
         // `docker run --rm cometbft/cometbft:v0.37.x show-node-id`
         let logs = runner
@@ -695,7 +706,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_docker_run_error() {
-        let runner = make_runner();
+        let runner = make_runner(&DockerImages::default().cometbft);
 
         let _err = runner
             .run_cmd("show-peer-id")
@@ -703,6 +714,20 @@ mod tests {
             .expect_err("wrong command should fail");
     }
 
+    #[tokio::test]
+    async fn test_create_config_image() {
+        let image = "fendermint:v0.1.0";
+        let runner = make_runner(image);
+
+        let config = runner.create_config(
+            "test-container".to_string(),
+            vec![(30045, 8445)],
+            vec!["fendermint".to_string()],
+        );
+
+        assert_eq!(config.image.as_deref(), Some(image));
+    }
+
     #[test]
     fn test_valid_cometbft_id() {
         assert!(
@@ -0,0 +1,224 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::{collections::HashSet, fmt::Display};
+
+use bollard::secret::{ContainerSummary, Network};
+
+use crate::TestnetName;
+
+/// Container states which mean the container is not going to do anything anymore.
+const ORPHAN_CONTAINER_STATES: [&str; 2] = ["exited", "dead"];
+
+/// A testnet resource considered for pruning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PruneItem {
+    Container { id: String, name: String },
+    Network { id: String, name: String },
+    Directory(TestnetName),
+}
+
+impl Display for PruneItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PruneItem::Container { id, name } => write!(f, "container {name} ({id})"),
+            PruneItem::Network { id, name } => write!(f, "network {name} ({id})"),
+            PruneItem::Directory(name) => write!(f, "directory {}", name.path_string()),
+        }
+    }
+}
+
+/// The outcome of classifying the resources of all testnets.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PrunePlan {
+    /// Resources that are still in use.
+    pub keep: Vec<PruneItem>,
+    /// Orphaned resources, which can be removed.
+    pub remove: Vec<PruneItem>,
+}
+
+/// Decide which testnet resources are orphaned:
+/// * containers which have exited
+/// * networks without any live container of their testnet
+/// * testnet directories without any docker resources, in any state
+///
+/// A testnet which was stopped keeps its directory, so it can be started again,
+/// even though its exited containers and their network are removed.
+///
+/// Containers are associated with testnets through their `testnet` label,
+/// networks through their name, which is the path of the testnet.
+pub fn prune_plan(
+    containers: &[ContainerSummary],
+    networks: &[Network],
+    testnet_dirs: &[TestnetName],
+) -> PrunePlan {
+    let mut plan = PrunePlan::default();
+    // Testnets that have a container or network which is kept.
+    let mut live = HashSet::new();
+    // Testnets that have any container or network, whether kept or not.
+    let mut existing = HashSet::new();
+
+    for c in containers {
+        let Some(id) = c.id.clone() else {
+            continue;
+        };
+        let name = c
+            .names
+            .as_ref()
+            .and_then(|ns| ns.first())
+            .map(|n| n.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| id.clone());
+
+        let testnet = c.labels.as_ref().and_then(|ls| ls.get("testnet")).cloned();
+        existing.extend(testnet.clone());
+
+        let is_orphan = c
+            .state
+            .as_deref()
+            .map(|s| ORPHAN_CONTAINER_STATES.contains(&s))
+            .unwrap_or_default();
+
+        let item = PruneItem::Container { id, name };
+
+        if is_orphan {
+            plan.remove.push(item);
+        } else {
+            live.extend(testnet);
+            plan.keep.push(item);
+        }
+    }
+
+    for n in networks {
+        let (Some(id), Some(name)) = (n.id.clone(), n.name.clone()) else {
+            continue;
+        };
+
+        let is_live = live.contains(&name);
+        existing.insert(name.clone());
+        let item = PruneItem::Network { id, name };
+
+        if is_live {
+            plan.keep.push(item);
+        } else {
+            plan.remove.push(item);
+        }
+    }
+
+    for t in testnet_dirs {
+        let item = PruneItem::Directory(t.clone());
+        if existing.contains(&t.path_string()) {
+            plan.keep.push(item);
+        } else {
+            plan.remove.push(item);
+        }
+    }
+
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bollard::secret::{ContainerSummary, Network};
+
+    use super::{prune_plan, PruneItem};
+    use crate::TestnetName;
+
+    fn container(id: &str, testnet: &TestnetName, state: &str) -> ContainerSummary {
+        ContainerSummary {
+            id: Some(id.to_string()),
+            names: Some(vec![format!("/{id}-name")]),
+            labels: Some(HashMap::from([(
+                "testnet".to_string(),
+                testnet.path_string(),
+            )])),
+            state: Some(state.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn network(id: &str, testnet: &TestnetName) -> Network {
+        Network {
+            id: Some(id.to_string()),
+            name: Some(testnet.path_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_prune_plan() {
+        let healthy = TestnetName::new("healthy");
+        let crashed = TestnetName::new("crashed");
+        let stopped = TestnetName::new("stopped");
+        let leftover = TestnetName::new("leftover");
+
+        let containers = vec![
+            container("c1", &healthy, "running"),
+            container("c2", &healthy, "exited"),
+            container("c3", &crashed, "exited"),
+            container("c4", &crashed, "dead"),
+            container("c5", &stopped, "exited"),
+        ];
+        let networks = vec![
+            network("n1", &healthy),
+            network("n2", &crashed),
+            network("n3", &stopped),
+        ];
+        let dirs = vec![
+            healthy.clone(),
+            crashed.clone(),
+            stopped.clone(),
+            leftover.clone(),
+        ];
+
+        let plan = prune_plan(&containers, &networks, &dirs);
+
+        let c = |id: &str| PruneItem::Container {
+            id: id.to_string(),
+            name: format!("{id}-name"),
+        };
+        let n = |id: &str, t: &TestnetName| PruneItem::Network {
+            id: id.to_string(),
+            name: t.path_string(),
+        };
+
+        assert_eq!(
+            plan.keep,
+            vec![
+                c("c1"),
+                n("n1", &healthy),
+                PruneItem::Directory(healthy.clone()),
+                PruneItem::Directory(crashed.clone()),
+                PruneItem::Directory(stopped.clone()),
+            ]
+        );
+        assert_eq!(
+            plan.remove,
+            vec![
+                c("c2"),
+                c("c3"),
+                c("c4"),
+                c("c5"),
+                n("n2", &crashed),
+                n("n3", &stopped),
+                PruneItem::Directory(leftover)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prune_plan_keeps_starting_containers() {
+        let testnet = TestnetName::new("starting");
+        let containers = vec![
+            container("c1", &testnet, "created"),
+            container("c2", &testnet, "restarting"),
+        ];
+        let networks = vec![network("n1", &testnet)];
+
+        let plan = prune_plan(&containers, &networks, &[testnet]);
+
+        assert_eq!(plan.keep.len(), 4);
+        assert!(plan.remove.is_empty());
+    }
+}
@@ -9,7 +9,7 @@ use bollard::Docker;
 use crate::{
     docker::{
         runner::{split_cmd, DockerRunner},
-        user_id, FENDERMINT_IMAGE,
+        user_id,
     },
     manifest::EnvMap,
     materials::{DefaultAccount, DefaultSubnet},
@@ -45,6 +45,7 @@ impl DockerRelayer {
         submitter: &DefaultAccount,
         network_name: Option<NetworkName>,
         env: &EnvMap,
+        image: &str,
     ) -> anyhow::Result<Self> {
         let container_name = container_name(relayer_name);
 
@@ -77,7 +78,7 @@ impl DockerRelayer {
             drop_policy.clone(),
             relayer_name.clone(),
             user,
-            FENDERMINT_IMAGE,
+            image,
             volumes,
             network_name,
         )
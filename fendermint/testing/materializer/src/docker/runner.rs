@@ -189,16 +189,15 @@ where
         Ok(out)
     }
 
-    /// Create a container to be started later.
-    pub async fn create(
+    /// The parameters of a container to be created.
+    pub fn create_config(
         &self,
         name: String,
-        // Host <-> Container port mappings
         ports: Vec<(u32, u32)>,
         entrypoint: Vec<String>,
-    ) -> anyhow::Result<DockerContainer> {
-        let config = Config {
-            hostname: Some(name.clone()),
+    ) -> Config<String> {
+        Config {
+            hostname: Some(name),
             image: Some(self.image.clone()),
             user: Some(self.user.to_string()),
             entrypoint: Some(entrypoint),
@@ -232,7 +231,18 @@ where
                 ..Default::default()
             }),
             ..Default::default()
-        };
+        }
+    }
+
+    /// Create a container to be started later.
+    pub async fn create(
+        &self,
+        name: String,
+        // Host <-> Container port mappings
+        ports: Vec<(u32, u32)>,
+        entrypoint: Vec<String>,
+    ) -> anyhow::Result<DockerContainer> {
+        let config = self.create_config(name.clone(), ports, entrypoint);
 
         let id = self
             .docker
@@ -1,6 +1,7 @@
 use ethers::providers::{Http, Provider};
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
+use anyhow::{anyhow, bail};
 use multihash::MultihashDigest;
 use serde::{Deserialize, Serialize};
 use std::fmt::Formatter;
@@ -25,13 +26,27 @@ mod arb;
 #[derive(Clone, Serialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ResourceId(String);
 
+impl ResourceId {
+    /// Create an ID from user input, sanitising URL-unfriendly characters.
+    ///
+    /// Fails if the ID is empty or consists only of whitespace.
+    pub fn new<T: Into<String>>(id: T) -> anyhow::Result<Self> {
+        let id = id.into();
+        if id.trim().is_empty() {
+            bail!("resource ID cannot be empty: '{id}'");
+        }
+        Ok(Self::from(id))
+    }
+}
+
 /// Implementing a deserializer which has the logic to sanitise URL-unfriendly characters.
 impl<'de> Deserialize<'de> for ResourceId {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        String::deserialize(deserializer).map(Self::from)
+        let id = String::deserialize(deserializer)?;
+        Self::new(id).map_err(serde::de::Error::custom)
     }
 }
 
@@ -56,7 +71,15 @@ impl From<&str> for ResourceId {
 /// Replace the path separator with a different character when reading strings.
 impl From<String> for ResourceId {
     fn from(value: String) -> Self {
-        Self(value.replace('/', "_"))
+        let id = value.replace('/', "_");
+        if id != value {
+            tracing::warn!(
+                from = value,
+                to = id,
+                "replaced path separators in resource ID"
+            );
+        }
+        Self(id)
     }
 }
 
@@ -269,36 +292,42 @@ impl SubnetName {
                 .is_some()
     }
 
-    pub fn parent(&self) -> Option<SubnetName> {
+    /// The parent of the subnet, or `None` if this is the root.
+    ///
+    /// Fails if the name doesn't have the structure of a subnet, ie. `.../subnets/<id>`.
+    pub fn parent(&self) -> anyhow::Result<Option<SubnetName>> {
         if self.is_root() {
-            None
-        } else {
-            let path = self
-                .path()
-                .parent()
-                .and_then(|p| p.parent())
-                .expect("invalid subnet path");
-
-            Some(Self(ResourceName(path.into())))
+            return Ok(None);
         }
+        let path = self
+            .path()
+            .parent()
+            .filter(|p| p.ends_with("subnets"))
+            .and_then(|p| p.parent())
+            .ok_or_else(|| anyhow!("invalid subnet path: {self}"))?;
+
+        Ok(Some(Self(ResourceName(path.into()))))
     }
 
     /// All the subnet names from the root to the parent of the subnet,
     /// excluding the subnet itself.
-    pub fn ancestors(&self) -> Vec<SubnetName> {
+    pub fn ancestors(&self) -> anyhow::Result<Vec<SubnetName>> {
         let mut ss = Vec::new();
-        let mut p = self.parent();
+        let mut p = self.parent()?;
         while let Some(s) = p {
-            p = s.parent();
+            p = s.parent()?;
             ss.push(s);
         }
         ss.reverse();
-        ss
+        Ok(ss)
     }
 
     /// parent->child hop pairs from the root to the current subnet.
-    pub fn ancestor_hops(&self, include_self: bool) -> Vec<(SubnetName, SubnetName)> {
-        let ss0 = self.ancestors();
+    pub fn ancestor_hops(
+        &self,
+        include_self: bool,
+    ) -> anyhow::Result<Vec<(SubnetName, SubnetName)>> {
+        let ss0 = self.ancestors()?;
 
         let ss1 = ss0
             .iter()
@@ -313,7 +342,7 @@ impl SubnetName {
             hops.pop();
         }
 
-        hops
+        Ok(hops)
     }
 
     /// Check that the subnet contains a certain resource name, ie. it's a prefix of it.
@@ -367,9 +396,9 @@ pub trait HasCometBftApi {
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use std::{collections::BTreeMap, path::PathBuf};
 
-    use crate::{TestnetName, TestnetResource};
+    use crate::{ResourceId, ResourceName, SubnetName, TestnetName, TestnetResource};
 
     #[test]
     fn test_path_join() {
@@ -385,8 +414,8 @@ mod tests {
         let tn = TestnetName::new("example");
         let rn = tn.root();
         let sn = rn.subnet("foo");
-        assert_eq!(rn.parent(), None, "root shouldn't have a parent");
-        assert_eq!(sn.parent(), Some(rn), "parent should be the root");
+        assert_eq!(rn.parent().unwrap(), None, "root shouldn't have a parent");
+        assert_eq!(sn.parent().unwrap(), Some(rn), "parent should be the root");
         assert_eq!(sn.testnet(), tn, "testnet is the prefix");
     }
 
@@ -394,7 +423,25 @@ mod tests {
     fn test_subnet_ancestors() {
         let tn = TestnetName::new("example");
         let sn = tn.root().subnet("foo").subnet("bar");
-        assert_eq!(sn.ancestors(), vec![tn.root(), tn.root().subnet("foo")]);
+        assert_eq!(
+            sn.ancestors().unwrap(),
+            vec![tn.root(), tn.root().subnet("foo")]
+        );
+    }
+
+    #[test]
+    fn test_subnet_malformed() {
+        let sn = SubnetName(ResourceName::from("testnets/example/foo"));
+        assert!(sn.parent().is_err(), "not under a subnets directory");
+
+        // The parent looks fine, but the grandparent is not a subnet.
+        let sn = SubnetName(ResourceName::from("subnets/foo/subnets/bar"));
+        assert_eq!(
+            sn.parent().unwrap(),
+            Some(SubnetName(ResourceName::from("subnets/foo")))
+        );
+        assert!(sn.ancestors().is_err());
+        assert!(sn.ancestor_hops(true).is_err());
     }
 
     #[test]
@@ -404,8 +451,8 @@ mod tests {
         let foo = rn.subnet("foo");
         let bar = foo.subnet("bar");
 
-        let hops0 = bar.ancestor_hops(false);
-        let hops1 = bar.ancestor_hops(true);
+        let hops0 = bar.ancestor_hops(false).unwrap();
+        let hops1 = bar.ancestor_hops(true).unwrap();
         let hops = [(rn, foo.clone()), (foo, bar)];
 
         assert_eq!(hops0[..], hops[..1]);
@@ -428,4 +475,26 @@ mod tests {
         assert_eq!(format!("{tn}"), "Testnet('testnets/display-test')");
         assert_eq!(format!("{tn:?}"), "Testnet('testnets/display-test')");
     }
+
+    #[test]
+    fn test_resource_id_new() {
+        assert!(ResourceId::new("").is_err());
+        assert!(ResourceId::new(" \t").is_err());
+        assert_eq!(ResourceId::new("foo/bar").unwrap().as_ref(), "foo_bar");
+        assert_eq!(ResourceId::new("alice").unwrap().as_ref(), "alice");
+    }
+
+    #[test]
+    fn test_resource_id_deserialize() {
+        let ids: BTreeMap<String, ResourceId> =
+            serde_yaml::from_str("normal: alice\nslashed: foo/bar").unwrap();
+        assert_eq!(ids["normal"].as_ref(), "alice");
+        assert_eq!(ids["slashed"].as_ref(), "foo_bar");
+
+        let err = serde_yaml::from_str::<BTreeMap<String, ResourceId>>("submitter: ''")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("submitter"), "should name the field: {err}");
+        assert!(err.contains("cannot be empty"));
+    }
 }
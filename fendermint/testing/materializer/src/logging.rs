@@ -53,13 +53,16 @@ where
     async fn fund_from_faucet<'s, 'a>(
         &'s mut self,
         account: &'a M::Account,
+        faucet: Option<&'a Url>,
         reference: Option<ResourceHash>,
     ) -> anyhow::Result<()>
     where
         's: 'a,
     {
         tracing::info!(%account, ctx=self.ctx, "fund_from_faucet");
-        self.inner.fund_from_faucet(account, reference).await
+        self.inner
+            .fund_from_faucet(account, faucet, reference)
+            .await
     }
 
     async fn new_deployment<'s, 'a>(
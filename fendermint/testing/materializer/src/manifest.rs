@@ -119,6 +119,11 @@ pub enum Rootnet {
         deployment: IpcDeployment,
         /// Addresses of JSON-RPC endpoints on the external L1.
         urls: Vec<Url>,
+        /// HTTP endpoint of a faucet which sends funds to the accounts on request.
+        ///
+        /// If it's missing, the materializer has to be configured with an account to fund them from.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        faucet: Option<Url>,
     },
 
     /// Provision a new chain to run the L1.
@@ -196,6 +201,13 @@ pub struct Node {
     /// will tell us that all subnet nodes need a parent.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub parent_node: Option<ParentNode>,
+    /// Docker image to run Fendermint and the Ethereum API with, instead of the default one,
+    /// e.g. to run different versions on different nodes while rehearsing an upgrade.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fendermint_image: Option<String>,
+    /// Docker image to run CometBFT with, instead of the default one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cometbft_image: Option<String>,
 }
 
 /// The mode in which CometBFT is running.
@@ -240,7 +252,16 @@ pub struct CheckpointConfig {
 mod tests {
     use quickcheck_macros::quickcheck;
 
-    use super::Manifest;
+    use super::{Manifest, Node};
+
+    #[test]
+    fn parse_node_image_overrides() {
+        let yaml = "mode:\n  type: Full\nethapi: false\nseed_nodes: []\nfendermint_image: fendermint:v0.1.0\n";
+        let node: Node = serde_yaml::from_str(yaml).expect("failed to parse node");
+
+        assert_eq!(node.fendermint_image.as_deref(), Some("fendermint:v0.1.0"));
+        assert_eq!(node.cometbft_image, None);
+    }
 
     #[quickcheck]
     fn manifest_json(value0: Manifest) {
@@ -52,9 +52,13 @@ pub trait Materializer<M: Materials> {
     fn create_account(&mut self, account_name: &AccountName) -> anyhow::Result<M::Account>;
 
     /// Fund an account on the rootnet from the faucet.
+    ///
+    /// The faucet is either an HTTP endpoint given in the manifest,
+    /// or something the materializer was configured with.
     async fn fund_from_faucet<'s, 'a>(
         &'s mut self,
         account: &'a M::Account,
+        faucet: Option<&'a Url>,
         reference: Option<ResourceHash>,
     ) -> anyhow::Result<()>
     where
@@ -222,6 +226,10 @@ pub struct NodeConfig<'a, M: Materials> {
     pub env: &'a EnvMap,
     /// Number of nodes to be expected in the subnet, including this node, or 0 if unknown.
     pub peer_count: usize,
+    /// Override the default Fendermint image.
+    pub fendermint_image: Option<&'a str>,
+    /// Override the default CometBFT image.
+    pub cometbft_image: Option<&'a str>,
 }
 
 /// Options regarding relayer configuration
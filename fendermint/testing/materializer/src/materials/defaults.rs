@@ -103,6 +103,10 @@ impl Display for DefaultAccount {
 }
 
 impl DefaultAccount {
+    pub fn account_name(&self) -> &AccountName {
+        &self.name
+    }
+
     pub fn account_id(&self) -> AccountId {
         self.name.0.id()
     }
@@ -285,7 +285,7 @@ where
         let network = self.network();
         let node_name = subnet_name.node(node_id);
 
-        let parent_node = match (subnet_name.parent(), &node.parent_node) {
+        let parent_node = match (subnet_name.parent()?, &node.parent_node) {
             (Some(ps), Some(ParentNode::Internal(id))) => {
                 let tc = TargetConfig::<M>::Internal(
                     self.node(&ps.node(id))
@@ -333,6 +333,8 @@ where
             ethapi: node.ethapi,
             env,
             peer_count,
+            fendermint_image: node.fendermint_image.as_deref(),
+            cometbft_image: node.cometbft_image.as_deref(),
         };
 
         let node = m
@@ -384,11 +386,12 @@ where
                 chain_id,
                 deployment,
                 urls,
+                faucet,
             } => {
                 // Establish balances.
                 for (id, a) in self.accounts.iter() {
                     let reference = ResourceHash::digest(format!("funding {id} from faucet"));
-                    m.fund_from_faucet(a, Some(reference))
+                    m.fund_from_faucet(a, faucet.as_ref(), Some(reference))
                         .await
                         .context("faucet failed")?;
                 }
@@ -483,7 +486,7 @@ where
             let created_subnet = self.subnet(&subnet_name)?;
 
             // Fund validator and balances collateral all the way from the root down to the parent.
-            for (fund_source, fund_target) in subnet_name.ancestor_hops(false) {
+            for (fund_source, fund_target) in subnet_name.ancestor_hops(false)? {
                 // Where can we send the subnet request.
                 let fund_submit_config = self.submit_config(&fund_source)?;
 
@@ -603,7 +606,7 @@ where
                     .node(&subnet_name.node(&relayer.follow_node))
                     .context("invalid follow node")?;
 
-                let submit_node = match (subnet_name.parent(), &relayer.submit_node) {
+                let submit_node = match (subnet_name.parent()?, &relayer.submit_node) {
                     (Some(p), ParentNode::Internal(s)) => TargetConfig::Internal(self.node(&p.node(s)).context("invalid submit node")?),
                     (Some(p), ParentNode::External(url)) if p.is_root() => TargetConfig::External(url.clone()),
                     (Some(_), ParentNode::External(_))  => bail!(
@@ -6,6 +6,8 @@ use either::Either;
 use ethers::types::H160;
 use fendermint_vm_genesis::Collateral;
 use fvm_shared::{chainid::ChainID, econ::TokenAmount};
+use lazy_static::lazy_static;
+use regex::Regex;
 use std::{
     collections::{BTreeMap, HashSet},
     fmt::Debug,
@@ -24,6 +26,14 @@ use crate::{
 
 const DEFAULT_FAUCET_FIL: u64 = 100;
 
+lazy_static! {
+    /// A simplified form of `[registry[:port]/]name[:tag][@digest]`.
+    static ref IMAGE_RE: Regex = Regex::new(
+        r"^[a-z0-9]+([._-][a-z0-9]+)*(:[0-9]+)?(/[a-z0-9]+([._-]+[a-z0-9]+)*)*(:[A-Za-z0-9_][A-Za-z0-9_.-]{0,127})?(@sha256:[a-f0-9]{64})?$"
+    )
+    .expect("image regex parses");
+}
+
 /// Do simple sanity checks on the manifest, e.g.:
 /// * we are not over allocating the balances
 /// * relayers have balances on the parent to submit transactions
@@ -130,7 +140,7 @@ impl ValidatingMaterializer {
         credit_child: bool,
     ) -> anyhow::Result<()> {
         let parent = subnet
-            .parent()
+            .parent()?
             .ok_or_else(|| anyhow!("{subnet} must have a parent to fund from"))?;
 
         self.ensure_subnet_exists(&parent)?;
@@ -175,6 +185,7 @@ impl Materializer<ValidationMaterials> for ValidatingMaterializer {
     async fn fund_from_faucet<'s, 'a>(
         &'s mut self,
         account: &'a VAccount,
+        _faucet: Option<&'a Url>,
         reference: Option<ResourceHash>,
     ) -> anyhow::Result<()>
     where
@@ -260,12 +271,18 @@ impl Materializer<ValidationMaterials> for ValidatingMaterializer {
     async fn create_node<'s, 'a>(
         &'s mut self,
         node_name: &NodeName,
-        _node_config: &NodeConfig<'a, ValidationMaterials>,
+        node_config: &NodeConfig<'a, ValidationMaterials>,
     ) -> anyhow::Result<VNode>
     where
         's: 'a,
     {
         self.ensure_contains(node_name)?;
+        for image in [node_config.fendermint_image, node_config.cometbft_image]
+            .into_iter()
+            .flatten()
+        {
+            validate_image(image)?;
+        }
         Ok(node_name.clone())
     }
 
@@ -309,7 +326,7 @@ impl Materializer<ValidationMaterials> for ValidatingMaterializer {
     {
         // Debit parent balance; Credit child balance
         self.fund_from_parent(subnet, account, amount, true)?;
-        self.ensure_unique(&subnet.parent().unwrap(), reference)?;
+        self.ensure_unique(&subnet.parent()?.unwrap(), reference)?;
         Ok(())
     }
 
@@ -329,7 +346,7 @@ impl Materializer<ValidationMaterials> for ValidatingMaterializer {
         self.fund_from_parent(subnet, account, collateral.0, false)?;
         // Debit parent balance; Credit child balance
         self.fund_from_parent(subnet, account, balance.0, true)?;
-        self.ensure_unique(&subnet.parent().unwrap(), reference)?;
+        self.ensure_unique(&subnet.parent()?.unwrap(), reference)?;
         Ok(())
     }
 
@@ -362,11 +379,21 @@ impl Materializer<ValidationMaterials> for ValidatingMaterializer {
     }
 }
 
+/// Reject image references docker would not be able to pull.
+fn validate_image(image: &str) -> anyhow::Result<()> {
+    if !IMAGE_RE.is_match(image) {
+        bail!("malformed docker image reference: {image:?}");
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 
     use crate::{manifest::Manifest, validation::validate_manifest, TestnetId, TestnetName};
 
+    use super::validate_image;
+
     // Unfortunately doesn't seem to work with quickcheck_async
     // /// Run the tests with `RUST_LOG=info` to see the logs, for example:
     // ///
@@ -383,4 +410,25 @@ mod tests {
         let name = TestnetName::new(id);
         validate_manifest(&name, &manifest).await
     }
+
+    #[test]
+    fn test_validate_image() {
+        for image in [
+            "fendermint:latest",
+            "cometbft/cometbft:v0.38.x",
+            "ghcr.io/consensus-shipyard/fendermint:sha-1a2b3c4",
+            "localhost:5000/fendermint",
+        ] {
+            assert!(validate_image(image).is_ok(), "{image} should be valid");
+        }
+        for image in [
+            "",
+            "Fendermint:latest",
+            "fendermint:",
+            "fendermint latest",
+            "/fendermint",
+        ] {
+            assert!(validate_image(image).is_err(), "{image} should be invalid");
+        }
+    }
 }
@@ -17,7 +17,7 @@ use std::{
 use anyhow::{anyhow, Context};
 use ethers::providers::Middleware;
 use fendermint_materializer::{
-    docker::{DockerMaterializer, DockerMaterials},
+    docker::{DockerImages, DockerMaterializer, DockerMaterials},
     manifest::Manifest,
     testnet::Testnet,
     HasCometBftApi, HasEthApi, TestnetName,
@@ -35,6 +35,18 @@ lazy_static! {
     static ref PRINT_LOGS_ON_ERROR: bool = *CI_PROFILE;
 }
 
+/// The images to test with; CI can pin them with the same env vars as the CLI.
+fn docker_images() -> DockerImages {
+    let mut images = DockerImages::default();
+    if let Ok(image) = std::env::var("FM_MATERIALIZER__FENDERMINT_IMAGE") {
+        images.fendermint = image;
+    }
+    if let Ok(image) = std::env::var("FM_MATERIALIZER__COMETBFT_IMAGE") {
+        images.cometbft = image;
+    }
+    images
+}
+
 /// Want to keep the testnet artifacts in the `tests/testnets` directory.
 fn tests_dir() -> PathBuf {
     let dir = current_dir().unwrap();
@@ -90,7 +102,8 @@ where
 
     // NOTE: Add `with_policy(DropPolicy::PERSISTENT)` if you want containers to stick around for inspection,
     // but logs and env vars should be available on disk even if the testnet is torn down at the end.
-    let mut materializer = DockerMaterializer::new(&test_data_dir(), 0)?;
+    let mut materializer =
+        DockerMaterializer::new(&test_data_dir(), 0)?.with_images(docker_images());
 
     // make sure we start with clean slate by removing any previous files
     materializer
@@ -128,7 +141,7 @@ where
         }
     }
 
-    // Tear down the testnet.
+    // Tear down the testnet; it has to be dropped before the materializer can shut down.
     drop(testnet);
 
     // Allow some time for containers to be dropped.
@@ -136,8 +149,11 @@ where
     // otherwise the system shuts down too quick, but
     // at least we can inspect the containers.
     // If they don't all get dropped, `docker system prune` helps.
-    let drop_handle = materializer.take_dropper();
-    let _ = tokio::time::timeout(*TEARDOWN_TIMEOUT, drop_handle).await;
+    match tokio::time::timeout(*TEARDOWN_TIMEOUT, materializer.shutdown()).await {
+        Ok(Err(e)) => eprintln!("failed to shut down the materializer: {e:#}"),
+        Err(_) => eprintln!("timed out waiting for the testnet to be torn down"),
+        Ok(Ok(())) => {}
+    }
 
     res
 }
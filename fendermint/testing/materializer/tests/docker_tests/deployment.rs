@@ -0,0 +1,71 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use anyhow::{bail, Context};
+use ethers::{providers::Middleware, types::H160};
+use fendermint_materializer::{materializer::Materializer, HasEthApi};
+use futures::FutureExt;
+
+use crate::with_testnet;
+
+const MANIFEST: &str = "deployment.yaml";
+
+/// Test that the IPC stack can be deployed on a running node,
+/// and that deploying it again reuses the existing contracts.
+#[serial_test::serial]
+#[tokio::test]
+async fn test_new_deployment() {
+    with_testnet(
+        MANIFEST,
+        |_| {},
+        |_, materializer, testnet| {
+            let test = async {
+                let bob = testnet.account("bob")?;
+
+                let pangea = testnet.node(&testnet.root().node("pangea"))?;
+                let url = pangea
+                    .ethapi_http_endpoint()
+                    .expect("ethapi should be enabled");
+
+                // Deploy onto the same rootnet under a different name than the built-in deployment.
+                let subnet_name = testnet.root().subnet("deployment");
+
+                let deployment = materializer
+                    .new_deployment(&subnet_name, bob, vec![url.clone()])
+                    .await
+                    .context("failed to deploy IPC contracts")?;
+
+                let provider = pangea
+                    .ethapi_http_provider()?
+                    .expect("ethapi should be enabled");
+
+                let gateway: H160 = deployment.gateway.into();
+                let code = provider
+                    .get_code(gateway, None)
+                    .await
+                    .context("failed to get gateway code")?;
+
+                if code.is_empty() {
+                    bail!("the gateway has no code");
+                }
+
+                let redeployment = materializer
+                    .new_deployment(&subnet_name, bob, vec![url])
+                    .await
+                    .context("failed to redeploy IPC contracts")?;
+
+                if redeployment.gateway != deployment.gateway
+                    || redeployment.registry != deployment.registry
+                {
+                    bail!("the redeployment did not reuse the existing contracts");
+                }
+
+                Ok(())
+            };
+
+            test.boxed_local()
+        },
+    )
+    .await
+    .unwrap()
+}
@@ -0,0 +1,73 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use anyhow::{bail, Context};
+use ethers::{providers::Middleware, types::H160};
+use fendermint_materializer::{
+    docker::DockerFaucet, materializer::Materializer, HasEthApi, ResourceHash,
+};
+use fendermint_vm_message::conv::from_fvm::to_eth_tokens;
+use futures::FutureExt;
+use fvm_shared::econ::TokenAmount;
+
+use crate::with_testnet;
+
+const MANIFEST: &str = "faucet.yaml";
+
+/// Test that an account can be funded from a rich account of the rootnet,
+/// and that funding it again with the same reference is a no-op.
+#[serial_test::serial]
+#[tokio::test]
+async fn test_fund_from_faucet() {
+    with_testnet(
+        MANIFEST,
+        |_| {},
+        |_, materializer, testnet| {
+            let test = async {
+                let bob = testnet.account("bob")?;
+                let dave = testnet.account("dave")?;
+
+                let pangea = testnet.node(&testnet.root().node("pangea"))?;
+                let url = pangea
+                    .ethapi_http_endpoint()
+                    .expect("ethapi should be enabled");
+
+                let amount = TokenAmount::from_whole(100);
+
+                materializer.set_faucet(Some(DockerFaucet {
+                    url,
+                    secret_key: bob.secret_key().clone(),
+                    amount: amount.clone(),
+                }));
+
+                let reference = ResourceHash::digest("funding dave from faucet");
+                for _ in 0..2 {
+                    materializer
+                        .fund_from_faucet(dave, None, Some(reference.clone()))
+                        .await
+                        .context("failed to fund from faucet")?;
+                }
+
+                let provider = pangea
+                    .ethapi_http_provider()?
+                    .expect("ethapi should be enabled");
+
+                let to: H160 = dave.eth_addr().into();
+                let balance = provider
+                    .get_balance(to, None)
+                    .await
+                    .context("failed to get balance")?;
+
+                if balance != to_eth_tokens(&amount)? {
+                    bail!("unexpected balance after funding from faucet: {balance}");
+                }
+
+                Ok(())
+            };
+
+            test.boxed_local()
+        },
+    )
+    .await
+    .unwrap()
+}
@@ -5,6 +5,8 @@
 //! sharing their materializer state.
 
 // Tests using the manifest bearing their name.
+pub mod deployment;
+pub mod faucet;
 pub mod layer2;
 pub mod root_only;
 pub mod standalone;
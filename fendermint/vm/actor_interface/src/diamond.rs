@@ -5,9 +5,14 @@
 // See https://medium.com/@MarqyMarq/how-to-implement-the-diamond-standard-69e87dae44e6
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 
+use anyhow::{anyhow, Context};
 use ethers::abi::Abi;
+use ethers::core::types as et;
+use fendermint_eth_hardhat::{ContractSourceAndName, Hardhat, FQN};
 use fvm_shared::ActorID;
+use ipc_actors_abis::i_diamond::FacetCut;
 
 #[derive(Clone, Debug)]
 pub struct EthFacet {
@@ -28,3 +33,75 @@ pub struct EthContract {
 }
 
 pub type EthContractMap = HashMap<&'static str, EthContract>;
+
+/// The Solidity source of a top level contract or facet, which are named after their file.
+pub fn contract_src(name: &str) -> PathBuf {
+    PathBuf::from(format!("{name}.sol"))
+}
+
+/// Collect the libraries and facets the top level contracts depend on, in the topological
+/// order they have to be deployed in.
+///
+/// The top level contracts themselves are left out, as they have constructors.
+pub fn eth_libraries(
+    hardhat: &Hardhat,
+    top_contracts: &EthContractMap,
+) -> anyhow::Result<Vec<ContractSourceAndName>> {
+    let mut root_contracts = Vec::new();
+    root_contracts.extend(top_contracts.keys());
+    root_contracts.extend(
+        top_contracts
+            .values()
+            .flat_map(|c| c.facets.iter().map(|f| f.name)),
+    );
+
+    let mut libs = hardhat
+        .dependencies(
+            &root_contracts
+                .iter()
+                .map(|n| (contract_src(n), *n))
+                .collect::<Vec<_>>(),
+        )
+        .context("failed to collect EVM contract dependencies")?;
+
+    // Only keep library dependencies, not contracts with constructors.
+    libs.retain(|(_, d)| !top_contracts.contains_key(d.as_str()));
+
+    Ok(libs)
+}
+
+/// Collect Facet Cuts for the diamond pattern, where the facet address comes from already deployed library facets.
+pub fn facet_cuts(
+    hardhat: &Hardhat,
+    contract: &EthContract,
+    lib_addrs: &HashMap<FQN, et::Address>,
+) -> anyhow::Result<Vec<FacetCut>> {
+    let mut facet_cuts = Vec::new();
+
+    for facet in contract.facets.iter() {
+        let facet_name = facet.name;
+        let facet_src = contract_src(facet_name);
+        let facet_fqn = hardhat.fqn(&facet_src, facet_name);
+
+        let facet_addr = lib_addrs
+            .get(&facet_fqn)
+            .ok_or_else(|| anyhow!("facet {facet_name} has not been deployed"))?;
+
+        let method_sigs = facet
+            .abi
+            .functions()
+            .filter(|f| f.signature() != "init(bytes)")
+            .map(|f| f.short_signature())
+            .collect();
+
+        let facet_cut = FacetCut {
+            facet_address: *facet_addr,
+            action: 0, // Add
+            function_selectors: method_sigs,
+        };
+
+        facet_cuts.push(facet_cut);
+    }
+
+    Ok(facet_cuts)
+}
@@ -449,8 +449,10 @@ pub mod gateway {
 }
 
 pub mod registry {
+    use anyhow::bail;
     use ethers::contract::{EthAbiCodec, EthAbiType};
     use ethers::core::types::Address;
+    use ipc_actors_abis::i_diamond::FacetCut;
 
     type FunctionSelector = [u8; 4];
 
@@ -480,6 +482,100 @@ pub mod registry {
         pub subnet_actor_ownership_selectors: Vec<FunctionSelector>,
         pub creation_privileges: u8, // 0 = Unrestricted, 1 = Owner.
     }
+
+    impl ConstructorParameters {
+        /// Number of subnet actor facets at the front of the registry facets,
+        /// which the registry uses for the subnets it creates.
+        const SUBNET_ACTOR_FACETS: usize = 8;
+
+        /// Split the facet cuts of the registry, in the order they are declared in
+        /// [IPC_CONTRACTS](super::IPC_CONTRACTS), into the subnet actor facets, which
+        /// are passed as parameters, and the facets of the registry itself.
+        ///
+        /// Returns the arguments of the registry constructor.
+        pub fn new(
+            gateway: Address,
+            mut facets: Vec<FacetCut>,
+        ) -> anyhow::Result<(Vec<FacetCut>, Self)> {
+            if facets.len() < Self::SUBNET_ACTOR_FACETS {
+                bail!(
+                    "expected at least {} registry facets, got {}",
+                    Self::SUBNET_ACTOR_FACETS,
+                    facets.len()
+                );
+            }
+            let registry_facets = facets.split_off(Self::SUBNET_ACTOR_FACETS);
+
+            let [
+                getter_facet,
+                manager_facet,
+                rewarder_facet,
+                checkpointer_facet,
+                pauser_facet,
+                diamond_loupe_facet,
+                diamond_cut_facet,
+                ownership_facet,
+            ]: [FacetCut; Self::SUBNET_ACTOR_FACETS] = facets.try_into().expect("length checked");
+
+            let params = Self {
+                gateway,
+                getter_facet: getter_facet.facet_address,
+                manager_facet: manager_facet.facet_address,
+                rewarder_facet: rewarder_facet.facet_address,
+                pauser_facet: pauser_facet.facet_address,
+                checkpointer_facet: checkpointer_facet.facet_address,
+                diamond_cut_facet: diamond_cut_facet.facet_address,
+                diamond_loupe_facet: diamond_loupe_facet.facet_address,
+                ownership_facet: ownership_facet.facet_address,
+                subnet_getter_selectors: getter_facet.function_selectors,
+                subnet_manager_selectors: manager_facet.function_selectors,
+                subnet_rewarder_selectors: rewarder_facet.function_selectors,
+                subnet_checkpointer_selectors: checkpointer_facet.function_selectors,
+                subnet_pauser_selectors: pauser_facet.function_selectors,
+                subnet_actor_diamond_cut_selectors: diamond_cut_facet.function_selectors,
+                subnet_actor_diamond_loupe_selectors: diamond_loupe_facet.function_selectors,
+                subnet_actor_ownership_selectors: ownership_facet.function_selectors,
+                creation_privileges: 0,
+            };
+
+            Ok((registry_facets, params))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use ethers::core::types::Address;
+        use ipc_actors_abis::i_diamond::FacetCut;
+
+        use super::ConstructorParameters;
+
+        fn facet_cuts(n: u8) -> Vec<FacetCut> {
+            (0..n)
+                .map(|i| FacetCut {
+                    facet_address: Address::repeat_byte(i),
+                    action: 0,
+                    function_selectors: vec![[i; 4]],
+                })
+                .collect()
+        }
+
+        #[test]
+        fn test_split_registry_facets() {
+            let gateway = Address::repeat_byte(0xff);
+            let (facets, params) = ConstructorParameters::new(gateway, facet_cuts(10)).unwrap();
+
+            assert_eq!(params.gateway, gateway);
+            assert_eq!(params.getter_facet, Address::repeat_byte(0));
+            assert_eq!(params.ownership_facet, Address::repeat_byte(7));
+            assert_eq!(params.subnet_pauser_selectors, vec![[4; 4]]);
+            assert_eq!(
+                facets.iter().map(|f| f.facet_address).collect::<Vec<_>>(),
+                vec![Address::repeat_byte(8), Address::repeat_byte(9)]
+            );
+
+            assert!(ConstructorParameters::new(gateway, facet_cuts(7)).is_err());
+        }
+    }
 }
 
 pub mod subnet {
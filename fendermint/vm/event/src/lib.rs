@@ -35,4 +35,20 @@ pub struct NewBottomUpCheckpoint<'a> {
 pub struct ParentFinalityMissingQuorum<'a> {
     pub block_height: BlockHeight,
     pub block_hash: BlockHashHex<'a>,
+    /// Number of validators whose vote counts towards this block.
+    pub votes_seen: usize,
+    /// Current power of the validators who voted.
+    pub power_voted: u64,
+    /// Power needed to reach the quorum.
+    pub quorum_threshold: u64,
+}
+
+/// A message was left out of the block this node is proposing.
+#[derive(Debug, Default)]
+pub struct ProposalMessageDropped<'a> {
+    /// Hex encoded hash of the transaction, as CometBFT calculates it.
+    pub tx_hash: &'a str,
+    /// Size of the transaction in bytes.
+    pub size: usize,
+    pub reason: &'a str,
 }
@@ -1,8 +1,8 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 use crate::{
-    ipc, Account, Actor, ActorMeta, Collateral, Genesis, Multisig, PermissionMode, Power,
-    SignerAddr, Validator, ValidatorKey,
+    ipc, Account, Actor, ActorMeta, CetfParams, Collateral, Genesis, Multisig, PermissionMode,
+    Power, SignerAddr, Validator, ValidatorKey,
 };
 use cid::multihash::MultihashDigest;
 use fendermint_crypto::SecretKey;
@@ -114,6 +114,21 @@ impl Arbitrary for Genesis {
             } else {
                 None
             },
+            cetf: if bool::arbitrary(g) {
+                Some(CetfParams::arbitrary(g))
+            } else {
+                None
+            },
+        }
+    }
+}
+
+impl Arbitrary for CetfParams {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {
+            max_pending_tags: u64::arbitrary(g) % 1000 + 1,
+            max_tags_per_height: None,
+            min_tag_distance: Option::<u64>::arbitrary(g).map(|d| d % 10 + 2),
         }
     }
 }
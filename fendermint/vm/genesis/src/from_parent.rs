@@ -0,0 +1,202 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Build the genesis of a child subnet from the information its parent tracks about it,
+//! shared by `fendermint genesis ... ipc from-parent` and `ipc-cli subnet create-genesis`.
+
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{bail, Context};
+use fendermint_crypto::PublicKey;
+use fendermint_vm_core::Timestamp;
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::version::NetworkVersion;
+use ipc_api::subnet_id::SubnetID;
+
+use crate::{
+    ipc, Account, Actor, ActorMeta, Collateral, Genesis, PermissionMode, PowerScale, SignerAddr,
+    Validator, ValidatorKey,
+};
+
+/// The genesis information of a child subnet, as stored in the parent.
+#[derive(Debug, Clone)]
+pub struct ParentGenesisInfo {
+    /// Epoch when the subnet was bootstrapped in the parent; 0 if it hasn't been yet.
+    pub genesis_epoch: ChainEpoch,
+    pub bottom_up_checkpoint_period: u64,
+    pub majority_percentage: u8,
+    pub active_validators_limit: u16,
+    /// Validators with their public key in the metadata and their collateral as the weight.
+    pub validators: Vec<ipc_api::validator::Validator>,
+    pub genesis_balances: BTreeMap<Address, TokenAmount>,
+}
+
+/// The parameters of the genesis that are not tracked by the parent.
+#[derive(Debug, Clone)]
+pub struct FromParentParams {
+    pub network_version: NetworkVersion,
+    pub base_fee: TokenAmount,
+    pub power_scale: PowerScale,
+    pub eam_permission_mode: PermissionMode,
+}
+
+/// Build the genesis of a child subnet from the genesis information stored in the parent.
+///
+/// The result only depends on the inputs, so querying the parent at a fixed height
+/// produces the same genesis for every participant.
+pub fn genesis_from_parent(
+    subnet_id: &SubnetID,
+    info: ParentGenesisInfo,
+    params: FromParentParams,
+) -> anyhow::Result<Genesis> {
+    if info.genesis_epoch <= 0 {
+        bail!("subnet {subnet_id} has not been bootstrapped in the parent yet");
+    }
+
+    let ipc_params = ipc::IpcParams {
+        gateway: ipc::GatewayParams {
+            subnet_id: subnet_id.clone(),
+            bottom_up_check_period: info.bottom_up_checkpoint_period,
+            majority_percentage: info.majority_percentage,
+            active_validators_limit: info.active_validators_limit,
+        },
+    };
+    let mut genesis = Genesis {
+        // We set the genesis epoch as the genesis timestamp so it can be
+        // generated deterministically by all participants.
+        timestamp: Timestamp(info.genesis_epoch as u64),
+        chain_name: subnet_id.to_string(),
+        network_version: params.network_version,
+        base_fee: params.base_fee,
+        power_scale: params.power_scale,
+        validators: Vec::new(),
+        accounts: Vec::new(),
+        eam_permission_mode: params.eam_permission_mode,
+        ipc: Some(ipc_params),
+        cetf: None,
+    };
+
+    for v in info.validators {
+        let pk = PublicKey::parse_slice(&v.metadata, None)
+            .with_context(|| format!("invalid public key of validator {}", v.addr))?;
+        genesis.validators.push(Validator {
+            public_key: ValidatorKey::new(pk),
+            power: Collateral(v.weight),
+        })
+    }
+
+    // The balances are in a `BTreeMap`, so the accounts are always in the same order.
+    for (a, b) in info.genesis_balances {
+        let meta = ActorMeta::Account(Account {
+            owner: SignerAddr(a),
+        });
+        let actor = Actor { meta, balance: b };
+        genesis.accounts.push(actor);
+    }
+
+    validate_genesis(&genesis)?;
+
+    Ok(genesis)
+}
+
+/// Sanity checks to catch a genesis the subnet would not be able to start with.
+fn validate_genesis(genesis: &Genesis) -> anyhow::Result<()> {
+    if genesis.validators.is_empty() {
+        bail!("the subnet has no genesis validators");
+    }
+
+    let mut keys = HashSet::new();
+    for v in genesis.validators.iter() {
+        let key = v.public_key.public_key().serialize();
+        if !keys.insert(key) {
+            bail!("duplicate genesis validator: {}", hex::encode(key));
+        }
+        if v.power.clone().into_power(genesis.power_scale).0 == 0 {
+            bail!("genesis validator {} has no power", hex::encode(key));
+        }
+    }
+
+    if let Some(ipc) = genesis.ipc.as_ref() {
+        ipc.gateway.validate()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::version::NetworkVersion;
+    use ipc_api::subnet_id::SubnetID;
+    use ipc_api::validator::Validator;
+
+    use super::{genesis_from_parent, FromParentParams, ParentGenesisInfo};
+    use crate::PermissionMode;
+
+    const VALIDATOR_KEY: &str = "04e3fc17076b544b68733edbfb2d8948e10e62abaf3ee448b5e08a73c4341b722d4e3580a9a2c5f408140f6b3d5b946ad6ce13db41115dad7872e5b79a3e3383e7";
+
+    /// The genesis info as it would be returned by the parent.
+    fn genesis_info() -> ParentGenesisInfo {
+        let owner = Address::from_str("f1eg363r3r5cluzx6qbgjcwaw73wwir2jzxopxhqy").unwrap();
+
+        ParentGenesisInfo {
+            genesis_epoch: 1000,
+            bottom_up_checkpoint_period: 10,
+            majority_percentage: 67,
+            active_validators_limit: 100,
+            validators: vec![Validator {
+                addr: Address::new_id(1002),
+                metadata: hex::decode(VALIDATOR_KEY).unwrap(),
+                weight: TokenAmount::from_whole(10),
+            }],
+            genesis_balances: BTreeMap::from([(owner, TokenAmount::from_whole(5))]),
+        }
+    }
+
+    fn params() -> FromParentParams {
+        FromParentParams {
+            network_version: NetworkVersion::V21,
+            base_fee: TokenAmount::from_atto(1000),
+            power_scale: 3,
+            eam_permission_mode: PermissionMode::Unrestricted,
+        }
+    }
+
+    fn subnet_id() -> SubnetID {
+        SubnetID::new_from_parent(&SubnetID::new_root(314159), Address::new_id(1001))
+    }
+
+    #[test]
+    fn test_genesis_from_parent_golden() {
+        let genesis = genesis_from_parent(&subnet_id(), genesis_info(), params()).unwrap();
+
+        let golden: serde_json::Value =
+            serde_json::from_str(include_str!("../golden/from_parent.json")).unwrap();
+
+        assert_eq!(serde_json::to_value(&genesis).unwrap(), golden);
+    }
+
+    #[test]
+    fn test_genesis_from_parent_not_bootstrapped() {
+        let mut info = genesis_info();
+        info.genesis_epoch = 0;
+        info.validators.clear();
+
+        assert!(genesis_from_parent(&subnet_id(), info, params()).is_err());
+    }
+
+    #[test]
+    fn test_genesis_from_parent_duplicate_validator() {
+        let mut info = genesis_info();
+        let mut v = info.validators[0].clone();
+        v.addr = Address::new_id(1003);
+        info.validators.push(v);
+
+        assert!(genesis_from_parent(&subnet_id(), info, params()).is_err());
+    }
+}
@@ -18,6 +18,7 @@ use fendermint_vm_encoding::IsHumanReadable;
 
 #[cfg(feature = "arb")]
 mod arb;
+pub mod from_parent;
 
 /// Power conversion decimal points, e.g. 3 decimals means 1 power per milliFIL.
 pub type PowerScale = i8;
@@ -47,6 +48,22 @@ pub struct Genesis {
     /// IPC related configuration, if enabled.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ipc: Option<ipc::IpcParams>,
+    /// CETF actor configuration, if it differs from the defaults.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cetf: Option<CetfParams>,
+}
+
+/// Parameters of the CETF actor.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CetfParams {
+    /// Maximum number of enqueued tags which haven't been signed yet.
+    pub max_pending_tags: u64,
+    /// Maximum number of tags signed at the same height; uses the actor default if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tags_per_height: Option<u64>,
+    /// Minimum number of epochs between enqueuing a tag and signing it; uses the actor default if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_tag_distance: Option<u64>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -240,19 +257,36 @@ pub mod ipc {
     pub struct GatewayParams {
         #[serde_as(as = "IsHumanReadable")]
         pub subnet_id: SubnetID,
+        /// The number of blocks between bottom-up checkpoints.
+        ///
+        /// On the rootnet it can be 0, which disables checkpointing, as there is no parent to submit them to.
         pub bottom_up_check_period: u64,
         pub majority_percentage: u8,
         pub active_validators_limit: u16,
     }
+
+    impl GatewayParams {
+        /// Check the parameters the gateway would not be able to work with.
+        pub fn validate(&self) -> anyhow::Result<()> {
+            if self.bottom_up_check_period == 0 && !self.subnet_id.is_root() {
+                anyhow::bail!(
+                    "the bottom-up checkpoint period of subnet {} cannot be 0",
+                    self.subnet_id
+                );
+            }
+            Ok(())
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use fvm_shared::{bigint::BigInt, econ::TokenAmount};
+    use fvm_shared::{address::Address, bigint::BigInt, econ::TokenAmount};
+    use ipc_api::subnet_id::SubnetID;
     use num_traits::Num;
     use quickcheck_macros::quickcheck;
 
-    use crate::{Collateral, Genesis};
+    use crate::{ipc::GatewayParams, Collateral, Genesis};
 
     #[quickcheck]
     fn genesis_json(value0: Genesis) {
@@ -312,4 +346,22 @@ mod tests {
             assert_eq!(Collateral::atto_per_power(scale), BigInt::from(atto))
         }
     }
+
+    #[test]
+    fn zero_checkpoint_period_only_on_root() {
+        let root = SubnetID::new_root(123);
+        let child = SubnetID::new_from_parent(&root, Address::new_id(1001));
+
+        let params = |subnet_id: &SubnetID, bottom_up_check_period| GatewayParams {
+            subnet_id: subnet_id.clone(),
+            bottom_up_check_period,
+            majority_percentage: 67,
+            active_validators_limit: 100,
+        };
+
+        assert!(params(&root, 0).validate().is_ok());
+        assert!(params(&root, 10).validate().is_ok());
+        assert!(params(&child, 10).validate().is_ok());
+        assert!(params(&child, 0).validate().is_err());
+    }
 }
@@ -3,9 +3,12 @@
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
 use cid::Cid;
+use fendermint_tracing::emit;
+use fendermint_vm_event::ProposalMessageDropped;
 use fendermint_vm_genesis::Genesis;
 use fendermint_vm_message::chain::ChainMessage;
 use fvm_ipld_encoding::Error as IpldError;
+use tendermint::crypto::sha256::Sha256;
 
 use crate::{
     chain::{ChainMessageApplyRet, ChainMessageCheckRes},
@@ -44,6 +47,8 @@ pub struct BytesMessageInterpreter<I> {
     reject_malformed_proposal: bool,
     /// Maximum number of messages to allow in a block.
     max_msgs: usize,
+    /// Maximum cumulative size of the messages in a block, in bytes.
+    max_bytes: usize,
 }
 
 impl<I> BytesMessageInterpreter<I> {
@@ -52,16 +57,45 @@ impl<I> BytesMessageInterpreter<I> {
         prepare_mode: ProposalPrepareMode,
         reject_malformed_proposal: bool,
         max_msgs: usize,
+        max_bytes: usize,
     ) -> Self {
         Self {
             inner,
             prepare_mode,
             reject_malformed_proposal,
             max_msgs,
+            max_bytes,
         }
     }
 }
 
+/// Take messages from the front of the proposal until adding the next one would exceed
+/// either the maximum number of messages or their maximum cumulative size in bytes.
+///
+/// Returns the selected messages along with their total size, and the messages left out.
+pub fn select_messages(
+    mut msgs: Vec<Vec<u8>>,
+    max_msgs: usize,
+    max_bytes: usize,
+) -> (Vec<Vec<u8>>, usize, Vec<Vec<u8>>) {
+    let mut size: usize = 0;
+    let mut count = 0;
+    for msg in msgs.iter() {
+        if count >= max_msgs || size.saturating_add(msg.len()) > max_bytes {
+            break;
+        }
+        size += msg.len();
+        count += 1;
+    }
+    let dropped = msgs.split_off(count);
+    (msgs, size, dropped)
+}
+
+/// Hash the transaction the way CometBFT does, so it can be looked up in its logs and RPC.
+fn tx_hash(msg: &[u8]) -> String {
+    hex::encode_upper(tendermint::crypto::default::Sha256::digest(msg))
+}
+
 #[async_trait]
 impl<I> ProposalInterpreter for BytesMessageInterpreter<I>
 where
@@ -89,6 +123,14 @@ where
                                 error = e.to_string(),
                                 "failed to decode message in mempool as ChainMessage"
                             );
+                            emit!(
+                                WARN,
+                                ProposalMessageDropped {
+                                    tx_hash: &tx_hash(msg),
+                                    size: msg.len(),
+                                    reason: "malformed",
+                                }
+                            );
                         }
                         Ok(msg) => chain_msgs.push(msg),
                     }
@@ -107,22 +149,45 @@ where
             })
             .collect::<anyhow::Result<Vec<Self::Message>>>()?;
 
-        let mut all_msgs = match self.prepare_mode {
+        let all_msgs = match self.prepare_mode {
             ProposalPrepareMode::PassThrough => chain_msgs,
             ProposalPrepareMode::AppendOnly => [msgs, chain_msgs].concat(),
             ProposalPrepareMode::PrependOnly => [chain_msgs, msgs].concat(),
         };
 
-        if all_msgs.len() > self.max_msgs {
+        let all_count = all_msgs.len();
+        let (msgs, size, dropped) = select_messages(all_msgs, self.max_msgs, self.max_bytes);
+
+        // These stay in the mempool and can be proposed in a later block.
+        for msg in dropped.iter() {
+            emit!(
+                DEBUG,
+                ProposalMessageDropped {
+                    tx_hash: &tx_hash(msg),
+                    size: msg.len(),
+                    reason: "block limits",
+                }
+            );
+        }
+
+        if msgs.len() < all_count {
             tracing::warn!(
                 max_msgs = self.max_msgs,
-                all_msgs = all_msgs.len(),
+                max_bytes = self.max_bytes,
+                all_msgs = all_count,
+                block_msgs = msgs.len(),
+                block_bytes = size,
                 "truncating proposal"
             );
-            all_msgs.truncate(self.max_msgs);
+        } else {
+            tracing::debug!(
+                block_msgs = msgs.len(),
+                block_bytes = size,
+                "selected proposal messages"
+            );
         }
 
-        Ok(all_msgs)
+        Ok(msgs)
     }
 
     /// Parse messages in the block, reject if unknown format. Pass the rest to the inner `ChainMessage` interpreter.
@@ -345,3 +410,58 @@ fn try_parse_genesis_cbor(bytes: &[u8]) -> anyhow::Result<Genesis> {
     let genesis = fvm_ipld_encoding::from_slice(bytes)?;
     Ok(genesis)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::select_messages;
+
+    fn msgs(sizes: &[usize]) -> Vec<Vec<u8>> {
+        sizes.iter().map(|size| vec![0u8; *size]).collect()
+    }
+
+    #[test]
+    fn select_all_within_limits() {
+        let (selected, size, dropped) = select_messages(msgs(&[10, 20, 30]), 10, 100);
+        assert_eq!(selected.len(), 3);
+        assert_eq!(size, 60);
+        assert!(dropped.is_empty());
+    }
+
+    #[test]
+    fn select_up_to_max_msgs() {
+        let (selected, size, dropped) = select_messages(msgs(&[1, 1, 1, 1, 1]), 3, 100);
+        assert_eq!(selected.len(), 3);
+        assert_eq!(size, 3);
+        assert_eq!(dropped.len(), 2);
+    }
+
+    #[test]
+    fn select_up_to_max_bytes() {
+        // The limit is inclusive.
+        let (selected, size, dropped) = select_messages(msgs(&[40, 60, 1]), 10, 100);
+        assert_eq!(selected.len(), 2);
+        assert_eq!(size, 100);
+        assert_eq!(dropped, msgs(&[1]));
+
+        // A large message stops the selection, even if later ones would fit.
+        let (selected, size, dropped) = select_messages(msgs(&[40, 70, 10]), 10, 100);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(size, 40);
+        assert_eq!(dropped, msgs(&[70, 10]));
+    }
+
+    #[test]
+    fn select_none_if_first_too_large() {
+        let (selected, size, dropped) = select_messages(msgs(&[101, 1]), 10, 100);
+        assert!(selected.is_empty());
+        assert_eq!(size, 0);
+        assert_eq!(dropped.len(), 2);
+    }
+
+    #[test]
+    fn select_preserves_order() {
+        let all = vec![vec![1u8; 5], vec![2u8; 50], vec![3u8; 5]];
+        let (selected, _, _) = select_messages(all.clone(), 2, 100);
+        assert_eq!(selected, all[..2].to_vec());
+    }
+}
@@ -29,7 +29,8 @@ use fendermint_vm_resolver::pool::{ResolveKey, ResolvePool};
 use fendermint_vm_topdown::proxy::IPCProviderProxy;
 use fendermint_vm_topdown::voting::{ValidatorKey, VoteTally};
 use fendermint_vm_topdown::{
-    CachedFinalityProvider, IPCParentFinality, ParentFinalityProvider, ParentViewProvider, Toggle,
+    effects_commitment, CachedFinalityProvider, IPCParentFinality, ParentFinalityProvider,
+    ParentViewProvider, Toggle,
 };
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::RawBytes;
@@ -138,10 +139,16 @@ where
         // hit some limits such as how many blocks we can propose in a single step.
         let finalities = atomically(|| {
             let parent = state.parent_finality_provider.next_proposal()?;
-            let quorum = state
-                .parent_finality_votes
-                .find_quorum()?
-                .map(|(height, block_hash)| IPCParentFinality { height, block_hash });
+            let quorum = match state.parent_finality_votes.find_quorum()? {
+                Some((height, block_hash)) => Some(IPCParentFinality {
+                    height,
+                    block_hash,
+                    effects_commitment: state
+                        .parent_finality_provider
+                        .effects_commitment(height)?,
+                }),
+                None => None,
+            };
 
             Ok((parent, quorum))
         })
@@ -154,11 +161,21 @@ where
                 quorum
             }),
             (Some(parent), None) => {
+                let (votes_seen, power_voted, quorum_threshold) = atomically(|| {
+                    state
+                        .parent_finality_votes
+                        .vote_tally(parent.height, &parent.block_hash)
+                })
+                .await;
+
                 emit!(
                     DEBUG,
                     ParentFinalityMissingQuorum {
                         block_height: parent.height,
                         block_hash: &hex::encode(&parent.block_hash),
+                        votes_seen,
+                        power_voted,
+                        quorum_threshold,
                     }
                 );
                 None
@@ -173,6 +190,7 @@ where
             msgs.push(ChainMessage::Ipc(IpcMessage::TopDownExec(ParentFinality {
                 height: finality.height as ChainEpoch,
                 block_hash: finality.block_hash,
+                effects_commitment: finality.effects_commitment,
             })))
         }
 
@@ -207,10 +225,12 @@ where
                 ChainMessage::Ipc(IpcMessage::TopDownExec(ParentFinality {
                     height,
                     block_hash,
+                    effects_commitment,
                 })) => {
                     let prop = IPCParentFinality {
                         height: height as u64,
                         block_hash,
+                        effects_commitment,
                     };
                     let is_final =
                         atomically(|| env.parent_finality_provider.check_proposal(&prop)).await;
@@ -252,20 +272,17 @@ where
         msg: Self::Message,
     ) -> anyhow::Result<(Self::State, Self::DeliverOutput)> {
         match msg {
-            ChainMessage::Cetf(msg) => match msg {
-                CetfMessage::CetfTag(height, sig) => {
-                    let msg = cetf_tag_msg_to_fvm(&(height, sig))
-                        .context("failed to syntesize FVM message")?;
+            ChainMessage::Cetf(msg) => {
+                let msg = cetf_tag_msg_to_fvm(&msg).context("failed to syntesize FVM message")?;
 
-                    let (state, ret) = self
-                        .inner
-                        .deliver(state, VerifiableMessage::NotVerify(msg))
-                        .await
-                        .context("failed to check cetf tag")?;
+                let (state, ret) = self
+                    .inner
+                    .deliver(state, VerifiableMessage::NotVerify(msg))
+                    .await
+                    .context("failed to check cetf tag")?;
 
-                    Ok(((env, state), ChainMessageApplyRet::Signed(ret)))
-                }
-            },
+                Ok(((env, state), ChainMessageApplyRet::Signed(ret)))
+            }
             ChainMessage::Signed(msg) => {
                 let (state, ret) = self
                     .inner
@@ -359,10 +376,6 @@ where
                         "chain interpreter received total validator changes"
                     );
 
-                    self.gateway_caller
-                        .store_validator_changes(&mut state, validator_changes)
-                        .context("failed to store validator changes")?;
-
                     // error happens if we cannot get the cross messages from ipc agent after retries
                     let msgs = env
                         .parent_finality_provider
@@ -377,6 +390,23 @@ where
                         "chain interpreter received topdown msgs",
                     );
 
+                    // Catch the validators having agreed on side effects other than what we fetched,
+                    // instead of diverging from them in the state.
+                    if let Some(ref expected) = p.effects_commitment {
+                        let actual = effects_commitment(&validator_changes, &msgs);
+                        if actual != *expected {
+                            bail!(
+                                "top-down effects from {execution_fr} to {execution_to} don't match the commitment in the proposal: expected {}, got {}",
+                                hex::encode(expected),
+                                hex::encode(&actual)
+                            );
+                        }
+                    }
+
+                    self.gateway_caller
+                        .store_validator_changes(&mut state, validator_changes)
+                        .context("failed to store validator changes")?;
+
                     let ret = topdown::execute_topdown_msgs(&self.gateway_caller, &mut state, msgs)
                         .await
                         .context("failed to execute top down messages")?;
@@ -487,20 +517,17 @@ where
                     }
                 }
             }
-            ChainMessage::Cetf(msg) => match msg {
-                CetfMessage::CetfTag(height, sig) => {
-                    let msg = cetf_tag_msg_to_fvm(&(height, sig))
-                        .context("failed to syntesize FVM message")?;
+            ChainMessage::Cetf(msg) => {
+                let msg = cetf_tag_msg_to_fvm(&msg).context("failed to syntesize FVM message")?;
 
-                    let (state, ret) = self
-                        .inner
-                        .check(state, VerifiableMessage::NotVerify(msg), is_recheck)
-                        .await
-                        .context("failed to check cetf tag")?;
+                let (state, ret) = self
+                    .inner
+                    .check(state, VerifiableMessage::NotVerify(msg), is_recheck)
+                    .await
+                    .context("failed to check cetf tag")?;
 
-                    Ok((state, Ok(ret)))
-                }
-            },
+                Ok((state, Ok(ret)))
+            }
         }
     }
 }
@@ -603,25 +630,49 @@ fn relayed_bottom_up_ckpt_to_fvm(
 }
 
 pub fn cetf_tag_msg_to_chainmessage(
-    tag_msg: &(u64, bls_signatures::Signature),
+    tag_msg: &(u64, Vec<bls_signatures::Signature>),
 ) -> anyhow::Result<ChainMessage> {
     let tag = tag_msg.0;
-    let sig = tag_msg.1.as_bytes();
-    let sig = BlsSignature(sig.try_into().unwrap());
-    Ok(ChainMessage::Cetf(CetfMessage::CetfTag(tag, sig)))
+    let mut sigs: Vec<BlsSignature> = tag_msg
+        .1
+        .iter()
+        .map(|sig| BlsSignature(sig.as_bytes().try_into().unwrap()))
+        .collect();
+    // A single signature goes in the message of the first version of the actor, which
+    // scheduled one tag per height, so chains which still run it keep working.
+    let msg = if sigs.len() == 1 {
+        CetfMessage::CetfTag(tag, sigs.remove(0))
+    } else {
+        CetfMessage::CetfTags(tag, sigs)
+    };
+    Ok(ChainMessage::Cetf(msg))
 }
 
-fn cetf_tag_msg_to_fvm(tag_msg: &(u64, BlsSignature)) -> anyhow::Result<FvmMessage> {
-    let params = RawBytes::serialize(&fendermint_actor_cetf::AddSignedTagParams {
-        height: tag_msg.0,
-        signature: tag_msg.1.clone(),
-    })?;
+fn cetf_tag_msg_to_fvm(tag_msg: &CetfMessage) -> anyhow::Result<FvmMessage> {
+    let (height, method_num, params) = match tag_msg {
+        CetfMessage::CetfTag(height, signature) => (
+            *height,
+            fendermint_actor_cetf::Method::AddSignedTag,
+            RawBytes::serialize(&fendermint_actor_cetf::AddSignedTagParams {
+                height: *height,
+                signature: *signature,
+            })?,
+        ),
+        CetfMessage::CetfTags(height, signatures) => (
+            *height,
+            fendermint_actor_cetf::Method::AddSignedTags,
+            RawBytes::serialize(&fendermint_actor_cetf::AddSignedTagsParams {
+                height: *height,
+                signatures: signatures.clone(),
+            })?,
+        ),
+    };
     let msg = FvmMessage {
         from: SYSTEM_ACTOR_ADDR,
         to: CETFSYSCALL_ACTOR_ADDR,
-        sequence: tag_msg.0,
+        sequence: height,
         gas_limit: BLOCK_GAS_LIMIT * 10000,
-        method_num: fendermint_actor_cetf::Method::AddSignedTag as u64,
+        method_num: method_num as u64,
         params,
         value: Default::default(),
         version: Default::default(),
@@ -1,6 +1,9 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
 use std::time::Duration;
 
 use anyhow::{anyhow, bail, Context};
@@ -19,26 +22,62 @@ use fendermint_rpc::tx::{CallClient, TxClient, TxSync};
 use fendermint_rpc::{client::FendermintClient, message::SignedMessageFactory};
 use fendermint_vm_message::query::FvmQueryHeight;
 
-macro_rules! retry {
-    ($max_retries:expr, $retry_delay:expr, $block:expr) => {{
-        let mut attempt = 0;
-        let value = loop {
-            match $block {
-                Err((code, msg)) if attempt == $max_retries || !can_retry(code) => {
-                    bail!(msg);
-                }
-                Err((_, msg)) => {
-                    tracing::warn!(error = msg, attempt, "retry broadcast");
-                    attempt += 1;
-                }
-                Ok(value) => {
-                    break value;
-                }
-            }
-            tokio::time::sleep($retry_delay).await;
-        };
-        value
-    }};
+/// The kinds of transactions a validator broadcasts, which can have very different gas needs,
+/// e.g. bottom-up checkpoint submissions are big and bursty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageKind {
+    /// Submitting signatures over bottom-up checkpoints.
+    Checkpoint,
+    /// Anything else.
+    Default,
+}
+
+/// Overrides of the gas parameters for a kind of message; anything not set falls back on the defaults.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GasOverride {
+    pub overestimation_rate: Option<f64>,
+    pub gas_premium: Option<TokenAmount>,
+}
+
+/// Decides the gas overestimation rate and premium to use for each kind of message.
+#[derive(Debug, Clone)]
+pub struct GasPolicy {
+    overestimation_rate: f64,
+    gas_premium: TokenAmount,
+    overrides: HashMap<MessageKind, GasOverride>,
+}
+
+impl GasPolicy {
+    /// Create a policy that applies the same values to every kind of message.
+    pub fn new(overestimation_rate: f64, gas_premium: TokenAmount) -> Self {
+        Self {
+            overestimation_rate,
+            gas_premium,
+            overrides: Default::default(),
+        }
+    }
+
+    /// Override the defaults for a kind of message.
+    pub fn with_override(mut self, kind: MessageKind, gas_override: GasOverride) -> Self {
+        self.overrides.insert(kind, gas_override);
+        self
+    }
+
+    /// The multiplier to apply on the gas estimate of a kind of message.
+    pub fn overestimation_rate(&self, kind: MessageKind) -> f64 {
+        self.overrides
+            .get(&kind)
+            .and_then(|o| o.overestimation_rate)
+            .unwrap_or(self.overestimation_rate)
+    }
+
+    /// The gas premium to offer for a kind of message.
+    pub fn gas_premium(&self, kind: MessageKind) -> &TokenAmount {
+        self.overrides
+            .get(&kind)
+            .and_then(|o| o.gas_premium.as_ref())
+            .unwrap_or(&self.gas_premium)
+    }
 }
 
 /// Broadcast transactions to Tendermint.
@@ -55,9 +94,9 @@ pub struct Broadcaster<C> {
     secret_key: SecretKey,
     addr: Address,
     gas_fee_cap: TokenAmount,
-    gas_premium: TokenAmount,
-    gas_overestimation_rate: f64,
+    gas_policy: GasPolicy,
     max_retries: u8,
+    nonce_race_retries: u8,
     retry_delay: Duration,
 }
 
@@ -79,19 +118,33 @@ where
             secret_key,
             addr,
             gas_fee_cap,
-            gas_premium,
-            gas_overestimation_rate,
+            gas_policy: GasPolicy::new(gas_overestimation_rate, gas_premium),
             max_retries: 0,
+            nonce_race_retries: 0,
             // Set the retry delay to rougly the block creation time.
             retry_delay: Duration::from_secs(1),
         }
     }
 
+    /// Override the gas overestimation rate and premium for a kind of message.
+    pub fn with_gas_override(mut self, kind: MessageKind, gas_override: GasOverride) -> Self {
+        self.gas_policy = self.gas_policy.with_override(kind, gas_override);
+        self
+    }
+
     pub fn with_max_retries(mut self, max_retries: u8) -> Self {
         self.max_retries = max_retries;
         self
     }
 
+    /// Number of times to retry a transaction which failed because its nonce was
+    /// taken by another transaction from the same sender. These are counted separately
+    /// from `max_retries` because re-querying the sequence is expected to resolve them.
+    pub fn with_nonce_race_retries(mut self, nonce_race_retries: u8) -> Self {
+        self.nonce_race_retries = nonce_race_retries;
+        self
+    }
+
     pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
         self.retry_delay = retry_delay;
         self
@@ -108,95 +161,115 @@ where
     /// client seems to time out if the check fails, waiting for the inclusion which will never come, instead of
     /// returning the result with no `deliver_tx` and a failed `check_tx`. We can add our own mechanism to wait
     /// for commits if we have to.
+    ///
+    /// The `kind` of the message decides which gas parameters of the [GasPolicy] are applied.
     pub async fn fevm_invoke(
         &self,
+        kind: MessageKind,
         contract: Address,
         calldata: et::Bytes,
         chain_id: ChainID,
     ) -> anyhow::Result<tendermint::hash::Hash> {
-        let tx_hash = retry!(self.max_retries, self.retry_delay, {
-            let sequence = self
-                .sequence()
-                .await
-                .context("failed to get broadcaster sequence")?;
-
-            let factory =
-                SignedMessageFactory::new(self.secret_key.clone(), self.addr, sequence, chain_id);
-
-            // Using the bound client as a one-shot transaction sender.
-            let mut client = self.client.clone().bind(factory);
-
-            // TODO: Maybe we should implement something like the Ethereum facade for estimating fees?
-            // I don't want to call the Ethereum API directly (it would be one more dependency).
-            // Another option is for Fendermint to recognise transactions coming from validators
-            // and always put them into the block to facilitate checkpointing.
-            let mut gas_params = GasParams {
-                gas_limit: BLOCK_GAS_LIMIT,
-                gas_fee_cap: self.gas_fee_cap.clone(),
-                gas_premium: self.gas_premium.clone(),
-            };
-
-            // Not expecting to send any tokens to the contracts.
-            let value = TokenAmount::zero();
-
-            // We can use the `Committed` state to execute the message, which is more efficient than doing it on `Pending`.
-            let gas_estimate = client
-                .fevm_estimate_gas(
-                    contract,
-                    calldata.0.clone(),
-                    value.clone(),
-                    gas_params.clone(),
-                    FvmQueryHeight::Committed,
-                )
-                .await
-                .context("failed to estimate gas")?;
-
-            if gas_estimate.value.exit_code.is_success() {
-                gas_params.gas_limit =
-                    (gas_estimate.value.gas_limit as f64 * self.gas_overestimation_rate) as u64;
-            } else {
-                bail!(
-                    "failed to estimate gas: {} - {}",
-                    gas_estimate.value.exit_code,
-                    gas_estimate.value.info
-                );
-            }
+        let retries = Retries {
+            max_retries: self.max_retries,
+            nonce_race_retries: self.nonce_race_retries,
+            retry_delay: self.retry_delay,
+        };
+
+        retry_broadcast(retries, || {
+            self.try_fevm_invoke(kind, contract, &calldata, chain_id)
+        })
+        .await
+    }
+
+    /// Make a single attempt at sending a transaction with the current sequence of the broadcaster.
+    ///
+    /// Returns the failure if the transaction was rejected by the check, so we can decide whether to retry.
+    async fn try_fevm_invoke(
+        &self,
+        kind: MessageKind,
+        contract: Address,
+        calldata: &et::Bytes,
+        chain_id: ChainID,
+    ) -> anyhow::Result<Result<tendermint::hash::Hash, CheckFailure>> {
+        let sequence = self
+            .sequence()
+            .await
+            .context("failed to get broadcaster sequence")?;
+
+        let factory =
+            SignedMessageFactory::new(self.secret_key.clone(), self.addr, sequence, chain_id);
+
+        // Using the bound client as a one-shot transaction sender.
+        let mut client = self.client.clone().bind(factory);
 
-            // Using TxSync instead of TxCommit because TxCommit times out if the `check_tx` part fails,
-            // instead of returning as soon as the check failed with some default values for `deliver_tx`.
-            let res = TxClient::<TxSync>::fevm_invoke(
-                &mut client,
+        // TODO: Maybe we should implement something like the Ethereum facade for estimating fees?
+        // I don't want to call the Ethereum API directly (it would be one more dependency).
+        // Another option is for Fendermint to recognise transactions coming from validators
+        // and always put them into the block to facilitate checkpointing.
+        let mut gas_params = GasParams {
+            gas_limit: BLOCK_GAS_LIMIT,
+            gas_fee_cap: self.gas_fee_cap.clone(),
+            gas_premium: self.gas_policy.gas_premium(kind).clone(),
+        };
+
+        // Not expecting to send any tokens to the contracts.
+        let value = TokenAmount::zero();
+
+        // We can use the `Committed` state to execute the message, which is more efficient than doing it on `Pending`.
+        let gas_estimate = client
+            .fevm_estimate_gas(
                 contract,
                 calldata.0.clone(),
-                value,
-                gas_params,
+                value.clone(),
+                gas_params.clone(),
+                FvmQueryHeight::Committed,
             )
             .await
-            .context("failed to invoke contract")?;
-
-            if res.response.code.is_err() {
-                // Not sure what exactly arrives in the data and how it's encoded.
-                // It might need the Base64 decoding or it may not. Let's assume
-                // that it doesn't because unlike `DeliverTx::data`, this response
-                // does have some Base64 lreated annotations.
-                let data = decode_fevm_return_data(RawBytes::new(res.response.data.to_vec()))
-                    .map(hex::encode)
-                    .unwrap_or_else(|_| hex::encode(res.response.data));
-
-                Err((
-                    res.response.code,
-                    format!(
-                        "broadcasted transaction failed during check: {}; data = {}",
-                        res.response.code.value(),
-                        data
-                    ),
-                ))
-            } else {
-                Ok(res.response.hash)
-            }
-        });
+            .context("failed to estimate gas")?;
 
-        Ok(tx_hash)
+        if gas_estimate.value.exit_code.is_success() {
+            gas_params.gas_limit = (gas_estimate.value.gas_limit as f64
+                * self.gas_policy.overestimation_rate(kind))
+                as u64;
+        } else {
+            bail!(
+                "failed to estimate gas: {} - {}",
+                gas_estimate.value.exit_code,
+                gas_estimate.value.info
+            );
+        }
+
+        // Using TxSync instead of TxCommit because TxCommit times out if the `check_tx` part fails,
+        // instead of returning as soon as the check failed with some default values for `deliver_tx`.
+        let res = TxClient::<TxSync>::fevm_invoke(
+            &mut client,
+            contract,
+            calldata.0.clone(),
+            value,
+            gas_params,
+        )
+        .await
+        .context("failed to invoke contract")?;
+
+        if res.response.code.is_err() {
+            // Not sure what exactly arrives in the data and how it's encoded.
+            // It might need the Base64 decoding or it may not. Let's assume
+            // that it doesn't because unlike `DeliverTx::data`, this response
+            // does have some Base64 lreated annotations.
+            let data = decode_fevm_return_data(RawBytes::new(res.response.data.to_vec()))
+                .map(hex::encode)
+                .unwrap_or_else(|_| hex::encode(res.response.data));
+
+            Ok(Err(CheckFailure {
+                code: res.response.code,
+                sequence,
+                log: res.response.log,
+                data,
+            }))
+        } else {
+            Ok(Ok(res.response.hash))
+        }
     }
 
     /// Fetch the current nonce to be used in the next message.
@@ -215,12 +288,90 @@ where
     }
 }
 
+/// Limits on how many times a failed broadcast is attempted again.
+#[derive(Debug, Clone, Copy)]
+struct Retries {
+    max_retries: u8,
+    nonce_race_retries: u8,
+    retry_delay: Duration,
+}
+
+/// A transaction that was rejected by the `check_tx` of the node.
+#[derive(Debug, Clone)]
+struct CheckFailure {
+    code: tendermint::abci::Code,
+    /// The sequence the transaction was signed with.
+    sequence: u64,
+    log: String,
+    data: String,
+}
+
+impl CheckFailure {
+    /// Check if the transaction was rejected because another transaction
+    /// from the same sender used the nonce before it.
+    fn is_nonce_race(&self) -> bool {
+        ExitCode::new(self.code.value()) == ExitCode::SYS_SENDER_STATE_INVALID
+            && self.log.contains("expected sequence")
+    }
+}
+
+impl Display for CheckFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "broadcasted transaction failed during check: {}; log = {}; data = {}",
+            self.code.value(),
+            self.log,
+            self.data
+        )
+    }
+}
+
+/// Keep calling `broadcast` until the transaction passes the check, or we run out of retries.
+///
+/// Every attempt is expected to re-query the sequence and re-sign the transaction, so a nonce
+/// race is resolved by simply trying again; those have their own budget of retries, while
+/// any other retryable failure counts against `max_retries`.
+async fn retry_broadcast<T, F, Fut>(retries: Retries, mut broadcast: F) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<Result<T, CheckFailure>>>,
+{
+    let mut attempt = 0;
+    let mut nonce_race_attempt = 0;
+    loop {
+        match broadcast().await? {
+            Ok(value) => return Ok(value),
+            Err(failure) if failure.is_nonce_race() => {
+                if nonce_race_attempt == retries.nonce_race_retries {
+                    bail!("{failure}; gave up after {nonce_race_attempt} nonce races");
+                }
+                nonce_race_attempt += 1;
+                tracing::warn!(
+                    error = failure.log,
+                    sequence = failure.sequence,
+                    attempt = nonce_race_attempt,
+                    "nonce race; retry broadcast with a fresh sequence"
+                );
+            }
+            Err(failure) if attempt == retries.max_retries || !can_retry(failure.code) => {
+                bail!(failure);
+            }
+            Err(failure) => {
+                attempt += 1;
+                tracing::warn!(error = failure.to_string(), attempt, "retry broadcast");
+            }
+        }
+        tokio::time::sleep(retries.retry_delay).await;
+    }
+}
+
 /// Decide if it's worth retrying the transaction.
 fn can_retry(code: tendermint::abci::Code) -> bool {
     match ExitCode::new(code.value()) {
         // If the sender doesn't exist it doesn't matter how many times we try.
         ExitCode::SYS_SENDER_INVALID => false,
-        // If the nonce was invalid, it might be because of a race condition, and we can try again.
+        // Nonce races are handled separately; otherwise the sender state might be catching up.
         ExitCode::SYS_SENDER_STATE_INVALID => true,
         // If the sender doesn't have enough funds to cover the gas, it's unlikely that repeating imemediately will help.
         ExitCode::SYS_INSUFFICIENT_FUNDS => false,
@@ -231,3 +382,173 @@ fn can_retry(code: tendermint::abci::Code) -> bool {
         _ => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::error::ExitCode;
+    use tendermint::abci::Code;
+
+    use super::{retry_broadcast, CheckFailure, GasOverride, GasPolicy, MessageKind, Retries};
+
+    const RETRIES: Retries = Retries {
+        max_retries: 1,
+        nonce_race_retries: 3,
+        retry_delay: Duration::from_millis(1),
+    };
+
+    /// Mock of the client which fails the check with the given failures before accepting the transaction.
+    struct MockClient {
+        failures: Vec<CheckFailure>,
+        attempts: Cell<u64>,
+    }
+
+    impl MockClient {
+        fn new(failures: Vec<CheckFailure>) -> Self {
+            Self {
+                failures,
+                attempts: Cell::new(0),
+            }
+        }
+
+        /// Pretend to query the sequence, which goes up with every failed attempt, and broadcast.
+        async fn broadcast(&self) -> anyhow::Result<Result<u64, CheckFailure>> {
+            let sequence = self.attempts.get();
+            self.attempts.set(sequence + 1);
+
+            match self.failures.get(sequence as usize) {
+                Some(failure) => Ok(Err(CheckFailure {
+                    sequence,
+                    ..failure.clone()
+                })),
+                None => Ok(Ok(sequence)),
+            }
+        }
+
+        fn attempts(&self) -> u64 {
+            self.attempts.get()
+        }
+    }
+
+    fn nonce_race() -> CheckFailure {
+        CheckFailure {
+            code: Code::from(ExitCode::SYS_SENDER_STATE_INVALID.value()),
+            sequence: 0,
+            log: "expected sequence 1, got 0".to_string(),
+            data: String::new(),
+        }
+    }
+
+    fn out_of_gas() -> CheckFailure {
+        CheckFailure {
+            code: Code::from(ExitCode::SYS_OUT_OF_GAS.value()),
+            sequence: 0,
+            log: "out of gas".to_string(),
+            data: String::new(),
+        }
+    }
+
+    fn insufficient_balance() -> CheckFailure {
+        CheckFailure {
+            code: Code::from(ExitCode::SYS_SENDER_STATE_INVALID.value()),
+            sequence: 0,
+            log: "actor balance 0 less than needed 1".to_string(),
+            data: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn nonce_race_then_success() {
+        let client = MockClient::new(vec![nonce_race()]);
+
+        let sequence = retry_broadcast(RETRIES, || client.broadcast())
+            .await
+            .expect("should succeed after re-querying the sequence");
+
+        assert_eq!(sequence, 1);
+        assert_eq!(client.attempts(), 2);
+    }
+
+    #[tokio::test]
+    async fn nonce_races_do_not_use_max_retries() {
+        // More races than `max_retries`, but within the nonce race budget,
+        // interleaved with another retryable failure.
+        let client = MockClient::new(vec![
+            nonce_race(),
+            insufficient_balance(),
+            nonce_race(),
+            nonce_race(),
+        ]);
+
+        let sequence = retry_broadcast(RETRIES, || client.broadcast())
+            .await
+            .expect("should succeed");
+
+        assert_eq!(sequence, 4);
+    }
+
+    #[tokio::test]
+    async fn nonce_races_exhausted() {
+        let client = MockClient::new(vec![nonce_race(); 10]);
+
+        let err = retry_broadcast(RETRIES, || client.broadcast())
+            .await
+            .expect_err("should run out of retries");
+
+        assert!(err.to_string().contains("nonce races"));
+        assert_eq!(client.attempts(), RETRIES.nonce_race_retries as u64 + 1);
+    }
+
+    #[tokio::test]
+    async fn non_retryable_failure() {
+        let client = MockClient::new(vec![out_of_gas()]);
+
+        retry_broadcast(RETRIES, || client.broadcast())
+            .await
+            .expect_err("should not retry");
+
+        assert_eq!(client.attempts(), 1);
+    }
+
+    #[test]
+    fn gas_policy_per_kind() {
+        let policy = GasPolicy::new(1.25, TokenAmount::from_atto(10)).with_override(
+            MessageKind::Checkpoint,
+            GasOverride {
+                overestimation_rate: Some(2.0),
+                gas_premium: Some(TokenAmount::from_atto(100)),
+            },
+        );
+
+        assert_eq!(policy.overestimation_rate(MessageKind::Checkpoint), 2.0);
+        assert_eq!(
+            policy.gas_premium(MessageKind::Checkpoint),
+            &TokenAmount::from_atto(100)
+        );
+
+        // Kinds without overrides use the defaults.
+        assert_eq!(policy.overestimation_rate(MessageKind::Default), 1.25);
+        assert_eq!(
+            policy.gas_premium(MessageKind::Default),
+            &TokenAmount::from_atto(10)
+        );
+
+        // Partial overrides fall back on the defaults.
+        let policy = GasPolicy::new(1.25, TokenAmount::from_atto(10)).with_override(
+            MessageKind::Checkpoint,
+            GasOverride {
+                overestimation_rate: Some(1.1),
+                gas_premium: None,
+            },
+        );
+
+        assert_eq!(policy.overestimation_rate(MessageKind::Checkpoint), 1.1);
+        assert_eq!(
+            policy.gas_premium(MessageKind::Checkpoint),
+            &TokenAmount::from_atto(10)
+        );
+    }
+}
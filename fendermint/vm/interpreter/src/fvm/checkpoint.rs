@@ -27,7 +27,7 @@ use ipc_api::staking::ConfigurationNumber;
 
 use super::state::ipc::tokens_to_burn;
 use super::{
-    broadcast::Broadcaster,
+    broadcast::{Broadcaster, MessageKind},
     state::{ipc::GatewayCaller, FvmExecState},
     ValidatorContext,
 };
@@ -280,7 +280,12 @@ where
         .context("failed to produce checkpoint signature calldata")?;
 
     let tx_hash = broadcaster
-        .fevm_invoke(Address::from(gateway.addr()), calldata, chain_id)
+        .fevm_invoke(
+            MessageKind::Checkpoint,
+            Address::from(gateway.addr()),
+            calldata,
+            chain_id,
+        )
         .await
         .context("failed to broadcast signature")?;
 
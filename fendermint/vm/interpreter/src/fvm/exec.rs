@@ -65,10 +65,18 @@ where
         let block_height: u64 = state.block_height().try_into().unwrap();
         if let Some(upgrade) = self.upgrade_scheduler.get(chain_id, block_height) {
             // TODO: consider using an explicit tracing enum for upgrades
-            tracing::info!(?chain_id, height = block_height, "Executing an upgrade");
+            tracing::info!(
+                ?chain_id,
+                height = block_height,
+                name = upgrade.name(),
+                "Executing an upgrade"
+            );
 
             // there is an upgrade scheduled for this height, lets run the migration
             let res = upgrade.execute(&mut state).context("upgrade failed")?;
+            if let Some(name) = upgrade.name() {
+                state.set_executed_upgrade(name);
+            }
             if let Some(new_app_version) = res {
                 state.update_app_version(|app_version| {
                     *app_version = new_app_version;
@@ -3,7 +3,7 @@
 
 use std::collections::{BTreeSet, HashMap};
 use std::marker::PhantomData;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
@@ -11,7 +11,7 @@ use ethers::abi::Tokenize;
 use ethers::core::types as et;
 use fendermint_actor_eam::PermissionModeParams;
 use fendermint_eth_hardhat::{Hardhat, FQN};
-use fendermint_vm_actor_interface::diamond::{EthContract, EthContractMap};
+use fendermint_vm_actor_interface::diamond::{self, EthContract, EthContractMap};
 use fendermint_vm_actor_interface::eam::EthAddress;
 use fendermint_vm_actor_interface::ipc::IPC_CONTRACTS;
 use fendermint_vm_actor_interface::{
@@ -113,7 +113,6 @@ where
 
         // Pre-defined IDs for top-level Ethereum contracts.
         let mut eth_builtin_ids = BTreeSet::new();
-        let mut eth_root_contracts = Vec::new();
         let mut eth_contracts = EthContractMap::default();
 
         // Only allocate IDs if the contracts are deployed.
@@ -122,25 +121,9 @@ where
         }
 
         eth_builtin_ids.extend(eth_contracts.values().map(|c| c.actor_id));
-        eth_root_contracts.extend(eth_contracts.keys());
-        eth_root_contracts.extend(
-            eth_contracts
-                .values()
-                .flat_map(|c| c.facets.iter().map(|f| f.name)),
-        );
-        // Collect dependencies of the main IPC actors.
-        let mut eth_libs = self
-            .contracts
-            .dependencies(
-                &eth_root_contracts
-                    .iter()
-                    .map(|n| (contract_src(n), *n))
-                    .collect::<Vec<_>>(),
-            )
-            .context("failed to collect EVM contract dependencies")?;
 
-        // Only keep library dependencies, not contracts with constructors.
-        eth_libs.retain(|(_, d)| !eth_contracts.contains_key(d.as_str()));
+        // Collect dependencies of the main IPC actors.
+        let eth_libs = diamond::eth_libraries(&self.contracts, &eth_contracts)?;
 
         // STAGE 1: First we initialize native built-in actors.
 
@@ -246,7 +229,29 @@ where
                 None,
             )
             .context("failed to create chainmetadata actor")?;
-        let cetf_state = fendermint_actor_cetf::State::new(&state.store())?;
+        let max_pending_tags = genesis
+            .cetf
+            .as_ref()
+            .map(|p| p.max_pending_tags)
+            .unwrap_or(fendermint_actor_cetf::DEFAULT_MAX_PENDING_TAGS);
+        let max_tags_per_height = genesis
+            .cetf
+            .as_ref()
+            .and_then(|p| p.max_tags_per_height)
+            .unwrap_or_else(|| {
+                max_pending_tags.min(fendermint_actor_cetf::DEFAULT_MAX_TAGS_PER_HEIGHT)
+            });
+        let min_tag_distance = genesis
+            .cetf
+            .as_ref()
+            .and_then(|p| p.min_tag_distance)
+            .unwrap_or(fendermint_actor_cetf::DEFAULT_MIN_TAG_DISTANCE);
+        let cetf_state = fendermint_actor_cetf::State::new(
+            &state.store(),
+            max_pending_tags,
+            max_tags_per_height,
+            min_tag_distance,
+        )?;
         state
             .create_custom_actor(
                 fendermint_actor_cetf::CETF_ACTOR_NAME,
@@ -319,6 +324,11 @@ where
             let gateway_addr = {
                 use ipc::gateway::ConstructorParameters;
 
+                ipc_params
+                    .gateway
+                    .validate()
+                    .context("invalid gateway parameters")?;
+
                 let params = ConstructorParameters::new(ipc_params.gateway, genesis.validators)
                     .context("failed to create gateway constructor")?;
 
@@ -337,41 +347,12 @@ where
             {
                 use ipc::registry::ConstructorParameters;
 
-                let mut facets = deployer
+                let facets = deployer
                     .facets(ipc::registry::CONTRACT_NAME)
                     .context("failed to collect registry facets")?;
 
-                let getter_facet = facets.remove(0);
-                let manager_facet = facets.remove(0);
-                let rewarder_facet = facets.remove(0);
-                let checkpointer_facet = facets.remove(0);
-                let pauser_facet = facets.remove(0);
-                let diamond_loupe_facet = facets.remove(0);
-                let diamond_cut_facet = facets.remove(0);
-                let ownership_facet = facets.remove(0);
-
-                debug_assert_eq!(facets.len(), 2, "SubnetRegistry has 2 facets of its own");
-
-                let params = ConstructorParameters {
-                    gateway: gateway_addr,
-                    getter_facet: getter_facet.facet_address,
-                    manager_facet: manager_facet.facet_address,
-                    rewarder_facet: rewarder_facet.facet_address,
-                    pauser_facet: pauser_facet.facet_address,
-                    checkpointer_facet: checkpointer_facet.facet_address,
-                    diamond_cut_facet: diamond_cut_facet.facet_address,
-                    diamond_loupe_facet: diamond_loupe_facet.facet_address,
-                    ownership_facet: ownership_facet.facet_address,
-                    subnet_getter_selectors: getter_facet.function_selectors,
-                    subnet_manager_selectors: manager_facet.function_selectors,
-                    subnet_rewarder_selectors: rewarder_facet.function_selectors,
-                    subnet_checkpointer_selectors: checkpointer_facet.function_selectors,
-                    subnet_pauser_selectors: pauser_facet.function_selectors,
-                    subnet_actor_diamond_cut_selectors: diamond_cut_facet.function_selectors,
-                    subnet_actor_diamond_loupe_selectors: diamond_loupe_facet.function_selectors,
-                    subnet_actor_ownership_selectors: ownership_facet.function_selectors,
-                    creation_privileges: 0,
-                };
+                let (facets, params) = ConstructorParameters::new(gateway_addr, facets)
+                    .context("failed to create registry constructor")?;
 
                 deployer.deploy_contract(
                     &mut state,
@@ -385,10 +366,6 @@ where
     }
 }
 
-fn contract_src(name: &str) -> PathBuf {
-    PathBuf::from(format!("{name}.sol"))
-}
-
 struct ContractDeployer<'a, DB> {
     hardhat: &'a Hardhat,
     top_contracts: &'a EthContractMap,
@@ -461,7 +438,7 @@ where
     {
         let contract = self.top_contract(contract_name)?;
         let contract_id = contract.actor_id;
-        let contract_src = contract_src(contract_name);
+        let contract_src = diamond::contract_src(contract_name);
 
         let bytecode = self
             .hardhat
@@ -490,35 +467,7 @@ where
     /// Collect Facet Cuts for the diamond pattern, where the facet address comes from already deployed library facets.
     pub fn facets(&self, contract_name: &str) -> anyhow::Result<Vec<FacetCut>> {
         let contract = self.top_contract(contract_name)?;
-        let mut facet_cuts = Vec::new();
-
-        for facet in contract.facets.iter() {
-            let facet_name = facet.name;
-            let facet_src = contract_src(facet_name);
-            let facet_fqn = self.hardhat.fqn(&facet_src, facet_name);
-
-            let facet_addr = self
-                .lib_addrs
-                .get(&facet_fqn)
-                .ok_or_else(|| anyhow!("facet {facet_name} has not been deployed"))?;
-
-            let method_sigs = facet
-                .abi
-                .functions()
-                .filter(|f| f.signature() != "init(bytes)")
-                .map(|f| f.short_signature())
-                .collect();
-
-            let facet_cut = FacetCut {
-                facet_address: *facet_addr,
-                action: 0, // Add
-                function_selectors: method_sigs,
-            };
-
-            facet_cuts.push(facet_cut);
-        }
-
-        Ok(facet_cuts)
+        diamond::facet_cuts(self.hardhat, contract, &self.lib_addrs)
     }
 
     fn top_contract(&self, contract_name: &str) -> anyhow::Result<&EthContract> {
@@ -540,8 +489,10 @@ mod tests {
     use std::{str::FromStr, sync::Arc};
 
     use cid::Cid;
-    use fendermint_vm_genesis::{ipc::IpcParams, Genesis};
+    use fendermint_vm_actor_interface::cetf::CETFSYSCALL_ACTOR_ID;
+    use fendermint_vm_genesis::{ipc::IpcParams, CetfParams, Genesis};
     use fvm::engine::MultiEngine;
+    use fvm_ipld_encoding::CborStore;
     use quickcheck::Arbitrary;
     use tendermint_rpc::{MockClient, MockRequestMethodMatcher};
 
@@ -589,6 +540,27 @@ mod tests {
 
         assert_eq!(period, genesis.ipc.unwrap().gateway.bottom_up_check_period);
 
+        // The CETF actor is configured with the parameters from the genesis.
+        let cetf_actor = exec_state
+            .state_tree()
+            .get_actor(CETFSYSCALL_ACTOR_ID)
+            .expect("error getting the cetf actor")
+            .expect("cetf actor not found");
+        let cetf_state: fendermint_actor_cetf::State = exec_state
+            .state_tree()
+            .store()
+            .get_cbor(&cetf_actor.state)
+            .expect("error reading the cetf state")
+            .expect("cetf state not found");
+
+        let cetf = genesis.cetf.unwrap();
+        assert_eq!(cetf_state.max_pending_tags, cetf.max_pending_tags);
+        assert_eq!(
+            Some(cetf_state.max_tags_per_height),
+            cetf.max_tags_per_height
+        );
+        assert_eq!(Some(cetf_state.min_tag_distance), cetf.min_tag_distance);
+
         let _state_root = state.commit().expect("failed to commit");
     }
 
@@ -664,6 +636,11 @@ mod tests {
 
         // Make sure we have IPC enabled.
         genesis.ipc = Some(IpcParams::arbitrary(&mut g));
+        genesis.cetf = Some(CetfParams {
+            max_pending_tags: 7,
+            max_tags_per_height: Some(5),
+            min_tag_distance: Some(3),
+        });
         genesis
     }
 
@@ -679,6 +656,7 @@ mod tests {
             false,
             UpgradeScheduler::new(),
         )
+        .expect("failed to create interpreter")
     }
 
     fn read_bundle() -> Vec<u8> {
@@ -19,6 +19,7 @@ pub mod upgrades;
 pub mod bundle;
 pub(crate) mod topdown;
 
+use anyhow::bail;
 pub use check::FvmCheckRet;
 pub use checkpoint::PowerUpdates;
 pub use exec::FvmApplyRet;
@@ -30,7 +31,7 @@ pub use genesis::FvmGenesisOutput;
 pub use query::FvmQueryRet;
 use tendermint_rpc::Client;
 
-pub use self::broadcast::Broadcaster;
+pub use self::broadcast::{Broadcaster, GasOverride, GasPolicy, MessageKind};
 use self::{state::ipc::GatewayCaller, upgrades::UpgradeScheduler};
 
 pub type FvmMessage = fvm_shared::message::Message;
@@ -85,10 +86,10 @@ where
     /// If this is a validator node, this should be the key we can use to sign transactions.
     validator_ctx: Option<ValidatorContext<C>>,
     /// Overestimation rate applied to gas to ensure that the
-    /// message goes through in the gas estimation.
+    /// message goes through in the gas estimation; at least 1.0.
     gas_overestimation_rate: f64,
     /// Gas search step increase used to find the optimal gas limit.
-    /// It determines how fine-grained we want the gas estimation to be.
+    /// It determines how fine-grained we want the gas estimation to be; more than 1.0.
     gas_search_step: f64,
     /// Indicate whether transactions should be fully executed during the checks performed
     /// when they are added to the mempool, or just the most basic ones are performed.
@@ -104,6 +105,13 @@ impl<DB, C> FvmMessageInterpreter<DB, C>
 where
     DB: Blockstore + 'static + Clone,
 {
+    /// Create a new interpreter.
+    ///
+    /// The `gas_overestimation_rate` multiplies the gas used by a message during estimation
+    /// to get its gas limit, so it has to be at least 1.0, otherwise the estimated messages
+    /// would run out of gas. The `gas_search_step` multiplies the gas limit in each round of
+    /// the search for the lowest limit that works, so it has to be more than 1.0 for the
+    /// search to make progress.
     pub fn new(
         client: C,
         validator_ctx: Option<ValidatorContext<C>>,
@@ -112,8 +120,14 @@ where
         gas_search_step: f64,
         exec_in_check: bool,
         upgrade_scheduler: UpgradeScheduler<DB>,
-    ) -> Self {
-        Self {
+    ) -> anyhow::Result<Self> {
+        if gas_overestimation_rate.is_nan() || gas_overestimation_rate < 1.0 {
+            bail!("gas overestimation rate has to be at least 1.0; got {gas_overestimation_rate}");
+        }
+        if gas_search_step.is_nan() || gas_search_step <= 1.0 {
+            bail!("gas search step has to be more than 1.0; got {gas_search_step}");
+        }
+        Ok(Self {
             client,
             validator_ctx,
             contracts: Hardhat::new(contracts_dir),
@@ -123,7 +137,7 @@ where
             push_chain_meta: true,
             gateway: GatewayCaller::default(),
             upgrade_scheduler,
-        }
+        })
     }
 
     pub fn with_push_chain_meta(mut self, push_chain_meta: bool) -> Self {
@@ -150,3 +164,45 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tendermint_rpc::{MockClient, MockRequestMethodMatcher};
+
+    use super::bundle::contracts_path;
+    use super::store::memory::MemoryBlockstore;
+    use super::upgrades::UpgradeScheduler;
+    use super::FvmMessageInterpreter;
+
+    fn make_interpreter(
+        gas_overestimation_rate: f64,
+        gas_search_step: f64,
+    ) -> anyhow::Result<FvmMessageInterpreter<MemoryBlockstore, MockClient<MockRequestMethodMatcher>>>
+    {
+        let (client, _) = MockClient::new(MockRequestMethodMatcher::default());
+        FvmMessageInterpreter::new(
+            client,
+            None,
+            contracts_path(),
+            gas_overestimation_rate,
+            gas_search_step,
+            false,
+            UpgradeScheduler::new(),
+        )
+    }
+
+    #[test]
+    fn test_gas_rates_validated() {
+        assert!(make_interpreter(1.05, 1.05).is_ok());
+        assert!(make_interpreter(1.0, 1.05).is_ok());
+
+        let err = make_interpreter(0.9, 1.05).err().unwrap();
+        assert!(err.to_string().contains("overestimation"), "{err}");
+
+        let err = make_interpreter(1.05, 0.9).err().unwrap();
+        assert!(err.to_string().contains("search step"), "{err}");
+
+        assert!(make_interpreter(1.05, 1.0).is_err());
+        assert!(make_interpreter(f64::NAN, 1.05).is_err());
+    }
+}
@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 use async_trait::async_trait;
 use cid::Cid;
-use fendermint_vm_message::query::{ActorState, FvmQuery, GasEstimate, StateParams};
+use fendermint_vm_message::query::{
+    ActorState, BlockHashLookup, FvmQuery, GasEstimate, StateParams,
+};
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::{
@@ -31,6 +33,8 @@ pub enum FvmQueryRet {
     StateParams(StateParams),
     /// Builtin actors known by the system.
     BuiltinActors(Vec<(String, Cid)>),
+    /// Block hash recorded by the chain metadata actor.
+    BlockHash(BlockHashLookup),
 }
 
 #[async_trait]
@@ -155,6 +159,17 @@ where
                 let (state, ret) = state.builtin_actors().await?;
                 Ok((state, FvmQueryRet::BuiltinActors(ret)))
             }
+            FvmQuery::BlockHash(height) => {
+                let (state, ret) = state.block_hash(height).await?;
+                tracing::info!(
+                    height = state.block_height(),
+                    pending = state.pending(),
+                    block_height = height,
+                    found = matches!(ret, BlockHashLookup::Found(_)),
+                    "query block hash"
+                );
+                Ok((state, FvmQueryRet::BlockHash(ret)))
+            }
         }
     }
 }
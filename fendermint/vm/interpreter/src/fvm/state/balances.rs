@@ -0,0 +1,45 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use anyhow::Context;
+use cid::Cid;
+use fendermint_vm_actor_interface::burntfunds::BURNT_FUNDS_ACTOR_ID;
+use fvm::state_tree::StateTree;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+
+/// The sum of the balances of all actors in a state tree.
+///
+/// This is not the circulating supply: it doesn't account for the gas burnt since genesis,
+/// which sits in the burnt funds actor together with the released tokens, nor for the
+/// supply source of the subnet, e.g. tokens locked in an ERC20 contract on the parent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BalanceTotals {
+    /// Sum of the balances of all actors, except the burnt funds actor.
+    pub balances: TokenAmount,
+    /// Balance of the burnt funds actor.
+    pub burnt: TokenAmount,
+}
+
+/// Sum the balances of all actors in the state tree with the given root.
+///
+/// This iterates over the whole state tree, so it is meant for offline diagnostics only.
+pub fn sum_balances<DB: Blockstore>(store: DB, state_root: &Cid) -> anyhow::Result<BalanceTotals> {
+    let state_tree =
+        StateTree::new_from_root(store, state_root).context("failed to load state tree")?;
+
+    let burnt_funds = Address::new_id(BURNT_FUNDS_ACTOR_ID);
+    let mut totals = BalanceTotals::default();
+
+    state_tree.for_each(|addr, state| {
+        if addr == burnt_funds {
+            totals.burnt += state.balance.clone();
+        } else {
+            totals.balances += state.balance.clone();
+        }
+        Ok(())
+    })?;
+
+    Ok(totals)
+}
@@ -1,20 +1,25 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 use crate::fvm::store::ReadOnlyBlockstore;
-use anyhow::anyhow;
-use cid::Cid;
-use fendermint_actor_cetf::{BlockHeight, Tag};
+use anyhow::{anyhow, Context};
+use cid::{multihash::Code, Cid};
+use fendermint_actor_cetf::{
+    state_v1, BlockHeight, Tag, DEFAULT_MAX_PENDING_TAGS, DEFAULT_MAX_TAGS_PER_HEIGHT,
+    DEFAULT_MIN_TAG_DISTANCE,
+};
 use fendermint_vm_actor_interface::cetf::CETFSYSCALL_ACTOR_ID;
 use fvm::state_tree::StateTree;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::CborStore;
 
-/// Reads the CETF system actor state to retrieve the tag
-pub fn get_tag_at_height<DB: Blockstore + Clone + 'static>(
+use super::FvmExecState;
+
+/// Reads the CETF system actor state to retrieve the tags to be signed at a height, in order.
+pub fn get_tags_at_height<DB: Blockstore + Clone + 'static>(
     db: DB,
     state_root: &Cid,
     height: BlockHeight,
-) -> anyhow::Result<Option<Tag>> {
+) -> anyhow::Result<Vec<Tag>> {
     let bstore = ReadOnlyBlockstore::new(db);
     let state_tree = StateTree::new_from_root(&bstore, state_root)?;
 
@@ -36,7 +41,8 @@ pub fn get_tag_at_height<DB: Blockstore + Clone + 'static>(
         }
     };
 
-    // get the actor state from the blockstore
+    // get the actor state from the blockstore; chains which haven't migrated it yet still have
+    // the layout of the first version of the actor, with a single tag per height
     let actor_state: fendermint_actor_cetf::State =
         match state_tree.store().get_cbor(&actor_state_cid) {
             Ok(Some(v)) => v,
@@ -46,14 +52,68 @@ pub fn get_tag_at_height<DB: Blockstore + Clone + 'static>(
                     CETFSYSCALL_ACTOR_ID
                 ));
             }
-            Err(err) => {
-                return Err(anyhow!(
-                    "failed to get CETF actor ({}) state, error: {}",
-                    CETFSYSCALL_ACTOR_ID,
-                    err
-                ));
-            }
+            Err(err) => match state_tree
+                .store()
+                .get_cbor::<state_v1::State>(&actor_state_cid)
+            {
+                Ok(Some(v1)) => {
+                    return Ok(v1.get_tag_at_height(&bstore, height)?.into_iter().collect());
+                }
+                _ => {
+                    return Err(anyhow!(
+                        "failed to get CETF actor ({}) state, error: {}",
+                        CETFSYSCALL_ACTOR_ID,
+                        err
+                    ));
+                }
+            },
         };
 
-    Ok(actor_state.get_tag_at_height(&bstore, &height)?)
+    Ok(actor_state.get_tags_at_height(&bstore, height)?)
+}
+
+/// Migrate the CETF actor state from the layout of the first version of the actor, which
+/// scheduled a single tag per height, to the current one, with the default limits.
+///
+/// A state which is already in the current layout is left alone. Only the state is migrated;
+/// the actor code reading it has to be replaced in the same upgrade.
+pub fn migrate_state_v1<DB>(state: &mut FvmExecState<DB>) -> anyhow::Result<()>
+where
+    DB: Blockstore + Clone + 'static,
+{
+    let state_tree = state.state_tree_mut();
+    let mut actor = state_tree.get_actor(CETFSYSCALL_ACTOR_ID)?.ok_or_else(|| {
+        anyhow!(
+            "CETF actor id ({}) not found in state",
+            CETFSYSCALL_ACTOR_ID
+        )
+    })?;
+
+    let store = state_tree.store();
+    if let Ok(Some(_)) = store.get_cbor::<fendermint_actor_cetf::State>(&actor.state) {
+        tracing::info!("CETF actor state is already in the current layout");
+        return Ok(());
+    }
+    let old: state_v1::State = store
+        .get_cbor(&actor.state)
+        .context("failed to decode the CETF actor state")?
+        .ok_or_else(|| anyhow!("CETF actor ({}) state not found", CETFSYSCALL_ACTOR_ID))?;
+
+    let new = fendermint_actor_cetf::State::migrate_v1(
+        store,
+        &old,
+        DEFAULT_MAX_PENDING_TAGS,
+        DEFAULT_MAX_TAGS_PER_HEIGHT,
+        DEFAULT_MIN_TAG_DISTANCE,
+    )?;
+    actor.state = store
+        .put_cbor(&new, Code::Blake2b256)
+        .context("failed to store the CETF actor state")?;
+
+    tracing::info!(
+        pending_tags = new.pending_tags,
+        "migrated the CETF actor state from the first version"
+    );
+    state_tree.set_actor(CETFSYSCALL_ACTOR_ID, actor);
+    Ok(())
 }
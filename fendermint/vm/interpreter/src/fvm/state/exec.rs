@@ -113,6 +113,9 @@ where
 
     /// Indicate whether the parameters have been updated.
     params_dirty: bool,
+
+    /// Name of the upgrade executed in this block, if any, for the application to record.
+    executed_upgrade: Option<String>,
 }
 
 impl<DB> FvmExecState<DB>
@@ -159,6 +162,7 @@ where
                 power_scale: params.power_scale,
             },
             params_dirty: false,
+            executed_upgrade: None,
         })
     }
 
@@ -223,6 +227,16 @@ where
         self.validator_id
     }
 
+    /// Name of the upgrade executed in this block, if it was given one.
+    pub fn executed_upgrade(&self) -> Option<&str> {
+        self.executed_upgrade.as_deref()
+    }
+
+    /// Remember the name of the upgrade executed in this block.
+    pub fn set_executed_upgrade(&mut self, name: impl ToString) {
+        self.executed_upgrade = Some(name.to_string());
+    }
+
     /// The timestamp of the currently executing block.
     pub fn timestamp(&self) -> Timestamp {
         Timestamp(self.executor.context().timestamp)
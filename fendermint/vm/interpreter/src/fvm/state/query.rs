@@ -7,11 +7,12 @@ use std::{cell::RefCell, sync::Arc};
 use anyhow::{anyhow, Context};
 
 use cid::Cid;
+use fendermint_vm_actor_interface::chainmetadata::CHAINMETADATA_ACTOR_ADDR;
 use fendermint_vm_actor_interface::system::{
     is_system_addr, State as SystemState, SYSTEM_ACTOR_ADDR,
 };
 use fendermint_vm_core::chainid::HasChainID;
-use fendermint_vm_message::query::ActorState;
+use fendermint_vm_message::query::{ActorState, BlockHashLookup};
 use fvm::engine::MultiEngine;
 use fvm::executor::ApplyRet;
 use fvm::state_tree::StateTree;
@@ -235,6 +236,36 @@ where
         Ok((s, ret))
     }
 
+    /// Look up the hash of a block in the chain metadata actor.
+    ///
+    /// Heights which are older than the lookback window of the actor are reported as pruned.
+    pub async fn block_hash(self, height: ChainEpoch) -> anyhow::Result<(Self, BlockHashLookup)> {
+        let (s, meta_state) = {
+            let (s, state) = self.actor_state(&CHAINMETADATA_ACTOR_ADDR).await?;
+            (s, state.ok_or(anyhow!("no chain metadata actor"))?.1)
+        };
+        let state: fendermint_actor_chainmetadata::State = s
+            .store
+            .get_cbor(&meta_state.state)
+            .context("failed to get chain metadata state")?
+            .ok_or(anyhow!("chain metadata actor state not found"))?;
+
+        if height < 0 {
+            return Ok((s, BlockHashLookup::NotFound));
+        }
+
+        let ret = match state.get_block_hash(&s.store, height)? {
+            Some(hash) => BlockHashLookup::Found(hash),
+            None if height.saturating_add(state.lookback_len as ChainEpoch) <= s.block_height => {
+                BlockHashLookup::Pruned {
+                    lookback_len: state.lookback_len,
+                }
+            }
+            None => BlockHashLookup::NotFound,
+        };
+        Ok((s, ret))
+    }
+
     pub fn block_height(&self) -> ChainEpoch {
         self.block_height
     }
@@ -1,15 +1,17 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
 
-use anyhow::bail;
+use anyhow::{anyhow, bail, Context};
 use fendermint_vm_core::chainid;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::chainid::ChainID;
+use serde::{Deserialize, Serialize};
 use std::collections::btree_map::Entry::{Occupied, Vacant};
 
-use super::state::{snapshot::BlockHeight, FvmExecState};
+use super::state::{cetf, snapshot::BlockHeight, FvmExecState};
 
 #[derive(PartialEq, Eq, Clone)]
 struct UpgradeKey(ChainID, BlockHeight);
@@ -49,6 +51,8 @@ where
     new_app_version: Option<u64>,
     /// the migration function to be executed
     migration: MigrationFunc<DB>,
+    /// the name of the upgrade, if it was given one
+    name: Option<String>,
 }
 
 impl<DB> Upgrade<DB>
@@ -66,6 +70,7 @@ where
             block_height,
             new_app_version,
             migration,
+            name: None,
         })
     }
 
@@ -80,9 +85,28 @@ where
             block_height,
             new_app_version,
             migration,
+            name: None,
         }
     }
 
+    /// Give the upgrade a name, so it can be identified in the logs and the executed upgrade records.
+    pub fn with_name(mut self, name: impl ToString) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn block_height(&self) -> BlockHeight {
+        self.block_height
+    }
+
+    pub fn new_app_version(&self) -> Option<u64> {
+        self.new_app_version
+    }
+
     pub fn execute(&self, state: &mut FvmExecState<DB>) -> anyhow::Result<Option<u64>> {
         (self.migration)(state)?;
 
@@ -143,8 +167,261 @@ where
 
     // check if there is an upgrade scheduled for the given chain_id at a given height
     pub fn get(&self, chain_id: ChainID, height: BlockHeight) -> Option<&Upgrade<DB>> {
+        self.upgrade_at(chain_id, height)
+    }
+
+    /// The upgrade scheduled for the given chain at the given height, if any.
+    pub fn upgrade_at(&self, chain_id: ChainID, height: BlockHeight) -> Option<&Upgrade<DB>> {
         self.upgrades.get(&UpgradeKey(chain_id, height))
     }
+
+    /// The chains and heights of all scheduled upgrades, ordered by chain ID and then by height.
+    pub fn scheduled_upgrades(&self) -> Vec<(ChainID, BlockHeight)> {
+        self.upgrades
+            .keys()
+            .map(|UpgradeKey(chain_id, height)| (*chain_id, *height))
+            .collect()
+    }
+
+    /// Add all the upgrades described in a file, resolving their migrations in the registry.
+    pub fn add_file(
+        &mut self,
+        file: &UpgradeFile,
+        registry: &MigrationRegistry<DB>,
+    ) -> anyhow::Result<()> {
+        file.validate()?;
+
+        let chain_id = file.chain_id()?;
+
+        for spec in file.upgrades.iter() {
+            let migration = registry.get(&spec.migration).ok_or_else(|| {
+                anyhow!(
+                    "upgrade {} refers to unknown migration {}",
+                    spec.name,
+                    spec.migration
+                )
+            })?;
+
+            let upgrade = Upgrade::new_by_id(chain_id, spec.height, spec.app_version, migration)
+                .with_name(&spec.name);
+
+            self.add(upgrade)
+                .with_context(|| format!("failed to add upgrade {}", spec.name))?;
+        }
+        Ok(())
+    }
+}
+
+/// Registry of the migrations that the upgrades in an [UpgradeFile] can refer to by their identifier.
+#[derive(Clone)]
+pub struct MigrationRegistry<DB>
+where
+    DB: Blockstore + 'static + Clone,
+{
+    migrations: HashMap<String, MigrationFunc<DB>>,
+}
+
+/// Identifier of the built-in migration which doesn't change the state,
+/// for upgrades which only bump the application version.
+pub const NOOP_MIGRATION: &str = "noop";
+
+/// Identifier of the built-in migration of the CETF actor state from the layout with
+/// a single tag per height to the tag queue with the pending tag limits.
+pub const CETF_STATE_V2_MIGRATION: &str = "cetf-state-v2";
+
+impl<DB> Default for MigrationRegistry<DB>
+where
+    DB: Blockstore + 'static + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<DB> MigrationRegistry<DB>
+where
+    DB: Blockstore + 'static + Clone,
+{
+    /// Create a registry with the built-in migrations.
+    pub fn new() -> Self {
+        let mut migrations: HashMap<String, MigrationFunc<DB>> = HashMap::new();
+        migrations.insert(NOOP_MIGRATION.to_string(), |_| Ok(()));
+        migrations.insert(
+            CETF_STATE_V2_MIGRATION.to_string(),
+            cetf::migrate_state_v1::<DB>,
+        );
+        Self { migrations }
+    }
+
+    /// Register a migration under a unique identifier.
+    pub fn register(
+        &mut self,
+        id: impl ToString,
+        migration: MigrationFunc<DB>,
+    ) -> anyhow::Result<()> {
+        let id = id.to_string();
+        if self.migrations.contains_key(&id) {
+            bail!("migration {id} already registered");
+        }
+        self.migrations.insert(id, migration);
+        Ok(())
+    }
+
+    /// Look up a migration by its identifier.
+    pub fn get(&self, id: &str) -> Option<MigrationFunc<DB>> {
+        self.migrations.get(id).copied()
+    }
+}
+
+/// A single upgrade as described in an [UpgradeFile].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UpgradeSpec {
+    /// Unique name of the upgrade.
+    pub name: String,
+    /// The block height at which the upgrade is executed.
+    pub height: BlockHeight,
+    /// The application version after the upgrade, if it changes.
+    pub app_version: Option<u64>,
+    /// Identifier of the migration function in the [MigrationRegistry].
+    pub migration: String,
+}
+
+/// Upgrades described in a file, typically `upgrades.toml` in the config directory,
+/// so that data-driven upgrades can be coordinated without shipping a new binary.
+///
+/// ```toml
+/// chain_name = "mychain"
+///
+/// [[upgrades]]
+/// name = "bump-app-version"
+/// height = 1000
+/// app_version = 1
+/// migration = "noop"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UpgradeFile {
+    /// Name of the chain the upgrades are meant for.
+    pub chain_name: String,
+    /// Upgrades in the order of their heights.
+    #[serde(default)]
+    pub upgrades: Vec<UpgradeSpec>,
+}
+
+impl UpgradeFile {
+    /// Parse and validate the TOML contents of an upgrade file.
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let file: Self = toml::from_str(contents).context("failed to parse upgrades")?;
+        file.validate()?;
+        Ok(file)
+    }
+
+    /// Read an upgrade file, if it exists.
+    pub fn load(path: &Path) -> anyhow::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read upgrades from {path:?}"))?;
+
+        Self::parse(&contents)
+            .with_context(|| format!("invalid upgrades in {path:?}"))
+            .map(Some)
+    }
+
+    /// The ID of the chain the upgrades are for.
+    pub fn chain_id(&self) -> anyhow::Result<ChainID> {
+        chainid::from_str_hashed(&self.chain_name)
+            .with_context(|| format!("invalid chain name: {}", self.chain_name))
+    }
+
+    /// Check that the heights are strictly increasing and the names are unique.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut names = HashSet::new();
+        let mut last_height = None;
+        for spec in self.upgrades.iter() {
+            if !names.insert(&spec.name) {
+                bail!("duplicate upgrade name: {}", spec.name);
+            }
+            if let Some(last_height) = last_height {
+                if spec.height <= last_height {
+                    bail!(
+                        "upgrade heights have to be strictly increasing: {} at {} follows {}",
+                        spec.name,
+                        spec.height,
+                        last_height
+                    );
+                }
+            }
+            last_height = Some(spec.height);
+        }
+        Ok(())
+    }
+
+    /// Check that the upgrades are meant for the chain with the given ID, which comes from the genesis.
+    ///
+    /// Otherwise the scheduler would silently ignore all of them.
+    pub fn check_chain_id(&self, chain_id: ChainID) -> anyhow::Result<()> {
+        let file_chain_id = self.chain_id()?;
+        if file_chain_id != chain_id {
+            bail!(
+                "upgrades are for chain {} with ID {}, but the chain ID is {}",
+                self.chain_name,
+                u64::from(file_chain_id),
+                u64::from(chain_id)
+            );
+        }
+        Ok(())
+    }
+
+    /// Check that none of the upgrades are scheduled at or below the last committed block height,
+    /// which would mean they will never be executed, unless they have already been executed.
+    ///
+    /// Upgrades at or below the height where the history starts are not checked, because
+    /// they happened before this node kept track of them, e.g. it was restored from a snapshot.
+    pub fn check_pending(
+        &self,
+        committed_height: BlockHeight,
+        history: &UpgradeHistory,
+    ) -> anyhow::Result<()> {
+        for spec in self.upgrades.iter() {
+            if spec.height > committed_height || spec.height <= history.since {
+                continue;
+            }
+            let is_executed = history
+                .executed
+                .iter()
+                .any(|e| e.name == spec.name && e.block_height == spec.height);
+
+            if !is_executed {
+                bail!(
+                    "upgrade {} at height {} is in the past; the last committed height is {}",
+                    spec.name,
+                    spec.height,
+                    committed_height
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Record of an upgrade which has been executed, kept by the application.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutedUpgrade {
+    pub name: String,
+    pub block_height: BlockHeight,
+}
+
+/// The upgrades executed by the application since it started keeping track of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpgradeHistory {
+    /// The block height from which on the executed upgrades are recorded, exclusive.
+    ///
+    /// Zero for nodes which executed the chain from genesis, otherwise the height of
+    /// the snapshot the node was restored from, or where it started to keep a history.
+    pub since: BlockHeight,
+    /// The upgrades executed after `since`, in the order of their heights.
+    pub executed: Vec<ExecutedUpgrade>,
 }
 
 #[test]
@@ -171,3 +448,166 @@ fn test_validate_upgrade_schedule() {
     assert!(upgrade_scheduler.get(mychain_id, 10).is_some());
     assert!(upgrade_scheduler.get(otherhain_id, 10).is_none());
 }
+
+#[test]
+fn test_scheduled_upgrades() {
+    use crate::fvm::store::memory::MemoryBlockstore;
+
+    let mut upgrade_scheduler: UpgradeScheduler<MemoryBlockstore> = UpgradeScheduler::new();
+    assert!(upgrade_scheduler.scheduled_upgrades().is_empty());
+
+    // added out of order on purpose
+    let upgrade = Upgrade::new("mychain", 20, None, |_state| Ok(()))
+        .unwrap()
+        .with_name("second");
+    upgrade_scheduler.add(upgrade).unwrap();
+
+    let upgrade = Upgrade::new("mychain", 10, Some(1), |_state| Ok(()))
+        .unwrap()
+        .with_name("first");
+    upgrade_scheduler.add(upgrade).unwrap();
+
+    let mychain_id = chainid::from_str_hashed("mychain").unwrap();
+
+    assert_eq!(
+        upgrade_scheduler.scheduled_upgrades(),
+        vec![(mychain_id, 10), (mychain_id, 20)]
+    );
+
+    let upgrade = upgrade_scheduler.upgrade_at(mychain_id, 10).unwrap();
+    assert_eq!(upgrade.name(), Some("first"));
+    assert_eq!(upgrade.new_app_version(), Some(1));
+
+    let upgrade = upgrade_scheduler.upgrade_at(mychain_id, 20).unwrap();
+    assert_eq!(upgrade.name(), Some("second"));
+    assert_eq!(upgrade.new_app_version(), None);
+
+    assert!(upgrade_scheduler.upgrade_at(mychain_id, 15).is_none());
+}
+
+#[cfg(test)]
+mod tests {
+    use fendermint_vm_core::chainid;
+
+    use super::{
+        ExecutedUpgrade, MigrationRegistry, UpgradeFile, UpgradeHistory, UpgradeScheduler,
+    };
+    use crate::fvm::store::memory::MemoryBlockstore;
+
+    const UPGRADES: &str = r#"
+        chain_name = "mychain"
+
+        [[upgrades]]
+        name = "first"
+        height = 10
+        app_version = 1
+        migration = "noop"
+
+        [[upgrades]]
+        name = "second"
+        height = 20
+        migration = "patch"
+    "#;
+
+    #[test]
+    fn parse_upgrade_file() {
+        let file = UpgradeFile::parse(UPGRADES).expect("failed to parse");
+        assert_eq!(file.chain_name, "mychain");
+        assert_eq!(file.upgrades.len(), 2);
+        assert_eq!(file.upgrades[0].name, "first");
+        assert_eq!(file.upgrades[0].height, 10);
+        assert_eq!(file.upgrades[0].app_version, Some(1));
+        assert_eq!(file.upgrades[1].app_version, None);
+        assert_eq!(file.upgrades[1].migration, "patch");
+    }
+
+    #[test]
+    fn reject_duplicate_heights() {
+        let upgrades = UPGRADES.replace("height = 20", "height = 10");
+        let err = UpgradeFile::parse(&upgrades).expect_err("should fail");
+        assert!(err.to_string().contains("strictly increasing"));
+    }
+
+    #[test]
+    fn reject_decreasing_heights() {
+        let upgrades = UPGRADES.replace("height = 20", "height = 5");
+        assert!(UpgradeFile::parse(&upgrades).is_err());
+    }
+
+    #[test]
+    fn reject_duplicate_names() {
+        let upgrades = UPGRADES.replace("\"second\"", "\"first\"");
+        let err = UpgradeFile::parse(&upgrades).expect_err("should fail");
+        assert!(err.to_string().contains("duplicate"));
+    }
+
+    #[test]
+    fn check_pending_upgrades() {
+        let file = UpgradeFile::parse(UPGRADES).unwrap();
+
+        let history = UpgradeHistory::default();
+        file.check_pending(5, &history)
+            .expect("all upgrades are in the future");
+        file.check_pending(15, &history)
+            .expect_err("the first upgrade was missed");
+
+        let history = UpgradeHistory {
+            since: 0,
+            executed: vec![ExecutedUpgrade {
+                name: "first".into(),
+                block_height: 10,
+            }],
+        };
+        file.check_pending(15, &history)
+            .expect("the first upgrade was executed");
+        file.check_pending(20, &history)
+            .expect_err("the second upgrade was missed");
+    }
+
+    #[test]
+    fn check_pending_upgrades_without_history() {
+        let file = UpgradeFile::parse(UPGRADES).unwrap();
+
+        // Restored from a snapshot at height 15, after the first upgrade.
+        let history = UpgradeHistory {
+            since: 15,
+            executed: vec![],
+        };
+        file.check_pending(15, &history)
+            .expect("the first upgrade happened before the snapshot");
+        file.check_pending(20, &history)
+            .expect_err("the second upgrade was missed");
+    }
+
+    #[test]
+    fn check_upgrades_chain_id() {
+        let file = UpgradeFile::parse(UPGRADES).unwrap();
+
+        file.check_chain_id(chainid::from_str_hashed("mychain").unwrap())
+            .expect("the chain matches");
+        file.check_chain_id(chainid::from_str_hashed("otherchain").unwrap())
+            .expect_err("the chain doesn't match");
+    }
+
+    #[test]
+    fn add_upgrades_from_file() {
+        let file = UpgradeFile::parse(UPGRADES).unwrap();
+        let mut registry = MigrationRegistry::<MemoryBlockstore>::new();
+        let mut scheduler = UpgradeScheduler::new();
+
+        scheduler
+            .add_file(&file, &registry)
+            .expect_err("the patch migration is not registered");
+
+        registry.register("patch", |_| Ok(())).unwrap();
+        let mut scheduler = UpgradeScheduler::new();
+        scheduler.add_file(&file, &registry).unwrap();
+
+        let chain_id = chainid::from_str_hashed("mychain").unwrap();
+        let upgrade = scheduler.get(chain_id, 10).expect("upgrade at 10");
+        assert_eq!(upgrade.name(), Some("first"));
+        assert_eq!(upgrade.new_app_version(), Some(1));
+        assert!(scheduler.get(chain_id, 20).is_some());
+        assert!(scheduler.get(chain_id, 15).is_none());
+    }
+}
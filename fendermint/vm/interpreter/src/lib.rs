@@ -142,6 +142,32 @@ pub trait ExecInterpreter: Sync + Send {
         msg: Self::Message,
     ) -> anyhow::Result<(Self::State, Self::DeliverOutput)>;
 
+    /// Apply multiple messages onto the state, e.g. when replaying blocks to catch up.
+    ///
+    /// The outputs must be in the same order as the messages, each message being applied
+    /// onto the state left by the previous one, just as if they were delivered one by one.
+    /// Processing stops at the first error, which is returned as is.
+    ///
+    /// The default implementation folds over [`ExecInterpreter::deliver`]; interpreters
+    /// which can apply messages more efficiently in bulk can override it.
+    async fn deliver_batch(
+        &self,
+        state: Self::State,
+        msgs: Vec<Self::Message>,
+    ) -> anyhow::Result<(Self::State, Vec<Self::DeliverOutput>)>
+    where
+        Self::DeliverOutput: Send,
+    {
+        let mut state = state;
+        let mut outputs = Vec::with_capacity(msgs.len());
+        for msg in msgs {
+            let (s, out) = self.deliver(state, msg).await?;
+            state = s;
+            outputs.push(out);
+        }
+        Ok((state, outputs))
+    }
+
     /// Called once at the end of a block.
     ///
     /// This is where we can apply end-of-epoch processing, for example to process staking
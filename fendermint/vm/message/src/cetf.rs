@@ -9,7 +9,13 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[allow(clippy::large_enum_variant)]
 pub enum CetfMessage {
+    /// Aggregated signature of the only tag scheduled at a height.
+    ///
+    /// This is the message of the first version of the CETF actor, which scheduled a single tag
+    /// per height; it is still used for heights with a single tag so blocks remain compatible.
     CetfTag(u64, BlsSignature),
+    /// Aggregated signatures of all the tags scheduled at a height, in the order they were enqueued.
+    CetfTags(u64, Vec<BlsSignature>),
 
     // BlockHeightTag(u64, BlsSignature),
 }
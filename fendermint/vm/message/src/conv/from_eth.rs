@@ -3,7 +3,7 @@
 
 //! Helper methods to convert between Ethereum and FVM data formats.
 
-use ethers_core::types::{Eip1559TransactionRequest, NameOrAddress, H160, U256};
+use ethers_core::types::{self as et, Eip1559TransactionRequest, NameOrAddress, H160, U256};
 use fendermint_vm_actor_interface::{
     eam::{self, EthAddress},
     evm,
@@ -12,10 +12,13 @@ use fvm_ipld_encoding::{BytesSer, RawBytes};
 use fvm_shared::{
     address::Address,
     bigint::{BigInt, Sign},
+    crypto::signature::Signature,
     econ::TokenAmount,
     message::Message,
 };
 
+use crate::signed::{SignatureScheme, SignedMessage};
+
 // https://github.com/filecoin-project/lotus/blob/594c52b96537a8c8728389b446482a2d7ea5617c/chain/types/ethtypes/eth_transactions.go#L152
 pub fn to_fvm_message(tx: &Eip1559TransactionRequest) -> anyhow::Result<Message> {
     // FIP-55 says that we should use `InvokeContract` for transfers instead of `METHOD_SEND`,
@@ -74,6 +77,26 @@ pub fn to_fvm_tokens(value: &U256) -> TokenAmount {
     TokenAmount::from_atto(atto)
 }
 
+/// Turn a signature produced by an Ethereum wallet into the format the FVM expects,
+/// where the recovery ID is 0 or 1, rather than the legacy 27 or 28.
+pub fn to_fvm_signature(sig: &et::Signature) -> Signature {
+    let mut bz = sig.to_vec();
+    if bz[64] >= 27 {
+        bz[64] -= 27;
+    }
+    Signature::new_secp256k1(bz)
+}
+
+/// Wrap a message signed by a wallet with `eth_signTypedData_v4`, over the typed-data
+/// returned by [crate::eip712::typed_data], into an envelope that can be sent to the chain.
+pub fn to_eip712_signed_message(message: Message, sig: &et::Signature) -> SignedMessage {
+    SignedMessage {
+        message,
+        signature: to_fvm_signature(sig),
+        scheme: SignatureScheme::Eip712,
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -119,10 +142,10 @@ mod tests {
         let (tx0, sig) = TypedTransaction::decode_signed(&rlp).expect("decode signed tx");
         let chain_id: ChainID = tx0.chain_id().unwrap().as_u64().into();
 
-        let msg = SignedMessage {
-            message: to_fvm_message(tx0.as_eip1559_ref().unwrap()).expect("to_fvm_message"),
-            signature: Signature::new_secp256k1(sig.to_vec()),
-        };
+        let msg = SignedMessage::new_unchecked(
+            to_fvm_message(tx0.as_eip1559_ref().unwrap()).expect("to_fvm_message"),
+            Signature::new_secp256k1(sig.to_vec()),
+        );
 
         let domain_hash = msg.domain_hash(&chain_id).expect("domain_hash");
 
@@ -7,7 +7,9 @@ use std::str::FromStr;
 
 use anyhow::anyhow;
 use anyhow::bail;
+use cid::multihash::MultihashDigest;
 use ethers_core::types as et;
+use ethers_core::types::transaction::eip2718::TypedTransaction;
 use fendermint_crypto::{RecoveryId, Signature};
 use fendermint_vm_actor_interface::eam::EthAddress;
 use fendermint_vm_actor_interface::eam::EAM_ACTOR_ID;
@@ -22,6 +24,8 @@ use fvm_shared::message::Message;
 use fvm_shared::{address::Payload, econ::TokenAmount};
 use lazy_static::lazy_static;
 
+use crate::signed::SignedMessage;
+
 lazy_static! {
     pub static ref MAX_U256: BigInt = BigInt::from_str(&et::U256::MAX.to_string()).unwrap();
 }
@@ -136,12 +140,29 @@ pub fn to_eth_transaction_request(
     Ok(tx)
 }
 
+/// Calculate the hash Ethereum tools know a transaction by, which is the Keccak256
+/// hash of the RLP encoded signed transaction reconstructed from the FVM message.
+///
+/// Only makes sense for messages sent from an Ethereum account to an address
+/// that can be converted to an Ethereum one.
+pub fn eth_tx_hash(msg: &SignedMessage, chain_id: &ChainID) -> anyhow::Result<[u8; 32]> {
+    let tx: TypedTransaction = to_eth_transaction_request(msg.message(), chain_id)?.into();
+    let sig = to_eth_signature(msg.signature(), true)?;
+    let rlp = tx.rlp_signed(&sig);
+
+    let hash = cid::multihash::Code::Keccak256.digest(&rlp);
+    let hash = hash.digest().try_into().expect("Keccak256 is 32 bytes");
+
+    Ok(hash)
+}
+
 #[cfg(test)]
 pub mod tests {
 
     use std::str::FromStr;
 
     use ethers::signers::{Signer, Wallet};
+    use ethers_core::types::{Bytes, TxHash};
     use ethers_core::utils::rlp;
     use ethers_core::{k256::ecdsa::SigningKey, types::transaction::eip2718::TypedTransaction};
     use fendermint_crypto::SecretKey;
@@ -157,7 +178,7 @@ pub mod tests {
         tests::{EthMessage, KeyPair},
     };
 
-    use super::{to_eth_signature, to_eth_tokens, to_eth_transaction_request};
+    use super::{eth_tx_hash, to_eth_signature, to_eth_tokens, to_eth_transaction_request};
 
     #[quickcheck]
     fn prop_to_eth_tokens(tokens: ArbTokenAmount) -> bool {
@@ -206,6 +227,30 @@ pub mod tests {
         Ok(())
     }
 
+    /// Check that the hash of a transaction signed by ethers can be reconstructed from the FVM message.
+    #[test]
+    fn test_eth_tx_hash() {
+        let expected_hash: TxHash =
+            "0x8fe4fd8e1c7c40dceed249c99a553bc218774f611cfefd8a48ede67b8f6e4725"
+                .parse()
+                .unwrap();
+
+        let raw_tx: Bytes = "0x02f86e87084472af917f2a8080808502540be400948ed26a19f0e0d6708546495611e9a298d9befb598203e880c080a0a37d03d98e50622ec3744ee368565c5e9469852a1d9111197608135928cd2430a010d1575c68602c96c89e9ec30fade44f5844bf34226044d2931afc60b0a8b2de".parse().unwrap();
+
+        let rlp = rlp::Rlp::new(&raw_tx);
+        let (tx, sig) = TypedTransaction::decode_signed(&rlp).expect("decode signed tx");
+        let chain_id: ChainID = tx.chain_id().unwrap().as_u64().into();
+
+        let msg = crate::signed::SignedMessage::new_unchecked(
+            to_fvm_message(tx.as_eip1559_ref().unwrap()).expect("to_fvm_message"),
+            Signature::new_secp256k1(sig.to_vec()),
+        );
+
+        let hash = eth_tx_hash(&msg, &chain_id).expect("eth_tx_hash");
+
+        assert_eq!(hash, expected_hash.0);
+    }
+
     #[quickcheck]
     fn prop_to_and_from_eth_transaction(msg: EthMessage, chain_id: u64) {
         let chain_id = ChainID::from(chain_id);
@@ -244,10 +289,7 @@ pub mod tests {
         let tx1 = tx1.as_eip1559_ref().expect("not an eip1559 transaction");
         let msg1 = to_fvm_message(tx1).expect("to_fvm_message failed");
 
-        let signed = SignedMessage {
-            message: msg1,
-            signature: Signature::new_secp256k1(sig.to_vec()),
-        };
+        let signed = SignedMessage::new_unchecked(msg1, Signature::new_secp256k1(sig.to_vec()));
 
         signed.verify(&chain_id).expect("signature should be valid")
     }
@@ -0,0 +1,204 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! EIP-712 typed-data representation of FVM messages.
+//!
+//! This allows wallets such as MetaMask, which can't sign arbitrary FVM messages,
+//! to sign subnet-native transactions with `eth_signTypedData_v4`, while displaying
+//! the fields of the message to the user.
+//!
+//! See <https://eips.ethereum.org/EIPS/eip-712>
+
+use anyhow::{anyhow, Context};
+use ethers_core::types::transaction::eip712::{Eip712, TypedData};
+use fvm_shared::address::Address;
+use fvm_shared::chainid::ChainID;
+use fvm_shared::message::Message;
+use serde_json::json;
+
+use crate::conv::from_fvm::to_eth_tokens;
+
+/// Name of the signing domain.
+pub const DOMAIN_NAME: &str = "Fendermint";
+/// Version of the signing domain; has to change if the typed-data layout does.
+pub const DOMAIN_VERSION: &str = "1";
+/// Name of the struct type describing the FVM message.
+pub const PRIMARY_TYPE: &str = "Message";
+
+/// Build the typed-data of a message, which is what is passed to wallets for signing.
+///
+/// Addresses are represented by their byte encoding rather than their string form,
+/// because the latter depends on the network (`f` or `t` prefix) the process is configured with.
+pub fn typed_data(message: &Message, chain_id: &ChainID) -> anyhow::Result<TypedData> {
+    let value = json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+            ],
+            PRIMARY_TYPE: [
+                { "name": "version", "type": "uint64" },
+                { "name": "from", "type": "bytes" },
+                { "name": "to", "type": "bytes" },
+                { "name": "sequence", "type": "uint64" },
+                { "name": "value", "type": "uint256" },
+                { "name": "methodNum", "type": "uint64" },
+                { "name": "params", "type": "bytes" },
+                { "name": "gasLimit", "type": "uint64" },
+                { "name": "gasFeeCap", "type": "uint256" },
+                { "name": "gasPremium", "type": "uint256" },
+            ],
+        },
+        "primaryType": PRIMARY_TYPE,
+        "domain": {
+            "name": DOMAIN_NAME,
+            "version": DOMAIN_VERSION,
+            "chainId": u64::from(*chain_id).to_string(),
+        },
+        "message": {
+            "version": message.version.to_string(),
+            "from": address_hex(&message.from),
+            "to": address_hex(&message.to),
+            "sequence": message.sequence.to_string(),
+            "value": to_eth_tokens(&message.value)?.to_string(),
+            "methodNum": message.method_num.to_string(),
+            "params": format!("0x{}", hex::encode(message.params.bytes())),
+            "gasLimit": message.gas_limit.to_string(),
+            "gasFeeCap": to_eth_tokens(&message.gas_fee_cap)?.to_string(),
+            "gasPremium": to_eth_tokens(&message.gas_premium)?.to_string(),
+        }
+    });
+
+    serde_json::from_value(value).context("failed to construct typed data")
+}
+
+/// Calculate the EIP-712 hash of a message, which is what the signature is over.
+pub fn hash(message: &Message, chain_id: &ChainID) -> anyhow::Result<[u8; 32]> {
+    typed_data(message, chain_id)?
+        .encode_eip712()
+        .map_err(|e| anyhow!("failed to hash typed data: {e}"))
+}
+
+fn address_hex(addr: &Address) -> String {
+    format!("0x{}", hex::encode(addr.to_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers_core::types as et;
+    use fendermint_vm_actor_interface::eam::EthAddress;
+    use fvm_shared::address::Address;
+    use fvm_shared::chainid::ChainID;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::message::Message;
+    use quickcheck_macros::quickcheck;
+
+    use crate::conv::from_eth::to_eip712_signed_message;
+    use crate::conv::tests::KeyPair;
+    use crate::signed::{SignatureScheme, SignedMessage};
+
+    /// Private key of the first default Anvil account.
+    const SECRET_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const CHAIN_ID: u64 = 1942764459484029;
+
+    fn wallet() -> LocalWallet {
+        SECRET_KEY.parse().expect("valid secret key")
+    }
+
+    fn message(from: Address) -> Message {
+        Message {
+            version: 0,
+            from,
+            to: Address::new_id(1001),
+            sequence: 7,
+            value: TokenAmount::from_whole(1),
+            method_num: 2,
+            params: vec![1, 2, 3].into(),
+            gas_limit: 10_000_000,
+            gas_fee_cap: TokenAmount::from_atto(1_000_000),
+            gas_premium: TokenAmount::from_atto(1000),
+        }
+    }
+
+    /// Sign the typed data with an `ethers` wallet, the same way `eth_signTypedData_v4` would.
+    async fn sign_with_wallet(
+        message: Message,
+        chain_id: &ChainID,
+    ) -> (et::Signature, SignedMessage) {
+        let data = super::typed_data(&message, chain_id).expect("typed data");
+        let sig = wallet()
+            .sign_typed_data(&data)
+            .await
+            .expect("failed to sign typed data");
+        let signed = to_eip712_signed_message(message, &sig);
+        (sig, signed)
+    }
+
+    /// Check that the wallet signed the same hash that we calculate.
+    #[tokio::test]
+    async fn wallet_signs_hash() {
+        let chain_id = ChainID::from(CHAIN_ID);
+        let wallet = wallet();
+        let msg = message(Address::from(EthAddress(wallet.address().0)));
+
+        let hash = super::hash(&msg, &chain_id).expect("failed to hash");
+        let (sig, _) = sign_with_wallet(msg, &chain_id).await;
+
+        let rec = sig.recover(et::H256(hash)).expect("failed to recover");
+        assert_eq!(rec, wallet.address());
+    }
+
+    #[tokio::test]
+    async fn wallet_signature_verifies() {
+        let chain_id = ChainID::from(CHAIN_ID);
+        let from = Address::from(EthAddress(wallet().address().0));
+
+        let (_, signed) = sign_with_wallet(message(from), &chain_id).await;
+        assert_eq!(signed.scheme, SignatureScheme::Eip712);
+        signed.verify(&chain_id).expect("signature should be valid");
+
+        // The signature is tied to the chain.
+        assert!(signed.verify(&ChainID::from(CHAIN_ID + 1)).is_err());
+
+        // Every field is covered by the signature, including the method.
+        let mut tampered = signed.clone();
+        tampered.message.method_num += 1;
+        assert!(tampered.verify(&chain_id).is_err());
+
+        // The typed-data signature is not valid under the native scheme.
+        let mut native = signed;
+        native.scheme = SignatureScheme::Native;
+        assert!(native.verify(&chain_id).is_err());
+    }
+
+    #[tokio::test]
+    async fn wallet_signature_fails_for_other_sender() {
+        let chain_id = ChainID::from(CHAIN_ID);
+        let from = Address::from(EthAddress([1u8; 20]));
+
+        let (_, signed) = sign_with_wallet(message(from), &chain_id).await;
+        assert!(signed.verify(&chain_id).is_err());
+    }
+
+    /// Check that what we sign can be verified, for both `f1` and `f410` senders.
+    #[quickcheck]
+    fn sign_and_verify(key: KeyPair, chain_id: u64, eth: bool) -> Result<(), String> {
+        let KeyPair { sk, pk } = key;
+        let chain_id = ChainID::from(chain_id);
+
+        let from = if eth {
+            Address::from(EthAddress::from(pk))
+        } else {
+            Address::new_secp256k1(&pk.serialize()).map_err(|e| e.to_string())?
+        };
+
+        let signed = SignedMessage::new_eip712(message(from), &sk, &chain_id)
+            .map_err(|e| format!("signing failed: {e}"))?;
+
+        signed
+            .verify(&chain_id)
+            .map_err(|e| format!("verifying failed: {e}"))
+    }
+}
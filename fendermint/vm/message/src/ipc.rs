@@ -101,6 +101,12 @@ pub struct ParentFinality {
     pub height: ChainEpoch,
     /// The block hash of the parent, expressed as bytes
     pub block_hash: Vec<u8>,
+    /// Hash over the top-down messages and validator changes executed with this finality.
+    ///
+    /// Optional so that proposals of validators which don't have it yet can still be decoded,
+    /// and omitted from the encoding when missing so those validators can decode ours.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effects_commitment: Option<Vec<u8>>,
 }
 
 #[cfg(feature = "arb")]
@@ -192,7 +198,61 @@ mod arb {
             Self {
                 height: u32::arbitrary(g).into(),
                 block_hash: Vec::arbitrary(g),
+                effects_commitment: Option::arbitrary(g),
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::ipc::ParentFinality;
+
+    /// The format before the effects commitment was added.
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct LegacyParentFinality {
+        height: i64,
+        block_hash: Vec<u8>,
+    }
+
+    #[test]
+    fn parent_finality_without_commitment_is_compatible() {
+        let legacy = LegacyParentFinality {
+            height: 100,
+            block_hash: vec![1; 32],
+        };
+        let legacy_bytes = fvm_ipld_encoding::to_vec(&legacy).unwrap();
+
+        let current: ParentFinality = fvm_ipld_encoding::from_slice(&legacy_bytes).unwrap();
+        assert_eq!(current.height, 100);
+        assert_eq!(current.block_hash, vec![1; 32]);
+        assert_eq!(current.effects_commitment, None);
+
+        // Without the commitment the encoding doesn't change at all.
+        assert_eq!(fvm_ipld_encoding::to_vec(&current).unwrap(), legacy_bytes);
+    }
+
+    #[test]
+    fn parent_finality_with_commitment_decodes_as_legacy() {
+        let current = ParentFinality {
+            height: 100,
+            block_hash: vec![1; 32],
+            effects_commitment: Some(vec![2; 32]),
+        };
+        let bytes = fvm_ipld_encoding::to_vec(&current).unwrap();
+
+        let legacy: LegacyParentFinality = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(
+            legacy,
+            LegacyParentFinality {
+                height: 100,
+                block_hash: vec![1; 32],
+            }
+        );
+
+        let decoded: ParentFinality = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, current);
+    }
+}
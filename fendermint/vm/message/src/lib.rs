@@ -7,6 +7,7 @@ use serde::Serialize;
 pub mod cetf;
 pub mod chain;
 pub mod conv;
+pub mod eip712;
 pub mod ipc;
 pub mod query;
 pub mod signed;
@@ -3,14 +3,21 @@
 use cid::Cid;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::{
-    address::Address, econ::TokenAmount, error::ExitCode, message::Message as FvmMessage,
-    version::NetworkVersion,
+    address::Address, clock::ChainEpoch, econ::TokenAmount, error::ExitCode,
+    message::Message as FvmMessage, version::NetworkVersion,
 };
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 
 use fendermint_vm_encoding::IsHumanReadable;
 
+/// ABCI query path to inspect the view of the parent chain cached by the top-down finality
+/// provider of the node; the query data is the IPLD encoded `Option<u64>` parent height to
+/// look up in the cache, and the response is the IPLD encoded [`TopDownView`].
+///
+/// Unlike [`FvmQuery`], it isn't about the ledger, but the local state of the node.
+pub const TOPDOWN_VIEW_PATH: &str = "/topdown/view";
+
 /// Height at which to run a query.
 #[derive(Debug, Clone, PartialEq, Eq, Copy, Default)]
 pub enum FvmQueryHeight {
@@ -76,6 +83,10 @@ pub enum FvmQuery {
     StateParams,
     /// Query the built-in actors known by the System actor.
     BuiltinActors,
+    /// Look up the hash of the block at a height, as recorded by the chain metadata actor.
+    ///
+    /// The response is IPLD encoded `BlockHashLookup`.
+    BlockHash(ChainEpoch),
 }
 
 /// State of all actor implementations.
@@ -150,6 +161,43 @@ pub struct BuiltinActors {
     pub registry: Vec<(String, Cid)>,
 }
 
+/// Result of looking up a block hash recorded by the chain metadata actor.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub enum BlockHashLookup {
+    /// The hash of the block at the height.
+    Found([u8; 32]),
+    /// The height is outside the lookback window of the actor,
+    /// which only keeps the hashes of the last `lookback_len` blocks.
+    Pruned { lookback_len: u64 },
+    /// There is no hash recorded at the height, e.g. because it hasn't been reached yet.
+    NotFound,
+}
+
+/// The view of the parent chain cached by the top-down finality provider of a node.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize, Default)]
+pub struct TopDownView {
+    /// Whether the node syncs with the parent at all.
+    pub enabled: bool,
+    /// The latest parent finality committed in the subnet.
+    pub committed_finality: Option<ParentBlockView>,
+    /// The lowest and highest parent heights in the cache, inclusive.
+    pub cached_heights: Option<(u64, u64)>,
+    /// The parent block at the requested height, if it's in the cache.
+    pub block: Option<ParentBlockView>,
+}
+
+/// A parent block as seen by the top-down finality provider.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct ParentBlockView {
+    pub height: u64,
+    /// The block hash, or `None` if the height was a null round in the parent.
+    pub block_hash: Option<Vec<u8>>,
+    /// The number of top-down messages in the block, if known.
+    pub top_down_msgs: Option<usize>,
+    /// The number of validator changes in the block, if known.
+    pub validator_changes: Option<usize>,
+}
+
 #[cfg(feature = "arb")]
 mod arb {
     use fendermint_testing::arb::{ArbAddress, ArbCid, ArbTokenAmount};
@@ -2,24 +2,27 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use std::fmt;
+
 use anyhow::anyhow;
-use cid::multihash::MultihashDigest;
 use cid::Cid;
 use ethers_core::types as et;
 use ethers_core::types::transaction::eip2718::TypedTransaction;
 use fendermint_crypto::{PublicKey, SecretKey};
 use fendermint_vm_actor_interface::eam::EthAddress;
 use fendermint_vm_actor_interface::{eam, evm};
-use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
-use fvm_shared::address::{Address, Payload};
+use fvm_shared::address::{Address, Payload, Protocol};
 use fvm_shared::chainid::ChainID;
 use fvm_shared::crypto::signature::ops::recover_secp_public_key;
 use fvm_shared::crypto::signature::{Signature, SignatureType, SECP_SIG_LEN};
 use fvm_shared::message::Message;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use thiserror::Error;
 
 use crate::conv::from_fvm;
+use crate::eip712;
 
 enum Signable {
     /// Pair of transaction hash and from.
@@ -52,17 +55,32 @@ pub enum DomainHash {
     Eth([u8; 32]),
 }
 
+/// The way the signature of a [SignedMessage] was produced.
+#[derive(PartialEq, Clone, Copy, Debug, Default, Hash, Eq)]
+pub enum SignatureScheme {
+    /// Signature over the CID of the message, or the Ethereum transaction hash,
+    /// depending on the sender and the recipient.
+    #[default]
+    Native,
+    /// Signature over the EIP-712 typed-data representation of the message,
+    /// which is what wallets like MetaMask can produce with `eth_signTypedData_v4`.
+    Eip712,
+}
+
 /// Represents a wrapped message with signature bytes.
 ///
 /// This is the message that the client needs to send, but only the `message`
 /// part is signed over.
 ///
 /// Tuple serialization is used because it might result in a more compact data structure for storage,
-/// and because the `Message` is already serialized as a tuple.
-#[derive(PartialEq, Clone, Debug, Serialize_tuple, Deserialize_tuple, Hash, Eq)]
+/// and because the `Message` is already serialized as a tuple. The `scheme` is only included in the
+/// tuple if it's not [SignatureScheme::Native], so that messages signed before it was introduced
+/// are encoded the same way.
+#[derive(PartialEq, Clone, Debug, Hash, Eq)]
 pub struct SignedMessage {
     pub message: Message,
     pub signature: Signature,
+    pub scheme: SignatureScheme,
 }
 
 impl SignedMessage {
@@ -70,7 +88,11 @@ impl SignedMessage {
     ///
     /// The signature will not be verified.
     pub fn new_unchecked(message: Message, signature: Signature) -> SignedMessage {
-        SignedMessage { message, signature }
+        SignedMessage {
+            message,
+            signature,
+            scheme: SignatureScheme::Native,
+        }
     }
 
     /// Create a signed message.
@@ -84,7 +106,24 @@ impl SignedMessage {
             Signable::Regular(data) => sign_regular(sk, &data),
             Signable::RegularFromEth((data, _)) => sign_regular(sk, &data),
         };
-        Ok(Self { message, signature })
+        Ok(Self::new_unchecked(message, signature))
+    }
+
+    /// Create a signed message using the EIP-712 typed-data scheme.
+    ///
+    /// This is what a wallet would produce, but can be used to test the scheme without one.
+    pub fn new_eip712(
+        message: Message,
+        sk: &SecretKey,
+        chain_id: &ChainID,
+    ) -> Result<Self, SignedMessageError> {
+        let hash = eip712::hash(&message, chain_id).map_err(SignedMessageError::Ethereum)?;
+        let signature = sign_secp256k1(sk, &hash);
+        Ok(Self {
+            message,
+            signature,
+            scheme: SignatureScheme::Eip712,
+        })
     }
 
     /// Calculate the CID of an FVM message.
@@ -178,24 +217,57 @@ impl SignedMessage {
         }
     }
 
+    /// Verify that the EIP-712 typed-data hash of the message was signed by the `from` address.
+    ///
+    /// The sender can either be an `f1` or an Ethereum delegated address, as both are secp256k1 keys.
+    pub fn verify_eip712_signature(
+        message: &Message,
+        signature: &Signature,
+        chain_id: &ChainID,
+    ) -> Result<(), SignedMessageError> {
+        if signature.signature_type() != SignatureType::Secp256k1 {
+            return Err(SignedMessageError::InvalidSignature(
+                "EIP-712 signatures must be Secp256k1".to_string(),
+            ));
+        }
+
+        let hash = eip712::hash(message, chain_id).map_err(SignedMessageError::Ethereum)?;
+
+        let rec = recover_secp256k1_hash(signature, &hash)
+            .map_err(SignedMessageError::InvalidSignature)?;
+
+        let is_signer = match maybe_eth_address(&message.from) {
+            Some(from) => EthAddress::from(rec).0 == from.0,
+            None if message.from.protocol() == Protocol::Secp256k1 => {
+                Address::new_secp256k1(&rec.serialize()).ok() == Some(message.from)
+            }
+            None => {
+                return Err(SignedMessageError::InvalidSignature(format!(
+                    "EIP-712 signatures are only supported for f1 and Ethereum senders; got {}",
+                    message.from
+                )))
+            }
+        };
+
+        if is_signer {
+            Ok(())
+        } else {
+            Err(SignedMessageError::InvalidSignature(format!("the sender did not match the one recovered from the EIP-712 signature (hash = 0x{})", hex::encode(hash))))
+        }
+    }
+
     /// Calculate an optional hash that ecosystem tools expect.
     pub fn domain_hash(
         &self,
         chain_id: &ChainID,
     ) -> Result<Option<DomainHash>, SignedMessageError> {
-        if is_eth_addr_deleg(&self.message.from) && is_eth_addr_compat(&self.message.to) {
-            let tx: TypedTransaction =
-                from_fvm::to_eth_transaction_request(self.message(), chain_id)
-                    .map_err(SignedMessageError::Ethereum)?
-                    .into();
-
-            let sig = from_fvm::to_eth_signature(self.signature(), true)
-                .map_err(SignedMessageError::Ethereum)?;
-
-            let rlp = tx.rlp_signed(&sig);
-
-            let hash = cid::multihash::Code::Keccak256.digest(&rlp);
-            let hash = hash.digest().try_into().expect("Keccak256 is 32 bytes");
+        // Only messages signed as Ethereum transactions can be looked up by their Ethereum hash.
+        if self.scheme == SignatureScheme::Native
+            && is_eth_addr_deleg(&self.message.from)
+            && is_eth_addr_compat(&self.message.to)
+        {
+            let hash =
+                from_fvm::eth_tx_hash(self, chain_id).map_err(SignedMessageError::Ethereum)?;
 
             Ok(Some(DomainHash::Eth(hash)))
         } else {
@@ -206,7 +278,14 @@ impl SignedMessage {
 
     /// Verifies that the from address of the message generated the signature.
     pub fn verify(&self, chain_id: &ChainID) -> Result<(), SignedMessageError> {
-        Self::verify_signature(&self.message, &self.signature, chain_id)
+        match self.scheme {
+            SignatureScheme::Native => {
+                Self::verify_signature(&self.message, &self.signature, chain_id)
+            }
+            SignatureScheme::Eip712 => {
+                Self::verify_eip712_signature(&self.message, &self.signature, chain_id)
+            }
+        }
     }
 
     /// Returns reference to the unsigned message.
@@ -219,6 +298,11 @@ impl SignedMessage {
         &self.signature
     }
 
+    /// Returns the scheme the signature was produced with.
+    pub fn scheme(&self) -> SignatureScheme {
+        self.scheme
+    }
+
     /// Consumes self and returns it's unsigned message.
     pub fn into_message(self) -> Message {
         self.message
@@ -235,6 +319,77 @@ impl SignedMessage {
     }
 }
 
+impl Serialize for SignatureScheme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let tag: u8 = match self {
+            SignatureScheme::Native => 0,
+            SignatureScheme::Eip712 => 1,
+        };
+        tag.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SignatureScheme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match u8::deserialize(deserializer)? {
+            0 => Ok(SignatureScheme::Native),
+            1 => Ok(SignatureScheme::Eip712),
+            other => Err(de::Error::custom(format!(
+                "unknown signature scheme: {other}"
+            ))),
+        }
+    }
+}
+
+impl Serialize for SignedMessage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.scheme {
+            SignatureScheme::Native => (&self.message, &self.signature).serialize(serializer),
+            scheme => (&self.message, &self.signature, scheme).serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SignedMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SignedMessageVisitor;
+
+        impl<'de> Visitor<'de> for SignedMessageVisitor {
+            type Value = SignedMessage;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a tuple of message, signature and optional scheme")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let message = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let signature = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                // The default scheme is left out, so that each message has a single encoding.
+                let scheme = match seq.next_element()? {
+                    None => SignatureScheme::Native,
+                    Some(SignatureScheme::Native) => {
+                        return Err(de::Error::custom(
+                            "the native signature scheme must not be encoded",
+                        ))
+                    }
+                    Some(scheme) => scheme,
+                };
+                Ok(SignedMessage {
+                    message,
+                    signature,
+                    scheme,
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(SignedMessageVisitor)
+    }
+}
+
 /// Sign a transaction pre-image using Blake2b256, in a way that [Signature::verify] expects it.
 fn sign_regular(sk: &SecretKey, data: &[u8]) -> Signature {
     let hash: [u8; 32] = blake2b_simd::Params::new()
@@ -322,6 +477,21 @@ pub fn sign_secp256k1(sk: &SecretKey, hash: &[u8; 32]) -> Signature {
 ///
 /// Based on how `Signature` does it, but without the final address hashing.
 fn recover_secp256k1(signature: &Signature, data: &[u8]) -> Result<PublicKey, String> {
+    // blake2b 256 hash
+    let hash = blake2b_simd::Params::new()
+        .hash_length(32)
+        .to_state()
+        .update(data)
+        .finalize();
+
+    recover_secp256k1_hash(
+        signature,
+        hash.as_bytes().try_into().expect("fixed array size"),
+    )
+}
+
+/// Recover the public key from a Secp256k1 signature over a hash.
+fn recover_secp256k1_hash(signature: &Signature, hash: &[u8; 32]) -> Result<PublicKey, String> {
     let signature = &signature.bytes;
 
     if signature.len() != SECP_SIG_LEN {
@@ -331,21 +501,10 @@ fn recover_secp256k1(signature: &Signature, data: &[u8]) -> Result<PublicKey, St
         ));
     }
 
-    // blake2b 256 hash
-    let hash = blake2b_simd::Params::new()
-        .hash_length(32)
-        .to_state()
-        .update(data)
-        .finalize();
-
     let mut sig = [0u8; SECP_SIG_LEN];
     sig[..].copy_from_slice(signature);
 
-    let rec_key =
-        recover_secp_public_key(hash.as_bytes().try_into().expect("fixed array size"), &sig)
-            .map_err(|e| e.to_string())?;
-
-    Ok(rec_key)
+    recover_secp_public_key(hash, &sig).map_err(|e| e.to_string())
 }
 
 /// Signed message with an invalid random signature.
@@ -354,7 +513,7 @@ mod arb {
     use fendermint_testing::arb::ArbMessage;
     use fvm_shared::crypto::signature::Signature;
 
-    use super::SignedMessage;
+    use super::{SignatureScheme, SignedMessage};
 
     /// An arbitrary `SignedMessage` that is at least as consistent as required for serialization.
     impl quickcheck::Arbitrary for SignedMessage {
@@ -362,6 +521,9 @@ mod arb {
             Self {
                 message: ArbMessage::arbitrary(g).0,
                 signature: Signature::arbitrary(g),
+                scheme: *g
+                    .choose(&[SignatureScheme::Native, SignatureScheme::Eip712])
+                    .unwrap(),
             }
         }
     }
@@ -378,7 +540,39 @@ mod tests {
 
     use crate::conv::tests::{EthMessage, KeyPair};
 
-    use super::SignedMessage;
+    use super::{SignatureScheme, SignedMessage};
+
+    /// Check that the scheme is only part of the encoding if it's not the default.
+    #[quickcheck]
+    fn scheme_encoding(msg: SignedMessage) -> Result<(), String> {
+        let bz = fvm_ipld_encoding::to_vec(&msg).map_err(|e| e.to_string())?;
+        let msg1: SignedMessage = fvm_ipld_encoding::from_slice(&bz).map_err(|e| e.to_string())?;
+
+        if msg1 != msg {
+            return Err("roundtrip failed".into());
+        }
+
+        let pair = fvm_ipld_encoding::to_vec(&(&msg.message, &msg.signature))
+            .map_err(|e| e.to_string())?;
+
+        match msg.scheme {
+            SignatureScheme::Native if bz != pair => {
+                Err("native messages should be encoded as a pair".into())
+            }
+            SignatureScheme::Eip712 if bz == pair => {
+                Err("the scheme should be part of the encoding".into())
+            }
+            _ => {
+                // An explicit default would mean the same message has two encodings.
+                let bz = fvm_ipld_encoding::to_vec(&(&msg.message, &msg.signature, 0u8))
+                    .map_err(|e| e.to_string())?;
+                match fvm_ipld_encoding::from_slice::<SignedMessage>(&bz) {
+                    Ok(_) => Err("the explicit native scheme should be rejected".into()),
+                    Err(_) => Ok(()),
+                }
+            }
+        }
+    }
 
     #[quickcheck]
     fn chain_id_in_signature(
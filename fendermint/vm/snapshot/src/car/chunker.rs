@@ -40,20 +40,27 @@ pub struct ChunkWriter {
     output_dir: PathBuf,
     max_size: usize,
     file_name: Box<dyn Fn(usize) -> String + Send + Sync>,
+    on_chunk: Box<dyn Fn(usize, u64) + Send + Sync>,
     next_idx: usize,
+    total_written: u64,
     state: ChunkWriterState,
 }
 
 impl ChunkWriter {
-    pub fn new<F>(output_dir: PathBuf, max_size: usize, file_name: F) -> Self
+    /// Create a writer which calls `on_chunk` with the number of chunks and
+    /// the total number of bytes written so far every time it closes a chunk.
+    pub fn new<F, G>(output_dir: PathBuf, max_size: usize, file_name: F, on_chunk: G) -> Self
     where
         F: Fn(usize) -> String + Send + Sync + 'static,
+        G: Fn(usize, u64) + Send + Sync + 'static,
     {
         Self {
             output_dir,
             max_size,
             file_name: Box::new(file_name),
+            on_chunk: Box::new(on_chunk),
             next_idx: 0,
+            total_written: 0,
             state: ChunkWriterState::Idle,
         }
     }
@@ -63,6 +70,12 @@ impl ChunkWriter {
         self.next_idx
     }
 
+    /// Account for the bytes written to the chunk about to be closed.
+    fn chunk_done(&mut self, written: usize) {
+        self.total_written += written as u64;
+        (self.on_chunk)(self.next_idx, self.total_written);
+    }
+
     fn take_state(&mut self) -> ChunkWriterState {
         let mut state = ChunkWriterState::Idle;
         std::mem::swap(&mut self.state, &mut state);
@@ -199,6 +212,7 @@ impl AsyncWrite for ChunkWriter {
                     // The file is closed here not in `poll_write` so we don't have torn writes where the varint showing the size is split from the data.
                     let close = this.next_idx == 1 || written >= this.max_size && this.max_size > 0;
                     if close {
+                        this.chunk_done(written);
                         Self::state_poll_close(cx, out)
                     } else {
                         Open { out, written }.ok(())
@@ -212,10 +226,13 @@ impl AsyncWrite for ChunkWriter {
     fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
         use ChunkWriterState::*;
 
-        self.poll_state(|_, state| match state {
+        self.poll_state(|this, state| match state {
             Idle => state.ok(()),
             Opening { out } => Self::state_poll_open(cx, out, Self::state_poll_close),
-            Open { out, .. } => Self::state_poll_close(cx, out),
+            Open { out, written } => {
+                this.chunk_done(written);
+                Self::state_poll_close(cx, out)
+            }
             Closing { out } => Self::state_poll_close(cx, out),
         })
     }
@@ -21,15 +21,20 @@ mod streamer;
 ///
 /// The first (0th) file will be just the header, with the rest containing the "content" blocks.
 ///
+/// The `on_chunk` callback is invoked with the number of chunks and bytes written so far
+/// every time a chunk is completed.
+///
 /// Returns the number of chunks created.
-pub async fn split<F>(
+pub async fn split<F, G>(
     input_file: &Path,
     output_dir: &Path,
     max_size: usize,
     file_name: F,
+    on_chunk: G,
 ) -> anyhow::Result<usize>
 where
     F: Fn(usize) -> String + Send + Sync + 'static,
+    G: Fn(usize, u64) + Send + Sync + 'static,
 {
     let file = tokio::fs::File::open(input_file)
         .await
@@ -40,7 +45,7 @@ where
         .context("failed to open CAR reader")?;
 
     // Create a Writer that opens new files when the maximum is reached.
-    let mut writer = ChunkWriter::new(output_dir.into(), max_size, file_name);
+    let mut writer = ChunkWriter::new(output_dir.into(), max_size, file_name, on_chunk);
 
     let header = CarHeader::new(reader.header.roots.clone(), reader.header.version);
 
@@ -72,6 +77,8 @@ where
 #[cfg(test)]
 mod tests {
 
+    use std::sync::{Arc, Mutex};
+
     use fendermint_vm_interpreter::fvm::bundle::bundle_path;
     use tempfile::tempdir;
 
@@ -87,9 +94,21 @@ mod tests {
         let target_count = 10;
         let max_size = bundle_bytes.len() / target_count;
 
-        let chunks_count = split(&bundle_path, tmp.path(), max_size, |idx| idx.to_string())
-            .await
-            .expect("failed to split CAR file");
+        let last_chunk = Arc::new(Mutex::new(None));
+        let on_chunk = {
+            let last_chunk = last_chunk.clone();
+            move |chunks, bytes| *last_chunk.lock().unwrap() = Some((chunks, bytes))
+        };
+
+        let chunks_count = split(
+            &bundle_path,
+            tmp.path(),
+            max_size,
+            |idx| idx.to_string(),
+            on_chunk,
+        )
+        .await
+        .expect("failed to split CAR file");
 
         let mut chunks = std::fs::read_dir(tmp.path())
             .unwrap()
@@ -125,5 +144,11 @@ mod tests {
         assert!(chunks[0].1 < 100, "header is small");
         assert_eq!(chunks_bytes.len(), bundle_bytes.len());
         assert_eq!(chunks_bytes[0..100], bundle_bytes[0..100]);
+
+        assert_eq!(
+            *last_chunk.lock().unwrap(),
+            Some((chunks_count, bundle_bytes.len() as u64)),
+            "all chunks are reported"
+        );
     }
 }
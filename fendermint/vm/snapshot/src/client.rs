@@ -8,11 +8,13 @@ use fendermint_vm_interpreter::fvm::state::{
     snapshot::{BlockHeight, SnapshotVersion},
     FvmStateParams,
 };
+use tokio::sync::watch;
 
 use crate::{
     manifest,
+    progress::ProgressSink,
     state::{SnapshotDownload, SnapshotState},
-    SnapshotError, SnapshotItem, SnapshotManifest, MANIFEST_FILE_NAME,
+    SnapshotError, SnapshotItem, SnapshotManifest, SnapshotProgress, MANIFEST_FILE_NAME,
 };
 
 /// Interface to snapshot state for the application.
@@ -22,6 +24,8 @@ pub struct SnapshotClient {
     /// The client will only notify the manager of snapshottable heights.
     snapshot_interval: BlockHeight,
     state: SnapshotState,
+    /// Optional channel to report the progress of downloads on.
+    progress: ProgressSink,
 }
 
 impl SnapshotClient {
@@ -34,8 +38,16 @@ impl SnapshotClient {
             download_dir,
             snapshot_interval,
             state,
+            progress: ProgressSink::default(),
         }
     }
+
+    /// Report the progress of downloading snapshots on a channel.
+    pub fn with_progress(mut self, progress: watch::Sender<SnapshotProgress>) -> Self {
+        self.progress = ProgressSink::new(progress);
+        self
+    }
+
     /// Set the latest block state parameters and notify the manager.
     ///
     /// Call this with the block height where the `app_hash` in the block reflects the
@@ -82,6 +94,8 @@ impl SnapshotClient {
     pub fn offer_snapshot(&self, manifest: SnapshotManifest) -> StmResult<PathBuf, SnapshotError> {
         if manifest.version != 1 {
             abort(SnapshotError::IncompatibleVersion(manifest.version))
+        } else if !manifest.compression.is_supported() {
+            abort(SnapshotError::UnsupportedCompression(manifest.compression))
         } else {
             match tempfile::tempdir_in(&self.download_dir) {
                 Ok(dir) => {
@@ -99,6 +113,7 @@ impl SnapshotClient {
                         manifest,
                         download_dir: Arc::new(dir),
                         next_index: TVar::new(0),
+                        bytes_done: TVar::new(0),
                     };
 
                     // Create a `parts` sub-directory for the chunks.
@@ -136,6 +151,7 @@ impl SnapshotClient {
                 abort(SnapshotError::UnexpectedChunk(next_index, index))
             } else {
                 let part_path = cd.parts_dir().join(format!("{}.part", index));
+                let part_size = contents.len() as u64;
 
                 // We are doing IO inside the STM transaction, but that's okay because there is no contention on the download.
                 match std::fs::write(part_path, contents) {
@@ -143,15 +159,30 @@ impl SnapshotClient {
                         let next_index = index + 1;
                         cd.next_index.write(next_index)?;
 
+                        let bytes_done = cd.bytes_done.read_clone()? + part_size;
+                        cd.bytes_done.write(bytes_done)?;
+
+                        self.progress.report(SnapshotProgress {
+                            block_height: cd.manifest.block_height,
+                            chunks_done: next_index,
+                            chunks_total: cd.manifest.chunks,
+                            bytes_done,
+                        });
+
                         if next_index == cd.manifest.chunks {
+                            let item = SnapshotItem::new(
+                                cd.download_dir.path().into(),
+                                cd.manifest.clone(),
+                            );
+                            // Verify the individual chunks first, so we can tell which one is corrupted.
+                            if let Err(e) = item.verify_chunks() {
+                                return abort(e);
+                            }
                             // Verify the checksum then load the snapshot and remove the current download from memory.
-                            match manifest::parts_checksum(cd.parts_dir()) {
+                            match manifest::parts_checksum(cd.parts_dir(), cd.manifest.compression)
+                            {
                                 Ok(checksum) => {
                                     if checksum == cd.manifest.checksum {
-                                        let item = SnapshotItem::new(
-                                            cd.download_dir.path().into(),
-                                            cd.manifest.clone(),
-                                        );
                                         Ok(Some(item))
                                     } else {
                                         abort(SnapshotError::WrongChecksum(
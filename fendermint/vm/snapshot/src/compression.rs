@@ -0,0 +1,113 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Compression applied to the `{idx}.part` files of a snapshot.
+///
+/// The parts are compressed individually after the CAR file has been split,
+/// so each chunk can be verified and decompressed on its own. The checksum
+/// of the snapshot is over the uncompressed contents.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PartCompression {
+    /// Parts are written as raw slices of the CAR file.
+    #[default]
+    None,
+    /// Parts are compressed with zstd at the given level.
+    Zstd { level: i32 },
+}
+
+impl PartCompression {
+    /// Check that this binary can read and write parts with this compression.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            PartCompression::None => true,
+            PartCompression::Zstd { .. } => cfg!(feature = "zstd"),
+        }
+    }
+
+    /// Compress a part file in place.
+    pub fn compress_part(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        match self {
+            PartCompression::None => Ok(()),
+            PartCompression::Zstd { level } => zstd_compress(path.as_ref(), *level),
+        }
+    }
+
+    /// Open a part file, decompressing its contents as they are read.
+    pub fn open_part(&self, path: impl AsRef<Path>) -> io::Result<Box<dyn Read>> {
+        let file = File::open(path)?;
+        match self {
+            PartCompression::None => Ok(Box::new(file)),
+            PartCompression::Zstd { .. } => zstd_decoder(file),
+        }
+    }
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_compress(path: &Path, level: i32) -> io::Result<()> {
+    let compressed_path = path.with_extension("zst");
+    {
+        let input = File::open(path)?;
+        let output = File::create(&compressed_path)?;
+        zstd::stream::copy_encode(input, output, level)?;
+    }
+    std::fs::rename(compressed_path, path)
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decoder(file: File) -> io::Result<Box<dyn Read>> {
+    Ok(Box::new(zstd::stream::read::Decoder::new(file)?))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_compress(_path: &Path, _level: i32) -> io::Result<()> {
+    Err(zstd_unsupported())
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decoder(_file: File) -> io::Result<Box<dyn Read>> {
+    Err(zstd_unsupported())
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "zstd compression requires the `zstd` feature",
+    )
+}
+
+#[cfg(all(test, feature = "zstd"))]
+mod tests {
+    use std::io::Read;
+
+    use super::PartCompression;
+
+    #[test]
+    fn compress_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("0.part");
+        let contents = vec![0u8; 64 * 1024];
+        std::fs::write(&path, &contents).unwrap();
+
+        let compression = PartCompression::Zstd { level: 3 };
+        compression.compress_part(&path).unwrap();
+
+        let compressed_len = std::fs::metadata(&path).unwrap().len();
+        assert!(compressed_len < contents.len() as u64);
+
+        let mut decompressed = Vec::new();
+        compression
+            .open_part(&path)
+            .unwrap()
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, contents);
+    }
+}
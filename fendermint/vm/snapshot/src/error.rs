@@ -3,11 +3,15 @@
 
 use fendermint_vm_interpreter::fvm::state::snapshot::SnapshotVersion;
 
+use crate::PartCompression;
+
 /// Possible errors with snapshots.
 #[derive(Debug, thiserror::Error)]
 pub enum SnapshotError {
     #[error("incompatible snapshot version: {0}")]
     IncompatibleVersion(SnapshotVersion),
+    #[error("unsupported snapshot compression: {0:?}")]
+    UnsupportedCompression(PartCompression),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     #[error("there is no ongoing snapshot download")]
@@ -16,4 +20,10 @@ pub enum SnapshotError {
     UnexpectedChunk(u32, u32),
     #[error("wrong checksum; expected {0}, got {1}")]
     WrongChecksum(tendermint::Hash, tendermint::Hash),
+    #[error("wrong checksum of chunk {0}; expected {1}, got {2}")]
+    WrongChunkChecksum(u32, tendermint::Hash, tendermint::Hash),
+    #[error("failed to read chunk {0}: {1}")]
+    ChunkIoError(u32, std::io::Error),
+    #[error("wrong number of chunk checksums; expected {0}, got {1}")]
+    ChunkCountMismatch(u32, usize),
 }
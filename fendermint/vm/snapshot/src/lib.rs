@@ -2,9 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 mod car;
 mod client;
+mod compression;
 mod error;
 mod manager;
 mod manifest;
+mod progress;
 mod state;
 
 /// The file name to export the CAR to.
@@ -17,7 +19,9 @@ const MANIFEST_FILE_NAME: &str = "manifest.json";
 const PARTS_DIR_NAME: &str = "parts";
 
 pub use client::SnapshotClient;
+pub use compression::PartCompression;
 pub use error::SnapshotError;
 pub use manager::{SnapshotManager, SnapshotParams};
 pub use manifest::SnapshotManifest;
+pub use progress::SnapshotProgress;
 pub use state::SnapshotItem;
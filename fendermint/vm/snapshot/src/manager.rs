@@ -4,15 +4,23 @@
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use crate::manifest::{file_checksum, list_manifests, write_manifest, SnapshotManifest};
+use crate::manifest::{
+    chunk_checksums, file_checksum, list_manifests, list_parts, write_manifest, SnapshotManifest,
+};
+use crate::progress::ProgressSink;
 use crate::state::SnapshotState;
-use crate::{car, SnapshotClient, SnapshotItem, PARTS_DIR_NAME, SNAPSHOT_FILE_NAME};
+use crate::{
+    car, PartCompression, SnapshotClient, SnapshotItem, SnapshotProgress, PARTS_DIR_NAME,
+    SNAPSHOT_FILE_NAME,
+};
 use anyhow::Context;
 use async_stm::{atomically, retry, TVar};
 use fendermint_vm_interpreter::fvm::state::snapshot::{BlockHeight, Snapshot};
 use fendermint_vm_interpreter::fvm::state::FvmStateParams;
 use fvm_ipld_blockstore::Blockstore;
 use tendermint_rpc::Client;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
 
 pub struct SnapshotParams {
     /// Location to store completed snapshots.
@@ -21,6 +29,8 @@ pub struct SnapshotParams {
     pub block_interval: BlockHeight,
     /// Target size in bytes for snapshot chunks.
     pub chunk_size: usize,
+    /// Compression to apply to the snapshot chunks.
+    pub compression: PartCompression,
     /// Number of snapshots to keep.
     ///
     /// 0 means unlimited.
@@ -37,6 +47,7 @@ pub struct SnapshotManager<BS> {
     store: BS,
     snapshots_dir: PathBuf,
     chunk_size: usize,
+    compression: PartCompression,
     hist_size: usize,
     last_access_hold: Duration,
     sync_poll_interval: Duration,
@@ -45,6 +56,8 @@ pub struct SnapshotManager<BS> {
     /// Indicate whether CometBFT has finished syncing with the chain,
     /// so that we can skip snapshotting old states while catching up.
     is_syncing: TVar<bool>,
+    /// Optional channel to report the progress of exports on.
+    progress: ProgressSink,
 }
 
 impl<BS> SnapshotManager<BS>
@@ -53,6 +66,10 @@ where
 {
     /// Create a new manager.
     pub fn new(store: BS, params: SnapshotParams) -> anyhow::Result<(Self, SnapshotClient)> {
+        if !params.compression.is_supported() {
+            anyhow::bail!("unsupported snapshot compression: {:?}", params.compression);
+        }
+
         // Make sure the target directory exists.
         std::fs::create_dir_all(&params.snapshots_dir)
             .context("failed to create snapshots directory")?;
@@ -66,12 +83,14 @@ where
             store,
             snapshots_dir: params.snapshots_dir,
             chunk_size: params.chunk_size,
+            compression: params.compression,
             hist_size: params.hist_size,
             last_access_hold: params.last_access_hold,
             sync_poll_interval: params.sync_poll_interval,
             state: state.clone(),
             // Assume we are syncing until we can determine otherwise.
             is_syncing: TVar::new(true),
+            progress: ProgressSink::default(),
         };
 
         let client = SnapshotClient::new(params.download_dir, params.block_interval, state);
@@ -79,8 +98,17 @@ where
         Ok((manager, client))
     }
 
-    /// Produce snapshots.
-    pub async fn run<C>(self, client: C)
+    /// Report the progress of exporting snapshots on a channel.
+    pub fn with_progress(mut self, progress: watch::Sender<SnapshotProgress>) -> Self {
+        self.progress = ProgressSink::new(progress);
+        self
+    }
+
+    /// Produce snapshots until the shutdown token is cancelled.
+    ///
+    /// A snapshot which is already being exported is finished before returning,
+    /// so that we don't leave half-written parts behind.
+    pub async fn run<C>(self, client: C, shutdown: CancellationToken)
     where
         C: Client + Send + Sync + 'static,
     {
@@ -101,7 +129,7 @@ where
 
         let mut last_params = None;
         loop {
-            let (state_params, block_height) = atomically(|| {
+            let next_params = atomically(|| {
                 // Check the current sync status. We could just query the API, but then we wouldn't
                 // be notified when we finally reach the end, and we'd only snapshot the next height,
                 // not the last one as soon as the chain is caught up.
@@ -114,8 +142,16 @@ where
                     unchanged if *unchanged == last_params => retry()?,
                     Some(new_params) => Ok(new_params.clone()),
                 }
-            })
-            .await;
+            });
+
+            let (state_params, block_height) = tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => {
+                    tracing::info!("stopping the snapshot manager");
+                    return;
+                }
+                params = next_params => params,
+            };
 
             match self
                 .create_snapshot(block_height, state_params.clone())
@@ -216,6 +252,11 @@ where
             "exporting snapshot..."
         );
 
+        self.progress.report(SnapshotProgress {
+            block_height,
+            ..Default::default()
+        });
+
         // Export the state to a CAR file.
         snapshot
             .write_car(&snapshot_path)
@@ -239,18 +280,49 @@ where
         // They can be listed in the right order with e.g. `ls | sort -n`
         // Alternatively we could pad them with zeroes based on the original file size and the chunk size,
         // but this way it will be easier to return them based on a numeric index.
-        let chunks_count = car::split(&snapshot_path, &parts_path, self.chunk_size, |idx| {
-            format!("{idx}.part")
-        })
+        // Until it's done we can only estimate the number of chunks, plus one for the header.
+        let chunks_estimate = snapshot_size.div_ceil(self.chunk_size.max(1)) + 1;
+        let progress = self.progress.clone();
+        let chunks_count = car::split(
+            &snapshot_path,
+            &parts_path,
+            self.chunk_size,
+            |idx| format!("{idx}.part"),
+            move |chunks_done, bytes_done| {
+                progress.report(SnapshotProgress {
+                    block_height,
+                    chunks_done: chunks_done as u32,
+                    chunks_total: chunks_estimate.max(chunks_done) as u32,
+                    bytes_done,
+                })
+            },
+        )
         .await
         .context("failed to split CAR into chunks")?;
 
+        self.progress.update(|p| {
+            p.chunks_done = chunks_count as u32;
+            p.chunks_total = chunks_count as u32;
+        });
+
+        // Compress the parts individually, so they can be verified as they are received.
+        for part in list_parts(&parts_path).context("failed to list parts")? {
+            self.compression
+                .compress_part(&part)
+                .with_context(|| format!("failed to compress part {}", part.to_string_lossy()))?;
+        }
+
+        let chunk_checksums =
+            chunk_checksums(&parts_path).context("failed to compute chunk checksums")?;
+
         // Create and export a manifest that we can easily look up.
         let manifest = SnapshotManifest {
             block_height,
             size: snapshot_size as u64,
             chunks: chunks_count as u32,
             checksum: checksum_bytes,
+            chunk_checksums,
+            compression: self.compression,
             state_params,
             version: snapshot_version,
         };
@@ -325,8 +397,11 @@ mod tests {
     };
     use fvm::engine::MultiEngine;
     use quickcheck::Arbitrary;
+    use tokio_util::sync::CancellationToken;
 
-    use crate::{manager::SnapshotParams, manifest, PARTS_DIR_NAME};
+    use crate::{
+        manager::SnapshotParams, manifest, PartCompression, SnapshotProgress, PARTS_DIR_NAME,
+    };
 
     use super::SnapshotManager;
 
@@ -363,6 +438,7 @@ mod tests {
                 download_dir: download_dir.path().into(),
                 block_interval: 1,
                 chunk_size: 10000,
+                compression: PartCompression::None,
                 hist_size: 1,
                 last_access_hold: Duration::ZERO,
                 sync_poll_interval: never_poll_sync,
@@ -370,8 +446,15 @@ mod tests {
         )
         .expect("failed to create snapshot manager");
 
+        let (progress_tx, progress_rx) = tokio::sync::watch::channel(SnapshotProgress::default());
+        let snapshot_manager = snapshot_manager.with_progress(progress_tx);
+
         // Start the manager in the background
-        tokio::spawn(async move { snapshot_manager.run(never_poll_client).await });
+        tokio::spawn(async move {
+            snapshot_manager
+                .run(never_poll_client, CancellationToken::new())
+                .await
+        });
 
         // Make sure we have no snapshots currently.
         let snapshots = atomically(|| snapshot_client.list_snapshots()).await;
@@ -414,15 +497,35 @@ mod tests {
         assert_eq!(snapshots.len(), 1, "can list manifests");
         assert_eq!(snapshots[0], snapshot);
 
-        let checksum =
-            manifest::parts_checksum(snapshot.snapshot_dir.as_path().join(PARTS_DIR_NAME))
-                .expect("parts checksum can be calculated");
+        let checksum = manifest::parts_checksum(
+            snapshot.snapshot_dir.as_path().join(PARTS_DIR_NAME),
+            snapshot.manifest.compression,
+        )
+        .expect("parts checksum can be calculated");
 
         assert_eq!(
             checksum, snapshot.manifest.checksum,
             "checksum should match"
         );
 
+        assert_eq!(
+            snapshot.manifest.chunk_checksums.len(),
+            snapshot.manifest.chunks as usize,
+            "there should be a checksum for each chunk"
+        );
+        snapshot.verify_chunks().expect("chunks should be valid");
+
+        assert_eq!(
+            *progress_rx.borrow(),
+            SnapshotProgress {
+                block_height: 0,
+                chunks_done: snapshot.manifest.chunks,
+                chunks_total: snapshot.manifest.chunks,
+                bytes_done: snapshot.manifest.size,
+            },
+            "final progress should equal the total"
+        );
+
         // Create a new manager instance
         let (_, new_client) = SnapshotManager::new(
             store,
@@ -431,6 +534,7 @@ mod tests {
                 download_dir: download_dir.path().into(),
                 block_interval: 1,
                 chunk_size: 10000,
+                compression: PartCompression::None,
                 hist_size: 1,
                 last_access_hold: Duration::ZERO,
                 sync_poll_interval: never_poll_sync,
@@ -465,7 +569,8 @@ mod tests {
             1.05,
             false,
             UpgradeScheduler::new(),
-        );
+        )
+        .expect("failed to create interpreter");
 
         let (state, out) = interpreter
             .init(state, genesis)
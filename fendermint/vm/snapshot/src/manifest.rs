@@ -11,7 +11,7 @@ use fendermint_vm_interpreter::fvm::state::{
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-use crate::{SnapshotItem, MANIFEST_FILE_NAME};
+use crate::{PartCompression, SnapshotItem, MANIFEST_FILE_NAME};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct SnapshotManifest {
@@ -25,6 +25,14 @@ pub struct SnapshotManifest {
     ///
     /// Using a [tendermint::Hash] type because it has nice formatting in JSON.
     pub checksum: tendermint::Hash,
+    /// SHA2 hash of each `{idx}.part` file, so that a corrupted chunk can be identified.
+    ///
+    /// Empty in manifests of snapshots taken before these were recorded.
+    #[serde(default)]
+    pub chunk_checksums: Vec<tendermint::Hash>,
+    /// Compression applied to the `{idx}.part` files.
+    #[serde(default)]
+    pub compression: PartCompression,
     /// The FVM parameters at the time of the snapshot,
     /// which are also in the CAR file, but it might be
     /// useful to see. It is annotated for human readability.
@@ -108,14 +116,17 @@ pub fn file_checksum(path: impl AsRef<Path>) -> anyhow::Result<tendermint::Hash>
     Ok(tendermint::Hash::Sha256(hash))
 }
 
-/// Calculate the Sha256 checksum of all `{idx}.part` files in a directory.
-pub fn parts_checksum(path: impl AsRef<Path>) -> anyhow::Result<tendermint::Hash> {
+/// Calculate the Sha256 checksum of the decompressed contents of all `{idx}.part` files in a directory.
+pub fn parts_checksum(
+    path: impl AsRef<Path>,
+    compression: PartCompression,
+) -> anyhow::Result<tendermint::Hash> {
     let mut hasher = Sha256::new();
 
     let chunks = list_parts(path)?;
 
     for path in chunks {
-        let mut file = std::fs::File::open(path).context("failed to open part")?;
+        let mut file = compression.open_part(path).context("failed to open part")?;
         let _ = std::io::copy(&mut file, &mut hasher)?;
     }
 
@@ -123,6 +134,11 @@ pub fn parts_checksum(path: impl AsRef<Path>) -> anyhow::Result<tendermint::Hash
     Ok(tendermint::Hash::Sha256(hash))
 }
 
+/// Calculate the Sha256 checksum of each `{idx}.part` file in a directory, in order.
+pub fn chunk_checksums(path: impl AsRef<Path>) -> anyhow::Result<Vec<tendermint::Hash>> {
+    list_parts(path)?.into_iter().map(file_checksum).collect()
+}
+
 /// List all the `{idx}.part` files in a directory.
 pub fn list_parts(path: impl AsRef<Path>) -> anyhow::Result<Vec<PathBuf>> {
     let mut chunks = std::fs::read_dir(path.as_ref())
@@ -165,10 +181,17 @@ mod arb {
     use quickcheck::Arbitrary;
 
     use super::SnapshotManifest;
+    use crate::PartCompression;
 
     impl quickcheck::Arbitrary for SnapshotManifest {
         fn arbitrary(g: &mut quickcheck::Gen) -> Self {
             let checksum: [u8; 32] = std::array::from_fn(|_| u8::arbitrary(g));
+            let chunk_checksums = (0..usize::arbitrary(g) % 5)
+                .map(|_| {
+                    let checksum: [u8; 32] = std::array::from_fn(|_| u8::arbitrary(g));
+                    tendermint::Hash::Sha256(checksum)
+                })
+                .collect();
 
             Self {
                 block_height: u32::arbitrary(g) as u64,
@@ -179,6 +202,14 @@ mod arb {
                     &checksum,
                 )
                 .unwrap(),
+                chunk_checksums,
+                compression: if bool::arbitrary(g) {
+                    PartCompression::Zstd {
+                        level: i32::from(u8::arbitrary(g) % 20) + 1,
+                    }
+                } else {
+                    PartCompression::None
+                },
                 state_params: FvmStateParams {
                     state_root: ArbCid::arbitrary(g).0,
                     timestamp: Timestamp(Arbitrary::arbitrary(g)),
@@ -0,0 +1,48 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::sync::Arc;
+
+use fendermint_vm_interpreter::fvm::state::snapshot::BlockHeight;
+use tokio::sync::watch;
+
+/// Progress of exporting or importing a snapshot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SnapshotProgress {
+    /// Height of the snapshot being exported or imported.
+    pub block_height: BlockHeight,
+    /// Number of chunks written or received so far.
+    pub chunks_done: u32,
+    /// Total number of chunks in the snapshot.
+    ///
+    /// During an export this is an estimate until the snapshot has been split into chunks.
+    pub chunks_total: u32,
+    /// Number of bytes in the chunks written or received so far.
+    pub bytes_done: u64,
+}
+
+/// Optional channel to publish progress reports on.
+#[derive(Clone, Default)]
+pub(crate) struct ProgressSink(Option<Arc<watch::Sender<SnapshotProgress>>>);
+
+impl ProgressSink {
+    pub fn new(tx: watch::Sender<SnapshotProgress>) -> Self {
+        Self(Some(Arc::new(tx)))
+    }
+
+    /// Publish the latest progress, if there is a channel.
+    pub fn report(&self, progress: SnapshotProgress) {
+        self.update(|p| *p = progress)
+    }
+
+    /// Modify the latest progress, if there is a channel.
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut SnapshotProgress),
+    {
+        if let Some(ref tx) = self.0 {
+            // Unlike `send`, this works even if there are no receivers at the moment.
+            tx.send_modify(f);
+        }
+    }
+}
@@ -7,11 +7,12 @@ use anyhow::{bail, Context};
 use async_stm::TVar;
 use fendermint_vm_interpreter::fvm::state::snapshot::{BlockStateParams, Snapshot};
 use fvm_ipld_blockstore::Blockstore;
+use sha2::{Digest, Sha256};
 use tempfile::TempDir;
 
 use crate::{
     manifest::{self, SnapshotManifest},
-    PARTS_DIR_NAME, SNAPSHOT_FILE_NAME,
+    SnapshotError, PARTS_DIR_NAME, SNAPSHOT_FILE_NAME,
 };
 
 /// State of snapshots, including the list of available completed ones
@@ -62,6 +63,10 @@ impl SnapshotItem {
         self.snapshot_dir.join(PARTS_DIR_NAME)
     }
 
+    fn chunk_path(&self, chunk: u32) -> PathBuf {
+        self.parts_dir().join(format!("{chunk}.part"))
+    }
+
     /// Load the data from disk.
     ///
     /// Returns an error if the chunk isn't within range or if the file doesn't exist any more.
@@ -72,7 +77,7 @@ impl SnapshotItem {
                 self.manifest.chunks
             );
         }
-        let chunk_file = self.parts_dir().join(format!("{chunk}.part"));
+        let chunk_file = self.chunk_path(chunk);
 
         let content = std::fs::read(&chunk_file)
             .with_context(|| format!("failed to read chunk {}", chunk_file.to_string_lossy()))?;
@@ -80,6 +85,43 @@ impl SnapshotItem {
         Ok(content)
     }
 
+    /// Check each chunk on disk against its checksum in the manifest.
+    ///
+    /// Returns an error identifying the first chunk which doesn't match. Manifests without
+    /// chunk checksums pass, leaving only the checksum of the whole snapshot to be checked.
+    pub fn verify_chunks(&self) -> Result<(), SnapshotError> {
+        let checksums = &self.manifest.chunk_checksums;
+
+        if checksums.is_empty() {
+            return Ok(());
+        }
+
+        if checksums.len() != self.manifest.chunks as usize {
+            return Err(SnapshotError::ChunkCountMismatch(
+                self.manifest.chunks,
+                checksums.len(),
+            ));
+        }
+
+        for (chunk, expected) in (0..).zip(checksums) {
+            let checksum = File::open(self.chunk_path(chunk))
+                .and_then(|mut file| {
+                    let mut hasher = Sha256::new();
+                    io::copy(&mut file, &mut hasher)?;
+                    Ok(tendermint::Hash::Sha256(hasher.finalize().into()))
+                })
+                .map_err(|e| SnapshotError::ChunkIoError(chunk, e))?;
+
+            if checksum != *expected {
+                return Err(SnapshotError::WrongChunkChecksum(
+                    chunk, *expected, checksum,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Import a snapshot into the blockstore.
     pub async fn import<BS>(&self, store: BS, validate: bool) -> anyhow::Result<Snapshot<BS>>
     where
@@ -93,9 +135,13 @@ impl SnapshotItem {
         let mut car_file = File::create(&car_path).context("failed to create CAR file")?;
 
         for part in parts {
-            let mut part_file = File::open(&part).with_context(|| {
-                format!("failed to open snapshot part {}", part.to_string_lossy())
-            })?;
+            let mut part_file = self
+                .manifest
+                .compression
+                .open_part(&part)
+                .with_context(|| {
+                    format!("failed to open snapshot part {}", part.to_string_lossy())
+                })?;
 
             io::copy(&mut part_file, &mut car_file)?;
         }
@@ -169,6 +215,8 @@ pub struct SnapshotDownload {
     pub download_dir: Arc<TempDir>,
     // Next expected chunk index.
     pub next_index: TVar<u32>,
+    // Number of bytes received so far.
+    pub bytes_done: TVar<u64>,
 }
 
 impl SnapshotDownload {
@@ -193,3 +241,58 @@ mod arb {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use quickcheck::Arbitrary;
+
+    use crate::{manifest, SnapshotError, SnapshotManifest, PARTS_DIR_NAME};
+
+    use super::SnapshotItem;
+
+    fn new_item(dir: &tempfile::TempDir, chunks: &[&[u8]]) -> SnapshotItem {
+        let parts_dir = dir.path().join(PARTS_DIR_NAME);
+        std::fs::create_dir(&parts_dir).unwrap();
+        for (idx, contents) in chunks.iter().enumerate() {
+            std::fs::write(parts_dir.join(format!("{idx}.part")), contents).unwrap();
+        }
+        let mut manifest = SnapshotManifest::arbitrary(&mut quickcheck::Gen::new(10));
+        manifest.chunks = chunks.len() as u32;
+        manifest.chunk_checksums = manifest::chunk_checksums(&parts_dir).unwrap();
+        SnapshotItem::new(dir.path().into(), manifest)
+    }
+
+    #[test]
+    fn verify_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let item = new_item(&dir, &[b"foo", b"bar", b"baz"]);
+
+        item.verify_chunks().expect("chunks should be valid");
+
+        std::fs::write(item.chunk_path(1), b"BAR").unwrap();
+        std::fs::write(item.chunk_path(2), b"BAZ").unwrap();
+
+        match item.verify_chunks() {
+            Err(SnapshotError::WrongChunkChecksum(1, _, _)) => {}
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        std::fs::remove_file(item.chunk_path(1)).unwrap();
+
+        match item.verify_chunks() {
+            Err(SnapshotError::ChunkIoError(1, _)) => {}
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn verify_chunks_without_checksums() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut item = new_item(&dir, &[b"foo"]);
+        item.manifest.chunk_checksums.clear();
+        std::fs::write(item.chunk_path(0), b"FOO").unwrap();
+
+        item.verify_chunks()
+            .expect("legacy manifests are not checked");
+    }
+}
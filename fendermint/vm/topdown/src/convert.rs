@@ -30,6 +30,7 @@ impl From<gateway_getter_facet::ParentFinality> for IPCParentFinality {
         IPCParentFinality {
             height: value.height.as_u64(),
             block_hash: value.block_hash.to_vec(),
+            effects_commitment: None,
         }
     }
 }
@@ -39,6 +40,7 @@ impl From<top_down_finality_facet::ParentFinality> for IPCParentFinality {
         IPCParentFinality {
             height: value.height.as_u64(),
             block_hash: value.block_hash.to_vec(),
+            effects_commitment: None,
         }
     }
 }
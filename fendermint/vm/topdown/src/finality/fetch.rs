@@ -5,9 +5,10 @@ use crate::finality::null::FinalityWithNull;
 use crate::finality::ParentViewPayload;
 use crate::proxy::ParentQueryProxy;
 use crate::{
-    handle_null_round, BlockHash, BlockHeight, Config, Error, IPCParentFinality,
-    ParentFinalityProvider, ParentViewProvider,
+    handle_null_round, BlockHash, BlockHeight, Bytes, CachedParentBlock, Config, Error,
+    IPCParentFinality, ParentFinalityProvider, ParentViewProvider,
 };
+use anyhow::anyhow;
 use async_stm::{Stm, StmResult};
 use ipc_api::cross::IpcEnvelope;
 use ipc_api::staking::StakingChangeRequest;
@@ -22,7 +23,8 @@ pub struct CachedFinalityProvider<T> {
     parent_client: Arc<T>,
 }
 
-/// Exponential backoff for futures
+/// Exponential backoff for futures, retrying every error except the ones known to be
+/// deterministic, see [crate::is_transient_error].
 macro_rules! retry {
     ($wait:expr, $retires:expr, $f:expr) => {{
         let mut retries = $retires;
@@ -39,6 +41,15 @@ macro_rules! retry {
                     break res;
                 }
 
+                // the same request would fail again, e.g. because the response cannot be decoded
+                if !crate::is_transient_error(e) {
+                    tracing::warn!(
+                        error = e.to_string(),
+                        "cannot query ipc parent_client due to non-transient error, skip retry"
+                    );
+                    break res;
+                }
+
                 tracing::warn!(
                     error = e.to_string(),
                     retries,
@@ -106,6 +117,37 @@ impl<T: ParentQueryProxy + Send + Sync + 'static> ParentViewProvider for CachedF
         }
         Ok(v)
     }
+
+    /// Get the latest block of the parent chain. If the head is a null round, the closest
+    /// non-null block before it is returned.
+    async fn chain_head(&self) -> anyhow::Result<(BlockHeight, BlockHash)> {
+        let head = retry!(
+            self.config.exponential_back_off,
+            self.config.exponential_retry_limit,
+            self.parent_client.get_chain_head_height().await
+        )?;
+
+        let genesis = self.genesis_epoch()?;
+        for height in (genesis..=head).rev() {
+            let r = retry!(
+                self.config.exponential_back_off,
+                self.config.exponential_retry_limit,
+                self.parent_client
+                    .get_block_hash(height)
+                    .await
+                    .map(|r| Some(r.block_hash))
+            );
+
+            if let Some(hash) = handle_null_round(r, || None)? {
+                return Ok((height, hash));
+            }
+            tracing::debug!(height, "parent chain head is a null round, trying previous");
+        }
+
+        Err(anyhow!(
+            "no non-null block between genesis {genesis} and parent chain head {head}"
+        ))
+    }
 }
 
 impl<T: ParentQueryProxy + Send + Sync + 'static> ParentFinalityProvider
@@ -119,6 +161,10 @@ impl<T: ParentQueryProxy + Send + Sync + 'static> ParentFinalityProvider
         self.inner.check_proposal(proposal)
     }
 
+    fn effects_commitment(&self, height: BlockHeight) -> Stm<Option<Bytes>> {
+        self.inner.effects_commitment(height)
+    }
+
     fn set_new_finality(
         &self,
         finality: IPCParentFinality,
@@ -237,6 +283,16 @@ impl<T> CachedFinalityProvider<T> {
     pub fn first_non_null_block(&self, height: BlockHeight) -> Stm<Option<BlockHeight>> {
         self.inner.first_non_null_block(height)
     }
+
+    /// Returns the lowest and highest heights in the cache, inclusive.
+    pub fn cached_heights(&self) -> Stm<Option<(BlockHeight, BlockHeight)>> {
+        self.inner.cached_heights()
+    }
+
+    /// Summarise the parent block at the height, if it's in the cache.
+    pub fn cached_block(&self, height: BlockHeight) -> Stm<Option<CachedParentBlock>> {
+        self.inner.cached_block(height)
+    }
 }
 
 #[cfg(test)]
@@ -255,8 +311,9 @@ mod tests {
     use ipc_api::staking::{StakingChange, StakingChangeRequest, StakingOperation};
     use ipc_api::subnet_id::SubnetID;
     use ipc_provider::manager::{GetBlockHashResult, TopDownQueryPayload};
+    use std::collections::VecDeque;
     use std::sync::atomic::{AtomicUsize, Ordering};
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
     use std::time::Duration;
 
     /// Creates a mock of a new parent blockchain view. The key is the height and the value is the
@@ -274,6 +331,17 @@ mod tests {
 
     struct TestParentProxy {
         blocks: SequentialKeyCache<BlockHeight, Option<ParentViewPayload>>,
+        /// Errors returned by the next queries of top down messages or validator changes.
+        errors: Mutex<VecDeque<&'static str>>,
+    }
+
+    impl TestParentProxy {
+        fn next_error(&self) -> anyhow::Result<()> {
+            match self.errors.lock().unwrap().pop_front() {
+                Some(e) => Err(anyhow!(e)),
+                None => Ok(()),
+            }
+        }
     }
 
     #[async_trait]
@@ -309,6 +377,7 @@ mod tests {
             &self,
             height: BlockHeight,
         ) -> anyhow::Result<TopDownQueryPayload<Vec<IpcEnvelope>>> {
+            self.next_error()?;
             let r = self.blocks.get_value(height).cloned().unwrap();
             if r.is_none() {
                 return Err(anyhow!(NULL_ROUND_ERR_MSG));
@@ -324,6 +393,7 @@ mod tests {
             &self,
             height: BlockHeight,
         ) -> anyhow::Result<TopDownQueryPayload<Vec<StakingChangeRequest>>> {
+            self.next_error()?;
             let r = self.blocks.get_value(height).cloned().unwrap();
             if r.is_none() {
                 return Err(anyhow!(NULL_ROUND_ERR_MSG));
@@ -338,21 +408,34 @@ mod tests {
 
     fn new_provider(
         blocks: SequentialKeyCache<BlockHeight, Option<ParentViewPayload>>,
+    ) -> CachedFinalityProvider<TestParentProxy> {
+        new_provider_with_errors(blocks, vec![], 0)
+    }
+
+    fn new_provider_with_errors(
+        blocks: SequentialKeyCache<BlockHeight, Option<ParentViewPayload>>,
+        errors: Vec<&'static str>,
+        exponential_retry_limit: usize,
     ) -> CachedFinalityProvider<TestParentProxy> {
         let config = Config {
             chain_head_delay: 2,
             polling_interval: Default::default(),
             exponential_back_off: Default::default(),
-            exponential_retry_limit: 0,
+            exponential_retry_limit,
             max_proposal_range: Some(1),
             max_cache_blocks: None,
             proposal_delay: None,
+            require_effects_commitment: false,
         };
         let genesis_epoch = blocks.lower_bound().unwrap();
-        let proxy = Arc::new(TestParentProxy { blocks });
+        let proxy = Arc::new(TestParentProxy {
+            blocks,
+            errors: Mutex::new(errors.into()),
+        });
         let committed_finality = IPCParentFinality {
             height: genesis_epoch,
             block_hash: vec![0; 32],
+            effects_commitment: None,
         };
 
         CachedFinalityProvider::new(config, genesis_epoch, Some(committed_finality), proxy)
@@ -389,9 +472,9 @@ mod tests {
         }
 
         impl Test {
-            async fn run(&self) -> Result<(), &'static str> {
+            async fn run(&self) -> anyhow::Result<()> {
                 self.nums_run.fetch_add(1, Ordering::SeqCst);
-                Err("mocked error")
+                Err(anyhow!("mocked error"))
             }
         }
 
@@ -405,6 +488,19 @@ mod tests {
         assert_eq!(t.nums_run.load(Ordering::SeqCst), 3);
     }
 
+    #[tokio::test]
+    async fn test_no_retry_deterministic_error() {
+        let nums_run = AtomicUsize::new(0);
+
+        let res: anyhow::Result<()> = retry!(Duration::from_secs(1), 2, {
+            nums_run.fetch_add(1, Ordering::SeqCst);
+            Err(anyhow!("failed to decode return data"))
+        });
+        assert!(res.is_err());
+        // fails fast without retrying
+        assert_eq!(nums_run.load(Ordering::SeqCst), 1);
+    }
+
     #[tokio::test]
     async fn test_query_topdown_msgs() {
         let parent_blocks = new_parent_blocks!(
@@ -447,4 +543,67 @@ mod tests {
 
         assert_eq!(messages.len(), 4)
     }
+
+    #[tokio::test]
+    async fn test_query_topdown_msgs_retries_unknown_errors() {
+        let parent_blocks = new_parent_blocks!(
+            100 => Some((vec![0; 32], vec![], vec![new_cross_msg(0)])),   // genesis block
+            101 => Some((vec![1; 32], vec![], vec![new_cross_msg(1)])),
+            102 => Some((vec![2; 32], vec![], vec![new_cross_msg(2)]))
+        );
+        let provider = new_provider_with_errors(
+            parent_blocks,
+            vec!["unexpected response", "connection reset by peer"],
+            2,
+        );
+        let messages = provider.top_down_msgs_from(101, 102).await.unwrap();
+        assert_eq!(messages, vec![new_cross_msg(1), new_cross_msg(2)]);
+    }
+
+    #[tokio::test]
+    async fn test_query_validator_changes_fails_fast_on_decode_error() {
+        let parent_blocks = new_parent_blocks!(
+            100 => Some((vec![0; 32], vec![new_validator_changes(0)], vec![])),   // genesis block
+            101 => Some((vec![1; 32], vec![new_validator_changes(1)], vec![]))
+        );
+        let provider = new_provider_with_errors(
+            parent_blocks,
+            vec![
+                "failed to decode return data",
+                "failed to decode return data",
+            ],
+            3,
+        );
+        assert!(provider.validator_changes_from(101, 101).await.is_err());
+        // only one query was made, the second error is still pending
+        assert_eq!(provider.parent_client.errors.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_query_chain_head() {
+        let parent_blocks = new_parent_blocks!(
+            100 => Some((vec![0; 32], vec![], vec![])),   // genesis block
+            101 => Some((vec![1; 32], vec![], vec![])),
+            102 => Some((vec![2; 32], vec![], vec![]))
+        );
+        let provider = new_provider(parent_blocks);
+        let head = provider.chain_head().await.unwrap();
+
+        assert_eq!(head, (102, vec![2; 32]))
+    }
+
+    #[tokio::test]
+    async fn test_query_chain_head_null_round() {
+        let parent_blocks = new_parent_blocks!(
+            100 => Some((vec![0; 32], vec![], vec![])),   // genesis block
+            101 => Some((vec![1; 32], vec![], vec![])),
+            102 => Some((vec![2; 32], vec![], vec![])),
+            103 => None,
+            104 => None
+        );
+        let provider = new_provider(parent_blocks);
+        let head = provider.chain_head().await.unwrap();
+
+        assert_eq!(head, (102, vec![2; 32]))
+    }
 }
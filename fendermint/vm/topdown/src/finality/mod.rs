@@ -98,6 +98,7 @@ mod tests {
         IPCParentFinality {
             height: 0,
             block_hash: vec![0; 32],
+            effects_commitment: None,
         }
     }
 
@@ -110,6 +111,7 @@ mod tests {
             max_proposal_range: None,
             max_cache_blocks: None,
             proposal_delay: None,
+            require_effects_commitment: false,
         };
 
         CachedFinalityProvider::new(config, 10, Some(genesis_finality()), mocked_agent_proxy())
@@ -129,6 +131,7 @@ mod tests {
             let finality = IPCParentFinality {
                 height: target_block,
                 block_hash: vec![1u8; 32],
+                effects_commitment: None,
             };
             provider.set_new_finality(finality.clone(), Some(genesis_finality()))?;
 
@@ -158,6 +161,7 @@ mod tests {
                 IPCParentFinality {
                     height: target_block - 1,
                     block_hash: vec![1u8; 32],
+                    effects_commitment: None,
                 },
                 Some(genesis_finality()),
             )?;
@@ -165,6 +169,7 @@ mod tests {
             let finality = IPCParentFinality {
                 height: target_block,
                 block_hash: vec![1u8; 32],
+                effects_commitment: None,
             };
 
             assert!(provider.check_proposal(&finality).is_ok());
@@ -4,7 +4,10 @@
 use crate::finality::{
     ensure_sequential, topdown_cross_msgs, validator_changes, ParentViewPayload,
 };
-use crate::{BlockHash, BlockHeight, Config, Error, IPCParentFinality, SequentialKeyCache};
+use crate::{
+    effects_commitment, BlockHash, BlockHeight, Bytes, CachedParentBlock, Config, Error,
+    IPCParentFinality, SequentialKeyCache,
+};
 use async_stm::{abort, atomically, Stm, StmResult, TVar};
 use ipc_api::cross::IpcEnvelope;
 use ipc_api::staking::StakingChangeRequest;
@@ -91,7 +94,13 @@ impl FinalityWithNull {
         // safe to unwrap as we make sure null height will not be proposed
         let block_hash = self.block_hash_at_height(height)?.unwrap();
 
-        let proposal = IPCParentFinality { height, block_hash };
+        let effects_commitment = self.effects_commitment(height)?;
+
+        let proposal = IPCParentFinality {
+            height,
+            block_hash,
+            effects_commitment,
+        };
         tracing::debug!(proposal = proposal.to_string(), "new proposal");
         Ok(Some(proposal))
     }
@@ -100,7 +109,39 @@ impl FinalityWithNull {
         if !self.check_height(proposal)? {
             return Ok(false);
         }
-        self.check_block_hash(proposal)
+        if !self.check_block_hash(proposal)? {
+            return Ok(false);
+        }
+        self.check_effects_commitment(proposal)
+    }
+
+    /// Commitment to the side effects of finalizing the parent at `height`.
+    ///
+    /// Because of the delayed execution, these are the validator changes and top-down messages
+    /// from the last committed height up to, but not including `height`. Returns `None` if not
+    /// all of those heights are in the cache, e.g. the last committed one after a restart.
+    pub fn effects_commitment(&self, height: BlockHeight) -> Stm<Option<Bytes>> {
+        let from = if let Some(f) = self.last_committed_finality.read()?.as_ref() {
+            f.height
+        } else {
+            return Ok(None);
+        };
+
+        let cache = self.cached_data.read()?;
+        let mut changes = vec![];
+        let mut msgs = vec![];
+        for h in from..height {
+            match cache.get_value(h) {
+                None => return Ok(None),
+                Some(None) => continue,
+                Some(Some(p)) => {
+                    changes.extend(validator_changes(p));
+                    msgs.extend(topdown_cross_msgs(p));
+                }
+            }
+        }
+
+        Ok(Some(effects_commitment(&changes, &msgs)))
     }
 
     pub fn set_new_finality(
@@ -167,6 +208,23 @@ impl FinalityWithNull {
         Ok(Some(h))
     }
 
+    /// Returns the lowest and highest heights in the cache, inclusive.
+    pub(crate) fn cached_heights(&self) -> Stm<Option<(BlockHeight, BlockHeight)>> {
+        let cache = self.cached_data.read()?;
+        Ok(cache.lower_bound().zip(cache.upper_bound()))
+    }
+
+    /// Summarise the parent block at the height, if it's in the cache.
+    pub(crate) fn cached_block(&self, height: BlockHeight) -> Stm<Option<CachedParentBlock>> {
+        let cache = self.cached_data.read()?;
+        Ok(cache.get_value(height).map(|payload| CachedParentBlock {
+            height,
+            block_hash: payload.as_ref().map(|(hash, _, _)| hash.clone()),
+            top_down_msgs: payload.as_ref().map_or(0, |(_, _, msgs)| msgs.len()),
+            validator_changes: payload.as_ref().map_or(0, |(_, changes, _)| changes.len()),
+        }))
+    }
+
     /// Get the first non-null block in the range of earliest cache block till the height specified, inclusive.
     pub(crate) fn first_non_null_block(&self, height: BlockHeight) -> Stm<Option<BlockHeight>> {
         let cache = self.cached_data.read()?;
@@ -353,6 +411,40 @@ impl FinalityWithNull {
         }
     }
 
+    fn check_effects_commitment(&self, proposal: &IPCParentFinality) -> Stm<bool> {
+        let commitment = if let Some(c) = proposal.effects_commitment.as_ref() {
+            c
+        } else {
+            let r = !self.config.require_effects_commitment;
+            tracing::debug!(
+                proposal = proposal.to_string(),
+                accept = r,
+                "proposal without effects commitment"
+            );
+            return Ok(r);
+        };
+
+        Ok(
+            if let Some(local) = self.effects_commitment(proposal.height)? {
+                let r = local == *commitment;
+                tracing::debug!(
+                    proposal = proposal.to_string(),
+                    is_same = r,
+                    "same effects commitment?"
+                );
+                r
+            } else {
+                // We can't tell without the data, but the commitment is checked again when
+                // the effects are fetched for execution.
+                tracing::debug!(
+                    proposal = proposal.to_string(),
+                    "effects not in cache, cannot check commitment"
+                );
+                true
+            },
+        )
+    }
+
     fn check_block_hash(&self, proposal: &IPCParentFinality) -> Stm<bool> {
         Ok(
             if let Some(block_hash) = self.block_hash_at_height(proposal.height)? {
@@ -371,8 +463,13 @@ impl FinalityWithNull {
 mod tests {
     use super::FinalityWithNull;
     use crate::finality::ParentViewPayload;
-    use crate::{BlockHeight, Config, IPCParentFinality};
+    use crate::{effects_commitment, BlockHeight, CachedParentBlock, Config, IPCParentFinality};
     use async_stm::{atomically, atomically_or_err};
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::cross::IpcEnvelope;
+    use ipc_api::staking::{StakingChange, StakingChangeRequest, StakingOperation};
+    use ipc_api::subnet_id::SubnetID;
 
     async fn new_provider(
         mut blocks: Vec<(BlockHeight, Option<ParentViewPayload>)>,
@@ -385,10 +482,12 @@ mod tests {
             max_proposal_range: Some(6),
             max_cache_blocks: None,
             proposal_delay: Some(2),
+            require_effects_commitment: false,
         };
         let committed_finality = IPCParentFinality {
             height: blocks[0].0,
             block_hash: vec![0; 32],
+            effects_commitment: None,
         };
 
         blocks.remove(0);
@@ -420,6 +519,7 @@ mod tests {
         let f = IPCParentFinality {
             height: 104,
             block_hash: vec![4; 32],
+            effects_commitment: None,
         };
         assert_eq!(
             atomically(|| provider.next_proposal()).await,
@@ -444,6 +544,154 @@ mod tests {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_cached_view() {
+        let msg = IpcEnvelope::new_fund_msg(
+            &SubnetID::new(10, vec![Address::new_id(1000)]),
+            &Address::new_id(1),
+            &Address::new_id(2),
+            TokenAmount::from_atto(100),
+        )
+        .unwrap();
+        let change = StakingChangeRequest {
+            configuration_number: 1,
+            change: StakingChange {
+                op: StakingOperation::Deposit,
+                payload: vec![],
+                validator: Address::new_id(3),
+            },
+        };
+        let parent_blocks = vec![
+            (100, Some((vec![0; 32], vec![], vec![]))), // last committed block
+            (
+                101,
+                Some((vec![1; 32], vec![change], vec![msg.clone(), msg])),
+            ),
+            (102, None),
+            (103, Some((vec![3; 32], vec![], vec![]))),
+        ];
+        let provider = new_provider(parent_blocks).await;
+
+        assert_eq!(
+            atomically(|| provider.cached_heights()).await,
+            Some((101, 103))
+        );
+        assert_eq!(
+            atomically(|| provider.cached_block(101)).await,
+            Some(CachedParentBlock {
+                height: 101,
+                block_hash: Some(vec![1; 32]),
+                top_down_msgs: 2,
+                validator_changes: 1,
+            })
+        );
+        // Null rounds are in the cache, without a block hash.
+        assert_eq!(
+            atomically(|| provider.cached_block(102)).await,
+            Some(CachedParentBlock {
+                height: 102,
+                block_hash: None,
+                top_down_msgs: 0,
+                validator_changes: 0,
+            })
+        );
+        // The committed finality is not in the cache any more.
+        assert_eq!(atomically(|| provider.cached_block(100)).await, None);
+        assert_eq!(atomically(|| provider.cached_block(104)).await, None);
+    }
+
+    fn fund_msg(nonce: u64) -> IpcEnvelope {
+        let mut msg = IpcEnvelope::new_fund_msg(
+            &SubnetID::new(10, vec![Address::new_id(1000)]),
+            &Address::new_id(1),
+            &Address::new_id(2),
+            TokenAmount::from_atto(100),
+        )
+        .unwrap();
+        msg.nonce = nonce;
+        msg
+    }
+
+    /// Blocks with the committed finality at 101 kept in the cache, as after a commit.
+    async fn new_committed_provider(msgs_at_102: Vec<IpcEnvelope>) -> FinalityWithNull {
+        let parent_blocks = vec![
+            (100, Some((vec![0; 32], vec![], vec![]))),
+            (101, Some((vec![1; 32], vec![], vec![]))), // last committed block
+            (102, Some((vec![2; 32], vec![], msgs_at_102))),
+            (103, None),
+            (104, Some((vec![4; 32], vec![], vec![]))),
+            (105, Some((vec![5; 32], vec![], vec![]))), // proposal height
+            (106, Some((vec![6; 32], vec![], vec![]))),
+            (107, Some((vec![7; 32], vec![], vec![]))), // max proposal height
+        ];
+        let provider = new_provider(parent_blocks).await;
+        atomically(|| {
+            let last = provider.last_committed_finality.read_clone()?;
+            provider.set_new_finality(IPCParentFinality::new(101, vec![1; 32]), last)
+        })
+        .await;
+        provider
+    }
+
+    #[tokio::test]
+    async fn test_proposal_effects_commitment() {
+        let provider = new_committed_provider(vec![fund_msg(0), fund_msg(1)]).await;
+
+        let proposal = atomically(|| provider.next_proposal()).await.unwrap();
+        assert_eq!(proposal.height, 105);
+        assert_eq!(
+            proposal.effects_commitment,
+            Some(effects_commitment(&[], &[fund_msg(0), fund_msg(1)]))
+        );
+        assert!(atomically(|| provider.check_proposal(&proposal)).await);
+    }
+
+    #[tokio::test]
+    async fn test_effects_commitment_divergence() {
+        let proposer = new_committed_provider(vec![fund_msg(0), fund_msg(1)]).await;
+        // Same block hashes, but this one's parent RPC returned only one of the messages.
+        let validator = new_committed_provider(vec![fund_msg(0)]).await;
+
+        let proposal = atomically(|| proposer.next_proposal()).await.unwrap();
+        assert!(!atomically(|| validator.check_proposal(&proposal)).await);
+
+        // Without the commitment, the divergence would have gone unnoticed.
+        let legacy = IPCParentFinality::new(105, vec![5; 32]);
+        assert!(atomically(|| validator.check_proposal(&legacy)).await);
+    }
+
+    #[tokio::test]
+    async fn test_require_effects_commitment() {
+        let mut provider = new_committed_provider(vec![]).await;
+        provider.config.require_effects_commitment = true;
+
+        let legacy = IPCParentFinality::new(105, vec![5; 32]);
+        assert!(!atomically(|| provider.check_proposal(&legacy)).await);
+
+        let proposal = atomically(|| provider.next_proposal()).await.unwrap();
+        assert!(proposal.effects_commitment.is_some());
+        assert!(atomically(|| provider.check_proposal(&proposal)).await);
+    }
+
+    #[tokio::test]
+    async fn test_effects_commitment_not_in_cache() {
+        // The committed block is not in the cache, like after a restart.
+        let parent_blocks = vec![
+            (100, Some((vec![0; 32], vec![], vec![]))), // last committed block
+            (101, Some((vec![1; 32], vec![], vec![]))),
+            (102, Some((vec![2; 32], vec![], vec![]))),
+            (103, Some((vec![3; 32], vec![], vec![]))),
+        ];
+        let provider = new_provider(parent_blocks).await;
+
+        assert_eq!(atomically(|| provider.effects_commitment(103)).await, None);
+
+        // Can't be checked here, so it's left to the execution.
+        let mut proposal = IPCParentFinality::new(103, vec![3; 32]);
+        proposal.effects_commitment = Some(vec![0; 32]);
+        assert!(atomically(|| provider.check_proposal(&proposal)).await);
+    }
+
     #[tokio::test]
     async fn test_not_enough_view() {
         // max_proposal_range is 6. proposal_delay is 2
@@ -462,7 +710,8 @@ mod tests {
             atomically(|| provider.next_proposal()).await,
             Some(IPCParentFinality {
                 height: 103,
-                block_hash: vec![3; 32]
+                block_hash: vec![3; 32],
+                effects_commitment: None,
             })
         );
     }
@@ -531,7 +780,8 @@ mod tests {
             atomically(|| provider.next_proposal()).await,
             Some(IPCParentFinality {
                 height: 107,
-                block_hash: vec![7; 32]
+                block_hash: vec![7; 32],
+                effects_commitment: None,
             })
         );
     }
@@ -559,7 +809,8 @@ mod tests {
             atomically(|| provider.next_proposal()).await,
             Some(IPCParentFinality {
                 height: 107,
-                block_hash: vec![7; 32]
+                block_hash: vec![7; 32],
+                effects_commitment: None,
             })
         );
     }
@@ -32,6 +32,17 @@ pub type BlockHash = Bytes;
 
 /// The null round error message
 pub(crate) const NULL_ROUND_ERR_MSG: &str = "requested epoch was a null round";
+/// Fragments of error messages from querying the parent which indicate that the same
+/// request would fail again, no matter how many times it is retried.
+const DETERMINISTIC_ERR_MSGS: &[&str] = &[
+    "decode",
+    "deserialize",
+    "abi error",
+    "invalid length",
+    "invalid address",
+    "bad address",
+    "unknown address",
+];
 /// Default topdown proposal height range
 pub(crate) const DEFAULT_MAX_PROPOSAL_RANGE: BlockHeight = 100;
 pub(crate) const DEFAULT_MAX_CACHE_BLOCK: BlockHeight = 500;
@@ -55,6 +66,10 @@ pub struct Config {
     /// Max number of blocks that should be stored in cache
     pub max_cache_blocks: Option<BlockHeight>,
     pub proposal_delay: Option<BlockHeight>,
+    /// Reject proposals without an effects commitment. Until all validators propose one,
+    /// proposals without it are accepted and only judged by their height and block hash.
+    #[serde(default)]
+    pub require_effects_commitment: bool,
 }
 
 impl Config {
@@ -72,6 +87,7 @@ impl Config {
             max_proposal_range: None,
             max_cache_blocks: None,
             proposal_delay: None,
+            require_effects_commitment: false,
         }
     }
 
@@ -90,6 +106,11 @@ impl Config {
         self
     }
 
+    pub fn with_require_effects_commitment(mut self, require_effects_commitment: bool) -> Self {
+        self.require_effects_commitment = require_effects_commitment;
+        self
+    }
+
     pub fn max_proposal_range(&self) -> BlockHeight {
         self.max_proposal_range
             .unwrap_or(DEFAULT_MAX_PROPOSAL_RANGE)
@@ -112,6 +133,10 @@ pub struct IPCParentFinality {
     /// The block hash. For FVM, it is a Cid. For Evm, it is bytes32 as one can now potentially
     /// deploy a subnet on EVM.
     pub block_hash: BlockHash,
+    /// Commitment to the side effects executed when this finality is committed, see
+    /// [`effects_commitment`]. Only proposals carry it, and older proposals don't have it at all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effects_commitment: Option<Bytes>,
 }
 
 impl IPCParentFinality {
@@ -119,6 +144,7 @@ impl IPCParentFinality {
         Self {
             height: height as BlockHeight,
             block_hash: hash,
+            effects_commitment: None,
         }
     }
 }
@@ -134,6 +160,33 @@ impl Display for IPCParentFinality {
     }
 }
 
+/// Hash over the canonically serialized validator changes and top-down messages which are
+/// executed together when a parent finality is committed.
+///
+/// Validators who fetched different side effects from their parent for the same range of
+/// heights end up with different commitments, which makes the divergence visible at proposal
+/// time, rather than when the effects are executed.
+pub fn effects_commitment(
+    validator_changes: &[StakingChangeRequest],
+    top_down_msgs: &[IpcEnvelope],
+) -> Bytes {
+    let bytes = fvm_ipld_encoding::to_vec(&(validator_changes, top_down_msgs))
+        .expect("validator changes and top-down messages are serializable");
+    ethers::utils::keccak256(bytes).to_vec()
+}
+
+/// Summary of a parent block in the cache of the finality provider, to inspect what a node has seen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedParentBlock {
+    pub height: BlockHeight,
+    /// The block hash, or `None` if the height was a null round in the parent.
+    pub block_hash: Option<BlockHash>,
+    /// The number of top-down messages in the block.
+    pub top_down_msgs: usize,
+    /// The number of validator changes in the block.
+    pub validator_changes: usize,
+}
+
 #[async_trait]
 pub trait ParentViewProvider {
     /// Obtain the genesis epoch of the current subnet in the parent
@@ -150,6 +203,8 @@ pub trait ParentViewProvider {
         from: BlockHeight,
         to: BlockHeight,
     ) -> anyhow::Result<Vec<IpcEnvelope>>;
+    /// Get the latest non-null block of the parent chain with its hash.
+    async fn chain_head(&self) -> anyhow::Result<(BlockHeight, BlockHash)>;
 }
 
 pub trait ParentFinalityProvider: ParentViewProvider {
@@ -157,6 +212,9 @@ pub trait ParentFinalityProvider: ParentViewProvider {
     fn next_proposal(&self) -> Stm<Option<IPCParentFinality>>;
     /// Check if the target proposal is valid
     fn check_proposal(&self, proposal: &IPCParentFinality) -> Stm<bool>;
+    /// Commitment to the side effects of finalizing the parent at `height`,
+    /// if all of them are in the cache.
+    fn effects_commitment(&self, height: BlockHeight) -> Stm<Option<Bytes>>;
     /// Called when finality is committed
     fn set_new_finality(
         &self,
@@ -189,3 +247,83 @@ pub(crate) fn is_null_round_error(err: &anyhow::Error) -> bool {
 pub(crate) fn is_null_round_str(s: &str) -> bool {
     s.contains(NULL_ROUND_ERR_MSG)
 }
+
+/// Check if an error querying the parent could go away when retried.
+///
+/// Errors are considered transient unless they are known to be deterministic, like null rounds,
+/// decoding errors or bad addresses, because the parent endpoint can fail in many ways which
+/// cannot all be listed, and giving up on a transient one would stall the syncer.
+pub fn is_transient_error(err: &anyhow::Error) -> bool {
+    let s = format!("{err:#}");
+    if is_null_round_str(&s) {
+        return false;
+    }
+    for e in err.chain() {
+        if let Some(e) = e.downcast_ref::<std::io::Error>() {
+            match e.kind() {
+                std::io::ErrorKind::InvalidData | std::io::ErrorKind::InvalidInput => return false,
+                std::io::ErrorKind::ConnectionReset
+                | std::io::ErrorKind::ConnectionRefused
+                | std::io::ErrorKind::ConnectionAborted
+                | std::io::ErrorKind::NotConnected
+                | std::io::ErrorKind::BrokenPipe
+                | std::io::ErrorKind::TimedOut
+                | std::io::ErrorKind::Interrupted => return true,
+                _ => {}
+            }
+        }
+    }
+    let s = s.to_lowercase();
+    !DETERMINISTIC_ERR_MSGS.iter().any(|m| s.contains(m))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use crate::{is_transient_error, NULL_ROUND_ERR_MSG};
+
+    #[test]
+    fn test_transient_rate_limit() {
+        let err = anyhow!("HTTP error 429 Too Many Requests").context("cannot get chain head");
+        assert!(is_transient_error(&err));
+    }
+
+    #[test]
+    fn test_transient_io_error() {
+        let err = anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::ConnectionReset))
+            .context("cannot get block hash");
+        assert!(is_transient_error(&err));
+    }
+
+    #[test]
+    fn test_unknown_error_transient() {
+        let err = anyhow!("unexpected response from the parent").context("cannot get chain head");
+        assert!(is_transient_error(&err));
+    }
+
+    #[test]
+    fn test_invalid_data_io_error_not_transient() {
+        let err = anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::InvalidData))
+            .context("cannot get block hash");
+        assert!(!is_transient_error(&err));
+    }
+
+    #[test]
+    fn test_bad_address_not_transient() {
+        let err = anyhow!("invalid address: f0xyz").context("cannot get validator changes");
+        assert!(!is_transient_error(&err));
+    }
+
+    #[test]
+    fn test_decode_error_not_transient() {
+        let err = anyhow!("failed to decode return data: invalid length 12, expected 32 bytes");
+        assert!(!is_transient_error(&err));
+    }
+
+    #[test]
+    fn test_null_round_not_transient() {
+        let err = anyhow!("{NULL_ROUND_ERR_MSG} (timeout waiting for block)");
+        assert!(!is_transient_error(&err));
+    }
+}
@@ -78,9 +78,11 @@ impl ParentQueryProxy for IPCProviderProxy {
     /// Getting the block hash at the target height.
     #[instrument(skip(self))]
     async fn get_block_hash(&self, height: BlockHeight) -> anyhow::Result<GetBlockHashResult> {
-        self.ipc_provider
+        let hash = self
+            .ipc_provider
             .get_block_hash(&self.parent_subnet, height as ChainEpoch)
-            .await
+            .await?;
+        Ok(hash)
     }
 
     /// Get the top down messages from the starting to the ending height.
@@ -89,14 +91,13 @@ impl ParentQueryProxy for IPCProviderProxy {
         &self,
         height: BlockHeight,
     ) -> anyhow::Result<TopDownQueryPayload<Vec<IpcEnvelope>>> {
-        self.ipc_provider
+        let mut v = self
+            .ipc_provider
             .get_top_down_msgs(&self.child_subnet, height as ChainEpoch)
-            .await
-            .map(|mut v| {
-                // sort ascending, we dont assume the changes are ordered
-                v.value.sort_by(|a, b| a.nonce.cmp(&b.nonce));
-                v
-            })
+            .await?;
+        // sort ascending, we dont assume the changes are ordered
+        v.value.sort_by(|a, b| a.nonce.cmp(&b.nonce));
+        Ok(v)
     }
 
     /// Get the validator set at the specified height.
@@ -105,14 +106,13 @@ impl ParentQueryProxy for IPCProviderProxy {
         &self,
         height: BlockHeight,
     ) -> anyhow::Result<TopDownQueryPayload<Vec<StakingChangeRequest>>> {
-        self.ipc_provider
+        let mut v = self
+            .ipc_provider
             .get_validator_changeset(&self.child_subnet, height as ChainEpoch)
-            .await
-            .map(|mut v| {
-                // sort ascending, we dont assume the changes are ordered
-                v.value
-                    .sort_by(|a, b| a.configuration_number.cmp(&b.configuration_number));
-                v
-            })
+            .await?;
+        // sort ascending, we dont assume the changes are ordered
+        v.value
+            .sort_by(|a, b| a.configuration_number.cmp(&b.configuration_number));
+        Ok(v)
     }
 }
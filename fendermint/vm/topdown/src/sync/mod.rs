@@ -16,6 +16,7 @@ use ethers::utils::hex;
 use ipc_ipld_resolver::ValidatorKey;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 use fendermint_vm_genesis::{Power, Validator};
 
@@ -71,6 +72,7 @@ where
             finality = IPCParentFinality {
                 height: genesis_epoch,
                 block_hash: r.block_hash,
+                effects_commitment: None,
             };
             tracing::info!(
                 genesis_finality = finality.to_string(),
@@ -113,6 +115,7 @@ pub async fn launch_polling_syncer<T, C, P>(
     vote_tally: VoteTally,
     parent_client: Arc<P>,
     tendermint_client: C,
+    shutdown: CancellationToken,
 ) -> anyhow::Result<()>
 where
     T: ParentFinalityStateQuery + Send + Sync + 'static,
@@ -124,7 +127,11 @@ where
     }
 
     let query = Arc::new(query);
-    let finality = query_starting_finality(&query, &parent_client).await?;
+    // The starting queries retry until the ledger is initialized, which might never happen.
+    let finality = tokio::select! {
+        _ = shutdown.cancelled() => return Ok(()),
+        finality = query_starting_finality(&query, &parent_client) => finality?,
+    };
 
     let power_table = query_starting_comittee(&query).await?;
     let power_table = power_table
@@ -156,19 +163,25 @@ where
         parent_client,
         query,
         tendermint_client,
-    );
+        shutdown,
+    )
+    .await;
 
     Ok(())
 }
 
-/// Start the parent finality listener in the background
-fn start_syncing<T, C, P>(
+/// Poll the parent for new blocks until the shutdown token is cancelled.
+///
+/// A sync round which has already started is allowed to finish, so the cache
+/// is not left with a partially applied block.
+async fn start_syncing<T, C, P>(
     config: Config,
     view_provider: Arc<Toggle<CachedFinalityProvider<P>>>,
     vote_tally: VoteTally,
     parent_proxy: Arc<P>,
     query: Arc<T>,
     tendermint_client: C,
+    shutdown: CancellationToken,
 ) where
     T: ParentFinalityStateQuery + Send + Sync + 'static,
     C: tendermint_rpc::Client + Send + Sync + 'static,
@@ -177,19 +190,23 @@ fn start_syncing<T, C, P>(
     let mut interval = tokio::time::interval(config.polling_interval);
     interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
-    tokio::spawn(async move {
-        let lotus_syncer =
-            LotusParentSyncer::new(config, parent_proxy, view_provider, vote_tally, query)
-                .expect("");
-
-        let mut tendermint_syncer = TendermintAwareSyncer::new(lotus_syncer, tendermint_client);
+    let lotus_syncer =
+        LotusParentSyncer::new(config, parent_proxy, view_provider, vote_tally, query).expect("");
 
-        loop {
-            interval.tick().await;
+    let mut tendermint_syncer = TendermintAwareSyncer::new(lotus_syncer, tendermint_client);
 
-            if let Err(e) = tendermint_syncer.sync().await {
-                tracing::error!(error = e.to_string(), "sync with parent encountered error");
+    loop {
+        tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => {
+                tracing::info!("stopping the parent syncer");
+                return;
             }
+            _ = interval.tick() => {}
+        }
+
+        if let Err(e) = tendermint_syncer.sync().await {
+            tracing::error!(error = e.to_string(), "sync with parent encountered error");
         }
-    });
+    }
 }
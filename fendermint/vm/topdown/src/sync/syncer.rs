@@ -488,12 +488,14 @@ mod tests {
             max_proposal_range: Some(1),
             max_cache_blocks: None,
             proposal_delay: None,
+            require_effects_commitment: false,
         };
         let genesis_epoch = blocks.lower_bound().unwrap();
         let proxy = Arc::new(TestParentProxy { blocks });
         let committed_finality = IPCParentFinality {
             height: genesis_epoch,
             block_hash: vec![0; 32],
+            effects_commitment: None,
         };
 
         let vote_tally = VoteTally::new(
@@ -3,8 +3,8 @@
 
 use crate::finality::ParentViewPayload;
 use crate::{
-    BlockHash, BlockHeight, CachedFinalityProvider, Error, IPCParentFinality,
-    ParentFinalityProvider, ParentViewProvider,
+    BlockHash, BlockHeight, Bytes, CachedFinalityProvider, CachedParentBlock, Error,
+    IPCParentFinality, ParentFinalityProvider, ParentViewProvider,
 };
 use anyhow::anyhow;
 use async_stm::{Stm, StmResult};
@@ -71,6 +71,13 @@ impl<P: ParentViewProvider + Send + Sync + 'static> ParentViewProvider for Toggl
             None => Err(anyhow!("provider is toggled off")),
         }
     }
+
+    async fn chain_head(&self) -> anyhow::Result<(BlockHeight, BlockHash)> {
+        match self.inner.as_ref() {
+            Some(p) => p.chain_head().await,
+            None => Err(anyhow!("provider is toggled off")),
+        }
+    }
 }
 
 impl<P: ParentFinalityProvider + Send + Sync + 'static> ParentFinalityProvider for Toggle<P> {
@@ -82,6 +89,10 @@ impl<P: ParentFinalityProvider + Send + Sync + 'static> ParentFinalityProvider f
         self.perform_or_else(|p| p.check_proposal(proposal), false)
     }
 
+    fn effects_commitment(&self, height: BlockHeight) -> Stm<Option<Bytes>> {
+        self.perform_or_else(|p| p.effects_commitment(height), None)
+    }
+
     fn set_new_finality(
         &self,
         finality: IPCParentFinality,
@@ -127,4 +138,12 @@ impl<P> Toggle<CachedFinalityProvider<P>> {
     pub fn first_non_null_block(&self, height: BlockHeight) -> Stm<Option<BlockHeight>> {
         self.perform_or_else(|p| p.first_non_null_block(height), None)
     }
+
+    pub fn cached_heights(&self) -> Stm<Option<(BlockHeight, BlockHeight)>> {
+        self.perform_or_else(|p| p.cached_heights(), None)
+    }
+
+    pub fn cached_block(&self, height: BlockHeight) -> Stm<Option<CachedParentBlock>> {
+        self.perform_or_else(|p| p.cached_block(height), None)
+    }
 }
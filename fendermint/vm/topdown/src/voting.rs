@@ -4,13 +4,14 @@
 use async_stm::{abort, atomically_or_err, retry, Stm, StmResult, TVar};
 use serde::{de::DeserializeOwned, Serialize};
 use std::hash::Hash;
+use std::ops::Bound;
 use std::{fmt::Debug, time::Duration};
 
 use crate::{BlockHash, BlockHeight};
 
 // Usign this type because it's `Hash`, unlike the normal `libsecp256k1::PublicKey`.
 pub use ipc_ipld_resolver::ValidatorKey;
-use ipc_ipld_resolver::VoteRecord;
+use ipc_ipld_resolver::{VoteRecord, VoteRecordVersion};
 
 pub type Weight = u64;
 
@@ -285,6 +286,49 @@ where
         Ok(None)
     }
 
+    /// Tally up the votes for a specific block, returning `(votes_seen, power_voted, quorum_threshold)`.
+    ///
+    /// As in [VoteTally::find_quorum], a vote for a later block on the chain we see as final
+    /// counts as a vote for this block as well, and the power is the current weight of the voters.
+    pub fn vote_tally(
+        &self,
+        block_height: BlockHeight,
+        block_hash: &V,
+    ) -> Stm<(usize, Weight, Weight)> {
+        let quorum_threshold = self.quorum_threshold()?;
+        let chain = self.chain.read()?;
+        let votes = self.votes.read()?;
+        let power_table = self.power_table.read()?;
+
+        let mut weight = 0;
+        let mut voters = im::HashSet::new();
+
+        let mut count_votes = |block_height: &BlockHeight, block_hash: &V| {
+            let Some(votes_for_block) = votes.get(block_height).and_then(|vs| vs.get(block_hash))
+            else {
+                return;
+            };
+            for vk in votes_for_block {
+                if voters.insert(vk.clone()).is_none() {
+                    weight += power_table.get(vk).cloned().unwrap_or_default();
+                }
+            }
+        };
+
+        count_votes(&block_height, block_hash);
+
+        // Votes on later blocks only imply this one if it's on our chain.
+        if chain.get(&block_height).and_then(|h| h.as_ref()) == Some(block_hash) {
+            for (h, bh) in chain.range((Bound::Excluded(block_height), Bound::Unbounded)) {
+                if let Some(bh) = bh {
+                    count_votes(h, bh);
+                }
+            }
+        }
+
+        Ok((voters.len(), weight, quorum_threshold))
+    }
+
     /// Call when a new finalized block is added to the ledger, to clear out all preceding blocks.
     ///
     /// After this operation the minimum item in the chain will the new finalized block.
@@ -331,13 +375,31 @@ where
     }
 }
 
+/// The version to gossip the vote about a parent block height with.
+///
+/// Votes are sent unversioned until the activation height is reached, so that the validators
+/// can upgrade to a release which understands the new version before anyone starts sending it.
+/// Receiving nodes accept both versions, so the tally is not affected by the switch.
+pub fn vote_record_version(
+    height: BlockHeight,
+    v1_activation_height: Option<BlockHeight>,
+) -> VoteRecordVersion {
+    match v1_activation_height {
+        Some(h) if height >= h => VoteRecordVersion::V1,
+        _ => VoteRecordVersion::V0,
+    }
+}
+
 /// Poll the vote tally for new finalized blocks and publish a vote about them if the validator is part of the power table.
+#[allow(clippy::too_many_arguments)]
 pub async fn publish_vote_loop<V, F>(
     vote_tally: VoteTally,
     // Throttle votes to maximum 1/interval
     vote_interval: Duration,
     // Publish a vote after a timeout even if it's the same as before.
     vote_timeout: Duration,
+    // Parent height from which votes are gossiped with the V1 payload.
+    vote_v1_height: Option<BlockHeight>,
     key: libp2p::identity::Keypair,
     subnet_id: ipc_api::subnet_id::SubnetID,
     client: ipc_ipld_resolver::Client<V>,
@@ -412,8 +474,9 @@ pub async fn publish_vote_loop<V, F>(
             tracing::debug!(block_height = next_height, "publishing finality vote");
 
             let vote = to_vote(next_height, next_hash.clone());
+            let version = vote_record_version(next_height, vote_v1_height);
 
-            match VoteRecord::signed(&key, subnet_id.clone(), vote) {
+            match VoteRecord::signed_with_version(&key, subnet_id.clone(), vote, version) {
                 Ok(vote) => {
                     if let Err(e) = client.publish_vote(vote) {
                         tracing::error!(error = e.to_string(), "failed to publish vote");
@@ -434,3 +497,55 @@ pub async fn publish_vote_loop<V, F>(
         prev = Some((next_height, next_hash, has_power));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use async_stm::{atomically, atomically_or_err};
+
+    use super::VoteTally;
+
+    type TestTally = VoteTally<&'static str, Vec<u8>>;
+
+    #[tokio::test]
+    async fn test_partial_quorum_tally() {
+        let tally = TestTally::new(vec![("a", 30), ("b", 30), ("c", 40)], (0, vec![0]));
+
+        atomically_or_err(|| {
+            tally.add_block(1, Some(vec![1]))?;
+            tally.add_block(2, None)?;
+            tally.add_block(3, Some(vec![3]))?;
+            tally.add_vote("a", 1, vec![1])?;
+            tally.add_vote("b", 3, vec![3])?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        // The vote for block 3 implies block 1 is final too, but 60% of the power is not enough.
+        assert_eq!(
+            atomically(|| tally.vote_tally(1, &vec![1])).await,
+            (2, 60, 67)
+        );
+        assert_eq!(
+            atomically(|| tally.vote_tally(3, &vec![3])).await,
+            (1, 30, 67)
+        );
+        assert_eq!(atomically(|| tally.find_quorum()).await, None);
+
+        // Votes for later blocks don't count for a block which is not on our chain.
+        assert_eq!(
+            atomically(|| tally.vote_tally(1, &vec![9])).await,
+            (0, 0, 67)
+        );
+
+        atomically_or_err(|| tally.add_vote("c", 1, vec![1]))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            atomically(|| tally.vote_tally(1, &vec![1])).await,
+            (3, 100, 67)
+        );
+        assert_eq!(atomically(|| tally.find_quorum()).await, Some((1, vec![1])));
+    }
+}
@@ -12,15 +12,19 @@ use crate::staking::StakingChangeRequest;
 use crate::subnet::SupplySource;
 use crate::subnet_id::SubnetID;
 use crate::{eth_to_fil_amount, ethers_address_to_fil_address};
-use anyhow::anyhow;
-use ethers::types::U256;
+use anyhow::{anyhow, bail};
+use ethers::abi::{Abi, Tokenize};
+use ethers::contract::EthError;
+use ethers::types::{H256, U256};
 use fvm_shared::address::{Address, Payload};
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use ipc_actors_abis::{
-    gateway_getter_facet, gateway_manager_facet, gateway_messenger_facet, lib_gateway,
-    register_subnet_facet, subnet_actor_checkpointing_facet, subnet_actor_diamond,
-    subnet_actor_getter_facet, top_down_finality_facet, xnet_messaging_facet,
+    checkpointing_facet, gateway_getter_facet, gateway_manager_facet, gateway_messenger_facet,
+    lib_gateway, lib_quorum, lib_staking, register_subnet_facet, subnet_actor_checkpointing_facet,
+    subnet_actor_diamond, subnet_actor_getter_facet, subnet_actor_manager_facet,
+    subnet_actor_pause_facet, subnet_actor_reward_facet, subnet_getter_facet,
+    top_down_finality_facet, xnet_messaging_facet,
 };
 
 /// The type conversion for IPC structs to evm solidity contracts. We need this convenient macro because
@@ -295,10 +299,99 @@ pub fn vec_to_bytes32(v: Vec<u8>) -> anyhow::Result<[u8; 32]> {
     Ok(r)
 }
 
+/// Half the order of the secp256k1 curve; `ECDSA.tryRecover` rejects signatures with a higher `s`.
+const SECP256K1_HALF_ORDER: &str =
+    "7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0";
+
+/// The hash of a bottom-up checkpoint the validators sign, calculated the same way
+/// as `keccak256(abi.encode(checkpoint))` in the subnet actor.
+pub fn bottom_up_checkpoint_hash(checkpoint: &BottomUpCheckpoint) -> anyhow::Result<[u8; 32]> {
+    let checkpoint =
+        subnet_actor_checkpointing_facet::BottomUpCheckpoint::try_from(checkpoint.clone())?;
+    Ok(ethers::utils::keccak256(ethers::abi::encode(
+        &(checkpoint,).into_tokens(),
+    )))
+}
+
+/// Recovers the address which made a 65 byte ECDSA signature of a hash, rejecting
+/// the signatures that `ECDSA.tryRecover` in the contracts would reject as well.
+pub fn recover_signer(hash: &[u8; 32], signature: &[u8]) -> anyhow::Result<ethers::types::Address> {
+    let signature = ethers::types::Signature::try_from(signature)?;
+
+    let half_order = U256::from_str_radix(SECP256K1_HALF_ORDER, 16)?;
+    if signature.s > half_order {
+        bail!("invalid signature s value");
+    }
+    if signature.v != 27 && signature.v != 28 {
+        bail!("invalid signature v value: {}", signature.v);
+    }
+
+    Ok(signature.recover(H256::from(*hash))?)
+}
+
+/// The ABIs of the IPC contracts whose custom errors we know how to decode.
+fn ipc_contract_abis() -> [&'static Abi; 17] {
+    [
+        &gateway_getter_facet::GATEWAYGETTERFACET_ABI,
+        &gateway_manager_facet::GATEWAYMANAGERFACET_ABI,
+        &gateway_messenger_facet::GATEWAYMESSENGERFACET_ABI,
+        &checkpointing_facet::CHECKPOINTINGFACET_ABI,
+        &top_down_finality_facet::TOPDOWNFINALITYFACET_ABI,
+        &xnet_messaging_facet::XNETMESSAGINGFACET_ABI,
+        &register_subnet_facet::REGISTERSUBNETFACET_ABI,
+        &subnet_getter_facet::SUBNETGETTERFACET_ABI,
+        &subnet_actor_diamond::SUBNETACTORDIAMOND_ABI,
+        &subnet_actor_manager_facet::SUBNETACTORMANAGERFACET_ABI,
+        &subnet_actor_getter_facet::SUBNETACTORGETTERFACET_ABI,
+        &subnet_actor_checkpointing_facet::SUBNETACTORCHECKPOINTINGFACET_ABI,
+        &subnet_actor_reward_facet::SUBNETACTORREWARDFACET_ABI,
+        &subnet_actor_pause_facet::SUBNETACTORPAUSEFACET_ABI,
+        &lib_gateway::LIBGATEWAY_ABI,
+        &lib_staking::LIBSTAKING_ABI,
+        &lib_quorum::LIBQUORUM_ABI,
+    ]
+}
+
+/// Decodes the revert data returned by the IPC contracts into a human readable reason,
+/// e.g. `NotEnoughCollateral` or `NotValidator(<address>)`.
+///
+/// Returns `None` if the data is neither a revert string nor a known IPC custom error.
+pub fn decode_revert_reason(data: &[u8]) -> Option<String> {
+    if let Some(reason) = String::decode_with_selector(data) {
+        return Some(reason);
+    }
+
+    if data.len() < 4 {
+        return None;
+    }
+    let (selector, args) = data.split_at(4);
+
+    let error = ipc_contract_abis()
+        .into_iter()
+        .flat_map(|abi| abi.errors())
+        .find(|e| &e.signature()[..4] == selector)?;
+
+    let reason = match error.decode(args) {
+        Ok(tokens) if !tokens.is_empty() => {
+            let tokens = tokens.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+            format!("{}({})", error.name, tokens.join(", "))
+        }
+        _ => error.name.clone(),
+    };
+    Some(reason)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::evm::subnet_id_to_evm_addresses;
+    use crate::checkpoint::BottomUpCheckpoint;
+    use crate::evm::{
+        bottom_up_checkpoint_hash, decode_revert_reason, recover_signer, subnet_id_to_evm_addresses,
+    };
     use crate::subnet_id::SubnetID;
+    use ethers::abi::Token;
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::H256;
+    use ethers::utils::id;
     use fvm_shared::address::Address;
     use ipc_types::EthAddress;
     use std::str::FromStr;
@@ -320,4 +413,64 @@ mod tests {
 
         assert_eq!(addrs, vec![a, b]);
     }
+
+    #[test]
+    fn test_decode_revert_reason() {
+        let data = id("NotEnoughCollateral()").to_vec();
+        assert_eq!(
+            decode_revert_reason(&data),
+            Some("NotEnoughCollateral".to_string())
+        );
+
+        let validator =
+            ethers::types::Address::from_str("0x2e714a3c385ea88a09998ed74db265dae9853667").unwrap();
+        let mut data = id("NotValidator(address)").to_vec();
+        data.extend(ethers::abi::encode(&[Token::Address(validator)]));
+        assert_eq!(
+            decode_revert_reason(&data),
+            Some("NotValidator(2e714a3c385ea88a09998ed74db265dae9853667)".to_string())
+        );
+
+        let mut data = id("Error(string)").to_vec();
+        data.extend(ethers::abi::encode(&[Token::String("not allowed".into())]));
+        assert_eq!(decode_revert_reason(&data), Some("not allowed".to_string()));
+
+        assert_eq!(decode_revert_reason(&id("UnknownError()")), None);
+        assert_eq!(decode_revert_reason(&[0x01]), None);
+    }
+
+    #[test]
+    fn test_recover_checkpoint_signer() {
+        let checkpoint = BottomUpCheckpoint {
+            subnet_id: SubnetID::new_root(123),
+            block_height: 10,
+            block_hash: vec![1; 32],
+            next_configuration_number: 0,
+            msgs: vec![],
+        };
+        let hash = bottom_up_checkpoint_hash(&checkpoint).unwrap();
+
+        let wallet = LocalWallet::from_bytes(&[1; 32]).unwrap();
+        let signature = wallet.sign_hash(H256::from(hash)).unwrap().to_vec();
+
+        assert_eq!(recover_signer(&hash, &signature).unwrap(), wallet.address());
+
+        // A different checkpoint recovers to some other address.
+        let other = bottom_up_checkpoint_hash(&BottomUpCheckpoint {
+            block_height: 20,
+            ..checkpoint
+        })
+        .unwrap();
+        assert_ne!(other, hash);
+        assert_ne!(
+            recover_signer(&other, &signature).unwrap(),
+            wallet.address()
+        );
+
+        // Truncated and non-Ethereum recovery IDs are rejected.
+        assert!(recover_signer(&hash, &signature[..64]).is_err());
+        let mut unshifted = signature.clone();
+        unshifted[64] -= 27;
+        assert!(recover_signer(&hash, &unshifted).is_err());
+    }
 }
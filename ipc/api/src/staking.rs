@@ -4,10 +4,12 @@
 //! Staking module related types and functions
 
 use crate::{eth_to_fil_amount, ethers_address_to_fil_address};
+use anyhow::anyhow;
 use ethers::utils::hex;
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use ipc_actors_abis::{lib_staking_change_log, subnet_actor_getter_facet};
+use num_traits::Zero;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
@@ -57,18 +59,21 @@ impl TryFrom<lib_staking_change_log::NewStakingChangeRequestFilter> for StakingC
 /// The staking validator information
 #[derive(Clone, Debug)]
 pub struct ValidatorStakingInfo {
-    confirmed_collateral: TokenAmount,
-    total_collateral: TokenAmount,
-    metadata: Vec<u8>,
+    pub confirmed_collateral: TokenAmount,
+    pub total_collateral: TokenAmount,
+    /// The power assigned by the subnet owner, only relevant in federated mode
+    pub federated_power: u128,
+    pub metadata: Vec<u8>,
 }
 
 impl Display for ValidatorStakingInfo {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "ValidatorStaking(confirmed_collateral: {}, total_collateral: {}, metadata: 0x{})",
+            "ValidatorStaking(confirmed_collateral: {}, total_collateral: {}, federated_power: {}, metadata: 0x{})",
             self.confirmed_collateral,
             self.total_collateral,
+            self.federated_power,
             hex::encode(&self.metadata)
         )
     }
@@ -81,6 +86,8 @@ impl TryFrom<subnet_actor_getter_facet::ValidatorInfo> for ValidatorStakingInfo
         Ok(Self {
             confirmed_collateral: eth_to_fil_amount(&value.confirmed_collateral)?,
             total_collateral: eth_to_fil_amount(&value.total_collateral)?,
+            federated_power: u128::try_from(value.federated_power)
+                .map_err(|_| anyhow!("federated power does not fit in u128"))?,
             metadata: value.metadata.to_vec(),
         })
     }
@@ -105,3 +112,128 @@ impl Display for ValidatorInfo {
         )
     }
 }
+
+/// The validator set of a subnet, including both active and waiting validators.
+#[derive(Clone, Debug)]
+pub struct ValidatorSetInfo {
+    /// The validators with their information, active validators first
+    pub validators: Vec<(Address, ValidatorInfo)>,
+    pub total_confirmed_collateral: TokenAmount,
+    pub total_collateral: TokenAmount,
+    pub total_federated_power: u128,
+}
+
+impl ValidatorSetInfo {
+    /// Builds the validator set from the active and waiting validators as returned
+    /// by the subnet actor getter facet.
+    pub fn from_contract_validators(
+        active: Vec<(
+            ethers::types::Address,
+            subnet_actor_getter_facet::ValidatorInfo,
+        )>,
+        waiting: Vec<(
+            ethers::types::Address,
+            subnet_actor_getter_facet::ValidatorInfo,
+        )>,
+    ) -> anyhow::Result<Self> {
+        let active = active.into_iter().map(|v| (v, true));
+        let waiting = waiting.into_iter().map(|v| (v, false));
+
+        let mut set = Self {
+            validators: vec![],
+            total_confirmed_collateral: TokenAmount::zero(),
+            total_collateral: TokenAmount::zero(),
+            total_federated_power: 0,
+        };
+
+        for ((addr, info), is_active) in active.chain(waiting) {
+            let staking = ValidatorStakingInfo::try_from(info)?;
+
+            set.total_confirmed_collateral += &staking.confirmed_collateral;
+            set.total_collateral += &staking.total_collateral;
+            set.total_federated_power = set
+                .total_federated_power
+                .checked_add(staking.federated_power)
+                .ok_or_else(|| anyhow!("total federated power overflow"))?;
+
+            set.validators.push((
+                ethers_address_to_fil_address(&addr)?,
+                ValidatorInfo {
+                    staking,
+                    is_active,
+                    is_waiting: !is_active,
+                },
+            ));
+        }
+
+        Ok(set)
+    }
+
+    /// Number of validators in the active set.
+    pub fn active_count(&self) -> usize {
+        self.validators.iter().filter(|(_, v)| v.is_active).count()
+    }
+
+    /// Number of validators in the waiting set.
+    pub fn waiting_count(&self) -> usize {
+        self.validators.iter().filter(|(_, v)| v.is_waiting).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValidatorSetInfo;
+    use crate::ethers_address_to_fil_address;
+    use ethers::types::{Address, Bytes, U256};
+    use fvm_shared::econ::TokenAmount;
+    use ipc_actors_abis::subnet_actor_getter_facet::ValidatorInfo;
+
+    fn validator_info(collateral: u64, federated_power: u64) -> ValidatorInfo {
+        ValidatorInfo {
+            federated_power: U256::from(federated_power),
+            confirmed_collateral: U256::from(collateral),
+            total_collateral: U256::from(collateral * 2),
+            metadata: Bytes::from(vec![1, 2, 3]),
+        }
+    }
+
+    #[test]
+    fn test_validator_set_from_contract() {
+        let a = Address::from_low_u64_be(1);
+        let b = Address::from_low_u64_be(2);
+        let c = Address::from_low_u64_be(3);
+
+        let set = ValidatorSetInfo::from_contract_validators(
+            vec![(a, validator_info(10, 1)), (b, validator_info(20, 2))],
+            vec![(c, validator_info(5, 0))],
+        )
+        .unwrap();
+
+        assert_eq!(set.validators.len(), 3);
+        assert_eq!(set.active_count(), 2);
+        assert_eq!(set.waiting_count(), 1);
+        assert_eq!(set.total_confirmed_collateral, TokenAmount::from_atto(35));
+        assert_eq!(set.total_collateral, TokenAmount::from_atto(70));
+        assert_eq!(set.total_federated_power, 3);
+
+        let (addr, info) = &set.validators[2];
+        assert_eq!(*addr, ethers_address_to_fil_address(&c).unwrap());
+        assert!(!info.is_active);
+        assert!(info.is_waiting);
+        assert_eq!(info.staking.metadata, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_validator_set_empty_waiting() {
+        let a = Address::from_low_u64_be(1);
+
+        let set =
+            ValidatorSetInfo::from_contract_validators(vec![(a, validator_info(10, 7))], vec![])
+                .unwrap();
+
+        assert_eq!(set.active_count(), 1);
+        assert_eq!(set.waiting_count(), 0);
+        assert!(set.validators.iter().all(|(_, v)| v.is_active));
+        assert_eq!(set.total_federated_power, 7);
+    }
+}
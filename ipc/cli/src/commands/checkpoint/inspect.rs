@@ -0,0 +1,356 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Inspect the bottom up checkpoint bundle at a height
+
+use std::fmt::{Debug, Write};
+use std::str::FromStr;
+
+use anyhow::bail;
+use async_trait::async_trait;
+use clap::Args;
+use ethers::abi::{ParamType, Token};
+use ethers::types::U256;
+use fvm_shared::clock::ChainEpoch;
+use ipc_api::checkpoint::BottomUpCheckpointBundle;
+use ipc_api::cross::{IpcEnvelope, IpcMsgKind};
+use ipc_api::evm::payload_to_evm_address;
+use ipc_api::subnet_id::SubnetID;
+use ipc_provider::manager::CheckpointSigners;
+
+use crate::commands::get_ipc_provider;
+use crate::{CommandLineHandler, GlobalArguments};
+
+/// The command to decode and print the bottom up checkpoint bundle at a height.
+pub(crate) struct InspectCheckpoint;
+
+#[async_trait]
+impl CommandLineHandler for InspectCheckpoint {
+    type Arguments = InspectCheckpointArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("inspect bottom up checkpoint with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = SubnetID::from_str(&arguments.subnet)?;
+
+        let Some(bundle) = provider
+            .get_bottom_up_bundle(&subnet, arguments.height)
+            .await?
+        else {
+            let genesis = provider.get_genesis_info(&subnet, None).await?;
+            bail!(missing_bundle_reason(
+                arguments.height,
+                genesis.bottom_up_checkpoint_period as ChainEpoch
+            ));
+        };
+
+        let signers = provider.checkpoint_signers(&subnet).await?;
+
+        if arguments.json {
+            let output = bundle_json(&bundle, &signers)?;
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else {
+            print!("{}", format_bundle(&bundle, &signers)?);
+        }
+
+        Ok(())
+    }
+}
+
+/// Explain why there is no checkpoint at the height.
+fn missing_bundle_reason(height: ChainEpoch, period: ChainEpoch) -> String {
+    if period > 0 && height % period != 0 {
+        let prev = height - height % period;
+        format!(
+            "height {height} is not a checkpoint boundary; checkpoints are cut every {period} blocks, the closest ones being at {prev} and {}",
+            prev + period
+        )
+    } else {
+        format!("no checkpoint found at height {height}, although it is a checkpoint boundary; it may not have been cut yet or it is missing")
+    }
+}
+
+/// The method called by a cross message, if it's a call.
+fn call_method(msg: &IpcEnvelope) -> Option<String> {
+    if msg.kind != IpcMsgKind::Call {
+        return None;
+    }
+    // The message is an ABI encoded `CallMsg { bytes method; bytes params; }`.
+    let call_msg = ParamType::Tuple(vec![ParamType::Bytes, ParamType::Bytes]);
+    let method = match ethers::abi::decode(&[call_msg], &msg.message) {
+        Ok(tokens) => match tokens.into_iter().next() {
+            Some(Token::Tuple(fields)) => match fields.into_iter().next() {
+                Some(Token::Bytes(method)) => format!("0x{}", hex::encode(method)),
+                _ => "unknown".to_string(),
+            },
+            _ => "unknown".to_string(),
+        },
+        Err(_) => "unknown".to_string(),
+    };
+    Some(method)
+}
+
+/// A signatory of the checkpoint with its current power.
+struct SignatoryPower {
+    address: String,
+    power: U256,
+    /// The share of the total active power, in basis points.
+    share_bps: U256,
+}
+
+fn signatory_powers(
+    bundle: &BottomUpCheckpointBundle,
+    signers: &CheckpointSigners,
+) -> anyhow::Result<Vec<SignatoryPower>> {
+    let total = signers.power.values().fold(U256::zero(), |acc, p| acc + *p);
+
+    bundle
+        .signatories
+        .iter()
+        .map(|signatory| {
+            let validator = payload_to_evm_address(signatory.payload())?;
+            let power = signers.power.get(&validator).cloned().unwrap_or_default();
+            let share_bps = if total.is_zero() {
+                U256::zero()
+            } else {
+                power * U256::from(10_000) / total
+            };
+            Ok(SignatoryPower {
+                address: signatory.to_string(),
+                power,
+                share_bps,
+            })
+        })
+        .collect()
+}
+
+fn format_share(share_bps: U256) -> String {
+    let bps = share_bps.as_u64();
+    format!("{}.{:02}%", bps / 100, bps % 100)
+}
+
+/// Render the checkpoint, its messages and its signatories as tables.
+fn format_bundle(
+    bundle: &BottomUpCheckpointBundle,
+    signers: &CheckpointSigners,
+) -> anyhow::Result<String> {
+    let checkpoint = &bundle.checkpoint;
+    let signatories = signatory_powers(bundle, signers)?;
+
+    let mut out = String::new();
+    writeln!(out, "subnet: {}", checkpoint.subnet_id)?;
+    writeln!(out, "block height: {}", checkpoint.block_height)?;
+    writeln!(out, "block hash: 0x{}", hex::encode(&checkpoint.block_hash))?;
+    writeln!(
+        out,
+        "next configuration number: {}",
+        checkpoint.next_configuration_number
+    )?;
+    writeln!(out, "messages: {}", checkpoint.msgs.len())?;
+
+    if !checkpoint.msgs.is_empty() {
+        writeln!(out)?;
+        writeln!(
+            out,
+            "{:>6} {:<8} {:<60} {:<60} {:>24} {:<10}",
+            "nonce", "kind", "from", "to", "value", "method"
+        )?;
+        for msg in checkpoint.msgs.iter() {
+            writeln!(
+                out,
+                "{:>6} {:<8} {:<60} {:<60} {:>24} {:<10}",
+                msg.nonce,
+                msg.kind.to_string(),
+                msg.from.to_string(),
+                msg.to.to_string(),
+                msg.value.to_string(),
+                call_method(msg).unwrap_or_else(|| "-".to_string())
+            )?;
+        }
+    }
+
+    writeln!(out)?;
+    writeln!(out, "{:<44} {:>24} {:>8}", "signatory", "power", "share")?;
+    let mut signed = U256::zero();
+    for s in signatories.iter() {
+        signed += s.share_bps;
+        writeln!(
+            out,
+            "{:<44} {:>24} {:>8}",
+            s.address,
+            s.power.to_string(),
+            format_share(s.share_bps)
+        )?;
+    }
+    writeln!(
+        out,
+        "signatories: {}, signed power share: {}, majority: {}%",
+        signatories.len(),
+        format_share(signed),
+        signers.majority_percentage
+    )?;
+
+    Ok(out)
+}
+
+/// Render the checkpoint, its messages and its signatories as JSON.
+fn bundle_json(
+    bundle: &BottomUpCheckpointBundle,
+    signers: &CheckpointSigners,
+) -> anyhow::Result<serde_json::Value> {
+    let checkpoint = &bundle.checkpoint;
+
+    let msgs = checkpoint
+        .msgs
+        .iter()
+        .map(|msg| {
+            serde_json::json!({
+                "nonce": msg.nonce,
+                "kind": msg.kind.to_string(),
+                "from": msg.from.to_string(),
+                "to": msg.to.to_string(),
+                "value": msg.value.to_string(),
+                "method": call_method(msg),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let signatories = signatory_powers(bundle, signers)?
+        .into_iter()
+        .map(|s| {
+            serde_json::json!({
+                "address": s.address,
+                "power": s.power.to_string(),
+                "share": format_share(s.share_bps),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(serde_json::json!({
+        "subnet": checkpoint.subnet_id.to_string(),
+        "block_height": checkpoint.block_height,
+        "block_hash": format!("0x{}", hex::encode(&checkpoint.block_hash)),
+        "next_configuration_number": checkpoint.next_configuration_number,
+        "messages_count": checkpoint.msgs.len(),
+        "messages": msgs,
+        "signatories": signatories,
+        "majority_percentage": signers.majority_percentage,
+    }))
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Decode and print the bottom up checkpoint bundle of a child subnet at a height")]
+pub(crate) struct InspectCheckpointArgs {
+    #[arg(long, help = "The child subnet the checkpoint was cut in")]
+    pub subnet: String,
+    #[arg(long, help = "The height of the checkpoint in the child subnet")]
+    pub height: ChainEpoch,
+    #[arg(long, help = "Print the checkpoint as JSON")]
+    pub json: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::Token;
+    use ethers::types::U256;
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::address::IPCAddress;
+    use ipc_api::checkpoint::{BottomUpCheckpoint, BottomUpCheckpointBundle};
+    use ipc_api::cross::{IpcEnvelope, IpcMsgKind};
+    use ipc_api::ethers_address_to_fil_address;
+    use ipc_api::subnet_id::SubnetID;
+    use ipc_provider::manager::CheckpointSigners;
+
+    use super::{bundle_json, format_bundle, missing_bundle_reason};
+
+    fn validator(i: u8) -> ethers::types::Address {
+        ethers::types::Address::repeat_byte(i)
+    }
+
+    /// A checkpoint with a transfer and a call, signed by two of the three validators.
+    fn bundle() -> (BottomUpCheckpointBundle, CheckpointSigners) {
+        let root = SubnetID::new_root(123);
+        let subnet = SubnetID::new_from_parent(&root, Address::new_id(1001));
+
+        let transfer = IpcEnvelope::new_release_msg(
+            &subnet,
+            &Address::new_id(10),
+            &Address::new_id(20),
+            TokenAmount::from_whole(5),
+        )
+        .unwrap();
+
+        let call = IpcEnvelope {
+            kind: IpcMsgKind::Call,
+            from: IPCAddress::new(&subnet, &Address::new_id(10)).unwrap(),
+            to: IPCAddress::new(&root, &Address::new_id(30)).unwrap(),
+            value: TokenAmount::from_whole(0),
+            message: ethers::abi::encode(&[Token::Tuple(vec![
+                Token::Bytes(vec![0xde, 0xad, 0xbe, 0xef]),
+                Token::Bytes(vec![]),
+            ])]),
+            nonce: 1,
+        };
+
+        let bundle = BottomUpCheckpointBundle {
+            checkpoint: BottomUpCheckpoint {
+                subnet_id: subnet,
+                block_height: 100,
+                block_hash: vec![1; 32],
+                next_configuration_number: 2,
+                msgs: vec![transfer, call],
+            },
+            signatures: vec![vec![0; 65], vec![0; 65]],
+            signatories: vec![
+                ethers_address_to_fil_address(&validator(1)).unwrap(),
+                ethers_address_to_fil_address(&validator(2)).unwrap(),
+            ],
+        };
+
+        let signers = CheckpointSigners {
+            power: [(1, 50), (2, 25), (3, 25)]
+                .into_iter()
+                .map(|(i, p)| (validator(i), U256::from(p)))
+                .collect(),
+            majority_percentage: 67,
+        };
+
+        (bundle, signers)
+    }
+
+    #[test]
+    fn test_format_bundle() {
+        let (bundle, signers) = bundle();
+        let out = format_bundle(&bundle, &signers).unwrap();
+
+        assert!(out.contains("block height: 100"));
+        assert!(out.contains(&format!("block hash: 0x{}", "01".repeat(32))));
+        assert!(out.contains("next configuration number: 2"));
+        assert!(out.contains("messages: 2"));
+        assert!(out.contains("0xdeadbeef"));
+        assert!(out.contains("50.00%"));
+        assert!(out.contains("25.00%"));
+        assert!(out.contains("signatories: 2, signed power share: 75.00%, majority: 67%"));
+    }
+
+    #[test]
+    fn test_bundle_json() {
+        let (bundle, signers) = bundle();
+        let json = bundle_json(&bundle, &signers).unwrap();
+
+        assert_eq!(json["block_height"], 100);
+        assert_eq!(json["messages_count"], 2);
+        assert_eq!(json["messages"][0]["kind"], "Transfer");
+        assert_eq!(json["messages"][0]["method"], serde_json::Value::Null);
+        assert_eq!(json["messages"][1]["method"], "0xdeadbeef");
+        assert_eq!(json["signatories"][0]["power"], "50");
+        assert_eq!(json["signatories"][1]["share"], "25.00%");
+    }
+
+    #[test]
+    fn test_missing_bundle_reason() {
+        assert!(missing_bundle_reason(105, 10).contains("closest ones being at 100 and 110"));
+        assert!(missing_bundle_reason(110, 10).contains("it is a checkpoint boundary"));
+    }
+}
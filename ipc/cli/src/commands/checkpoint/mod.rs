@@ -4,6 +4,7 @@ use crate::commands::checkpoint::bottomup_bundles::{GetBottomUpBundles, GetBotto
 use crate::commands::checkpoint::bottomup_height::{
     LastBottomUpCheckpointHeight, LastBottomUpCheckpointHeightArgs,
 };
+use crate::commands::checkpoint::inspect::{InspectCheckpoint, InspectCheckpointArgs};
 use crate::commands::checkpoint::list_validator_changes::{
     ListValidatorChanges, ListValidatorChangesArgs,
 };
@@ -16,6 +17,7 @@ use clap::{Args, Subcommand};
 
 mod bottomup_bundles;
 mod bottomup_height;
+mod inspect;
 mod list_validator_changes;
 mod quorum_reached;
 mod relayer;
@@ -42,6 +44,7 @@ impl CheckpointCommandsArgs {
             Commands::LastBottomupCheckpointHeight(args) => {
                 LastBottomUpCheckpointHeight::handle(global, args).await
             }
+            Commands::Inspect(args) => InspectCheckpoint::handle(global, args).await,
         }
     }
 }
@@ -53,4 +56,5 @@ pub(crate) enum Commands {
     ListBottomupBundle(GetBottomUpBundlesArgs),
     QuorumReachedEvents(GetQuorumReachedEventsArgs),
     LastBottomupCheckpointHeight(LastBottomUpCheckpointHeightArgs),
+    Inspect(InspectCheckpointArgs),
 }
@@ -1,7 +1,7 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
 
-use crate::commands::get_subnet_config;
+use crate::commands::{evm_keystore_password, get_subnet_config};
 use crate::{require_fil_addr_from_str, CommandLineHandler, GlobalArguments};
 use anyhow::anyhow;
 use async_trait::async_trait;
@@ -31,7 +31,8 @@ impl CommandLineHandler for BottomUpRelayer {
 
         let config_path = global.config_path();
         let config = Arc::new(Config::from_file(&config_path)?);
-        let mut keystore = new_evm_keystore_from_config(config)?;
+        let password = evm_keystore_password(&config)?;
+        let mut keystore = new_evm_keystore_from_config(config, password.as_deref())?;
         let submitter = match (arguments.submitter.as_ref(), keystore.get_default()?) {
             (Some(submitter), _) => require_fil_addr_from_str(submitter)?,
             (None, Some(addr)) => {
@@ -63,6 +64,10 @@ impl CommandLineHandler for BottomUpRelayer {
             manager = manager.with_finalization_blocks(v as ChainEpoch);
         }
 
+        if let Some(h) = arguments.from_height {
+            manager = manager.with_from_height(h as ChainEpoch);
+        }
+
         let interval = Duration::from_secs(
             arguments
                 .checkpoint_interval_sec
@@ -95,4 +100,9 @@ pub(crate) struct BottomUpRelayerArgs {
         help = "The max parallelism for submitting checkpoints"
     )]
     pub max_parallelism: usize,
+    #[arg(
+        long,
+        help = "Submit missed checkpoints from this height instead of the last one committed in the parent"
+    )]
+    pub from_height: Option<u64>,
 }
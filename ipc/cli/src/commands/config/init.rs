@@ -1,11 +1,23 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
 use crate::{CommandLineHandler, GlobalArguments};
+use anyhow::{anyhow, bail, Context};
 use async_trait::async_trait;
-use ipc_provider::config::DEFAULT_CONFIG_TEMPLATE;
+use fvm_shared::address::Address;
+use ipc_api::subnet_id::SubnetID;
+use ipc_provider::config::subnet::{EVMSubnet, SubnetConfig};
+use ipc_provider::config::{Config, Subnet, DEFAULT_CONFIG_TEMPLATE};
+use ipc_types::EthAddress;
 use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use url::Url;
 
-use clap::Args;
+use clap::{Args, ValueEnum};
+
+/// How long to wait for the RPC endpoint to answer when checking it's reachable.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// The command to initialize a new config template in a specific path
 pub(crate) struct InitConfig;
@@ -14,11 +26,29 @@ pub(crate) struct InitConfig;
 impl CommandLineHandler for InitConfig {
     type Arguments = InitConfigArgs;
 
-    async fn handle(global: &GlobalArguments, _arguments: &Self::Arguments) -> anyhow::Result<()> {
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
         let path = global.config_path();
-        log::debug!("initializing empty config file in {}", path);
+        log::debug!("initializing config file in {}", path);
+
+        let file_path = Path::new(&path);
+        if file_path.exists() && !arguments.force {
+            bail!("config file {path} already exists; use --force to overwrite it");
+        }
+
+        let contents = match arguments.network {
+            None => DEFAULT_CONFIG_TEMPLATE.to_string(),
+            Some(network) => {
+                let subnet = match network {
+                    ParentNetwork::Calibration => calibration_subnet(),
+                    ParentNetwork::Custom => custom_subnet(arguments)?,
+                };
+                if !arguments.skip_probe {
+                    probe(subnet.rpc_http()).await?;
+                }
+                render_config(subnet)?
+            }
+        };
 
-        let file_path = std::path::Path::new(&path);
         if let Some(parent) = file_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
@@ -26,18 +56,249 @@ impl CommandLineHandler for InitConfig {
             log::error!("couldn't create config file");
             e
         })?;
-        file.write_all(DEFAULT_CONFIG_TEMPLATE.as_bytes())
-            .map_err(|e| {
-                log::error!("error populating empty config template");
-                e
-            })?;
+        file.write_all(contents.as_bytes()).map_err(|e| {
+            log::error!("error populating config file");
+            e
+        })?;
 
-        log::info!("Empty config populated successful in {}", &path);
+        log::info!("Config populated successfully in {}", &path);
 
         Ok(())
     }
 }
 
+/// Parent networks to generate the config for.
+///
+/// Mainnet will be added once the IPC contracts are deployed there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ParentNetwork {
+    /// Filecoin Calibration, with the well-known gateway and registry.
+    Calibration,
+    /// Any other network, with the RPC URL and the contract addresses given in the
+    /// arguments or prompted for.
+    Custom,
+}
+
+/// The Filecoin Calibration subnet, as in the [`DEFAULT_CONFIG_TEMPLATE`].
+fn calibration_subnet() -> Subnet {
+    make_subnet(
+        SubnetID::new_root(314159),
+        Url::parse("https://api.calibration.node.glif.io/rpc/v1").expect("valid URL"),
+        parse_eth_address("0x1AEe8A878a22280fc2753b3C63571C8F895D2FE3").expect("valid address"),
+        parse_eth_address("0x0b4e239FF21b40120cDa817fba77bD1B366c1bcD").expect("valid address"),
+    )
+}
+
+/// Put together a custom subnet, prompting for whatever wasn't given in the arguments.
+fn custom_subnet(arguments: &InitConfigArgs) -> anyhow::Result<Subnet> {
+    let id = arg_or_prompt(&arguments.subnet_id, "Subnet ID (e.g. /r314159)", |s| {
+        SubnetID::from_str(s).map_err(|e| anyhow!("invalid subnet ID: {e}"))
+    })?;
+    let provider_http = arg_or_prompt(&arguments.provider_http, "RPC URL", parse_rpc_url)?;
+    let gateway_addr = arg_or_prompt(
+        &arguments.gateway_addr,
+        "Gateway address",
+        parse_eth_address,
+    )?;
+    let registry_addr = arg_or_prompt(
+        &arguments.registry_addr,
+        "Registry address",
+        parse_eth_address,
+    )?;
+
+    let subnet = make_subnet(id, provider_http, gateway_addr, registry_addr);
+    subnet.validate()?;
+    Ok(subnet)
+}
+
+fn make_subnet(
+    id: SubnetID,
+    provider_http: Url,
+    gateway_addr: Address,
+    registry_addr: Address,
+) -> Subnet {
+    Subnet {
+        id,
+        config: SubnetConfig::Fevm(EVMSubnet {
+            provider_http,
+            provider_ws: None,
+            provider_timeout: None,
+            auth_token: None,
+            registry_addr,
+            gateway_addr,
+            checkpoint_submission: None,
+        }),
+    }
+}
+
+/// Render the config file with the keystore in the default repo and a single subnet.
+fn render_config(subnet: Subnet) -> anyhow::Result<String> {
+    let mut config = Config::new();
+    config.keystore_path = Some("~/.ipc".to_string());
+    config.add_subnet(subnet);
+    Ok(toml::to_string_pretty(&config)?)
+}
+
+/// Parse an EVM address, rejecting mixed-case addresses with an invalid EIP-55 checksum,
+/// which is usually a sign of a typo.
+fn parse_eth_address(s: &str) -> anyhow::Result<Address> {
+    let s = s.trim();
+    let addr =
+        ethers::types::Address::from_str(s).map_err(|e| anyhow!("invalid address {s}: {e}"))?;
+
+    let hex = s.strip_prefix("0x").unwrap_or(s);
+    let mixed_case =
+        hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+    if mixed_case && ethers::utils::to_checksum(&addr, None) != format!("0x{hex}") {
+        bail!("invalid checksum in address {s}; check it for typos");
+    }
+
+    Ok(Address::from(EthAddress(addr.0)))
+}
+
+fn parse_rpc_url(s: &str) -> anyhow::Result<Url> {
+    let url = Url::parse(s.trim()).map_err(|e| anyhow!("invalid URL {s}: {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("the RPC URL has to be http or https: {url}");
+    }
+    Ok(url)
+}
+
+/// Parse the value of an argument if it was given, otherwise keep prompting until we get a valid one.
+fn arg_or_prompt<T>(
+    arg: &Option<String>,
+    question: &str,
+    parse: impl Fn(&str) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    if let Some(value) = arg {
+        return parse(value);
+    }
+    loop {
+        print!("{question}: ");
+        std::io::stdout().flush()?;
+
+        let mut line = String::new();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            bail!("no value given for: {question}");
+        }
+        match parse(line.trim()) {
+            Ok(value) => return Ok(value),
+            Err(e) => println!("{e:#}"),
+        }
+    }
+}
+
+/// Check that the RPC endpoint answers a JSON-RPC request.
+async fn probe(url: &Url) -> anyhow::Result<()> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_chainId",
+        "params": [],
+    });
+    reqwest::Client::new()
+        .post(url.clone())
+        .timeout(PROBE_TIMEOUT)
+        .json(&request)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .with_context(|| {
+            format!("cannot reach {url}; use --skip-probe to write the config anyway")
+        })?;
+    Ok(())
+}
+
 #[derive(Debug, Args)]
-#[command(about = "Arguments to initialize a new empty config file")]
-pub(crate) struct InitConfigArgs {}
+#[command(about = "Arguments to initialize a new config file")]
+pub(crate) struct InitConfigArgs {
+    #[arg(
+        long,
+        value_enum,
+        help = "The parent network to configure; writes a commented template if not given"
+    )]
+    pub network: Option<ParentNetwork>,
+    #[arg(
+        long,
+        help = "The subnet ID of the custom network; prompted for if not given"
+    )]
+    pub subnet_id: Option<String>,
+    #[arg(
+        long,
+        help = "The RPC URL of the custom network; prompted for if not given"
+    )]
+    pub provider_http: Option<String>,
+    #[arg(
+        long,
+        help = "The gateway address of the custom network; prompted for if not given"
+    )]
+    pub gateway_addr: Option<String>,
+    #[arg(
+        long,
+        help = "The registry address of the custom network; prompted for if not given"
+    )]
+    pub registry_addr: Option<String>,
+    #[arg(long, help = "Don't check that the RPC URL is reachable")]
+    pub skip_probe: bool,
+    #[arg(long, help = "Overwrite the config file if it already exists")]
+    pub force: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use ipc_provider::config::Config;
+
+    use super::{calibration_subnet, make_subnet, parse_eth_address, parse_rpc_url, render_config};
+
+    /// Write the config out and load it the way the CLI does.
+    fn roundtrip(toml: &str) -> Config {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, toml).unwrap();
+        Config::from_file(&path).unwrap()
+    }
+
+    #[test]
+    fn test_calibration_config_parses() {
+        let subnet = calibration_subnet();
+        let config = roundtrip(&render_config(subnet.clone()).unwrap());
+
+        assert_eq!(config.keystore_path, Some("~/.ipc".to_string()));
+        assert_eq!(config.subnets.len(), 1);
+        assert_eq!(config.subnets[&subnet.id], subnet);
+    }
+
+    #[test]
+    fn test_custom_config_parses() {
+        let subnet = make_subnet(
+            "/r314159/t410f5kaxlxjakwsjlxeulvcqjamx5ru5mbyyqoqp4oq"
+                .parse()
+                .unwrap(),
+            parse_rpc_url("http://localhost:8545").unwrap(),
+            parse_eth_address("0x77aa40b105843728088c0132e43fc44348881da8").unwrap(),
+            parse_eth_address("0x74539671A1D2F1C8F200826BABA665179F53A1B7").unwrap(),
+        );
+        let config = roundtrip(&render_config(subnet.clone()).unwrap());
+
+        assert_eq!(config.subnets[&subnet.id], subnet);
+    }
+
+    #[test]
+    fn test_parse_eth_address_checksum() {
+        // Checksummed, all lowercase and all uppercase are all fine.
+        assert!(parse_eth_address("0x1AEe8A878a22280fc2753b3C63571C8F895D2FE3").is_ok());
+        assert!(parse_eth_address("0x1aee8a878a22280fc2753b3c63571c8f895d2fe3").is_ok());
+        assert!(parse_eth_address("0x1AEE8A878A22280FC2753B3C63571C8F895D2FE3").is_ok());
+        // A single character with the wrong case.
+        assert!(parse_eth_address("0x1aEe8A878a22280fc2753b3C63571C8F895D2FE3").is_err());
+        // Too short.
+        assert!(parse_eth_address("0x1AEe8A878a22280fc2753b3C63571C8F895D2F").is_err());
+    }
+
+    #[test]
+    fn test_parse_rpc_url() {
+        assert!(parse_rpc_url("https://api.calibration.node.glif.io/rpc/v1").is_ok());
+        assert!(parse_rpc_url("ws://localhost:8545").is_err());
+        assert!(parse_rpc_url("localhost").is_err());
+    }
+}
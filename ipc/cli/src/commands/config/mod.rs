@@ -3,11 +3,15 @@
 //! This mod triggers a config reload in the IPC-Agent Json RPC server.
 
 mod init;
+mod show;
+mod show_subnets;
 
 use clap::{Args, Subcommand};
 use std::fmt::Debug;
 
 use crate::commands::config::init::{InitConfig, InitConfigArgs};
+use crate::commands::config::show::{ShowConfig, ShowConfigArgs};
+use crate::commands::config::show_subnets::{ShowSubnets, ShowSubnetsArgs};
 use crate::{CommandLineHandler, GlobalArguments};
 
 #[derive(Debug, Args)]
@@ -22,6 +26,8 @@ impl ConfigCommandsArgs {
     pub async fn handle(&self, global: &GlobalArguments) -> anyhow::Result<()> {
         match &self.command {
             Commands::Init(args) => InitConfig::handle(global, args).await,
+            Commands::Show(args) => ShowConfig::handle(global, args).await,
+            Commands::ShowSubnets(args) => ShowSubnets::handle(global, args).await,
         }
     }
 }
@@ -29,4 +35,6 @@ impl ConfigCommandsArgs {
 #[derive(Debug, Subcommand)]
 pub(crate) enum Commands {
     Init(InitConfigArgs),
+    Show(ShowConfigArgs),
+    ShowSubnets(ShowSubnetsArgs),
 }
@@ -0,0 +1,82 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+use crate::{CommandLineHandler, GlobalArguments};
+use async_trait::async_trait;
+use ipc_provider::config::{subnet::SubnetConfig, Config};
+
+use clap::Args;
+
+/// Placeholder for secrets which shouldn't end up in the terminal or in bug reports.
+const REDACTED: &str = "<redacted>";
+
+/// The command to print the config as the CLI sees it after loading it.
+pub(crate) struct ShowConfig;
+
+#[async_trait]
+impl CommandLineHandler for ShowConfig {
+    type Arguments = ShowConfigArgs;
+
+    async fn handle(global: &GlobalArguments, _arguments: &Self::Arguments) -> anyhow::Result<()> {
+        let path = global.config_path();
+        log::debug!("showing config loaded from {}", path);
+
+        let mut config = global.config()?;
+        redact(&mut config);
+
+        println!("# loaded from {path}");
+        println!("{}", toml::to_string_pretty(&config)?);
+
+        Ok(())
+    }
+}
+
+/// Replace the auth tokens of the subnets with a placeholder.
+fn redact(config: &mut Config) {
+    for subnet in config.subnets.values_mut() {
+        match &mut subnet.config {
+            SubnetConfig::Fevm(s) => {
+                if s.auth_token.is_some() {
+                    s.auth_token = Some(REDACTED.to_string());
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Print the config loaded from the config file, with auth tokens redacted")]
+pub(crate) struct ShowConfigArgs {}
+
+#[cfg(test)]
+mod tests {
+    use ipc_provider::config::Config;
+
+    use super::{redact, REDACTED};
+
+    #[test]
+    fn test_redact_auth_tokens() {
+        let mut config = Config::from_toml_str(
+            r#"
+            keystore_path = "~/.ipc"
+
+            [[subnets]]
+            id = "/r314159"
+
+            [subnets.config]
+            network_type = "fevm"
+            provider_http = "https://api.calibration.node.glif.io/rpc/v1"
+            auth_token = "secret"
+            gateway_addr = "0x1AEe8A878a22280fc2753b3C63571C8F895D2FE3"
+            registry_addr = "0x0b4e239FF21b40120cDa817fba77bD1B366c1bcD"
+            "#,
+        )
+        .unwrap();
+
+        redact(&mut config);
+
+        let shown = toml::to_string_pretty(&config).unwrap();
+        assert!(!shown.contains("secret"));
+        assert!(shown.contains(REDACTED));
+        assert!(shown.contains("/r314159"));
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+use crate::{CommandLineHandler, GlobalArguments};
+use async_trait::async_trait;
+use ipc_api::evm::payload_to_evm_address;
+use ipc_provider::config::subnet::SubnetConfig;
+
+use clap::Args;
+
+/// The command to list the subnets in the config, to check they are the expected ones.
+pub(crate) struct ShowSubnets;
+
+#[async_trait]
+impl CommandLineHandler for ShowSubnets {
+    type Arguments = ShowSubnetsArgs;
+
+    async fn handle(global: &GlobalArguments, _arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("showing subnets configured in {}", global.config_path());
+
+        let config = global.config()?;
+
+        let mut subnets = config.subnets.values().collect::<Vec<_>>();
+        subnets.sort_by_key(|s| s.id.to_string());
+
+        if subnets.is_empty() {
+            println!("no subnets configured");
+        }
+
+        for subnet in subnets {
+            match &subnet.config {
+                SubnetConfig::Fevm(s) => {
+                    let gateway = payload_to_evm_address(s.gateway_addr.payload())?;
+                    let registry = payload_to_evm_address(s.registry_addr.payload())?;
+                    println!("{}", subnet.id);
+                    println!("    provider_http: {}", s.provider_http);
+                    println!("    gateway_addr: {gateway:?}");
+                    println!("    registry_addr: {registry:?}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "List the subnets in the config file with their RPC URL and contracts")]
+pub(crate) struct ShowSubnetsArgs {}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: MIT
 use self::fund::{FundWithToken, FundWithTokenArgs, PreFund, PreFundArgs};
 use self::release::{PreRelease, PreReleaseArgs};
+use self::status::{CrossMsgStatusArgs, CrossMsgStatusCmd};
 use self::topdown_cross::{
     LatestParentFinality, LatestParentFinalityArgs, ListTopdownMsgs, ListTopdownMsgsArgs,
 };
@@ -18,6 +19,7 @@ use clap::{Args, Subcommand};
 pub mod fund;
 pub mod propagate;
 pub mod release;
+mod status;
 mod topdown_cross;
 
 #[derive(Debug, Args)]
@@ -39,6 +41,7 @@ impl CrossMsgsCommandsArgs {
             Commands::Propagate(args) => Propagate::handle(global, args).await,
             Commands::ListTopdownMsgs(args) => ListTopdownMsgs::handle(global, args).await,
             Commands::ParentFinality(args) => LatestParentFinality::handle(global, args).await,
+            Commands::Status(args) => CrossMsgStatusCmd::handle(global, args).await,
         }
     }
 }
@@ -53,4 +56,5 @@ pub(crate) enum Commands {
     Propagate(PropagateArgs),
     ListTopdownMsgs(ListTopdownMsgsArgs),
     ParentFinality(LatestParentFinalityArgs),
+    Status(CrossMsgStatusArgs),
 }
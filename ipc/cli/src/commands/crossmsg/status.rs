@@ -0,0 +1,99 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Cross-net message status cli command handler.
+
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use clap::{ArgGroup, Args};
+use fvm_shared::clock::ChainEpoch;
+use ipc_api::subnet_id::SubnetID;
+use ipc_provider::crossmsg::{CrossMsgDirection, CrossMsgRef, CrossMsgStatus};
+
+use crate::commands::get_ipc_provider;
+use crate::{CommandLineHandler, GlobalArguments};
+
+/// The command to find out whether a cross-net message has reached its destination.
+pub(crate) struct CrossMsgStatusCmd;
+
+#[async_trait]
+impl CommandLineHandler for CrossMsgStatusCmd {
+    type Arguments = CrossMsgStatusArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("cross msg status with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let msg = match (arguments.nonce, arguments.epoch) {
+            (Some(nonce), _) => CrossMsgRef::Nonce(nonce),
+            (None, Some(epoch)) => CrossMsgRef::Epoch(epoch),
+            (None, None) => unreachable!("clap requires either the nonce or the epoch"),
+        };
+
+        let status = provider
+            .cross_msg_status(&subnet, arguments.direction, msg)
+            .await?;
+
+        println!("{}", explain(arguments.direction, &status));
+        Ok(())
+    }
+}
+
+/// Describe the status of the message to the user, and what it is waiting for.
+fn explain(direction: CrossMsgDirection, status: &CrossMsgStatus) -> String {
+    match (direction, status) {
+        (CrossMsgDirection::TopDown, CrossMsgStatus::Pending) => {
+            "pending: the child subnet has not committed the parent finality including the message yet".to_string()
+        }
+        (CrossMsgDirection::TopDown, CrossMsgStatus::InFlight { height }) => format!(
+            "in flight: the child subnet committed the parent finality at {height}, but has not executed the message yet"
+        ),
+        (CrossMsgDirection::TopDown, CrossMsgStatus::Executed { height }) => format!(
+            "executed: the child subnet executed the message, with the parent finality at {height}"
+        ),
+        (CrossMsgDirection::BottomUp, CrossMsgStatus::Pending) => {
+            "pending: the child subnet has not created the checkpoint including the message yet".to_string()
+        }
+        (CrossMsgDirection::BottomUp, CrossMsgStatus::InFlight { height }) => format!(
+            "in flight: the message is in the checkpoint at {height}, which has not been submitted to the parent yet"
+        ),
+        (CrossMsgDirection::BottomUp, CrossMsgStatus::Executed { height }) => format!(
+            "executed: the parent executed the message, with the checkpoint at {height}"
+        ),
+        (_, CrossMsgStatus::Unknown) => {
+            "unknown: no such message was found; check the subnet, the direction and the nonce or epoch".to_string()
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Check whether a fund or release message has been executed in its destination")]
+#[clap(group(ArgGroup::new("message")
+.required(true)
+.multiple(false)
+.args(&["nonce", "epoch"]),
+))]
+pub(crate) struct CrossMsgStatusArgs {
+    #[arg(long, help = "The child subnet the message was sent to or from")]
+    pub subnet: String,
+    #[arg(
+        long,
+        help = "The direction of the message: top-down (e.g. fund) or bottom-up (e.g. release)",
+        value_parser = CrossMsgDirection::from_str,
+    )]
+    pub direction: CrossMsgDirection,
+    #[arg(
+        long,
+        group = "message",
+        help = "The nonce the gateway assigned to the message"
+    )]
+    pub nonce: Option<u64>,
+    #[arg(
+        long,
+        group = "message",
+        help = "The epoch the message was sent at, as printed by fund or release"
+    )]
+    pub epoch: Option<ChainEpoch>,
+}
@@ -24,6 +24,8 @@ use ipc_api::ethers_address_to_fil_address;
 use fvm_shared::address::set_current_network;
 use ipc_api::subnet_id::SubnetID;
 use ipc_provider::config::{Config, Subnet};
+use ipc_provider::evm_keystore_path;
+use ipc_wallet::{EthKeyAddress, PersistentKeyStore};
 use std::fmt::Debug;
 use std::io;
 use std::path::Path;
@@ -147,7 +149,39 @@ fn print_completions<G: Generator>(gen: G, cmd: &mut Command) {
 }
 
 pub(crate) fn get_ipc_provider(global: &GlobalArguments) -> Result<ipc_provider::IpcProvider> {
-    ipc_provider::IpcProvider::new_from_config(global.config_path())
+    let password = evm_keystore_password(&global.config()?)?;
+    ipc_provider::IpcProvider::new_from_config(global.config_path(), password.as_deref())
+}
+
+/// The password of the evm keystore in the config, taken from the `IPC_KEYSTORE_PASSWORD`
+/// environment variable, or prompted for if the variable is not set but the keystore is encrypted.
+pub(crate) fn evm_keystore_password(config: &Config) -> Result<Option<String>> {
+    if let Ok(password) = std::env::var(ipc_wallet::EVM_KEYSTORE_PASSWORD_ENV) {
+        return Ok(Some(password));
+    }
+    let Some(repo) = config.keystore_path.as_ref() else {
+        return Ok(None);
+    };
+    if PersistentKeyStore::<EthKeyAddress>::is_encrypted_at(&evm_keystore_path(repo))? {
+        Ok(Some(prompt_keystore_password(false)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Reads the password of the evm keystore from the terminal, asking for it twice to
+/// `confirm` a new password.
+pub(crate) fn prompt_keystore_password(confirm: bool) -> Result<String> {
+    let password = rpassword::prompt_password("Keystore password: ")
+        .map_err(|e| anyhow!("failed to read the keystore password: {e}"))?;
+    if confirm {
+        let repeated = rpassword::prompt_password("Repeat the keystore password: ")
+            .map_err(|e| anyhow!("failed to read the keystore password: {e}"))?;
+        if password != repeated {
+            return Err(anyhow!("the passwords do not match"));
+        }
+    }
+    Ok(password)
 }
 
 pub(crate) fn f64_to_token_amount(f: f64) -> anyhow::Result<TokenAmount> {
@@ -185,7 +219,10 @@ pub(crate) fn get_subnet_config(
 
 #[cfg(test)]
 mod tests {
+    use crate::commands::subnet::Commands as SubnetCommands;
+    use crate::commands::{Commands, IPCAgentCliCommands};
     use crate::f64_to_token_amount;
+    use clap::Parser;
     use fvm_shared::econ::TokenAmount;
 
     #[test]
@@ -193,4 +230,27 @@ mod tests {
         let amount = f64_to_token_amount(1000000.1f64).unwrap();
         assert_eq!(amount, TokenAmount::from_nano(1000000100000000u128));
     }
+
+    #[test]
+    fn test_parse_rm_bootstrap() {
+        let cli = IPCAgentCliCommands::try_parse_from([
+            "ipc-cli",
+            "subnet",
+            "rm-bootstrap",
+            "--subnet",
+            "/r314159/t410ffzyuupbyl2uiucmzr3lu3mtf3luyknthaz4xsrq",
+            "--endpoint",
+            "abc@127.0.0.1:26656",
+        ])
+        .unwrap();
+
+        let Some(Commands::Subnet(subnet)) = cli.command else {
+            panic!("expected a subnet command");
+        };
+        let SubnetCommands::RemoveBootstrap(args) = subnet.command else {
+            panic!("expected the rm-bootstrap command");
+        };
+        assert_eq!(args.from, None);
+        assert_eq!(args.endpoint, "abc@127.0.0.1:26656");
+    }
 }
@@ -28,7 +28,8 @@ impl CommandLineHandler for AddBootstrap {
 
         provider
             .add_bootstrap(&subnet, from, arguments.endpoint.clone())
-            .await
+            .await?;
+        Ok(())
     }
 }
 
@@ -43,6 +44,44 @@ pub struct AddBootstrapArgs {
     pub endpoint: String,
 }
 
+/// The command to remove a bootstrap of a subnet
+pub struct RemoveBootstrap;
+
+#[async_trait]
+impl CommandLineHandler for RemoveBootstrap {
+    type Arguments = RemoveBootstrapArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("remove subnet bootstrap with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let from = match &arguments.from {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+
+        provider
+            .remove_bootstrap(&subnet, from, arguments.endpoint.clone())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "rm-bootstrap",
+    about = "Remove an advertised bootstrap from the subnet"
+)]
+pub struct RemoveBootstrapArgs {
+    #[arg(long, help = "The address of the validator that added the bootstrap")]
+    pub from: Option<String>,
+    #[arg(long, help = "The subnet to remove the bootstrap from")]
+    pub subnet: String,
+    #[arg(long, help = "The bootstrap node's network endpoint")]
+    pub endpoint: String,
+}
+
 /// The command to list bootstrap nodes in a subnet
 pub struct ListBootstraps;
 
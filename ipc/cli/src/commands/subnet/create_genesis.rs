@@ -0,0 +1,170 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Create the genesis file of a child subnet from the state of its parent
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use clap::{Args, ValueEnum};
+use fendermint_vm_genesis::from_parent::{
+    genesis_from_parent, FromParentParams, ParentGenesisInfo,
+};
+use fendermint_vm_genesis::{PermissionMode, SignerAddr};
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::version::NetworkVersion;
+use ipc_api::subnet_id::SubnetID;
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::{get_ipc_provider, require_fil_addr_from_str, CommandLineHandler, GlobalArguments};
+
+/// The command to create the genesis file of a child subnet.
+pub(crate) struct CreateGenesis;
+
+#[async_trait]
+impl CommandLineHandler for CreateGenesis {
+    type Arguments = CreateGenesisArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("create genesis with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = SubnetID::from_str(&arguments.subnet)?;
+
+        let genesis_info = provider.get_genesis_info(&subnet, arguments.height).await?;
+
+        let info = ParentGenesisInfo {
+            genesis_epoch: genesis_info.genesis_epoch,
+            bottom_up_checkpoint_period: genesis_info.bottom_up_checkpoint_period,
+            majority_percentage: genesis_info.majority_percentage,
+            active_validators_limit: genesis_info.active_validators_limit,
+            validators: genesis_info.validators,
+            genesis_balances: genesis_info.genesis_balances,
+        };
+
+        let params = FromParentParams {
+            network_version: NetworkVersion::from(arguments.network_version),
+            base_fee: TokenAmount::from_atto(arguments.base_fee),
+            power_scale: arguments.power_scale,
+            eam_permission_mode: eam_permission_mode(
+                &arguments.eam_permission_mode,
+                &arguments.eam_allowed_addresses,
+            )?,
+        };
+
+        let genesis = genesis_from_parent(&subnet, info, params)?;
+
+        let json = serde_json::to_string_pretty(&genesis)?;
+        std::fs::write(&arguments.out, json)
+            .with_context(|| format!("failed to write genesis to {:?}", arguments.out))?;
+
+        println!(
+            "genesis of subnet {subnet} with {} validators written to {:?}",
+            genesis.validators.len(),
+            arguments.out
+        );
+
+        Ok(())
+    }
+}
+
+fn eam_permission_mode(
+    mode: &EamPermissionMode,
+    allowed_addresses: &[String],
+) -> anyhow::Result<PermissionMode> {
+    match mode {
+        EamPermissionMode::Unrestricted => {
+            if !allowed_addresses.is_empty() {
+                bail!("allowed addresses require the allowlist eam permission mode");
+            }
+            Ok(PermissionMode::Unrestricted)
+        }
+        EamPermissionMode::Allowlist => {
+            let addresses = allowed_addresses
+                .iter()
+                .map(|a| require_fil_addr_from_str(a).map(SignerAddr))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            Ok(PermissionMode::AllowList { addresses })
+        }
+    }
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub(crate) enum EamPermissionMode {
+    /// Everyone can deploy contracts.
+    Unrestricted,
+    /// Only the allowed addresses can deploy contracts.
+    Allowlist,
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "create-genesis",
+    about = "Create the genesis file of a child subnet from the state of its parent"
+)]
+pub(crate) struct CreateGenesisArgs {
+    #[arg(long, help = "The subnet id to create the genesis for")]
+    pub subnet: String,
+    #[arg(long, help = "The file to write the genesis json to")]
+    pub out: PathBuf,
+    #[arg(
+        long,
+        help = "The parent height to query the genesis info at, defaults to the latest block"
+    )]
+    pub height: Option<ChainEpoch>,
+    #[arg(
+        long,
+        default_value = "21",
+        help = "Network version, governs which set of built-in actors to use"
+    )]
+    pub network_version: u32,
+    #[arg(
+        long,
+        default_value = "1000",
+        help = "Base fee for running transactions in atto"
+    )]
+    pub base_fee: u64,
+    #[arg(
+        long,
+        default_value = "3",
+        help = "Number of decimals to use during converting FIL to Power"
+    )]
+    pub power_scale: i8,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "unrestricted",
+        help = "Who can deploy contracts in the subnet"
+    )]
+    pub eam_permission_mode: EamPermissionMode,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Comma separated addresses allowed to deploy contracts with the allowlist mode"
+    )]
+    pub eam_allowed_addresses: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use fendermint_vm_genesis::PermissionMode;
+
+    use super::{eam_permission_mode, EamPermissionMode};
+
+    #[test]
+    fn test_eam_permission_mode() {
+        let addresses = vec!["f1eg363r3r5cluzx6qbgjcwaw73wwir2jzxopxhqy".to_string()];
+
+        assert_eq!(
+            eam_permission_mode(&EamPermissionMode::Unrestricted, &[]).unwrap(),
+            PermissionMode::Unrestricted
+        );
+        assert!(eam_permission_mode(&EamPermissionMode::Unrestricted, &addresses).is_err());
+
+        match eam_permission_mode(&EamPermissionMode::Allowlist, &addresses).unwrap() {
+            PermissionMode::AllowList { addresses } => assert_eq!(addresses.len(), 1),
+            other => panic!("unexpected permission mode: {other:?}"),
+        }
+    }
+}
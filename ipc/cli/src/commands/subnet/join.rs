@@ -81,7 +81,8 @@ impl CommandLineHandler for StakeSubnet {
         };
         provider
             .stake(subnet, from, f64_to_token_amount(arguments.collateral)?)
-            .await
+            .await?;
+        Ok(())
     }
 }
 
@@ -117,7 +118,8 @@ impl CommandLineHandler for UnstakeSubnet {
         };
         provider
             .unstake(subnet, from, f64_to_token_amount(arguments.collateral)?)
-            .await
+            .await?;
+        Ok(())
     }
 }
 
@@ -26,7 +26,8 @@ impl CommandLineHandler for KillSubnet {
             None => None,
         };
 
-        provider.kill_subnet(subnet, from).await
+        provider.kill_subnet(subnet, from).await?;
+        Ok(())
     }
 }
 
@@ -25,7 +25,8 @@ impl CommandLineHandler for LeaveSubnet {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
         };
-        provider.leave_subnet(subnet, from).await
+        provider.leave_subnet(subnet, from).await?;
+        Ok(())
     }
 }
 
@@ -54,7 +55,8 @@ impl CommandLineHandler for Claim {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
         };
-        provider.claim_collateral(subnet, from).await
+        provider.claim_collateral(subnet, from).await?;
+        Ok(())
     }
 }
 
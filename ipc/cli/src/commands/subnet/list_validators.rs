@@ -0,0 +1,95 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! List the validators of a subnet
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_api::subnet_id::SubnetID;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use crate::{get_ipc_provider, CommandLineHandler, GlobalArguments};
+
+/// The command to list all the validators of a subnet
+pub(crate) struct ListValidators;
+
+#[async_trait]
+impl CommandLineHandler for ListValidators {
+    type Arguments = ListValidatorsArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("list validators with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = SubnetID::from_str(&arguments.subnet)?;
+
+        let set = provider.list_validators(&subnet).await?;
+
+        if arguments.json {
+            let validators = set
+                .validators
+                .iter()
+                .map(|(addr, info)| {
+                    serde_json::json!({
+                        "address": addr.to_string(),
+                        "is_active": info.is_active,
+                        "is_waiting": info.is_waiting,
+                        "confirmed_collateral": info.staking.confirmed_collateral.to_string(),
+                        "total_collateral": info.staking.total_collateral.to_string(),
+                        "federated_power": info.staking.federated_power.to_string(),
+                        "metadata": format!("0x{}", hex::encode(&info.staking.metadata)),
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let output = serde_json::json!({
+                "validators": validators,
+                "active": set.active_count(),
+                "waiting": set.waiting_count(),
+                "total_confirmed_collateral": set.total_confirmed_collateral.to_string(),
+                "total_collateral": set.total_collateral.to_string(),
+                "total_federated_power": set.total_federated_power.to_string(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return Ok(());
+        }
+
+        println!(
+            "{:<44} {:<8} {:>24} {:>24} {:>16}",
+            "address", "state", "confirmed collateral", "total collateral", "federated power"
+        );
+        for (addr, info) in set.validators.iter() {
+            let state = if info.is_active { "active" } else { "waiting" };
+            println!(
+                "{:<44} {:<8} {:>24} {:>24} {:>16}",
+                addr.to_string(),
+                state,
+                info.staking.confirmed_collateral.to_string(),
+                info.staking.total_collateral.to_string(),
+                info.staking.federated_power
+            );
+        }
+        println!(
+            "active: {}, waiting: {}, confirmed collateral: {} FIL, total collateral: {} FIL, federated power: {}",
+            set.active_count(),
+            set.waiting_count(),
+            set.total_confirmed_collateral,
+            set.total_collateral,
+            set.total_federated_power
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "list-validators",
+    about = "List the active and waiting validators of a subnet"
+)]
+pub(crate) struct ListValidatorsArgs {
+    #[arg(long, help = "The subnet id to list validators from")]
+    pub subnet: String,
+    #[arg(long, help = "Print the validators as JSON")]
+    pub json: bool,
+}
@@ -2,37 +2,46 @@
 // SPDX-License-Identifier: MIT
 
 pub use crate::commands::subnet::create::{CreateSubnet, CreateSubnetArgs};
+use crate::commands::subnet::create_genesis::{CreateGenesis, CreateGenesisArgs};
 use crate::commands::subnet::genesis_epoch::{GenesisEpoch, GenesisEpochArgs};
 pub use crate::commands::subnet::join::{JoinSubnet, JoinSubnetArgs};
 pub use crate::commands::subnet::kill::{KillSubnet, KillSubnetArgs};
 pub use crate::commands::subnet::leave::{LeaveSubnet, LeaveSubnetArgs};
 use crate::commands::subnet::list_subnets::{ListSubnets, ListSubnetsArgs};
+use crate::commands::subnet::list_validators::{ListValidators, ListValidatorsArgs};
 use crate::commands::subnet::rpc::{RPCSubnet, RPCSubnetArgs};
 use crate::commands::subnet::send_value::{SendValue, SendValueArgs};
 use crate::commands::subnet::set_federated_power::{SetFederatedPower, SetFederatedPowerArgs};
 use crate::commands::subnet::show_gateway_contract_commit_sha::{
     ShowGatewayContractCommitSha, ShowGatewayContractCommitShaArgs,
 };
+use crate::commands::subnet::status::{SubnetStatus, SubnetStatusArgs};
 use crate::commands::subnet::validator::{ValidatorInfo, ValidatorInfoArgs};
 use crate::{CommandLineHandler, GlobalArguments};
 use clap::{Args, Subcommand};
 
-use self::bootstrap::{AddBootstrap, AddBootstrapArgs, ListBootstraps, ListBootstrapsArgs};
+use self::bootstrap::{
+    AddBootstrap, AddBootstrapArgs, ListBootstraps, ListBootstrapsArgs, RemoveBootstrap,
+    RemoveBootstrapArgs,
+};
 use self::join::{StakeSubnet, StakeSubnetArgs, UnstakeSubnet, UnstakeSubnetArgs};
 use self::leave::{Claim, ClaimArgs};
 use self::rpc::{ChainIdSubnet, ChainIdSubnetArgs};
 
 pub mod bootstrap;
 pub mod create;
+mod create_genesis;
 mod genesis_epoch;
 pub mod join;
 pub mod kill;
 pub mod leave;
 pub mod list_subnets;
+mod list_validators;
 pub mod rpc;
 pub mod send_value;
 mod set_federated_power;
 pub mod show_gateway_contract_commit_sha;
+mod status;
 mod validator;
 
 #[derive(Debug, Args)]
@@ -43,7 +52,7 @@ mod validator;
 #[command(args_conflicts_with_subcommands = true)]
 pub(crate) struct SubnetCommandsArgs {
     #[command(subcommand)]
-    command: Commands,
+    pub(crate) command: Commands,
 }
 
 impl SubnetCommandsArgs {
@@ -61,13 +70,17 @@ impl SubnetCommandsArgs {
             Commands::Unstake(args) => UnstakeSubnet::handle(global, args).await,
             Commands::Claim(args) => Claim::handle(global, args).await,
             Commands::AddBootstrap(args) => AddBootstrap::handle(global, args).await,
+            Commands::RemoveBootstrap(args) => RemoveBootstrap::handle(global, args).await,
             Commands::ListBootstraps(args) => ListBootstraps::handle(global, args).await,
             Commands::GenesisEpoch(args) => GenesisEpoch::handle(global, args).await,
+            Commands::CreateGenesis(args) => CreateGenesis::handle(global, args).await,
             Commands::GetValidator(args) => ValidatorInfo::handle(global, args).await,
+            Commands::ListValidators(args) => ListValidators::handle(global, args).await,
             Commands::ShowGatewayContractCommitSha(args) => {
                 ShowGatewayContractCommitSha::handle(global, args).await
             }
             Commands::SetFederatedPower(args) => SetFederatedPower::handle(global, args).await,
+            Commands::Status(args) => SubnetStatus::handle(global, args).await,
         }
     }
 }
@@ -86,9 +99,14 @@ pub(crate) enum Commands {
     Unstake(UnstakeSubnetArgs),
     Claim(ClaimArgs),
     AddBootstrap(AddBootstrapArgs),
+    #[command(name = "rm-bootstrap")]
+    RemoveBootstrap(RemoveBootstrapArgs),
     ListBootstraps(ListBootstrapsArgs),
     GenesisEpoch(GenesisEpochArgs),
+    CreateGenesis(CreateGenesisArgs),
     GetValidator(ValidatorInfoArgs),
+    ListValidators(ListValidatorsArgs),
     ShowGatewayContractCommitSha(ShowGatewayContractCommitShaArgs),
     SetFederatedPower(SetFederatedPowerArgs),
+    Status(SubnetStatusArgs),
 }
@@ -35,7 +35,8 @@ impl CommandLineHandler for SendValue {
                 require_fil_addr_from_str(&arguments.to)?,
                 f64_to_token_amount(arguments.amount)?,
             )
-            .await
+            .await?;
+        Ok(())
     }
 }
 
@@ -0,0 +1,75 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Subnet status cli command
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_api::subnet_id::SubnetID;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use crate::{get_ipc_provider, CommandLineHandler, GlobalArguments};
+
+/// The command to check whether a subnet exists and is running before funding or joining it.
+pub(crate) struct SubnetStatus;
+
+#[async_trait]
+impl CommandLineHandler for SubnetStatus {
+    type Arguments = SubnetStatusArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("subnet status with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = SubnetID::from_str(&arguments.subnet)?;
+
+        let status = provider.subnet_status(&subnet).await?;
+
+        if arguments.json {
+            let output = serde_json::json!({
+                "exists": status.exists,
+                "bootstrapped": status.bootstrapped,
+                "killed": status.killed,
+                "active": status.is_active(),
+                "stake": status.stake.to_string(),
+                "validators": status.validators,
+                "active_validators": status.active_validators,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return Ok(());
+        }
+
+        if !status.exists {
+            println!("subnet {subnet} does not exist in its parent");
+            return Ok(());
+        }
+
+        let state = if status.killed {
+            "killed"
+        } else if status.bootstrapped {
+            "active"
+        } else {
+            "waiting for bootstrap"
+        };
+        println!("state: {state}");
+        println!("stake: {} FIL", status.stake);
+        println!(
+            "validators: {} ({} active)",
+            status.validators, status.active_validators
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "status",
+    about = "Check whether a subnet exists in its parent and is active"
+)]
+pub(crate) struct SubnetStatusArgs {
+    #[arg(long, help = "The subnet id to check")]
+    pub subnet: String,
+    #[arg(long, help = "Print the status as JSON")]
+    pub json: bool,
+}
@@ -45,6 +45,7 @@ impl CommandLineHandler for WalletBalances {
                                 )
                                 .await
                                 .map(|balance| (balance, addr))
+                                .map_err(anyhow::Error::from)
                         }
                     })
                     .collect::<Vec<_>>();
@@ -94,7 +95,7 @@ impl CommandLineHandler for WalletBalances {
                 let r = join_all(r)
                     .await
                     .into_iter()
-                    .collect::<anyhow::Result<Vec<(TokenAmount, &Address)>>>()?;
+                    .collect::<Result<Vec<(TokenAmount, &Address)>, _>>()?;
                 for (balance, addr) in r {
                     println!("{:?} - Balance: {}", addr, balance);
                 }
@@ -0,0 +1,62 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Wallet encrypt cli handler
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use clap::Args;
+use ipc_provider::evm_keystore_path;
+use ipc_wallet::{EthKeyAddress, PersistentKeyStore};
+use std::fmt::Debug;
+
+use crate::commands::prompt_keystore_password;
+use crate::{CommandLineHandler, GlobalArguments};
+
+pub(crate) struct WalletEncrypt;
+
+#[async_trait]
+impl CommandLineHandler for WalletEncrypt {
+    type Arguments = WalletEncryptArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("encrypt wallet with args: {:?}", arguments);
+
+        // The provider can't be used here, because it fails to open a plaintext keystore
+        // while the password is set.
+        let config = global.config()?;
+        let repo = config
+            .keystore_path
+            .as_ref()
+            .ok_or_else(|| anyhow!("No keystore repo found in config"))?;
+        let path = evm_keystore_path(repo);
+
+        if !path.exists() {
+            return Err(anyhow!("no evm keystore found at {:?}", path));
+        }
+        if PersistentKeyStore::<EthKeyAddress>::is_encrypted_at(&path)? {
+            return Err(anyhow!(
+                "the evm keystore at {:?} is already encrypted",
+                path
+            ));
+        }
+
+        let password = match std::env::var(ipc_wallet::EVM_KEYSTORE_PASSWORD_ENV) {
+            Ok(password) => password,
+            Err(_) => prompt_keystore_password(true)?,
+        };
+
+        PersistentKeyStore::<EthKeyAddress>::encrypt(path.clone(), &password)?;
+
+        println!(
+            "encrypted the evm keystore at {:?}, set {} or enter the password when prompted to use it",
+            path,
+            ipc_wallet::EVM_KEYSTORE_PASSWORD_ENV
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Encrypt the private keys of the evm keystore with a password")]
+pub(crate) struct WalletEncryptArgs {}
@@ -9,6 +9,7 @@ use clap::{Args, Subcommand};
 use self::default::{
     WalletGetDefault, WalletGetDefaultArgs, WalletSetDefault, WalletSetDefaultArgs,
 };
+use self::encrypt::{WalletEncrypt, WalletEncryptArgs};
 use self::export::{WalletExport, WalletExportArgs, WalletPublicKey, WalletPublicKeyArgs};
 use self::import::{WalletImport, WalletImportArgs};
 use self::list::{WalletList, WalletListArgs};
@@ -16,6 +17,7 @@ use self::remove::{WalletRemove, WalletRemoveArgs};
 
 mod balances;
 mod default;
+mod encrypt;
 mod export;
 mod import;
 mod list;
@@ -42,6 +44,7 @@ impl WalletCommandsArgs {
             Commands::GetDefault(args) => WalletGetDefault::handle(global, args).await,
             Commands::PubKey(args) => WalletPublicKey::handle(global, args).await,
             Commands::List(args) => WalletList::handle(global, args).await,
+            Commands::Encrypt(args) => WalletEncrypt::handle(global, args).await,
         }
     }
 }
@@ -57,4 +60,5 @@ pub(crate) enum Commands {
     GetDefault(WalletGetDefaultArgs),
     PubKey(WalletPublicKeyArgs),
     List(WalletListArgs),
+    Encrypt(WalletEncryptArgs),
 }
@@ -10,6 +10,7 @@ mod commands;
 
 pub use commands::*;
 use ipc_provider::config::Config;
+use ipc_provider::error::ProviderError;
 
 /// The trait that represents the abstraction of a command line handler. To implement a new command
 /// line operation, implement this trait and register it.
@@ -40,10 +41,11 @@ pub struct GlobalArguments {
     config_path: Option<String>,
 
     /// Set the FVM Address Network. It's value affects whether `f` (main) or `t` (test) prefixed addresses are accepted.
-    #[arg(long = "network", default_value = "testnet", env = "IPC_NETWORK", value_parser = parse_network)]
-    _network: Network,
+    /// Defaults to `testnet`.
+    #[arg(long = "network", env = "IPC_NETWORK", value_parser = parse_network)]
+    _network: Option<Network>,
 
-    /// Legacy env var for network
+    /// Legacy env var for network, deprecated in favour of `IPC_NETWORK`.
     #[arg(long = "__network", hide = true, env = "NETWORK", value_parser = parse_network)]
     __network: Option<Network>,
 }
@@ -60,8 +62,13 @@ impl GlobalArguments {
         Config::from_file(config_path)
     }
 
+    /// The network set with `--network` or `IPC_NETWORK`, falling back to the legacy `NETWORK`
+    /// env var, then to testnet.
     pub fn network(&self) -> Network {
-        self.__network.unwrap_or(self._network)
+        if self.__network.is_some() {
+            log::warn!("the NETWORK env var is deprecated, use IPC_NETWORK or --network instead");
+        }
+        self._network.or(self.__network).unwrap_or(Network::Testnet)
     }
 }
 
@@ -81,3 +88,133 @@ fn parse_network(s: &str) -> Result<Network, String> {
         }
     }
 }
+
+/// Finds the provider error that caused the command to fail, if any.
+fn provider_error(e: &anyhow::Error) -> Option<&ProviderError> {
+    e.chain()
+        .find_map(|cause| cause.downcast_ref::<ProviderError>())
+}
+
+/// The process exit code for the error returned by a command, so scripts can tell apart
+/// configuration problems from failures of the network or the contracts.
+pub fn exit_code(e: &anyhow::Error) -> i32 {
+    match provider_error(e) {
+        Some(ProviderError::SubnetNotConfigured { .. }) | Some(ProviderError::MissingParent(_)) => {
+            2
+        }
+        Some(ProviderError::WalletMissing(_))
+        | Some(ProviderError::KeyNotFound(_))
+        | Some(ProviderError::NoDefaultSender) => 3,
+        Some(ProviderError::RpcTransport(_)) => 4,
+        Some(ProviderError::ContractRevert { .. }) => 5,
+        Some(ProviderError::Serialization(_)) => 6,
+        Some(ProviderError::RpcResponse { .. }) => 7,
+        Some(ProviderError::Other(_)) | None => 1,
+    }
+}
+
+/// A hint for the user on how to fix the error returned by a command, if we know of one.
+pub fn error_hint(e: &anyhow::Error) -> Option<String> {
+    let hint = match provider_error(e)? {
+        ProviderError::SubnetNotConfigured { subnet, .. } => {
+            format!("add a [[subnets]] entry for {subnet} to your config file")
+        }
+        ProviderError::MissingParent(subnet) => {
+            format!("{subnet} is a root network, the command needs a child subnet")
+        }
+        ProviderError::WalletMissing(_) => {
+            "set the `keystore_path` in your config file to use a wallet".to_string()
+        }
+        ProviderError::KeyNotFound(_) => {
+            "import the key with `ipc-cli wallet import` or pass a different `--from`".to_string()
+        }
+        ProviderError::NoDefaultSender => {
+            "pass `--from` or set a default key with `ipc-cli wallet set-default`".to_string()
+        }
+        ProviderError::RpcTransport(_) => {
+            "check that the rpc endpoint of the subnet in your config file is reachable".to_string()
+        }
+        ProviderError::RpcResponse { .. } => {
+            "the rpc endpoint is reachable but rejected the request".to_string()
+        }
+        ProviderError::ContractRevert { .. } => {
+            "the transaction was rejected by the contract, nothing was sent".to_string()
+        }
+        ProviderError::Serialization(_) | ProviderError::Other(_) => return None,
+    };
+    Some(hint)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+    use clap::Parser;
+    use fvm_shared::address::Network;
+    use ipc_provider::error::ProviderError;
+
+    use crate::{error_hint, exit_code, GlobalArguments};
+
+    #[derive(Parser, Debug)]
+    struct TestOptions {
+        #[command(flatten)]
+        global: GlobalArguments,
+    }
+
+    fn with_env_vars<F, T>(vars: &[(&str, &str)], f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        for (k, v) in vars.iter() {
+            std::env::set_var(k, v);
+        }
+        let result = f();
+        for (k, _) in vars {
+            std::env::remove_var(k);
+        }
+        result
+    }
+
+    #[test]
+    fn test_network_from_env() {
+        for key in ["IPC_NETWORK", "NETWORK"] {
+            std::env::remove_var(key);
+        }
+
+        let examples = [
+            (vec![], vec![], Network::Testnet),
+            (vec!["--network", "mainnet"], vec![], Network::Mainnet),
+            (vec![], vec![("IPC_NETWORK", "mainnet")], Network::Mainnet),
+            (vec![], vec![("NETWORK", "mainnet")], Network::Mainnet),
+            (
+                vec!["--network", "testnet"],
+                vec![("NETWORK", "mainnet")],
+                Network::Testnet,
+            ),
+            (
+                vec![],
+                vec![("IPC_NETWORK", "testnet"), ("NETWORK", "mainnet")],
+                Network::Testnet,
+            ),
+        ];
+
+        for (i, (args, vars, network)) in examples.iter().enumerate() {
+            let opts = with_env_vars(vars, || {
+                TestOptions::parse_from(std::iter::once("ipc-cli").chain(args.iter().copied()))
+            });
+            assert_eq!(opts.global.network(), *network, "example {i}");
+        }
+    }
+
+    #[test]
+    fn test_exit_code_of_wrapped_provider_error() {
+        let e = Err::<(), _>(ProviderError::WalletMissing("evm"))
+            .context("failed to join subnet")
+            .unwrap_err();
+        assert_eq!(exit_code(&e), 3);
+        assert!(error_hint(&e).is_some());
+
+        let e = anyhow::anyhow!("something else");
+        assert_eq!(exit_code(&e), 1);
+        assert!(error_hint(&e).is_none());
+    }
+}
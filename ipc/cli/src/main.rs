@@ -13,6 +13,9 @@ async fn main() {
 
     if let Err(e) = ipc_cli::cli().await {
         log::error!("main process failed: {e:#}");
-        std::process::exit(1);
+        if let Some(hint) = ipc_cli::error_hint(&e) {
+            eprintln!("hint: {hint}");
+        }
+        std::process::exit(ipc_cli::exit_code(&e));
     }
 }
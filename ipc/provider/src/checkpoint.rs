@@ -2,15 +2,23 @@
 // SPDX-License-Identifier: MIT
 //! Bottom up checkpoint manager
 
+use crate::config::subnet::CheckpointSubmissionPolicy;
 use crate::config::Subnet;
-use crate::manager::{BottomUpCheckpointRelayer, EthSubnetManager};
-use anyhow::{anyhow, Result};
+use crate::manager::{
+    BottomUpCheckpointRelayer, CheckpointSigners, CheckpointSubmission, EthSubnetManager,
+    PendingSubmission,
+};
+use anyhow::{anyhow, bail, Result};
+use ethers::types::U256;
 use futures_util::future::try_join_all;
 use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
 use ipc_api::checkpoint::{BottomUpCheckpointBundle, QuorumReachedEvent};
+use ipc_api::evm::{bottom_up_checkpoint_hash, payload_to_evm_address, recover_signer};
 use ipc_wallet::{EthKeyAddress, PersistentKeyStore};
 use std::cmp::max;
+use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
@@ -24,6 +32,49 @@ pub struct CheckpointConfig {
     period: ChainEpoch,
 }
 
+/// Why a signature in a checkpoint bundle was dropped before submission.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureRejection {
+    /// The signatory is not in the active validator set of the parent.
+    NotActiveValidator,
+    /// The signatory already signed the checkpoint.
+    Duplicate,
+    /// The signature cannot be recovered, or would not be accepted by the subnet actor.
+    Malformed(String),
+    /// The signature was made by a different key, e.g. one the validator rotated out.
+    WrongSigner(ethers::types::Address),
+}
+
+impl Display for SignatureRejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotActiveValidator => write!(f, "not an active validator"),
+            Self::Duplicate => write!(f, "duplicate signature"),
+            Self::Malformed(e) => write!(f, "malformed signature: {e}"),
+            Self::WrongSigner(signer) => write!(f, "signed by {signer:?}"),
+        }
+    }
+}
+
+/// The valid signatures of a checkpoint don't add up to the majority of the active power,
+/// so the subnet actor would reject it.
+#[derive(Debug, thiserror::Error)]
+#[error("checkpoint({height}) signatures weigh {weight} of the required {threshold}; rejected: {}", format_rejected(.rejected))]
+pub struct NoCheckpointQuorum {
+    pub height: ChainEpoch,
+    pub weight: U256,
+    pub threshold: U256,
+    pub rejected: Vec<(ethers::types::Address, SignatureRejection)>,
+}
+
+fn format_rejected(rejected: &[(ethers::types::Address, SignatureRejection)]) -> String {
+    rejected
+        .iter()
+        .map(|(validator, reason)| format!("{validator:?} ({reason})"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Manages the submission of bottom up checkpoint. It checks if the submitter has already
 /// submitted in the `last_checkpoint_height`, if not, it will submit the checkpoint at that height.
 /// Then it will submit at the next submission height for the new checkpoint.
@@ -34,6 +85,10 @@ pub struct BottomUpCheckpointManager<T> {
     /// The number of blocks away from the chain head that is considered final
     finalization_blocks: ChainEpoch,
     submission_semaphore: Arc<Semaphore>,
+    /// How to retry submissions that are not confirmed in time
+    submission_policy: CheckpointSubmissionPolicy,
+    /// Overrides the height from which missed checkpoints are submitted on startup
+    from_height: Option<ChainEpoch>,
 }
 
 impl<T: BottomUpCheckpointRelayer> BottomUpCheckpointManager<T> {
@@ -48,6 +103,7 @@ impl<T: BottomUpCheckpointRelayer> BottomUpCheckpointManager<T> {
             .checkpoint_period(&child.id)
             .await
             .map_err(|e| anyhow!("cannot get bottom up checkpoint period: {e}"))?;
+        let submission_policy = parent.checkpoint_submission();
         Ok(Self {
             metadata: CheckpointConfig {
                 parent,
@@ -58,6 +114,8 @@ impl<T: BottomUpCheckpointRelayer> BottomUpCheckpointManager<T> {
             child_handler,
             finalization_blocks: 0,
             submission_semaphore: Arc::new(Semaphore::new(max_parallelism)),
+            submission_policy,
+            from_height: None,
         })
     }
 
@@ -65,6 +123,13 @@ impl<T: BottomUpCheckpointRelayer> BottomUpCheckpointManager<T> {
         self.finalization_blocks = finalization_blocks;
         self
     }
+
+    /// Submit missed checkpoints starting at this height instead of the last
+    /// checkpoint height committed in the parent.
+    pub fn with_from_height(mut self, from_height: ChainEpoch) -> Self {
+        self.from_height = Some(from_height);
+        self
+    }
 }
 
 impl BottomUpCheckpointManager<EthSubnetManager> {
@@ -119,6 +184,11 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
     pub async fn run(self, submitter: Address, submission_interval: Duration) {
         tracing::info!("launching {self} for {submitter}");
 
+        match self.submit_missed_checkpoints(submitter).await {
+            Ok(count) => tracing::info!("submitted {count} missed checkpoints"),
+            Err(e) => tracing::error!("cannot submit missed checkpoints: {e}"),
+        }
+
         loop {
             if let Err(e) = self.submit_next_epoch(submitter).await {
                 tracing::error!("cannot submit checkpoint for submitter: {submitter} due to {e}");
@@ -127,6 +197,62 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
         }
     }
 
+    /// Submits, strictly in order, the checkpoints of every period between the last checkpoint
+    /// committed in the parent (or the configured starting height) and the finalized child head.
+    ///
+    /// Stops at the first period without a checkpoint bundle in the child, and fails at the
+    /// first submission error, as later checkpoints would be rejected by the parent anyway.
+    /// Returns the number of checkpoints submitted.
+    pub async fn submit_missed_checkpoints(&self, submitter: Address) -> Result<usize> {
+        let period = self.metadata.period;
+        let start = match self.from_height {
+            Some(h) => h,
+            None => {
+                let last = self
+                    .parent_handler
+                    .last_bottom_up_checkpoint_height(&self.metadata.child.id)
+                    .await
+                    .map_err(|e| {
+                        anyhow!("cannot obtain the last bottom up checkpoint height due to: {e:}")
+                    })?;
+                last + 1
+            }
+        };
+
+        let current_height = self.child_handler.current_epoch().await?;
+        let finalized_height = max(1, current_height - self.finalization_blocks);
+
+        // The first checkpoint height at or after the start.
+        let mut height = (start + period - 1) / period * period;
+        let mut count = 0;
+
+        while height <= finalized_height {
+            let bundle = match self.child_handler.checkpoint_bundle_at(height).await? {
+                Some(bundle) => bundle,
+                None => {
+                    tracing::debug!("no checkpoint bundle at height {height}, stop catching up");
+                    break;
+                }
+            };
+
+            submit_with_retries(
+                self.parent_handler.as_ref(),
+                &submitter,
+                bundle,
+                &self.submission_policy,
+            )
+            .await
+            .map_err(|e| anyhow!("cannot submit missed checkpoint at height {height}: {e}"))?;
+
+            tracing::info!("submitted missed bottom up checkpoint({height})");
+
+            count += 1;
+            height += period;
+        }
+
+        Ok(count)
+    }
+
     /// Checks if the relayer has already submitted at the next submission epoch, if not it submits it.
     async fn submit_next_epoch(&self, submitter: Address) -> Result<()> {
         let last_checkpoint_epoch = self
@@ -192,6 +318,7 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
                 // We need to acquire a permit (from a limited permit pool) before submitting a checkpoint.
                 // We may wait here until a permit is available.
                 let parent_handler_clone = Arc::clone(&self.parent_handler);
+                let policy = self.submission_policy.clone();
                 let submission_permit = self
                     .submission_semaphore
                     .clone()
@@ -200,14 +327,17 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
                     .unwrap();
                 all_submit_tasks.push(tokio::task::spawn(async move {
                     let height = event.height;
-                    let result =
-                        Self::submit_checkpoint(parent_handler_clone, submitter, bundle, event)
-                            .await
-                            .inspect_err(|err| {
-                                tracing::error!(
-                                    "Fail to submit checkpoint at height {height}: {err}"
-                                );
-                            });
+                    let result = Self::submit_checkpoint(
+                        parent_handler_clone,
+                        submitter,
+                        bundle,
+                        event,
+                        policy,
+                    )
+                    .await
+                    .inspect_err(|err| {
+                        tracing::error!("Fail to submit checkpoint at height {height}: {err}");
+                    });
                     drop(submission_permit);
                     result
                 }));
@@ -229,14 +359,9 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
         submitter: Address,
         bundle: BottomUpCheckpointBundle,
         event: QuorumReachedEvent,
+        policy: CheckpointSubmissionPolicy,
     ) -> Result<(), anyhow::Error> {
-        let epoch = parent_handler
-            .submit_checkpoint(
-                &submitter,
-                bundle.checkpoint,
-                bundle.signatures,
-                bundle.signatories,
-            )
+        let epoch = submit_with_retries(parent_handler.as_ref(), &submitter, bundle, &policy)
             .await
             .map_err(|e| {
                 anyhow!(
@@ -245,11 +370,654 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
                 )
             })?;
 
-        tracing::info!(
-            "submitted bottom up checkpoint({}) in parent at height {}",
-            event.height,
-            epoch
-        );
+        match epoch {
+            Some(epoch) => tracing::info!(
+                "submitted bottom up checkpoint({}) in parent at height {}",
+                event.height,
+                epoch
+            ),
+            None => tracing::info!(
+                "bottom up checkpoint({}) already submitted in parent",
+                event.height
+            ),
+        }
         Ok(())
     }
 }
+
+/// Checks the signatures of the bundle the same way the subnet actor in the parent does,
+/// dropping the ones it would reject, so a single bad signature doesn't revert the whole
+/// submission.
+///
+/// Fails with [`NoCheckpointQuorum`] if the remaining signatures are not enough.
+pub fn verify_signatures(
+    bundle: BottomUpCheckpointBundle,
+    signers: &CheckpointSigners,
+) -> Result<BottomUpCheckpointBundle> {
+    let BottomUpCheckpointBundle {
+        checkpoint,
+        signatures,
+        signatories,
+    } = bundle;
+
+    if signatures.len() != signatories.len() {
+        bail!(
+            "checkpoint({}) has {} signatures but {} signatories",
+            checkpoint.block_height,
+            signatures.len(),
+            signatories.len()
+        );
+    }
+
+    let hash = bottom_up_checkpoint_hash(&checkpoint)?;
+
+    let mut seen = HashSet::new();
+    let mut weight = U256::zero();
+    let mut valid_signatures = Vec::new();
+    let mut valid_signatories = Vec::new();
+    let mut rejected = Vec::new();
+
+    for (signature, signatory) in signatures.into_iter().zip(signatories) {
+        let validator = match payload_to_evm_address(signatory.payload()) {
+            Ok(validator) => validator,
+            Err(e) => {
+                tracing::warn!("dropping signature of {signatory}: {e}");
+                continue;
+            }
+        };
+
+        let Some(power) = signers.power.get(&validator) else {
+            rejected.push((validator, SignatureRejection::NotActiveValidator));
+            continue;
+        };
+        if seen.contains(&validator) {
+            rejected.push((validator, SignatureRejection::Duplicate));
+            continue;
+        }
+        match recover_signer(&hash, &signature) {
+            Ok(signer) if signer == validator => {}
+            Ok(signer) => {
+                rejected.push((validator, SignatureRejection::WrongSigner(signer)));
+                continue;
+            }
+            Err(e) => {
+                rejected.push((validator, SignatureRejection::Malformed(format!("{e:#}"))));
+                continue;
+            }
+        }
+
+        seen.insert(validator);
+        weight += *power;
+        valid_signatures.push(signature);
+        valid_signatories.push(signatory);
+    }
+
+    for (validator, reason) in rejected.iter() {
+        tracing::warn!(
+            "dropping signature of {validator:?} from checkpoint({}): {reason}",
+            checkpoint.block_height
+        );
+    }
+
+    let threshold = signers.threshold();
+    if valid_signatures.is_empty() || weight < threshold {
+        return Err(NoCheckpointQuorum {
+            height: checkpoint.block_height,
+            weight,
+            threshold,
+            rejected,
+        }
+        .into());
+    }
+
+    Ok(BottomUpCheckpointBundle {
+        checkpoint,
+        signatures: valid_signatures,
+        signatories: valid_signatories,
+    })
+}
+
+/// Submits the checkpoint bundle, re-submitting it with a bumped gas premium whenever
+/// it is not confirmed within the timeout of the policy.
+///
+/// Returns the parent epoch the checkpoint was executed at, or `None` if it had already
+/// been submitted, e.g. by another relayer, or by an earlier attempt whose replacement
+/// was then rejected.
+async fn submit_with_retries<T: BottomUpCheckpointRelayer>(
+    handler: &T,
+    submitter: &Address,
+    bundle: BottomUpCheckpointBundle,
+    policy: &CheckpointSubmissionPolicy,
+) -> Result<Option<ChainEpoch>> {
+    let signers = handler
+        .checkpoint_signers(&bundle.checkpoint.subnet_id)
+        .await
+        .map_err(|e| anyhow!("cannot get the checkpoint signers: {e}"))?;
+    let bundle = verify_signatures(bundle, &signers)?;
+
+    let height = bundle.checkpoint.block_height;
+    let mut replace: Option<PendingSubmission> = None;
+
+    for attempt in 1..=policy.max_attempts {
+        let is_replacement = replace.is_some();
+        let submission = match handler
+            .submit_checkpoint_with_timeout(
+                submitter,
+                bundle.checkpoint.clone(),
+                bundle.signatures.clone(),
+                bundle.signatories.clone(),
+                replace.take(),
+                policy.confirmation_timeout,
+            )
+            .await
+        {
+            Ok(submission) => submission,
+            // The transaction being replaced can be mined just before its replacement is sent,
+            // which then fails, e.g. with "nonce too low". The parent tells if that happened.
+            Err(e) if is_replacement => {
+                match handler
+                    .last_bottom_up_checkpoint_height(&bundle.checkpoint.subnet_id)
+                    .await
+                {
+                    Ok(last_height) if last_height >= height => {
+                        tracing::info!(
+                            "checkpoint({height}) replacement failed, but an earlier submission was executed: {e:#}"
+                        );
+                        return Ok(None);
+                    }
+                    _ => return Err(e),
+                }
+            }
+            Err(e) => return Err(e),
+        };
+
+        match submission {
+            CheckpointSubmission::Executed(epoch) => return Ok(Some(epoch)),
+            CheckpointSubmission::AlreadySubmitted => return Ok(None),
+            CheckpointSubmission::Pending(pending) => {
+                let gas_premium =
+                    bump_gas_premium(&pending.gas_premium, policy.gas_premium_bump_bps);
+                tracing::warn!(
+                    "checkpoint({height}) submission attempt {attempt} not confirmed in {:?}, re-submitting with gas premium {}",
+                    policy.confirmation_timeout,
+                    gas_premium.atto()
+                );
+                replace = Some(PendingSubmission {
+                    nonce: pending.nonce,
+                    gas_premium,
+                });
+            }
+        }
+    }
+
+    Err(anyhow!(
+        "checkpoint not confirmed after {} attempts",
+        policy.max_attempts
+    ))
+}
+
+/// Increases the gas premium by the given basis points, making sure it always goes up.
+fn bump_gas_premium(gas_premium: &TokenAmount, bump_bps: u32) -> TokenAmount {
+    let bumped = gas_premium.atto() * (10_000 + bump_bps) / 10_000u32;
+    let bumped = TokenAmount::from_atto(bumped);
+    if bumped > *gas_premium {
+        bumped
+    } else {
+        TokenAmount::from_atto(gas_premium.atto() + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        bump_gas_premium, submit_with_retries, verify_signatures, BottomUpCheckpointManager,
+        NoCheckpointQuorum, SignatureRejection,
+    };
+    use crate::config::subnet::{CheckpointSubmissionPolicy, EVMSubnet, SubnetConfig};
+    use crate::config::Subnet;
+    use crate::manager::{
+        BottomUpCheckpointRelayer, CheckpointSigners, CheckpointSubmission, PendingSubmission,
+    };
+    use async_trait::async_trait;
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::{H256, U256};
+    use futures_util::stream::BoxStream;
+    use fvm_shared::address::Address;
+    use fvm_shared::clock::ChainEpoch;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::checkpoint::{
+        BottomUpCheckpoint, BottomUpCheckpointBundle, QuorumReachedEvent, Signature,
+    };
+    use ipc_api::ethers_address_to_fil_address;
+    use ipc_api::evm::bottom_up_checkpoint_hash;
+    use ipc_api::subnet_id::SubnetID;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    const PERIOD: ChainEpoch = 10;
+
+    /// A relayer that replays a fixed sequence of submission outcomes, executing
+    /// submissions once they run out, and records what it was asked to submit.
+    struct MockRelayer {
+        outcomes: Mutex<Vec<CheckpointSubmission>>,
+        replacements: Mutex<Vec<Option<PendingSubmission>>>,
+        submitted: Mutex<Vec<ChainEpoch>>,
+        /// The last checkpoint height committed, when acting as the parent
+        last_height: ChainEpoch,
+        /// The chain head, when acting as the child
+        head: ChainEpoch,
+        /// Submissions at this height fail
+        fail_at: Option<ChainEpoch>,
+        /// Submissions replacing a pending one fail
+        fail_replacements: bool,
+    }
+
+    impl MockRelayer {
+        fn new(mut outcomes: Vec<CheckpointSubmission>) -> Self {
+            outcomes.reverse();
+            Self {
+                outcomes: Mutex::new(outcomes),
+                replacements: Mutex::new(vec![]),
+                submitted: Mutex::new(vec![]),
+                last_height: 0,
+                head: 0,
+                fail_at: None,
+                fail_replacements: false,
+            }
+        }
+
+        fn parent(last_height: ChainEpoch, fail_at: Option<ChainEpoch>) -> Self {
+            Self {
+                last_height,
+                fail_at,
+                ..Self::new(vec![])
+            }
+        }
+
+        fn child(head: ChainEpoch) -> Self {
+            Self {
+                head,
+                ..Self::new(vec![])
+            }
+        }
+
+        fn replacements(&self) -> Vec<Option<PendingSubmission>> {
+            self.replacements.lock().unwrap().clone()
+        }
+
+        fn submitted(&self) -> Vec<ChainEpoch> {
+            self.submitted.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl BottomUpCheckpointRelayer for MockRelayer {
+        async fn submit_checkpoint(
+            &self,
+            _submitter: &Address,
+            _checkpoint: BottomUpCheckpoint,
+            _signatures: Vec<Signature>,
+            _signatories: Vec<Address>,
+        ) -> anyhow::Result<ChainEpoch> {
+            unimplemented!()
+        }
+
+        async fn submit_checkpoint_with_timeout(
+            &self,
+            _submitter: &Address,
+            checkpoint: BottomUpCheckpoint,
+            _signatures: Vec<Signature>,
+            _signatories: Vec<Address>,
+            replace: Option<PendingSubmission>,
+            _timeout: Duration,
+        ) -> anyhow::Result<CheckpointSubmission> {
+            let height = checkpoint.block_height;
+            let is_replacement = replace.is_some();
+            self.replacements.lock().unwrap().push(replace);
+            self.submitted.lock().unwrap().push(height);
+
+            if self.fail_at == Some(height) {
+                return Err(anyhow::anyhow!("submission reverted"));
+            }
+            if self.fail_replacements && is_replacement {
+                return Err(anyhow::anyhow!("nonce too low"));
+            }
+
+            Ok(self
+                .outcomes
+                .lock()
+                .unwrap()
+                .pop()
+                .unwrap_or(CheckpointSubmission::Executed(height)))
+        }
+
+        async fn last_bottom_up_checkpoint_height(
+            &self,
+            _subnet_id: &SubnetID,
+        ) -> anyhow::Result<ChainEpoch> {
+            Ok(self.last_height)
+        }
+
+        async fn checkpoint_period(&self, _subnet_id: &SubnetID) -> anyhow::Result<ChainEpoch> {
+            Ok(PERIOD)
+        }
+
+        async fn checkpoint_signers(
+            &self,
+            _subnet_id: &SubnetID,
+        ) -> anyhow::Result<CheckpointSigners> {
+            Ok(signers())
+        }
+
+        async fn checkpoint_bundle_at(
+            &self,
+            height: ChainEpoch,
+        ) -> anyhow::Result<Option<BottomUpCheckpointBundle>> {
+            Ok((height <= self.head).then(|| bundle_at(height)))
+        }
+
+        async fn quorum_reached_events(
+            &self,
+            _height: ChainEpoch,
+        ) -> anyhow::Result<Vec<QuorumReachedEvent>> {
+            unimplemented!()
+        }
+
+        fn subscribe_quorum_events(
+            self: Arc<Self>,
+            _from_height: ChainEpoch,
+        ) -> BoxStream<'static, anyhow::Result<QuorumReachedEvent>> {
+            unimplemented!()
+        }
+
+        async fn current_epoch(&self) -> anyhow::Result<ChainEpoch> {
+            Ok(self.head)
+        }
+    }
+
+    fn bundle() -> BottomUpCheckpointBundle {
+        bundle_at(10)
+    }
+
+    /// The validators of the child subnet, with equal power.
+    fn validators() -> Vec<LocalWallet> {
+        (1..=3u8)
+            .map(|i| LocalWallet::from_bytes(&[i; 32]).unwrap())
+            .collect()
+    }
+
+    /// Any two of the three validators make a quorum.
+    fn signers() -> CheckpointSigners {
+        CheckpointSigners {
+            power: validators()
+                .iter()
+                .map(|v| (v.address(), U256::from(10)))
+                .collect(),
+            majority_percentage: 67,
+        }
+    }
+
+    fn sign(bundle: &mut BottomUpCheckpointBundle, validator: &LocalWallet, key: &LocalWallet) {
+        let hash = bottom_up_checkpoint_hash(&bundle.checkpoint).unwrap();
+        let signature = key.sign_hash(H256::from(hash)).unwrap();
+        bundle.signatures.push(signature.to_vec());
+        bundle
+            .signatories
+            .push(ethers_address_to_fil_address(&validator.address()).unwrap());
+    }
+
+    /// A checkpoint signed by all the validators.
+    fn bundle_at(height: ChainEpoch) -> BottomUpCheckpointBundle {
+        let mut bundle = BottomUpCheckpointBundle {
+            checkpoint: BottomUpCheckpoint {
+                subnet_id: SubnetID::new_root(123),
+                block_height: height,
+                block_hash: vec![0; 32],
+                next_configuration_number: 0,
+                msgs: vec![],
+            },
+            signatures: vec![],
+            signatories: vec![],
+        };
+        for validator in validators() {
+            sign(&mut bundle, &validator, &validator);
+        }
+        bundle
+    }
+
+    fn subnet(id: SubnetID) -> Subnet {
+        Subnet {
+            id,
+            config: SubnetConfig::Fevm(EVMSubnet {
+                provider_http: "http://127.0.0.1:8545".parse().unwrap(),
+                provider_ws: None,
+                provider_timeout: None,
+                auth_token: None,
+                registry_addr: Address::new_id(100),
+                gateway_addr: Address::new_id(101),
+                checkpoint_submission: None,
+            }),
+        }
+    }
+
+    async fn manager(
+        parent: MockRelayer,
+        child: MockRelayer,
+    ) -> BottomUpCheckpointManager<MockRelayer> {
+        let root = SubnetID::new_root(123);
+        let child_id = SubnetID::new_from_parent(&root, Address::new_id(1001));
+        BottomUpCheckpointManager::new(subnet(root), subnet(child_id), parent, child, 1)
+            .await
+            .unwrap()
+    }
+
+    fn pending(gas_premium: u64) -> CheckpointSubmission {
+        CheckpointSubmission::Pending(PendingSubmission {
+            nonce: 7,
+            gas_premium: TokenAmount::from_atto(gas_premium),
+        })
+    }
+
+    fn policy(max_attempts: u32) -> CheckpointSubmissionPolicy {
+        CheckpointSubmissionPolicy {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stuck_submission_is_bumped_until_executed() {
+        let relayer = MockRelayer::new(vec![
+            pending(1000),
+            pending(1125),
+            CheckpointSubmission::Executed(42),
+        ]);
+
+        let epoch = submit_with_retries(&relayer, &Address::new_id(1), bundle(), &policy(5))
+            .await
+            .unwrap();
+
+        assert_eq!(epoch, Some(42));
+
+        let premiums = relayer
+            .replacements()
+            .into_iter()
+            .map(|r| r.map(|p| (p.nonce, p.gas_premium.atto().to_string())))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            premiums,
+            vec![
+                None,
+                Some((7, "1125".to_string())),
+                Some((7, "1265".to_string()))
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stuck_submission_fails_after_max_attempts() {
+        let relayer = MockRelayer::new(vec![pending(1000), pending(1125), pending(1265)]);
+
+        let res = submit_with_retries(&relayer, &Address::new_id(1), bundle(), &policy(3)).await;
+
+        assert!(res.is_err());
+        assert_eq!(relayer.replacements().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_already_submitted_is_success() {
+        let relayer = MockRelayer::new(vec![pending(1000), CheckpointSubmission::AlreadySubmitted]);
+
+        let epoch = submit_with_retries(&relayer, &Address::new_id(1), bundle(), &policy(5))
+            .await
+            .unwrap();
+
+        assert_eq!(epoch, None);
+        assert_eq!(relayer.replacements().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_replacement_of_executed_submission_is_success() {
+        let relayer = MockRelayer {
+            last_height: 10,
+            fail_replacements: true,
+            ..MockRelayer::new(vec![pending(1000)])
+        };
+
+        let epoch = submit_with_retries(&relayer, &Address::new_id(1), bundle(), &policy(5))
+            .await
+            .unwrap();
+
+        assert_eq!(epoch, None);
+        assert_eq!(relayer.replacements().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_replacement_of_unexecuted_submission_is_error() {
+        let relayer = MockRelayer {
+            last_height: 0,
+            fail_replacements: true,
+            ..MockRelayer::new(vec![pending(1000)])
+        };
+
+        let err = submit_with_retries(&relayer, &Address::new_id(1), bundle(), &policy(5))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("nonce too low"));
+        assert_eq!(relayer.replacements().len(), 2);
+    }
+
+    #[test]
+    fn test_bump_gas_premium() {
+        let bump = |p: u64| bump_gas_premium(&TokenAmount::from_atto(p), 1250);
+
+        assert_eq!(bump(1000), TokenAmount::from_atto(1125));
+        assert_eq!(bump(1), TokenAmount::from_atto(2));
+        assert_eq!(bump(0), TokenAmount::from_atto(1));
+    }
+
+    #[tokio::test]
+    async fn test_missed_checkpoints_submitted_in_order() {
+        let manager = manager(MockRelayer::parent(10, None), MockRelayer::child(45)).await;
+
+        let count = manager
+            .submit_missed_checkpoints(Address::new_id(1))
+            .await
+            .unwrap();
+
+        assert_eq!(count, 3);
+        assert_eq!(manager.parent_handler.submitted(), vec![20, 30, 40]);
+    }
+
+    #[tokio::test]
+    async fn test_missed_checkpoints_stop_at_first_failure() {
+        let manager = manager(MockRelayer::parent(10, Some(30)), MockRelayer::child(45)).await;
+
+        let err = manager
+            .submit_missed_checkpoints(Address::new_id(1))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("height 30"));
+        assert_eq!(manager.parent_handler.submitted(), vec![20, 30]);
+    }
+
+    #[tokio::test]
+    async fn test_missed_checkpoints_from_height() {
+        let manager = manager(MockRelayer::parent(30, None), MockRelayer::child(45))
+            .await
+            .with_from_height(15);
+
+        let count = manager
+            .submit_missed_checkpoints(Address::new_id(1))
+            .await
+            .unwrap();
+
+        assert_eq!(count, 3);
+        assert_eq!(manager.parent_handler.submitted(), vec![20, 30, 40]);
+    }
+
+    #[test]
+    fn test_bad_signature_is_dropped() {
+        let [v1, v2, v3]: [LocalWallet; 3] = validators().try_into().unwrap();
+        let rotated = LocalWallet::from_bytes(&[9; 32]).unwrap();
+
+        let mut bundle = bundle_at(10);
+        bundle.signatures.clear();
+        bundle.signatories.clear();
+        sign(&mut bundle, &v1, &v1);
+        sign(&mut bundle, &v2, &rotated);
+        sign(&mut bundle, &v3, &v3);
+
+        let verified = verify_signatures(bundle, &signers()).unwrap();
+
+        assert_eq!(
+            verified.signatories,
+            vec![
+                ethers_address_to_fil_address(&v1.address()).unwrap(),
+                ethers_address_to_fil_address(&v3.address()).unwrap(),
+            ]
+        );
+        assert_eq!(verified.signatures.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_under_quorum_is_not_submitted() {
+        let [v1, v2, v3]: [LocalWallet; 3] = validators().try_into().unwrap();
+        let rotated = LocalWallet::from_bytes(&[9; 32]).unwrap();
+
+        let mut bundle = bundle_at(10);
+        bundle.signatures.clear();
+        bundle.signatories.clear();
+        sign(&mut bundle, &v1, &v1);
+        sign(&mut bundle, &v2, &rotated);
+        sign(&mut bundle, &v3, &v3);
+        // Truncate the last signature.
+        bundle.signatures[2].truncate(64);
+
+        let relayer = MockRelayer::new(vec![]);
+        let err = submit_with_retries(&relayer, &Address::new_id(1), bundle, &policy(5))
+            .await
+            .unwrap_err();
+
+        let err = err.downcast::<NoCheckpointQuorum>().unwrap();
+        assert_eq!(err.weight, U256::from(10));
+        assert_eq!(err.threshold, U256::from(20));
+        assert_eq!(err.rejected.len(), 2);
+        assert_eq!(
+            err.rejected[0],
+            (
+                v2.address(),
+                SignatureRejection::WrongSigner(rotated.address())
+            )
+        );
+        assert_eq!(err.rejected[1].0, v3.address());
+        assert!(matches!(
+            err.rejected[1].1,
+            SignatureRejection::Malformed(_)
+        ));
+        assert!(relayer.submitted().is_empty());
+    }
+}
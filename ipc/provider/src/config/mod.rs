@@ -46,6 +46,8 @@ registry_addr = "0x0b4e239FF21b40120cDa817fba77bD1B366c1bcD"
 # [subnets.config]
 # network_type = "fevm"
 # provider_http = "https://<RPC_ADDR>/"
+# # Optional, to subscribe to new blocks instead of polling.
+# provider_ws = "wss://<RPC_ADDR>/"
 # gateway_addr = "0x77aa40b105843728088c0132e43fc44348881da8"
 # registry_addr = "0x74539671a1d2f1c8f200826baba665179f53a1b7"
 "#;
@@ -59,6 +61,25 @@ pub struct Config {
     #[serde(deserialize_with = "deserialize_subnets_from_vec", default)]
     #[serde(serialize_with = "serialize_subnets_to_str")]
     pub subnets: HashMap<SubnetID, Subnet>,
+    /// EVM accounts whose transactions are signed on a Ledger device, instead of with a key
+    /// from the keystore.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ledger_accounts: Vec<LedgerAccount>,
+}
+
+/// An EVM account living on a Ledger device.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LedgerAccount {
+    /// The address of the account, which has to match the one derived by the device.
+    pub address: ethers::types::Address,
+    /// The derivation path of the account on the device.
+    #[serde(default = "default_derivation_path")]
+    pub derivation_path: String,
+}
+
+/// The path of the first account in Ledger Live.
+fn default_derivation_path() -> String {
+    "m/44'/60'/0'/0/0".to_string()
 }
 
 impl Config {
@@ -67,6 +88,7 @@ impl Config {
         Config {
             keystore_path: None,
             subnets: Default::default(),
+            ledger_accounts: Vec::new(),
         }
     }
 
@@ -88,9 +110,21 @@ impl Config {
         let config: Config =
             Config::from_toml_str(contents.as_str()).context("failed to parse config TOML")?;
 
+        config
+            .validate()
+            .with_context(|| format!("invalid config in {}", path.as_ref().to_string_lossy()))?;
+
         Ok(config)
     }
 
+    /// Check that all the subnets are usable, see [`Subnet::validate`].
+    pub fn validate(&self) -> Result<()> {
+        for subnet in self.subnets.values() {
+            subnet.validate()?;
+        }
+        Ok(())
+    }
+
     /// Reads a TOML configuration file specified in the `path` and returns a [`Config`] struct.
     pub async fn from_file_async(path: impl AsRef<Path>) -> Result<Self> {
         let contents = tokio::fs::read_to_string(path).await?;
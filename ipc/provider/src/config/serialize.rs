@@ -122,6 +122,7 @@ mod tests {
         let mut config = Config {
             keystore_path: Some(String::from("~/.ipc")),
             subnets: Default::default(),
+            ledger_accounts: Vec::new(),
         };
 
         let eth_addr1 = EthAddress::from_str("0x6BE1Ccf648c74800380d0520D797a170c808b624").unwrap();
@@ -130,9 +131,11 @@ mod tests {
             config: SubnetConfig::Fevm(EVMSubnet {
                 gateway_addr: Address::from(eth_addr1),
                 provider_http: "http://127.0.0.1:3030/rpc/v1".parse().unwrap(),
+                provider_ws: None,
                 provider_timeout: None,
                 auth_token: None,
                 registry_addr: Address::from(eth_addr1),
+                checkpoint_submission: None,
             }),
         };
         config.add_subnet(subnet2);
@@ -2,8 +2,10 @@ use std::time::Duration;
 
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
+use anyhow::bail;
 use fvm_shared::address::Address;
 use ipc_api::subnet_id::SubnetID;
+use ipc_types::EthAddress;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DurationSeconds};
 use url::Url;
@@ -56,6 +58,12 @@ impl Subnet {
         }
     }
 
+    pub fn rpc_ws(&self) -> Option<&Url> {
+        match &self.config {
+            SubnetConfig::Fevm(s) => s.provider_ws.as_ref(),
+        }
+    }
+
     pub fn rpc_timeout(&self) -> Option<Duration> {
         match &self.config {
             SubnetConfig::Fevm(s) => s.provider_timeout,
@@ -67,6 +75,48 @@ impl Subnet {
             SubnetConfig::Fevm(s) => s.gateway_addr,
         }
     }
+
+    /// Check the settings which parse fine, but would only fail when the subnet is used,
+    /// like a gateway left at the zero address.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match &self.config {
+            SubnetConfig::Fevm(s) => {
+                let zero = Address::from(EthAddress([0; 20]));
+                if s.gateway_addr == zero {
+                    bail!("subnet {}: gateway_addr is the zero address", self.id);
+                }
+                if s.registry_addr == zero {
+                    bail!("subnet {}: registry_addr is the zero address", self.id);
+                }
+                if !matches!(s.provider_http.scheme(), "http" | "https")
+                    || s.provider_http.host().is_none()
+                {
+                    bail!(
+                        "subnet {}: provider_http is not an HTTP URL: {}",
+                        self.id,
+                        s.provider_http
+                    );
+                }
+                if let Some(ws) = &s.provider_ws {
+                    if !matches!(ws.scheme(), "ws" | "wss") || ws.host().is_none() {
+                        bail!(
+                            "subnet {}: provider_ws is not a WebSocket URL: {}",
+                            self.id,
+                            ws
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The policy used by the relayer when submitting bottom-up checkpoints to this subnet.
+    pub fn checkpoint_submission(&self) -> CheckpointSubmissionPolicy {
+        match &self.config {
+            SubnetConfig::Fevm(s) => s.checkpoint_submission.clone().unwrap_or_default(),
+        }
+    }
 }
 
 /// The FVM subnet config parameters
@@ -84,6 +134,10 @@ pub struct FVMSubnet {
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
 pub struct EVMSubnet {
     pub provider_http: Url,
+    /// WebSocket endpoint of the same node, used for subscriptions instead of polling over
+    /// `provider_http`, which is still used for everything else.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_ws: Option<Url>,
     #[serde_as(as = "Option<DurationSeconds<u64>>")]
     pub provider_timeout: Option<Duration>,
     pub auth_token: Option<String>,
@@ -95,4 +149,47 @@ pub struct EVMSubnet {
     #[serde(deserialize_with = "deserialize_eth_address_from_str")]
     #[serde(serialize_with = "serialize_eth_address_to_str")]
     pub gateway_addr: Address,
+
+    /// Overrides the default checkpoint submission policy of the relayer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint_submission: Option<CheckpointSubmissionPolicy>,
+}
+
+/// Retry policy of the bottom-up checkpoint relayer when the submission of a
+/// checkpoint to the parent is not confirmed in time, e.g. because it is under-priced.
+#[serde_as]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct CheckpointSubmissionPolicy {
+    /// How long to wait for a submission to be confirmed before re-submitting it.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(default = "default_confirmation_timeout")]
+    pub confirmation_timeout: Duration,
+    /// The increase of the gas premium for each re-submission, in basis points (1250 = 12.5%).
+    #[serde(default = "default_gas_premium_bump_bps")]
+    pub gas_premium_bump_bps: u32,
+    /// Maximum number of submissions of the same checkpoint, including the first one.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+}
+
+impl Default for CheckpointSubmissionPolicy {
+    fn default() -> Self {
+        Self {
+            confirmation_timeout: default_confirmation_timeout(),
+            gas_premium_bump_bps: default_gas_premium_bump_bps(),
+            max_attempts: default_max_attempts(),
+        }
+    }
+}
+
+fn default_confirmation_timeout() -> Duration {
+    Duration::from_secs(120)
+}
+
+fn default_gas_premium_bump_bps() -> u32 {
+    1250
+}
+
+fn default_max_attempts() -> u32 {
+    5
 }
@@ -1,6 +1,7 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
 use std::str::FromStr;
+use std::time::Duration;
 
 use fvm_shared::address::Address;
 use indoc::formatdoc;
@@ -8,6 +9,7 @@ use ipc_api::subnet_id::SubnetID;
 use ipc_types::EthAddress;
 use url::Url;
 
+use crate::config::subnet::CheckpointSubmissionPolicy;
 use crate::config::Config;
 
 // Arguments for the config's fields
@@ -15,6 +17,7 @@ const REPO_PATH: &str = "~/.ipc";
 const CHILD_ID: &str = "/r123/f0100";
 const CHILD_AUTH_TOKEN: &str = "CHILD_AUTH_TOKEN";
 const PROVIDER_HTTP: &str = "http://127.0.0.1:3030/rpc/v1";
+const PROVIDER_WS: &str = "ws://127.0.0.1:3030/rpc/v1";
 const ETH_ADDRESS: &str = "0x6be1ccf648c74800380d0520d797a170c808b624";
 
 #[test]
@@ -42,6 +45,65 @@ fn check_subnets_config() {
     assert_eq!(child.auth_token().as_ref().unwrap(), CHILD_AUTH_TOKEN);
 }
 
+#[test]
+fn check_checkpoint_submission_config() {
+    let child_id = SubnetID::from_str(CHILD_ID).unwrap();
+
+    let config = read_config().subnets;
+    assert_eq!(
+        config[&child_id].checkpoint_submission(),
+        CheckpointSubmissionPolicy::default()
+    );
+
+    let config = Config::from_toml_str(&formatdoc!(
+        r#"
+        {}
+        [subnets.config.checkpoint_submission]
+        confirmation_timeout = 30
+        max_attempts = 3
+        "#,
+        config_str()
+    ))
+    .unwrap()
+    .subnets;
+
+    let policy = config[&child_id].checkpoint_submission();
+    assert_eq!(policy.confirmation_timeout, Duration::from_secs(30));
+    assert_eq!(policy.max_attempts, 3);
+    assert_eq!(
+        policy.gas_premium_bump_bps,
+        CheckpointSubmissionPolicy::default().gas_premium_bump_bps
+    );
+}
+
+#[test]
+fn check_ledger_accounts_config() {
+    assert!(read_config().ledger_accounts.is_empty());
+
+    let config = Config::from_toml_str(&formatdoc!(
+        r#"
+        {}
+        [[ledger_accounts]]
+        address = "{ETH_ADDRESS}"
+
+        [[ledger_accounts]]
+        address = "{ETH_ADDRESS}"
+        derivation_path = "m/44'/60'/1'/0/0"
+        "#,
+        config_str()
+    ))
+    .unwrap();
+
+    let accounts = config.ledger_accounts;
+    assert_eq!(accounts.len(), 2);
+    assert_eq!(
+        accounts[0].address,
+        ethers::types::Address::from_str(ETH_ADDRESS).unwrap()
+    );
+    assert_eq!(accounts[0].derivation_path, "m/44'/60'/0'/0/0");
+    assert_eq!(accounts[1].derivation_path, "m/44'/60'/1'/0/0");
+}
+
 fn config_str() -> String {
     formatdoc!(
         r#"
@@ -63,3 +125,75 @@ fn config_str() -> String {
 fn read_config() -> Config {
     Config::from_toml_str(config_str().as_str()).unwrap()
 }
+
+#[test]
+fn check_subnet_validation() {
+    assert!(read_config().validate().is_ok());
+
+    let zero_gateway = config_str().replace(
+        &format!(r#"gateway_addr = "{ETH_ADDRESS}""#),
+        r#"gateway_addr = "0x0000000000000000000000000000000000000000""#,
+    );
+    let err = Config::from_toml_str(&zero_gateway)
+        .unwrap()
+        .validate()
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains(CHILD_ID), "{err}");
+    assert!(err.contains("gateway_addr"), "{err}");
+
+    // Parses as a URL with a `localhost` scheme and no host.
+    let malformed_url = config_str().replace(PROVIDER_HTTP, "localhost:3030/rpc/v1");
+    let err = Config::from_toml_str(&malformed_url)
+        .unwrap()
+        .validate()
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains(CHILD_ID), "{err}");
+    assert!(err.contains("provider_http"), "{err}");
+}
+
+#[test]
+fn check_from_file_validates() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let zero_registry = config_str().replace(
+        &format!(r#"registry_addr = "{ETH_ADDRESS}""#),
+        r#"registry_addr = "0x0000000000000000000000000000000000000000""#,
+    );
+    std::fs::write(file.path(), zero_registry).unwrap();
+
+    let err = Config::from_file(file.path()).unwrap_err();
+    assert!(format!("{err:#}").contains("registry_addr"), "{err:#}");
+}
+
+#[test]
+fn check_provider_ws_config() {
+    let child_id = SubnetID::from_str(CHILD_ID).unwrap();
+
+    // HTTP only configs keep working without a WebSocket endpoint.
+    assert!(read_config().subnets[&child_id].rpc_ws().is_none());
+
+    let with_ws = config_str().replace(
+        &format!(r#"provider_http = "{PROVIDER_HTTP}""#),
+        &format!("provider_http = \"{PROVIDER_HTTP}\"\nprovider_ws = \"{PROVIDER_WS}\""),
+    );
+    let config = Config::from_toml_str(&with_ws).unwrap();
+    assert!(config.validate().is_ok());
+    assert_eq!(
+        config.subnets[&child_id].rpc_ws(),
+        Some(&Url::from_str(PROVIDER_WS).unwrap())
+    );
+
+    // The HTTP endpoint is still required.
+    let ws_only = with_ws.replace(&format!(r#"provider_http = "{PROVIDER_HTTP}""#), "");
+    assert!(Config::from_toml_str(&ws_only).is_err());
+
+    let not_ws = with_ws.replace(PROVIDER_WS, PROVIDER_HTTP);
+    let err = Config::from_toml_str(&not_ws)
+        .unwrap()
+        .validate()
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains(CHILD_ID), "{err}");
+    assert!(err.contains("provider_ws"), "{err}");
+}
@@ -0,0 +1,561 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Tracking of cross-net messages from the subnet they were sent in to their destination.
+
+use anyhow::{anyhow, Result};
+use fvm_shared::clock::ChainEpoch;
+use ipc_api::subnet_id::SubnetID;
+
+use crate::manager::{BottomUpCheckpointRelayer, CrossMsgQuery, TopDownFinalityQuery};
+
+/// The direction of a cross-net message between a subnet and its parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::VariantNames)]
+#[strum(serialize_all = "kebab-case")]
+pub enum CrossMsgDirection {
+    /// From the parent to the child, e.g. `fund`.
+    TopDown,
+    /// From the child to the parent, e.g. `release`.
+    BottomUp,
+}
+
+/// Identifies the message to look for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrossMsgRef {
+    /// The nonce the gateway assigned to the message.
+    Nonce(u64),
+    /// The epoch the message was sent at in the source subnet, as returned by `fund` and `release`.
+    Epoch(ChainEpoch),
+}
+
+/// Where a cross-net message is on its way to the destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrossMsgStatus {
+    /// The message hasn't been picked up yet by the parent finality (top-down)
+    /// or a bottom-up checkpoint (bottom-up).
+    Pending,
+    /// The message has been picked up by the parent finality or the checkpoint at `height`,
+    /// but it hasn't been executed in the destination yet.
+    InFlight { height: ChainEpoch },
+    /// The message has been executed in the destination, at the latest by
+    /// the parent finality or the checkpoint at `height`.
+    Executed { height: ChainEpoch },
+    /// There is no such message.
+    Unknown,
+}
+
+/// Find out where a cross-net message between `subnet` and its parent is.
+///
+/// Looking up a message by its nonce can only tell whether it has been executed,
+/// so messages which haven't been are reported as [`CrossMsgStatus::Pending`].
+pub async fn cross_msg_status<M>(
+    parent: &M,
+    child: &M,
+    subnet: &SubnetID,
+    direction: CrossMsgDirection,
+    msg: CrossMsgRef,
+) -> Result<CrossMsgStatus>
+where
+    M: TopDownFinalityQuery + BottomUpCheckpointRelayer + CrossMsgQuery + ?Sized,
+{
+    match direction {
+        CrossMsgDirection::TopDown => top_down_status(parent, child, subnet, msg).await,
+        CrossMsgDirection::BottomUp => bottom_up_status(parent, child, subnet, msg).await,
+    }
+}
+
+/// Compare the nonce of the message with the ones applied by the child, and the epoch
+/// it was sent at with the latest parent finality the child has committed.
+async fn top_down_status<M>(
+    parent: &M,
+    child: &M,
+    subnet: &SubnetID,
+    msg: CrossMsgRef,
+) -> Result<CrossMsgStatus>
+where
+    M: TopDownFinalityQuery + CrossMsgQuery + ?Sized,
+{
+    let (nonce, epoch) = match msg {
+        CrossMsgRef::Nonce(nonce) => {
+            if nonce >= parent.top_down_nonce(subnet).await? {
+                return Ok(CrossMsgStatus::Unknown);
+            }
+            (nonce, None)
+        }
+        CrossMsgRef::Epoch(epoch) => {
+            // There might be other messages to the subnet in the same block; wait for all of them.
+            let msgs = parent.get_top_down_msgs(subnet, epoch).await?.value;
+            match msgs.iter().map(|m| m.nonce).max() {
+                Some(nonce) => (nonce, Some(epoch)),
+                None => return Ok(CrossMsgStatus::Unknown),
+            }
+        }
+    };
+
+    let finality = child.latest_parent_finality().await?;
+
+    if child.applied_top_down_nonce().await? > nonce {
+        return Ok(CrossMsgStatus::Executed { height: finality });
+    }
+
+    match epoch {
+        Some(epoch) if finality >= epoch => Ok(CrossMsgStatus::InFlight { height: finality }),
+        _ => Ok(CrossMsgStatus::Pending),
+    }
+}
+
+/// Find the checkpoint which carries the messages sent at an epoch and check whether the parent
+/// has executed it, or compare the nonce of the message with the ones applied by the parent.
+async fn bottom_up_status<M>(
+    parent: &M,
+    child: &M,
+    subnet: &SubnetID,
+    msg: CrossMsgRef,
+) -> Result<CrossMsgStatus>
+where
+    M: BottomUpCheckpointRelayer + CrossMsgQuery + ?Sized,
+{
+    match msg {
+        CrossMsgRef::Nonce(nonce) => {
+            if nonce >= child.bottom_up_nonce().await? {
+                return Ok(CrossMsgStatus::Unknown);
+            }
+            if parent.applied_bottom_up_nonce(subnet).await? > nonce {
+                let height = parent.last_bottom_up_checkpoint_height(subnet).await?;
+                return Ok(CrossMsgStatus::Executed { height });
+            }
+            Ok(CrossMsgStatus::Pending)
+        }
+        CrossMsgRef::Epoch(epoch) => {
+            let period = parent.checkpoint_period(subnet).await?;
+            if period <= 0 {
+                return Err(anyhow!("invalid checkpoint period: {period}"));
+            }
+            let height = checkpoint_height(epoch, period);
+
+            // The gateway might have already pruned the checkpoint after it was executed.
+            let bundle = child.checkpoint_bundle_at(height).await?;
+            if let Some(ref bundle) = bundle {
+                if bundle.checkpoint.msgs.is_empty() {
+                    return Ok(CrossMsgStatus::Unknown);
+                }
+            }
+
+            if parent.last_bottom_up_checkpoint_height(subnet).await? >= height {
+                return Ok(CrossMsgStatus::Executed { height });
+            }
+
+            match bundle {
+                Some(_) => Ok(CrossMsgStatus::InFlight { height }),
+                None => Ok(CrossMsgStatus::Pending),
+            }
+        }
+    }
+}
+
+/// The height of the checkpoint which includes the bottom-up messages sent at `epoch`.
+///
+/// The gateway batches the messages for the checkpoint at the next multiple of the period,
+/// which is always above the epoch they were sent at.
+fn checkpoint_height(epoch: ChainEpoch, period: ChainEpoch) -> ChainEpoch {
+    (epoch / period + 1) * period
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use futures_util::stream::BoxStream;
+    use fvm_shared::address::Address;
+    use fvm_shared::clock::ChainEpoch;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::checkpoint::{
+        BottomUpCheckpoint, BottomUpCheckpointBundle, QuorumReachedEvent, Signature,
+    };
+    use ipc_api::cross::IpcEnvelope;
+    use ipc_api::staking::StakingChangeRequest;
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::{
+        checkpoint_height, cross_msg_status, CrossMsgDirection, CrossMsgRef, CrossMsgStatus,
+    };
+    use crate::manager::{
+        BottomUpCheckpointRelayer, CheckpointSigners, CheckpointSubmission, CrossMsgQuery,
+        GetBlockHashResult, PendingSubmission, TopDownFinalityQuery, TopDownQueryPayload,
+    };
+
+    const PERIOD: ChainEpoch = 10;
+
+    /// Gateway responses of either the parent or the child, depending on how it is used.
+    #[derive(Default)]
+    struct MockManager {
+        /// The nonces of the top-down messages sent at an epoch, when acting as the parent.
+        top_down_msgs: Vec<(ChainEpoch, u64)>,
+        /// The next top-down nonce, when acting as the parent.
+        top_down_nonce: u64,
+        /// The number of bottom-up messages applied, when acting as the parent.
+        applied_bottom_up_nonce: u64,
+        /// The last checkpoint executed, when acting as the parent.
+        last_checkpoint_height: ChainEpoch,
+        /// The latest parent finality, when acting as the child.
+        finality: ChainEpoch,
+        /// The number of top-down messages applied, when acting as the child.
+        applied_top_down_nonce: u64,
+        /// The next bottom-up nonce, when acting as the child.
+        bottom_up_nonce: u64,
+        /// The checkpoints with the number of messages in them, when acting as the child.
+        checkpoints: Vec<(ChainEpoch, usize)>,
+    }
+
+    #[async_trait]
+    impl TopDownFinalityQuery for MockManager {
+        async fn genesis_epoch(&self, _subnet_id: &SubnetID) -> anyhow::Result<ChainEpoch> {
+            unimplemented!()
+        }
+
+        async fn chain_head_height(&self) -> anyhow::Result<ChainEpoch> {
+            unimplemented!()
+        }
+
+        async fn get_top_down_msgs(
+            &self,
+            _subnet_id: &SubnetID,
+            epoch: ChainEpoch,
+        ) -> anyhow::Result<TopDownQueryPayload<Vec<IpcEnvelope>>> {
+            let value = self
+                .top_down_msgs
+                .iter()
+                .filter(|(e, _)| *e == epoch)
+                .map(|(_, nonce)| envelope(*nonce))
+                .collect();
+            Ok(TopDownQueryPayload {
+                value,
+                block_hash: vec![],
+            })
+        }
+
+        async fn get_block_hash(&self, _height: ChainEpoch) -> anyhow::Result<GetBlockHashResult> {
+            unimplemented!()
+        }
+
+        async fn get_validator_changeset(
+            &self,
+            _subnet_id: &SubnetID,
+            _epoch: ChainEpoch,
+        ) -> anyhow::Result<TopDownQueryPayload<Vec<StakingChangeRequest>>> {
+            unimplemented!()
+        }
+
+        async fn latest_parent_finality(&self) -> anyhow::Result<ChainEpoch> {
+            Ok(self.finality)
+        }
+    }
+
+    #[async_trait]
+    impl BottomUpCheckpointRelayer for MockManager {
+        async fn submit_checkpoint(
+            &self,
+            _submitter: &Address,
+            _checkpoint: BottomUpCheckpoint,
+            _signatures: Vec<Signature>,
+            _signatories: Vec<Address>,
+        ) -> anyhow::Result<ChainEpoch> {
+            unimplemented!()
+        }
+
+        async fn submit_checkpoint_with_timeout(
+            &self,
+            _submitter: &Address,
+            _checkpoint: BottomUpCheckpoint,
+            _signatures: Vec<Signature>,
+            _signatories: Vec<Address>,
+            _replace: Option<PendingSubmission>,
+            _timeout: Duration,
+        ) -> anyhow::Result<CheckpointSubmission> {
+            unimplemented!()
+        }
+
+        async fn last_bottom_up_checkpoint_height(
+            &self,
+            _subnet_id: &SubnetID,
+        ) -> anyhow::Result<ChainEpoch> {
+            Ok(self.last_checkpoint_height)
+        }
+
+        async fn checkpoint_period(&self, _subnet_id: &SubnetID) -> anyhow::Result<ChainEpoch> {
+            Ok(PERIOD)
+        }
+
+        async fn checkpoint_signers(
+            &self,
+            _subnet_id: &SubnetID,
+        ) -> anyhow::Result<CheckpointSigners> {
+            unimplemented!()
+        }
+
+        async fn checkpoint_bundle_at(
+            &self,
+            height: ChainEpoch,
+        ) -> anyhow::Result<Option<BottomUpCheckpointBundle>> {
+            Ok(self
+                .checkpoints
+                .iter()
+                .find(|(h, _)| *h == height)
+                .map(|(_, msgs)| bundle_at(height, *msgs)))
+        }
+
+        async fn quorum_reached_events(
+            &self,
+            _height: ChainEpoch,
+        ) -> anyhow::Result<Vec<QuorumReachedEvent>> {
+            unimplemented!()
+        }
+
+        fn subscribe_quorum_events(
+            self: Arc<Self>,
+            _from_height: ChainEpoch,
+        ) -> BoxStream<'static, anyhow::Result<QuorumReachedEvent>> {
+            unimplemented!()
+        }
+
+        async fn current_epoch(&self) -> anyhow::Result<ChainEpoch> {
+            unimplemented!()
+        }
+    }
+
+    #[async_trait]
+    impl CrossMsgQuery for MockManager {
+        async fn top_down_nonce(&self, _subnet_id: &SubnetID) -> anyhow::Result<u64> {
+            Ok(self.top_down_nonce)
+        }
+
+        async fn applied_top_down_nonce(&self) -> anyhow::Result<u64> {
+            Ok(self.applied_top_down_nonce)
+        }
+
+        async fn bottom_up_nonce(&self) -> anyhow::Result<u64> {
+            Ok(self.bottom_up_nonce)
+        }
+
+        async fn applied_bottom_up_nonce(&self, _subnet_id: &SubnetID) -> anyhow::Result<u64> {
+            Ok(self.applied_bottom_up_nonce)
+        }
+    }
+
+    fn subnet() -> SubnetID {
+        SubnetID::new_from_parent(&SubnetID::new_root(123), Address::new_id(1000))
+    }
+
+    fn envelope(nonce: u64) -> IpcEnvelope {
+        let mut msg = IpcEnvelope::new_fund_msg(
+            &subnet(),
+            &Address::new_id(100),
+            &Address::new_id(100),
+            TokenAmount::from_whole(1),
+        )
+        .unwrap();
+        msg.nonce = nonce;
+        msg
+    }
+
+    fn bundle_at(height: ChainEpoch, msgs: usize) -> BottomUpCheckpointBundle {
+        BottomUpCheckpointBundle {
+            checkpoint: BottomUpCheckpoint {
+                subnet_id: subnet(),
+                block_height: height,
+                block_hash: vec![0; 32],
+                next_configuration_number: 0,
+                msgs: (0..msgs as u64).map(envelope).collect(),
+            },
+            signatures: vec![],
+            signatories: vec![],
+        }
+    }
+
+    async fn status(
+        parent: MockManager,
+        child: MockManager,
+        direction: CrossMsgDirection,
+        msg: CrossMsgRef,
+    ) -> CrossMsgStatus {
+        cross_msg_status(&parent, &child, &subnet(), direction, msg)
+            .await
+            .unwrap()
+    }
+
+    /// A parent with messages with nonce 3 and 4 sent at epoch 50.
+    fn top_down_parent() -> MockManager {
+        MockManager {
+            top_down_msgs: vec![(50, 3), (50, 4)],
+            top_down_nonce: 5,
+            ..Default::default()
+        }
+    }
+
+    fn top_down_child(finality: ChainEpoch, applied_top_down_nonce: u64) -> MockManager {
+        MockManager {
+            finality,
+            applied_top_down_nonce,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_top_down_by_epoch() {
+        let cases = [
+            (top_down_child(40, 3), CrossMsgStatus::Pending),
+            // Nonce 4 hasn't been applied yet, although the finality covers it.
+            (
+                top_down_child(50, 4),
+                CrossMsgStatus::InFlight { height: 50 },
+            ),
+            (
+                top_down_child(52, 5),
+                CrossMsgStatus::Executed { height: 52 },
+            ),
+        ];
+        for (child, expected) in cases {
+            let status = status(
+                top_down_parent(),
+                child,
+                CrossMsgDirection::TopDown,
+                CrossMsgRef::Epoch(50),
+            )
+            .await;
+            assert_eq!(status, expected);
+        }
+
+        // Nothing was sent to the subnet at that epoch.
+        let status = status(
+            top_down_parent(),
+            top_down_child(52, 5),
+            CrossMsgDirection::TopDown,
+            CrossMsgRef::Epoch(51),
+        )
+        .await;
+        assert_eq!(status, CrossMsgStatus::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_top_down_by_nonce() {
+        let cases = [
+            (3, top_down_child(50, 3), CrossMsgStatus::Pending),
+            (
+                3,
+                top_down_child(50, 4),
+                CrossMsgStatus::Executed { height: 50 },
+            ),
+            // The parent hasn't assigned nonce 5 yet.
+            (5, top_down_child(50, 5), CrossMsgStatus::Unknown),
+        ];
+        for (nonce, child, expected) in cases {
+            let status = status(
+                top_down_parent(),
+                child,
+                CrossMsgDirection::TopDown,
+                CrossMsgRef::Nonce(nonce),
+            )
+            .await;
+            assert_eq!(status, expected);
+        }
+    }
+
+    fn bottom_up_parent(last_checkpoint_height: ChainEpoch, applied: u64) -> MockManager {
+        MockManager {
+            last_checkpoint_height,
+            applied_bottom_up_nonce: applied,
+            ..Default::default()
+        }
+    }
+
+    /// A child with a checkpoint with messages at 20 and one without messages at 30.
+    fn bottom_up_child() -> MockManager {
+        MockManager {
+            bottom_up_nonce: 2,
+            checkpoints: vec![(10, 0), (20, 2), (30, 0)],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bottom_up_by_epoch() {
+        let cases = [
+            // The messages sent at 15 are in the checkpoint at 20.
+            (
+                15,
+                bottom_up_parent(10, 0),
+                CrossMsgStatus::InFlight { height: 20 },
+            ),
+            (
+                15,
+                bottom_up_parent(20, 2),
+                CrossMsgStatus::Executed { height: 20 },
+            ),
+            // The messages sent at 20 go into the checkpoint at 30, which doesn't have any.
+            (20, bottom_up_parent(20, 2), CrossMsgStatus::Unknown),
+            // The checkpoint at 40 hasn't been created yet.
+            (35, bottom_up_parent(30, 2), CrossMsgStatus::Pending),
+            // The checkpoint at 50 has been pruned after it was executed.
+            (
+                45,
+                bottom_up_parent(50, 5),
+                CrossMsgStatus::Executed { height: 50 },
+            ),
+        ];
+        for (epoch, parent, expected) in cases {
+            let status = status(
+                parent,
+                bottom_up_child(),
+                CrossMsgDirection::BottomUp,
+                CrossMsgRef::Epoch(epoch),
+            )
+            .await;
+            assert_eq!(status, expected, "epoch {epoch}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bottom_up_by_nonce() {
+        let cases = [
+            (1, bottom_up_parent(10, 1), CrossMsgStatus::Pending),
+            (
+                1,
+                bottom_up_parent(20, 2),
+                CrossMsgStatus::Executed { height: 20 },
+            ),
+            // The child hasn't assigned nonce 2 yet.
+            (2, bottom_up_parent(20, 2), CrossMsgStatus::Unknown),
+        ];
+        for (nonce, parent, expected) in cases {
+            let status = status(
+                parent,
+                bottom_up_child(),
+                CrossMsgDirection::BottomUp,
+                CrossMsgRef::Nonce(nonce),
+            )
+            .await;
+            assert_eq!(status, expected, "nonce {nonce}");
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_height() {
+        assert_eq!(checkpoint_height(0, PERIOD), 10);
+        assert_eq!(checkpoint_height(9, PERIOD), 10);
+        assert_eq!(checkpoint_height(10, PERIOD), 20);
+    }
+
+    #[test]
+    fn test_parse_direction() {
+        assert_eq!(
+            "top-down".parse::<CrossMsgDirection>().unwrap(),
+            CrossMsgDirection::TopDown
+        );
+        assert_eq!(
+            "bottom-up".parse::<CrossMsgDirection>().unwrap(),
+            CrossMsgDirection::BottomUp
+        );
+        assert!("sideways".parse::<CrossMsgDirection>().is_err());
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! The errors returned by the entry points of the [`IpcProvider`](crate::IpcProvider).
+
+use ethers::contract::ContractError;
+use ethers::providers::{Http, JsonRpcError, Middleware, MiddlewareError, Provider, RpcError};
+use ipc_api::subnet_id::SubnetID;
+use thiserror::Error;
+
+use crate::manager::evm::DefaultSignerMiddleware;
+
+#[derive(Debug, Error)]
+pub enum ProviderError {
+    #[error("subnet not found: {subnet}; known subnets: {known:?}")]
+    SubnetNotConfigured {
+        subnet: SubnetID,
+        known: Vec<String>,
+    },
+    #[error("no parent found for subnet: {0}")]
+    MissingParent(SubnetID),
+    #[error("no {0} wallet found in provider")]
+    WalletMissing(&'static str),
+    #[error("key not found: {0}")]
+    KeyNotFound(String),
+    #[error("no sender given and no default account set in the wallet")]
+    NoDefaultSender,
+    #[error("rpc transport error: {0:#}")]
+    RpcTransport(anyhow::Error),
+    #[error("rpc error response {code}: {message}")]
+    RpcResponse { code: i64, message: String },
+    #[error("contract reverted: {}", revert_reason(.selector, .data))]
+    ContractRevert {
+        selector: Option<[u8; 4]>,
+        data: Vec<u8>,
+    },
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl ProviderError {
+    /// Builds a [`ProviderError::ContractRevert`] from the raw revert data.
+    pub fn revert(data: &[u8]) -> Self {
+        let selector = data.get(..4).map(|s| {
+            let mut selector = [0u8; 4];
+            selector.copy_from_slice(s);
+            selector
+        });
+        ProviderError::ContractRevert {
+            selector,
+            data: data.to_vec(),
+        }
+    }
+}
+
+/// The errors of the subnet managers are still `anyhow`, so they are classified here by looking
+/// for well known error types in the chain: contract reverts, error responses of the node and
+/// transport failures get their own variants, everything else ends up in [`ProviderError::Other`].
+///
+/// Only failures to reach the node count as transport errors; a JSON-RPC error response such
+/// as "nonce too low" means the node is reachable but rejected the request.
+impl From<anyhow::Error> for ProviderError {
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<serde_json::Error>() {
+            Ok(e) => return ProviderError::Serialization(e),
+            Err(e) => e,
+        };
+
+        let mut transport = false;
+        for cause in e.chain() {
+            let kind =
+                if let Some(c) = cause.downcast_ref::<ContractError<DefaultSignerMiddleware>>() {
+                    contract_error_kind(c)
+                } else if let Some(c) = cause.downcast_ref::<ContractError<Provider<Http>>>() {
+                    contract_error_kind(c)
+                } else if let Some(p) = cause.downcast_ref::<ethers::providers::ProviderError>() {
+                    provider_error_kind(p)
+                } else if let Some(r) = cause.downcast_ref::<JsonRpcError>() {
+                    Some(ErrorKind::Response(r.clone()))
+                } else if let Some(r) = cause.downcast_ref::<reqwest::Error>() {
+                    is_transport_error(r).then_some(ErrorKind::Transport)
+                } else {
+                    None
+                };
+
+            match kind {
+                Some(ErrorKind::Revert(data)) => return ProviderError::revert(&data),
+                Some(ErrorKind::Response(r)) => {
+                    return ProviderError::RpcResponse {
+                        code: r.code,
+                        message: r.message,
+                    }
+                }
+                Some(ErrorKind::Transport) => {
+                    transport = true;
+                    break;
+                }
+                None => {}
+            }
+        }
+
+        if transport {
+            ProviderError::RpcTransport(e)
+        } else {
+            ProviderError::Other(e)
+        }
+    }
+}
+
+enum ErrorKind {
+    Revert(Vec<u8>),
+    Response(JsonRpcError),
+    Transport,
+}
+
+fn contract_error_kind<M: Middleware>(e: &ContractError<M>) -> Option<ErrorKind> {
+    if let Some(data) = e.as_revert() {
+        return Some(ErrorKind::Revert(data.to_vec()));
+    }
+    match e {
+        ContractError::MiddlewareError { e } => e.as_provider_error().and_then(provider_error_kind),
+        ContractError::ProviderError { e } => provider_error_kind(e),
+        _ => None,
+    }
+}
+
+fn provider_error_kind(e: &ethers::providers::ProviderError) -> Option<ErrorKind> {
+    if let Some(r) = RpcError::as_error_response(e) {
+        return Some(ErrorKind::Response(r.clone()));
+    }
+    match e {
+        ethers::providers::ProviderError::HTTPError(_) => Some(ErrorKind::Transport),
+        _ => None,
+    }
+}
+
+/// Whether the request failed because the node could not be reached in time.
+fn is_transport_error(e: &reqwest::Error) -> bool {
+    e.is_connect() || e.is_timeout()
+}
+
+fn revert_reason(selector: &Option<[u8; 4]>, data: &[u8]) -> String {
+    match ipc_api::evm::decode_revert_reason(data) {
+        Some(reason) => reason,
+        None => match selector {
+            Some(selector) => format!("unknown error 0x{}", hex::encode(selector)),
+            None => "no revert data".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::contract::ContractError;
+    use ethers::providers::{Http, HttpClientError, JsonRpcError, Middleware, Provider};
+
+    use super::ProviderError;
+
+    #[test]
+    fn test_rpc_error_response_is_not_transport() {
+        let response = JsonRpcError {
+            code: -32000,
+            message: "nonce too low".to_string(),
+            data: None,
+        };
+        let e = ethers::providers::ProviderError::JsonRpcClientError(Box::new(
+            HttpClientError::JsonRpcError(response),
+        ));
+        let e = anyhow::Error::new(ContractError::<Provider<Http>>::ProviderError { e })
+            .context("failed to send transaction");
+
+        match ProviderError::from(e) {
+            ProviderError::RpcResponse { code, message } => {
+                assert_eq!(code, -32000);
+                assert_eq!(message, "nonce too low");
+            }
+            e => panic!("unexpected error: {e:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_node_is_transport() {
+        // Nothing listens on port 1, so the connection is refused.
+        let provider = Provider::<Http>::try_from("http://127.0.0.1:1").unwrap();
+        let e = provider.get_block_number().await.unwrap_err();
+        let e = anyhow::Error::new(e).context("failed to get the chain head");
+
+        assert!(matches!(
+            ProviderError::from(e),
+            ProviderError::RpcTransport(_)
+        ));
+    }
+}
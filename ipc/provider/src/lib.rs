@@ -2,16 +2,22 @@
 // SPDX-License-Identifier: MIT
 //! Ipc agent sdk, contains the json rpc client to interact with the IPC agent rpc server.
 
-use crate::manager::{GetBlockHashResult, TopDownQueryPayload};
+use crate::crossmsg::{CrossMsgDirection, CrossMsgRef, CrossMsgStatus};
+use crate::manager::{
+    CallEstimate, CheckpointSigners, GetBlockHashResult, SubnetOperation, TopDownQueryPayload,
+};
+use crate::topology::{SubnetNode, MAX_RECURSIVE_SUBNETS};
 use anyhow::anyhow;
 use base64::Engine;
 use config::Config;
+use error::ProviderError;
+use futures_util::stream::BoxStream;
 use fvm_shared::{
     address::Address, clock::ChainEpoch, crypto::signature::SignatureType, econ::TokenAmount,
 };
 use ipc_api::checkpoint::{BottomUpCheckpointBundle, QuorumReachedEvent};
 use ipc_api::evm::payload_to_evm_address;
-use ipc_api::staking::{StakingChangeRequest, ValidatorInfo};
+use ipc_api::staking::{StakingChangeRequest, ValidatorInfo, ValidatorSetInfo};
 use ipc_api::subnet::{PermissionMode, SupplySource};
 use ipc_api::{
     cross::IpcEnvelope,
@@ -19,36 +25,84 @@ use ipc_api::{
     subnet_id::SubnetID,
 };
 use ipc_wallet::{
-    EthKeyAddress, EvmKeyStore, KeyStore, KeyStoreConfig, PersistentKeyStore, Wallet,
+    EthKeyAddress, EvmKeyStore, KeyStore, KeyStoreConfig, PersistentKeyStore, SignerSelector,
+    Wallet,
 };
 use lotus::message::wallet::WalletKeyType;
-use manager::{EthSubnetManager, SubnetGenesisInfo, SubnetInfo, SubnetManager};
+use manager::{EthSubnetManager, SubnetGenesisInfo, SubnetInfo, SubnetManager, SubnetStatus};
 use serde::{Deserialize, Serialize};
 use std::{
     borrow::Borrow,
     collections::HashMap,
     path::{Path, PathBuf},
     str::FromStr,
-    sync::{Arc, RwLock},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, SystemTime},
 };
+use tokio::task::JoinHandle;
 use zeroize::Zeroize;
 
 pub mod checkpoint;
 pub mod config;
+pub mod crossmsg;
+pub mod error;
 pub mod jsonrpc;
 pub mod lotus;
 pub mod manager;
+pub mod topology;
+
+#[cfg(test)]
+mod tests;
 
 const DEFAULT_REPO_PATH: &str = ".ipc";
 const DEFAULT_CONFIG_NAME: &str = "config.toml";
+/// Number of consecutive transport errors after which a cached connection is replaced.
+const MAX_TRANSPORT_ERRORS: usize = 3;
+
+#[cfg(test)]
+thread_local! {
+    /// Number of connections created on the current thread, to check they are reused.
+    static CONNECTIONS_CREATED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
 
 /// The subnet manager connection that holds the subnet config and the manager instance.
+///
+/// Connections are cached by the provider and shared between calls, so that they reuse the
+/// same HTTP client and its pooled connections; cloning one is cheap.
+#[derive(Clone)]
 pub struct Connection {
-    subnet: config::Subnet,
-    manager: Box<dyn SubnetManager + 'static>,
+    subnet: Arc<config::Subnet>,
+    manager: Arc<dyn SubnetManager + 'static>,
+    /// Consecutive transport errors seen through this connection.
+    transport_errors: Arc<AtomicUsize>,
 }
 
 impl Connection {
+    fn new(
+        subnet: &config::Subnet,
+        config: &Config,
+        keystore: Option<Arc<RwLock<PersistentKeyStore<EthKeyAddress>>>>,
+    ) -> anyhow::Result<Self> {
+        let manager = match &subnet.config {
+            config::subnet::SubnetConfig::Fevm(_) => {
+                EthSubnetManager::from_subnet_with_wallet_store(subnet, keystore)?
+                    .with_signer_selector(signer_selector(config))
+            }
+        };
+
+        #[cfg(test)]
+        CONNECTIONS_CREATED.with(|c| c.set(c.get() + 1));
+
+        Ok(Self {
+            subnet: Arc::new(subnet.clone()),
+            manager: Arc::new(manager),
+            transport_errors: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
     /// Get the subnet config.
     pub fn subnet(&self) -> &config::Subnet {
         &self.subnet
@@ -63,7 +117,17 @@ impl Connection {
 #[derive(Clone)]
 pub struct IpcProvider {
     sender: Option<Address>,
-    config: Arc<Config>,
+    /// The current config, shared by the clones of the provider so that they all see reloads.
+    ///
+    /// Connections take a snapshot of it when they are created, so they are not affected
+    /// by reloads while they are in use.
+    config: Arc<RwLock<Arc<Config>>>,
+    /// Connections created from the current config, shared by the clones of the provider.
+    ///
+    /// When both are needed, this lock is taken before the one of the config.
+    connections: Arc<RwLock<HashMap<SubnetID, Arc<Connection>>>>,
+    /// The file the config was loaded from, if any, to reload it from.
+    config_path: Option<PathBuf>,
     fvm_wallet: Option<Arc<RwLock<Wallet>>>,
     evm_keystore: Option<Arc<RwLock<PersistentKeyStore<EthKeyAddress>>>>,
 }
@@ -76,7 +140,9 @@ impl IpcProvider {
     ) -> Self {
         Self {
             sender: None,
-            config,
+            config: Arc::new(RwLock::new(config)),
+            connections: Default::default(),
+            config_path: None,
             fvm_wallet: Some(fvm_wallet),
             evm_keystore: Some(evm_keystore),
         }
@@ -84,13 +150,24 @@ impl IpcProvider {
 
     /// Initializes an `IpcProvider` from the config specified in the
     /// argument's config path.
-    pub fn new_from_config(config_path: String) -> anyhow::Result<Self> {
-        let config = Arc::new(Config::from_file(config_path)?);
+    ///
+    /// The `keystore_password` is required if the evm keystore is encrypted.
+    pub fn new_from_config(
+        config_path: String,
+        keystore_password: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let config = Arc::new(Config::from_file(&config_path)?);
         let fvm_wallet = Arc::new(RwLock::new(Wallet::new(new_fvm_wallet_from_config(
             config.clone(),
         )?)));
-        let evm_keystore = Arc::new(RwLock::new(new_evm_keystore_from_config(config.clone())?));
-        Ok(Self::new(config, fvm_wallet, evm_keystore))
+        let evm_keystore = Arc::new(RwLock::new(new_evm_keystore_from_config(
+            config.clone(),
+            keystore_password,
+        )?));
+        Ok(Self {
+            config_path: Some(PathBuf::from(config_path)),
+            ..Self::new(config, fvm_wallet, evm_keystore)
+        })
     }
 
     /// Initializes a new `IpcProvider` configured to interact with
@@ -107,12 +184,14 @@ impl IpcProvider {
             let fvm_wallet = Arc::new(RwLock::new(Wallet::new(new_fvm_keystore_from_path(
                 &repo_path,
             )?)));
-            let evm_keystore = Arc::new(RwLock::new(new_evm_keystore_from_path(&repo_path)?));
+            let evm_keystore = Arc::new(RwLock::new(new_evm_keystore_from_path(&repo_path, None)?));
             Ok(Self::new(config, fvm_wallet, evm_keystore))
         } else {
             Ok(Self {
                 sender: None,
-                config,
+                config: Arc::new(RwLock::new(config)),
+                connections: Default::default(),
+                config_path: None,
                 fvm_wallet: None,
                 evm_keystore: None,
             })
@@ -121,46 +200,148 @@ impl IpcProvider {
 
     /// Initialized an `IpcProvider` using the default config path.
     pub fn new_default() -> anyhow::Result<Self> {
-        Self::new_from_config(default_config_path())
-    }
-
-    /// Get the connection instance for the subnet.
-    pub fn connection(&self, subnet: &SubnetID) -> Option<Connection> {
-        let subnets = &self.config.subnets;
-
-        match subnets.get(subnet) {
-            Some(subnet) => match &subnet.config {
-                config::subnet::SubnetConfig::Fevm(_) => {
-                    let wallet = self.evm_keystore.clone();
-                    let manager =
-                        match EthSubnetManager::from_subnet_with_wallet_store(subnet, wallet) {
-                            Ok(w) => Some(w),
-                            Err(e) => {
-                                tracing::warn!("error initializing evm manager: {e}");
-                                return None;
-                            }
-                        };
-                    Some(Connection {
-                        manager: Box::new(manager.unwrap()),
-                        subnet: subnet.clone(),
-                    })
+        Self::new_from_config(default_config_path(), None)
+    }
+
+    /// The current config; it doesn't change if the config is reloaded afterwards.
+    pub fn config(&self) -> Arc<Config> {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Re-read the config from the file the provider was created from, and use it for
+    /// the connections created from now on.
+    ///
+    /// Subnets removed from the file can no longer be connected to. The wallets are not
+    /// reopened, so a change of the `keystore_path` needs a new provider. If the file is
+    /// not a valid config, the current one is kept.
+    pub fn reload_config(&self) -> Result<(), ProviderError> {
+        let path = self.config_path.as_ref().ok_or_else(|| {
+            ProviderError::Other(anyhow!("the provider was not created from a config file"))
+        })?;
+
+        let config = Config::from_file(path)?;
+
+        let mut connections = self.connections.write().unwrap();
+        let mut current = self.config.write().unwrap();
+        for id in config.subnets.keys() {
+            if !current.subnets.contains_key(id) {
+                tracing::info!(subnet = id.to_string(), "subnet added to the config");
+            }
+        }
+        for id in current.subnets.keys() {
+            if !config.subnets.contains_key(id) {
+                tracing::info!(subnet = id.to_string(), "subnet removed from the config");
+            }
+        }
+        *current = Arc::new(config);
+        // The subnet configs might have changed, so connect again when needed.
+        connections.clear();
+
+        Ok(())
+    }
+
+    /// Reload the config whenever its file changes, checking for changes every `interval`.
+    ///
+    /// The clones of the provider see the reloaded config as well. Invalid configs, e.g. a
+    /// file which is still being written, are logged and skipped until the next change.
+    /// The watcher runs until the returned task is aborted.
+    pub fn watch_config(&self, interval: Duration) -> Result<JoinHandle<()>, ProviderError> {
+        let path = self.config_path.clone().ok_or_else(|| {
+            ProviderError::Other(anyhow!("the provider was not created from a config file"))
+        })?;
+
+        let provider = self.clone();
+        let mut last_version = config_file_version(&path);
+
+        Ok(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+
+                let version = config_file_version(&path);
+                if version == last_version {
+                    continue;
                 }
-            },
-            None => None,
+                last_version = version;
+
+                match provider.reload_config() {
+                    Ok(()) => tracing::info!(path = ?path, "config reloaded"),
+                    Err(e) => tracing::warn!(path = ?path, "failed to reload the config: {e}"),
+                }
+            }
+        }))
+    }
+
+    /// Get the connection instance for the subnet, connecting on first use.
+    ///
+    /// The connection is cached until the config is reloaded, or until it fails
+    /// with too many transport errors in a row.
+    pub fn connection(&self, subnet: &SubnetID) -> Option<Arc<Connection>> {
+        if let Some(conn) = self.connections.read().unwrap().get(subnet) {
+            return Some(conn.clone());
+        }
+
+        let config = self.config();
+        let subnet = config.subnets.get(subnet)?;
+
+        let conn = match Connection::new(subnet, &config, self.evm_keystore.clone()) {
+            Ok(conn) => Arc::new(conn),
+            Err(e) => {
+                tracing::warn!("error initializing evm manager: {e}");
+                return None;
+            }
+        };
+
+        let mut connections = self.connections.write().unwrap();
+        // Don't cache a connection made with a config which has been reloaded since.
+        if !Arc::ptr_eq(&config, &self.config.read().unwrap()) {
+            return Some(conn);
+        }
+        // Someone else might have connected in the meantime; use the same connection as them.
+        Some(connections.entry(subnet.id.clone()).or_insert(conn).clone())
+    }
+
+    /// Convert the result of a call made through a connection, dropping the connection from
+    /// the cache if the node couldn't be reached through it a number of times in a row.
+    fn track<T>(&self, conn: &Arc<Connection>, res: anyhow::Result<T>) -> Result<T, ProviderError> {
+        match res {
+            Ok(value) => {
+                conn.transport_errors.store(0, Ordering::Relaxed);
+                Ok(value)
+            }
+            Err(e) => {
+                let e = ProviderError::from(e);
+                if matches!(e, ProviderError::RpcTransport(_))
+                    && conn.transport_errors.fetch_add(1, Ordering::Relaxed) + 1
+                        >= MAX_TRANSPORT_ERRORS
+                {
+                    let mut connections = self.connections.write().unwrap();
+                    let id = &conn.subnet.id;
+                    if connections.get(id).is_some_and(|c| Arc::ptr_eq(c, conn)) {
+                        tracing::warn!(
+                            subnet = id.to_string(),
+                            "dropping connection after repeated transport errors"
+                        );
+                        connections.remove(id);
+                    }
+                }
+                Err(e)
+            }
         }
     }
 
     /// Get the connection of a subnet, or return an error.
-    fn get_connection(&self, subnet: &SubnetID) -> anyhow::Result<Connection> {
+    fn get_connection(&self, subnet: &SubnetID) -> Result<Arc<Connection>, ProviderError> {
         match self.connection(subnet) {
-            None => Err(anyhow!(
-                "subnet not found: {subnet}; known subnets: {:?}",
-                self.config
+            None => Err(ProviderError::SubnetNotConfigured {
+                subnet: subnet.clone(),
+                known: self
+                    .config()
                     .subnets
                     .keys()
                     .map(|id| id.to_string())
-                    .collect::<Vec<_>>()
-            )),
+                    .collect(),
+            }),
             Some(conn) => Ok(conn),
         }
     }
@@ -174,21 +355,23 @@ impl IpcProvider {
     ///
     /// This method should be used when we want the wallet retrieval to throw an error
     /// if it is not configured (i.e. when the provider needs to sign transactions).
-    pub fn evm_wallet(&self) -> anyhow::Result<Arc<RwLock<PersistentKeyStore<EthKeyAddress>>>> {
+    pub fn evm_wallet(
+        &self,
+    ) -> Result<Arc<RwLock<PersistentKeyStore<EthKeyAddress>>>, ProviderError> {
         if let Some(wallet) = &self.evm_keystore {
             Ok(wallet.clone())
         } else {
-            Err(anyhow!("No evm wallet found in provider"))
+            Err(ProviderError::WalletMissing("evm"))
         }
     }
 
     // FIXME: Reconcile these into a single wallet method that
     // accepts an `ipc_wallet::WalletType` as an input.
-    pub fn fvm_wallet(&self) -> anyhow::Result<Arc<RwLock<Wallet>>> {
+    pub fn fvm_wallet(&self) -> Result<Arc<RwLock<Wallet>>, ProviderError> {
         if let Some(wallet) = &self.fvm_wallet {
             Ok(wallet.clone())
         } else {
-            Err(anyhow!("No fvm wallet found in provider"))
+            Err(ProviderError::WalletMissing("fvm"))
         }
     }
 
@@ -196,7 +379,7 @@ impl IpcProvider {
         &mut self,
         subnet: &config::Subnet,
         from: Option<Address>,
-    ) -> anyhow::Result<Address> {
+    ) -> Result<Address, ProviderError> {
         // if there is from use that.
         if let Some(from) = from {
             return Ok(from);
@@ -214,8 +397,8 @@ impl IpcProvider {
                 if self.sender.is_none() {
                     let wallet = self.evm_wallet()?;
                     let addr = match wallet.write().unwrap().get_default()? {
-                        None => return Err(anyhow!("no default evm account configured")),
-                        Some(addr) => Address::try_from(addr)?,
+                        None => return Err(ProviderError::NoDefaultSender),
+                        Some(addr) => Address::try_from(addr).map_err(anyhow::Error::from)?,
                     };
                     self.sender = Some(addr);
                     return Ok(addr);
@@ -223,15 +406,31 @@ impl IpcProvider {
             }
         };
 
-        Err(anyhow!("error fetching a valid sender"))
+        Err(ProviderError::NoDefaultSender)
     }
 
     /// Lists available subnet connections
     pub fn list_connections(&self) -> HashMap<SubnetID, config::Subnet> {
-        self.config.subnets.clone()
+        self.config().subnets.clone()
     }
 }
 
+/// Signs with a Ledger for the accounts configured as such, with the keystore otherwise.
+fn signer_selector(config: &Config) -> SignerSelector {
+    let ledger_accounts = config
+        .ledger_accounts
+        .iter()
+        .map(|a| (a.address, a.derivation_path.clone()))
+        .collect();
+    SignerSelector::with_ledger(ledger_accounts)
+}
+
+/// The modification time and size of the config file, to tell when it has changed.
+fn config_file_version(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
 /// IpcProvider spawns a daemon-less client to interact with IPC subnets.
 ///
 /// At this point the provider assumes that the user providers a `config.toml`
@@ -253,7 +452,7 @@ impl IpcProvider {
         min_cross_msg_fee: TokenAmount,
         permission_mode: PermissionMode,
         supply_source: SupplySource,
-    ) -> anyhow::Result<Address> {
+    ) -> Result<Address, ProviderError> {
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
@@ -272,9 +471,12 @@ impl IpcProvider {
             supply_source,
         };
 
-        conn.manager()
-            .create_subnet(sender, constructor_params)
-            .await
+        self.track(
+            &conn,
+            conn.manager()
+                .create_subnet(sender, constructor_params)
+                .await,
+        )
     }
 
     pub async fn join_subnet(
@@ -282,8 +484,10 @@ impl IpcProvider {
         subnet: SubnetID,
         from: Option<Address>,
         collateral: TokenAmount,
-    ) -> anyhow::Result<ChainEpoch> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<ChainEpoch, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
@@ -294,15 +498,19 @@ impl IpcProvider {
             .read()
             .unwrap()
             .get(&addr.into())?
-            .ok_or_else(|| anyhow!("key does not exists"))?;
-        let sk = libsecp256k1::SecretKey::parse_slice(key_info.private_key())?;
+            .ok_or_else(|| ProviderError::KeyNotFound(format!("{addr:?}")))?;
+        let sk = libsecp256k1::SecretKey::parse_slice(key_info.private_key())
+            .map_err(anyhow::Error::from)?;
         let public_key = libsecp256k1::PublicKey::from_secret_key(&sk).serialize();
         let hex_public_key = hex::encode(public_key);
         log::info!("joining subnet with public key: {hex_public_key:?}");
 
-        conn.manager()
-            .join_subnet(subnet, sender, collateral, public_key.into())
-            .await
+        self.track(
+            &conn,
+            conn.manager()
+                .join_subnet(subnet, sender, collateral, public_key.into())
+                .await,
+        )
     }
 
     pub async fn pre_fund(
@@ -310,13 +518,18 @@ impl IpcProvider {
         subnet: SubnetID,
         from: Option<Address>,
         balance: TokenAmount,
-    ) -> anyhow::Result<()> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<(), ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().pre_fund(subnet, sender, balance).await
+        self.track(
+            &conn,
+            conn.manager().pre_fund(subnet, sender, balance).await,
+        )
     }
 
     pub async fn pre_release(
@@ -324,14 +537,19 @@ impl IpcProvider {
         subnet: SubnetID,
         from: Option<Address>,
         amount: TokenAmount,
-    ) -> anyhow::Result<()> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<(), ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().pre_release(subnet, sender, amount).await
+        self.track(
+            &conn,
+            conn.manager().pre_release(subnet, sender, amount).await,
+        )
     }
 
     pub async fn stake(
@@ -339,14 +557,19 @@ impl IpcProvider {
         subnet: SubnetID,
         from: Option<Address>,
         collateral: TokenAmount,
-    ) -> anyhow::Result<()> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<(), ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().stake(subnet, sender, collateral).await
+        self.track(
+            &conn,
+            conn.manager().stake(subnet, sender, collateral).await,
+        )
     }
 
     pub async fn unstake(
@@ -354,63 +577,74 @@ impl IpcProvider {
         subnet: SubnetID,
         from: Option<Address>,
         collateral: TokenAmount,
-    ) -> anyhow::Result<()> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<(), ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().unstake(subnet, sender, collateral).await
+        self.track(
+            &conn,
+            conn.manager().unstake(subnet, sender, collateral).await,
+        )
     }
 
     pub async fn leave_subnet(
         &mut self,
         subnet: SubnetID,
         from: Option<Address>,
-    ) -> anyhow::Result<()> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<(), ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().leave_subnet(subnet, sender).await
+        self.track(&conn, conn.manager().leave_subnet(subnet, sender).await)
     }
 
     pub async fn claim_collateral(
         &mut self,
         subnet: SubnetID,
         from: Option<Address>,
-    ) -> anyhow::Result<()> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<(), ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().claim_collateral(subnet, sender).await
+        self.track(&conn, conn.manager().claim_collateral(subnet, sender).await)
     }
 
     pub async fn kill_subnet(
         &mut self,
         subnet: SubnetID,
         from: Option<Address>,
-    ) -> anyhow::Result<()> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<(), ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().kill_subnet(subnet, sender).await
+        self.track(&conn, conn.manager().kill_subnet(subnet, sender).await)
     }
 
     pub async fn list_child_subnets(
         &self,
         gateway_addr: Option<Address>,
         subnet: &SubnetID,
-    ) -> anyhow::Result<HashMap<SubnetID, SubnetInfo>> {
+    ) -> Result<HashMap<SubnetID, SubnetInfo>, ProviderError> {
         let conn = self.get_connection(subnet)?;
 
         let subnet_config = conn.subnet();
@@ -420,7 +654,38 @@ impl IpcProvider {
             Some(addr) => addr,
         };
 
-        conn.manager().list_child_subnets(gateway_addr).await
+        self.track(&conn, conn.manager().list_child_subnets(gateway_addr).await)
+    }
+
+    /// Lists the subnets below `subnet` down to `max_depth` levels, each with its parent.
+    ///
+    /// Only the subnets configured in the provider can be queried for their children,
+    /// the ones which aren't are returned as leaves. At most [`MAX_RECURSIVE_SUBNETS`]
+    /// subnets are returned.
+    pub async fn list_subnets_recursive(
+        &self,
+        subnet: &SubnetID,
+        max_depth: usize,
+    ) -> Result<HashMap<SubnetID, SubnetNode>, ProviderError> {
+        let nodes = topology::list_subnets_recursive(
+            subnet,
+            max_depth,
+            MAX_RECURSIVE_SUBNETS,
+            |subnet| async move {
+                match self.connection(&subnet) {
+                    None => Ok(None),
+                    Some(conn) => {
+                        let gateway_addr = conn.subnet().gateway_addr();
+                        let children = self
+                            .track(&conn, conn.manager().list_child_subnets(gateway_addr).await)?;
+                        Ok(Some(children))
+                    }
+                }
+            },
+        )
+        .await?;
+
+        Ok(nodes)
     }
 
     /// Funds an account in a child subnet, if `to` is `None`, the self account
@@ -432,8 +697,10 @@ impl IpcProvider {
         from: Option<Address>,
         to: Option<Address>,
         amount: TokenAmount,
-    ) -> anyhow::Result<ChainEpoch> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<ChainEpoch, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
@@ -444,9 +711,12 @@ impl IpcProvider {
             Some(addr) => addr,
         };
 
-        conn.manager()
-            .fund(subnet, gateway_addr, sender, to.unwrap_or(sender), amount)
-            .await
+        self.track(
+            &conn,
+            conn.manager()
+                .fund(subnet, gateway_addr, sender, to.unwrap_or(sender), amount)
+                .await,
+        )
     }
 
     /// Funds an account in a child subnet with erc20 token, provided that the supply source kind is
@@ -458,16 +728,21 @@ impl IpcProvider {
         from: Option<Address>,
         to: Option<Address>,
         amount: TokenAmount,
-    ) -> anyhow::Result<ChainEpoch> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<ChainEpoch, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager()
-            .fund_with_token(subnet, sender, to.unwrap_or(sender), amount)
-            .await
+        self.track(
+            &conn,
+            conn.manager()
+                .fund_with_token(subnet, sender, to.unwrap_or(sender), amount)
+                .await,
+        )
     }
 
     /// Approve an erc20 token for transfer by the gateway. Can be used in preparation for fund_with_token.
@@ -478,17 +753,19 @@ impl IpcProvider {
         subnet: SubnetID,
         from: Option<Address>,
         amount: TokenAmount,
-    ) -> anyhow::Result<ChainEpoch> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
-        let conn = match self.connection(&parent) {
-            None => return Err(anyhow!("target parent subnet not found")),
-            Some(conn) => conn,
-        };
+    ) -> Result<ChainEpoch, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
+        let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().approve_token(subnet, sender, amount).await
+        self.track(
+            &conn,
+            conn.manager().approve_token(subnet, sender, amount).await,
+        )
     }
 
     /// Release to an account in a child subnet, if `to` is `None`, the self account
@@ -500,11 +777,8 @@ impl IpcProvider {
         from: Option<Address>,
         to: Option<Address>,
         amount: TokenAmount,
-    ) -> anyhow::Result<ChainEpoch> {
-        let conn = match self.connection(&subnet) {
-            None => return Err(anyhow!("target subnet not found: {subnet}")),
-            Some(conn) => conn,
-        };
+    ) -> Result<ChainEpoch, ProviderError> {
+        let conn = self.get_connection(&subnet)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
@@ -514,9 +788,12 @@ impl IpcProvider {
             Some(addr) => addr,
         };
 
-        conn.manager()
-            .release(gateway_addr, sender, to.unwrap_or(sender), amount)
-            .await
+        self.track(
+            &conn,
+            conn.manager()
+                .release(gateway_addr, sender, to.unwrap_or(sender), amount)
+                .await,
+        )
     }
 
     /// Propagate a cross-net message forward. For `postbox_msg_key`, we are using bytes because different
@@ -528,7 +805,7 @@ impl IpcProvider {
         _gateway_addr: Address,
         _from: Address,
         _postbox_msg_key: Vec<u8>,
-    ) -> anyhow::Result<()> {
+    ) -> Result<(), ProviderError> {
         todo!()
     }
 
@@ -539,7 +816,7 @@ impl IpcProvider {
         from: Option<Address>,
         to: Address,
         amount: TokenAmount,
-    ) -> anyhow::Result<()> {
+    ) -> Result<(), ProviderError> {
         let conn = self.get_connection(subnet)?;
 
         let subnet_config = conn.subnet();
@@ -558,7 +835,7 @@ impl IpcProvider {
         //     }
         // };
 
-        conn.manager().send_value(sender, to, amount).await
+        self.track(&conn, conn.manager().send_value(sender, to, amount).await)
     }
 
     /// Get the balance of an address
@@ -566,23 +843,25 @@ impl IpcProvider {
         &self,
         subnet: &SubnetID,
         address: &Address,
-    ) -> anyhow::Result<TokenAmount> {
+    ) -> Result<TokenAmount, ProviderError> {
         let conn = self.get_connection(subnet)?;
 
-        conn.manager().wallet_balance(address).await
+        self.track(&conn, conn.manager().wallet_balance(address).await)
     }
 
-    pub async fn chain_head(&self, subnet: &SubnetID) -> anyhow::Result<ChainEpoch> {
+    pub async fn chain_head(&self, subnet: &SubnetID) -> Result<ChainEpoch, ProviderError> {
         let conn = self.get_connection(subnet)?;
 
-        conn.manager().chain_head_height().await
+        self.track(&conn, conn.manager().chain_head_height().await)
     }
 
     /// Obtain the genesis epoch of the input subnet.
-    pub async fn genesis_epoch(&self, subnet: &SubnetID) -> anyhow::Result<ChainEpoch> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    pub async fn genesis_epoch(&self, subnet: &SubnetID) -> Result<ChainEpoch, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
-        conn.manager().genesis_epoch(subnet).await
+        self.track(&conn, conn.manager().genesis_epoch(subnet).await)
     }
 
     /// Get the validator information.
@@ -590,11 +869,39 @@ impl IpcProvider {
         &self,
         subnet: &SubnetID,
         validator: &Address,
-    ) -> anyhow::Result<ValidatorInfo> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<ValidatorInfo, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
-        conn.manager().get_validator_info(subnet, validator).await
+        self.track(
+            &conn,
+            conn.manager().get_validator_info(subnet, validator).await,
+        )
+    }
+
+    /// Lists all the validators of a subnet, active and waiting, with their staking information.
+    pub async fn list_validators(
+        &self,
+        subnet: &SubnetID,
+    ) -> Result<ValidatorSetInfo, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
+        let conn = self.get_connection(&parent)?;
+
+        self.track(&conn, conn.manager().list_validators(subnet).await)
+    }
+
+    /// Checks whether a subnet exists in its parent and is running, e.g. before funding or joining it.
+    pub async fn subnet_status(&self, subnet: &SubnetID) -> Result<SubnetStatus, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
+        let conn = self.get_connection(&parent)?;
+
+        self.track(&conn, conn.manager().subnet_status(subnet).await)
     }
 
     /// Get the changes in subnet validators. This is fetched from parent.
@@ -602,93 +909,136 @@ impl IpcProvider {
         &self,
         subnet: &SubnetID,
         epoch: ChainEpoch,
-    ) -> anyhow::Result<TopDownQueryPayload<Vec<StakingChangeRequest>>> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<TopDownQueryPayload<Vec<StakingChangeRequest>>, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
-        conn.manager().get_validator_changeset(subnet, epoch).await
+        self.track(
+            &conn,
+            conn.manager().get_validator_changeset(subnet, epoch).await,
+        )
     }
 
     /// Get genesis info for a child subnet. This can be used to deterministically
-    /// generate the genesis of the subnet
-    pub async fn get_genesis_info(&self, subnet: &SubnetID) -> anyhow::Result<SubnetGenesisInfo> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    /// generate the genesis of the subnet, pinning the parent `height` to query at.
+    pub async fn get_genesis_info(
+        &self,
+        subnet: &SubnetID,
+        height: Option<ChainEpoch>,
+    ) -> Result<SubnetGenesisInfo, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
-        conn.manager().get_genesis_info(subnet).await
+        self.track(&conn, conn.manager().get_genesis_info(subnet, height).await)
     }
 
     pub async fn get_top_down_msgs(
         &self,
         subnet: &SubnetID,
         epoch: ChainEpoch,
-    ) -> anyhow::Result<TopDownQueryPayload<Vec<IpcEnvelope>>> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<TopDownQueryPayload<Vec<IpcEnvelope>>, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
-        conn.manager().get_top_down_msgs(subnet, epoch).await
+        self.track(&conn, conn.manager().get_top_down_msgs(subnet, epoch).await)
     }
 
     pub async fn get_block_hash(
         &self,
         subnet: &SubnetID,
         height: ChainEpoch,
-    ) -> anyhow::Result<GetBlockHashResult> {
+    ) -> Result<GetBlockHashResult, ProviderError> {
         let conn = self.get_connection(subnet)?;
 
-        conn.manager().get_block_hash(height).await
+        self.track(&conn, conn.manager().get_block_hash(height).await)
     }
 
-    pub async fn get_chain_id(&self, subnet: &SubnetID) -> anyhow::Result<String> {
+    pub async fn get_chain_id(&self, subnet: &SubnetID) -> Result<String, ProviderError> {
         let conn = self.get_connection(subnet)?;
 
-        conn.manager().get_chain_id().await
+        self.track(&conn, conn.manager().get_chain_id().await)
     }
 
-    pub async fn get_commit_sha(&self, subnet: &SubnetID) -> anyhow::Result<[u8; 32]> {
+    pub async fn get_commit_sha(&self, subnet: &SubnetID) -> Result<[u8; 32], ProviderError> {
         let conn = self.get_connection(subnet)?;
 
-        conn.manager().get_commit_sha().await
+        self.track(&conn, conn.manager().get_commit_sha().await)
     }
 
-    pub async fn get_chain_head_height(&self, subnet: &SubnetID) -> anyhow::Result<ChainEpoch> {
+    pub async fn get_chain_head_height(
+        &self,
+        subnet: &SubnetID,
+    ) -> Result<ChainEpoch, ProviderError> {
         let conn = self.get_connection(subnet)?;
 
-        conn.manager().chain_head_height().await
+        self.track(&conn, conn.manager().chain_head_height().await)
     }
 
     pub async fn get_bottom_up_bundle(
         &self,
         subnet: &SubnetID,
         height: ChainEpoch,
-    ) -> anyhow::Result<Option<BottomUpCheckpointBundle>> {
-        let conn = match self.connection(subnet) {
-            None => return Err(anyhow!("target subnet not found")),
-            Some(conn) => conn,
-        };
+    ) -> Result<Option<BottomUpCheckpointBundle>, ProviderError> {
+        let conn = self.get_connection(subnet)?;
+
+        self.track(&conn, conn.manager().checkpoint_bundle_at(height).await)
+    }
+
+    /// Get the active validators of the subnet with their power, as the parent sees them when
+    /// checking the signatures of bottom-up checkpoints.
+    pub async fn checkpoint_signers(
+        &self,
+        subnet: &SubnetID,
+    ) -> Result<CheckpointSigners, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
+        let conn = self.get_connection(&parent)?;
 
-        conn.manager().checkpoint_bundle_at(height).await
+        self.track(&conn, conn.manager().checkpoint_signers(subnet).await)
     }
 
     pub async fn last_bottom_up_checkpoint_height(
         &self,
         subnet: &SubnetID,
-    ) -> anyhow::Result<ChainEpoch> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<ChainEpoch, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
-        conn.manager()
-            .last_bottom_up_checkpoint_height(subnet)
-            .await
+        self.track(
+            &conn,
+            conn.manager()
+                .last_bottom_up_checkpoint_height(subnet)
+                .await,
+        )
     }
 
     pub async fn quorum_reached_events(
         &self,
         subnet: &SubnetID,
         height: ChainEpoch,
-    ) -> anyhow::Result<Vec<QuorumReachedEvent>> {
+    ) -> Result<Vec<QuorumReachedEvent>, ProviderError> {
+        let conn = self.get_connection(subnet)?;
+
+        self.track(&conn, conn.manager().quorum_reached_events(height).await)
+    }
+
+    /// Streams the quorum reached events of the subnet starting at `from_height`.
+    pub fn subscribe_quorum_events(
+        &self,
+        subnet: &SubnetID,
+        from_height: ChainEpoch,
+    ) -> Result<BoxStream<'static, anyhow::Result<QuorumReachedEvent>>, ProviderError> {
         let conn = self.get_connection(subnet)?;
 
-        conn.manager().quorum_reached_events(height).await
+        Ok(conn.manager.clone().subscribe_quorum_events(from_height))
     }
 
     /// Advertises the endpoint of a bootstrap node for the subnet.
@@ -697,31 +1047,91 @@ impl IpcProvider {
         subnet: &SubnetID,
         from: Option<Address>,
         endpoint: String,
-    ) -> anyhow::Result<()> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<(), ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager()
-            .add_bootstrap(subnet, &sender, endpoint)
-            .await
+        self.track(
+            &conn,
+            conn.manager()
+                .add_bootstrap(subnet, &sender, endpoint)
+                .await,
+        )
+    }
+
+    /// Removes the endpoint of a bootstrap node previously advertised for the subnet.
+    pub async fn remove_bootstrap(
+        &mut self,
+        subnet: &SubnetID,
+        from: Option<Address>,
+        endpoint: String,
+    ) -> Result<(), ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
+        let conn = self.get_connection(&parent)?;
+
+        let subnet_config = conn.subnet();
+        let sender = self.check_sender(subnet_config, from)?;
+
+        self.track(
+            &conn,
+            conn.manager()
+                .remove_bootstrap(subnet, &sender, endpoint)
+                .await,
+        )
     }
 
     /// Lists the bootstrap nodes of a subnet
-    pub async fn list_bootstrap_nodes(&self, subnet: &SubnetID) -> anyhow::Result<Vec<String>> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    pub async fn list_bootstrap_nodes(
+        &self,
+        subnet: &SubnetID,
+    ) -> Result<Vec<String>, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
 
-        conn.manager().list_bootstrap_nodes(subnet).await
+        self.track(&conn, conn.manager().list_bootstrap_nodes(subnet).await)
     }
 
     /// Returns the latest finality from the parent committed in a child subnet.
-    pub async fn latest_parent_finality(&self, subnet: &SubnetID) -> anyhow::Result<ChainEpoch> {
+    pub async fn latest_parent_finality(
+        &self,
+        subnet: &SubnetID,
+    ) -> Result<ChainEpoch, ProviderError> {
         let conn = self.get_connection(subnet)?;
 
-        conn.manager().latest_parent_finality().await
+        self.track(&conn, conn.manager().latest_parent_finality().await)
+    }
+
+    /// Finds out whether a cross-net message between `subnet` and its parent has been executed
+    /// in its destination, e.g. after a `fund` or a `release`.
+    pub async fn cross_msg_status(
+        &self,
+        subnet: &SubnetID,
+        direction: CrossMsgDirection,
+        msg: CrossMsgRef,
+    ) -> Result<CrossMsgStatus, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
+        let parent_conn = self.get_connection(&parent)?;
+        let child_conn = self.get_connection(subnet)?;
+
+        Ok(crossmsg::cross_msg_status(
+            parent_conn.manager(),
+            child_conn.manager(),
+            subnet,
+            direction,
+            msg,
+        )
+        .await?)
     }
 
     pub async fn set_federated_power(
@@ -731,12 +1141,29 @@ impl IpcProvider {
         validators: &[Address],
         public_keys: &[Vec<u8>],
         federated_power: &[u128],
-    ) -> anyhow::Result<ChainEpoch> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+    ) -> Result<ChainEpoch, ProviderError> {
+        let parent = subnet
+            .parent()
+            .ok_or_else(|| ProviderError::MissingParent(subnet.clone()))?;
         let conn = self.get_connection(&parent)?;
-        conn.manager()
-            .set_federated_power(from, subnet, validators, public_keys, federated_power)
-            .await
+        self.track(
+            &conn,
+            conn.manager()
+                .set_federated_power(from, subnet, validators, public_keys, federated_power)
+                .await,
+        )
+    }
+
+    /// Simulates a state changing operation in the `subnet` it would be sent to, without sending
+    /// it, e.g. the parent for `JoinSubnet` or the child for `Release`. Returns the estimated gas
+    /// or the decoded reason why the transaction would revert.
+    pub async fn estimate(
+        &self,
+        subnet: &SubnetID,
+        operation: SubnetOperation,
+    ) -> Result<CallEstimate, ProviderError> {
+        let conn = self.get_connection(subnet)?;
+        self.track(&conn, conn.manager().estimate(operation).await)
     }
 }
 
@@ -769,59 +1196,64 @@ impl Drop for LotusJsonKeyType {
 // This should become its own module within the provider, we should have different
 // categories for each group of commands
 impl IpcProvider {
-    pub fn new_fvm_key(&self, tp: WalletKeyType) -> anyhow::Result<Address> {
+    pub fn new_fvm_key(&self, tp: WalletKeyType) -> Result<Address, ProviderError> {
         let tp = match tp {
             WalletKeyType::BLS => SignatureType::BLS,
             WalletKeyType::Secp256k1 => SignatureType::Secp256k1,
-            WalletKeyType::Secp256k1Ledger => return Err(anyhow!("ledger key type not supported")),
+            WalletKeyType::Secp256k1Ledger => {
+                return Err(ProviderError::Other(anyhow!(
+                    "ledger key type not supported"
+                )))
+            }
         };
 
-        self.fvm_wallet()?.write().unwrap().generate_addr(tp)
+        Ok(self.fvm_wallet()?.write().unwrap().generate_addr(tp)?)
     }
 
-    pub fn new_evm_key(&self) -> anyhow::Result<EthKeyAddress> {
+    pub fn new_evm_key(&self) -> Result<EthKeyAddress, ProviderError> {
         let key_info = ipc_wallet::random_eth_key_info();
         let wallet = self.evm_wallet()?;
 
-        let out = wallet.write().unwrap().put(key_info);
-        out
+        let out = wallet.write().unwrap().put(key_info)?;
+        Ok(out)
     }
 
-    pub fn import_fvm_key(&self, keyinfo: &str) -> anyhow::Result<Address> {
+    pub fn import_fvm_key(&self, keyinfo: &str) -> Result<Address, ProviderError> {
         let wallet = self.fvm_wallet()?;
         let mut wallet = wallet.write().unwrap();
         let keyinfo = LotusJsonKeyType::from_str(keyinfo)?;
 
-        let key_type = if WalletKeyType::from_str(&keyinfo.r#type)? == WalletKeyType::BLS {
+        let key_type = WalletKeyType::from_str(&keyinfo.r#type).map_err(anyhow::Error::from)?;
+        let key_type = if key_type == WalletKeyType::BLS {
             SignatureType::BLS
         } else {
             SignatureType::Secp256k1
         };
 
-        let key_info = ipc_wallet::json::KeyInfoJson(ipc_wallet::KeyInfo::new(
-            key_type,
-            base64::engine::general_purpose::STANDARD.decode(&keyinfo.private_key)?,
-        ));
+        let private_key = base64::engine::general_purpose::STANDARD
+            .decode(&keyinfo.private_key)
+            .map_err(anyhow::Error::from)?;
+        let key_info =
+            ipc_wallet::json::KeyInfoJson(ipc_wallet::KeyInfo::new(key_type, private_key));
         let key_info = ipc_wallet::KeyInfo::from(key_info);
-        Ok(wallet.import(key_info)?)
+        Ok(wallet.import(key_info).map_err(anyhow::Error::from)?)
     }
 
-    pub fn import_evm_key_from_privkey(&self, private_key: &str) -> anyhow::Result<EthKeyAddress> {
+    pub fn import_evm_key_from_privkey(
+        &self,
+        private_key: &str,
+    ) -> Result<EthKeyAddress, ProviderError> {
         let keystore = self.evm_wallet()?;
         let mut keystore = keystore.write().unwrap();
 
-        let private_key = if !private_key.starts_with("0x") {
-            hex::decode(private_key)?
-        } else {
-            hex::decode(&private_key[2..])?
-        };
-        keystore.put(ipc_wallet::EvmKeyInfo::new(private_key))
+        let private_key = private_key.strip_prefix("0x").unwrap_or(private_key);
+        let private_key = hex::decode(private_key).map_err(anyhow::Error::from)?;
+        Ok(keystore.put(ipc_wallet::EvmKeyInfo::new(private_key))?)
     }
 
-    pub fn import_evm_key_from_json(&self, keyinfo: &str) -> anyhow::Result<EthKeyAddress> {
+    pub fn import_evm_key_from_json(&self, keyinfo: &str) -> Result<EthKeyAddress, ProviderError> {
         let persisted: ipc_wallet::PersistentKeyInfo = serde_json::from_str(keyinfo)?;
-        let persisted: String = persisted.private_key().parse()?;
-        self.import_evm_key_from_privkey(&persisted)
+        self.import_evm_key_from_privkey(persisted.private_key())
     }
 }
 
@@ -838,21 +1270,32 @@ fn new_fvm_wallet_from_config(config: Arc<Config>) -> anyhow::Result<KeyStore> {
 
 pub fn new_evm_keystore_from_config(
     config: Arc<Config>,
+    password: Option<&str>,
 ) -> anyhow::Result<PersistentKeyStore<EthKeyAddress>> {
     let repo_str = &config.keystore_path;
     if let Some(repo_str) = repo_str {
-        new_evm_keystore_from_path(repo_str)
+        new_evm_keystore_from_path(repo_str, password)
     } else {
         Err(anyhow!("No keystore repo found in config"))
     }
 }
 
+/// Opens the evm keystore in the repo, which is expected to be encrypted if a `password` is given.
 pub fn new_evm_keystore_from_path(
     repo_str: &str,
+    password: Option<&str>,
 ) -> anyhow::Result<PersistentKeyStore<EthKeyAddress>> {
-    let repo = Path::new(&repo_str).join(ipc_wallet::DEFAULT_KEYSTORE_NAME);
-    let repo = expand_tilde(repo);
-    PersistentKeyStore::new(repo).map_err(|e| anyhow!("Failed to create evm keystore: {}", e))
+    let repo = evm_keystore_path(repo_str);
+    match password {
+        Some(password) => PersistentKeyStore::new_encrypted(repo, password),
+        None => PersistentKeyStore::new(repo),
+    }
+    .map_err(|e| anyhow!("Failed to create evm keystore: {}", e))
+}
+
+/// The path of the evm keystore file in the repo.
+pub fn evm_keystore_path(repo_str: &str) -> PathBuf {
+    expand_tilde(Path::new(repo_str).join(ipc_wallet::DEFAULT_KEYSTORE_NAME))
 }
 
 pub fn new_fvm_keystore_from_path(repo_str: &str) -> anyhow::Result<KeyStore> {
@@ -6,14 +6,16 @@ use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
-use ethers_contract::{ContractError, EthLogDecode, LogMeta};
+use ethers_contract::{ContractCall, ContractError, EthLogDecode, LogMeta};
 use ipc_actors_abis::{
     checkpointing_facet, gateway_getter_facet, gateway_manager_facet, gateway_messenger_facet,
     lib_gateway, lib_quorum, lib_staking_change_log, register_subnet_facet,
     subnet_actor_checkpointing_facet, subnet_actor_getter_facet, subnet_actor_manager_facet,
     subnet_actor_reward_facet,
 };
-use ipc_api::evm::{fil_to_eth_amount, payload_to_evm_address, subnet_id_to_evm_addresses};
+use ipc_api::evm::{
+    decode_revert_reason, fil_to_eth_amount, payload_to_evm_address, subnet_id_to_evm_addresses,
+};
 use ipc_api::validator::from_contract_validators;
 use reqwest::header::HeaderValue;
 use reqwest::Client;
@@ -26,34 +28,44 @@ use crate::config::subnet::SubnetConfig;
 use crate::config::Subnet;
 use crate::lotus::message::ipc::SubnetInfo;
 use crate::manager::subnet::{
-    BottomUpCheckpointRelayer, GetBlockHashResult, SubnetGenesisInfo, TopDownFinalityQuery,
-    TopDownQueryPayload,
+    BottomUpCheckpointRelayer, CallEstimate, CheckpointSigners, CheckpointSubmission,
+    CrossMsgQuery, GetBlockHashResult, PendingSubmission, SubnetGenesisInfo, SubnetOperation,
+    SubnetStatus, TopDownFinalityQuery, TopDownQueryPayload,
+};
+use crate::manager::{
+    quorum_event_stream, quorum_event_stream_with_heads, EthManager, SubnetManager,
+    DEFAULT_QUORUM_POLL_INTERVAL,
 };
-use crate::manager::{EthManager, SubnetManager};
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
-use ethers::abi::Tokenizable;
+use ethers::abi::{Detokenize, Tokenizable};
 use ethers::contract::abigen;
-use ethers::prelude::k256::ecdsa::SigningKey;
-use ethers::prelude::{Signer, SignerMiddleware};
-use ethers::providers::{Authorization, Http, Middleware, Provider};
-use ethers::signers::{LocalWallet, Wallet};
-use ethers::types::{BlockId, Eip1559TransactionRequest, ValueOrArray, I256, U256};
+use ethers::prelude::SignerMiddleware;
+use ethers::providers::{
+    Authorization, ConnectionDetails, Http, JsonRpcClient, Middleware, Provider, PubsubClient, Ws,
+};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{BlockId, BlockNumber, Eip1559TransactionRequest, ValueOrArray, I256, U256};
 
+use futures_util::future::try_join_all;
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::{address::Address, econ::TokenAmount};
 use ipc_api::checkpoint::{
     BottomUpCheckpoint, BottomUpCheckpointBundle, QuorumReachedEvent, Signature,
 };
 use ipc_api::cross::IpcEnvelope;
-use ipc_api::staking::{StakingChangeRequest, ValidatorInfo, ValidatorStakingInfo};
+use ipc_api::staking::{
+    StakingChangeRequest, ValidatorInfo, ValidatorSetInfo, ValidatorStakingInfo,
+};
 use ipc_api::subnet::ConstructParams;
 use ipc_api::subnet_id::SubnetID;
-use ipc_wallet::{EthKeyAddress, EvmKeyStore, PersistentKeyStore};
+use ipc_wallet::{EthKeyAddress, PersistentKeyStore, SignerBackend, SignerSelector};
 use num_traits::ToPrimitive;
 use std::result;
 
-pub type DefaultSignerMiddleware = SignerMiddleware<Provider<Http>, Wallet<SigningKey>>;
+pub type DefaultSignerMiddleware = SignerMiddleware<Provider<Http>, SignerBackend>;
 
 /// Default polling time used by the Ethers provider to check for pending
 /// transactions and events. Default is 7, and for our child subnets we
@@ -70,9 +82,12 @@ const TRANSACTION_RECEIPT_RETRIES: usize = 200;
 
 /// The majority vote percentage for checkpoint submission when creating a subnet.
 const SUBNET_MAJORITY_PERCENTAGE: u8 = 67;
+/// How many times to try to reconnect a dropped WebSocket before giving up on it.
+const WS_RECONNECTS: usize = 5;
 
 pub struct EthSubnetManager {
     keystore: Option<Arc<RwLock<PersistentKeyStore<EthKeyAddress>>>>,
+    signers: SignerSelector,
     ipc_contract_info: IPCContractInfo,
 }
 
@@ -82,6 +97,15 @@ struct IPCContractInfo {
     registry_addr: ethers::types::Address,
     chain_id: u64,
     provider: Provider<Http>,
+    /// Optional WebSocket endpoint to subscribe to new blocks instead of polling.
+    ws_endpoint: Option<WsEndpoint>,
+}
+
+/// Where to open WebSocket subscriptions to the same node as the HTTP provider.
+#[derive(Clone)]
+struct WsEndpoint {
+    url: url::Url,
+    auth_token: Option<String>,
 }
 
 //TODO receive clarity on this implementation
@@ -97,20 +121,7 @@ abigen!(
 #[async_trait]
 impl TopDownFinalityQuery for EthSubnetManager {
     async fn genesis_epoch(&self, subnet_id: &SubnetID) -> Result<ChainEpoch> {
-        let address = contract_address_from_subnet(subnet_id)?;
-        tracing::info!("querying genesis epoch in evm subnet contract: {address:}");
-
-        let evm_subnet_id = gateway_getter_facet::SubnetID::try_from(subnet_id)?;
-
-        let contract = gateway_getter_facet::GatewayGetterFacet::new(
-            self.ipc_contract_info.gateway_addr,
-            Arc::new(self.ipc_contract_info.provider.clone()),
-        );
-        let (exists, subnet) = contract.get_subnet(evm_subnet_id).call().await?;
-        if !exists {
-            return Err(anyhow!("subnet: {} does not exists", subnet_id));
-        }
-        Ok(subnet.genesis_epoch.as_u64() as ChainEpoch)
+        self.genesis_epoch_at(subnet_id, None).await
     }
 
     async fn chain_head_height(&self) -> Result<ChainEpoch> {
@@ -248,47 +259,9 @@ impl TopDownFinalityQuery for EthSubnetManager {
 #[async_trait]
 impl SubnetManager for EthSubnetManager {
     async fn create_subnet(&self, from: Address, params: ConstructParams) -> Result<Address> {
-        self.ensure_same_gateway(&params.ipc_gateway_addr)?;
-
-        let min_validator_stake = params
-            .min_validator_stake
-            .atto()
-            .to_u128()
-            .ok_or_else(|| anyhow!("invalid min validator stake"))?;
-
-        tracing::debug!("calling create subnet for EVM manager");
-
-        let route = subnet_id_to_evm_addresses(&params.parent)?;
-        tracing::debug!("root SubnetID as Ethereum type: {route:?}");
-
-        let params = register_subnet_facet::ConstructorParams {
-            parent_id: register_subnet_facet::SubnetID {
-                root: params.parent.root_id(),
-                route,
-            },
-            ipc_gateway_addr: self.ipc_contract_info.gateway_addr,
-            consensus: params.consensus as u64 as u8,
-            min_activation_collateral: ethers::types::U256::from(min_validator_stake),
-            min_validators: params.min_validators,
-            bottom_up_check_period: params.bottomup_check_period as u64,
-            majority_percentage: SUBNET_MAJORITY_PERCENTAGE,
-            active_validators_limit: params.active_validators_limit,
-            power_scale: 3,
-            permission_mode: params.permission_mode as u8,
-            supply_source: register_subnet_facet::SupplySource::try_from(params.supply_source)?,
-        };
-
-        tracing::info!("creating subnet on evm with params: {params:?}");
-
-        let signer = self.get_signer(&from)?;
-        let signer = Arc::new(signer);
-        let registry_contract = register_subnet_facet::RegisterSubnetFacet::new(
-            self.ipc_contract_info.registry_addr,
-            signer.clone(),
-        );
-
-        let call = call_with_premium_estimation(signer, registry_contract.new_subnet_actor(params))
-            .await?;
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let call = self.create_subnet_call(signer.clone(), params)?;
+        let call = call_with_premium_estimation(signer, call).await?;
         // TODO: Edit call to get estimate premium
         let pending_tx = call.send().await?;
         // We need the retry to parse the deployment event. At the time of this writing, it's a bug
@@ -329,22 +302,8 @@ impl SubnetManager for EthSubnetManager {
         collateral: TokenAmount,
         pub_key: Vec<u8>,
     ) -> Result<ChainEpoch> {
-        let collateral = collateral
-            .atto()
-            .to_u128()
-            .ok_or_else(|| anyhow!("invalid min validator stake"))?;
-
-        let address = contract_address_from_subnet(&subnet)?;
-        tracing::info!(
-            "interacting with evm subnet contract: {address:} with collateral: {collateral:}"
-        );
-
-        let signer = Arc::new(self.get_signer(&from)?);
-        let contract =
-            subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
-
-        let mut txn = contract.join(ethers::types::Bytes::from(pub_key));
-        txn.tx.set_value(collateral);
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let txn = self.join_subnet_call(signer.clone(), &subnet, collateral, pub_key)?;
         let txn = call_with_premium_estimation(signer, txn).await?;
 
         // Use the pending state to get the nonce because there could have been a pre-fund. Best would be to use this for everything.
@@ -356,20 +315,8 @@ impl SubnetManager for EthSubnetManager {
     }
 
     async fn pre_fund(&self, subnet: SubnetID, from: Address, balance: TokenAmount) -> Result<()> {
-        let balance = balance
-            .atto()
-            .to_u128()
-            .ok_or_else(|| anyhow!("invalid initial balance"))?;
-
-        let address = contract_address_from_subnet(&subnet)?;
-        tracing::info!("interacting with evm subnet contract: {address:} with balance: {balance:}");
-
-        let signer = Arc::new(self.get_signer(&from)?);
-        let contract =
-            subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
-
-        let mut txn = contract.pre_fund();
-        txn.tx.set_value(balance);
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let txn = self.pre_fund_call(signer.clone(), &subnet, balance)?;
         let txn = call_with_premium_estimation(signer, txn).await?;
 
         txn.send().await?;
@@ -382,19 +329,10 @@ impl SubnetManager for EthSubnetManager {
         from: Address,
         amount: TokenAmount,
     ) -> Result<()> {
-        let address = contract_address_from_subnet(&subnet)?;
-        tracing::info!("pre-release funds from {subnet:} at contract: {address:}");
-
-        let amount = amount
-            .atto()
-            .to_u128()
-            .ok_or_else(|| anyhow!("invalid pre-release amount"))?;
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let txn = self.pre_release_call(signer.clone(), &subnet, amount)?;
 
-        let signer = Arc::new(self.get_signer(&from)?);
-        let contract =
-            subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
-
-        call_with_premium_estimation(signer, contract.pre_release(amount.into()))
+        call_with_premium_estimation(signer, txn)
             .await?
             .send()
             .await?
@@ -404,22 +342,8 @@ impl SubnetManager for EthSubnetManager {
     }
 
     async fn stake(&self, subnet: SubnetID, from: Address, collateral: TokenAmount) -> Result<()> {
-        let collateral = collateral
-            .atto()
-            .to_u128()
-            .ok_or_else(|| anyhow!("invalid collateral amount"))?;
-
-        let address = contract_address_from_subnet(&subnet)?;
-        tracing::info!(
-            "interacting with evm subnet contract: {address:} with collateral: {collateral:}"
-        );
-
-        let signer = Arc::new(self.get_signer(&from)?);
-        let contract =
-            subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
-
-        let mut txn = contract.stake();
-        txn.tx.set_value(collateral);
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let txn = self.stake_call(signer.clone(), &subnet, collateral)?;
         let txn = call_with_premium_estimation(signer, txn).await?;
 
         txn.send().await?.await?;
@@ -433,35 +357,19 @@ impl SubnetManager for EthSubnetManager {
         from: Address,
         collateral: TokenAmount,
     ) -> Result<()> {
-        let collateral = collateral
-            .atto()
-            .to_u128()
-            .ok_or_else(|| anyhow!("invalid collateral amount"))?;
-
-        let address = contract_address_from_subnet(&subnet)?;
-        tracing::info!(
-            "interacting with evm subnet contract: {address:} with collateral: {collateral:}"
-        );
-
-        let signer = Arc::new(self.get_signer(&from)?);
-        let contract =
-            subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
-
-        let txn = call_with_premium_estimation(signer, contract.unstake(collateral.into())).await?;
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let txn = self.unstake_call(signer.clone(), &subnet, collateral)?;
+        let txn = call_with_premium_estimation(signer, txn).await?;
         txn.send().await?.await?;
 
         Ok(())
     }
 
     async fn leave_subnet(&self, subnet: SubnetID, from: Address) -> Result<()> {
-        let address = contract_address_from_subnet(&subnet)?;
-        tracing::info!("leaving evm subnet: {subnet:} at contract: {address:}");
-
-        let signer = Arc::new(self.get_signer(&from)?);
-        let contract =
-            subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let txn = self.leave_subnet_call(signer.clone(), &subnet)?;
 
-        call_with_premium_estimation(signer, contract.leave())
+        call_with_premium_estimation(signer, txn)
             .await?
             .send()
             .await?
@@ -471,14 +379,10 @@ impl SubnetManager for EthSubnetManager {
     }
 
     async fn kill_subnet(&self, subnet: SubnetID, from: Address) -> Result<()> {
-        let address = contract_address_from_subnet(&subnet)?;
-        tracing::info!("kill evm subnet: {subnet:} at contract: {address:}");
-
-        let signer = Arc::new(self.get_signer(&from)?);
-        let contract =
-            subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let txn = self.kill_subnet_call(signer.clone(), &subnet)?;
 
-        call_with_premium_estimation(signer, contract.kill())
+        call_with_premium_estimation(signer, txn)
             .await?
             .send()
             .await?
@@ -512,14 +416,10 @@ impl SubnetManager for EthSubnetManager {
     }
 
     async fn claim_collateral(&self, subnet: SubnetID, from: Address) -> Result<()> {
-        let address = contract_address_from_subnet(&subnet)?;
-        tracing::info!("claim collateral evm subnet: {subnet:} at contract: {address:}");
-
-        let signer = Arc::new(self.get_signer(&from)?);
-        let contract =
-            subnet_actor_reward_facet::SubnetActorRewardFacet::new(address, signer.clone());
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let txn = self.claim_collateral_call(signer.clone(), &subnet)?;
 
-        call_with_premium_estimation(signer, contract.claim())
+        call_with_premium_estimation(signer, txn)
             .await?
             .send()
             .await?
@@ -536,29 +436,8 @@ impl SubnetManager for EthSubnetManager {
         to: Address,
         amount: TokenAmount,
     ) -> Result<ChainEpoch> {
-        self.ensure_same_gateway(&gateway_addr)?;
-
-        let value = amount
-            .atto()
-            .to_u128()
-            .ok_or_else(|| anyhow!("invalid value to fund"))?;
-
-        tracing::info!("fund with evm gateway contract: {gateway_addr:} with value: {value:}, original: {amount:?}");
-
-        let evm_subnet_id = gateway_manager_facet::SubnetID::try_from(&subnet)?;
-        tracing::debug!("evm subnet id to fund: {evm_subnet_id:?}");
-
-        let signer = Arc::new(self.get_signer(&from)?);
-        let gateway_contract = gateway_manager_facet::GatewayManagerFacet::new(
-            self.ipc_contract_info.gateway_addr,
-            signer.clone(),
-        );
-
-        let mut txn = gateway_contract.fund(
-            evm_subnet_id,
-            gateway_manager_facet::FvmAddress::try_from(to)?,
-        );
-        txn.tx.set_value(value);
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let txn = self.fund_call(signer.clone(), &subnet, &gateway_addr, to, amount)?;
         let txn = call_with_premium_estimation(signer, txn).await?;
 
         let pending_tx = txn.send().await?;
@@ -575,18 +454,10 @@ impl SubnetManager for EthSubnetManager {
     ) -> Result<ChainEpoch> {
         log::debug!("approve token, subnet: {subnet}, amount: {amount}, from: {from}");
 
-        let value = fil_amount_to_eth_amount(&amount)?;
-
-        let signer = Arc::new(self.get_signer(&from)?);
-
-        let subnet_supply_source = self.get_subnet_supply_source(&subnet).await?;
-        if subnet_supply_source.kind != SupplyKind::ERC20 as u8 {
-            return Err(anyhow!("Invalid operation: Expected the subnet's supply source to be ERC20, but found a different kind."));
-        }
-
-        let token_contract = IERC20::new(subnet_supply_source.token_address, signer.clone());
-
-        let txn = token_contract.approve(self.ipc_contract_info.gateway_addr, value);
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let txn = self
+            .approve_token_call(signer.clone(), &subnet, amount)
+            .await?;
         let txn = call_with_premium_estimation(signer, txn).await?;
 
         let pending_tx = txn.send().await?;
@@ -605,20 +476,8 @@ impl SubnetManager for EthSubnetManager {
             "fund with token, subnet: {subnet}, amount: {amount}, from: {from}, to: {to}"
         );
 
-        let value = fil_amount_to_eth_amount(&amount)?;
-        let evm_subnet_id = gateway_manager_facet::SubnetID::try_from(&subnet)?;
-
-        let signer = Arc::new(self.get_signer(&from)?);
-        let gateway_contract = gateway_manager_facet::GatewayManagerFacet::new(
-            self.ipc_contract_info.gateway_addr,
-            signer.clone(),
-        );
-
-        let txn = gateway_contract.fund_with_token(
-            evm_subnet_id,
-            gateway_manager_facet::FvmAddress::try_from(to)?,
-            value,
-        );
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let txn = self.fund_with_token_call(signer.clone(), &subnet, to, amount)?;
         let txn = call_with_premium_estimation(signer, txn).await?;
 
         let pending_tx = txn.send().await?;
@@ -633,22 +492,8 @@ impl SubnetManager for EthSubnetManager {
         to: Address,
         amount: TokenAmount,
     ) -> Result<ChainEpoch> {
-        self.ensure_same_gateway(&gateway_addr)?;
-
-        let value = amount
-            .atto()
-            .to_u128()
-            .ok_or_else(|| anyhow!("invalid value to fund"))?;
-
-        tracing::info!("release with evm gateway contract: {gateway_addr:} with value: {value:}");
-
-        let signer = Arc::new(self.get_signer(&from)?);
-        let gateway_contract = gateway_manager_facet::GatewayManagerFacet::new(
-            self.ipc_contract_info.gateway_addr,
-            signer.clone(),
-        );
-        let mut txn = gateway_contract.release(gateway_manager_facet::FvmAddress::try_from(to)?);
-        txn.tx.set_value(value);
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let txn = self.release_call(signer.clone(), &gateway_addr, to, amount)?;
         let txn = call_with_premium_estimation(signer, txn).await?;
 
         let pending_tx = txn.send().await?;
@@ -664,27 +509,10 @@ impl SubnetManager for EthSubnetManager {
         from: Address,
         postbox_msg_key: Vec<u8>,
     ) -> Result<()> {
-        if postbox_msg_key.len() != 32 {
-            return Err(anyhow!(
-                "invalid message cid length, expect 32 but found {}",
-                postbox_msg_key.len()
-            ));
-        }
-
-        self.ensure_same_gateway(&gateway_addr)?;
-
-        tracing::info!("propagate postbox evm gateway contract: {gateway_addr:} with message key: {postbox_msg_key:?}");
-
-        let signer = Arc::new(self.get_signer(&from)?);
-        let gateway_contract = gateway_messenger_facet::GatewayMessengerFacet::new(
-            self.ipc_contract_info.gateway_addr,
-            signer.clone(),
-        );
-
-        let mut key = [0u8; 32];
-        key.copy_from_slice(&postbox_msg_key);
+        let signer = Arc::new(self.get_signer(&from).await?);
+        let txn = self.propagate_call(signer.clone(), &gateway_addr, postbox_msg_key)?;
 
-        call_with_premium_estimation(signer, gateway_contract.propagate(key))
+        call_with_premium_estimation(signer, txn)
             .await?
             .send()
             .await?;
@@ -694,11 +522,9 @@ impl SubnetManager for EthSubnetManager {
 
     /// Send value between two addresses in a subnet
     async fn send_value(&self, from: Address, to: Address, amount: TokenAmount) -> Result<()> {
-        let signer = Arc::new(self.get_signer(&from)?);
+        let signer = Arc::new(self.get_signer(&from).await?);
         let (fee, fee_cap) = premium_estimation(signer.clone()).await?;
-        let tx = Eip1559TransactionRequest::new()
-            .to(payload_to_evm_address(to.payload())?)
-            .value(fil_to_eth_amount(&amount)?)
+        let tx = send_value_tx(to, &amount)?
             .max_priority_fee_per_gas(fee)
             .max_fee_per_gas(fee_cap);
 
@@ -766,29 +592,50 @@ impl SubnetManager for EthSubnetManager {
         Ok(contract.supply_source().call().await?)
     }
 
-    async fn get_genesis_info(&self, subnet: &SubnetID) -> Result<SubnetGenesisInfo> {
+    async fn get_genesis_info(
+        &self,
+        subnet: &SubnetID,
+        height: Option<ChainEpoch>,
+    ) -> Result<SubnetGenesisInfo> {
         let address = contract_address_from_subnet(subnet)?;
         let contract = subnet_actor_getter_facet::SubnetActorGetterFacet::new(
             address,
             Arc::new(self.ipc_contract_info.provider.clone()),
         );
 
-        let genesis_balances = contract.genesis_balances().await?;
-        let bottom_up_checkpoint_period = contract.bottom_up_check_period().call().await?.as_u64();
+        // Query every value at the same block so the result is consistent and reproducible.
+        let block = height.map(|h| BlockId::from(h as u64));
+        let genesis_balances = at_block(contract.genesis_balances(), block).call().await?;
+        let bottom_up_checkpoint_period = at_block(contract.bottom_up_check_period(), block)
+            .call()
+            .await?
+            .as_u64();
 
         Ok(SubnetGenesisInfo {
             // Active validators limit set for the child subnet.
-            active_validators_limit: contract.active_validators_limit().call().await?,
+            active_validators_limit: at_block(contract.active_validators_limit(), block)
+                .call()
+                .await?,
             // Bottom-up checkpoint period set in the subnet actor.
             bottom_up_checkpoint_period,
             // Genesis epoch when the subnet was bootstrapped in the parent.
-            genesis_epoch: self.genesis_epoch(subnet).await?,
+            genesis_epoch: self.genesis_epoch_at(subnet, block).await?,
             // Majority percentage of
-            majority_percentage: contract.majority_percentage().call().await?,
+            majority_percentage: at_block(contract.majority_percentage(), block)
+                .call()
+                .await?,
             // Minimum collateral required for subnets to register into the subnet
-            min_collateral: eth_to_fil_amount(&contract.min_activation_collateral().call().await?)?,
+            min_collateral: eth_to_fil_amount(
+                &at_block(contract.min_activation_collateral(), block)
+                    .call()
+                    .await?,
+            )?,
             // Custom message fee that the child subnet wants to set for cross-net messages
-            validators: from_contract_validators(contract.genesis_validators().call().await?)?,
+            validators: from_contract_validators(
+                at_block(contract.genesis_validators(), block)
+                    .call()
+                    .await?,
+            )?,
             genesis_balances: into_genesis_balance_map(genesis_balances.0, genesis_balances.1)?,
             // TODO: fixme https://github.com/consensus-shipyard/ipc-monorepo/issues/496
             permission_mode: PermissionMode::Collateral,
@@ -805,17 +652,28 @@ impl SubnetManager for EthSubnetManager {
         from: &Address,
         endpoint: String,
     ) -> Result<()> {
-        let address = contract_address_from_subnet(subnet)?;
+        let signer = Arc::new(self.get_signer(from).await?);
+        let txn = self.add_bootstrap_call(signer.clone(), subnet, endpoint)?;
 
-        if is_valid_bootstrap_addr(&endpoint).is_none() {
-            return Err(anyhow!("wrong format for bootstrap endpoint"));
-        }
+        call_with_premium_estimation(signer, txn)
+            .await?
+            .send()
+            .await?
+            .await?;
+
+        Ok(())
+    }
 
-        let signer = Arc::new(self.get_signer(from)?);
-        let contract =
-            subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
+    async fn remove_bootstrap(
+        &self,
+        subnet: &SubnetID,
+        from: &Address,
+        endpoint: String,
+    ) -> Result<()> {
+        let signer = Arc::new(self.get_signer(from).await?);
+        let txn = self.remove_bootstrap_call(signer.clone(), subnet, endpoint)?;
 
-        call_with_premium_estimation(signer, contract.add_bootstrap_node(endpoint))
+        call_with_premium_estimation(signer, txn)
             .await?
             .send()
             .await?
@@ -856,6 +714,59 @@ impl SubnetManager for EthSubnetManager {
         })
     }
 
+    async fn list_validators(&self, subnet: &SubnetID) -> Result<ValidatorSetInfo> {
+        let address = contract_address_from_subnet(subnet)?;
+        let contract = subnet_actor_getter_facet::SubnetActorGetterFacet::new(
+            address,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+
+        let (active, waiting) = tokio::try_join!(
+            contract.get_active_validators().call(),
+            contract.get_waiting_validators().call()
+        )?;
+
+        // Fetch the details of all validators concurrently rather than one round trip at a time.
+        let with_info = |validators: Vec<ethers::types::Address>| {
+            try_join_all(validators.into_iter().map(|validator| {
+                let call = contract.get_validator(validator);
+                async move { Ok::<_, anyhow::Error>((validator, call.call().await?)) }
+            }))
+        };
+        let (active, waiting) = tokio::try_join!(with_info(active), with_info(waiting))?;
+
+        ValidatorSetInfo::from_contract_validators(active, waiting)
+    }
+
+    async fn subnet_status(&self, subnet: &SubnetID) -> Result<SubnetStatus> {
+        let address = contract_address_from_subnet(subnet)?;
+
+        // Calling the getters of an address without code would fail to decode the empty result.
+        let code = self
+            .ipc_contract_info
+            .provider
+            .get_code(address, None)
+            .await
+            .context("failed to get the subnet actor code")?;
+        if code.is_empty() {
+            return Ok(SubnetStatus::default());
+        }
+
+        let contract = subnet_actor_getter_facet::SubnetActorGetterFacet::new(
+            address,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+
+        Ok(SubnetStatus {
+            exists: true,
+            bootstrapped: contract.bootstrapped().call().await?,
+            killed: contract.killed().call().await?,
+            stake: eth_to_fil_amount(&contract.get_total_confirmed_collateral().call().await?)?,
+            validators: contract.get_total_validators_number().call().await?,
+            active_validators: contract.get_active_validators_number().call().await?,
+        })
+    }
+
     async fn set_federated_power(
         &self,
         from: &Address,
@@ -864,38 +775,171 @@ impl SubnetManager for EthSubnetManager {
         public_keys: &[Vec<u8>],
         federated_power: &[u128],
     ) -> Result<ChainEpoch> {
-        let address = contract_address_from_subnet(subnet)?;
-        tracing::info!("interacting with evm subnet contract: {address:}");
-
-        let signer = Arc::new(self.get_signer(from)?);
-        let contract =
-            subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
+        tracing::debug!("from address: {:?}", from);
 
-        let addresses: Vec<ethers::core::types::Address> = validators
-            .iter()
-            .map(|validator_address| payload_to_evm_address(validator_address.payload()).unwrap())
-            .collect();
-        tracing::debug!("converted addresses: {:?}", addresses);
+        let signer = Arc::new(self.get_signer(from).await?);
+        let call = self.set_federated_power_call(
+            signer.clone(),
+            subnet,
+            validators,
+            public_keys,
+            federated_power,
+        )?;
+        let txn = call_with_premium_estimation(signer, call).await?;
+        let pending_tx = txn.send().await?;
+        let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
+        block_number_from_receipt(receipt)
+    }
 
-        let pubkeys: Vec<ethers::core::types::Bytes> = public_keys
-            .iter()
-            .map(|key| ethers::core::types::Bytes::from(key.clone()))
-            .collect();
-        tracing::debug!("converted pubkeys: {:?}", pubkeys);
-
-        let power_u256: Vec<ethers::core::types::U256> = federated_power
-            .iter()
-            .map(|power| ethers::core::types::U256::from(*power))
-            .collect();
-        tracing::debug!("converted power: {:?}", power_u256);
-
-        tracing::debug!("from address: {:?}", from);
-
-        let call = contract.set_federated_power(addresses, pubkeys, power_u256);
-        let txn = call_with_premium_estimation(signer, call).await?;
-        let pending_tx = txn.send().await?;
-        let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
-        block_number_from_receipt(receipt)
+    async fn estimate(&self, operation: SubnetOperation) -> Result<CallEstimate> {
+        match operation {
+            SubnetOperation::CreateSubnet { from, params } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.create_subnet_call(signer, params)?).await
+            }
+            SubnetOperation::JoinSubnet {
+                subnet,
+                from,
+                collateral,
+                metadata,
+            } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.join_subnet_call(signer, &subnet, collateral, metadata)?).await
+            }
+            SubnetOperation::PreFund {
+                subnet,
+                from,
+                balance,
+            } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.pre_fund_call(signer, &subnet, balance)?).await
+            }
+            SubnetOperation::PreRelease {
+                subnet,
+                from,
+                amount,
+            } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.pre_release_call(signer, &subnet, amount)?).await
+            }
+            SubnetOperation::Stake {
+                subnet,
+                from,
+                collateral,
+            } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.stake_call(signer, &subnet, collateral)?).await
+            }
+            SubnetOperation::Unstake {
+                subnet,
+                from,
+                collateral,
+            } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.unstake_call(signer, &subnet, collateral)?).await
+            }
+            SubnetOperation::LeaveSubnet { subnet, from } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.leave_subnet_call(signer, &subnet)?).await
+            }
+            SubnetOperation::KillSubnet { subnet, from } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.kill_subnet_call(signer, &subnet)?).await
+            }
+            SubnetOperation::ClaimCollateral { subnet, from } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.claim_collateral_call(signer, &subnet)?).await
+            }
+            SubnetOperation::Fund {
+                subnet,
+                gateway_addr,
+                from,
+                to,
+                amount,
+            } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.fund_call(signer, &subnet, &gateway_addr, to, amount)?).await
+            }
+            SubnetOperation::ApproveToken {
+                subnet,
+                from,
+                amount,
+            } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.approve_token_call(signer, &subnet, amount).await?).await
+            }
+            SubnetOperation::FundWithToken {
+                subnet,
+                from,
+                to,
+                amount,
+            } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.fund_with_token_call(signer, &subnet, to, amount)?).await
+            }
+            SubnetOperation::Release {
+                gateway_addr,
+                from,
+                to,
+                amount,
+            } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.release_call(signer, &gateway_addr, to, amount)?).await
+            }
+            SubnetOperation::Propagate {
+                gateway_addr,
+                from,
+                postbox_msg_key,
+            } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.propagate_call(signer, &gateway_addr, postbox_msg_key)?).await
+            }
+            SubnetOperation::SendValue { from, to, amount } => {
+                let signer = self.get_signer(&from).await?;
+                let tx: TypedTransaction =
+                    send_value_tx(to, &amount)?.from(signer.address()).into();
+                match signer.estimate_gas(&tx, None).await {
+                    Ok(gas) => Ok(CallEstimate {
+                        gas: gas.as_u64(),
+                        revert_reason: None,
+                    }),
+                    Err(e) => revert_estimate(ContractError::from_middleware_error(e)),
+                }
+            }
+            SubnetOperation::AddBootstrap {
+                subnet,
+                from,
+                endpoint,
+            } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.add_bootstrap_call(signer, &subnet, endpoint)?).await
+            }
+            SubnetOperation::RemoveBootstrap {
+                subnet,
+                from,
+                endpoint,
+            } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                estimate_call(self.remove_bootstrap_call(signer, &subnet, endpoint)?).await
+            }
+            SubnetOperation::SetFederatedPower {
+                from,
+                subnet,
+                validators,
+                public_keys,
+                federated_power,
+            } => {
+                let signer = Arc::new(self.get_signer(&from).await?);
+                let call = self.set_federated_power_call(
+                    signer,
+                    &subnet,
+                    &validators,
+                    &public_keys,
+                    &federated_power,
+                )?;
+                estimate_call(call).await
+            }
+        }
     }
 }
 
@@ -1000,7 +1044,79 @@ impl EthManager for EthSubnetManager {
     }
 }
 
+#[async_trait]
+impl CrossMsgQuery for EthSubnetManager {
+    async fn top_down_nonce(&self, subnet_id: &SubnetID) -> Result<u64> {
+        // Despite its name, this is the nonce of the subnet in the parent gateway.
+        self.get_applied_top_down_nonce(subnet_id).await
+    }
+
+    async fn applied_top_down_nonce(&self) -> Result<u64> {
+        let contract = gateway_getter_facet::GatewayGetterFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+        Ok(contract.applied_top_down_nonce().call().await?)
+    }
+
+    async fn bottom_up_nonce(&self) -> Result<u64> {
+        let contract = gateway_getter_facet::GatewayGetterFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+        Ok(contract.bottom_up_nonce().call().await?)
+    }
+
+    async fn applied_bottom_up_nonce(&self, subnet_id: &SubnetID) -> Result<u64> {
+        let evm_subnet_id = gateway_getter_facet::SubnetID {
+            root: subnet_id.root_id(),
+            route: subnet_id_to_evm_addresses(subnet_id)?,
+        };
+
+        let contract = gateway_getter_facet::GatewayGetterFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+
+        let (exists, nonce) = contract
+            .get_applied_bottom_up_nonce(evm_subnet_id)
+            .call()
+            .await
+            .map_err(|e| anyhow!("cannot get applied bottom up nonce due to: {e:}"))?;
+
+        if !exists {
+            Err(anyhow!("subnet {:?} does not exists", subnet_id))
+        } else {
+            Ok(nonce)
+        }
+    }
+}
+
 impl EthSubnetManager {
+    /// Query the genesis epoch of a subnet as of the given parent block, or the latest one.
+    async fn genesis_epoch_at(
+        &self,
+        subnet_id: &SubnetID,
+        block: Option<BlockId>,
+    ) -> Result<ChainEpoch> {
+        let address = contract_address_from_subnet(subnet_id)?;
+        tracing::info!("querying genesis epoch in evm subnet contract: {address:}");
+
+        let evm_subnet_id = gateway_getter_facet::SubnetID::try_from(subnet_id)?;
+
+        let contract = gateway_getter_facet::GatewayGetterFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+        let (exists, subnet) = at_block(contract.get_subnet(evm_subnet_id), block)
+            .call()
+            .await?;
+        if !exists {
+            return Err(anyhow!("subnet: {} does not exists", subnet_id));
+        }
+        Ok(subnet.genesis_epoch.as_u64() as ChainEpoch)
+    }
+
     pub fn new(
         gateway_addr: ethers::types::Address,
         registry_addr: ethers::types::Address,
@@ -1010,15 +1126,23 @@ impl EthSubnetManager {
     ) -> Self {
         Self {
             keystore,
+            signers: SignerSelector::default(),
             ipc_contract_info: IPCContractInfo {
                 gateway_addr,
                 registry_addr,
                 chain_id,
                 provider,
+                ws_endpoint: None,
             },
         }
     }
 
+    /// Subscribe to new blocks over WebSocket, instead of polling the HTTP provider.
+    pub fn with_ws_endpoint(mut self, url: url::Url, auth_token: Option<String>) -> Self {
+        self.ipc_contract_info.ws_endpoint = Some(WsEndpoint { url, auth_token });
+        self
+    }
+
     pub fn ensure_same_gateway(&self, gateway: &Address) -> Result<()> {
         let evm_gateway_addr = payload_to_evm_address(gateway.payload())?;
         if evm_gateway_addr != self.ipc_contract_info.gateway_addr {
@@ -1034,23 +1158,31 @@ impl EthSubnetManager {
             .ok_or(anyhow!("no evm keystore available"))
     }
 
+    /// Sign the transactions of the configured accounts with a hardware wallet,
+    /// instead of with the private keys in the keystore.
+    pub fn with_signer_selector(mut self, signers: SignerSelector) -> Self {
+        self.signers = signers;
+        self
+    }
+
     /// Get the ethers singer instance.
     /// We use filecoin addresses throughout our whole code-base
     /// and translate them to evm addresses when relevant.
-    fn get_signer(&self, addr: &Address) -> Result<DefaultSignerMiddleware> {
+    async fn get_signer(&self, addr: &Address) -> Result<DefaultSignerMiddleware> {
         // convert to its underlying eth address
         let addr = payload_to_evm_address(addr.payload())?;
-        let keystore = self.keystore()?;
-        let keystore = keystore.read().unwrap();
-        let private_key = keystore
-            .get(&addr.into())?
-            .ok_or_else(|| anyhow!("address {addr:} does not have private key in key store"))?;
-        let wallet = LocalWallet::from_bytes(private_key.private_key())?
-            .with_chain_id(self.ipc_contract_info.chain_id);
+        let signer = self
+            .signers
+            .signer(
+                &addr,
+                self.ipc_contract_info.chain_id,
+                self.keystore.as_deref(),
+            )
+            .await?;
 
         Ok(SignerMiddleware::new(
             self.ipc_contract_info.provider.clone(),
-            wallet,
+            signer,
         ))
     }
 
@@ -1092,14 +1224,401 @@ impl EthSubnetManager {
         let gateway_address = payload_to_evm_address(config.gateway_addr.payload())?;
         let registry_address = payload_to_evm_address(config.registry_addr.payload())?;
 
-        Ok(Self::new(
+        let mut manager = Self::new(
             gateway_address,
             registry_address,
             subnet.id.chain_id(),
             provider,
             keystore,
+        );
+
+        if let Some(ws_url) = subnet.rpc_ws() {
+            manager = manager.with_ws_endpoint(ws_url.clone(), subnet.auth_token());
+        }
+
+        Ok(manager)
+    }
+}
+
+/// Builders of the contract calls sent by the state changing methods, shared with
+/// [`SubnetManager::estimate`] so that the estimation uses the exact same calldata.
+impl EthSubnetManager {
+    fn create_subnet_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        params: ConstructParams,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        self.ensure_same_gateway(&params.ipc_gateway_addr)?;
+
+        let min_validator_stake = params
+            .min_validator_stake
+            .atto()
+            .to_u128()
+            .ok_or_else(|| anyhow!("invalid min validator stake"))?;
+
+        tracing::debug!("calling create subnet for EVM manager");
+
+        let route = subnet_id_to_evm_addresses(&params.parent)?;
+        tracing::debug!("root SubnetID as Ethereum type: {route:?}");
+
+        let params = register_subnet_facet::ConstructorParams {
+            parent_id: register_subnet_facet::SubnetID {
+                root: params.parent.root_id(),
+                route,
+            },
+            ipc_gateway_addr: self.ipc_contract_info.gateway_addr,
+            consensus: params.consensus as u64 as u8,
+            min_activation_collateral: ethers::types::U256::from(min_validator_stake),
+            min_validators: params.min_validators,
+            bottom_up_check_period: params.bottomup_check_period as u64,
+            majority_percentage: SUBNET_MAJORITY_PERCENTAGE,
+            active_validators_limit: params.active_validators_limit,
+            power_scale: 3,
+            permission_mode: params.permission_mode as u8,
+            supply_source: register_subnet_facet::SupplySource::try_from(params.supply_source)?,
+        };
+
+        tracing::info!("creating subnet on evm with params: {params:?}");
+
+        let registry_contract = register_subnet_facet::RegisterSubnetFacet::new(
+            self.ipc_contract_info.registry_addr,
+            signer,
+        );
+        Ok(registry_contract.new_subnet_actor(params))
+    }
+
+    fn join_subnet_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+        collateral: TokenAmount,
+        pub_key: Vec<u8>,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        let collateral = collateral
+            .atto()
+            .to_u128()
+            .ok_or_else(|| anyhow!("invalid min validator stake"))?;
+
+        let address = contract_address_from_subnet(subnet)?;
+        tracing::info!(
+            "interacting with evm subnet contract: {address:} with collateral: {collateral:}"
+        );
+
+        let contract = subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer);
+
+        let mut txn = contract.join(ethers::types::Bytes::from(pub_key));
+        txn.tx.set_value(collateral);
+        Ok(txn)
+    }
+
+    fn pre_fund_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+        balance: TokenAmount,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        let balance = balance
+            .atto()
+            .to_u128()
+            .ok_or_else(|| anyhow!("invalid initial balance"))?;
+
+        let address = contract_address_from_subnet(subnet)?;
+        tracing::info!("interacting with evm subnet contract: {address:} with balance: {balance:}");
+
+        let contract = subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer);
+
+        let mut txn = contract.pre_fund();
+        txn.tx.set_value(balance);
+        Ok(txn)
+    }
+
+    fn pre_release_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+        amount: TokenAmount,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        let address = contract_address_from_subnet(subnet)?;
+        tracing::info!("pre-release funds from {subnet:} at contract: {address:}");
+
+        let amount = amount
+            .atto()
+            .to_u128()
+            .ok_or_else(|| anyhow!("invalid pre-release amount"))?;
+
+        let contract = subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer);
+        Ok(contract.pre_release(amount.into()))
+    }
+
+    fn stake_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+        collateral: TokenAmount,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        let collateral = collateral
+            .atto()
+            .to_u128()
+            .ok_or_else(|| anyhow!("invalid collateral amount"))?;
+
+        let address = contract_address_from_subnet(subnet)?;
+        tracing::info!(
+            "interacting with evm subnet contract: {address:} with collateral: {collateral:}"
+        );
+
+        let contract = subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer);
+
+        let mut txn = contract.stake();
+        txn.tx.set_value(collateral);
+        Ok(txn)
+    }
+
+    fn unstake_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+        collateral: TokenAmount,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        let collateral = collateral
+            .atto()
+            .to_u128()
+            .ok_or_else(|| anyhow!("invalid collateral amount"))?;
+
+        let address = contract_address_from_subnet(subnet)?;
+        tracing::info!(
+            "interacting with evm subnet contract: {address:} with collateral: {collateral:}"
+        );
+
+        let contract = subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer);
+        Ok(contract.unstake(collateral.into()))
+    }
+
+    fn leave_subnet_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        let address = contract_address_from_subnet(subnet)?;
+        tracing::info!("leaving evm subnet: {subnet:} at contract: {address:}");
+
+        let contract = subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer);
+        Ok(contract.leave())
+    }
+
+    fn kill_subnet_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        let address = contract_address_from_subnet(subnet)?;
+        tracing::info!("kill evm subnet: {subnet:} at contract: {address:}");
+
+        let contract = subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer);
+        Ok(contract.kill())
+    }
+
+    fn claim_collateral_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        let address = contract_address_from_subnet(subnet)?;
+        tracing::info!("claim collateral evm subnet: {subnet:} at contract: {address:}");
+
+        let contract = subnet_actor_reward_facet::SubnetActorRewardFacet::new(address, signer);
+        Ok(contract.claim())
+    }
+
+    fn fund_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+        gateway_addr: &Address,
+        to: Address,
+        amount: TokenAmount,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        self.ensure_same_gateway(gateway_addr)?;
+
+        let value = amount
+            .atto()
+            .to_u128()
+            .ok_or_else(|| anyhow!("invalid value to fund"))?;
+
+        tracing::info!("fund with evm gateway contract: {gateway_addr:} with value: {value:}, original: {amount:?}");
+
+        let evm_subnet_id = gateway_manager_facet::SubnetID::try_from(subnet)?;
+        tracing::debug!("evm subnet id to fund: {evm_subnet_id:?}");
+
+        let gateway_contract = gateway_manager_facet::GatewayManagerFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            signer,
+        );
+
+        let mut txn = gateway_contract.fund(
+            evm_subnet_id,
+            gateway_manager_facet::FvmAddress::try_from(to)?,
+        );
+        txn.tx.set_value(value);
+        Ok(txn)
+    }
+
+    async fn approve_token_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+        amount: TokenAmount,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        let value = fil_amount_to_eth_amount(&amount)?;
+
+        let subnet_supply_source = self.get_subnet_supply_source(subnet).await?;
+        if subnet_supply_source.kind != SupplyKind::ERC20 as u8 {
+            return Err(anyhow!("Invalid operation: Expected the subnet's supply source to be ERC20, but found a different kind."));
+        }
+
+        let token_contract = IERC20::new(subnet_supply_source.token_address, signer);
+        Ok(token_contract.approve(self.ipc_contract_info.gateway_addr, value))
+    }
+
+    fn fund_with_token_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+        to: Address,
+        amount: TokenAmount,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        let value = fil_amount_to_eth_amount(&amount)?;
+        let evm_subnet_id = gateway_manager_facet::SubnetID::try_from(subnet)?;
+
+        let gateway_contract = gateway_manager_facet::GatewayManagerFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            signer,
+        );
+
+        Ok(gateway_contract.fund_with_token(
+            evm_subnet_id,
+            gateway_manager_facet::FvmAddress::try_from(to)?,
+            value,
         ))
     }
+
+    fn release_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        gateway_addr: &Address,
+        to: Address,
+        amount: TokenAmount,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        self.ensure_same_gateway(gateway_addr)?;
+
+        let value = amount
+            .atto()
+            .to_u128()
+            .ok_or_else(|| anyhow!("invalid value to fund"))?;
+
+        tracing::info!("release with evm gateway contract: {gateway_addr:} with value: {value:}");
+
+        let gateway_contract = gateway_manager_facet::GatewayManagerFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            signer,
+        );
+        let mut txn = gateway_contract.release(gateway_manager_facet::FvmAddress::try_from(to)?);
+        txn.tx.set_value(value);
+        Ok(txn)
+    }
+
+    fn propagate_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        gateway_addr: &Address,
+        postbox_msg_key: Vec<u8>,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        if postbox_msg_key.len() != 32 {
+            return Err(anyhow!(
+                "invalid message cid length, expect 32 but found {}",
+                postbox_msg_key.len()
+            ));
+        }
+
+        self.ensure_same_gateway(gateway_addr)?;
+
+        tracing::info!("propagate postbox evm gateway contract: {gateway_addr:} with message key: {postbox_msg_key:?}");
+
+        let gateway_contract = gateway_messenger_facet::GatewayMessengerFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            signer,
+        );
+
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&postbox_msg_key);
+
+        Ok(gateway_contract.propagate(key))
+    }
+
+    fn add_bootstrap_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+        endpoint: String,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        let address = contract_address_from_subnet(subnet)?;
+
+        if is_valid_bootstrap_addr(&endpoint).is_none() {
+            return Err(anyhow!("wrong format for bootstrap endpoint"));
+        }
+
+        let contract = subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer);
+        Ok(contract.add_bootstrap_node(endpoint))
+    }
+
+    fn remove_bootstrap_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+        endpoint: String,
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        let address = contract_address_from_subnet(subnet)?;
+
+        if is_valid_bootstrap_addr(&endpoint).is_none() {
+            return Err(anyhow!("wrong format for bootstrap endpoint"));
+        }
+
+        let contract = subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer);
+        Ok(contract.remove_bootstrap_node(endpoint))
+    }
+
+    fn set_federated_power_call(
+        &self,
+        signer: Arc<DefaultSignerMiddleware>,
+        subnet: &SubnetID,
+        validators: &[Address],
+        public_keys: &[Vec<u8>],
+        federated_power: &[u128],
+    ) -> Result<ContractCall<DefaultSignerMiddleware, impl Detokenize>> {
+        let address = contract_address_from_subnet(subnet)?;
+        tracing::info!("interacting with evm subnet contract: {address:}");
+
+        let contract = subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer);
+
+        let addresses: Vec<ethers::core::types::Address> = validators
+            .iter()
+            .map(|validator_address| payload_to_evm_address(validator_address.payload()).unwrap())
+            .collect();
+        tracing::debug!("converted addresses: {:?}", addresses);
+
+        let pubkeys: Vec<ethers::core::types::Bytes> = public_keys
+            .iter()
+            .map(|key| ethers::core::types::Bytes::from(key.clone()))
+            .collect();
+        tracing::debug!("converted pubkeys: {:?}", pubkeys);
+
+        let power_u256: Vec<ethers::core::types::U256> = federated_power
+            .iter()
+            .map(|power| ethers::core::types::U256::from(*power))
+            .collect();
+        tracing::debug!("converted power: {:?}", power_u256);
+
+        Ok(contract.set_federated_power(addresses, pubkeys, power_u256))
+    }
 }
 
 #[async_trait]
@@ -1111,29 +1630,8 @@ impl BottomUpCheckpointRelayer for EthSubnetManager {
         signatures: Vec<Signature>,
         signatories: Vec<Address>,
     ) -> anyhow::Result<ChainEpoch> {
-        let address = contract_address_from_subnet(&checkpoint.subnet_id)?;
-        tracing::debug!(
-            "submit bottom up checkpoint: {checkpoint:?} in evm subnet contract: {address:}"
-        );
-
-        let signatures = signatures
-            .into_iter()
-            .map(ethers::types::Bytes::from)
-            .collect::<Vec<_>>();
-        let signatories = signatories
-            .into_iter()
-            .map(|addr| payload_to_evm_address(addr.payload()))
-            .collect::<result::Result<Vec<_>, _>>()?;
-
-        let checkpoint =
-            subnet_actor_checkpointing_facet::BottomUpCheckpoint::try_from(checkpoint)?;
-
-        let signer = Arc::new(self.get_signer(submitter)?);
-        let contract = subnet_actor_checkpointing_facet::SubnetActorCheckpointingFacet::new(
-            address,
-            signer.clone(),
-        );
-        let call = contract.submit_checkpoint(checkpoint, signatories, signatures);
+        let signer = Arc::new(self.get_signer(submitter).await?);
+        let call = submit_checkpoint_call(signer.clone(), checkpoint, signatures, signatories)?;
         let call = call_with_premium_estimation(signer, call).await?;
 
         let pending_tx = call.send().await?;
@@ -1141,6 +1639,55 @@ impl BottomUpCheckpointRelayer for EthSubnetManager {
         block_number_from_receipt(receipt)
     }
 
+    async fn submit_checkpoint_with_timeout(
+        &self,
+        submitter: &Address,
+        checkpoint: BottomUpCheckpoint,
+        signatures: Vec<Signature>,
+        signatories: Vec<Address>,
+        replace: Option<PendingSubmission>,
+        timeout: Duration,
+    ) -> anyhow::Result<CheckpointSubmission> {
+        let signer = Arc::new(self.get_signer(submitter).await?);
+        let call = submit_checkpoint_call(signer.clone(), checkpoint, signatures, signatories)?;
+
+        let (nonce, gas_premium) = match replace {
+            Some(pending) => (
+                U256::from(pending.nonce),
+                fil_amount_to_eth_amount(&pending.gas_premium)?,
+            ),
+            None => {
+                let nonce = signer
+                    .get_transaction_count(
+                        signer.address(),
+                        Some(BlockId::from(BlockNumber::Pending)),
+                    )
+                    .await?;
+                let (gas_premium, _) = premium_estimation(signer.clone()).await?;
+                (nonce, gas_premium)
+            }
+        };
+        let call = call.nonce(nonce).gas_price(gas_premium);
+
+        let pending_tx = match call.send().await {
+            Ok(tx) => tx,
+            Err(e) if is_checkpoint_already_submitted(&e) => {
+                return Ok(CheckpointSubmission::AlreadySubmitted)
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        match tokio::time::timeout(timeout, pending_tx.retries(TRANSACTION_RECEIPT_RETRIES)).await {
+            Ok(receipt) => Ok(CheckpointSubmission::Executed(block_number_from_receipt(
+                receipt?,
+            )?)),
+            Err(_) => Ok(CheckpointSubmission::Pending(PendingSubmission {
+                nonce: nonce.as_u64(),
+                gas_premium: eth_to_fil_amount(&gas_premium)?,
+            })),
+        }
+    }
+
     async fn last_bottom_up_checkpoint_height(
         &self,
         subnet_id: &SubnetID,
@@ -1164,6 +1711,32 @@ impl BottomUpCheckpointRelayer for EthSubnetManager {
         Ok(epoch.as_u64() as ChainEpoch)
     }
 
+    async fn checkpoint_signers(&self, subnet_id: &SubnetID) -> anyhow::Result<CheckpointSigners> {
+        let address = contract_address_from_subnet(subnet_id)?;
+        let contract = subnet_actor_getter_facet::SubnetActorGetterFacet::new(
+            address,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+
+        let (validators, majority_percentage) = tokio::try_join!(
+            contract.get_active_validators().call(),
+            contract.majority_percentage().call()
+        )?;
+
+        let power = try_join_all(validators.into_iter().map(|validator| {
+            let call = contract.get_power(validator);
+            async move { Ok::<_, anyhow::Error>((validator, call.call().await?)) }
+        }))
+        .await?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+        Ok(CheckpointSigners {
+            power,
+            majority_percentage,
+        })
+    }
+
     async fn checkpoint_bundle_at(
         &self,
         height: ChainEpoch,
@@ -1213,16 +1786,42 @@ impl BottomUpCheckpointRelayer for EthSubnetManager {
 
         let mut events = vec![];
         for (event, _meta) in query_with_meta(ev, contract.client()).await? {
-            events.push(QuorumReachedEvent {
-                obj_kind: event.obj_kind,
-                height: event.height.as_u64() as ChainEpoch,
-                obj_hash: event.obj_hash.to_vec(),
-                quorum_weight: eth_to_fil_amount(&event.quorum_weight)?,
-            });
+            events.push(quorum_reached_event(event)?);
         }
 
         Ok(events)
     }
+
+    fn subscribe_quorum_events(
+        self: Arc<Self>,
+        from_height: ChainEpoch,
+    ) -> BoxStream<'static, Result<QuorumReachedEvent>> {
+        // Without a WebSocket endpoint the provider is HTTP only, so we poll for new blocks.
+        let Some(ws) = self.ipc_contract_info.ws_endpoint.clone() else {
+            return quorum_event_stream(self, from_height, DEFAULT_QUORUM_POLL_INTERVAL);
+        };
+
+        stream::once(async move {
+            let new_heads = match subscribe_new_heads(&ws).await {
+                Ok(new_heads) => Some(new_heads),
+                Err(e) => {
+                    tracing::warn!(
+                        url = ws.url.to_string(),
+                        "cannot subscribe to new blocks, falling back to polling: {e:#}"
+                    );
+                    None
+                }
+            };
+            quorum_event_stream_with_heads(
+                self,
+                from_height,
+                DEFAULT_QUORUM_POLL_INTERVAL,
+                new_heads,
+            )
+        })
+        .flatten()
+        .boxed()
+    }
     async fn current_epoch(&self) -> Result<ChainEpoch> {
         let epoch = self
             .ipc_contract_info
@@ -1234,6 +1833,119 @@ impl BottomUpCheckpointRelayer for EthSubnetManager {
     }
 }
 
+/// Open a WebSocket connection and subscribe to the headers of new blocks.
+///
+/// The returned stream owns the connection and only signals that a new block arrived;
+/// the events in it are still queried through the HTTP provider.
+async fn subscribe_new_heads(ws: &WsEndpoint) -> Result<BoxStream<'static, ()>> {
+    let auth = ws.auth_token.clone().map(Authorization::Bearer);
+    let client =
+        Ws::connect_with_reconnects(ConnectionDetails::new(ws.url.as_str(), auth), WS_RECONNECTS)
+            .await
+            .with_context(|| format!("failed to connect to {}", ws.url))?;
+
+    let id: U256 = client
+        .request("eth_subscribe", ["newHeads"])
+        .await
+        .context("failed to subscribe to new heads")?;
+    let notifications = client.subscribe(id)?;
+
+    // Keep the client alive for as long as the stream is, otherwise the connection closes.
+    Ok(notifications
+        .map(move |_| {
+            let _ = &client;
+        })
+        .boxed())
+}
+
+/// Decodes the quorum reached event emitted by the gateway.
+fn quorum_reached_event(event: lib_quorum::QuorumReachedFilter) -> Result<QuorumReachedEvent> {
+    Ok(QuorumReachedEvent {
+        obj_kind: event.obj_kind,
+        height: event.height.as_u64() as ChainEpoch,
+        obj_hash: event.obj_hash.to_vec(),
+        quorum_weight: eth_to_fil_amount(&event.quorum_weight)?,
+    })
+}
+
+/// Prepares the call to submit a bottom-up checkpoint to the subnet actor in the parent.
+fn submit_checkpoint_call(
+    signer: Arc<DefaultSignerMiddleware>,
+    checkpoint: BottomUpCheckpoint,
+    signatures: Vec<Signature>,
+    signatories: Vec<Address>,
+) -> Result<ethers_contract::ContractCall<DefaultSignerMiddleware, ()>> {
+    let address = contract_address_from_subnet(&checkpoint.subnet_id)?;
+    tracing::debug!(
+        "submit bottom up checkpoint: {checkpoint:?} in evm subnet contract: {address:}"
+    );
+
+    let signatures = signatures
+        .into_iter()
+        .map(ethers::types::Bytes::from)
+        .collect::<Vec<_>>();
+    let signatories = signatories
+        .into_iter()
+        .map(|addr| payload_to_evm_address(addr.payload()))
+        .collect::<result::Result<Vec<_>, _>>()?;
+
+    let checkpoint = subnet_actor_checkpointing_facet::BottomUpCheckpoint::try_from(checkpoint)?;
+
+    let contract =
+        subnet_actor_checkpointing_facet::SubnetActorCheckpointingFacet::new(address, signer);
+    Ok(contract.submit_checkpoint(checkpoint, signatories, signatures))
+}
+
+/// Checks whether the submission reverted because the checkpoint has already been committed.
+fn is_checkpoint_already_submitted(e: &ContractError<DefaultSignerMiddleware>) -> bool {
+    use subnet_actor_checkpointing_facet::SubnetActorCheckpointingFacetErrors as Errors;
+
+    matches!(
+        e.decode_contract_revert::<Errors>(),
+        Some(Errors::BottomUpCheckpointAlreadySubmitted(_))
+    )
+}
+
+/// The transaction sending `amount` to the `to` address.
+fn send_value_tx(to: Address, amount: &TokenAmount) -> Result<Eip1559TransactionRequest> {
+    Ok(Eip1559TransactionRequest::new()
+        .to(payload_to_evm_address(to.payload())?)
+        .value(fil_to_eth_amount(amount)?))
+}
+
+/// Simulates the call with `eth_call` and estimates its gas, without sending the transaction.
+/// If the call reverts, the revert data is decoded against the IPC contract errors.
+async fn estimate_call<M: Middleware + 'static, D: Detokenize>(
+    call: ContractCall<M, D>,
+) -> Result<CallEstimate> {
+    if let Err(e) = call.call().await {
+        return revert_estimate(e);
+    }
+
+    match call.estimate_gas().await {
+        Ok(gas) => Ok(CallEstimate {
+            gas: gas.as_u64(),
+            revert_reason: None,
+        }),
+        Err(e) => revert_estimate(e),
+    }
+}
+
+/// Turns a revert into an estimate with its reason, propagating any other error.
+fn revert_estimate<M: Middleware + 'static>(e: ContractError<M>) -> Result<CallEstimate> {
+    match e.as_revert() {
+        Some(data) => {
+            let reason =
+                decode_revert_reason(data).unwrap_or_else(|| format!("0x{}", hex::encode(data)));
+            Ok(CallEstimate {
+                gas: 0,
+                revert_reason: Some(reason),
+            })
+        }
+        None => Err(e.into()),
+    }
+}
+
 /// Receives an input `FunctionCall` and returns a new instance
 /// after estimating an optimal `gas_premium` for the transaction
 pub(crate) async fn call_with_premium_estimation<B, D, M>(
@@ -1384,6 +2096,17 @@ fn is_valid_bootstrap_addr(input: &str) -> Option<(String, IpAddr, u16)> {
     None
 }
 
+/// Pin a contract call to a block, if given, otherwise it runs at the latest one.
+fn at_block<M: Middleware, D: Detokenize>(
+    call: ContractCall<M, D>,
+    block: Option<BlockId>,
+) -> ContractCall<M, D> {
+    match block {
+        Some(block) => call.block(block),
+        None => call,
+    }
+}
+
 /// This is a replacement for `Event::query_with_meta` in `ethers-contract`
 /// because in that one we don't get access to the `reverted` field, which
 /// we need to filteron in the currently deployed `1.25-rc4` version of Lotus.
@@ -1457,10 +2180,58 @@ impl TryFrom<gateway_getter_facet::Subnet> for SubnetInfo {
 
 #[cfg(test)]
 mod tests {
-    use crate::manager::evm::manager::contract_address_from_subnet;
+    use crate::config::subnet::{EVMSubnet, SubnetConfig};
+    use crate::config::Subnet;
+    use crate::manager::evm::manager::{
+        contract_address_from_subnet, estimate_call, quorum_reached_event, DefaultSignerMiddleware,
+        EthSubnetManager,
+    };
+    use crate::manager::CallEstimate;
+    use ethers::abi::{ParamType, Token};
+    use ethers::prelude::SignerMiddleware;
+    use ethers::providers::{Http, JsonRpcError, MockProvider, MockResponse, Provider};
+    use ethers::signers::LocalWallet;
+    use ethers::types::{Bytes, U256};
     use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_actors_abis::lib_quorum::QuorumReachedFilter;
+    use ipc_actors_abis::subnet_actor_manager_facet::SubnetActorManagerFacet;
     use ipc_api::subnet_id::SubnetID;
+    use ipc_wallet::SignerBackend;
     use std::str::FromStr;
+    use std::sync::Arc;
+
+    fn mocked_contract() -> (
+        SubnetActorManagerFacet<Provider<MockProvider>>,
+        MockProvider,
+    ) {
+        let (provider, mock) = Provider::mocked();
+        let contract = SubnetActorManagerFacet::new(
+            ethers::types::Address::repeat_byte(1),
+            Arc::new(provider),
+        );
+        (contract, mock)
+    }
+
+    /// A manager and signer that are never connected, only used to build calls.
+    fn offline_manager() -> (EthSubnetManager, Arc<DefaultSignerMiddleware>) {
+        let provider = Provider::<Http>::try_from("http://localhost:1").unwrap();
+        let manager = EthSubnetManager::new(
+            ethers::types::Address::repeat_byte(1),
+            ethers::types::Address::repeat_byte(2),
+            314159,
+            provider.clone(),
+            None,
+        );
+        let wallet = LocalWallet::from_bytes(&[1; 32]).unwrap();
+        let signer = SignerMiddleware::new(provider, SignerBackend::Software(wallet));
+        (manager, Arc::new(signer))
+    }
+
+    fn test_subnet() -> SubnetID {
+        let addr = Address::from_str("f410ffzyuupbyl2uiucmzr3lu3mtf3luyknthaz4xsrq").unwrap();
+        SubnetID::new(0, vec![addr])
+    }
 
     #[test]
     fn test_agent_subnet_to_evm_address() {
@@ -1473,4 +2244,121 @@ mod tests {
             "0x2e714a3c385ea88a09998ed74db265dae9853667"
         );
     }
+
+    #[test]
+    fn test_decode_quorum_reached_event() {
+        let event = quorum_reached_event(QuorumReachedFilter {
+            obj_kind: 0,
+            height: U256::from(120),
+            obj_hash: [1; 32],
+            quorum_weight: U256::from(10).pow(U256::from(18)),
+        })
+        .unwrap();
+
+        assert_eq!(event.obj_kind, 0);
+        assert_eq!(event.height, 120);
+        assert_eq!(event.obj_hash, vec![1; 32]);
+        assert_eq!(event.quorum_weight, TokenAmount::from_whole(1));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_call() {
+        let (contract, mock) = mocked_contract();
+        // The mock returns the responses in reverse order: `eth_call` then `eth_estimateGas`.
+        mock.push(U256::from(21_000)).unwrap();
+        mock.push(Bytes::default()).unwrap();
+
+        let estimate = estimate_call(contract.leave()).await.unwrap();
+        assert_eq!(
+            estimate,
+            CallEstimate {
+                gas: 21_000,
+                revert_reason: None
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_estimate_call_revert() {
+        let (contract, mock) = mocked_contract();
+        let selector = ethers::utils::id("NotEnoughCollateral()");
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: Some(format!("0x{}", hex::encode(selector)).into()),
+        }));
+
+        let estimate = estimate_call(contract.stake()).await.unwrap();
+        assert_eq!(
+            estimate,
+            CallEstimate {
+                gas: 0,
+                revert_reason: Some("NotEnoughCollateral".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn test_remove_bootstrap_calldata() {
+        let (manager, signer) = offline_manager();
+        let endpoint = "abc@127.0.0.1:26656".to_string();
+
+        let call = manager
+            .remove_bootstrap_call(signer, &test_subnet(), endpoint.clone())
+            .unwrap();
+        let data = call.tx.data().unwrap();
+
+        assert_eq!(&data[..4], ethers::utils::id("removeBootstrapNode(string)"));
+        assert_eq!(
+            ethers::abi::decode(&[ParamType::String], &data[4..]).unwrap(),
+            vec![Token::String(endpoint)]
+        );
+        assert_eq!(
+            call.tx.to_addr(),
+            Some(&contract_address_from_subnet(&test_subnet()).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_remove_bootstrap_invalid_endpoint() {
+        let (manager, signer) = offline_manager();
+
+        for endpoint in ["127.0.0.1:26656", "abc@localhost", "abc@127.0.0.1"] {
+            assert!(
+                manager
+                    .remove_bootstrap_call(signer.clone(), &test_subnet(), endpoint.to_string())
+                    .is_err(),
+                "{endpoint} should be rejected"
+            );
+        }
+    }
+    #[test]
+    fn test_ws_endpoint_from_subnet() {
+        let subnet = |provider_ws: Option<&str>| Subnet {
+            id: test_subnet(),
+            config: SubnetConfig::Fevm(EVMSubnet {
+                provider_http: "http://localhost:8545".parse().unwrap(),
+                provider_ws: provider_ws.map(|url| url.parse().unwrap()),
+                provider_timeout: None,
+                auth_token: Some("token".to_string()),
+                registry_addr: Address::from_str("f410ffzyuupbyl2uiucmzr3lu3mtf3luyknthaz4xsrq")
+                    .unwrap(),
+                gateway_addr: Address::from_str("f410ffzyuupbyl2uiucmzr3lu3mtf3luyknthaz4xsrq")
+                    .unwrap(),
+                checkpoint_submission: None,
+            }),
+        };
+
+        let manager = EthSubnetManager::from_subnet_with_wallet_store(&subnet(None), None).unwrap();
+        assert!(manager.ipc_contract_info.ws_endpoint.is_none());
+
+        let manager = EthSubnetManager::from_subnet_with_wallet_store(
+            &subnet(Some("ws://localhost:8546")),
+            None,
+        )
+        .unwrap();
+        let ws = manager.ipc_contract_info.ws_endpoint.unwrap();
+        assert_eq!(ws.url.as_str(), "ws://localhost:8546/");
+        assert_eq!(ws.auth_token.as_deref(), Some("token"));
+    }
 }
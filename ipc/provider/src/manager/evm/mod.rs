@@ -8,7 +8,7 @@ use fvm_shared::clock::ChainEpoch;
 use ipc_api::subnet_id::SubnetID;
 
 use super::subnet::SubnetManager;
-pub use manager::EthSubnetManager;
+pub use manager::{DefaultSignerMiddleware, EthSubnetManager};
 
 use ipc_actors_abis::subnet_actor_checkpointing_facet;
 
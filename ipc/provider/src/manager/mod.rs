@@ -2,10 +2,16 @@
 // SPDX-License-Identifier: MIT
 pub use crate::lotus::message::ipc::SubnetInfo;
 pub use evm::{EthManager, EthSubnetManager};
+pub use quorum::{
+    quorum_event_stream, quorum_event_stream_with_heads, QuorumEventSource,
+    DEFAULT_QUORUM_POLL_INTERVAL,
+};
 pub use subnet::{
-    BottomUpCheckpointRelayer, GetBlockHashResult, SubnetGenesisInfo, SubnetManager,
-    TopDownFinalityQuery, TopDownQueryPayload,
+    BottomUpCheckpointRelayer, CallEstimate, CheckpointSigners, CheckpointSubmission,
+    CrossMsgQuery, GetBlockHashResult, PendingSubmission, SubnetGenesisInfo, SubnetManager,
+    SubnetOperation, SubnetStatus, TopDownFinalityQuery, TopDownQueryPayload,
 };
 
 pub mod evm;
+mod quorum;
 mod subnet;
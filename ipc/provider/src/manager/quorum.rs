@@ -0,0 +1,268 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Streaming of the quorum reached events emitted by the gateway.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
+use fvm_shared::clock::ChainEpoch;
+use ipc_api::checkpoint::QuorumReachedEvent;
+
+use crate::manager::subnet::BottomUpCheckpointRelayer;
+
+/// The default interval between polls of the chain head when no new blocks are available.
+pub const DEFAULT_QUORUM_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// With a subscription to new blocks we only poll when one arrives, or after this long without
+/// any, in case the subscription stalled without closing.
+const NEW_HEADS_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The source of quorum reached events, queried height by height.
+#[async_trait]
+pub trait QuorumEventSource: Send + Sync {
+    /// The quorum reached events emitted at the given height.
+    async fn events_at(&self, height: ChainEpoch) -> Result<Vec<QuorumReachedEvent>>;
+    /// The latest height with events available.
+    async fn chain_head(&self) -> Result<ChainEpoch>;
+}
+
+#[async_trait]
+impl<T: BottomUpCheckpointRelayer + ?Sized> QuorumEventSource for T {
+    async fn events_at(&self, height: ChainEpoch) -> Result<Vec<QuorumReachedEvent>> {
+        self.quorum_reached_events(height).await
+    }
+
+    async fn chain_head(&self) -> Result<ChainEpoch> {
+        self.current_epoch().await
+    }
+}
+
+struct QuorumEventPoller<S: ?Sized> {
+    source: Arc<S>,
+    /// The next height to query, i.e. everything below has been yielded.
+    next_height: ChainEpoch,
+    pending: VecDeque<QuorumReachedEvent>,
+    poll_interval: Duration,
+    /// Notifications of new blocks, if subscribed to them.
+    new_heads: Option<BoxStream<'static, ()>>,
+}
+
+impl<S: QuorumEventSource + ?Sized> QuorumEventPoller<S> {
+    /// Queries the next height if available. Returns `false` if we are caught up with the head.
+    async fn poll(&mut self) -> Result<bool> {
+        let head = self.source.chain_head().await?;
+        if self.next_height > head {
+            return Ok(false);
+        }
+
+        let events = self.source.events_at(self.next_height).await?;
+        self.pending.extend(events);
+        self.next_height += 1;
+
+        Ok(true)
+    }
+
+    /// Wait until there might be a new height to query.
+    async fn wait(&mut self) {
+        let Some(new_heads) = self.new_heads.as_mut() else {
+            tokio::time::sleep(self.poll_interval).await;
+            return;
+        };
+        if let Ok(None) = tokio::time::timeout(NEW_HEADS_TIMEOUT, new_heads.next()).await {
+            tracing::warn!("new block subscription closed, falling back to polling");
+            self.new_heads = None;
+        }
+    }
+}
+
+/// Streams the quorum reached events starting at `from_height`, polling the source block by block.
+///
+/// The stream never ends on its own. Errors from the source are yielded and polling resumes from
+/// the last height that was not fully processed, so the stream survives reconnects of the
+/// underlying provider without skipping or repeating events.
+pub fn quorum_event_stream<S: QuorumEventSource + ?Sized + 'static>(
+    source: Arc<S>,
+    from_height: ChainEpoch,
+    poll_interval: Duration,
+) -> BoxStream<'static, Result<QuorumReachedEvent>> {
+    quorum_event_stream_with_heads(source, from_height, poll_interval, None)
+}
+
+/// Same as [`quorum_event_stream`], but once caught up with the chain head, it waits for the
+/// next item from `new_heads` to query the source again, instead of polling the head.
+///
+/// If `new_heads` ends, e.g. because the subscription was dropped by the node, it goes back
+/// to polling every `poll_interval`.
+pub fn quorum_event_stream_with_heads<S: QuorumEventSource + ?Sized + 'static>(
+    source: Arc<S>,
+    from_height: ChainEpoch,
+    poll_interval: Duration,
+    new_heads: Option<BoxStream<'static, ()>>,
+) -> BoxStream<'static, Result<QuorumReachedEvent>> {
+    let poller = QuorumEventPoller {
+        source,
+        next_height: from_height,
+        pending: VecDeque::new(),
+        poll_interval,
+        new_heads,
+    };
+
+    Box::pin(stream::unfold(poller, |mut poller| async move {
+        loop {
+            if let Some(event) = poller.pending.pop_front() {
+                return Some((Ok(event), poller));
+            }
+
+            match poller.poll().await {
+                Ok(true) => continue,
+                Ok(false) => poller.wait().await,
+                Err(e) => {
+                    tracing::warn!(
+                        "failed to query quorum events at height {}, retrying: {e}",
+                        poller.next_height
+                    );
+                    tokio::time::sleep(poller.poll_interval).await;
+                    return Some((Err(e), poller));
+                }
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use anyhow::anyhow;
+    use async_trait::async_trait;
+    use futures_util::stream::{self, BoxStream};
+    use futures_util::StreamExt;
+    use fvm_shared::clock::ChainEpoch;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::checkpoint::QuorumReachedEvent;
+
+    use super::{quorum_event_stream, quorum_event_stream_with_heads, QuorumEventSource};
+
+    /// Emits one event per height and fails the configured number of times at `fail_at`.
+    struct MockSource {
+        head: AtomicI64,
+        fail_at: ChainEpoch,
+        failures: AtomicUsize,
+        queried: Mutex<Vec<ChainEpoch>>,
+    }
+
+    impl MockSource {
+        fn new(head: ChainEpoch, fail_at: ChainEpoch, failures: usize) -> Self {
+            Self {
+                head: AtomicI64::new(head),
+                fail_at,
+                failures: AtomicUsize::new(failures),
+                queried: Mutex::new(vec![]),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl QuorumEventSource for MockSource {
+        async fn events_at(&self, height: ChainEpoch) -> anyhow::Result<Vec<QuorumReachedEvent>> {
+            self.queried.lock().unwrap().push(height);
+            if height == self.fail_at && self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                return Err(anyhow!("connection reset"));
+            }
+            Ok(vec![event_at(height)])
+        }
+
+        async fn chain_head(&self) -> anyhow::Result<ChainEpoch> {
+            Ok(self.head.load(Ordering::SeqCst))
+        }
+    }
+
+    fn event_at(height: ChainEpoch) -> QuorumReachedEvent {
+        QuorumReachedEvent {
+            obj_kind: 0,
+            height,
+            obj_hash: vec![height as u8; 32],
+            quorum_weight: TokenAmount::from_whole(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_from_height() {
+        let source = Arc::new(MockSource::new(10, -1, 0));
+        let events = quorum_event_stream(source, 5, Duration::from_millis(1))
+            .take(6)
+            .collect::<Vec<_>>()
+            .await;
+
+        let heights = events
+            .into_iter()
+            .map(|r| r.unwrap().height)
+            .collect::<Vec<_>>();
+        assert_eq!(heights, vec![5, 6, 7, 8, 9, 10]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_resumes_after_error() {
+        let source = Arc::new(MockSource::new(4, 2, 2));
+        let events = quorum_event_stream(source.clone(), 1, Duration::from_millis(1))
+            .take(6)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(events[0].as_ref().unwrap(), &event_at(1));
+        assert!(events[1].is_err());
+        assert!(events[2].is_err());
+        let heights = events[3..]
+            .iter()
+            .map(|r| r.as_ref().unwrap().height)
+            .collect::<Vec<_>>();
+        assert_eq!(heights, vec![2, 3, 4]);
+
+        // The failed height is re-queried and nothing before it.
+        assert_eq!(*source.queried.lock().unwrap(), vec![1, 2, 2, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_waits_for_new_heads() {
+        let source = Arc::new(MockSource::new(2, -1, 0));
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+        let new_heads: BoxStream<'static, ()> = Box::pin(stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|()| ((), rx))
+        }));
+
+        // Polling would only notice the new height after an hour.
+        let mut events = quorum_event_stream_with_heads(
+            source.clone(),
+            1,
+            Duration::from_secs(3600),
+            Some(new_heads),
+        );
+
+        for height in 1..=2 {
+            assert_eq!(events.next().await.unwrap().unwrap().height, height);
+        }
+
+        // Caught up, so it waits for a new head.
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), events.next())
+                .await
+                .is_err()
+        );
+
+        source.head.store(3, Ordering::SeqCst);
+        tx.send(()).unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .expect("the new head should trigger a query");
+        assert_eq!(event.unwrap().unwrap().height, 3);
+    }
+}
@@ -2,16 +2,20 @@
 // SPDX-License-Identifier: MIT
 
 use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use ethers::types::U256;
+use futures_util::stream::BoxStream;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::{address::Address, econ::TokenAmount};
 use ipc_api::checkpoint::{
     BottomUpCheckpoint, BottomUpCheckpointBundle, QuorumReachedEvent, Signature,
 };
 use ipc_api::cross::IpcEnvelope;
-use ipc_api::staking::{StakingChangeRequest, ValidatorInfo};
+use ipc_api::staking::{StakingChangeRequest, ValidatorInfo, ValidatorSetInfo};
 use ipc_api::subnet::{ConstructParams, PermissionMode, SupplySource};
 use ipc_api::subnet_id::SubnetID;
 use ipc_api::validator::Validator;
@@ -20,7 +24,9 @@ use crate::lotus::message::ipc::SubnetInfo;
 
 /// Trait to interact with a subnet and handle its lifecycle.
 #[async_trait]
-pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpointRelayer {
+pub trait SubnetManager:
+    Send + Sync + TopDownFinalityQuery + BottomUpCheckpointRelayer + CrossMsgQuery
+{
     /// Deploys a new subnet actor on the `parent` subnet and with the
     /// configuration passed in `ConstructParams`.
     /// The result of the function is the ID address for the subnet actor from which the final
@@ -168,8 +174,13 @@ pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpoint
         subnet: &SubnetID,
     ) -> Result<ipc_actors_abis::subnet_actor_getter_facet::SupplySource>;
 
-    /// Gets the genesis information required to bootstrap a child subnet
-    async fn get_genesis_info(&self, subnet: &SubnetID) -> Result<SubnetGenesisInfo>;
+    /// Gets the genesis information required to bootstrap a child subnet, as of the parent
+    /// `height` if given, otherwise as of the latest block.
+    async fn get_genesis_info(
+        &self,
+        subnet: &SubnetID,
+        height: Option<ChainEpoch>,
+    ) -> Result<SubnetGenesisInfo>;
 
     /// Advertises the endpoint of a bootstrap node for the subnet.
     async fn add_bootstrap(
@@ -179,6 +190,14 @@ pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpoint
         endpoint: String,
     ) -> Result<()>;
 
+    /// Removes the endpoint of a bootstrap node previously advertised by `from`.
+    async fn remove_bootstrap(
+        &self,
+        subnet: &SubnetID,
+        from: &Address,
+        endpoint: String,
+    ) -> Result<()>;
+
     /// Lists the bootstrap nodes of a subnet
     async fn list_bootstrap_nodes(&self, subnet: &SubnetID) -> Result<Vec<String>>;
 
@@ -189,6 +208,13 @@ pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpoint
         validator: &Address,
     ) -> Result<ValidatorInfo>;
 
+    /// Lists all the validators of the subnet, both active and waiting, with their
+    /// staking information.
+    async fn list_validators(&self, subnet: &SubnetID) -> Result<ValidatorSetInfo>;
+
+    /// Summarizes whether the subnet actor exists and is usable, reading from its getter facet.
+    async fn subnet_status(&self, subnet: &SubnetID) -> Result<SubnetStatus>;
+
     async fn set_federated_power(
         &self,
         from: &Address,
@@ -197,6 +223,142 @@ pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpoint
         public_keys: &[Vec<u8>],
         federated_power: &[u128],
     ) -> Result<ChainEpoch>;
+
+    /// Simulates a state changing operation without sending the transaction, returning the
+    /// estimated gas or the decoded reason why it would revert.
+    async fn estimate(&self, operation: SubnetOperation) -> Result<CallEstimate>;
+}
+
+/// The state changing operations of the [`SubnetManager`], with the same arguments as the
+/// corresponding methods, so they can be estimated without being sent.
+#[derive(Debug, Clone)]
+pub enum SubnetOperation {
+    CreateSubnet {
+        from: Address,
+        params: ConstructParams,
+    },
+    JoinSubnet {
+        subnet: SubnetID,
+        from: Address,
+        collateral: TokenAmount,
+        metadata: Vec<u8>,
+    },
+    PreFund {
+        subnet: SubnetID,
+        from: Address,
+        balance: TokenAmount,
+    },
+    PreRelease {
+        subnet: SubnetID,
+        from: Address,
+        amount: TokenAmount,
+    },
+    Stake {
+        subnet: SubnetID,
+        from: Address,
+        collateral: TokenAmount,
+    },
+    Unstake {
+        subnet: SubnetID,
+        from: Address,
+        collateral: TokenAmount,
+    },
+    LeaveSubnet {
+        subnet: SubnetID,
+        from: Address,
+    },
+    KillSubnet {
+        subnet: SubnetID,
+        from: Address,
+    },
+    ClaimCollateral {
+        subnet: SubnetID,
+        from: Address,
+    },
+    Fund {
+        subnet: SubnetID,
+        gateway_addr: Address,
+        from: Address,
+        to: Address,
+        amount: TokenAmount,
+    },
+    ApproveToken {
+        subnet: SubnetID,
+        from: Address,
+        amount: TokenAmount,
+    },
+    FundWithToken {
+        subnet: SubnetID,
+        from: Address,
+        to: Address,
+        amount: TokenAmount,
+    },
+    Release {
+        gateway_addr: Address,
+        from: Address,
+        to: Address,
+        amount: TokenAmount,
+    },
+    Propagate {
+        gateway_addr: Address,
+        from: Address,
+        postbox_msg_key: Vec<u8>,
+    },
+    SendValue {
+        from: Address,
+        to: Address,
+        amount: TokenAmount,
+    },
+    AddBootstrap {
+        subnet: SubnetID,
+        from: Address,
+        endpoint: String,
+    },
+    RemoveBootstrap {
+        subnet: SubnetID,
+        from: Address,
+        endpoint: String,
+    },
+    SetFederatedPower {
+        from: Address,
+        subnet: SubnetID,
+        validators: Vec<Address>,
+        public_keys: Vec<Vec<u8>>,
+        federated_power: Vec<u128>,
+    },
+}
+
+/// The outcome of simulating a [`SubnetOperation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallEstimate {
+    /// The estimated gas of the transaction, zero if it reverts.
+    pub gas: u64,
+    /// The decoded revert reason if the transaction would fail.
+    pub revert_reason: Option<String>,
+}
+
+/// The state of a subnet actor in its parent, see [`SubnetManager::subnet_status`].
+///
+/// If the actor doesn't exist, all the other fields are left at their defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubnetStatus {
+    /// Whether the subnet actor is deployed in the parent.
+    pub exists: bool,
+    /// Whether the subnet reached the collateral and number of validators it needs to start.
+    pub bootstrapped: bool,
+    pub killed: bool,
+    /// Total confirmed collateral of the validators.
+    pub stake: TokenAmount,
+    /// Number of validators, active and waiting.
+    pub validators: u16,
+    pub active_validators: u16,
+}
+
+impl SubnetStatus {
+    /// Whether the subnet can be funded or joined as a running subnet.
+    pub fn is_active(&self) -> bool {
+        self.exists && self.bootstrapped && !self.killed
+    }
 }
 
 #[derive(Debug)]
@@ -251,6 +413,57 @@ pub trait TopDownFinalityQuery: Send + Sync {
     async fn latest_parent_finality(&self) -> Result<ChainEpoch>;
 }
 
+/// Queries of the gateway to follow cross-net messages to their destination.
+#[async_trait]
+pub trait CrossMsgQuery: Send + Sync {
+    /// The nonce the gateway will assign to the next top-down message to `subnet_id`.
+    async fn top_down_nonce(&self, subnet_id: &SubnetID) -> Result<u64>;
+    /// The number of top-down messages from the parent executed by the gateway.
+    async fn applied_top_down_nonce(&self) -> Result<u64>;
+    /// The nonce the gateway will assign to the next bottom-up message to the parent.
+    async fn bottom_up_nonce(&self) -> Result<u64>;
+    /// The number of bottom-up messages from `subnet_id` executed by the gateway.
+    async fn applied_bottom_up_nonce(&self, subnet_id: &SubnetID) -> Result<u64>;
+}
+
+/// A checkpoint submission that has been sent but not yet confirmed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingSubmission {
+    /// The nonce of the submission transaction, used to replace it.
+    pub nonce: u64,
+    /// The gas premium the submission transaction was sent with.
+    pub gas_premium: TokenAmount,
+}
+
+/// The validators whose signatures a bottom-up checkpoint needs, as the subnet actor
+/// in the parent sees them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckpointSigners {
+    /// The power of each active validator by its Ethereum address, which weighs its signature.
+    pub power: HashMap<ethers::types::Address, U256>,
+    /// The percentage of the total active power the signatures have to add up to.
+    pub majority_percentage: u8,
+}
+
+impl CheckpointSigners {
+    /// The weight the signatures have to reach, calculated the same way as in the subnet actor.
+    pub fn threshold(&self) -> U256 {
+        let total = self.power.values().fold(U256::zero(), |acc, p| acc + *p);
+        total * U256::from(self.majority_percentage) / U256::from(100)
+    }
+}
+
+/// The outcome of a single attempt to submit a bottom-up checkpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckpointSubmission {
+    /// The checkpoint was executed in the parent at the given epoch.
+    Executed(ChainEpoch),
+    /// The submission was not confirmed before the timeout.
+    Pending(PendingSubmission),
+    /// The checkpoint had already been committed, e.g. by another relayer.
+    AlreadySubmitted,
+}
+
 /// The bottom up checkpoint manager that handles the bottom up relaying from child subnet to the parent
 /// subnet.
 #[async_trait]
@@ -265,10 +478,25 @@ pub trait BottomUpCheckpointRelayer: Send + Sync {
         signatures: Vec<Signature>,
         signatories: Vec<Address>,
     ) -> Result<ChainEpoch>;
+    /// Submit a checkpoint and wait at most `timeout` for it to be executed.
+    /// If `replace` is given, the pending submission with the same nonce is replaced using
+    /// its gas premium, otherwise the gas premium is estimated.
+    async fn submit_checkpoint_with_timeout(
+        &self,
+        submitter: &Address,
+        checkpoint: BottomUpCheckpoint,
+        signatures: Vec<Signature>,
+        signatories: Vec<Address>,
+        replace: Option<PendingSubmission>,
+        timeout: Duration,
+    ) -> Result<CheckpointSubmission>;
     /// The last confirmed/submitted checkpoint height.
     async fn last_bottom_up_checkpoint_height(&self, subnet_id: &SubnetID) -> Result<ChainEpoch>;
     /// Get the checkpoint period, i.e the number of blocks to submit bottom up checkpoints.
     async fn checkpoint_period(&self, subnet_id: &SubnetID) -> Result<ChainEpoch>;
+    /// Get the active validators of the subnet with their power, to verify the signatures
+    /// of a checkpoint before submitting it.
+    async fn checkpoint_signers(&self, subnet_id: &SubnetID) -> Result<CheckpointSigners>;
     /// Get the checkpoint bundle at a specific height. If it does not exist, it will through error.
     async fn checkpoint_bundle_at(
         &self,
@@ -276,6 +504,12 @@ pub trait BottomUpCheckpointRelayer: Send + Sync {
     ) -> Result<Option<BottomUpCheckpointBundle>>;
     /// Queries the signature quorum reached events at target height.
     async fn quorum_reached_events(&self, height: ChainEpoch) -> Result<Vec<QuorumReachedEvent>>;
+    /// Streams the signature quorum reached events starting at `from_height`.
+    /// The stream keeps following the chain and resumes from the last seen height on errors.
+    fn subscribe_quorum_events(
+        self: Arc<Self>,
+        from_height: ChainEpoch,
+    ) -> BoxStream<'static, Result<QuorumReachedEvent>>;
     /// Get the current epoch in the current subnet
     async fn current_epoch(&self) -> Result<ChainEpoch>;
 }
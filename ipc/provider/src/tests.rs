@@ -0,0 +1,214 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use ipc_api::ethers_address_to_fil_address;
+use ipc_api::subnet_id::SubnetID;
+use url::Url;
+
+use crate::config::subnet::{EVMSubnet, SubnetConfig};
+use crate::config::Subnet;
+use crate::error::ProviderError;
+use crate::{IpcProvider, CONNECTIONS_CREATED, MAX_TRANSPORT_ERRORS};
+
+/// A provider configured with the root network only and no wallet.
+fn root_provider(root: &SubnetID) -> IpcProvider {
+    IpcProvider::new_with_subnet(None, root_subnet(root)).unwrap()
+}
+
+fn root_subnet(root: &SubnetID) -> Subnet {
+    let contract = ethers_address_to_fil_address(&ethers::types::Address::zero()).unwrap();
+    Subnet {
+        id: root.clone(),
+        config: SubnetConfig::Fevm(EVMSubnet {
+            provider_http: Url::from_str("http://127.0.0.1:8545").unwrap(),
+            provider_ws: None,
+            provider_timeout: None,
+            auth_token: None,
+            registry_addr: contract,
+            gateway_addr: contract,
+            checkpoint_submission: None,
+        }),
+    }
+}
+
+#[tokio::test]
+async fn test_subnet_not_configured() {
+    let root = SubnetID::new_root(314159);
+    let provider = root_provider(&root);
+
+    let unknown = SubnetID::new_root(123);
+    let err = provider.chain_head(&unknown).await.unwrap_err();
+    match err {
+        ProviderError::SubnetNotConfigured { subnet, known } => {
+            assert_eq!(subnet, unknown);
+            assert_eq!(known, vec![root.to_string()]);
+        }
+        e => panic!("unexpected error: {e}"),
+    }
+
+    // The parent of the child is not configured either.
+    let child = SubnetID::new_from_parent(&unknown, Address::new_id(1000));
+    let err = provider.list_validators(&child).await.unwrap_err();
+    assert!(matches!(err, ProviderError::SubnetNotConfigured { .. }));
+}
+
+#[tokio::test]
+async fn test_missing_parent() {
+    let root = SubnetID::new_root(314159);
+    let provider = root_provider(&root);
+
+    let err = provider.genesis_epoch(&root).await.unwrap_err();
+    assert!(matches!(err, ProviderError::MissingParent(subnet) if subnet == root));
+}
+
+#[tokio::test]
+async fn test_wallet_missing() {
+    let root = SubnetID::new_root(314159);
+    let mut provider = root_provider(&root);
+
+    assert!(matches!(
+        provider.evm_wallet(),
+        Err(ProviderError::WalletMissing("evm"))
+    ));
+    assert!(matches!(
+        provider.fvm_wallet(),
+        Err(ProviderError::WalletMissing("fvm"))
+    ));
+
+    // Without a `from` the provider falls back to the default key of the wallet.
+    let child = SubnetID::new_from_parent(&root, Address::new_id(1000));
+    let err = provider
+        .join_subnet(child, None, TokenAmount::from_whole(1))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ProviderError::WalletMissing("evm")));
+}
+
+#[tokio::test]
+async fn test_no_default_sender() {
+    let root = SubnetID::new_root(314159);
+    let repo = tempfile::tempdir().unwrap();
+    let mut provider = IpcProvider::new_with_subnet(
+        Some(repo.path().to_string_lossy().to_string()),
+        root_subnet(&root),
+    )
+    .unwrap();
+
+    // The wallet is there, but it's empty.
+    let child = SubnetID::new_from_parent(&root, Address::new_id(1000));
+    let err = provider
+        .join_subnet(child, None, TokenAmount::from_whole(1))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ProviderError::NoDefaultSender));
+}
+
+#[tokio::test]
+async fn test_reload_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.toml");
+    let subnet = |id: &str| {
+        format!(
+            r#"
+[[subnets]]
+id = "{id}"
+
+[subnets.config]
+network_type = "fevm"
+provider_http = "http://127.0.0.1:8545"
+gateway_addr = "0x77aa40b105843728088c0132e43fc44348881da8"
+registry_addr = "0x74539671a1d2f1c8f200826baba665179f53a1b7"
+"#
+        )
+    };
+    let header = format!("keystore_path = {:?}\n", dir.path().to_string_lossy());
+
+    std::fs::write(&config_path, format!("{header}{}", subnet("/r314159"))).unwrap();
+    let provider =
+        IpcProvider::new_from_config(config_path.to_string_lossy().to_string(), None).unwrap();
+    // A clone, e.g. one handed to another component, sees the reloads as well.
+    let clone = provider.clone();
+
+    let root = SubnetID::new_root(314159);
+    let child =
+        SubnetID::from_str("/r314159/t410f5kaxlxjakwsjlxeulvcqjamx5ru5mbyyqoqp4oq").unwrap();
+    assert!(provider.connection(&child).is_none());
+
+    // Add a subnet.
+    std::fs::write(
+        &config_path,
+        format!(
+            "{header}{}{}",
+            subnet("/r314159"),
+            subnet(&child.to_string())
+        ),
+    )
+    .unwrap();
+
+    let conn = provider.connection(&root).unwrap();
+    provider.reload_config().unwrap();
+
+    let subnets = clone.list_connections();
+    assert_eq!(subnets.len(), 2);
+    assert!(subnets.contains_key(&child));
+    assert!(clone.connection(&child).is_some());
+    // The connections made with the old config are not reused.
+    assert!(!Arc::ptr_eq(&conn, &clone.connection(&root).unwrap()));
+
+    // Remove the root; the connection created before the reload keeps working.
+    std::fs::write(
+        &config_path,
+        format!("{header}{}", subnet(&child.to_string())),
+    )
+    .unwrap();
+    provider.reload_config().unwrap();
+
+    assert!(provider.connection(&root).is_none());
+    assert_eq!(conn.subnet().id, root);
+
+    // An invalid config is not loaded.
+    std::fs::write(&config_path, "[[subnets]]").unwrap();
+    assert!(provider.reload_config().is_err());
+    assert_eq!(provider.list_connections().len(), 1);
+}
+
+#[test]
+fn test_connection_is_reused() {
+    let root = SubnetID::new_root(314159);
+    let provider = root_provider(&root);
+    let created = || CONNECTIONS_CREATED.with(|c| c.get());
+
+    let before = created();
+    let conn = provider.connection(&root).unwrap();
+    for _ in 0..10 {
+        // Clones share the connections as well.
+        let again = provider.clone().connection(&root).unwrap();
+        assert!(Arc::ptr_eq(&conn, &again));
+    }
+    assert_eq!(created() - before, 1);
+}
+
+#[tokio::test]
+async fn test_connection_dropped_after_transport_errors() {
+    let root = SubnetID::new_root(314159);
+    let mut subnet = root_subnet(&root);
+    // Nothing listens on port 1, so the connection is refused.
+    let SubnetConfig::Fevm(ref mut config) = subnet.config;
+    config.provider_http = Url::from_str("http://127.0.0.1:1").unwrap();
+    let provider = IpcProvider::new_with_subnet(None, subnet).unwrap();
+
+    let conn = provider.connection(&root).unwrap();
+    for _ in 0..MAX_TRANSPORT_ERRORS {
+        assert!(Arc::ptr_eq(&conn, &provider.connection(&root).unwrap()));
+        let err = provider.chain_head(&root).await.unwrap_err();
+        assert!(matches!(err, ProviderError::RpcTransport(_)));
+    }
+
+    // The next call connects again.
+    assert!(!Arc::ptr_eq(&conn, &provider.connection(&root).unwrap()));
+}
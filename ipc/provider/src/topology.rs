@@ -0,0 +1,181 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Enumeration of the subnet tree below a subnet, following the child subnets of each gateway.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+
+use anyhow::Result;
+use ipc_api::subnet_id::SubnetID;
+
+use crate::manager::SubnetInfo;
+
+/// The maximum number of subnets [`list_subnets_recursive`] returns from the provider.
+pub const MAX_RECURSIVE_SUBNETS: usize = 1000;
+
+/// A subnet found while walking the subnet tree.
+#[derive(Debug)]
+pub struct SubnetNode {
+    /// The subnet whose gateway lists this subnet as a child.
+    pub parent: SubnetID,
+    /// Distance from the subnet the walk started from; direct children are at depth 1.
+    pub depth: usize,
+    pub info: SubnetInfo,
+}
+
+/// Walk the subnet tree below `root` breadth-first, down to `max_depth` levels.
+///
+/// `list_children` returns the child subnets registered in the gateway of a subnet,
+/// or `None` if the subnet can't be queried, in which case it is left as a leaf.
+/// Subnets which have already been visited are not listed again, in case a gateway
+/// reports one of its ancestors, and the walk stops once `max_subnets` have been found.
+pub async fn list_subnets_recursive<F, Fut>(
+    root: &SubnetID,
+    max_depth: usize,
+    max_subnets: usize,
+    mut list_children: F,
+) -> Result<HashMap<SubnetID, SubnetNode>>
+where
+    F: FnMut(SubnetID) -> Fut,
+    Fut: Future<Output = Result<Option<HashMap<SubnetID, SubnetInfo>>>>,
+{
+    let mut nodes = HashMap::new();
+    let mut visited = HashSet::from([root.clone()]);
+    let mut queue = VecDeque::from([(root.clone(), 0)]);
+
+    while let Some((parent, depth)) = queue.pop_front() {
+        if depth >= max_depth {
+            continue;
+        }
+
+        let Some(children) = list_children(parent.clone()).await? else {
+            tracing::debug!(subnet = parent.to_string(), "cannot list the child subnets");
+            continue;
+        };
+
+        // Sort the children so the same subnets are returned when the cap is hit.
+        let mut children = children.into_iter().collect::<Vec<_>>();
+        children.sort_by_key(|(id, _)| id.to_string());
+
+        for (id, info) in children {
+            if !visited.insert(id.clone()) {
+                tracing::warn!(
+                    subnet = id.to_string(),
+                    parent = parent.to_string(),
+                    "subnet already visited"
+                );
+                continue;
+            }
+            if nodes.len() >= max_subnets {
+                tracing::warn!(max_subnets, "too many subnets; stopping the enumeration");
+                return Ok(nodes);
+            }
+            queue.push_back((id.clone(), depth + 1));
+            nodes.insert(
+                id,
+                SubnetNode {
+                    parent: parent.clone(),
+                    depth: depth + 1,
+                    info,
+                },
+            );
+        }
+    }
+
+    Ok(nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::list_subnets_recursive;
+    use crate::manager::SubnetInfo;
+
+    const ROOT: &str = "/r123";
+    const A: &str = "/r123/f0100";
+    const B: &str = "/r123/f0101";
+    const AA: &str = "/r123/f0100/f0102";
+
+    fn id(s: &str) -> SubnetID {
+        SubnetID::from_str(s).unwrap()
+    }
+
+    /// The gateway of each subnet with its children; subnets missing from it can't be queried.
+    fn tree(edges: &[(&str, &[&str])]) -> HashMap<SubnetID, Vec<SubnetID>> {
+        edges
+            .iter()
+            .map(|(parent, children)| (id(parent), children.iter().map(|c| id(c)).collect()))
+            .collect()
+    }
+
+    async fn walk(
+        tree: &HashMap<SubnetID, Vec<SubnetID>>,
+        max_depth: usize,
+        max_subnets: usize,
+    ) -> HashMap<SubnetID, (SubnetID, usize)> {
+        list_subnets_recursive(&id(ROOT), max_depth, max_subnets, |subnet| async move {
+            Ok(tree.get(&subnet).map(|children| {
+                children
+                    .iter()
+                    .map(|c| {
+                        let info = SubnetInfo {
+                            id: c.clone(),
+                            stake: TokenAmount::default(),
+                            circ_supply: TokenAmount::default(),
+                            genesis_epoch: 0,
+                        };
+                        (c.clone(), info)
+                    })
+                    .collect()
+            }))
+        })
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(id, node)| (id, (node.parent, node.depth)))
+        .collect()
+    }
+
+    #[tokio::test]
+    async fn walks_the_tree_breadth_first() {
+        let tree = tree(&[(ROOT, &[A, B]), (A, &[AA]), (AA, &[])]);
+
+        let nodes = walk(&tree, 10, 100).await;
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[&id(A)], (id(ROOT), 1));
+        assert_eq!(nodes[&id(B)], (id(ROOT), 1));
+        assert_eq!(nodes[&id(AA)], (id(A), 2));
+
+        let nodes = walk(&tree, 1, 100).await;
+        assert_eq!(nodes.len(), 2);
+        assert!(!nodes.contains_key(&id(AA)));
+
+        assert!(walk(&tree, 0, 100).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn guards_against_cycles() {
+        // A misbehaving gateway lists the root and its sibling as its children.
+        let tree = tree(&[(ROOT, &[A, B]), (A, &[ROOT, B, AA]), (AA, &[A])]);
+
+        let nodes = walk(&tree, 10, 100).await;
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[&id(B)], (id(ROOT), 1));
+        assert_eq!(nodes[&id(AA)], (id(A), 2));
+    }
+
+    #[tokio::test]
+    async fn caps_the_results() {
+        let tree = tree(&[(ROOT, &[A, B]), (A, &[AA])]);
+
+        let nodes = walk(&tree, 10, 2).await;
+        assert_eq!(nodes.len(), 2);
+        assert!(nodes.contains_key(&id(A)));
+        assert!(nodes.contains_key(&id(B)));
+    }
+}
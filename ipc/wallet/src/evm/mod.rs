@@ -5,6 +5,8 @@
 
 mod memory;
 mod persistent;
+#[cfg(feature = "with-ethers")]
+mod signer;
 
 use anyhow::Result;
 use std::fmt::{Display, Formatter};
@@ -15,9 +17,16 @@ use zeroize::Zeroize;
 use std::str::FromStr;
 
 pub use crate::evm::persistent::{PersistentKeyInfo, PersistentKeyStore};
+#[cfg(feature = "with-ledger")]
+pub use crate::evm::signer::LedgerWallet;
+#[cfg(feature = "with-ethers")]
+pub use crate::evm::signer::{HardwareWallet, SignerBackend, SignerBackendError, SignerSelector};
 
 pub const DEFAULT_KEYSTORE_NAME: &str = "evm_keystore.json";
 
+/// Environment variable holding the password of an encrypted key store.
+pub const KEYSTORE_PASSWORD_ENV: &str = "IPC_KEYSTORE_PASSWORD";
+
 /// The key store trait for different evm key store
 pub trait KeyStore {
     /// The type of the key that is stored
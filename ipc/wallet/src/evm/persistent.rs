@@ -5,6 +5,7 @@
 
 use crate::evm::memory::MemoryKeyStore;
 use crate::evm::{KeyInfo, KeyStore};
+use crate::fvm::keystore::{EncryptedKeyStore, SaltByteArray};
 use anyhow::anyhow;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -12,14 +13,16 @@ use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::hash::Hash;
-use std::io::{BufReader, BufWriter, ErrorKind};
-use std::path::PathBuf;
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
 use zeroize::Zeroize;
 
 #[derive(Default)]
 pub struct PersistentKeyStore<T> {
     memory: MemoryKeyStore<T>,
     file_path: PathBuf,
+    /// Set if the private keys are encrypted on disk, with a key derived from a password.
+    encryption: Option<EncryptedKeyStore>,
 }
 
 /// The persistent key information written to disk
@@ -28,8 +31,11 @@ pub struct PersistentKeyInfo {
     /// The address associated with the private key. We can derive this from the private key
     /// but for the ease of debugging, we keep this field
     address: String,
-    /// Hex encoded private key
+    /// Hex encoded private key, encrypted if the key store is password protected
     private_key: String,
+    /// Hex encoded salt of the password key derivation, only set if the private key is encrypted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salt: Option<String>,
 }
 
 impl PersistentKeyInfo {
@@ -37,6 +43,7 @@ impl PersistentKeyInfo {
         Self {
             address,
             private_key,
+            salt: None,
         }
     }
 
@@ -66,29 +73,73 @@ impl<T: Clone + Eq + Hash + TryFrom<KeyInfo> + Default + ToString> KeyStore
 
     fn put(&mut self, info: KeyInfo) -> Result<Self::Key> {
         let addr = self.memory.put(info)?;
-        self.flush_no_encryption()?;
+        self.flush()?;
         Ok(addr)
     }
 
     fn remove(&mut self, addr: &Self::Key) -> Result<()> {
         self.memory.remove(addr)?;
-        self.flush_no_encryption()
+        self.flush()
     }
 
     fn set_default(&mut self, addr: &Self::Key) -> Result<()> {
         self.memory.set_default(addr)?;
-        self.flush_no_encryption()
+        self.flush()
     }
 
     fn get_default(&mut self) -> Result<Option<Self::Key>> {
-        let default = self.memory.get_default()?;
-        self.flush_no_encryption()?;
-        Ok(default)
+        self.memory.get_default()
     }
 }
 
 impl<T: Clone + Eq + Hash + TryFrom<KeyInfo> + Default + ToString> PersistentKeyStore<T> {
+    /// Opens the plaintext key store at `path`, or initializes an empty one if it does not exist.
     pub fn new(path: PathBuf) -> Result<Self> {
+        Self::open(path, None)
+    }
+
+    /// Opens the key store at `path` whose private keys are encrypted with a key derived from
+    /// `password`, or initializes an empty one if it does not exist.
+    ///
+    /// Keys are decrypted when the store is opened, so a wrong password fails here and not on `get`.
+    pub fn new_encrypted(path: PathBuf, password: &str) -> Result<Self> {
+        Self::open(path, Some(password))
+    }
+
+    /// Encrypts the existing plaintext key store at `path` in place, with a key derived from `password`.
+    pub fn encrypt(path: PathBuf, password: &str) -> Result<Self> {
+        let mut store = Self::new(path)?;
+        store.encryption = Some(derive_encryption(password, None)?);
+        store.flush()?;
+
+        log::info!("encrypted key store at: {:?}", store.file_path);
+
+        Ok(store)
+    }
+
+    /// Whether the private keys are encrypted on disk.
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption.is_some()
+    }
+
+    /// Whether the private keys of the key store at `path` are encrypted, without decrypting
+    /// them, e.g. to find out if a password is needed to open it.
+    ///
+    /// A key store which does not exist yet is not encrypted.
+    pub fn is_encrypted_at(path: &Path) -> Result<bool> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(anyhow!("cannot open key store: {e:}")),
+        };
+        let persisted_key_info: Vec<PersistentKeyInfo> =
+            serde_json::from_reader(BufReader::new(file))
+                .map_err(|e| anyhow!("failed to deserialize keyfile at {:?}: {e:}", path))?;
+
+        Ok(persisted_key_info.iter().any(|i| i.salt.is_some()))
+    }
+
+    fn open(path: PathBuf, password: Option<&str>) -> Result<Self> {
         if let Some(p) = path.parent() {
             if !p.exists() {
                 return Err(anyhow!("parent does not exist for key store"));
@@ -106,6 +157,9 @@ impl<T: Clone + Eq + Hash + TryFrom<KeyInfo> + Default + ToString> PersistentKey
                             default: None,
                         },
                         file_path: path,
+                        encryption: password
+                            .map(|password| derive_encryption(password, None))
+                            .transpose()?,
                     })
                 } else {
                     Err(anyhow!("cannot create key store: {e:}"))
@@ -122,11 +176,48 @@ impl<T: Clone + Eq + Hash + TryFrom<KeyInfo> + Default + ToString> PersistentKey
                 )
             })?;
 
+        // All keys are encrypted with the same salt, so the key only needs to be derived once.
+        let encryption = match password {
+            Some(password) => {
+                let salt = match persisted_key_info.first().and_then(|i| i.salt.as_ref()) {
+                    Some(salt) => Some(decode_salt(salt)?),
+                    None => None,
+                };
+                Some(derive_encryption(password, salt)?)
+            }
+            None => None,
+        };
+
         let mut key_infos = HashMap::new();
         for info in persisted_key_info.iter() {
-            let key_info = KeyInfo {
-                private_key: hex::decode(&info.private_key)?,
+            let private_key = match (&info.salt, &encryption) {
+                (None, None) => hex::decode(&info.private_key)?,
+                (Some(_), None) => {
+                    return Err(anyhow!(
+                        "key store at {:?} is encrypted, a password is required",
+                        path
+                    ))
+                }
+                (None, Some(_)) => {
+                    return Err(anyhow!(
+                        "key store at {:?} is not encrypted, it has to be encrypted first, e.g. with `ipc-cli wallet encrypt`",
+                        path
+                    ))
+                }
+                (Some(salt), Some(encryption)) => {
+                    if decode_salt(salt)? != encryption.salt {
+                        return Err(anyhow!(
+                            "keys of the key store at {:?} are encrypted with different salts",
+                            path
+                        ));
+                    }
+                    let data = hex::decode(&info.private_key)?;
+                    EncryptedKeyStore::decrypt(&encryption.encryption_key, &data).map_err(|_| {
+                        anyhow!("failed to decrypt key store at {:?}, wrong password?", path)
+                    })?
+                }
             };
+            let key_info = KeyInfo { private_key };
             let mut addr = T::default();
             // only infer the address if this is not the default key
             if info.address != addr.to_string() {
@@ -151,11 +242,14 @@ impl<T: Clone + Eq + Hash + TryFrom<KeyInfo> + Default + ToString> PersistentKey
                 default,
             },
             file_path: path,
+            encryption,
         })
     }
 
-    /// Write all keys to file without any encryption.
-    fn flush_no_encryption(&self) -> Result<()> {
+    /// Write all keys to file, encrypting the private keys if the key store has a password.
+    ///
+    /// The keys are written to a temporary file first, so a failure can't truncate the key store.
+    fn flush(&self) -> Result<()> {
         let dir = self
             .file_path
             .parent()
@@ -163,37 +257,67 @@ impl<T: Clone + Eq + Hash + TryFrom<KeyInfo> + Default + ToString> PersistentKey
 
         fs::create_dir_all(dir)?;
 
-        let file = File::create(&self.file_path)?;
+        let tmp_path = self.file_path.with_extension("tmp");
+        let file = File::create(&tmp_path)?;
 
-        // TODO: do we need to set path permission?
+        // Restrict permissions on files containing private keys
+        #[cfg(unix)]
+        crate::fvm::utils::set_user_perm(&file)?;
 
-        let writer = BufWriter::new(file);
+        let mut writer = BufWriter::new(file);
 
         let to_persist = self
             .memory
             .data
             .iter()
             .map(|(key, val)| {
-                let private_key = hex::encode(&val.private_key);
+                let (private_key, salt) = match &self.encryption {
+                    Some(encryption) => {
+                        let data = EncryptedKeyStore::encrypt(
+                            &encryption.encryption_key,
+                            &val.private_key,
+                        )?;
+                        (hex::encode(data), Some(hex::encode(encryption.salt)))
+                    }
+                    None => (hex::encode(&val.private_key), None),
+                };
                 let address = key.to_string();
-                PersistentKeyInfo {
+                Ok(PersistentKeyInfo {
                     address,
                     private_key,
-                }
+                    salt,
+                })
             })
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>>>()?;
 
-        serde_json::to_writer_pretty(writer, &to_persist)
+        serde_json::to_writer_pretty(&mut writer, &to_persist)
             .map_err(|e| anyhow!("failed to serialize and write key info: {e}"))?;
+        writer.flush()?;
+
+        fs::rename(&tmp_path, &self.file_path)?;
 
         Ok(())
     }
 }
 
+fn derive_encryption(password: &str, salt: Option<SaltByteArray>) -> Result<EncryptedKeyStore> {
+    let (salt, encryption_key) = EncryptedKeyStore::derive_key(password, salt)?;
+    Ok(EncryptedKeyStore {
+        salt,
+        encryption_key,
+    })
+}
+
+fn decode_salt(salt: &str) -> Result<SaltByteArray> {
+    hex::decode(salt)?
+        .try_into()
+        .map_err(|_| anyhow!("invalid key store salt length"))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::evm::KeyInfo;
-    use crate::{EvmKeyStore, PersistentKeyStore};
+    use crate::{EvmKeyStore, PersistentKeyInfo, PersistentKeyStore};
     use std::fmt::{Display, Formatter};
 
     #[derive(Clone, Eq, PartialEq, Hash, Debug)]
@@ -287,4 +411,110 @@ mod tests {
         // the default is also recovered from persistent storage
         assert_eq!(ks.get_default().unwrap().unwrap(), new_addr);
     }
+
+    const PASSWORD: &str = "foobarbaz";
+
+    #[test]
+    fn test_encrypted_keystore_roundtrip() {
+        let keystore_folder = tempfile::tempdir().unwrap().into_path();
+        let keystore_location = keystore_folder.join("eth_keystore");
+
+        let mut ks =
+            PersistentKeyStore::<Key>::new_encrypted(keystore_location.clone(), PASSWORD).unwrap();
+        assert!(ks.is_encrypted());
+
+        let key_info = KeyInfo {
+            private_key: vec![0, 1, 2],
+        };
+        let addr = Key::try_from(key_info.clone()).unwrap();
+        ks.put(key_info.clone()).unwrap();
+        ks.set_default(&addr).unwrap();
+
+        // The private keys are not written in plaintext
+        let persisted: Vec<PersistentKeyInfo> =
+            serde_json::from_reader(std::fs::File::open(&keystore_location).unwrap()).unwrap();
+        assert_eq!(persisted.len(), 2);
+        for info in persisted.iter() {
+            assert!(info.salt.is_some());
+            assert_ne!(info.private_key, hex::encode(&key_info.private_key));
+        }
+
+        // Create the key store again
+        let mut ks = PersistentKeyStore::new_encrypted(keystore_location, PASSWORD).unwrap();
+        assert_eq!(ks.get(&addr).unwrap().unwrap(), key_info);
+        assert_eq!(ks.get_default().unwrap().unwrap(), addr);
+    }
+
+    #[test]
+    fn test_get_default_does_not_write() {
+        let keystore_folder = tempfile::tempdir().unwrap().into_path();
+        let keystore_location = keystore_folder.join("eth_keystore");
+
+        let mut ks =
+            PersistentKeyStore::<Key>::new_encrypted(keystore_location.clone(), PASSWORD).unwrap();
+        let key_info = KeyInfo {
+            private_key: vec![0, 1, 2],
+        };
+        let addr = Key::try_from(key_info.clone()).unwrap();
+        ks.put(key_info).unwrap();
+        ks.set_default(&addr).unwrap();
+
+        // Re-encrypting would change the file, as every encryption uses a new nonce.
+        let before = std::fs::read(&keystore_location).unwrap();
+        let mut ks =
+            PersistentKeyStore::<Key>::new_encrypted(keystore_location.clone(), PASSWORD).unwrap();
+        assert_eq!(ks.get_default().unwrap().unwrap(), addr);
+        assert_eq!(std::fs::read(&keystore_location).unwrap(), before);
+    }
+
+    #[test]
+    fn test_encrypted_keystore_wrong_password() {
+        let keystore_folder = tempfile::tempdir().unwrap().into_path();
+        let keystore_location = keystore_folder.join("eth_keystore");
+
+        let mut ks =
+            PersistentKeyStore::<Key>::new_encrypted(keystore_location.clone(), PASSWORD).unwrap();
+        ks.put(KeyInfo {
+            private_key: vec![0, 1, 2],
+        })
+        .unwrap();
+
+        let err = PersistentKeyStore::<Key>::new_encrypted(keystore_location.clone(), "wrong")
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("wrong password"));
+
+        // Opening without a password fails too
+        assert!(PersistentKeyStore::<Key>::new(keystore_location).is_err());
+    }
+
+    #[test]
+    fn test_encrypt_legacy_keystore() {
+        let keystore_folder = tempfile::tempdir().unwrap().into_path();
+        let keystore_location = keystore_folder.join("eth_keystore");
+        assert!(!PersistentKeyStore::<Key>::is_encrypted_at(&keystore_location).unwrap());
+
+        let mut ks = PersistentKeyStore::new(keystore_location.clone()).unwrap();
+        let key_info = KeyInfo {
+            private_key: vec![0, 1, 2],
+        };
+        let addr = Key::try_from(key_info.clone()).unwrap();
+        ks.put(key_info.clone()).unwrap();
+        assert!(!PersistentKeyStore::<Key>::is_encrypted_at(&keystore_location).unwrap());
+
+        // An encrypted store can't be opened on top of a plaintext one
+        assert!(
+            PersistentKeyStore::<Key>::new_encrypted(keystore_location.clone(), PASSWORD).is_err()
+        );
+
+        let ks = PersistentKeyStore::<Key>::encrypt(keystore_location.clone(), PASSWORD).unwrap();
+        assert!(ks.is_encrypted());
+        assert_eq!(ks.get(&addr).unwrap().unwrap(), key_info);
+        assert!(PersistentKeyStore::<Key>::is_encrypted_at(&keystore_location).unwrap());
+
+        // The migrated store only opens with the password
+        assert!(PersistentKeyStore::<Key>::new(keystore_location.clone()).is_err());
+        let ks = PersistentKeyStore::new_encrypted(keystore_location, PASSWORD).unwrap();
+        assert_eq!(ks.get(&addr).unwrap().unwrap(), key_info);
+    }
 }
@@ -0,0 +1,314 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+
+//! Signers of EVM transactions, backed by a key from the key store or a hardware wallet.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use ethers::signers::{LocalWallet, Signer, WalletError};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::{Address, Signature};
+
+#[cfg(feature = "with-ledger")]
+use ethers::signers::{HDPath, Ledger, LedgerError};
+
+use crate::evm::{EthKeyAddress, KeyStore};
+
+/// The signer of the transactions of an account.
+#[derive(Debug)]
+pub enum SignerBackend {
+    /// Signs with a private key loaded from the key store.
+    Software(LocalWallet),
+    /// Signs on a Ledger device, the private key never leaves it.
+    #[cfg(feature = "with-ledger")]
+    Ledger(Ledger),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignerBackendError {
+    #[error(transparent)]
+    Software(#[from] WalletError),
+    #[cfg(feature = "with-ledger")]
+    #[error(transparent)]
+    Ledger(#[from] LedgerError),
+}
+
+#[async_trait]
+impl Signer for SignerBackend {
+    type Error = SignerBackendError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            Self::Software(s) => Ok(s.sign_message(message).await?),
+            #[cfg(feature = "with-ledger")]
+            Self::Ledger(s) => Ok(s.sign_message(message).await?),
+        }
+    }
+
+    async fn sign_transaction(&self, message: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            Self::Software(s) => Ok(s.sign_transaction(message).await?),
+            #[cfg(feature = "with-ledger")]
+            Self::Ledger(s) => Ok(s.sign_transaction(message).await?),
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            Self::Software(s) => Ok(s.sign_typed_data(payload).await?),
+            #[cfg(feature = "with-ledger")]
+            Self::Ledger(s) => Ok(s.sign_typed_data(payload).await?),
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            Self::Software(s) => s.address(),
+            #[cfg(feature = "with-ledger")]
+            Self::Ledger(s) => s.address(),
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            Self::Software(s) => s.chain_id(),
+            #[cfg(feature = "with-ledger")]
+            Self::Ledger(s) => s.chain_id(),
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            Self::Software(s) => Self::Software(s.with_chain_id(chain_id)),
+            #[cfg(feature = "with-ledger")]
+            Self::Ledger(s) => Self::Ledger(s.with_chain_id(chain_id)),
+        }
+    }
+}
+
+/// A device holding private keys, which can be asked for a signer of one of its accounts.
+#[async_trait]
+pub trait HardwareWallet: Send + Sync {
+    /// Open a signer for the account at the given derivation path.
+    async fn signer(&self, derivation_path: &str, chain_id: u64) -> Result<SignerBackend>;
+}
+
+/// A Ledger device connected over USB, running the Ethereum app.
+#[cfg(feature = "with-ledger")]
+pub struct LedgerWallet;
+
+#[cfg(feature = "with-ledger")]
+#[async_trait]
+impl HardwareWallet for LedgerWallet {
+    async fn signer(&self, derivation_path: &str, chain_id: u64) -> Result<SignerBackend> {
+        let ledger = Ledger::new(HDPath::Other(derivation_path.to_string()), chain_id).await?;
+        Ok(SignerBackend::Ledger(ledger))
+    }
+}
+
+/// Chooses the signer of an account: accounts with a configured derivation path sign on
+/// the hardware wallet, all the others with their private key from the key store.
+#[derive(Clone, Default)]
+pub struct SignerSelector {
+    /// Derivation path of the hardware wallet accounts.
+    hardware_accounts: HashMap<Address, String>,
+    hardware: Option<Arc<dyn HardwareWallet>>,
+}
+
+impl SignerSelector {
+    pub fn new(
+        hardware_accounts: HashMap<Address, String>,
+        hardware: Option<Arc<dyn HardwareWallet>>,
+    ) -> Self {
+        Self {
+            hardware_accounts,
+            hardware,
+        }
+    }
+
+    /// Sign the transactions of the given accounts on a Ledger, if the build supports it.
+    pub fn with_ledger(ledger_accounts: HashMap<Address, String>) -> Self {
+        #[cfg(feature = "with-ledger")]
+        let hardware: Option<Arc<dyn HardwareWallet>> = Some(Arc::new(LedgerWallet));
+        #[cfg(not(feature = "with-ledger"))]
+        let hardware = None;
+
+        Self::new(ledger_accounts, hardware)
+    }
+
+    /// The derivation path of the account if it lives on the hardware wallet.
+    pub fn derivation_path(&self, addr: &Address) -> Option<&str> {
+        self.hardware_accounts.get(addr).map(|p| p.as_str())
+    }
+
+    /// Get the signer of an account for the chain.
+    pub async fn signer<K>(
+        &self,
+        addr: &Address,
+        chain_id: u64,
+        keystore: Option<&RwLock<K>>,
+    ) -> Result<SignerBackend>
+    where
+        K: KeyStore<Key = EthKeyAddress>,
+    {
+        if let Some(path) = self.derivation_path(addr) {
+            let hardware = self.hardware.as_ref().ok_or_else(|| {
+                anyhow!("address {addr:?} is a ledger account, but ledger support is not enabled")
+            })?;
+            let signer = hardware.signer(path, chain_id).await?;
+            if signer.address() != *addr {
+                bail!(
+                    "derivation path {path} is for address {:?}, not {addr:?}",
+                    signer.address()
+                );
+            }
+            return Ok(signer);
+        }
+
+        let keystore = keystore.ok_or_else(|| anyhow!("no evm keystore available"))?;
+        let key_info = keystore
+            .read()
+            .unwrap()
+            .get(&EthKeyAddress::from(*addr))?
+            .ok_or_else(|| anyhow!("address {addr:?} does not have private key in key store"))?;
+        let wallet = LocalWallet::from_bytes(key_info.private_key())?.with_chain_id(chain_id);
+
+        Ok(SignerBackend::Software(wallet))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex, RwLock};
+
+    use async_trait::async_trait;
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::Address;
+
+    use super::{HardwareWallet, SignerBackend, SignerSelector};
+    use crate::evm::memory::MemoryKeyStore;
+    use crate::evm::{EthKeyAddress, KeyInfo, KeyStore};
+
+    const CHAIN_ID: u64 = 314159;
+    const PATH: &str = "m/44'/60'/0'/0/0";
+
+    /// Stands in for a device, handing out a software signer for a fixed key.
+    struct MockHardware {
+        key: [u8; 32],
+        opened: Mutex<Vec<String>>,
+    }
+
+    impl MockHardware {
+        fn new(key: [u8; 32]) -> Self {
+            Self {
+                key,
+                opened: Mutex::new(vec![]),
+            }
+        }
+
+        fn address(&self) -> Address {
+            LocalWallet::from_bytes(&self.key).unwrap().address()
+        }
+    }
+
+    #[async_trait]
+    impl HardwareWallet for MockHardware {
+        async fn signer(
+            &self,
+            derivation_path: &str,
+            chain_id: u64,
+        ) -> anyhow::Result<SignerBackend> {
+            self.opened
+                .lock()
+                .unwrap()
+                .push(derivation_path.to_string());
+            let wallet = LocalWallet::from_bytes(&self.key)?.with_chain_id(chain_id);
+            Ok(SignerBackend::Software(wallet))
+        }
+    }
+
+    fn keystore_with(key: [u8; 32]) -> (RwLock<MemoryKeyStore<EthKeyAddress>>, Address) {
+        let mut keystore = MemoryKeyStore::default();
+        let addr = keystore.put(KeyInfo::new(key.to_vec())).unwrap();
+        (RwLock::new(keystore), addr.into())
+    }
+
+    #[tokio::test]
+    async fn test_keystore_account() {
+        let hardware = Arc::new(MockHardware::new([2; 32]));
+        let selector = SignerSelector::new(
+            HashMap::from([(hardware.address(), PATH.to_string())]),
+            Some(hardware.clone()),
+        );
+        let (keystore, addr) = keystore_with([1; 32]);
+
+        let signer = selector
+            .signer(&addr, CHAIN_ID, Some(&keystore))
+            .await
+            .unwrap();
+
+        assert!(matches!(signer, SignerBackend::Software(_)));
+        assert_eq!(signer.address(), addr);
+        assert_eq!(signer.chain_id(), CHAIN_ID);
+        assert!(hardware.opened.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hardware_account() {
+        let hardware = Arc::new(MockHardware::new([2; 32]));
+        let addr = hardware.address();
+        let selector = SignerSelector::new(
+            HashMap::from([(addr, PATH.to_string())]),
+            Some(hardware.clone()),
+        );
+        // The key store doesn't have the key, nor is it needed.
+        let (keystore, _) = keystore_with([1; 32]);
+
+        let signer = selector
+            .signer(&addr, CHAIN_ID, Some(&keystore))
+            .await
+            .unwrap();
+
+        assert_eq!(signer.address(), addr);
+        assert_eq!(*hardware.opened.lock().unwrap(), vec![PATH.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_hardware_account_address_mismatch() {
+        let hardware = Arc::new(MockHardware::new([2; 32]));
+        let (keystore, addr) = keystore_with([1; 32]);
+        // The configured address isn't the one derived by the device.
+        let selector = SignerSelector::new(
+            HashMap::from([(addr, PATH.to_string())]),
+            Some(hardware.clone()),
+        );
+
+        let err = selector
+            .signer(&addr, CHAIN_ID, Some(&keystore))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("derivation path"));
+    }
+
+    #[tokio::test]
+    async fn test_hardware_account_without_device() {
+        let (keystore, addr) = keystore_with([1; 32]);
+        let selector = SignerSelector::new(HashMap::from([(addr, PATH.to_string())]), None);
+
+        assert!(selector
+            .signer(&addr, CHAIN_ID, Some(&keystore))
+            .await
+            .is_err());
+    }
+}
@@ -33,7 +33,7 @@ pub const ENCRYPTED_KEYSTORE_NAME: &str = "keystore";
 /// Environmental variable which holds the `KeyStore` encryption phrase.
 pub const FOREST_KEYSTORE_PHRASE_ENV: &str = "FOREST_KEYSTORE_PHRASE";
 
-type SaltByteArray = [u8; RECOMMENDED_SALT_LEN];
+pub(crate) type SaltByteArray = [u8; RECOMMENDED_SALT_LEN];
 
 // TODO need to update keyinfo to not use SignatureType, use string instead to
 // save keys like jwt secret
@@ -154,9 +154,9 @@ struct PersistentKeyStore {
 /// `XSalsa20Poly1305` authenticated encryption
 /// CBOR encoding
 #[derive(Clone, PartialEq, Debug, Eq)]
-struct EncryptedKeyStore {
-    salt: SaltByteArray,
-    encryption_key: Vec<u8>,
+pub(crate) struct EncryptedKeyStore {
+    pub(crate) salt: SaltByteArray,
+    pub(crate) encryption_key: Vec<u8>,
 }
 
 #[derive(Debug, Error)]
@@ -427,7 +427,7 @@ impl KeyStore {
 }
 
 impl EncryptedKeyStore {
-    fn derive_key(
+    pub(crate) fn derive_key(
         passphrase: &str,
         prev_salt: Option<SaltByteArray>,
     ) -> anyhow::Result<(SaltByteArray, Vec<u8>)> {
@@ -469,7 +469,7 @@ impl EncryptedKeyStore {
         }
     }
 
-    fn encrypt(encryption_key: &[u8], msg: &[u8]) -> anyhow::Result<Vec<u8>> {
+    pub(crate) fn encrypt(encryption_key: &[u8], msg: &[u8]) -> anyhow::Result<Vec<u8>> {
         let mut nonce = [0; NONCE_SIZE];
         OsRng.fill_bytes(&mut nonce);
         let nonce = GenericArray::from_slice(&nonce);
@@ -480,7 +480,10 @@ impl EncryptedKeyStore {
         Ok(ciphertext)
     }
 
-    fn decrypt(encryption_key: &[u8], msg: &[u8]) -> anyhow::Result<Vec<u8>> {
+    pub(crate) fn decrypt(encryption_key: &[u8], msg: &[u8]) -> anyhow::Result<Vec<u8>> {
+        if msg.len() < NONCE_SIZE {
+            anyhow::bail!(EncryptedKeyStoreError::DecryptionError)
+        }
         let cyphertext_len = msg.len() - NONCE_SIZE;
         let ciphertext = &msg[..cyphertext_len];
         let nonce = GenericArray::from_slice(&msg[cyphertext_len..]);
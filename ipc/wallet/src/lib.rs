@@ -9,11 +9,16 @@ use serde::{Deserialize, Serialize};
 mod evm;
 mod fvm;
 
+#[cfg(feature = "with-ledger")]
+pub use crate::evm::LedgerWallet;
 #[cfg(feature = "with-ethers")]
-pub use crate::evm::{random_eth_key_info, EthKeyAddress};
+pub use crate::evm::{
+    random_eth_key_info, EthKeyAddress, HardwareWallet, SignerBackend, SignerBackendError,
+    SignerSelector,
+};
 pub use crate::evm::{
     KeyInfo as EvmKeyInfo, KeyStore as EvmKeyStore, PersistentKeyInfo, PersistentKeyStore,
-    DEFAULT_KEYSTORE_NAME,
+    DEFAULT_KEYSTORE_NAME, KEYSTORE_PASSWORD_ENV as EVM_KEYSTORE_PASSWORD_ENV,
 };
 pub use crate::fvm::*;
 
@@ -227,6 +227,7 @@ impl<P: StoreParams> NetworkBehaviour for Behaviour<P> {
         // I'm not sure what we can do without moving rate limiting into the bitswap library itself, because
         // what we did here relied on the ability to redirect the channels inside the request, but if the event
         // itself is private to the `request_response` protocol there's nothing I can do.
+        // In the meantime `RateLimitedStore` can limit what we serve per subnet, without knowing the peer.
         // match event {
 
         //     request_response::handler::Event::Request {
@@ -351,6 +351,7 @@ where
             }
             Ok(_msg_id) => {
                 stats::MEMBERSHIP_PUBLISH_SUCCESS.inc();
+                stats::MEMBERSHIP_VOTES_PUBLISHED.inc();
                 Ok(())
             }
         }
@@ -388,6 +389,7 @@ where
     /// Call this method when the discovery service forgets the address of a peer.
     pub fn set_unroutable(&mut self, peer_id: PeerId) {
         self.provider_cache.set_unroutable(peer_id);
+        self.update_subnet_provider_stats();
         self.outbox.push_back(Event::Removed(peer_id))
     }
 
@@ -398,6 +400,17 @@ where
         self.provider_cache.providers_of_subnet(subnet_id)
     }
 
+    /// List the current providers of a subnet with the timestamp of their last record.
+    ///
+    /// Useful for diagnosing why content cannot be resolved from a subnet.
+    pub fn providers_of_subnet_with_timestamps(
+        &self,
+        subnet_id: &SubnetID,
+    ) -> Vec<(PeerId, Timestamp)> {
+        self.provider_cache
+            .providers_of_subnet_with_timestamps(subnet_id)
+    }
+
     /// Parse and handle a [`gossipsub::Message`]. If it's from the expected topic,
     /// then raise domain event to let the rest of the application know about a
     /// provider. Also update all the book keeping in the behaviour that we use
@@ -449,6 +462,7 @@ where
             }
             Some(d) if d.is_empty() && !d.is_new => (None, false),
             Some(d) => {
+                self.update_subnet_provider_stats();
                 let publish = d.is_new;
                 (Some(Event::Updated(record.peer_id, d)), publish)
             }
@@ -466,6 +480,7 @@ where
 
     /// Raise an event to tell we received a new vote.
     fn handle_vote_record(&mut self, record: VoteRecord<V>) {
+        stats::MEMBERSHIP_VOTES_RECEIVED.inc();
         self.outbox.push_back(Event::ReceivedVote(Box::new(record)))
     }
 
@@ -517,6 +532,17 @@ where
             stats::MEMBERSHIP_PROVIDER_PEERS.dec();
             self.outbox.push_back(Event::Removed(peer_id))
         }
+        self.update_subnet_provider_stats();
+    }
+
+    /// Set the number of providers per subnet, dropping the subnets no longer in the cache.
+    fn update_subnet_provider_stats(&self) {
+        stats::MEMBERSHIP_SUBNET_PROVIDERS.reset();
+        for (subnet_id, count) in self.provider_cache.subnet_provider_counts() {
+            stats::MEMBERSHIP_SUBNET_PROVIDERS
+                .with_label_values(&[&subnet_id.to_string()])
+                .set(count as i64);
+        }
     }
 }
 
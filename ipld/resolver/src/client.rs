@@ -1,17 +1,31 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
+use std::time::Duration;
+
 use anyhow::anyhow;
 use async_trait::async_trait;
 use ipc_api::subnet_id::SubnetID;
 use libipld::Cid;
+use libp2p::PeerId;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::oneshot;
 
 use crate::{
     service::{Request, ResolveResult},
     vote_record::SignedVoteRecord,
+    Timestamp,
 };
 
+/// Error returned when the resolution of a CID didn't complete in the allotted time,
+/// for example because no peer in the subnet has the content.
+#[derive(thiserror::Error, Debug)]
+#[error("Timed out resolving {cid} from subnet {subnet_id} after {timeout:?}")]
+pub struct ResolveTimeout {
+    pub cid: Cid,
+    pub subnet_id: SubnetID,
+    pub timeout: Duration,
+}
+
 /// A facade to the [`Service`] to provide a nicer interface than message passing would allow on its own.
 #[derive(Clone)]
 pub struct Client<V> {
@@ -76,6 +90,21 @@ impl<V> Client<V> {
         self.send_request(req)
     }
 
+    /// List the peers we know to be providing a subnet, along with the timestamp
+    /// of the last membership record we received from them.
+    ///
+    /// Useful for diagnosing why content cannot be resolved from a subnet.
+    pub async fn list_providers(
+        &self,
+        subnet_id: &SubnetID,
+    ) -> anyhow::Result<Vec<(PeerId, Timestamp)>> {
+        let (tx, rx) = oneshot::channel();
+        let req = Request::ListProviders(subnet_id.clone(), tx);
+        self.send_request(req)?;
+        let providers = rx.await?;
+        Ok(providers)
+    }
+
     /// Publish pre-emptively to a subnet that agents in the parent subnet
     /// would be subscribed to if they are interested in receiving data
     /// before they would have to use [`Client::resolve`] instead.
@@ -93,6 +122,17 @@ pub trait Resolver {
     ///
     /// Upon success, the data should be found in the store.
     async fn resolve(&self, cid: Cid, subnet_id: SubnetID) -> anyhow::Result<ResolveResult>;
+
+    /// Same as [`Resolver::resolve`] but gives up waiting if there is no response within `timeout`,
+    /// returning a [`ResolveTimeout`] as the result.
+    ///
+    /// The service might still complete the resolution in the background.
+    async fn resolve_with_timeout(
+        &self,
+        cid: Cid,
+        subnet_id: SubnetID,
+        timeout: Duration,
+    ) -> anyhow::Result<ResolveResult>;
 }
 
 #[async_trait]
@@ -111,4 +151,100 @@ where
         let res = rx.await?;
         Ok(res)
     }
+
+    /// Send a CID for resolution from a specific subnet, and await its completion
+    /// for at most `timeout`, after which a [`ResolveTimeout`] is returned as the result.
+    async fn resolve_with_timeout(
+        &self,
+        cid: Cid,
+        subnet_id: SubnetID,
+        timeout: Duration,
+    ) -> anyhow::Result<ResolveResult> {
+        let (tx, rx) = oneshot::channel();
+        let req = Request::Resolve(cid, subnet_id.clone(), tx);
+        self.send_request(req)?;
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(res) => Ok(res?),
+            Err(_) => Ok(Err(anyhow!(ResolveTimeout {
+                cid,
+                subnet_id,
+                timeout
+            }))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use fvm_ipld_encoding::IPLD_RAW;
+    use ipc_api::subnet_id::SubnetID;
+    use libipld::{Cid, Multihash};
+    use tokio::sync::mpsc;
+
+    use super::{Client, ResolveTimeout, Resolver};
+    use crate::service::Request;
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    fn subnet_id() -> SubnetID {
+        SubnetID::new_root(123)
+    }
+
+    fn cid() -> Cid {
+        Cid::new_v1(IPLD_RAW, Multihash::wrap(0, b"foo").unwrap())
+    }
+
+    #[tokio::test]
+    async fn resolve_with_timeout_responds() {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Request<()>>();
+        let client = Client::new(tx);
+
+        // Respond to the resolution immediately.
+        tokio::spawn(async move {
+            if let Some(Request::Resolve(_, _, tx)) = rx.recv().await {
+                let _ = tx.send(Ok(()));
+            }
+        });
+
+        let res = client
+            .resolve_with_timeout(cid(), subnet_id(), TIMEOUT)
+            .await
+            .expect("service is listening");
+
+        assert!(res.is_ok());
+    }
+
+    #[tokio::test]
+    async fn resolve_with_timeout_times_out() {
+        // Keep the receiver around but never respond, like a service with no peers having the content.
+        let (tx, _rx) = mpsc::unbounded_channel::<Request<()>>();
+        let client = Client::new(tx);
+
+        let res = client
+            .resolve_with_timeout(cid(), subnet_id(), TIMEOUT)
+            .await
+            .expect("service is listening");
+
+        let err = res.expect_err("should time out");
+        let timeout = err
+            .downcast_ref::<ResolveTimeout>()
+            .expect("should be a timeout");
+
+        assert_eq!(timeout.subnet_id, subnet_id());
+        assert_eq!(timeout.timeout, TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn resolve_with_timeout_disconnected() {
+        let (tx, rx) = mpsc::unbounded_channel::<Request<()>>();
+        let client = Client::new(tx);
+        drop(rx);
+
+        assert!(client
+            .resolve_with_timeout(cid(), subnet_id(), TIMEOUT)
+            .await
+            .is_err());
+    }
 }
@@ -4,6 +4,7 @@ mod behaviour;
 mod client;
 mod hash;
 mod limiter;
+mod rate_limited_store;
 mod service;
 mod stats;
 mod timestamp;
@@ -20,7 +21,9 @@ mod arb;
 pub mod missing_blocks;
 
 pub use behaviour::{ContentConfig, DiscoveryConfig, MembershipConfig, NetworkConfig};
-pub use client::{Client, Resolver};
+pub use client::{Client, ResolveTimeout, Resolver};
+pub use limiter::{SubnetLimit, SubnetLimits};
+pub use rate_limited_store::RateLimitedStore;
 pub use service::{Config, ConnectionConfig, Event, NoKnownPeers, Service};
 pub use timestamp::Timestamp;
-pub use vote_record::{ValidatorKey, VoteRecord};
+pub use vote_record::{ValidatorKey, VoteRecord, VoteRecordVersion};
@@ -1,9 +1,11 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
+use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, Instant};
 
 use gcra::GcraState;
 pub use gcra::RateLimit;
+use ipc_api::subnet_id::SubnetID;
 use lru_time_cache::LruCache;
 
 /// Track the rate limit of resources (e.g. bytes) consumed per key.
@@ -42,11 +44,192 @@ where
     }
 }
 
+/// Limits on the content served on behalf of a subnet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubnetLimit {
+    /// Number of bytes that can be served per second.
+    ///
+    /// Blocks larger than this are never served, so it should be at least
+    /// the maximum block size. 0 means no limit.
+    pub bytes_per_sec: u32,
+    /// Number of block requests that can be served per second.
+    ///
+    /// 0 means no limit.
+    pub requests_per_sec: u32,
+}
+
+/// Content serving limits keyed by subnet.
+#[derive(Debug, Clone, Default)]
+pub struct SubnetLimits {
+    /// Limit shared by all subnets which don't have their own entry.
+    pub default: SubnetLimit,
+    /// Limits for specific subnets, each consumed separately.
+    pub subnets: HashMap<SubnetID, SubnetLimit>,
+}
+
+impl SubnetLimits {
+    /// Select the bucket to charge for a subnet and the limit that applies to it.
+    ///
+    /// Subnets with their own limit have their own bucket; everything else,
+    /// including content we can't attribute to a subnet, shares the default one.
+    pub fn select(&self, subnet_id: Option<&SubnetID>) -> (Option<SubnetID>, SubnetLimit) {
+        match subnet_id.and_then(|id| self.subnets.get_key_value(id)) {
+            Some((id, limit)) => (Some(id.clone()), *limit),
+            None => (None, self.default),
+        }
+    }
+}
+
+/// GCRA state for one subnet bucket.
+struct SubnetBucket {
+    bytes: Option<(RateLimit, GcraState)>,
+    requests: Option<(RateLimit, GcraState)>,
+}
+
+impl SubnetBucket {
+    fn new(limit: SubnetLimit) -> Self {
+        let per_sec = |n: u32| {
+            if n == 0 {
+                None
+            } else {
+                Some((
+                    RateLimit::new(n, Duration::from_secs(1)),
+                    GcraState::default(),
+                ))
+            }
+        };
+        Self {
+            bytes: per_sec(limit.bytes_per_sec),
+            requests: per_sec(limit.requests_per_sec),
+        }
+    }
+}
+
+/// Track the requests and bytes served per subnet against [`SubnetLimits`].
+///
+/// Unlike [`RateLimiter`] this doesn't need to forget keys, because
+/// there is one bucket per configured subnet, plus the default.
+pub struct SubnetRateLimiter {
+    limits: SubnetLimits,
+    buckets: HashMap<Option<SubnetID>, SubnetBucket>,
+}
+
+impl SubnetRateLimiter {
+    pub fn new(limits: SubnetLimits) -> Self {
+        Self {
+            limits,
+            buckets: Default::default(),
+        }
+    }
+
+    /// Try to consume one request from the bucket of the subnet.
+    ///
+    /// Return `true` if the subnet was within limits, `false` if it needs to wait.
+    pub fn add_request(&mut self, subnet_id: Option<&SubnetID>) -> bool {
+        self.add_request_at(subnet_id, Instant::now())
+    }
+
+    /// Same as [`SubnetRateLimiter::add_request`] but allows passing in the time, for testing.
+    pub fn add_request_at(&mut self, subnet_id: Option<&SubnetID>, at: Instant) -> bool {
+        match self.bucket(subnet_id).requests {
+            Some((ref limit, ref mut state)) => state.check_and_modify_at(limit, at, 1).is_ok(),
+            None => true,
+        }
+    }
+
+    /// Try to consume a number of bytes from the bucket of the subnet.
+    ///
+    /// Return `true` if the subnet was within limits, `false` if it needs to wait.
+    pub fn add_bytes(&mut self, subnet_id: Option<&SubnetID>, bytes: usize) -> bool {
+        self.add_bytes_at(subnet_id, bytes, Instant::now())
+    }
+
+    /// Same as [`SubnetRateLimiter::add_bytes`] but allows passing in the time, for testing.
+    pub fn add_bytes_at(
+        &mut self,
+        subnet_id: Option<&SubnetID>,
+        bytes: usize,
+        at: Instant,
+    ) -> bool {
+        let bytes = bytes.try_into().unwrap_or(u32::MAX);
+        match self.bucket(subnet_id).bytes {
+            Some((ref limit, ref mut state)) => state.check_and_modify_at(limit, at, bytes).is_ok(),
+            None => true,
+        }
+    }
+
+    fn bucket(&mut self, subnet_id: Option<&SubnetID>) -> &mut SubnetBucket {
+        let (key, limit) = self.limits.select(subnet_id);
+        self.buckets
+            .entry(key)
+            .or_insert_with(|| SubnetBucket::new(limit))
+    }
+}
+
+/// Limit the number of requests in flight, queueing the ones over the limit
+/// until earlier ones finish, rather than rejecting them.
+pub struct ConcurrencyLimiter<T> {
+    /// Maximum number of requests in flight; 0 means no limit.
+    max_in_flight: usize,
+    in_flight: usize,
+    queue: VecDeque<T>,
+}
+
+impl<T> ConcurrencyLimiter<T> {
+    pub fn new(max_in_flight: usize) -> Self {
+        Self {
+            max_in_flight,
+            in_flight: 0,
+            queue: Default::default(),
+        }
+    }
+
+    /// Add a new request.
+    ///
+    /// Return the request if it can be started right away, otherwise queue it.
+    pub fn push(&mut self, request: T) -> Option<T> {
+        if self.max_in_flight == 0 || self.in_flight < self.max_in_flight {
+            self.in_flight += 1;
+            Some(request)
+        } else {
+            self.queue.push_back(request);
+            None
+        }
+    }
+
+    /// Indicate that a request in flight has finished.
+    ///
+    /// Return the next queued request, which takes its place in flight.
+    pub fn finish(&mut self) -> Option<T> {
+        match self.queue.pop_front() {
+            Some(request) => Some(request),
+            None => {
+                self.in_flight = self.in_flight.saturating_sub(1);
+                None
+            }
+        }
+    }
+
+    /// Number of requests started but not finished yet.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight
+    }
+
+    /// Number of requests waiting to be started.
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::{Duration, Instant};
 
-    use super::{RateLimit, RateLimiter};
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::{
+        ConcurrencyLimiter, RateLimit, RateLimiter, SubnetLimit, SubnetLimits, SubnetRateLimiter,
+    };
 
     #[test]
     fn basics() {
@@ -82,4 +265,110 @@ mod tests {
             "can raise quota"
         );
     }
+
+    #[test]
+    fn concurrency_limit() {
+        let mut limiter = ConcurrencyLimiter::new(3);
+
+        let started = (0..10).filter_map(|i| limiter.push(i)).collect::<Vec<_>>();
+        assert_eq!(started, vec![0, 1, 2]);
+        assert_eq!(limiter.in_flight(), 3);
+        assert_eq!(limiter.queued(), 7);
+
+        // Finishing one lets the next one in, in the order they were added.
+        let mut finished = 0;
+        while limiter.in_flight() > 0 {
+            assert!(limiter.in_flight() <= 3);
+            if let Some(next) = limiter.finish() {
+                assert_eq!(next, started.len() + finished);
+            }
+            finished += 1;
+        }
+        assert_eq!(finished, 10);
+        assert_eq!(limiter.queued(), 0);
+    }
+
+    #[test]
+    fn concurrency_unlimited() {
+        let mut limiter = ConcurrencyLimiter::new(0);
+        assert!((0..100).all(|i| limiter.push(i).is_some()));
+        assert_eq!(limiter.in_flight(), 100);
+        assert_eq!(limiter.queued(), 0);
+    }
+
+    fn subnet_limits() -> SubnetLimits {
+        SubnetLimits {
+            default: SubnetLimit {
+                bytes_per_sec: 100,
+                requests_per_sec: 2,
+            },
+            subnets: [(
+                SubnetID::new_root(1),
+                SubnetLimit {
+                    bytes_per_sec: 1000,
+                    requests_per_sec: 0,
+                },
+            )]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    #[test]
+    fn subnet_bucket_selection() {
+        let limits = subnet_limits();
+        let subnet_id1 = SubnetID::new_root(1);
+        let subnet_id2 = SubnetID::new_root(2);
+
+        assert_eq!(
+            limits.select(Some(&subnet_id1)),
+            (Some(subnet_id1), limits.subnets[&SubnetID::new_root(1)]),
+            "configured subnets have their own bucket"
+        );
+        assert_eq!(
+            limits.select(Some(&subnet_id2)),
+            (None, limits.default),
+            "other subnets fall back to the default"
+        );
+        assert_eq!(
+            limits.select(None),
+            (None, limits.default),
+            "unknown subnets fall back to the default"
+        );
+    }
+
+    #[test]
+    fn subnet_rate_limit() {
+        let mut limiter = SubnetRateLimiter::new(subnet_limits());
+        let subnet_id1 = SubnetID::new_root(1);
+        let subnet_id2 = SubnetID::new_root(2);
+        let now = Instant::now();
+
+        assert!(limiter.add_bytes_at(Some(&subnet_id1), 1000, now));
+        assert!(
+            !limiter.add_bytes_at(Some(&subnet_id1), 1, now),
+            "can't over consume"
+        );
+        assert!(
+            (0..100).all(|_| limiter.add_request_at(Some(&subnet_id1), now)),
+            "0 means no limit"
+        );
+
+        assert!(limiter.add_request_at(Some(&subnet_id2), now));
+        assert!(limiter.add_request_at(None, now));
+        assert!(
+            !limiter.add_request_at(Some(&subnet_id2), now),
+            "the default bucket is shared"
+        );
+        assert!(
+            limiter.add_request_at(Some(&subnet_id2), now + Duration::from_secs(1)),
+            "can request again in the future"
+        );
+
+        assert!(
+            !limiter.add_bytes_at(None, 101, now),
+            "can't serve blocks over the limit"
+        );
+        assert!(limiter.add_bytes_at(None, 100, now));
+    }
 }
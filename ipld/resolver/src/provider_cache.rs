@@ -197,6 +197,11 @@ impl SubnetProviderCache {
         to_prune
     }
 
+    /// Number of known providers in each subnet.
+    pub fn subnet_provider_counts(&self) -> impl Iterator<Item = (&SubnetID, usize)> {
+        self.subnet_providers.iter().map(|(id, ps)| (id, ps.len()))
+    }
+
     /// List any known providers of a subnet.
     pub fn providers_of_subnet(&self, subnet_id: &SubnetID) -> Vec<PeerId> {
         self.subnet_providers
@@ -204,11 +209,34 @@ impl SubnetProviderCache {
             .map(|hs| hs.iter().cloned().collect())
             .unwrap_or_default()
     }
+
+    /// List any known providers of a subnet, along with the timestamp of the last record received from them.
+    pub fn providers_of_subnet_with_timestamps(
+        &self,
+        subnet_id: &SubnetID,
+    ) -> Vec<(PeerId, Timestamp)> {
+        self.subnet_providers
+            .get(subnet_id)
+            .map(|hs| {
+                hs.iter()
+                    .map(|peer_id| {
+                        let ts = self
+                            .peer_timestamps
+                            .get(peer_id)
+                            .cloned()
+                            .unwrap_or_default();
+                        (*peer_id, ts)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::{HashMap, HashSet};
+    use std::time::Duration;
 
     use ipc_api::subnet_id::SubnetID;
     use libp2p::{identity::Keypair, PeerId};
@@ -365,6 +393,52 @@ mod tests {
         check_providers(&providers, &cache)
     }
 
+    fn ts(secs: u64) -> Timestamp {
+        Timestamp::default() + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn providers_listed_with_timestamps() {
+        let peer_id1 = Keypair::generate_ed25519().public().to_peer_id();
+        let peer_id2 = Keypair::generate_ed25519().public().to_peer_id();
+        let subnet_id1 = SubnetID::new_root(1);
+        let subnet_id2 = SubnetID::new_root(2);
+
+        let mut cache = SubnetProviderCache::new(usize::MAX, Vec::new());
+        cache.set_routable(peer_id1);
+        cache.set_routable(peer_id2);
+
+        let records = [
+            (peer_id1, vec![subnet_id1.clone()], ts(10)),
+            (
+                peer_id2,
+                vec![subnet_id1.clone(), subnet_id2.clone()],
+                ts(20),
+            ),
+            // A later record replaces the timestamp.
+            (peer_id1, vec![subnet_id1.clone()], ts(30)),
+        ];
+        for (peer_id, subnet_ids, timestamp) in records {
+            cache.add_provider(&ProviderRecord {
+                peer_id,
+                subnet_ids,
+                timestamp,
+            });
+        }
+
+        let mut providers = cache.providers_of_subnet_with_timestamps(&subnet_id1);
+        providers.sort_by_key(|(_, t)| *t);
+        assert_eq!(providers, vec![(peer_id2, ts(20)), (peer_id1, ts(30))]);
+
+        assert_eq!(
+            cache.providers_of_subnet_with_timestamps(&subnet_id2),
+            vec![(peer_id2, ts(20))]
+        );
+        assert!(cache
+            .providers_of_subnet_with_timestamps(&SubnetID::new_root(3))
+            .is_empty());
+    }
+
     #[quickcheck]
     fn prop_providers_pruned(
         records: TestRecords,
@@ -0,0 +1,82 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+use ipc_api::subnet_id::SubnetID;
+use libipld::{Block, Cid};
+use libp2p_bitswap::BitswapStore;
+use log::warn;
+
+use crate::limiter::{SubnetLimits, SubnetRateLimiter};
+use crate::stats;
+
+/// A [`BitswapStore`] which charges the blocks it serves to remote peers
+/// against the quota of the subnet the content belongs to.
+///
+/// [`Bitswap`](libp2p_bitswap::Bitswap) only calls [`BitswapStore::get`] to respond
+/// to `WANT-BLOCK` requests, and it does so from its own thread without telling the
+/// store which peer is asking, so the limits apply to the subnet as a whole, not to
+/// individual peers. Requests over the limit are answered as if we didn't have the block.
+pub struct RateLimitedStore<S, F> {
+    inner: S,
+    /// Find out which subnet a block belongs to; `None` is charged to the default limit.
+    subnet_of: F,
+    limiter: SubnetRateLimiter,
+}
+
+impl<S, F> RateLimitedStore<S, F>
+where
+    F: Fn(&Cid) -> Option<SubnetID>,
+{
+    pub fn new(inner: S, limits: SubnetLimits, subnet_of: F) -> Self {
+        Self {
+            inner,
+            subnet_of,
+            limiter: SubnetRateLimiter::new(limits),
+        }
+    }
+}
+
+impl<S, F> BitswapStore for RateLimitedStore<S, F>
+where
+    S: BitswapStore,
+    F: Fn(&Cid) -> Option<SubnetID> + Send + Sync + 'static,
+{
+    type Params = S::Params;
+
+    fn contains(&mut self, cid: &Cid) -> anyhow::Result<bool> {
+        self.inner.contains(cid)
+    }
+
+    fn get(&mut self, cid: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        let subnet_id = (self.subnet_of)(cid);
+        let subnet_id = subnet_id.as_ref();
+
+        if !self.limiter.add_request(subnet_id) {
+            rate_limited(subnet_id, cid, "requests");
+            return Ok(None);
+        }
+
+        match self.inner.get(cid)? {
+            Some(data) if !self.limiter.add_bytes(subnet_id, data.len()) => {
+                rate_limited(subnet_id, cid, "bytes");
+                Ok(None)
+            }
+            other => Ok(other),
+        }
+    }
+
+    fn insert(&mut self, block: &Block<Self::Params>) -> anyhow::Result<()> {
+        self.inner.insert(block)
+    }
+
+    fn missing_blocks(&mut self, cid: &Cid) -> anyhow::Result<Vec<Cid>> {
+        self.inner.missing_blocks(cid)
+    }
+}
+
+fn rate_limited(subnet_id: Option<&SubnetID>, cid: &Cid, quota: &str) {
+    match subnet_id {
+        Some(subnet_id) => warn!("rate limiting {cid} in subnet {subnet_id}: out of {quota}"),
+        None => warn!("rate limiting {cid} in the default quota: out of {quota}"),
+    }
+    stats::CONTENT_RATE_LIMITED.inc();
+}
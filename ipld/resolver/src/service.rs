@@ -1,7 +1,7 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
 use std::collections::HashMap;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::anyhow;
 use bloom::{BloomFilter, ASMS};
@@ -33,8 +33,10 @@ use crate::behaviour::{
     DiscoveryConfig, MembershipConfig, NetworkConfig,
 };
 use crate::client::Client;
+use crate::limiter::ConcurrencyLimiter;
 use crate::stats;
 use crate::vote_record::{SignedVoteRecord, VoteRecord};
+use crate::Timestamp;
 
 /// Result of attempting to resolve a CID.
 pub type ResolveResult = anyhow::Result<()>;
@@ -49,6 +51,7 @@ struct Query {
     subnet_id: SubnetID,
     fallback_peer_ids: Vec<PeerId>,
     response_channel: ResponseChannel,
+    started_at: Instant,
 }
 
 /// Keeps track of where to send query responses to.
@@ -72,6 +75,10 @@ pub struct ConnectionConfig {
     pub expected_peer_count: u32,
     /// Maximum number of peers to send Bitswap requests to in a single attempt.
     pub max_peers_per_query: u32,
+    /// Maximum number of CID resolutions in flight; the rest are queued until one finishes.
+    ///
+    /// 0 means no limit.
+    pub max_concurrent_requests: u32,
     /// Maximum number of events in the push-based broadcast channel before a slow
     /// consumer gets an error because it's falling behind.
     pub event_buffer_capacity: u32,
@@ -96,6 +103,7 @@ pub(crate) enum Request<V> {
     PinSubnet(SubnetID),
     UnpinSubnet(SubnetID),
     Resolve(Cid, SubnetID, ResponseChannel),
+    ListProviders(SubnetID, oneshot::Sender<Vec<(PeerId, Timestamp)>>),
     RateLimitUsed(PeerId, usize),
     UpdateRateLimit(u32),
 }
@@ -131,6 +139,8 @@ where
     background_lookup_filter: BloomFilter,
     /// To limit the number of peers contacted in a Bitswap resolution attempt.
     max_peers_per_query: usize,
+    /// To limit the number of resolutions running at the same time.
+    resolve_limiter: ConcurrencyLimiter<(Cid, SubnetID, ResponseChannel)>,
 }
 
 impl<P, V> Service<P, V>
@@ -210,6 +220,9 @@ where
                 config.connection.expected_peer_count,
             ),
             max_peers_per_query: config.connection.max_peers_per_query as usize,
+            resolve_limiter: ConcurrencyLimiter::new(
+                config.connection.max_concurrent_requests as usize,
+            ),
         };
 
         Ok(service)
@@ -461,7 +474,24 @@ where
                 }
             }
             Request::Resolve(cid, subnet_id, response_channel) => {
-                self.start_query(cid, subnet_id, response_channel)
+                let next = self
+                    .resolve_limiter
+                    .push((cid, subnet_id, response_channel));
+                if next.is_none() {
+                    debug!(
+                        "queueing resolution of {cid}; {} requests in flight",
+                        self.resolve_limiter.in_flight()
+                    );
+                }
+                self.start_queries(next)
+            }
+            Request::ListProviders(subnet_id, tx) => {
+                let providers = self
+                    .membership_mut()
+                    .providers_of_subnet_with_timestamps(&subnet_id);
+                if tx.send(providers).is_err() {
+                    error!("error sending provider list; listener closed")
+                }
             }
             Request::RateLimitUsed(peer_id, bytes) => {
                 self.content_mut().rate_limit_used(peer_id, bytes)
@@ -470,8 +500,34 @@ where
         }
     }
 
+    /// Start the next resolution let through by the limiter, and keep starting
+    /// queued ones as long as they finish immediately.
+    fn start_queries(&mut self, mut next: Option<(Cid, SubnetID, ResponseChannel)>) {
+        while let Some((cid, subnet_id, response_channel)) = next {
+            if self.start_query(cid, subnet_id, response_channel) {
+                break;
+            }
+            next = self.resolve_limiter.finish();
+        }
+    }
+
+    /// Indicate that a resolution has finished, and start the next one in the queue.
+    fn finish_query(&mut self) {
+        let next = self.resolve_limiter.finish();
+        self.start_queries(next)
+    }
+
     /// Start a CID resolution.
-    fn start_query(&mut self, cid: Cid, subnet_id: SubnetID, response_channel: ResponseChannel) {
+    ///
+    /// Returns `false` if the resolution finished without starting a Bitswap query.
+    fn start_query(
+        &mut self,
+        cid: Cid,
+        subnet_id: SubnetID,
+        response_channel: ResponseChannel,
+    ) -> bool {
+        stats::CONTENT_RESOLVE_STARTED.inc();
+
         let mut peers = self.membership_mut().providers_of_subnet(&subnet_id);
 
         stats::CONTENT_RESOLVE_PEERS.observe(peers.len() as f64);
@@ -479,6 +535,7 @@ where
         if peers.is_empty() {
             stats::CONTENT_RESOLVE_NO_PEERS.inc();
             send_resolve_result(response_channel, Err(anyhow!(NoKnownPeers(subnet_id))));
+            false
         } else {
             // Connect to them in a random order, so as not to overwhelm any specific peer.
             peers.shuffle(&mut rand::thread_rng());
@@ -498,11 +555,13 @@ where
                 subnet_id,
                 response_channel,
                 fallback_peer_ids: fallback,
+                started_at: Instant::now(),
             };
 
             let query_id = self.content_mut().resolve(cid, peers);
 
             self.queries.insert(query_id, query);
+            true
         }
     }
 
@@ -515,11 +574,18 @@ where
         match result {
             Ok(_) => {
                 stats::CONTENT_RESOLVE_SUCCESS.inc();
-                send_resolve_result(query.response_channel, result)
+                stats::CONTENT_RESOLVE_DURATION.observe(query.started_at.elapsed().as_secs_f64());
+                send_resolve_result(query.response_channel, result);
+                self.finish_query();
             }
-            Err(_) if query.fallback_peer_ids.is_empty() => {
+            Err(e) if query.fallback_peer_ids.is_empty() => {
                 stats::CONTENT_RESOLVE_FAILURE.inc();
-                send_resolve_result(query.response_channel, result)
+                if is_timeout(&e) {
+                    stats::CONTENT_RESOLVE_TIMEOUT.inc();
+                }
+                stats::CONTENT_RESOLVE_DURATION.observe(query.started_at.elapsed().as_secs_f64());
+                send_resolve_result(query.response_channel, Err(e));
+                self.finish_query();
             }
             Err(e) => {
                 stats::CONTENT_RESOLVE_FALLBACK.inc();
@@ -564,6 +630,22 @@ where
     }
 }
 
+/// Check whether a resolution failed because some peer didn't respond in time.
+///
+/// The errors coming from Bitswap are not typed, so apart from I/O errors
+/// we have to go by the message.
+fn is_timeout(e: &anyhow::Error) -> bool {
+    e.chain().any(|c| {
+        if let Some(e) = c.downcast_ref::<std::io::Error>() {
+            if e.kind() == std::io::ErrorKind::TimedOut {
+                return true;
+            }
+        }
+        let msg = c.to_string().to_lowercase();
+        msg.contains("timeout") || msg.contains("timed out")
+    })
+}
+
 /// Respond to the sender of the query, if they are still listening.
 fn send_resolve_result(tx: Sender<ResolveResult>, res: ResolveResult) {
     if tx.send(res).is_err() {
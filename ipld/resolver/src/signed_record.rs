@@ -15,6 +15,15 @@ pub trait Record {
     /// Check that the [`PublicKey`] recovered from the [`SignedEnvelope`]
     /// is consistent with the payload.
     fn check_signing_key(&self, key: &PublicKey) -> bool;
+    /// Decode the record from the payload of the [`SignedEnvelope`].
+    ///
+    /// Records which can have multiple encodings can override this.
+    fn decode_payload(payload: &[u8]) -> Result<Self, FromEnvelopeError>
+    where
+        Self: Sized + DeserializeOwned,
+    {
+        Ok(fvm_ipld_encoding::from_slice(payload)?)
+    }
 }
 
 /// A [`ProviderRecord`] with a [`SignedEnvelope`] proving that the
@@ -36,6 +45,16 @@ where
     /// which can be shared with others.
     pub fn new(key: &Keypair, record: R) -> anyhow::Result<Self> {
         let payload = fvm_ipld_encoding::to_vec(&record)?;
+        Self::new_with_payload(key, record, payload)
+    }
+
+    /// Create a new [`SignedRecord`] from a record and its already encoded payload,
+    /// for records which are not encoded with the default CBOR format.
+    pub(crate) fn new_with_payload(
+        key: &Keypair,
+        record: R,
+        payload: Vec<u8>,
+    ) -> anyhow::Result<Self> {
         let envelope = SignedEnvelope::new(
             key,
             DOMAIN_SEP.to_owned(),
@@ -49,7 +68,7 @@ where
         let (payload, signing_key) = envelope
             .payload_and_signing_key(DOMAIN_SEP.to_owned(), R::payload_type().as_bytes())?;
 
-        let record = fvm_ipld_encoding::from_slice::<R>(payload)?;
+        let record = R::decode_payload(payload)?;
 
         if !record.check_signing_key(signing_key) {
             return Err(FromEnvelopeError::MismatchedSignature);
@@ -93,6 +112,9 @@ pub enum FromEnvelopeError {
     /// The signer of the envelope is different than the peer id in the record.
     #[error("The signer of the envelope is different than the peer id in the record")]
     MismatchedSignature,
+    /// The record was encoded with a version this node does not know about.
+    #[error("Unsupported record version: {0}")]
+    UnsupportedVersion(u8),
 }
 
 #[cfg(test)]
@@ -1,7 +1,7 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
 use lazy_static::lazy_static;
-use prometheus::{Histogram, HistogramOpts, IntCounter, IntGauge, Registry};
+use prometheus::{Histogram, HistogramOpts, IntCounter, IntGauge, IntGaugeVec, Opts, Registry};
 
 macro_rules! metrics {
     ($($name:ident : $type:ty = $make:expr);* $(;)?) => {
@@ -54,6 +54,11 @@ metrics! {
     MEMBERSHIP_PROVIDER_PEERS: IntGauge =
         IntGauge::new("membership_provider_peers", "Number of unique providers");
 
+    MEMBERSHIP_SUBNET_PROVIDERS: IntGaugeVec = IntGaugeVec::new(
+        Opts::new("membership_subnet_providers", "Number of providers per subnet"),
+        &["subnet"]
+    );
+
     MEMBERSHIP_UNKNOWN_TOPIC: IntCounter = IntCounter::new(
         "membership_unknown_topic",
         "Number of messages with unknown topic"
@@ -73,6 +78,21 @@ metrics! {
         "Number of failed publish attempts"
     );
 
+    MEMBERSHIP_VOTES_PUBLISHED: IntCounter = IntCounter::new(
+        "membership_votes_published",
+        "Number of votes published"
+    );
+
+    MEMBERSHIP_VOTES_RECEIVED: IntCounter = IntCounter::new(
+        "membership_votes_received",
+        "Number of votes received"
+    );
+
+    CONTENT_RESOLVE_STARTED: IntCounter = IntCounter::new(
+        "content_resolve_started",
+        "Number of requested resolutions"
+    );
+
     CONTENT_RESOLVE_RUNNING: IntGauge = IntGauge::new(
         "content_resolve_running",
         "Number of currently running content resolutions"
@@ -93,6 +113,16 @@ metrics! {
       "Number of failed resolutions"
     );
 
+    CONTENT_RESOLVE_TIMEOUT: IntCounter = IntCounter::new(
+      "content_resolve_timeout",
+      "Number of failed resolutions due to timeouts, also counted as failures"
+    );
+
+    CONTENT_RESOLVE_DURATION: Histogram = Histogram::with_opts(HistogramOpts::new(
+        "content_resolve_duration",
+        "Duration of resolutions in seconds, including the fallback attempts"
+    ));
+
     CONTENT_RESOLVE_FALLBACK: IntCounter = IntCounter::new(
         "content_resolve_fallback",
         "Number of resolutions that fall back on secondary peers"
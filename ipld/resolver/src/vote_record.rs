@@ -6,10 +6,26 @@ use serde::de::{DeserializeOwned, Error};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    signed_record::{Record, SignedRecord},
+    signed_record::{FromEnvelopeError, Record, SignedRecord},
     Timestamp,
 };
 
+/// Tag of the [`VoteRecordVersion::V1`] payload.
+const VOTE_RECORD_V1: u8 = 1;
+
+/// The version of the payload a [`VoteRecord`] is gossiped in.
+///
+/// Nodes accept every version they know about, so a new version should only be
+/// emitted once all nodes have been upgraded to understand it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VoteRecordVersion {
+    /// The original format: the CBOR encoded record without a version tag.
+    #[default]
+    V0,
+    /// A version byte followed by the CBOR encoded record.
+    V1,
+}
+
 /// The basic idea is that validators, identified by their public key,
 /// vote about things regarding the subnet in which they participate.
 #[derive(Debug, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
@@ -75,7 +91,10 @@ pub struct VoteRecord<C> {
     pub timestamp: Timestamp,
 }
 
-impl<C> Record for VoteRecord<C> {
+impl<C> Record for VoteRecord<C>
+where
+    C: Serialize + DeserializeOwned,
+{
     fn payload_type() -> &'static str {
         "/ipc/vote-record"
     }
@@ -83,6 +102,13 @@ impl<C> Record for VoteRecord<C> {
     fn check_signing_key(&self, key: &PublicKey) -> bool {
         self.public_key.0 == *key
     }
+
+    fn decode_payload(payload: &[u8]) -> Result<Self, FromEnvelopeError>
+    where
+        Self: Sized + DeserializeOwned,
+    {
+        Ok(Self::decode(payload)?.0)
+    }
 }
 
 pub type SignedVoteRecord<C> = SignedRecord<VoteRecord<C>>;
@@ -97,6 +123,16 @@ where
         key: &Keypair,
         subnet_id: SubnetID,
         content: C,
+    ) -> anyhow::Result<SignedVoteRecord<C>> {
+        Self::signed_with_version(key, subnet_id, content, VoteRecordVersion::default())
+    }
+
+    /// Same as [`VoteRecord::signed`] but encoding the payload with a specific version.
+    pub fn signed_with_version(
+        key: &Keypair,
+        subnet_id: SubnetID,
+        content: C,
+        version: VoteRecordVersion,
     ) -> anyhow::Result<SignedVoteRecord<C>> {
         let timestamp = Timestamp::now();
         let record = VoteRecord {
@@ -105,9 +141,43 @@ where
             content,
             timestamp,
         };
-        let signed = SignedRecord::new(key, record)?;
+        let payload = record.encode(version)?;
+        let signed = SignedRecord::new_with_payload(key, record, payload)?;
         Ok(signed)
     }
+
+    /// Encode the record as the payload of a signed envelope.
+    pub fn encode(&self, version: VoteRecordVersion) -> anyhow::Result<Vec<u8>> {
+        let body = fvm_ipld_encoding::to_vec(self)?;
+        match version {
+            VoteRecordVersion::V0 => Ok(body),
+            VoteRecordVersion::V1 => {
+                let mut bz = Vec::with_capacity(body.len() + 1);
+                bz.push(VOTE_RECORD_V1);
+                bz.extend(body);
+                Ok(bz)
+            }
+        }
+    }
+
+    /// Decode a payload in any of the supported versions.
+    ///
+    /// The V0 payload is a CBOR map, which can be told apart from the version tags.
+    /// Returns the version the payload was encoded with.
+    pub fn decode(bz: &[u8]) -> Result<(Self, VoteRecordVersion), FromEnvelopeError> {
+        match bz.first() {
+            Some(&VOTE_RECORD_V1) => {
+                let record = fvm_ipld_encoding::from_slice(&bz[1..])?;
+                Ok((record, VoteRecordVersion::V1))
+            }
+            // Anything but a CBOR map (major type 5) cannot be a V0 payload.
+            Some(tag) if tag >> 5 != 5 => Err(FromEnvelopeError::UnsupportedVersion(*tag)),
+            _ => {
+                let record = fvm_ipld_encoding::from_slice(bz)?;
+                Ok((record, VoteRecordVersion::V0))
+            }
+        }
+    }
 }
 
 #[cfg(any(test, feature = "arb"))]
@@ -138,12 +208,75 @@ mod arb {
 
 #[cfg(test)]
 mod tests {
+    use libp2p::identity::Keypair;
     use quickcheck_macros::quickcheck;
 
-    use super::SignedVoteRecord;
+    use crate::arb::ArbSubnetID;
+    use crate::signed_record::FromEnvelopeError;
+
+    use super::{SignedVoteRecord, VoteRecord, VoteRecordVersion};
 
     #[quickcheck]
     fn prop_roundtrip(signed_record: SignedVoteRecord<String>) -> bool {
         crate::signed_record::tests::prop_roundtrip(signed_record)
     }
+
+    #[quickcheck]
+    fn prop_versions_roundtrip(subnet_id: ArbSubnetID, content: String) {
+        let key = Keypair::generate_secp256k1();
+
+        for version in [VoteRecordVersion::V0, VoteRecordVersion::V1] {
+            let signed = VoteRecord::signed_with_version(
+                &key,
+                subnet_id.0.clone(),
+                content.clone(),
+                version,
+            )
+            .unwrap();
+
+            let bz = signed.envelope().clone().into_protobuf_encoding();
+            let decoded = SignedVoteRecord::<String>::from_bytes(&bz).unwrap();
+            assert_eq!(decoded.record(), signed.record());
+
+            let payload = signed.record().encode(version).unwrap();
+            let (record, v) = VoteRecord::<String>::decode(&payload).unwrap();
+            assert_eq!(v, version);
+            assert_eq!(record, *signed.record());
+        }
+    }
+
+    #[quickcheck]
+    fn prop_v0_is_unversioned(signed_record: SignedVoteRecord<String>) {
+        // Earlier versions encoded the record directly, which is what V0 has to stay compatible with.
+        let legacy = fvm_ipld_encoding::to_vec(signed_record.record()).unwrap();
+        let v0 = signed_record
+            .record()
+            .encode(VoteRecordVersion::V0)
+            .unwrap();
+        let v1 = signed_record
+            .record()
+            .encode(VoteRecordVersion::V1)
+            .unwrap();
+
+        assert_eq!(legacy, v0);
+        assert_eq!(v1[1..], legacy);
+        assert_eq!(
+            VoteRecord::<String>::decode(&legacy).unwrap(),
+            (signed_record.record().clone(), VoteRecordVersion::V0)
+        );
+    }
+
+    #[quickcheck]
+    fn prop_garbage_rejected(bz: Vec<u8>) {
+        assert!(VoteRecord::<String>::decode(&bz).is_err());
+        assert!(SignedVoteRecord::<String>::from_bytes(&bz).is_err());
+    }
+
+    #[test]
+    fn unknown_version_rejected() {
+        assert!(matches!(
+            VoteRecord::<String>::decode(&[2, 0xa0]),
+            Err(FromEnvelopeError::UnsupportedVersion(2))
+        ));
+    }
 }
@@ -0,0 +1,102 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Test that the [`RateLimitedStore`] stops serving blocks once a subnet ran out of its quota.
+
+use cid::Cid;
+use fvm_ipld_encoding::IPLD_RAW;
+use ipc_api::subnet_id::SubnetID;
+use ipc_ipld_resolver::{RateLimitedStore, SubnetLimit, SubnetLimits};
+use libp2p_bitswap::BitswapStore;
+use multihash::{Code, MultihashDigest};
+
+mod store;
+use store::*;
+
+fn raw_cid(data: &[u8]) -> Cid {
+    Cid::new_v1(IPLD_RAW, Code::Sha2_256.digest(data))
+}
+
+fn put_raw<S: BitswapStore<Params = TestStoreParams>>(store: &mut S, data: &[u8]) {
+    let block = libipld::Block::<TestStoreParams>::new(raw_cid(data), data.to_vec())
+        .expect("invalid block");
+    store.insert(&block).expect("insert failed");
+}
+
+#[test]
+fn rate_limit_per_subnet() {
+    let limited_cid = raw_cid(b"limited");
+    let other_cid = raw_cid(b"other");
+
+    let limited_subnet = SubnetID::new_root(1);
+    let limits = SubnetLimits {
+        default: SubnetLimit::default(),
+        subnets: [(
+            limited_subnet.clone(),
+            SubnetLimit {
+                bytes_per_sec: 0,
+                requests_per_sec: 1,
+            },
+        )]
+        .into_iter()
+        .collect(),
+    };
+
+    let mut store = RateLimitedStore::new(TestBlockstore::default(), limits, move |cid: &Cid| {
+        if *cid == limited_cid {
+            Some(limited_subnet.clone())
+        } else {
+            Some(SubnetID::new_root(2))
+        }
+    });
+    put_raw(&mut store, b"limited");
+    put_raw(&mut store, b"other");
+
+    assert_eq!(
+        BitswapStore::get(&mut store, &limited_cid).unwrap(),
+        Some(b"limited".to_vec())
+    );
+    assert_eq!(
+        BitswapStore::get(&mut store, &limited_cid).unwrap(),
+        None,
+        "the subnet ran out of requests"
+    );
+    assert!(
+        store.contains(&limited_cid).unwrap(),
+        "only serving is limited"
+    );
+    for _ in 0..10 {
+        assert_eq!(
+            BitswapStore::get(&mut store, &other_cid).unwrap(),
+            Some(b"other".to_vec()),
+            "other subnets fall back to the unlimited default"
+        );
+    }
+}
+
+#[test]
+fn rate_limit_default_bytes() {
+    let small_cid = raw_cid(b"small");
+    let large_cid = raw_cid(&[0u8; 100]);
+
+    let limits = SubnetLimits {
+        default: SubnetLimit {
+            bytes_per_sec: 50,
+            requests_per_sec: 0,
+        },
+        subnets: Default::default(),
+    };
+
+    let mut store = RateLimitedStore::new(TestBlockstore::default(), limits, |_: &Cid| None);
+    put_raw(&mut store, b"small");
+    put_raw(&mut store, &[0u8; 100]);
+
+    assert_eq!(
+        BitswapStore::get(&mut store, &small_cid).unwrap(),
+        Some(b"small".to_vec())
+    );
+    assert_eq!(
+        BitswapStore::get(&mut store, &large_cid).unwrap(),
+        None,
+        "blocks over the byte limit are not served"
+    );
+}
@@ -126,6 +126,14 @@ impl ClusterBuilder {
         });
     }
 
+    /// Register the metrics on a local registry. They are shared by all services in the process,
+    /// so registering them through a single service is enough to observe the whole cluster.
+    fn register_metrics(&mut self, registry: &prometheus::Registry) {
+        self.services[0]
+            .register_metrics(registry)
+            .expect("failed to register metrics");
+    }
+
     /// Start running all services
     fn run(self) -> Cluster {
         for service in self.services {
@@ -293,6 +301,90 @@ async fn can_register_metrics() {
     service.register_metrics(&registry).unwrap();
 }
 
+/// Resolve from a subnet nobody provides and check that it shows up in the metrics.
+#[tokio::test]
+async fn resolve_without_peers_metrics() {
+    init_log();
+
+    let registry = prometheus::Registry::new();
+    let mut builder = ClusterBuilder::new(1);
+    builder.add_node(None);
+    builder.register_metrics(&registry);
+    let cluster = builder.run();
+
+    let started = counter_value(&registry, "content_resolve_started");
+    let no_peers = counter_value(&registry, "content_resolve_no_peers");
+
+    let cid = Cid::new_v1(IPLD_RAW, Code::Sha2_256.digest(b"nobody has this"));
+    let res = cluster.agents[0]
+        .client
+        .resolve(cid, make_subnet_id(1001))
+        .await
+        .expect("failed to send request");
+
+    assert!(res.is_err(), "there should be no peers to resolve from");
+    // Other tests in the same process can update the counters as well.
+    assert!(counter_value(&registry, "content_resolve_started") > started);
+    assert!(counter_value(&registry, "content_resolve_no_peers") > no_peers);
+}
+
+/// Publish a vote and check that both sides count it.
+#[tokio::test]
+async fn publish_receive_vote_metrics() {
+    init_log();
+
+    let registry = prometheus::Registry::new();
+    let mut builder = ClusterBuilder::new(2);
+    builder.add_node(None);
+    builder.add_node(Some(0));
+    builder.register_metrics(&registry);
+    let mut cluster = builder.run();
+    cluster.await_connect().await;
+
+    let subnet_id = make_subnet_id(1001);
+
+    for i in 0..cluster.size() {
+        cluster.agents[i]
+            .client
+            .add_provided_subnet(subnet_id.clone())
+            .expect("failed to add provided subnet");
+    }
+
+    // TODO: Wait on some condition instead of sleep.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    let published = counter_value(&registry, "membership_votes_published");
+    let received = counter_value(&registry, "membership_votes_received");
+
+    let validator_key = Keypair::generate_secp256k1();
+    let cid = Cid::new_v1(IPLD_RAW, Code::Sha2_256.digest(b"bar"));
+    let vote =
+        VoteRecord::signed(&validator_key, subnet_id, TestVote(cid)).expect("failed to sign vote");
+
+    cluster.agents[0]
+        .client
+        .publish_vote(vote)
+        .expect("failed to send vote");
+
+    timeout(Duration::from_secs(2), cluster.agents[1].events.recv())
+        .await
+        .expect("timeout receiving vote")
+        .expect("error receiving vote");
+
+    assert!(counter_value(&registry, "membership_votes_published") > published);
+    assert!(counter_value(&registry, "membership_votes_received") > received);
+}
+
+/// Read the value of a counter from the registry.
+fn counter_value(registry: &prometheus::Registry, name: &str) -> f64 {
+    registry
+        .gather()
+        .iter()
+        .find(|mf| mf.get_name() == name)
+        .map(|mf| mf.get_metric()[0].get_counter().get_value())
+        .unwrap_or_default()
+}
+
 async fn make_cluster_with_bootstrap(cluster_size: u32, bootstrap_idx: usize) -> Cluster {
     // TODO: Get the seed from QuickCheck
     let mut builder = ClusterBuilder::new(cluster_size);
@@ -322,6 +414,7 @@ fn make_config(rng: &mut StdRng, cluster_size: u32, bootstrap_addr: Option<Multi
             expected_peer_count: cluster_size,
             max_incoming: cluster_size,
             max_peers_per_query: cluster_size,
+            max_concurrent_requests: cluster_size,
             event_buffer_capacity: cluster_size,
         },
         network: NetworkConfig {